@@ -0,0 +1,170 @@
+/*
+ * hgitmap interactive heatmap widget.
+ *
+ *   <script src="https://hgitmap.example.com/embed.js" data-user="alice"></script>
+ *
+ * Attributes:
+ *   data-user      username to show (required)
+ *   data-theme     slug of the theme to start with; defaults to the user's default theme
+ *   data-token     signed embed token, for a private profile
+ *   data-switcher  "false" hides the theme switcher
+ *
+ * The widget is drawn where the script tag is. It reads
+ * /embed/{user}/data.json from the server the script was loaded from.
+ */
+(function () {
+  "use strict";
+
+  var script = document.currentScript;
+  if (!script) {
+    return;
+  }
+
+  var SVG_NS = "http://www.w3.org/2000/svg";
+  var MONTHS = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+  var DAY_LABEL_WIDTH = 28;
+  var MONTH_LABEL_HEIGHT = 16;
+
+  var user = script.getAttribute("data-user");
+  var token = script.getAttribute("data-token");
+  var showSwitcher = script.getAttribute("data-switcher") !== "false";
+  var base = script.src.split(/[?#]/)[0].replace(/\/embed\.js$/, "");
+
+  var root = document.createElement("div");
+  root.className = "hgitmap-widget";
+  root.style.cssText = "position:relative;display:inline-block;max-width:100%;overflow-x:auto;";
+  script.parentNode.insertBefore(root, script.nextSibling);
+
+  if (!user) {
+    root.textContent = "hgitmap: data-user is missing";
+    return;
+  }
+
+  var url = base + "/embed/" + encodeURIComponent(user) + "/data.json";
+  if (token) {
+    url += "?token=" + encodeURIComponent(token);
+  }
+
+  fetch(url)
+    .then(function (response) {
+      if (!response.ok) {
+        throw new Error(response.status === 404 ? "user not found" : "HTTP " + response.status);
+      }
+      return response.json();
+    })
+    .then(function (data) {
+      if (!data.themes.length) {
+        throw new Error("no themes");
+      }
+      var wanted = script.getAttribute("data-theme");
+      var theme = data.themes.filter(function (t) { return t.slug === wanted; })[0] || data.themes[0];
+      render(data, theme);
+    })
+    .catch(function (error) {
+      root.textContent = "hgitmap: " + error.message;
+    });
+
+  function render(data, theme) {
+    root.innerHTML = "";
+    root.style.background = theme.background_color;
+    root.style.color = theme.text_color;
+    root.style.fontFamily = theme.font_family;
+    root.style.fontSize = "12px";
+    root.style.padding = "8px";
+    root.style.borderRadius = "6px";
+
+    var header = document.createElement("div");
+    header.style.cssText = "display:flex;justify-content:space-between;align-items:center;gap:12px;margin-bottom:6px;";
+    var total = document.createElement("span");
+    total.textContent = data.total_count + " contributions by " + data.username;
+    header.appendChild(total);
+
+    if (showSwitcher && data.themes.length > 1) {
+      var select = document.createElement("select");
+      select.setAttribute("aria-label", "Theme");
+      data.themes.forEach(function (t) {
+        var option = document.createElement("option");
+        option.value = t.slug;
+        option.textContent = t.name;
+        option.selected = t.slug === theme.slug;
+        select.appendChild(option);
+      });
+      select.addEventListener("change", function () {
+        render(data, data.themes.filter(function (t) { return t.slug === select.value; })[0]);
+      });
+      header.appendChild(select);
+    }
+    root.appendChild(header);
+
+    var step = theme.cell_size + theme.cell_gap;
+    var weeks = Math.ceil(data.days.length / 7);
+    var svg = document.createElementNS(SVG_NS, "svg");
+    svg.setAttribute("width", DAY_LABEL_WIDTH + weeks * step);
+    svg.setAttribute("height", MONTH_LABEL_HEIGHT + 7 * step);
+    svg.setAttribute("role", "img");
+    svg.setAttribute("aria-label", total.textContent);
+
+    [[1, "Mon"], [3, "Wed"], [5, "Fri"]].forEach(function (label) {
+      svg.appendChild(text(label[1], 0, MONTH_LABEL_HEIGHT + label[0] * step + theme.cell_size - 1, theme));
+    });
+
+    var lastMonth = -1;
+    data.days.forEach(function (day, index) {
+      var week = Math.floor(index / 7);
+      var date = new Date(day.date + "T00:00:00Z");
+      var month = date.getUTCMonth();
+      if (index % 7 === 0 && month !== lastMonth) {
+        // Skip a label squeezed in by a partial first week
+        if (lastMonth !== -1 || date.getUTCDate() <= 7) {
+          svg.appendChild(text(MONTHS[month], DAY_LABEL_WIDTH + week * step, MONTH_LABEL_HEIGHT - 4, theme));
+        }
+        lastMonth = month;
+      }
+
+      var cell = document.createElementNS(SVG_NS, "rect");
+      cell.setAttribute("x", DAY_LABEL_WIDTH + week * step);
+      cell.setAttribute("y", MONTH_LABEL_HEIGHT + (index % 7) * step);
+      cell.setAttribute("width", theme.cell_size);
+      cell.setAttribute("height", theme.cell_size);
+      cell.setAttribute("rx", theme.cell_border_radius);
+      cell.setAttribute("fill", theme.colors[Math.min(day.level, theme.colors.length - 1)]);
+      cell.addEventListener("mouseenter", function () { showTooltip(cell, day); });
+      cell.addEventListener("mouseleave", hideTooltip);
+      svg.appendChild(cell);
+    });
+    root.appendChild(svg);
+
+    var tooltip = document.createElement("div");
+    tooltip.style.cssText = "position:absolute;display:none;pointer-events:none;white-space:nowrap;" +
+      "padding:4px 8px;border-radius:4px;background:rgba(0,0,0,0.85);color:#fff;font-size:12px;";
+    root.appendChild(tooltip);
+
+    function showTooltip(cell, day) {
+      var label = day.count === 1 ? "1 contribution" : day.count + " contributions";
+      var date = new Date(day.date + "T00:00:00Z").toLocaleDateString(undefined, {
+        timeZone: "UTC", year: "numeric", month: "short", day: "numeric"
+      });
+      tooltip.textContent = label + " on " + date;
+      tooltip.style.display = "block";
+
+      var cellBox = cell.getBoundingClientRect();
+      var rootBox = root.getBoundingClientRect();
+      tooltip.style.left = (cellBox.left - rootBox.left + root.scrollLeft + cellBox.width / 2 - tooltip.offsetWidth / 2) + "px";
+      tooltip.style.top = (cellBox.top - rootBox.top - tooltip.offsetHeight - 4) + "px";
+    }
+
+    function hideTooltip() {
+      tooltip.style.display = "none";
+    }
+  }
+
+  function text(content, x, y, theme) {
+    var node = document.createElementNS(SVG_NS, "text");
+    node.setAttribute("x", x);
+    node.setAttribute("y", y);
+    node.setAttribute("fill", theme.text_color);
+    node.setAttribute("font-size", "10");
+    node.textContent = content;
+    return node;
+  }
+})();
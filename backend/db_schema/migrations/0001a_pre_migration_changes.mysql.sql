@@ -0,0 +1,106 @@
+-- See 0001a_pre_migration_changes.sql. MySQL support started after these
+-- changes, so every MySQL database has them from schema.mysql.sql and this
+-- is a no-op. The new columns stay in schema.mysql.sql, since MySQL has no
+-- ADD COLUMN IF NOT EXISTS.
+
+-- Login sessions, each holding one rotating refresh token
+CREATE TABLE IF NOT EXISTS sessions (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64), -- Rotated-out token; presenting it again revokes the session
+    user_agent VARCHAR(255),
+    ip_address VARCHAR(64),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    last_used_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    INDEX idx_sessions_user (user_id),
+    INDEX idx_sessions_previous_token (previous_token_hash),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Previous usernames, redirected to the user's current profile and embed
+-- URLs for a grace period; nobody else can register them until it ends
+CREATE TABLE IF NOT EXISTS username_redirects (
+    old_username VARCHAR(255) PRIMARY KEY, -- Lowercase
+    user_id BINARY(16) NOT NULL,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Pending email address changes, applied once the link sent to the new address is opened
+CREATE TABLE IF NOT EXISTS email_changes (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) UNIQUE NOT NULL, -- One pending change per user
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Invitations that let people register while open registration is disabled
+CREATE TABLE IF NOT EXISTS invitations (
+    id BINARY(16) PRIMARY KEY,
+    code_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the invitation code
+    email VARCHAR(255), -- Only this address may use the code; NULL for a shareable code
+    max_uses INTEGER, -- NULL for unlimited
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at DATETIME(6),
+    created_by BINARY(16),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Banners shown to every user, e.g. for maintenance windows
+CREATE TABLE IF NOT EXISTS announcements (
+    id BINARY(16) PRIMARY KEY,
+    message TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL DEFAULT 'info', -- info, warning or critical
+    starts_at DATETIME(6), -- NULL shows it right away
+    ends_at DATETIME(6), -- NULL shows it until deleted
+    dismissible BOOLEAN NOT NULL DEFAULT true,
+    created_by BINARY(16),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    INDEX idx_announcements_ends_at (ends_at),
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Instance settings changed by admins at runtime; they override the
+-- environment defaults
+CREATE TABLE IF NOT EXISTS instance_settings (
+    `key` VARCHAR(100) PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_by BINARY(16),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (updated_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- GitHub/GitLab identities a user can sign in with
+CREATE TABLE IF NOT EXISTS user_identities (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    provider VARCHAR(50) NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '', -- Empty for github.com
+    provider_user_id VARCHAR(255) NOT NULL, -- Stable numeric ID, survives renames
+    provider_username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    last_login_at DATETIME(6),
+    UNIQUE(provider, instance_url(255), provider_user_id),
+    INDEX idx_user_identities_user (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Admin overrides of how many jobs per user the processors pick up at once;
+-- users without a row get JOB_CONCURRENCY_PER_USER
+CREATE TABLE IF NOT EXISTS user_job_limits (
+    user_id BINARY(16) PRIMARY KEY,
+    max_concurrent_jobs INTEGER NOT NULL CHECK (max_concurrent_jobs >= 1),
+    updated_by BINARY(16),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id) ON DELETE SET NULL
+);
@@ -0,0 +1,126 @@
+-- Schema changes made before migrations existed, when new installs took
+-- schema.sql as it stood. Databases set up from an older copy of it are
+-- adopted as 0001_initial_schema and caught up here; on newer ones every
+-- statement is a no-op.
+
+-- Users who only sign in with GitHub/GitLab or single sign-on have no password
+ALTER TABLE users
+    ALTER COLUMN password_hash DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS oidc_subject VARCHAR(255) UNIQUE, -- "sub" claim from the OIDC provider, for single sign-on users
+    ADD COLUMN IF NOT EXISTS username_changed_at TIMESTAMP WITH TIME ZONE; -- Last rename, for the rename cooldown
+
+-- The list columns start out as arrays; 0002_json_lists moves them to JSON
+ALTER TABLE user_settings
+    ADD COLUMN IF NOT EXISTS profile_visibility VARCHAR(20) NOT NULL DEFAULT 'public', -- public, unlisted or private
+    ADD COLUMN IF NOT EXISTS excluded_repositories TEXT[] NOT NULL DEFAULT '{}', -- Patterns like owner/repo or owner/*, hidden from the public profile
+    ADD COLUMN IF NOT EXISTS embed_allowed_domains TEXT[] NOT NULL DEFAULT '{}', -- Sites allowed to embed the heatmap images; empty allows all
+    ADD COLUMN IF NOT EXISTS activity_retention_days INTEGER; -- Prune older activities; NULL follows the instance policy
+
+ALTER TABLE api_tokens
+    ADD COLUMN IF NOT EXISTS scopes TEXT[] NOT NULL DEFAULT '{}'; -- e.g. read:contributions, write:themes, admin
+
+ALTER TABLE oauth_states
+    ALTER COLUMN user_id DROP NOT NULL, -- NULL while signing in
+    ALTER COLUMN platform DROP NOT NULL, -- NULL for OIDC sign-in and login codes
+    ADD COLUMN IF NOT EXISTS purpose VARCHAR(20) NOT NULL DEFAULT 'connect', -- connect, login, link, oidc or login_code
+    ADD COLUMN IF NOT EXISTS nonce VARCHAR(255); -- OIDC nonce the ID token must echo
+
+-- Login sessions, each holding one rotating refresh token
+CREATE TABLE IF NOT EXISTS sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64), -- Rotated-out token; presenting it again revokes the session
+    user_agent VARCHAR(255),
+    ip_address VARCHAR(64),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id);
+CREATE INDEX IF NOT EXISTS idx_sessions_previous_token ON sessions(previous_token_hash);
+
+-- Previous usernames, redirected to the user's current profile and embed
+-- URLs for a grace period; nobody else can register them until it ends
+CREATE TABLE IF NOT EXISTS username_redirects (
+    old_username VARCHAR(255) PRIMARY KEY, -- Lowercase
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Pending email address changes, applied once the link sent to the new address is opened
+CREATE TABLE IF NOT EXISTS email_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- One pending change per user
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Invitations that let people register while open registration is disabled
+CREATE TABLE IF NOT EXISTS invitations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    code_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the invitation code
+    email VARCHAR(255), -- Only this address may use the code; NULL for a shareable code
+    max_uses INTEGER, -- NULL for unlimited
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Banners shown to every user, e.g. for maintenance windows
+CREATE TABLE IF NOT EXISTS announcements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL DEFAULT 'info', -- info, warning or critical
+    starts_at TIMESTAMP WITH TIME ZONE, -- NULL shows it right away
+    ends_at TIMESTAMP WITH TIME ZONE, -- NULL shows it until deleted
+    dismissible BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcements_ends_at ON announcements(ends_at);
+
+DROP TRIGGER IF EXISTS update_announcements_updated_at ON announcements;
+CREATE TRIGGER update_announcements_updated_at BEFORE UPDATE ON announcements
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Instance settings changed by admins at runtime; they override the
+-- environment defaults
+CREATE TABLE IF NOT EXISTS instance_settings (
+    key VARCHAR(100) PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- GitHub/GitLab identities a user can sign in with
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider git_platform NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '', -- Empty for github.com
+    provider_user_id VARCHAR(255) NOT NULL, -- Stable numeric ID, survives renames
+    provider_username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_login_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE(provider, instance_url, provider_user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user ON user_identities(user_id);
+
+-- Admin overrides of how many jobs per user the processors pick up at once;
+-- users without a row get JOB_CONCURRENCY_PER_USER
+CREATE TABLE IF NOT EXISTS user_job_limits (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    max_concurrent_jobs INTEGER NOT NULL CHECK (max_concurrent_jobs >= 1),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
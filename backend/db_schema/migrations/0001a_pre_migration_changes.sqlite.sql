@@ -0,0 +1,106 @@
+-- See 0001a_pre_migration_changes.sql. SQLite support started after these
+-- changes, so every SQLite database has them from schema.sqlite.sql and this
+-- is a no-op. The new columns stay in schema.sqlite.sql, since SQLite has no
+-- ADD COLUMN IF NOT EXISTS.
+
+-- Login sessions, each holding one rotating refresh token
+CREATE TABLE IF NOT EXISTS sessions (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64), -- Rotated-out token; presenting it again revokes the session
+    user_agent VARCHAR(255),
+    ip_address VARCHAR(64),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    last_used_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id);
+CREATE INDEX IF NOT EXISTS idx_sessions_previous_token ON sessions(previous_token_hash);
+
+-- Previous usernames, redirected to the user's current profile and embed
+-- URLs for a grace period; nobody else can register them until it ends
+CREATE TABLE IF NOT EXISTS username_redirects (
+    old_username VARCHAR(255) PRIMARY KEY, -- Lowercase
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL
+);
+
+-- Pending email address changes, applied once the link sent to the new address is opened
+CREATE TABLE IF NOT EXISTS email_changes (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- One pending change per user
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL
+);
+
+-- Invitations that let people register while open registration is disabled
+CREATE TABLE IF NOT EXISTS invitations (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    code_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the invitation code
+    email VARCHAR(255), -- Only this address may use the code; NULL for a shareable code
+    max_uses INTEGER, -- NULL for unlimited
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TEXT,
+    created_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Banners shown to every user, e.g. for maintenance windows
+CREATE TABLE IF NOT EXISTS announcements (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    message TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL DEFAULT 'info', -- info, warning or critical
+    starts_at TEXT, -- NULL shows it right away
+    ends_at TEXT, -- NULL shows it until deleted
+    dismissible BOOLEAN NOT NULL DEFAULT true,
+    created_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcements_ends_at ON announcements(ends_at);
+
+CREATE TRIGGER IF NOT EXISTS update_announcements_updated_at AFTER UPDATE ON announcements
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE announcements SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+-- Instance settings changed by admins at runtime; they override the
+-- environment defaults
+CREATE TABLE IF NOT EXISTS instance_settings (
+    key VARCHAR(100) PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- GitHub/GitLab identities a user can sign in with
+CREATE TABLE IF NOT EXISTS user_identities (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '', -- Empty for github.com
+    provider_user_id VARCHAR(255) NOT NULL, -- Stable numeric ID, survives renames
+    provider_username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    last_login_at TEXT,
+    UNIQUE(provider, instance_url, provider_user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user ON user_identities(user_id);
+
+-- Admin overrides of how many jobs per user the processors pick up at once;
+-- users without a row get JOB_CONCURRENCY_PER_USER
+CREATE TABLE IF NOT EXISTS user_job_limits (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    max_concurrent_jobs INTEGER NOT NULL CHECK (max_concurrent_jobs >= 1),
+    updated_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
@@ -0,0 +1,177 @@
+-- List columns move from Postgres arrays to JSON arrays, which every
+-- supported database can store
+ALTER TABLE user_settings
+    ALTER COLUMN excluded_repositories DROP DEFAULT,
+    ALTER COLUMN excluded_repositories TYPE JSONB USING to_jsonb(excluded_repositories),
+    ALTER COLUMN excluded_repositories SET DEFAULT '[]',
+    ALTER COLUMN embed_allowed_domains DROP DEFAULT,
+    ALTER COLUMN embed_allowed_domains TYPE JSONB USING to_jsonb(embed_allowed_domains),
+    ALTER COLUMN embed_allowed_domains SET DEFAULT '[]';
+
+ALTER TABLE api_tokens
+    ALTER COLUMN scopes DROP DEFAULT,
+    ALTER COLUMN scopes TYPE JSONB USING to_jsonb(scopes),
+    ALTER COLUMN scopes SET DEFAULT '[]';
+
+ALTER TABLE heatmap_themes
+    ALTER COLUMN output_formats DROP DEFAULT,
+    ALTER COLUMN output_formats TYPE JSONB USING to_jsonb(output_formats),
+    ALTER COLUMN output_formats SET DEFAULT '["png"]';
+
+-- Function to automatically create default themes for new users
+CREATE OR REPLACE FUNCTION create_default_heatmap_themes()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Create default light theme (matches current frontend UI exactly)
+    INSERT INTO heatmap_themes (
+        user_id,
+        name,
+        slug,
+        description,
+        is_default,
+        theme_mode,
+        color_scheme,
+        custom_colors,
+        background_color,
+        border_color,
+        text_color,
+        empty_cell_color,
+        cell_size,
+        cell_gap,
+        cell_border_radius,
+        cell_border_width,
+        cell_border_color,
+        padding_top,
+        padding_right,
+        padding_bottom,
+        padding_left,
+        day_label_width,
+        month_label_height,
+        title_height,
+        legend_height,
+        show_month_labels,
+        show_day_labels,
+        show_legend,
+        show_total_count,
+        show_username,
+        show_watermark,
+        font_family,
+        font_size,
+        output_formats
+    ) VALUES (
+        NEW.id,
+        'Default Light',
+        'default-light',
+        'Classic GitHub contribution graph style (light mode)',
+        true,
+        'light',
+        'custom',
+        '["#eff2f5", "#aceebb", "#4ac26b", "#2da44e", "#116329"]'::jsonb,
+        '#ffffff',
+        '#e1e4e8',
+        '#586069',
+        '#eff2f5',
+        10,
+        3,
+        2,
+        1,
+        '#e1e4e8',
+        20,
+        20,
+        10,
+        28,
+        28,
+        15,
+        30,
+        8,
+        true,
+        true,
+        true,
+        true,
+        false,
+        false,
+        'sans-serif',
+        10,
+        '["png", "svg"]'::jsonb
+    );
+
+    -- Create default dark theme (matches current frontend dark mode)
+    INSERT INTO heatmap_themes (
+        user_id,
+        name,
+        slug,
+        description,
+        is_default,
+        theme_mode,
+        color_scheme,
+        custom_colors,
+        background_color,
+        border_color,
+        text_color,
+        empty_cell_color,
+        cell_size,
+        cell_gap,
+        cell_border_radius,
+        cell_border_width,
+        cell_border_color,
+        padding_top,
+        padding_right,
+        padding_bottom,
+        padding_left,
+        day_label_width,
+        month_label_height,
+        title_height,
+        legend_height,
+        show_month_labels,
+        show_day_labels,
+        show_legend,
+        show_total_count,
+        show_username,
+        show_watermark,
+        font_family,
+        font_size,
+        output_formats
+    ) VALUES (
+        NEW.id,
+        'Default Dark',
+        'default-dark',
+        'GitHub contribution graph for dark mode',
+        false,
+        'dark',
+        'custom',
+        '["#151b23", "#033a16", "#196c2e", "#2ea043", "#56d364"]'::jsonb,
+        '#0d1117',
+        '#30363d',
+        '#8b949e',
+        '#151b23',
+        10,
+        3,
+        2,
+        1,
+        '#30363d',
+        20,
+        20,
+        10,
+        28,
+        28,
+        15,
+        30,
+        8,
+        true,
+        true,
+        true,
+        true,
+        false,
+        false,
+        'sans-serif',
+        10,
+        '["png", "svg"]'::jsonb
+    );
+
+    -- Create generation settings with defaults
+    INSERT INTO heatmap_generation_settings (user_id)
+    VALUES (NEW.id);
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,6 @@
+-- See 0003_activity_type_index.sql. MySQL has no CREATE INDEX IF NOT EXISTS,
+-- so schema.mysql.sql keeps the old index and this runs on every database.
+-- The new index is created first; the account foreign key needs one of them.
+CREATE INDEX idx_activities_account_date_type
+    ON activities(git_platform_account_id, activity_date DESC, activity_type);
+DROP INDEX idx_activities_account_date ON activities;
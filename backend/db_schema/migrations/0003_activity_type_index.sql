@@ -0,0 +1,8 @@
+-- Activity filters by type (timeline, GraphQL, organization lookups) can be
+-- answered from the index once the type is part of it. It starts with the
+-- columns of idx_activities_account_date, which it replaces.
+-- Contributions already have idx_contributions_account_date, and the
+-- UNIQUE (user_id, theme_id, format) constraint indexes generated_heatmaps.
+CREATE INDEX IF NOT EXISTS idx_activities_account_date_type
+    ON activities(git_platform_account_id, activity_date DESC, activity_type);
+DROP INDEX IF EXISTS idx_activities_account_date;
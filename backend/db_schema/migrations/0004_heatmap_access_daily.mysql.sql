@@ -0,0 +1,14 @@
+-- Daily views of generated heatmaps and embeds, per theme, referring site
+-- and kind of client
+CREATE TABLE IF NOT EXISTS heatmap_access_daily (
+    user_id BINARY(16) NOT NULL,
+    theme_id BINARY(16) NOT NULL,
+    access_date DATE NOT NULL,
+    referrer_host VARCHAR(255) NOT NULL DEFAULT '', -- empty without a Referer header
+    client VARCHAR(20) NOT NULL, -- browser, github, bot or other
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (theme_id, access_date, referrer_host, client),
+    INDEX idx_heatmap_access_daily_user_date (user_id, access_date),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (theme_id) REFERENCES heatmap_themes(id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Daily views of generated heatmaps and embeds, per theme, referring site
+-- and kind of client
+CREATE TABLE IF NOT EXISTS heatmap_access_daily (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id UUID NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    access_date DATE NOT NULL,
+    referrer_host VARCHAR(255) NOT NULL DEFAULT '', -- empty without a Referer header
+    client VARCHAR(20) NOT NULL, -- browser, github, bot or other
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (theme_id, access_date, referrer_host, client)
+);
+
+CREATE INDEX IF NOT EXISTS idx_heatmap_access_daily_user_date ON heatmap_access_daily(user_id, access_date);
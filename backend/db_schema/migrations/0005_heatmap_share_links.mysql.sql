@@ -0,0 +1,15 @@
+-- Signed links to one theme's generated heatmap in one format, which work
+-- even when the profile is private
+CREATE TABLE IF NOT EXISTS heatmap_share_links (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    theme_id BINARY(16) NOT NULL,
+    format VARCHAR(50) NOT NULL,
+    expires_at DATETIME(6), -- never expires when NULL
+    max_views INTEGER, -- unlimited when NULL
+    view_count INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_heatmap_share_links_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (theme_id) REFERENCES heatmap_themes(id) ON DELETE CASCADE
+);
@@ -0,0 +1,14 @@
+-- Signed links to one theme's generated heatmap in one format, which work
+-- even when the profile is private
+CREATE TABLE IF NOT EXISTS heatmap_share_links (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id UUID NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    format heatmap_format NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE, -- never expires when NULL
+    max_views INTEGER, -- unlimited when NULL
+    view_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_heatmap_share_links_user_id ON heatmap_share_links(user_id);
@@ -0,0 +1,14 @@
+-- Signed links to one theme's generated heatmap in one format, which work
+-- even when the profile is private
+CREATE TABLE IF NOT EXISTS heatmap_share_links (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id BLOB NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    format TEXT NOT NULL,
+    expires_at TEXT, -- never expires when NULL
+    max_views INTEGER, -- unlimited when NULL
+    view_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_heatmap_share_links_user_id ON heatmap_share_links(user_id);
@@ -0,0 +1,8 @@
+-- Users who opted in to a periodic summary email
+CREATE TABLE IF NOT EXISTS email_digests (
+    user_id BINARY(16) PRIMARY KEY,
+    frequency VARCHAR(10) NOT NULL, -- weekly or monthly
+    last_sent_at DATETIME(6) NOT NULL, -- the next digest covers the time since
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
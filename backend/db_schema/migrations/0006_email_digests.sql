@@ -0,0 +1,7 @@
+-- Users who opted in to a periodic summary email
+CREATE TABLE IF NOT EXISTS email_digests (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    frequency VARCHAR(10) NOT NULL, -- weekly or monthly
+    last_sent_at TIMESTAMP WITH TIME ZONE NOT NULL, -- the next digest covers the time since
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,7 @@
+-- Users who opted in to a periodic summary email
+CREATE TABLE IF NOT EXISTS email_digests (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    frequency TEXT NOT NULL, -- weekly or monthly
+    last_sent_at TEXT NOT NULL, -- the next digest covers the time since
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
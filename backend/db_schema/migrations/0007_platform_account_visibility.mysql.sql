@@ -0,0 +1,9 @@
+-- What public profile endpoints show of a platform account; accounts
+-- without a row are listed, without their sync time
+CREATE TABLE IF NOT EXISTS platform_account_visibility (
+    account_id BINARY(16) PRIMARY KEY,
+    is_public BOOLEAN NOT NULL DEFAULT true, -- listed on the public profile
+    show_last_synced_at BOOLEAN NOT NULL DEFAULT false,
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- What public profile endpoints show of a platform account; accounts
+-- without a row are listed, without their sync time
+CREATE TABLE IF NOT EXISTS platform_account_visibility (
+    account_id UUID PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    is_public BOOLEAN NOT NULL DEFAULT true, -- listed on the public profile
+    show_last_synced_at BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+-- Names users gave their platform accounts, e.g. to tell two accounts on
+-- the same platform apart
+CREATE TABLE IF NOT EXISTS platform_account_labels (
+    account_id BINARY(16) PRIMARY KEY,
+    label VARCHAR(100) NOT NULL,
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,7 @@
+-- Names users gave their platform accounts, e.g. to tell two accounts on
+-- the same platform apart
+CREATE TABLE IF NOT EXISTS platform_account_labels (
+    account_id BLOB PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    label VARCHAR(100) NOT NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
@@ -0,0 +1,11 @@
+-- Organizations a platform account belongs to, replaced on every activity
+-- sync
+CREATE TABLE IF NOT EXISTS organization_memberships (
+    account_id BINARY(16) NOT NULL,
+    login VARCHAR(255) NOT NULL,
+    avatar_url TEXT,
+    joined_at DATE, -- NULL when the platform does not tell
+    synced_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (account_id, login),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
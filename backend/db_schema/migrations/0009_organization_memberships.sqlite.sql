@@ -0,0 +1,10 @@
+-- Organizations a platform account belongs to, replaced on every activity
+-- sync
+CREATE TABLE IF NOT EXISTS organization_memberships (
+    account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    login VARCHAR(255) NOT NULL,
+    avatar_url TEXT,
+    joined_at DATE, -- NULL when the platform does not tell
+    synced_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (account_id, login)
+);
@@ -0,0 +1,9 @@
+-- Scopes and redirect URI an admin set for an OAuth app; apps without a
+-- row use the defaults
+CREATE TABLE IF NOT EXISTS oauth_application_settings (
+    application_id BINARY(16) PRIMARY KEY,
+    scopes TEXT, -- NULL: the scopes hgitmap needs
+    redirect_uri TEXT, -- NULL: the callback route under BASE_URL
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (application_id) REFERENCES oauth_applications(id) ON DELETE CASCADE
+);
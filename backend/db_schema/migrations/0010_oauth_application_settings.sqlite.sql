@@ -0,0 +1,8 @@
+-- Scopes and redirect URI an admin set for an OAuth app; apps without a
+-- row use the defaults
+CREATE TABLE IF NOT EXISTS oauth_application_settings (
+    application_id BLOB PRIMARY KEY REFERENCES oauth_applications(id) ON DELETE CASCADE,
+    scopes TEXT, -- NULL: the scopes hgitmap needs
+    redirect_uri TEXT, -- NULL: the callback route under BASE_URL
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
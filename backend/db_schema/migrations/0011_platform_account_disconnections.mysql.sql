@@ -0,0 +1,8 @@
+-- Disconnected platform accounts kept for a while so they can be restored;
+-- the cleanup job deletes the account once purge_after has passed
+CREATE TABLE IF NOT EXISTS platform_account_disconnections (
+    platform_account_id BINARY(16) PRIMARY KEY,
+    disconnected_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    purge_after DATETIME(6) NOT NULL,
+    FOREIGN KEY (platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,7 @@
+-- Disconnected platform accounts kept for a while so they can be restored;
+-- the cleanup job deletes the account once purge_after has passed
+CREATE TABLE IF NOT EXISTS platform_account_disconnections (
+    platform_account_id UUID PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    disconnected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    purge_after TIMESTAMP WITH TIME ZONE NOT NULL
+);
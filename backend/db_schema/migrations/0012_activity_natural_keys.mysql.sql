@@ -0,0 +1,5 @@
+-- See 0012_activity_natural_keys.sql. MySQL has no ADD COLUMN IF NOT
+-- EXISTS, so schema.mysql.sql leaves the column to this migration.
+ALTER TABLE activities ADD COLUMN natural_key VARCHAR(64);
+CREATE UNIQUE INDEX unique_activity_per_account_key
+    ON activities(git_platform_account_id, natural_key);
@@ -0,0 +1,6 @@
+-- A key that identifies an activity on its platform, so syncing the same
+-- range again updates the stored activities instead of adding copies.
+-- Activities stored before this have no key until their range is synced.
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS natural_key VARCHAR(64);
+CREATE UNIQUE INDEX IF NOT EXISTS unique_activity_per_account_key
+    ON activities(git_platform_account_id, natural_key);
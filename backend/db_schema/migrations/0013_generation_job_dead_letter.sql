@@ -0,0 +1,4 @@
+-- Generation jobs that ran out of retries are dead-lettered instead of
+-- failed, which is left for cancelled jobs. SQLite and MySQL store the
+-- status as text, so only PostgreSQL needs this.
+ALTER TYPE generation_job_status ADD VALUE IF NOT EXISTS 'dead_letter';
@@ -0,0 +1,6 @@
+-- See 0014_generation_schedule.sql. MySQL has no ADD COLUMN IF NOT
+-- EXISTS, so schema.mysql.sql leaves the columns to this migration.
+ALTER TABLE heatmap_generation_settings ADD COLUMN timezone VARCHAR(64);
+ALTER TABLE heatmap_generation_settings ADD COLUMN generation_window_start_minutes INTEGER;
+ALTER TABLE heatmap_generation_settings ADD COLUMN generation_window_end_minutes INTEGER;
+ALTER TABLE heatmap_generation_settings ADD COLUMN generation_cron VARCHAR(128);
@@ -0,0 +1,7 @@
+-- When scheduled syncs and generations may start, in the user's timezone:
+-- a daily window in minutes after midnight, or a cron expression that
+-- replaces the interval and the window. NULL keeps the plain interval.
+ALTER TABLE heatmap_generation_settings ADD COLUMN IF NOT EXISTS timezone VARCHAR(64);
+ALTER TABLE heatmap_generation_settings ADD COLUMN IF NOT EXISTS generation_window_start_minutes INTEGER;
+ALTER TABLE heatmap_generation_settings ADD COLUMN IF NOT EXISTS generation_window_end_minutes INTEGER;
+ALTER TABLE heatmap_generation_settings ADD COLUMN IF NOT EXISTS generation_cron VARCHAR(128);
@@ -0,0 +1,15 @@
+-- Platform API calls made for each account, per hour and kind of API, with
+-- the rate limit the platform last reported; the cleanup job removes old hours
+CREATE TABLE IF NOT EXISTS platform_api_usage_hourly (
+    platform_account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    period_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    api VARCHAR(10) NOT NULL, -- rest or graphql
+    requests INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0, -- no response or a 5xx
+    rate_limited INTEGER NOT NULL DEFAULT 0,
+    rate_limit_remaining INTEGER,
+    rate_limit_limit INTEGER,
+    rate_limit_reset_at TIMESTAMP WITH TIME ZONE,
+    last_called_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (platform_account_id, period_start, api)
+);
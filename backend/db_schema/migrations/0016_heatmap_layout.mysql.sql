@@ -0,0 +1,3 @@
+-- See 0016_heatmap_layout.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the column to this migration.
+ALTER TABLE heatmap_themes ADD COLUMN layout VARCHAR(32) NOT NULL DEFAULT 'grid';
@@ -0,0 +1,3 @@
+-- Which renderer draws a theme's heatmap: grid, skyline, stats_card or
+-- badge (see services/heatmap_renderers). Existing themes keep the grid.
+ALTER TABLE heatmap_themes ADD COLUMN IF NOT EXISTS layout VARCHAR(32) NOT NULL DEFAULT 'grid';
@@ -0,0 +1,4 @@
+-- See 0017_theme_custom_template.sql. MySQL has no ADD COLUMN IF NOT
+-- EXISTS, so schema.mysql.sql leaves the column to this migration.
+-- MEDIUMTEXT, as templates may take up the whole 64 KB TEXT holds.
+ALTER TABLE heatmap_themes ADD COLUMN custom_template MEDIUMTEXT;
@@ -0,0 +1,3 @@
+-- A theme's own SVG template (see utils/svg_template), drawn instead of its
+-- layout. NULL uses the layout.
+ALTER TABLE heatmap_themes ADD COLUMN IF NOT EXISTS custom_template TEXT;
@@ -0,0 +1,3 @@
+-- See 0017_theme_custom_template.sql. SQLite has no ADD COLUMN IF NOT
+-- EXISTS, so schema.sqlite.sql leaves the column to this migration.
+ALTER TABLE heatmap_themes ADD COLUMN custom_template TEXT;
@@ -0,0 +1,3 @@
+-- See 0018_theme_mini_days.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the column to this migration.
+ALTER TABLE heatmap_themes ADD COLUMN mini_days INT;
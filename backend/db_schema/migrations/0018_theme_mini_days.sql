@@ -0,0 +1,3 @@
+-- Days a theme's mini heatmap covers, 30 or 90. Themes with a value also
+-- get `{slug}-mini.{ext}` files next to their main ones; NULL for none.
+ALTER TABLE heatmap_themes ADD COLUMN IF NOT EXISTS mini_days INTEGER;
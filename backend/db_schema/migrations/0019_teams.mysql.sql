@@ -0,0 +1,23 @@
+-- Groups of users whose contributions are drawn as one heatmap, managed by
+-- their owner
+CREATE TABLE IF NOT EXISTS teams (
+    id BINARY(16) PRIMARY KEY,
+    owner_id BINARY(16) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_teams_owner_id (owner_id),
+    FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS team_members (
+    team_id BINARY(16) NOT NULL,
+    user_id BINARY(16) NOT NULL,
+    added_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (team_id, user_id),
+    INDEX idx_team_members_user_id (user_id),
+    FOREIGN KEY (team_id) REFERENCES teams(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
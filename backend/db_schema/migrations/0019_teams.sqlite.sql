@@ -0,0 +1,22 @@
+-- Groups of users whose contributions are drawn as one heatmap, managed by
+-- their owner
+CREATE TABLE IF NOT EXISTS teams (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    owner_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_teams_owner_id ON teams(owner_id);
+
+CREATE TABLE IF NOT EXISTS team_members (
+    team_id BLOB NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_team_members_user_id ON team_members(user_id);
@@ -0,0 +1,10 @@
+-- Users whose public activities appear in a user's feed
+CREATE TABLE IF NOT EXISTS follows (
+    follower_id BINARY(16) NOT NULL,
+    followee_id BINARY(16) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (follower_id, followee_id),
+    INDEX idx_follows_followee_id (followee_id),
+    FOREIGN KEY (follower_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (followee_id) REFERENCES users(id) ON DELETE CASCADE
+);
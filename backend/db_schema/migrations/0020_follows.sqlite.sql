@@ -0,0 +1,9 @@
+-- Users whose public activities appear in a user's feed
+CREATE TABLE IF NOT EXISTS follows (
+    follower_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    followee_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (follower_id, followee_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_follows_followee_id ON follows(followee_id);
@@ -0,0 +1,3 @@
+-- See 0021_user_directory.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the column to this migration.
+ALTER TABLE user_settings ADD COLUMN listed_in_directory BOOLEAN NOT NULL DEFAULT FALSE;
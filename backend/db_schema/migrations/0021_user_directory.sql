@@ -0,0 +1,3 @@
+-- Whether the user is listed in the public user directory (GET /users).
+-- Off until they opt in; only public profiles are listed either way.
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS listed_in_directory BOOLEAN NOT NULL DEFAULT FALSE;
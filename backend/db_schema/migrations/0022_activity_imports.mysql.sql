@@ -0,0 +1,22 @@
+-- See 0022_activity_imports.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the column to this migration.
+ALTER TABLE activities ADD COLUMN imported BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS activity_imports (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    platform_account_id BINARY(16) NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'pending',
+    events_read INTEGER NOT NULL DEFAULT 0,
+    events_matched INTEGER NOT NULL DEFAULT 0,
+    activities_total INTEGER NOT NULL DEFAULT 0,
+    activities_imported INTEGER NOT NULL DEFAULT 0,
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    started_at DATETIME(6),
+    completed_at DATETIME(6),
+    INDEX idx_activity_imports_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
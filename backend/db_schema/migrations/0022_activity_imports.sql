@@ -0,0 +1,23 @@
+-- Activities backfilled from an uploaded event file (GH Archive slice or a
+-- saved Events API response) rather than synced. A sync never prunes them,
+-- since the Events API cannot return anything older than its 300 events.
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS imported BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Progress of an import, polled while it runs in the background
+CREATE TABLE IF NOT EXISTS activity_imports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform_account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    status sync_job_status NOT NULL DEFAULT 'pending',
+    events_read INTEGER NOT NULL DEFAULT 0, -- Every event in the file
+    events_matched INTEGER NOT NULL DEFAULT 0, -- The account's events that map to activities
+    activities_total INTEGER NOT NULL DEFAULT 0,
+    activities_imported INTEGER NOT NULL DEFAULT 0, -- New activities stored
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0, -- Already stored, e.g. by a sync
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_imports_user_id ON activity_imports(user_id);
@@ -0,0 +1,21 @@
+-- See 0022_activity_imports.sql. SQLite has no ADD COLUMN IF NOT EXISTS, so
+-- schema.sqlite.sql leaves the column to this migration.
+ALTER TABLE activities ADD COLUMN imported BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS activity_imports (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform_account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    events_read INTEGER NOT NULL DEFAULT 0,
+    events_matched INTEGER NOT NULL DEFAULT 0,
+    activities_total INTEGER NOT NULL DEFAULT 0,
+    activities_imported INTEGER NOT NULL DEFAULT 0,
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    started_at TEXT,
+    completed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_imports_user_id ON activity_imports(user_id);
@@ -0,0 +1,4 @@
+-- See 0023_away_periods.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the columns to this migration.
+ALTER TABLE user_settings ADD COLUMN away_periods JSON NOT NULL DEFAULT ('[]');
+ALTER TABLE user_settings ADD COLUMN hatch_away_days BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- Date ranges the user was away, e.g. on vacation. Days without
+-- contributions in them do not break a streak, and heatmaps can hatch them.
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS away_periods JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS hatch_away_days BOOLEAN NOT NULL DEFAULT FALSE;
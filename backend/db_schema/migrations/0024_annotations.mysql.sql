@@ -0,0 +1,12 @@
+-- See 0024_annotations.sql
+CREATE TABLE IF NOT EXISTS annotations (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    date DATE NOT NULL,
+    text VARCHAR(200) NOT NULL,
+    emoji VARCHAR(32),
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_annotations_user_date (user_id, date),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
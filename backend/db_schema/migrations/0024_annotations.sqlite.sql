@@ -0,0 +1,12 @@
+-- See 0024_annotations.sql
+CREATE TABLE IF NOT EXISTS annotations (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    text VARCHAR(200) NOT NULL,
+    emoji VARCHAR(32),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_annotations_user_date ON annotations(user_id, date);
@@ -0,0 +1,16 @@
+-- See 0025_milestones.sql. Activity types are plain text here. MySQL has no
+-- ADD COLUMN IF NOT EXISTS, so schema.mysql.sql leaves the column to this
+-- migration.
+CREATE TABLE IF NOT EXISTS milestones (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    milestone_key VARCHAR(255) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    achieved_on DATE NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    UNIQUE KEY uq_milestones_user_kind_key (user_id, kind, milestone_key),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE user_settings ADD COLUMN milestone_webhook_url VARCHAR(2048);
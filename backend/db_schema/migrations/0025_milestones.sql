@@ -0,0 +1,16 @@
+-- Milestones a user reached, like their 1,000th contribution or a 100-day
+-- streak. Each is also recorded as a `milestone` activity on the timeline.
+ALTER TYPE activity_type ADD VALUE IF NOT EXISTS 'milestone';
+
+CREATE TABLE IF NOT EXISTS milestones (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    milestone_key VARCHAR(255) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    achieved_on DATE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, kind, milestone_key)
+);
+
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS milestone_webhook_url VARCHAR(2048);
@@ -0,0 +1,15 @@
+-- See 0025_milestones.sql. Activity types are plain text here. SQLite has no
+-- ADD COLUMN IF NOT EXISTS, so schema.sqlite.sql leaves the column to this
+-- migration.
+CREATE TABLE IF NOT EXISTS milestones (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    milestone_key VARCHAR(255) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    achieved_on DATE NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    UNIQUE (user_id, kind, milestone_key)
+);
+
+ALTER TABLE user_settings ADD COLUMN milestone_webhook_url VARCHAR(2048);
@@ -0,0 +1,12 @@
+-- See 0026_achievements.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the column to this migration.
+CREATE TABLE IF NOT EXISTS achievements (
+    user_id BINARY(16) NOT NULL,
+    achievement VARCHAR(32) NOT NULL,
+    earned_on DATE NOT NULL,
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (user_id, achievement),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE heatmap_themes ADD COLUMN show_achievements BOOLEAN NOT NULL DEFAULT FALSE;
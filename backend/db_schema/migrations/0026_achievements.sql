@@ -0,0 +1,12 @@
+-- Achievements a user has earned, like night owl or polyglot. Re-evaluated
+-- after every sync; an achievement no longer earned is removed.
+CREATE TABLE IF NOT EXISTS achievements (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement VARCHAR(32) NOT NULL,
+    earned_on DATE NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, achievement)
+);
+
+-- Whether a theme's stats card lists the achievements
+ALTER TABLE heatmap_themes ADD COLUMN IF NOT EXISTS show_achievements BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,11 @@
+-- See 0026_achievements.sql. SQLite has no ADD COLUMN IF NOT EXISTS, so
+-- schema.sqlite.sql leaves the column to this migration.
+CREATE TABLE IF NOT EXISTS achievements (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement VARCHAR(32) NOT NULL,
+    earned_on DATE NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (user_id, achievement)
+);
+
+ALTER TABLE heatmap_themes ADD COLUMN show_achievements BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- See 0027_repository_languages.sql
+CREATE TABLE IF NOT EXISTS repository_languages (
+    account_id BINARY(16) NOT NULL,
+    repository_name VARCHAR(255) NOT NULL,
+    language VARCHAR(100) NOT NULL,
+    bytes BIGINT NOT NULL,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (account_id, repository_name, language),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Bytes of code per language in the repositories an account contributed
+-- to, as the platform counts them. Refreshed by activity syncs.
+CREATE TABLE IF NOT EXISTS repository_languages (
+    account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL,
+    language VARCHAR(100) NOT NULL,
+    bytes BIGINT NOT NULL,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, repository_name, language)
+);
@@ -0,0 +1,10 @@
+-- See 0027_repository_languages.sql
+CREATE TABLE IF NOT EXISTS repository_languages (
+    account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL,
+    language VARCHAR(100) NOT NULL,
+    bytes BIGINT NOT NULL,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (account_id, repository_name, language)
+);
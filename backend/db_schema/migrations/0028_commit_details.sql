@@ -0,0 +1,18 @@
+-- Individual commits of accounts with detailed sync on: when they were made
+-- and, where the platform tells, the lines they changed. Off by default,
+-- since a busy account stores thousands of rows a year.
+ALTER TABLE git_platform_accounts ADD COLUMN IF NOT EXISTS detailed_sync BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE git_platform_accounts ADD COLUMN IF NOT EXISTS commit_retention_days INTEGER;
+
+CREATE TABLE IF NOT EXISTS commit_details (
+    account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL,
+    sha VARCHAR(64) NOT NULL,
+    committed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    additions INTEGER,
+    deletions INTEGER,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (account_id, repository_name, sha)
+);
+
+CREATE INDEX IF NOT EXISTS idx_commit_details_account_time ON commit_details(account_id, committed_at);
@@ -0,0 +1,17 @@
+-- See 0028_commit_details.sql. SQLite has no ADD COLUMN IF NOT EXISTS, so
+-- schema.sqlite.sql leaves the columns to this migration.
+ALTER TABLE git_platform_accounts ADD COLUMN detailed_sync BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE git_platform_accounts ADD COLUMN commit_retention_days INTEGER;
+
+CREATE TABLE IF NOT EXISTS commit_details (
+    account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL,
+    sha VARCHAR(64) NOT NULL,
+    committed_at TEXT NOT NULL,
+    additions INTEGER,
+    deletions INTEGER,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (account_id, repository_name, sha)
+);
+
+CREATE INDEX IF NOT EXISTS idx_commit_details_account_time ON commit_details(account_id, committed_at);
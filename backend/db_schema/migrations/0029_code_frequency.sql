@@ -0,0 +1,3 @@
+-- Whether a grid theme draws lines added and removed per week under the
+-- heatmap, from the commits of accounts with detailed sync
+ALTER TABLE heatmap_themes ADD COLUMN IF NOT EXISTS show_code_frequency BOOLEAN NOT NULL DEFAULT FALSE;
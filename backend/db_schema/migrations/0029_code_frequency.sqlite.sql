@@ -0,0 +1,3 @@
+-- See 0029_code_frequency.sql. SQLite has no ADD COLUMN IF NOT EXISTS, so
+-- schema.sqlite.sql leaves the column to this migration.
+ALTER TABLE heatmap_themes ADD COLUMN show_code_frequency BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- See 0030_commit_emails.sql. MySQL has no ADD COLUMN IF NOT EXISTS, so
+-- schema.mysql.sql leaves the column to this migration.
+CREATE TABLE IF NOT EXISTS commit_emails (
+    user_id BINARY(16) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (user_id, email),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE commit_details ADD COLUMN co_authored BOOLEAN NOT NULL DEFAULT FALSE;
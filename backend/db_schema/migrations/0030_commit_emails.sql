@@ -0,0 +1,12 @@
+-- Extra email addresses of a user, for attributing commits the user
+-- co-authored. A Co-authored-by trailer with one of them counts the commit
+-- for the account whose detailed sync found it.
+CREATE TABLE IF NOT EXISTS commit_emails (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, email)
+);
+
+-- Whether the user co-authored the commit rather than authored it
+ALTER TABLE commit_details ADD COLUMN IF NOT EXISTS co_authored BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- See 0030_commit_emails.sql. SQLite has no ADD COLUMN IF NOT EXISTS, so
+-- schema.sqlite.sql leaves the column to this migration.
+CREATE TABLE IF NOT EXISTS commit_emails (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (user_id, email)
+);
+
+ALTER TABLE commit_details ADD COLUMN co_authored BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- See 0031_commit_email_verification.sql. MySQL has no ADD COLUMN IF NOT
+-- EXISTS, so schema.mysql.sql leaves the columns to this migration.
+ALTER TABLE commit_emails ADD COLUMN verified_at DATETIME(6) NULL;
+ALTER TABLE commit_emails ADD COLUMN token_hash VARCHAR(64) NULL;
+ALTER TABLE commit_emails ADD COLUMN token_expires_at DATETIME(6) NULL;
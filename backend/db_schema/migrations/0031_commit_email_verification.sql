@@ -0,0 +1,6 @@
+-- Commit emails count once confirmed through a link mailed to them, so
+-- nobody claims commits by adding someone else's address. Emails added
+-- before stay unconfirmed until a new link is requested.
+ALTER TABLE commit_emails ADD COLUMN IF NOT EXISTS verified_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE commit_emails ADD COLUMN IF NOT EXISTS token_hash VARCHAR(64);
+ALTER TABLE commit_emails ADD COLUMN IF NOT EXISTS token_expires_at TIMESTAMP WITH TIME ZONE;
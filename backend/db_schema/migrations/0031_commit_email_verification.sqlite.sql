@@ -0,0 +1,5 @@
+-- See 0031_commit_email_verification.sql. SQLite has no ADD COLUMN IF NOT
+-- EXISTS, so schema.sqlite.sql leaves the columns to this migration.
+ALTER TABLE commit_emails ADD COLUMN verified_at TEXT;
+ALTER TABLE commit_emails ADD COLUMN token_hash VARCHAR(64);
+ALTER TABLE commit_emails ADD COLUMN token_expires_at TEXT;
@@ -0,0 +1,14 @@
+-- See 0032_mqtt_publishers.sql
+CREATE TABLE IF NOT EXISTS mqtt_publishers (
+    user_id BINARY(16) PRIMARY KEY,
+    broker_url VARCHAR(512) NOT NULL,
+    username VARCHAR(255),
+    password TEXT,
+    topic VARCHAR(200) NOT NULL,
+    home_assistant_discovery BOOLEAN NOT NULL DEFAULT FALSE,
+    last_published_at DATETIME(6),
+    last_error TEXT,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
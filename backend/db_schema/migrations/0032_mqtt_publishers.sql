@@ -0,0 +1,14 @@
+-- Per-user MQTT broker that daily totals and the streak are published to
+-- after each sync, for LED matrices and smart-home displays
+CREATE TABLE IF NOT EXISTS mqtt_publishers (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    broker_url VARCHAR(512) NOT NULL, -- mqtt:// or mqtts://host[:port]
+    username VARCHAR(255),
+    password TEXT, -- Encrypted
+    topic VARCHAR(200) NOT NULL, -- Prefix of the published topics
+    home_assistant_discovery BOOLEAN NOT NULL DEFAULT FALSE,
+    last_published_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- See 0032_mqtt_publishers.sql
+CREATE TABLE IF NOT EXISTS mqtt_publishers (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    broker_url VARCHAR(512) NOT NULL,
+    username VARCHAR(255),
+    password TEXT,
+    topic VARCHAR(200) NOT NULL,
+    home_assistant_discovery BOOLEAN NOT NULL DEFAULT 0,
+    last_published_at TEXT,
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
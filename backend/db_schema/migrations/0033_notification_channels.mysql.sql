@@ -0,0 +1,14 @@
+-- See 0033_notification_channels.sql
+CREATE TABLE IF NOT EXISTS notification_channels (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    kind VARCHAR(20) NOT NULL,
+    instance_url VARCHAR(512) NOT NULL,
+    account VARCHAR(255) NOT NULL,
+    secret TEXT NOT NULL,
+    last_sent_at DATETIME(6) NOT NULL,
+    last_error TEXT,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_notification_channels_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
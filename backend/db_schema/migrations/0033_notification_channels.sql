@@ -0,0 +1,15 @@
+-- Accounts elsewhere that hgitmap posts to on a user's behalf, like a
+-- weekly summary on Mastodon or Bluesky
+CREATE TABLE IF NOT EXISTS notification_channels (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL, -- mastodon or bluesky
+    instance_url VARCHAR(512) NOT NULL, -- Mastodon instance or Bluesky PDS
+    account VARCHAR(255) NOT NULL, -- Handle posted as
+    secret TEXT NOT NULL, -- Encrypted access token or app password
+    last_sent_at TIMESTAMP WITH TIME ZONE NOT NULL, -- The next summary covers the time since
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_channels_user_id ON notification_channels(user_id);
@@ -0,0 +1,14 @@
+-- See 0033_notification_channels.sql
+CREATE TABLE IF NOT EXISTS notification_channels (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    instance_url VARCHAR(512) NOT NULL,
+    account VARCHAR(255) NOT NULL,
+    secret TEXT NOT NULL,
+    last_sent_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_channels_user_id ON notification_channels(user_id);
@@ -0,0 +1,3 @@
+-- See 0034_notification_channel_events.sql. MySQL has no ADD COLUMN IF NOT
+-- EXISTS, so schema.mysql.sql leaves the column to this migration.
+ALTER TABLE notification_channels ADD COLUMN events JSON NOT NULL DEFAULT ('["weekly_summary"]');
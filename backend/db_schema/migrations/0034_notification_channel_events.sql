@@ -0,0 +1,4 @@
+-- Notification channels beyond Mastodon and Bluesky (email, webhooks,
+-- Slack, Discord, MQTT), each routed the events the user picks. Channels
+-- connected before keep posting the weekly summary.
+ALTER TABLE notification_channels ADD COLUMN IF NOT EXISTS events JSONB NOT NULL DEFAULT '["weekly_summary"]';
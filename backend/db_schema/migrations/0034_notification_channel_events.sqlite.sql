@@ -0,0 +1,3 @@
+-- See 0034_notification_channel_events.sql. SQLite has no ADD COLUMN IF NOT
+-- EXISTS, so schema.sqlite.sql leaves the column to this migration.
+ALTER TABLE notification_channels ADD COLUMN events TEXT NOT NULL DEFAULT '["weekly_summary"]';
@@ -0,0 +1,8 @@
+-- See 0035_oauth_application_listing.sql. MySQL has no ADD COLUMN IF NOT
+-- EXISTS, so schema.mysql.sql leaves the columns to this migration.
+ALTER TABLE oauth_applications ADD COLUMN display_name VARCHAR(255) NULL;
+ALTER TABLE oauth_applications ADD COLUMN icon_url VARCHAR(512) NULL;
+ALTER TABLE oauth_applications ADD COLUMN description TEXT NULL;
+ALTER TABLE oauth_applications ADD COLUMN login_hint VARCHAR(255) NULL;
+ALTER TABLE oauth_applications ADD COLUMN sort_order INT NOT NULL DEFAULT 0;
+ALTER TABLE oauth_applications ADD COLUMN is_featured BOOLEAN NOT NULL DEFAULT false;
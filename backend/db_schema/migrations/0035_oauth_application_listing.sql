@@ -0,0 +1,9 @@
+-- How an OAuth app is shown in the connect and sign-in screens: a name,
+-- icon, description and hint instead of the bare instance name, in the
+-- order admins choose, with featured apps first
+ALTER TABLE oauth_applications ADD COLUMN IF NOT EXISTS display_name VARCHAR(255);
+ALTER TABLE oauth_applications ADD COLUMN IF NOT EXISTS icon_url VARCHAR(512);
+ALTER TABLE oauth_applications ADD COLUMN IF NOT EXISTS description TEXT;
+ALTER TABLE oauth_applications ADD COLUMN IF NOT EXISTS login_hint VARCHAR(255);
+ALTER TABLE oauth_applications ADD COLUMN IF NOT EXISTS sort_order INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE oauth_applications ADD COLUMN IF NOT EXISTS is_featured BOOLEAN NOT NULL DEFAULT false;
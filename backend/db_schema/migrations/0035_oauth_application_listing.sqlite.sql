@@ -0,0 +1,8 @@
+-- See 0035_oauth_application_listing.sql. SQLite has no ADD COLUMN IF NOT
+-- EXISTS, so schema.sqlite.sql leaves the columns to this migration.
+ALTER TABLE oauth_applications ADD COLUMN display_name VARCHAR(255);
+ALTER TABLE oauth_applications ADD COLUMN icon_url VARCHAR(512);
+ALTER TABLE oauth_applications ADD COLUMN description TEXT;
+ALTER TABLE oauth_applications ADD COLUMN login_hint VARCHAR(255);
+ALTER TABLE oauth_applications ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE oauth_applications ADD COLUMN is_featured BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,4 @@
+-- See 0036_platform_account_earliest_sync_year.sql. MySQL has no ADD
+-- COLUMN IF NOT EXISTS, so schema.mysql.sql leaves the column to this
+-- migration.
+ALTER TABLE git_platform_accounts ADD COLUMN earliest_sync_year INT NOT NULL DEFAULT 2020;
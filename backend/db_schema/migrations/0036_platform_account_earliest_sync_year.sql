@@ -0,0 +1,4 @@
+-- The first year an account's all-years syncs go back to. New accounts
+-- take the instance's default_earliest_sync_year; 2020 was the fixed start
+-- before.
+ALTER TABLE git_platform_accounts ADD COLUMN IF NOT EXISTS earliest_sync_year INTEGER NOT NULL DEFAULT 2020;
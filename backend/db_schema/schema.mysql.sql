@@ -0,0 +1,1077 @@
+-- Database Schema for hgitmap
+-- MySQL 8.0.13+ / MariaDB 10.5+, used when DATABASE_URL starts with mysql://
+--
+-- Mirrors schema.sql. UUIDs are stored as BINARY(16), timestamps as UTC
+-- DATETIME(6) and enums as VARCHAR. updated_at columns use ON UPDATE
+-- instead of triggers. Create the database with CHARACTER SET utf8mb4.
+
+-- Users table
+CREATE TABLE users (
+    id BINARY(16) PRIMARY KEY,
+    username VARCHAR(255) UNIQUE NOT NULL,
+    password_hash VARCHAR(255), -- NULL for users who only sign in with GitHub/GitLab
+    email VARCHAR(255),
+    is_admin BOOLEAN DEFAULT false,
+    oidc_subject VARCHAR(255) UNIQUE, -- "sub" claim from the OIDC provider, for single sign-on users
+    username_changed_at DATETIME(6), -- Last rename, for the rename cooldown
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6)
+);
+
+-- Git platform accounts table
+CREATE TABLE git_platform_accounts (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    platform_type VARCHAR(50) NOT NULL,
+    platform_username VARCHAR(255) NOT NULL,
+    access_token TEXT, -- Encrypted OAuth token or API key
+    refresh_token TEXT, -- For OAuth refresh
+    platform_url VARCHAR(512), -- For self-hosted instances (Gitea, GitLab)
+    is_active BOOLEAN DEFAULT true,
+    last_synced_at DATETIME(6),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    -- Profile fields from the git platform
+    avatar_url VARCHAR(1024),
+    display_name VARCHAR(255),
+    bio TEXT,
+    profile_url VARCHAR(1024),
+    location VARCHAR(255),
+    company VARCHAR(255),
+    followers_count INTEGER DEFAULT 0,
+    following_count INTEGER DEFAULT 0,
+    -- Sync preferences (per-platform control)
+    sync_profile BOOLEAN DEFAULT true, -- Enable/disable syncing profile data (avatar, bio, etc.)
+    sync_contributions BOOLEAN DEFAULT true, -- Enable/disable syncing contributions and activities together
+    -- Authentication method
+    auth_type VARCHAR(50) NOT NULL DEFAULT 'oauth',
+    UNIQUE(user_id, platform_type, platform_username, platform_url(255)),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- detailed_sync and commit_retention_days are added by
+-- migrations/0028_commit_details, which runs on every database
+-- earliest_sync_year is added by
+-- migrations/0036_platform_account_earliest_sync_year, which runs on every
+-- database
+
+-- Contributions table
+CREATE TABLE contributions (
+    id BINARY(16) PRIMARY KEY,
+    git_platform_account_id BINARY(16) NOT NULL,
+    contribution_date DATE NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+    repository_name VARCHAR(512),
+    is_private_repo BOOLEAN DEFAULT false,
+    repository_key VARCHAR(512) AS (IFNULL(repository_name, '')) STORED,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (git_platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Unique constraint: one contribution per account, date, and repository
+-- (through repository_key so only one NULL repository_name is allowed per date)
+CREATE UNIQUE INDEX unique_contribution_per_account_date_repo
+    ON contributions(git_platform_account_id, contribution_date, repository_key);
+
+-- Index for fast date-range queries
+CREATE INDEX idx_contributions_date ON contributions(contribution_date);
+CREATE INDEX idx_contributions_account_date ON contributions(git_platform_account_id, contribution_date);
+
+-- Activities table for contribution timeline
+-- This stores various types of activities (commits, repos created, PRs, issues, etc.)
+CREATE TABLE activities (
+    id BINARY(16) PRIMARY KEY,
+    git_platform_account_id BINARY(16) NOT NULL,
+    activity_type VARCHAR(50) NOT NULL,
+    activity_date DATE NOT NULL,
+
+    -- Activity metadata (stored as JSON for flexibility)
+    -- Structure varies by activity_type (see examples below)
+    metadata JSON NOT NULL DEFAULT ('{}'),
+
+    -- Common fields extracted for easier querying
+    repository_name VARCHAR(512),
+    repository_url VARCHAR(1024),
+    is_private_repo BOOLEAN DEFAULT false,
+    count INTEGER DEFAULT 1, -- For commits, this is the commit count
+
+    -- Primary language for repository_created activities
+    primary_language VARCHAR(50),
+
+    -- Organization name for organization_joined activities
+    organization_name VARCHAR(255),
+    organization_avatar_url VARCHAR(1024),
+
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (git_platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- natural_key and its unique index are added by
+-- migrations/0012_activity_natural_keys, which runs on every database
+-- imported is added by migrations/0022_activity_imports, which runs on
+-- every database
+
+-- Indexes for fast activity queries
+CREATE INDEX idx_activities_account_date ON activities(git_platform_account_id, activity_date DESC);
+CREATE INDEX idx_activities_date ON activities(activity_date DESC);
+CREATE INDEX idx_activities_type ON activities(activity_type);
+
+-- Example metadata structures for different activity types:
+--
+-- commit:
+-- {
+--   "repositories": [
+--     {
+--       "name": "username/repo",
+--       "commit_count": 10,
+--       "commits": [{"sha": "abc123", "message": "...", "url": "..."}]
+--     }
+--   ],
+--   "total_count": 12
+-- }
+--
+-- repository_created:
+-- {
+--   "name": "username/repo",
+--   "description": "...",
+--   "language": "Rust",
+--   "is_fork": false,
+--   "created_at": "2025-12-16T00:00:00Z"
+-- }
+--
+-- organization_joined:
+-- {
+--   "organization": "org-name",
+--   "avatar_url": "https://...",
+--   "joined_at": "2025-12-14T00:00:00Z"
+-- }
+--
+-- pull_request/issue:
+-- {
+--   "title": "Feature X",
+--   "number": 123,
+--   "state": "open/closed/merged",
+--   "repository": "owner/repo",
+--   "url": "https://..."
+-- }
+
+-- User settings table
+CREATE TABLE user_settings (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) UNIQUE NOT NULL,
+    show_private_contributions BOOLEAN DEFAULT true,
+    hide_private_repo_names BOOLEAN DEFAULT false,
+    heatmap_color_scheme VARCHAR(50) DEFAULT 'green',
+    heatmap_size VARCHAR(20) DEFAULT 'medium',
+    dark_mode_enabled BOOLEAN DEFAULT false,
+    profile_visibility VARCHAR(20) NOT NULL DEFAULT 'public', -- public, unlisted or private
+    excluded_repositories JSON NOT NULL DEFAULT ('[]'), -- Patterns like owner/repo or owner/*, hidden from the public profile
+    embed_allowed_domains JSON NOT NULL DEFAULT ('[]'), -- Sites allowed to embed the heatmap images; empty allows all
+    activity_retention_days INTEGER, -- Prune older activities; NULL follows the instance policy
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- listed_in_directory is added by migrations/0021_user_directory, which
+-- runs on every database
+
+-- away_periods and hatch_away_days are added by migrations/0023_away_periods,
+-- which runs on every database
+
+-- milestone_webhook_url is added by migrations/0025_milestones, which runs on
+-- every database
+
+-- API tokens for embedding heatmap images
+CREATE TABLE api_tokens (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    token VARCHAR(255) UNIQUE NOT NULL, -- SHA-256 hash of the token
+    name VARCHAR(255),
+    scopes JSON NOT NULL DEFAULT ('[]'), -- e.g. read:contributions, write:themes, admin
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6),
+    last_used_at DATETIME(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_api_tokens_token ON api_tokens(token);
+
+-- Login sessions, each holding one rotating refresh token
+CREATE TABLE sessions (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64), -- Rotated-out token; presenting it again revokes the session
+    user_agent VARCHAR(255),
+    ip_address VARCHAR(64),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    last_used_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_sessions_user ON sessions(user_id);
+CREATE INDEX idx_sessions_previous_token ON sessions(previous_token_hash);
+
+-- Previous usernames, redirected to the user's current profile and embed
+-- URLs for a grace period; nobody else can register them until it ends
+CREATE TABLE username_redirects (
+    old_username VARCHAR(255) PRIMARY KEY, -- Lowercase
+    user_id BINARY(16) NOT NULL,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Pending email address changes, applied once the link sent to the new address is opened
+CREATE TABLE email_changes (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) UNIQUE NOT NULL, -- One pending change per user
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Invitations that let people register while open registration is disabled
+CREATE TABLE invitations (
+    id BINARY(16) PRIMARY KEY,
+    code_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the invitation code
+    email VARCHAR(255), -- Only this address may use the code; NULL for a shareable code
+    max_uses INTEGER, -- NULL for unlimited
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at DATETIME(6),
+    created_by BINARY(16),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Banners shown to every user, e.g. for maintenance windows
+CREATE TABLE announcements (
+    id BINARY(16) PRIMARY KEY,
+    message TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL DEFAULT 'info', -- info, warning or critical
+    starts_at DATETIME(6), -- NULL shows it right away
+    ends_at DATETIME(6), -- NULL shows it until deleted
+    dismissible BOOLEAN NOT NULL DEFAULT true,
+    created_by BINARY(16),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_announcements_ends_at ON announcements(ends_at);
+
+-- Instance settings changed by admins at runtime; they override the
+-- environment defaults
+CREATE TABLE instance_settings (
+    `key` VARCHAR(100) PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_by BINARY(16),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (updated_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- OAuth applications table (for web-based OAuth configuration)
+-- display_name, icon_url, description, login_hint, sort_order and is_featured
+-- are added by migrations/0035_oauth_application_listing, which runs on every
+-- database
+CREATE TABLE oauth_applications (
+    id BINARY(16) PRIMARY KEY,
+    platform VARCHAR(50) NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '',
+    instance_name VARCHAR(255) NOT NULL,
+    client_id VARCHAR(512) NOT NULL,
+    client_secret TEXT NOT NULL, -- Encrypted with AES-256-GCM
+    is_enabled BOOLEAN DEFAULT true,
+    is_default BOOLEAN DEFAULT false,
+    created_by BINARY(16),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    UNIQUE(platform, instance_url),
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Index for OAuth app lookups
+CREATE INDEX idx_oauth_apps_platform ON oauth_applications(platform, instance_url, is_enabled);
+
+-- OAuth state table for secure callback handling
+CREATE TABLE oauth_states (
+    id BINARY(16) PRIMARY KEY,
+    state_token VARCHAR(255) UNIQUE NOT NULL,
+    user_id BINARY(16), -- NULL while signing in
+    platform VARCHAR(50), -- NULL for OIDC sign-in and login codes
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    expires_at DATETIME(6) NOT NULL,
+    instance_url VARCHAR(500), -- Instance URL for self-hosted platforms (Gitea/GitLab OAuth)
+    purpose VARCHAR(20) NOT NULL DEFAULT 'connect', -- connect, login, link, oidc or login_code
+    nonce VARCHAR(255), -- OIDC nonce the ID token must echo
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_oauth_states_token ON oauth_states(state_token);
+CREATE INDEX idx_oauth_states_expires ON oauth_states(expires_at);
+
+-- GitHub/GitLab identities a user can sign in with
+CREATE TABLE user_identities (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    provider VARCHAR(50) NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '', -- Empty for github.com
+    provider_user_id VARCHAR(255) NOT NULL, -- Stable numeric ID, survives renames
+    provider_username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    last_login_at DATETIME(6),
+    UNIQUE(provider, instance_url(255), provider_user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_user_identities_user ON user_identities(user_id);
+
+-- Platform sync jobs table for async background syncing
+CREATE TABLE platform_sync_jobs (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    platform_account_id BINARY(16) NOT NULL,
+    
+    status VARCHAR(50) NOT NULL DEFAULT 'pending',
+    
+    -- Sync parameters
+    sync_all_years BOOLEAN NOT NULL DEFAULT FALSE,
+    specific_year INTEGER,
+    sync_contributions BOOLEAN NOT NULL DEFAULT TRUE,
+    sync_activities BOOLEAN NOT NULL DEFAULT TRUE,
+    sync_profile BOOLEAN NOT NULL DEFAULT FALSE,
+    
+    -- Job scheduling
+    scheduled_at DATETIME(6) NOT NULL,
+    started_at DATETIME(6),
+    completed_at DATETIME(6),
+    
+    -- Result tracking
+    error_message TEXT,
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    
+    -- Progress tracking
+    contributions_synced INTEGER,
+    activities_synced INTEGER,
+    years_completed INTEGER DEFAULT 0,
+    total_years INTEGER DEFAULT 0,
+    
+    -- Job metadata
+    is_manual BOOLEAN NOT NULL DEFAULT TRUE,
+    priority INTEGER NOT NULL DEFAULT 0,
+    
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Indexes for efficient sync job queries
+CREATE INDEX idx_platform_sync_jobs_status ON platform_sync_jobs(status);
+CREATE INDEX idx_platform_sync_jobs_user_id ON platform_sync_jobs(user_id);
+CREATE INDEX idx_platform_sync_jobs_platform_account_id ON platform_sync_jobs(platform_account_id);
+CREATE INDEX idx_platform_sync_jobs_scheduled_at ON platform_sync_jobs(scheduled_at);
+CREATE INDEX idx_platform_sync_jobs_priority_scheduled ON platform_sync_jobs(status, priority DESC, scheduled_at ASC);
+
+-- ============================================================
+-- Heatmap Theme and Generation System Schema
+-- ============================================================
+
+-- Heatmap themes table
+-- Users can define multiple themes with different color schemes, sizes, and styles
+CREATE TABLE heatmap_themes (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+
+    -- Theme identification
+    name VARCHAR(255) NOT NULL,
+    slug VARCHAR(255) NOT NULL, -- URL-safe identifier (e.g., 'dark-ocean', 'light-green')
+    description TEXT,
+    is_default BOOLEAN DEFAULT false,
+
+    -- Light/Dark mode
+    theme_mode VARCHAR(50) NOT NULL DEFAULT 'light',
+
+    -- Color configuration
+    color_scheme VARCHAR(50) NOT NULL DEFAULT 'github_green',
+
+    -- Custom contribution level colors (for 'custom' scheme type)
+    -- Stored as JSON array of 5 colors from low to high intensity
+    -- Example: ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"]
+    custom_colors JSON,
+
+    -- Theme colors
+    background_color VARCHAR(7) DEFAULT '#ffffff', -- Hex color
+    border_color VARCHAR(7) DEFAULT '#d1d5da',
+    text_color VARCHAR(7) DEFAULT '#24292e',
+    empty_cell_color VARCHAR(7) DEFAULT '#ebedf0', -- Color for days with no contributions
+
+    -- Cell/Rectangle styling
+    cell_size INTEGER DEFAULT 10, -- Size of each cell in pixels (width and height)
+    cell_gap INTEGER DEFAULT 2, -- Gap between cells in pixels
+    cell_border_radius INTEGER DEFAULT 2, -- Border radius for rounded corners (0 = square)
+    cell_border_width INTEGER DEFAULT 0, -- Border width for cells (0 = no border)
+    cell_border_color VARCHAR(7) DEFAULT '#d1d5da',
+
+    -- Overall heatmap dimensions
+    -- If null, auto-calculate based on data and cell size
+    heatmap_width INTEGER, -- Total width in pixels
+    heatmap_height INTEGER, -- Total height in pixels
+
+    -- Padding around the heatmap
+    padding_top INTEGER DEFAULT 20,
+    padding_right INTEGER DEFAULT 20,
+    padding_bottom INTEGER DEFAULT 17,
+    padding_left INTEGER DEFAULT 20,
+
+    -- Layout spacing settings (customize spacing for various UI elements)
+    day_label_width INTEGER DEFAULT 28, -- Width reserved for day labels (Mon, Wed, Fri)
+    month_label_height INTEGER DEFAULT 15, -- Height reserved for month labels
+    title_height INTEGER DEFAULT 30, -- Height reserved for title/header area
+    legend_height INTEGER DEFAULT 8, -- Height reserved for legend area
+
+    -- Display options
+    show_month_labels BOOLEAN DEFAULT true,
+    show_day_labels BOOLEAN DEFAULT true,
+    show_legend BOOLEAN DEFAULT true,
+    show_total_count BOOLEAN DEFAULT true, -- Show total contribution count
+    show_username BOOLEAN DEFAULT true, -- Show username at top
+    show_watermark BOOLEAN DEFAULT true, -- Show "Powered by Hgitmap" watermark
+
+    -- Font settings
+    font_family VARCHAR(255) DEFAULT 'sans-serif',
+    font_size INTEGER DEFAULT 10, -- Font size in pixels
+
+    -- Legend settings
+    legend_position VARCHAR(20) DEFAULT 'bottom', -- 'top', 'bottom', 'left', 'right', 'none'
+
+    -- Output formats (stored as a JSON array)
+    -- Users can generate multiple formats for the same theme
+    -- Example: ["png", "svg"] or ["png", "webp", "jpeg"]
+    output_formats JSON DEFAULT ('["png"]'),
+
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+
+    UNIQUE(user_id, slug),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- layout, custom_template and mini_days are added by
+-- migrations/0016_heatmap_layout, 0017_theme_custom_template and
+-- 0018_theme_mini_days, which run on every database
+-- show_achievements is added by migrations/0026_achievements, which runs on
+-- every database
+-- show_code_frequency is added by migrations/0029_code_frequency, which runs
+-- on every database
+
+-- Index for fast theme lookups
+CREATE INDEX idx_heatmap_themes_user ON heatmap_themes(user_id);
+CREATE INDEX idx_heatmap_themes_slug ON heatmap_themes(user_id, slug);
+
+-- Heatmap generation settings per user
+CREATE TABLE heatmap_generation_settings (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) UNIQUE NOT NULL,
+
+    -- Update interval in minutes (default: 60 minutes = 1 hour)
+    -- Common values: 15, 30, 60, 180, 360, 720, 1440 (24 hours)
+    update_interval_minutes INTEGER NOT NULL DEFAULT 60,
+
+    -- Allow users to pause automatic generation
+    auto_generation_enabled BOOLEAN DEFAULT false,
+
+    -- Date range for heatmap (in days, e.g., 365 for one year)
+    date_range_days INTEGER DEFAULT 365,
+
+    -- Whether to include private contributions
+    include_private_contributions BOOLEAN DEFAULT true,
+
+    -- Storage path customization (relative to static files directory)
+    storage_path VARCHAR(512), -- Optional custom path (default: /static/heatmaps/{user_id}/)
+
+    -- Last scheduled generation time
+    last_scheduled_generation_at DATETIME(6),
+    next_scheduled_generation_at DATETIME(6),
+
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- timezone, the generation window and generation_cron are added by
+-- migrations/0014_generation_schedule, which runs on every database
+
+-- Generated heatmaps tracking table
+-- Tracks all generated heatmap files and their metadata
+-- Each format for a theme gets its own row
+CREATE TABLE generated_heatmaps (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    theme_id BINARY(16) NOT NULL,
+
+    -- Format of this generated file
+    format VARCHAR(50) NOT NULL,
+
+    -- File information
+    file_path VARCHAR(1024) NOT NULL, -- Path to the generated file (e.g., /static/heatmaps/{user_id}/{theme_slug}.png)
+    file_size_bytes BIGINT, -- File size in bytes
+    file_hash VARCHAR(64), -- SHA-256 hash for cache invalidation
+
+    -- Generation metadata
+    generated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    generation_duration_ms INTEGER, -- How long it took to generate
+
+    -- Data snapshot info
+    contribution_count INTEGER DEFAULT 0, -- Total contributions in this heatmap
+    date_range_start DATE NOT NULL,
+    date_range_end DATE NOT NULL,
+
+    -- Access tracking
+    access_count INTEGER DEFAULT 0,
+    last_accessed_at DATETIME(6),
+
+    -- Status
+    is_valid BOOLEAN DEFAULT true, -- Mark as invalid when needs regeneration
+
+    UNIQUE(user_id, theme_id, format),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (theme_id) REFERENCES heatmap_themes(id) ON DELETE CASCADE
+);
+
+-- Indexes for generated heatmaps
+CREATE INDEX idx_generated_heatmaps_user ON generated_heatmaps(user_id);
+CREATE INDEX idx_generated_heatmaps_theme ON generated_heatmaps(theme_id);
+CREATE INDEX idx_generated_heatmaps_valid ON generated_heatmaps(is_valid);
+CREATE INDEX idx_generated_heatmaps_generated_at ON generated_heatmaps(generated_at);
+
+-- Heatmap generation queue/job table
+CREATE TABLE heatmap_generation_jobs (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    theme_id BINARY(16), -- NULL means regenerate all themes
+
+    status VARCHAR(50) NOT NULL DEFAULT 'pending',
+
+    -- Job scheduling
+    scheduled_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    started_at DATETIME(6),
+    completed_at DATETIME(6),
+
+    -- Result tracking
+    error_message TEXT,
+    retry_count INTEGER DEFAULT 0,
+    max_retries INTEGER DEFAULT 3,
+
+    -- Job metadata
+    is_manual BOOLEAN DEFAULT false, -- User triggered vs automatic
+    priority INTEGER DEFAULT 0, -- Higher priority jobs run first
+
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (theme_id) REFERENCES heatmap_themes(id) ON DELETE CASCADE
+);
+
+-- Indexes for job queue management
+CREATE INDEX idx_generation_jobs_status ON heatmap_generation_jobs(status, scheduled_at);
+CREATE INDEX idx_generation_jobs_user ON heatmap_generation_jobs(user_id);
+CREATE INDEX idx_generation_jobs_priority ON heatmap_generation_jobs(priority DESC, scheduled_at);
+
+-- Admin overrides of how many jobs per user the processors pick up at once;
+-- users without a row get JOB_CONCURRENCY_PER_USER
+CREATE TABLE user_job_limits (
+    user_id BINARY(16) PRIMARY KEY,
+    max_concurrent_jobs INTEGER NOT NULL CHECK (max_concurrent_jobs >= 1),
+    updated_by BINARY(16),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Daily views of generated heatmaps and embeds, per theme, referring site
+-- and kind of client
+CREATE TABLE heatmap_access_daily (
+    user_id BINARY(16) NOT NULL,
+    theme_id BINARY(16) NOT NULL,
+    access_date DATE NOT NULL,
+    referrer_host VARCHAR(255) NOT NULL DEFAULT '', -- empty without a Referer header
+    client VARCHAR(20) NOT NULL, -- browser, github, bot or other
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (theme_id, access_date, referrer_host, client),
+    INDEX idx_heatmap_access_daily_user_date (user_id, access_date),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (theme_id) REFERENCES heatmap_themes(id) ON DELETE CASCADE
+);
+
+-- Signed links to one theme's generated heatmap in one format, which work
+-- even when the profile is private
+CREATE TABLE heatmap_share_links (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    theme_id BINARY(16) NOT NULL,
+    format VARCHAR(50) NOT NULL,
+    expires_at DATETIME(6), -- never expires when NULL
+    max_views INTEGER, -- unlimited when NULL
+    view_count INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_heatmap_share_links_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (theme_id) REFERENCES heatmap_themes(id) ON DELETE CASCADE
+);
+
+-- Users who opted in to a periodic summary email
+CREATE TABLE email_digests (
+    user_id BINARY(16) PRIMARY KEY,
+    frequency VARCHAR(10) NOT NULL, -- weekly or monthly
+    last_sent_at DATETIME(6) NOT NULL, -- the next digest covers the time since
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- What public profile endpoints show of a platform account; accounts
+-- without a row are listed, without their sync time
+CREATE TABLE platform_account_visibility (
+    account_id BINARY(16) PRIMARY KEY,
+    is_public BOOLEAN NOT NULL DEFAULT true, -- listed on the public profile
+    show_last_synced_at BOOLEAN NOT NULL DEFAULT false,
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Names users gave their platform accounts, e.g. to tell two accounts on
+-- the same platform apart
+CREATE TABLE platform_account_labels (
+    account_id BINARY(16) PRIMARY KEY,
+    label VARCHAR(100) NOT NULL,
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Organizations a platform account belongs to, replaced on every activity
+-- sync
+CREATE TABLE organization_memberships (
+    account_id BINARY(16) NOT NULL,
+    login VARCHAR(255) NOT NULL,
+    avatar_url TEXT,
+    joined_at DATE, -- NULL when the platform does not tell
+    synced_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (account_id, login),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Scopes and redirect URI an admin set for an OAuth app; apps without a
+-- row use the defaults
+CREATE TABLE oauth_application_settings (
+    application_id BINARY(16) PRIMARY KEY,
+    scopes TEXT, -- NULL: the scopes hgitmap needs
+    redirect_uri TEXT, -- NULL: the callback route under BASE_URL
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (application_id) REFERENCES oauth_applications(id) ON DELETE CASCADE
+);
+
+-- Disconnected platform accounts kept for a while so they can be restored;
+-- the cleanup job deletes the account once purge_after has passed
+CREATE TABLE platform_account_disconnections (
+    platform_account_id BINARY(16) PRIMARY KEY,
+    disconnected_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    purge_after DATETIME(6) NOT NULL,
+    FOREIGN KEY (platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Platform API calls made for each account, per hour and kind of API, with
+-- the rate limit the platform last reported; the cleanup job removes old hours
+CREATE TABLE platform_api_usage_hourly (
+    platform_account_id BINARY(16) NOT NULL,
+    period_start DATETIME(6) NOT NULL,
+    api VARCHAR(10) NOT NULL, -- rest or graphql
+    requests INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0, -- no response or a 5xx
+    rate_limited INTEGER NOT NULL DEFAULT 0,
+    rate_limit_remaining INTEGER,
+    rate_limit_limit INTEGER,
+    rate_limit_reset_at DATETIME(6),
+    last_called_at DATETIME(6) NOT NULL,
+    PRIMARY KEY (platform_account_id, period_start, api),
+    FOREIGN KEY (platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Groups of users whose contributions are drawn as one heatmap, managed by
+-- their owner
+CREATE TABLE teams (
+    id BINARY(16) PRIMARY KEY,
+    owner_id BINARY(16) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_teams_owner_id (owner_id),
+    FOREIGN KEY (owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE team_members (
+    team_id BINARY(16) NOT NULL,
+    user_id BINARY(16) NOT NULL,
+    added_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (team_id, user_id),
+    INDEX idx_team_members_user_id (user_id),
+    FOREIGN KEY (team_id) REFERENCES teams(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Users whose public activities appear in a user's feed
+CREATE TABLE follows (
+    follower_id BINARY(16) NOT NULL,
+    followee_id BINARY(16) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (follower_id, followee_id),
+    INDEX idx_follows_followee_id (followee_id),
+    FOREIGN KEY (follower_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (followee_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Progress of an activity import, polled while it runs in the background
+CREATE TABLE activity_imports (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    platform_account_id BINARY(16) NOT NULL,
+    status VARCHAR(50) NOT NULL DEFAULT 'pending',
+    events_read INTEGER NOT NULL DEFAULT 0,
+    events_matched INTEGER NOT NULL DEFAULT 0,
+    activities_total INTEGER NOT NULL DEFAULT 0,
+    activities_imported INTEGER NOT NULL DEFAULT 0,
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    started_at DATETIME(6),
+    completed_at DATETIME(6),
+    INDEX idx_activity_imports_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (platform_account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Private notes on dates of a user's graph, like "conference" or "shipped v2.0"
+CREATE TABLE annotations (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    date DATE NOT NULL,
+    text VARCHAR(200) NOT NULL,
+    emoji VARCHAR(32),
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_annotations_user_date (user_id, date),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Milestones a user reached, like their 1,000th contribution or a 100-day streak
+CREATE TABLE milestones (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    kind VARCHAR(32) NOT NULL, -- contributions, streak or organization
+    milestone_key VARCHAR(255) NOT NULL, -- The threshold, or the organization in lowercase
+    title VARCHAR(255) NOT NULL,
+    achieved_on DATE NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    UNIQUE KEY uq_milestones_user_kind_key (user_id, kind, milestone_key),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Achievements a user has earned, like night owl or polyglot
+CREATE TABLE achievements (
+    user_id BINARY(16) NOT NULL,
+    achievement VARCHAR(32) NOT NULL, -- night_owl, early_bird, polyglot or marathoner
+    earned_on DATE NOT NULL,
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (user_id, achievement),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Bytes of code per language in the repositories an account contributed to
+CREATE TABLE repository_languages (
+    account_id BINARY(16) NOT NULL,
+    repository_name VARCHAR(255) NOT NULL, -- owner/repo, as in contributions
+    language VARCHAR(100) NOT NULL,
+    bytes BIGINT NOT NULL,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (account_id, repository_name, language),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+-- Individual commits of accounts with detailed sync on
+CREATE TABLE commit_details (
+    account_id BINARY(16) NOT NULL,
+    repository_name VARCHAR(255) NOT NULL, -- owner/repo, as in contributions
+    sha VARCHAR(64) NOT NULL,
+    committed_at DATETIME(6) NOT NULL,
+    additions INTEGER, -- NULL when the platform does not tell
+    deletions INTEGER,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (account_id, repository_name, sha),
+    FOREIGN KEY (account_id) REFERENCES git_platform_accounts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_commit_details_account_time ON commit_details(account_id, committed_at);
+
+-- co_authored is added by migrations/0030_commit_emails, which runs on every
+-- database
+
+-- Extra email addresses of a user, matched against commit authors and
+-- Co-authored-by trailers once confirmed. verified_at, token_hash and
+-- token_expires_at are added by migrations/0031_commit_email_verification,
+-- which runs on every database
+CREATE TABLE commit_emails (
+    user_id BINARY(16) NOT NULL,
+    email VARCHAR(255) NOT NULL, -- Lowercase
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (user_id, email),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Per-user MQTT broker that daily totals and the streak are published to
+-- after each sync, for LED matrices and smart-home displays
+CREATE TABLE mqtt_publishers (
+    user_id BINARY(16) PRIMARY KEY,
+    broker_url VARCHAR(512) NOT NULL, -- mqtt:// or mqtts://host[:port]
+    username VARCHAR(255),
+    password TEXT, -- Encrypted
+    topic VARCHAR(200) NOT NULL, -- Prefix of the published topics
+    home_assistant_discovery BOOLEAN NOT NULL DEFAULT FALSE,
+    last_published_at DATETIME(6),
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Places hgitmap notifies a user at, like a weekly summary posted to
+-- Mastodon or a webhook called when a sync fails
+-- events is added by migrations/0034_notification_channel_events, which runs
+-- on every database
+CREATE TABLE notification_channels (
+    id BINARY(16) PRIMARY KEY,
+    user_id BINARY(16) NOT NULL,
+    kind VARCHAR(20) NOT NULL, -- mastodon, bluesky, email, webhook, slack, discord or mqtt
+    instance_url VARCHAR(512) NOT NULL, -- Mastodon instance, Bluesky PDS or webhook; empty for email and MQTT
+    account VARCHAR(255) NOT NULL, -- Handle posted as, or what the user recognizes it by
+    secret TEXT NOT NULL, -- Encrypted token, app password, signing secret or webhook URL; empty when unused
+    last_sent_at DATETIME(6) NOT NULL, -- The next summary covers the time since
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    INDEX idx_notification_channels_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Invalidate generated heatmaps when contributions change
+CREATE TRIGGER invalidate_heatmaps_on_new_contribution
+    AFTER INSERT ON contributions
+    FOR EACH ROW
+    BEGIN
+        -- Mark all generated heatmaps for this user as invalid
+        UPDATE generated_heatmaps
+        SET is_valid = false
+        WHERE user_id IN (
+            SELECT user_id
+            FROM git_platform_accounts
+            WHERE id = NEW.git_platform_account_id
+        );
+    END;
+
+CREATE TRIGGER invalidate_heatmaps_on_changed_contribution
+    AFTER UPDATE ON contributions
+    FOR EACH ROW
+    BEGIN
+        UPDATE generated_heatmaps
+        SET is_valid = false
+        WHERE user_id IN (
+            SELECT user_id
+            FROM git_platform_accounts
+            WHERE id = NEW.git_platform_account_id
+        );
+    END;
+
+-- Create default themes and generation settings for new users
+CREATE TRIGGER create_default_themes_on_user_creation
+    AFTER INSERT ON users
+    FOR EACH ROW
+    BEGIN
+        -- Create default light theme (matches current frontend UI exactly)
+        INSERT INTO heatmap_themes (
+            id,
+            user_id,
+            name,
+            slug,
+            description,
+            is_default,
+            theme_mode,
+            color_scheme,
+            custom_colors,
+            background_color,
+            border_color,
+            text_color,
+            empty_cell_color,
+            cell_size,
+            cell_gap,
+            cell_border_radius,
+            cell_border_width,
+            cell_border_color,
+            padding_top,
+            padding_right,
+            padding_bottom,
+            padding_left,
+            day_label_width,
+            month_label_height,
+            title_height,
+            legend_height,
+            show_month_labels,
+            show_day_labels,
+            show_legend,
+            show_total_count,
+            show_username,
+            show_watermark,
+            font_family,
+            font_size,
+            output_formats
+        ) VALUES (
+            UNHEX(REPLACE(UUID(), '-', '')),
+            NEW.id,
+            'Default Light',
+            'default-light',
+            'Classic GitHub contribution graph style (light mode)',
+            true,
+            'light',
+            'custom',
+            '["#eff2f5", "#aceebb", "#4ac26b", "#2da44e", "#116329"]',
+            '#ffffff',
+            '#e1e4e8',
+            '#586069',
+            '#eff2f5',
+            10,
+            3,
+            2,
+            1,
+            '#e1e4e8',
+            20,
+            20,
+            10,
+            28,
+            28,
+            15,
+            30,
+            8,
+            true,
+            true,
+            true,
+            true,
+            false,
+            false,
+            'sans-serif',
+            10,
+            '["png", "svg"]'
+        );
+
+        -- Create default dark theme (matches current frontend dark mode)
+        INSERT INTO heatmap_themes (
+            id,
+            user_id,
+            name,
+            slug,
+            description,
+            is_default,
+            theme_mode,
+            color_scheme,
+            custom_colors,
+            background_color,
+            border_color,
+            text_color,
+            empty_cell_color,
+            cell_size,
+            cell_gap,
+            cell_border_radius,
+            cell_border_width,
+            cell_border_color,
+            padding_top,
+            padding_right,
+            padding_bottom,
+            padding_left,
+            day_label_width,
+            month_label_height,
+            title_height,
+            legend_height,
+            show_month_labels,
+            show_day_labels,
+            show_legend,
+            show_total_count,
+            show_username,
+            show_watermark,
+            font_family,
+            font_size,
+            output_formats
+        ) VALUES (
+            UNHEX(REPLACE(UUID(), '-', '')),
+            NEW.id,
+            'Default Dark',
+            'default-dark',
+            'GitHub contribution graph for dark mode',
+            false,
+            'dark',
+            'custom',
+            '["#151b23", "#033a16", "#196c2e", "#2ea043", "#56d364"]',
+            '#0d1117',
+            '#30363d',
+            '#8b949e',
+            '#151b23',
+            10,
+            3,
+            2,
+            1,
+            '#30363d',
+            20,
+            20,
+            10,
+            28,
+            28,
+            15,
+            30,
+            8,
+            true,
+            true,
+            true,
+            true,
+            false,
+            false,
+            'sans-serif',
+            10,
+            '["png", "svg"]'
+        );
+
+        -- Create generation settings with defaults
+        INSERT INTO heatmap_generation_settings (id, user_id)
+        VALUES (UNHEX(REPLACE(UUID(), '-', '')), NEW.id);
+
+    END;
+
+-- View for easily querying heatmap status per user
+CREATE VIEW user_heatmap_status AS
+SELECT
+    u.id as user_id,
+    u.username,
+    COUNT(DISTINCT ht.id) as total_themes,
+    COUNT(DISTINCT gh.id) as total_generated,
+    COUNT(DISTINCT CASE WHEN gh.is_valid = true THEN gh.id END) as valid_generated,
+    hgs.auto_generation_enabled,
+    hgs.update_interval_minutes,
+    hgs.next_scheduled_generation_at,
+    MAX(gh.generated_at) as last_generated_at
+FROM users u
+LEFT JOIN heatmap_themes ht ON u.id = ht.user_id
+LEFT JOIN generated_heatmaps gh ON u.id = gh.user_id
+LEFT JOIN heatmap_generation_settings hgs ON u.id = hgs.user_id
+GROUP BY u.id, u.username, hgs.auto_generation_enabled, hgs.update_interval_minutes, hgs.next_scheduled_generation_at;
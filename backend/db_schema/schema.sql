@@ -8,9 +8,11 @@ CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 CREATE TABLE users (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     username VARCHAR(255) UNIQUE NOT NULL,
-    password_hash VARCHAR(255) NOT NULL,
+    password_hash VARCHAR(255), -- NULL for users who only sign in with GitHub/GitLab
     email VARCHAR(255),
     is_admin BOOLEAN DEFAULT false,
+    oidc_subject VARCHAR(255) UNIQUE, -- "sub" claim from the OIDC provider, for single sign-on users
+    username_changed_at TIMESTAMP WITH TIME ZONE, -- Last rename, for the rename cooldown
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
 );
@@ -46,6 +48,9 @@ CREATE TABLE git_platform_accounts (
     -- Sync preferences (per-platform control)
     sync_profile BOOLEAN DEFAULT true, -- Enable/disable syncing profile data (avatar, bio, etc.)
     sync_contributions BOOLEAN DEFAULT true, -- Enable/disable syncing contributions and activities together
+    detailed_sync BOOLEAN NOT NULL DEFAULT FALSE, -- Also store individual commits in commit_details
+    commit_retention_days INTEGER, -- Days of commit details kept; NULL keeps them
+    earliest_sync_year INTEGER NOT NULL DEFAULT 2020, -- First year all-years syncs go back to
     -- Authentication method
     auth_type auth_type NOT NULL DEFAULT 'oauth',
     UNIQUE(user_id, platform_type, platform_username, platform_url)
@@ -81,7 +86,8 @@ CREATE TYPE activity_type AS ENUM (
     'organization_joined',
     'fork',
     'release',
-    'star'
+    'star',
+    'milestone'
 );
 
 -- Activities table for contribution timeline
@@ -109,12 +115,20 @@ CREATE TABLE activities (
     organization_name VARCHAR(255),
     organization_avatar_url VARCHAR(1024),
 
+    -- Identifies the activity on its platform (SHA-256 hex), so syncing
+    -- again updates it in place; NULL for activities from before 0012
+    natural_key VARCHAR(64),
+
+    -- Backfilled from an uploaded event file; never pruned by a sync
+    imported BOOLEAN NOT NULL DEFAULT FALSE,
+
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT unique_activity_per_account_key UNIQUE (git_platform_account_id, natural_key)
 );
 
 -- Indexes for fast activity queries
-CREATE INDEX idx_activities_account_date ON activities(git_platform_account_id, activity_date DESC);
+CREATE INDEX idx_activities_account_date_type ON activities(git_platform_account_id, activity_date DESC, activity_type);
 CREATE INDEX idx_activities_date ON activities(activity_date DESC);
 CREATE INDEX idx_activities_type ON activities(activity_type);
 CREATE INDEX idx_activities_metadata ON activities USING GIN (metadata);
@@ -167,6 +181,14 @@ CREATE TABLE user_settings (
     heatmap_color_scheme VARCHAR(50) DEFAULT 'green',
     heatmap_size VARCHAR(20) DEFAULT 'medium',
     dark_mode_enabled BOOLEAN DEFAULT false,
+    profile_visibility VARCHAR(20) NOT NULL DEFAULT 'public', -- public, unlisted or private
+    excluded_repositories JSONB NOT NULL DEFAULT '[]', -- Patterns like owner/repo or owner/*, hidden from the public profile
+    embed_allowed_domains JSONB NOT NULL DEFAULT '[]', -- Sites allowed to embed the heatmap images; empty allows all
+    activity_retention_days INTEGER, -- Prune older activities; NULL follows the instance policy
+    listed_in_directory BOOLEAN NOT NULL DEFAULT false, -- Opted in to the public user directory
+    away_periods JSONB NOT NULL DEFAULT '[]', -- Date ranges that do not break a streak, like {"start": "2026-07-01", "end": "2026-07-14"}
+    hatch_away_days BOOLEAN NOT NULL DEFAULT false, -- Draw away days without contributions hatched
+    milestone_webhook_url VARCHAR(2048), -- Receives a POST for each milestone reached
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
 );
@@ -175,8 +197,9 @@ CREATE TABLE user_settings (
 CREATE TABLE api_tokens (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-    token VARCHAR(255) UNIQUE NOT NULL,
+    token VARCHAR(255) UNIQUE NOT NULL, -- SHA-256 hash of the token
     name VARCHAR(255),
+    scopes JSONB NOT NULL DEFAULT '[]', -- e.g. read:contributions, write:themes, admin
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     expires_at TIMESTAMP WITH TIME ZONE,
     last_used_at TIMESTAMP WITH TIME ZONE
@@ -184,6 +207,77 @@ CREATE TABLE api_tokens (
 
 CREATE INDEX idx_api_tokens_token ON api_tokens(token);
 
+-- Login sessions, each holding one rotating refresh token
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64), -- Rotated-out token; presenting it again revokes the session
+    user_agent VARCHAR(255),
+    ip_address VARCHAR(64),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_sessions_user ON sessions(user_id);
+CREATE INDEX idx_sessions_previous_token ON sessions(previous_token_hash);
+
+-- Previous usernames, redirected to the user's current profile and embed
+-- URLs for a grace period; nobody else can register them until it ends
+CREATE TABLE username_redirects (
+    old_username VARCHAR(255) PRIMARY KEY, -- Lowercase
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Pending email address changes, applied once the link sent to the new address is opened
+CREATE TABLE email_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- One pending change per user
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Invitations that let people register while open registration is disabled
+CREATE TABLE invitations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    code_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the invitation code
+    email VARCHAR(255), -- Only this address may use the code; NULL for a shareable code
+    max_uses INTEGER, -- NULL for unlimited
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Banners shown to every user, e.g. for maintenance windows
+CREATE TABLE announcements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    message TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL DEFAULT 'info', -- info, warning or critical
+    starts_at TIMESTAMP WITH TIME ZONE, -- NULL shows it right away
+    ends_at TIMESTAMP WITH TIME ZONE, -- NULL shows it until deleted
+    dismissible BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_announcements_ends_at ON announcements(ends_at);
+
+-- Instance settings changed by admins at runtime; they override the
+-- environment defaults
+CREATE TABLE instance_settings (
+    key VARCHAR(100) PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
 -- OAuth applications table (for web-based OAuth configuration)
 CREATE TABLE oauth_applications (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
@@ -197,6 +291,12 @@ CREATE TABLE oauth_applications (
     created_by UUID REFERENCES users(id) ON DELETE SET NULL,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    display_name VARCHAR(255), -- Shown instead of instance_name when set
+    icon_url VARCHAR(512),
+    description TEXT,
+    login_hint VARCHAR(255), -- e.g. which account to sign in with
+    sort_order INTEGER NOT NULL DEFAULT 0, -- Lowest first, after featured apps
+    is_featured BOOLEAN NOT NULL DEFAULT false,
     UNIQUE(platform, instance_url)
 );
 
@@ -207,16 +307,34 @@ CREATE INDEX idx_oauth_apps_platform ON oauth_applications(platform, instance_ur
 CREATE TABLE oauth_states (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     state_token VARCHAR(255) UNIQUE NOT NULL,
-    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-    platform git_platform NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE, -- NULL while signing in
+    platform git_platform, -- NULL for OIDC sign-in and login codes
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
-    instance_url VARCHAR(500) -- Instance URL for self-hosted platforms (Gitea/GitLab OAuth)
+    instance_url VARCHAR(500), -- Instance URL for self-hosted platforms (Gitea/GitLab OAuth)
+    purpose VARCHAR(20) NOT NULL DEFAULT 'connect', -- connect, login, link, oidc or login_code
+    nonce VARCHAR(255) -- OIDC nonce the ID token must echo
 );
 
 CREATE INDEX idx_oauth_states_token ON oauth_states(state_token);
 CREATE INDEX idx_oauth_states_expires ON oauth_states(expires_at);
 
+-- GitHub/GitLab identities a user can sign in with
+CREATE TABLE user_identities (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider git_platform NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '', -- Empty for github.com
+    provider_user_id VARCHAR(255) NOT NULL, -- Stable numeric ID, survives renames
+    provider_username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_login_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE(provider, instance_url, provider_user_id)
+);
+
+CREATE INDEX idx_user_identities_user ON user_identities(user_id);
+
 -- Sync job status enum
 CREATE TYPE sync_job_status AS ENUM ('pending', 'processing', 'completed', 'failed');
 
@@ -293,6 +411,9 @@ CREATE TRIGGER update_user_settings_updated_at BEFORE UPDATE ON user_settings
 CREATE TRIGGER update_oauth_applications_updated_at BEFORE UPDATE ON oauth_applications
     FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
 
+CREATE TRIGGER update_announcements_updated_at BEFORE UPDATE ON announcements
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
 -- ============================================================
 -- Heatmap Theme and Generation System Schema
 -- ============================================================
@@ -385,10 +506,22 @@ CREATE TABLE heatmap_themes (
     -- Legend settings
     legend_position VARCHAR(20) DEFAULT 'bottom', -- 'top', 'bottom', 'left', 'right', 'none'
 
-    -- Output formats (stored as array)
+    -- Renderer that draws the heatmap: 'grid', 'skyline', 'month_grid',
+    -- 'stats_card', 'badge' or 'mini'
+    layout VARCHAR(32) NOT NULL DEFAULT 'grid',
+    -- minijinja template producing the whole SVG instead of the layout
+    custom_template TEXT,
+    -- Days of the `{slug}-mini.{ext}` files, 30 or 90; NULL for none
+    mini_days INTEGER,
+    -- Whether the stats card lists the user's achievements
+    show_achievements BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Whether a grid draws lines added and removed per week under the cells
+    show_code_frequency BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Output formats (stored as a JSON array)
     -- Users can generate multiple formats for the same theme
-    -- Example: ['png', 'svg'] or ['png', 'webp', 'jpeg']
-    output_formats heatmap_format[] DEFAULT ARRAY['png']::heatmap_format[],
+    -- Example: ["png", "svg"] or ["png", "webp", "jpeg"]
+    output_formats JSONB DEFAULT '["png"]',
 
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
@@ -421,6 +554,14 @@ CREATE TABLE heatmap_generation_settings (
     -- Storage path customization (relative to static files directory)
     storage_path VARCHAR(512), -- Optional custom path (default: /static/heatmaps/{user_id}/)
 
+    -- When scheduled runs may start, in the user's timezone: a daily window
+    -- in minutes after midnight (the end may be past midnight), or a cron
+    -- expression that replaces the interval and window
+    timezone VARCHAR(64),
+    generation_window_start_minutes INTEGER,
+    generation_window_end_minutes INTEGER,
+    generation_cron VARCHAR(128),
+
     -- Last scheduled generation time
     last_scheduled_generation_at TIMESTAMP WITH TIME ZONE,
     next_scheduled_generation_at TIMESTAMP WITH TIME ZONE,
@@ -472,7 +613,7 @@ CREATE INDEX idx_generated_heatmaps_generated_at ON generated_heatmaps(generated
 
 -- Heatmap generation queue/job table
 -- Tracks pending and completed generation jobs
-CREATE TYPE generation_job_status AS ENUM ('pending', 'processing', 'completed', 'failed');
+CREATE TYPE generation_job_status AS ENUM ('pending', 'processing', 'completed', 'failed', 'dead_letter');
 
 CREATE TABLE heatmap_generation_jobs (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
@@ -503,6 +644,269 @@ CREATE INDEX idx_generation_jobs_status ON heatmap_generation_jobs(status, sched
 CREATE INDEX idx_generation_jobs_user ON heatmap_generation_jobs(user_id);
 CREATE INDEX idx_generation_jobs_priority ON heatmap_generation_jobs(priority DESC, scheduled_at);
 
+-- Admin overrides of how many jobs per user the processors pick up at once;
+-- users without a row get JOB_CONCURRENCY_PER_USER
+CREATE TABLE user_job_limits (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    max_concurrent_jobs INTEGER NOT NULL CHECK (max_concurrent_jobs >= 1),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Daily views of generated heatmaps and embeds, per theme, referring site
+-- and kind of client
+CREATE TABLE heatmap_access_daily (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id UUID NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    access_date DATE NOT NULL,
+    referrer_host VARCHAR(255) NOT NULL DEFAULT '', -- empty without a Referer header
+    client VARCHAR(20) NOT NULL, -- browser, github, bot or other
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (theme_id, access_date, referrer_host, client)
+);
+
+CREATE INDEX idx_heatmap_access_daily_user_date ON heatmap_access_daily(user_id, access_date);
+
+-- Signed links to one theme's generated heatmap in one format, which work
+-- even when the profile is private
+CREATE TABLE heatmap_share_links (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id UUID NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    format heatmap_format NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE, -- never expires when NULL
+    max_views INTEGER, -- unlimited when NULL
+    view_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_heatmap_share_links_user_id ON heatmap_share_links(user_id);
+
+-- Users who opted in to a periodic summary email
+CREATE TABLE email_digests (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    frequency VARCHAR(10) NOT NULL, -- weekly or monthly
+    last_sent_at TIMESTAMP WITH TIME ZONE NOT NULL, -- the next digest covers the time since
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- What public profile endpoints show of a platform account; accounts
+-- without a row are listed, without their sync time
+CREATE TABLE platform_account_visibility (
+    account_id UUID PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    is_public BOOLEAN NOT NULL DEFAULT true, -- listed on the public profile
+    show_last_synced_at BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Names users gave their platform accounts, e.g. to tell two accounts on
+-- the same platform apart
+CREATE TABLE platform_account_labels (
+    account_id UUID PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    label VARCHAR(100) NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Organizations a platform account belongs to, replaced on every activity
+-- sync
+CREATE TABLE organization_memberships (
+    account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    login VARCHAR(255) NOT NULL,
+    avatar_url TEXT,
+    joined_at DATE, -- NULL when the platform does not tell
+    synced_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, login)
+);
+
+-- Scopes and redirect URI an admin set for an OAuth app; apps without a
+-- row use the defaults
+CREATE TABLE oauth_application_settings (
+    application_id UUID PRIMARY KEY REFERENCES oauth_applications(id) ON DELETE CASCADE,
+    scopes TEXT, -- NULL: the scopes hgitmap needs
+    redirect_uri TEXT, -- NULL: the callback route under BASE_URL
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Disconnected platform accounts kept for a while so they can be restored;
+-- the cleanup job deletes the account once purge_after has passed
+CREATE TABLE platform_account_disconnections (
+    platform_account_id UUID PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    disconnected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    purge_after TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Platform API calls made for each account, per hour and kind of API, with
+-- the rate limit the platform last reported; the cleanup job removes old hours
+CREATE TABLE platform_api_usage_hourly (
+    platform_account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    period_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    api VARCHAR(10) NOT NULL, -- rest or graphql
+    requests INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0, -- no response or a 5xx
+    rate_limited INTEGER NOT NULL DEFAULT 0,
+    rate_limit_remaining INTEGER,
+    rate_limit_limit INTEGER,
+    rate_limit_reset_at TIMESTAMP WITH TIME ZONE,
+    last_called_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (platform_account_id, period_start, api)
+);
+
+-- Groups of users whose contributions are drawn as one heatmap, managed by
+-- their owner
+CREATE TABLE teams (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_teams_owner_id ON teams(owner_id);
+
+CREATE TABLE team_members (
+    team_id UUID NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE INDEX idx_team_members_user_id ON team_members(user_id);
+
+-- Users whose public activities appear in a user's feed
+CREATE TABLE follows (
+    follower_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    followee_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (follower_id, followee_id)
+);
+
+CREATE INDEX idx_follows_followee_id ON follows(followee_id);
+
+-- Progress of an activity import, polled while it runs in the background
+CREATE TABLE activity_imports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform_account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    status sync_job_status NOT NULL DEFAULT 'pending',
+    events_read INTEGER NOT NULL DEFAULT 0, -- Every event in the file
+    events_matched INTEGER NOT NULL DEFAULT 0, -- The account's events that map to activities
+    activities_total INTEGER NOT NULL DEFAULT 0,
+    activities_imported INTEGER NOT NULL DEFAULT 0, -- New activities stored
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0, -- Already stored, e.g. by a sync
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_activity_imports_user_id ON activity_imports(user_id);
+
+-- Private notes on dates of a user's graph, like "conference" or "shipped v2.0"
+CREATE TABLE annotations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    text VARCHAR(200) NOT NULL,
+    emoji VARCHAR(32),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_annotations_user_date ON annotations(user_id, date);
+
+-- Milestones a user reached, like their 1,000th contribution or a 100-day streak
+CREATE TABLE milestones (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL, -- contributions, streak or organization
+    milestone_key VARCHAR(255) NOT NULL, -- The threshold, or the organization in lowercase
+    title VARCHAR(255) NOT NULL,
+    achieved_on DATE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, kind, milestone_key)
+);
+
+-- Achievements a user has earned, like night owl or polyglot
+CREATE TABLE achievements (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement VARCHAR(32) NOT NULL, -- night_owl, early_bird, polyglot or marathoner
+    earned_on DATE NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, achievement)
+);
+
+-- Bytes of code per language in the repositories an account contributed to
+CREATE TABLE repository_languages (
+    account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL, -- owner/repo, as in contributions
+    language VARCHAR(100) NOT NULL,
+    bytes BIGINT NOT NULL,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, repository_name, language)
+);
+
+-- Individual commits of accounts with detailed sync on
+CREATE TABLE commit_details (
+    account_id UUID NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL, -- owner/repo, as in contributions
+    sha VARCHAR(64) NOT NULL,
+    committed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    additions INTEGER, -- NULL when the platform does not tell
+    deletions INTEGER,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    co_authored BOOLEAN NOT NULL DEFAULT FALSE, -- The user is a co-author, not the author
+    PRIMARY KEY (account_id, repository_name, sha)
+);
+
+CREATE INDEX idx_commit_details_account_time ON commit_details(account_id, committed_at);
+
+-- Extra email addresses of a user, matched against commit authors and
+-- Co-authored-by trailers once confirmed
+CREATE TABLE commit_emails (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL, -- Lowercase
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    verified_at TIMESTAMP WITH TIME ZONE, -- NULL until the mailed link is opened
+    token_hash VARCHAR(64), -- SHA-256 of the pending confirmation token
+    token_expires_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (user_id, email)
+);
+
+-- Per-user MQTT broker that daily totals and the streak are published to
+-- after each sync, for LED matrices and smart-home displays
+CREATE TABLE mqtt_publishers (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    broker_url VARCHAR(512) NOT NULL, -- mqtt:// or mqtts://host[:port]
+    username VARCHAR(255),
+    password TEXT, -- Encrypted
+    topic VARCHAR(200) NOT NULL, -- Prefix of the published topics
+    home_assistant_discovery BOOLEAN NOT NULL DEFAULT FALSE,
+    last_published_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Places hgitmap notifies a user at, like a weekly summary posted to
+-- Mastodon or a webhook called when a sync fails
+CREATE TABLE notification_channels (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL, -- mastodon, bluesky, email, webhook, slack, discord or mqtt
+    instance_url VARCHAR(512) NOT NULL, -- Mastodon instance, Bluesky PDS or webhook; empty for email and MQTT
+    account VARCHAR(255) NOT NULL, -- Handle posted as, or what the user recognizes it by
+    secret TEXT NOT NULL, -- Encrypted token, app password, signing secret or webhook URL; empty when unused
+    last_sent_at TIMESTAMP WITH TIME ZONE NOT NULL, -- The next summary covers the time since
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    events JSONB NOT NULL DEFAULT '["weekly_summary"]' -- Events routed to the channel
+);
+
+CREATE INDEX idx_notification_channels_user_id ON notification_channels(user_id);
+
 -- Add triggers for updated_at
 CREATE TRIGGER update_heatmap_themes_updated_at BEFORE UPDATE ON heatmap_themes
     FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -606,7 +1010,7 @@ BEGIN
         false,
         'sans-serif',
         10,
-        ARRAY['png', 'svg']::heatmap_format[]
+        '["png", "svg"]'::jsonb
     );
 
     -- Create default dark theme (matches current frontend dark mode)
@@ -679,7 +1083,7 @@ BEGIN
         false,
         'sans-serif',
         10,
-        ARRAY['png', 'svg']::heatmap_format[]
+        '["png", "svg"]'::jsonb
     );
 
     -- Create generation settings with defaults
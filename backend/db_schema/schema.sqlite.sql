@@ -0,0 +1,1085 @@
+-- Database Schema for hgitmap
+-- SQLite 3.35+, used when DATABASE_URL starts with sqlite://
+--
+-- Mirrors schema.sql. UUIDs are stored as 16-byte blobs, timestamps as
+-- RFC 3339 text, enums as text and lists as JSON arrays.
+
+-- Users table
+CREATE TABLE users (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    username VARCHAR(255) UNIQUE NOT NULL,
+    password_hash VARCHAR(255), -- NULL for users who only sign in with GitHub/GitLab
+    email VARCHAR(255),
+    is_admin BOOLEAN DEFAULT false,
+    oidc_subject VARCHAR(255) UNIQUE, -- "sub" claim from the OIDC provider, for single sign-on users
+    username_changed_at TEXT, -- Last rename, for the rename cooldown
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Git platform accounts table
+CREATE TABLE git_platform_accounts (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform_type TEXT NOT NULL,
+    platform_username VARCHAR(255) NOT NULL,
+    access_token TEXT, -- Encrypted OAuth token or API key
+    refresh_token TEXT, -- For OAuth refresh
+    platform_url VARCHAR(512), -- For self-hosted instances (Gitea, GitLab)
+    is_active BOOLEAN DEFAULT true,
+    last_synced_at TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    -- Profile fields from the git platform
+    avatar_url VARCHAR(1024),
+    display_name VARCHAR(255),
+    bio TEXT,
+    profile_url VARCHAR(1024),
+    location VARCHAR(255),
+    company VARCHAR(255),
+    followers_count INTEGER DEFAULT 0,
+    following_count INTEGER DEFAULT 0,
+    -- Sync preferences (per-platform control)
+    sync_profile BOOLEAN DEFAULT true, -- Enable/disable syncing profile data (avatar, bio, etc.)
+    sync_contributions BOOLEAN DEFAULT true, -- Enable/disable syncing contributions and activities together
+    -- Authentication method
+    auth_type TEXT NOT NULL DEFAULT 'oauth',
+    UNIQUE(user_id, platform_type, platform_username, platform_url)
+);
+
+-- detailed_sync and commit_retention_days are added by
+-- migrations/0028_commit_details, which runs on every database
+-- earliest_sync_year is added by
+-- migrations/0036_platform_account_earliest_sync_year, which runs on every
+-- database
+
+-- Contributions table
+CREATE TABLE contributions (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    git_platform_account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    contribution_date DATE NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+    repository_name VARCHAR(512),
+    is_private_repo BOOLEAN DEFAULT false,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Unique constraint: one contribution per account, date, and repository
+-- (IFNULL so only one NULL repository_name is allowed per date)
+CREATE UNIQUE INDEX unique_contribution_per_account_date_repo
+    ON contributions(git_platform_account_id, contribution_date, IFNULL(repository_name, ''));
+
+-- Index for fast date-range queries
+CREATE INDEX idx_contributions_date ON contributions(contribution_date);
+CREATE INDEX idx_contributions_account_date ON contributions(git_platform_account_id, contribution_date);
+
+-- Activities table for contribution timeline
+-- This stores various types of activities (commits, repos created, PRs, issues, etc.)
+CREATE TABLE activities (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    git_platform_account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    activity_type TEXT NOT NULL,
+    activity_date DATE NOT NULL,
+
+    -- Activity metadata (stored as TEXT for flexibility)
+    -- Structure varies by activity_type (see examples below)
+    metadata TEXT NOT NULL DEFAULT '{}',
+
+    -- Common fields extracted for easier querying
+    repository_name VARCHAR(512),
+    repository_url VARCHAR(1024),
+    is_private_repo BOOLEAN DEFAULT false,
+    count INTEGER DEFAULT 1, -- For commits, this is the commit count
+
+    -- Primary language for repository_created activities
+    primary_language VARCHAR(50),
+
+    -- Organization name for organization_joined activities
+    organization_name VARCHAR(255),
+    organization_avatar_url VARCHAR(1024),
+
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- natural_key and its unique index are added by
+-- migrations/0012_activity_natural_keys, which runs on every database
+-- imported is added by migrations/0022_activity_imports, which runs on
+-- every database
+
+-- Indexes for fast activity queries
+CREATE INDEX idx_activities_account_date_type ON activities(git_platform_account_id, activity_date DESC, activity_type);
+CREATE INDEX idx_activities_date ON activities(activity_date DESC);
+CREATE INDEX idx_activities_type ON activities(activity_type);
+
+-- Example metadata structures for different activity types:
+--
+-- commit:
+-- {
+--   "repositories": [
+--     {
+--       "name": "username/repo",
+--       "commit_count": 10,
+--       "commits": [{"sha": "abc123", "message": "...", "url": "..."}]
+--     }
+--   ],
+--   "total_count": 12
+-- }
+--
+-- repository_created:
+-- {
+--   "name": "username/repo",
+--   "description": "...",
+--   "language": "Rust",
+--   "is_fork": false,
+--   "created_at": "2025-12-16T00:00:00Z"
+-- }
+--
+-- organization_joined:
+-- {
+--   "organization": "org-name",
+--   "avatar_url": "https://...",
+--   "joined_at": "2025-12-14T00:00:00Z"
+-- }
+--
+-- pull_request/issue:
+-- {
+--   "title": "Feature X",
+--   "number": 123,
+--   "state": "open/closed/merged",
+--   "repository": "owner/repo",
+--   "url": "https://..."
+-- }
+
+-- User settings table
+CREATE TABLE user_settings (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    show_private_contributions BOOLEAN DEFAULT true,
+    hide_private_repo_names BOOLEAN DEFAULT false,
+    heatmap_color_scheme VARCHAR(50) DEFAULT 'green',
+    heatmap_size VARCHAR(20) DEFAULT 'medium',
+    dark_mode_enabled BOOLEAN DEFAULT false,
+    profile_visibility VARCHAR(20) NOT NULL DEFAULT 'public', -- public, unlisted or private
+    excluded_repositories TEXT NOT NULL DEFAULT '[]', -- Patterns like owner/repo or owner/*, hidden from the public profile
+    embed_allowed_domains TEXT NOT NULL DEFAULT '[]', -- Sites allowed to embed the heatmap images; empty allows all
+    activity_retention_days INTEGER, -- Prune older activities; NULL follows the instance policy
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- listed_in_directory is added by migrations/0021_user_directory, which
+-- runs on every database
+
+-- away_periods and hatch_away_days are added by migrations/0023_away_periods,
+-- which runs on every database
+
+-- milestone_webhook_url is added by migrations/0025_milestones, which runs on
+-- every database
+
+-- API tokens for embedding heatmap images
+CREATE TABLE api_tokens (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token VARCHAR(255) UNIQUE NOT NULL, -- SHA-256 hash of the token
+    name VARCHAR(255),
+    scopes TEXT NOT NULL DEFAULT '[]', -- e.g. read:contributions, write:themes, admin
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT,
+    last_used_at TEXT
+);
+
+CREATE INDEX idx_api_tokens_token ON api_tokens(token);
+
+-- Login sessions, each holding one rotating refresh token
+CREATE TABLE sessions (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64), -- Rotated-out token; presenting it again revokes the session
+    user_agent VARCHAR(255),
+    ip_address VARCHAR(64),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    last_used_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_sessions_user ON sessions(user_id);
+CREATE INDEX idx_sessions_previous_token ON sessions(previous_token_hash);
+
+-- Previous usernames, redirected to the user's current profile and embed
+-- URLs for a grace period; nobody else can register them until it ends
+CREATE TABLE username_redirects (
+    old_username VARCHAR(255) PRIMARY KEY, -- Lowercase
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL
+);
+
+-- Pending email address changes, applied once the link sent to the new address is opened
+CREATE TABLE email_changes (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- One pending change per user
+    new_email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the confirmation token
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL
+);
+
+-- Invitations that let people register while open registration is disabled
+CREATE TABLE invitations (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    code_hash VARCHAR(64) UNIQUE NOT NULL, -- SHA-256 of the invitation code
+    email VARCHAR(255), -- Only this address may use the code; NULL for a shareable code
+    max_uses INTEGER, -- NULL for unlimited
+    use_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TEXT,
+    created_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Banners shown to every user, e.g. for maintenance windows
+CREATE TABLE announcements (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    message TEXT NOT NULL,
+    severity VARCHAR(20) NOT NULL DEFAULT 'info', -- info, warning or critical
+    starts_at TEXT, -- NULL shows it right away
+    ends_at TEXT, -- NULL shows it until deleted
+    dismissible BOOLEAN NOT NULL DEFAULT true,
+    created_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_announcements_ends_at ON announcements(ends_at);
+
+-- Instance settings changed by admins at runtime; they override the
+-- environment defaults
+CREATE TABLE instance_settings (
+    key VARCHAR(100) PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- OAuth applications table (for web-based OAuth configuration)
+-- display_name, icon_url, description, login_hint, sort_order and is_featured
+-- are added by migrations/0035_oauth_application_listing, which runs on every
+-- database
+CREATE TABLE oauth_applications (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    platform TEXT NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '',
+    instance_name VARCHAR(255) NOT NULL,
+    client_id VARCHAR(512) NOT NULL,
+    client_secret TEXT NOT NULL, -- Encrypted with AES-256-GCM
+    is_enabled BOOLEAN DEFAULT true,
+    is_default BOOLEAN DEFAULT false,
+    created_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    UNIQUE(platform, instance_url)
+);
+
+-- Index for OAuth app lookups
+CREATE INDEX idx_oauth_apps_platform ON oauth_applications(platform, instance_url, is_enabled);
+
+-- OAuth state table for secure callback handling
+CREATE TABLE oauth_states (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    state_token VARCHAR(255) UNIQUE NOT NULL,
+    user_id BLOB REFERENCES users(id) ON DELETE CASCADE, -- NULL while signing in
+    platform TEXT, -- NULL for OIDC sign-in and login codes
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    expires_at TEXT NOT NULL,
+    instance_url VARCHAR(500), -- Instance URL for self-hosted platforms (Gitea/GitLab OAuth)
+    purpose VARCHAR(20) NOT NULL DEFAULT 'connect', -- connect, login, link, oidc or login_code
+    nonce VARCHAR(255) -- OIDC nonce the ID token must echo
+);
+
+CREATE INDEX idx_oauth_states_token ON oauth_states(state_token);
+CREATE INDEX idx_oauth_states_expires ON oauth_states(expires_at);
+
+-- GitHub/GitLab identities a user can sign in with
+CREATE TABLE user_identities (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    instance_url VARCHAR(512) NOT NULL DEFAULT '', -- Empty for github.com
+    provider_user_id VARCHAR(255) NOT NULL, -- Stable numeric ID, survives renames
+    provider_username VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    last_login_at TEXT,
+    UNIQUE(provider, instance_url, provider_user_id)
+);
+
+CREATE INDEX idx_user_identities_user ON user_identities(user_id);
+
+-- Platform sync jobs table for async background syncing
+CREATE TABLE platform_sync_jobs (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform_account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    
+    status TEXT NOT NULL DEFAULT 'pending',
+    
+    -- Sync parameters
+    sync_all_years BOOLEAN NOT NULL DEFAULT FALSE,
+    specific_year INTEGER,
+    sync_contributions BOOLEAN NOT NULL DEFAULT TRUE,
+    sync_activities BOOLEAN NOT NULL DEFAULT TRUE,
+    sync_profile BOOLEAN NOT NULL DEFAULT FALSE,
+    
+    -- Job scheduling
+    scheduled_at TEXT NOT NULL,
+    started_at TEXT,
+    completed_at TEXT,
+    
+    -- Result tracking
+    error_message TEXT,
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    
+    -- Progress tracking
+    contributions_synced INTEGER,
+    activities_synced INTEGER,
+    years_completed INTEGER DEFAULT 0,
+    total_years INTEGER DEFAULT 0,
+    
+    -- Job metadata
+    is_manual BOOLEAN NOT NULL DEFAULT TRUE,
+    priority INTEGER NOT NULL DEFAULT 0,
+    
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Indexes for efficient sync job queries
+CREATE INDEX idx_platform_sync_jobs_status ON platform_sync_jobs(status);
+CREATE INDEX idx_platform_sync_jobs_user_id ON platform_sync_jobs(user_id);
+CREATE INDEX idx_platform_sync_jobs_platform_account_id ON platform_sync_jobs(platform_account_id);
+CREATE INDEX idx_platform_sync_jobs_scheduled_at ON platform_sync_jobs(scheduled_at);
+CREATE INDEX idx_platform_sync_jobs_priority_scheduled ON platform_sync_jobs(priority DESC, scheduled_at ASC) WHERE status = 'pending';
+
+-- Create triggers for updated_at
+CREATE TRIGGER update_users_updated_at AFTER UPDATE ON users
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE users SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_git_platform_accounts_updated_at AFTER UPDATE ON git_platform_accounts
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE git_platform_accounts SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_contributions_updated_at AFTER UPDATE ON contributions
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE contributions SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_activities_updated_at AFTER UPDATE ON activities
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE activities SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_user_settings_updated_at AFTER UPDATE ON user_settings
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE user_settings SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_oauth_applications_updated_at AFTER UPDATE ON oauth_applications
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE oauth_applications SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_announcements_updated_at AFTER UPDATE ON announcements
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE announcements SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+-- ============================================================
+-- Heatmap Theme and Generation System Schema
+-- ============================================================
+
+-- Heatmap themes table
+-- Users can define multiple themes with different color schemes, sizes, and styles
+CREATE TABLE heatmap_themes (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Theme identification
+    name VARCHAR(255) NOT NULL,
+    slug VARCHAR(255) NOT NULL, -- URL-safe identifier (e.g., 'dark-ocean', 'light-green')
+    description TEXT,
+    is_default BOOLEAN DEFAULT false,
+
+    -- Light/Dark mode
+    theme_mode TEXT NOT NULL DEFAULT 'light',
+
+    -- Color configuration
+    color_scheme TEXT NOT NULL DEFAULT 'github_green',
+
+    -- Custom contribution level colors (for 'custom' scheme type)
+    -- Stored as JSON array of 5 colors from low to high intensity
+    -- Example: ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"]
+    custom_colors TEXT,
+
+    -- Theme colors
+    background_color VARCHAR(7) DEFAULT '#ffffff', -- Hex color
+    border_color VARCHAR(7) DEFAULT '#d1d5da',
+    text_color VARCHAR(7) DEFAULT '#24292e',
+    empty_cell_color VARCHAR(7) DEFAULT '#ebedf0', -- Color for days with no contributions
+
+    -- Cell/Rectangle styling
+    cell_size INTEGER DEFAULT 10, -- Size of each cell in pixels (width and height)
+    cell_gap INTEGER DEFAULT 2, -- Gap between cells in pixels
+    cell_border_radius INTEGER DEFAULT 2, -- Border radius for rounded corners (0 = square)
+    cell_border_width INTEGER DEFAULT 0, -- Border width for cells (0 = no border)
+    cell_border_color VARCHAR(7) DEFAULT '#d1d5da',
+
+    -- Overall heatmap dimensions
+    -- If null, auto-calculate based on data and cell size
+    heatmap_width INTEGER, -- Total width in pixels
+    heatmap_height INTEGER, -- Total height in pixels
+
+    -- Padding around the heatmap
+    padding_top INTEGER DEFAULT 20,
+    padding_right INTEGER DEFAULT 20,
+    padding_bottom INTEGER DEFAULT 17,
+    padding_left INTEGER DEFAULT 20,
+
+    -- Layout spacing settings (customize spacing for various UI elements)
+    day_label_width INTEGER DEFAULT 28, -- Width reserved for day labels (Mon, Wed, Fri)
+    month_label_height INTEGER DEFAULT 15, -- Height reserved for month labels
+    title_height INTEGER DEFAULT 30, -- Height reserved for title/header area
+    legend_height INTEGER DEFAULT 8, -- Height reserved for legend area
+
+    -- Display options
+    show_month_labels BOOLEAN DEFAULT true,
+    show_day_labels BOOLEAN DEFAULT true,
+    show_legend BOOLEAN DEFAULT true,
+    show_total_count BOOLEAN DEFAULT true, -- Show total contribution count
+    show_username BOOLEAN DEFAULT true, -- Show username at top
+    show_watermark BOOLEAN DEFAULT true, -- Show "Powered by Hgitmap" watermark
+
+    -- Font settings
+    font_family VARCHAR(255) DEFAULT 'sans-serif',
+    font_size INTEGER DEFAULT 10, -- Font size in pixels
+
+    -- Legend settings
+    legend_position VARCHAR(20) DEFAULT 'bottom', -- 'top', 'bottom', 'left', 'right', 'none'
+
+    -- Output formats (stored as a JSON array)
+    -- Users can generate multiple formats for the same theme
+    -- Example: ["png", "svg"] or ["png", "webp", "jpeg"]
+    output_formats TEXT DEFAULT '["png"]',
+
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+    UNIQUE(user_id, slug)
+);
+
+-- layout, custom_template and mini_days are added by
+-- migrations/0016_heatmap_layout, 0017_theme_custom_template and
+-- 0018_theme_mini_days, which run on every database
+-- show_achievements is added by migrations/0026_achievements, which runs on
+-- every database
+-- show_code_frequency is added by migrations/0029_code_frequency, which runs
+-- on every database
+
+-- Index for fast theme lookups
+CREATE INDEX idx_heatmap_themes_user ON heatmap_themes(user_id);
+CREATE INDEX idx_heatmap_themes_slug ON heatmap_themes(user_id, slug);
+
+-- Heatmap generation settings per user
+CREATE TABLE heatmap_generation_settings (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB UNIQUE NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Update interval in minutes (default: 60 minutes = 1 hour)
+    -- Common values: 15, 30, 60, 180, 360, 720, 1440 (24 hours)
+    update_interval_minutes INTEGER NOT NULL DEFAULT 60,
+
+    -- Allow users to pause automatic generation
+    auto_generation_enabled BOOLEAN DEFAULT false,
+
+    -- Date range for heatmap (in days, e.g., 365 for one year)
+    date_range_days INTEGER DEFAULT 365,
+
+    -- Whether to include private contributions
+    include_private_contributions BOOLEAN DEFAULT true,
+
+    -- Storage path customization (relative to static files directory)
+    storage_path VARCHAR(512), -- Optional custom path (default: /static/heatmaps/{user_id}/)
+
+    -- Last scheduled generation time
+    last_scheduled_generation_at TEXT,
+    next_scheduled_generation_at TEXT,
+
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- timezone, the generation window and generation_cron are added by
+-- migrations/0014_generation_schedule, which runs on every database
+
+-- Generated heatmaps tracking table
+-- Tracks all generated heatmap files and their metadata
+-- Each format for a theme gets its own row
+CREATE TABLE generated_heatmaps (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id BLOB NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+
+    -- Format of this generated file
+    format TEXT NOT NULL,
+
+    -- File information
+    file_path VARCHAR(1024) NOT NULL, -- Path to the generated file (e.g., /static/heatmaps/{user_id}/{theme_slug}.png)
+    file_size_bytes BIGINT, -- File size in bytes
+    file_hash VARCHAR(64), -- SHA-256 hash for cache invalidation
+
+    -- Generation metadata
+    generated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    generation_duration_ms INTEGER, -- How long it took to generate
+
+    -- Data snapshot info
+    contribution_count INTEGER DEFAULT 0, -- Total contributions in this heatmap
+    date_range_start DATE NOT NULL,
+    date_range_end DATE NOT NULL,
+
+    -- Access tracking
+    access_count INTEGER DEFAULT 0,
+    last_accessed_at TEXT,
+
+    -- Status
+    is_valid BOOLEAN DEFAULT true, -- Mark as invalid when needs regeneration
+
+    UNIQUE(user_id, theme_id, format)
+);
+
+-- Indexes for generated heatmaps
+CREATE INDEX idx_generated_heatmaps_user ON generated_heatmaps(user_id);
+CREATE INDEX idx_generated_heatmaps_theme ON generated_heatmaps(theme_id);
+CREATE INDEX idx_generated_heatmaps_valid ON generated_heatmaps(is_valid);
+CREATE INDEX idx_generated_heatmaps_generated_at ON generated_heatmaps(generated_at);
+
+-- Heatmap generation queue/job table
+CREATE TABLE heatmap_generation_jobs (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id BLOB REFERENCES heatmap_themes(id) ON DELETE CASCADE, -- NULL means regenerate all themes
+
+    status TEXT NOT NULL DEFAULT 'pending',
+
+    -- Job scheduling
+    scheduled_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    started_at TEXT,
+    completed_at TEXT,
+
+    -- Result tracking
+    error_message TEXT,
+    retry_count INTEGER DEFAULT 0,
+    max_retries INTEGER DEFAULT 3,
+
+    -- Job metadata
+    is_manual BOOLEAN DEFAULT false, -- User triggered vs automatic
+    priority INTEGER DEFAULT 0, -- Higher priority jobs run first
+
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Indexes for job queue management
+CREATE INDEX idx_generation_jobs_status ON heatmap_generation_jobs(status, scheduled_at);
+CREATE INDEX idx_generation_jobs_user ON heatmap_generation_jobs(user_id);
+CREATE INDEX idx_generation_jobs_priority ON heatmap_generation_jobs(priority DESC, scheduled_at);
+
+-- Admin overrides of how many jobs per user the processors pick up at once;
+-- users without a row get JOB_CONCURRENCY_PER_USER
+CREATE TABLE user_job_limits (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    max_concurrent_jobs INTEGER NOT NULL CHECK (max_concurrent_jobs >= 1),
+    updated_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Daily views of generated heatmaps and embeds, per theme, referring site
+-- and kind of client
+CREATE TABLE heatmap_access_daily (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id BLOB NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    access_date DATE NOT NULL,
+    referrer_host VARCHAR(255) NOT NULL DEFAULT '', -- empty without a Referer header
+    client VARCHAR(20) NOT NULL, -- browser, github, bot or other
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (theme_id, access_date, referrer_host, client)
+);
+
+CREATE INDEX idx_heatmap_access_daily_user_date ON heatmap_access_daily(user_id, access_date);
+
+-- Signed links to one theme's generated heatmap in one format, which work
+-- even when the profile is private
+CREATE TABLE heatmap_share_links (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    theme_id BLOB NOT NULL REFERENCES heatmap_themes(id) ON DELETE CASCADE,
+    format TEXT NOT NULL,
+    expires_at TEXT, -- never expires when NULL
+    max_views INTEGER, -- unlimited when NULL
+    view_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_heatmap_share_links_user_id ON heatmap_share_links(user_id);
+
+-- Users who opted in to a periodic summary email
+CREATE TABLE email_digests (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    frequency TEXT NOT NULL, -- weekly or monthly
+    last_sent_at TEXT NOT NULL, -- the next digest covers the time since
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- What public profile endpoints show of a platform account; accounts
+-- without a row are listed, without their sync time
+CREATE TABLE platform_account_visibility (
+    account_id BLOB PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    is_public BOOLEAN NOT NULL DEFAULT true, -- listed on the public profile
+    show_last_synced_at BOOLEAN NOT NULL DEFAULT false,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Names users gave their platform accounts, e.g. to tell two accounts on
+-- the same platform apart
+CREATE TABLE platform_account_labels (
+    account_id BLOB PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    label VARCHAR(100) NOT NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Organizations a platform account belongs to, replaced on every activity
+-- sync
+CREATE TABLE organization_memberships (
+    account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    login VARCHAR(255) NOT NULL,
+    avatar_url TEXT,
+    joined_at DATE, -- NULL when the platform does not tell
+    synced_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (account_id, login)
+);
+
+-- Scopes and redirect URI an admin set for an OAuth app; apps without a
+-- row use the defaults
+CREATE TABLE oauth_application_settings (
+    application_id BLOB PRIMARY KEY REFERENCES oauth_applications(id) ON DELETE CASCADE,
+    scopes TEXT, -- NULL: the scopes hgitmap needs
+    redirect_uri TEXT, -- NULL: the callback route under BASE_URL
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Disconnected platform accounts kept for a while so they can be restored;
+-- the cleanup job deletes the account once purge_after has passed
+CREATE TABLE platform_account_disconnections (
+    platform_account_id BLOB PRIMARY KEY REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    disconnected_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    purge_after TEXT NOT NULL
+);
+
+-- Platform API calls made for each account, per hour and kind of API, with
+-- the rate limit the platform last reported; the cleanup job removes old hours
+CREATE TABLE platform_api_usage_hourly (
+    platform_account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    period_start TEXT NOT NULL,
+    api VARCHAR(10) NOT NULL, -- rest or graphql
+    requests INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0, -- no response or a 5xx
+    rate_limited INTEGER NOT NULL DEFAULT 0,
+    rate_limit_remaining INTEGER,
+    rate_limit_limit INTEGER,
+    rate_limit_reset_at TEXT,
+    last_called_at TEXT NOT NULL,
+    PRIMARY KEY (platform_account_id, period_start, api)
+);
+
+-- Groups of users whose contributions are drawn as one heatmap, managed by
+-- their owner
+CREATE TABLE teams (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    owner_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_teams_owner_id ON teams(owner_id);
+
+CREATE TABLE team_members (
+    team_id BLOB NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE INDEX idx_team_members_user_id ON team_members(user_id);
+
+-- Users whose public activities appear in a user's feed
+CREATE TABLE follows (
+    follower_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    followee_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (follower_id, followee_id)
+);
+
+CREATE INDEX idx_follows_followee_id ON follows(followee_id);
+
+-- Progress of an activity import, polled while it runs in the background
+CREATE TABLE activity_imports (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform_account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    events_read INTEGER NOT NULL DEFAULT 0,
+    events_matched INTEGER NOT NULL DEFAULT 0,
+    activities_total INTEGER NOT NULL DEFAULT 0,
+    activities_imported INTEGER NOT NULL DEFAULT 0,
+    duplicates_skipped INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    started_at TEXT,
+    completed_at TEXT
+);
+
+CREATE INDEX idx_activity_imports_user_id ON activity_imports(user_id);
+
+-- Private notes on dates of a user's graph, like "conference" or "shipped v2.0"
+CREATE TABLE annotations (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    text VARCHAR(200) NOT NULL,
+    emoji VARCHAR(32),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_annotations_user_date ON annotations(user_id, date);
+
+-- Milestones a user reached, like their 1,000th contribution or a 100-day streak
+CREATE TABLE milestones (
+    id BLOB PRIMARY KEY DEFAULT (randomblob(16)),
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL, -- contributions, streak or organization
+    milestone_key VARCHAR(255) NOT NULL, -- The threshold, or the organization in lowercase
+    title VARCHAR(255) NOT NULL,
+    achieved_on DATE NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    UNIQUE (user_id, kind, milestone_key)
+);
+
+-- Achievements a user has earned, like night owl or polyglot
+CREATE TABLE achievements (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    achievement VARCHAR(32) NOT NULL, -- night_owl, early_bird, polyglot or marathoner
+    earned_on DATE NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (user_id, achievement)
+);
+
+-- Bytes of code per language in the repositories an account contributed to
+CREATE TABLE repository_languages (
+    account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL, -- owner/repo, as in contributions
+    language VARCHAR(100) NOT NULL,
+    bytes BIGINT NOT NULL,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    fetched_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (account_id, repository_name, language)
+);
+
+-- Individual commits of accounts with detailed sync on
+CREATE TABLE commit_details (
+    account_id BLOB NOT NULL REFERENCES git_platform_accounts(id) ON DELETE CASCADE,
+    repository_name VARCHAR(255) NOT NULL, -- owner/repo, as in contributions
+    sha VARCHAR(64) NOT NULL,
+    committed_at TEXT NOT NULL,
+    additions INTEGER, -- NULL when the platform does not tell
+    deletions INTEGER,
+    is_private_repo BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (account_id, repository_name, sha)
+);
+
+CREATE INDEX idx_commit_details_account_time ON commit_details(account_id, committed_at);
+
+-- co_authored is added by migrations/0030_commit_emails, which runs on every
+-- database
+
+-- Extra email addresses of a user, matched against commit authors and
+-- Co-authored-by trailers once confirmed. verified_at, token_hash and
+-- token_expires_at are added by migrations/0031_commit_email_verification,
+-- which runs on every database
+CREATE TABLE commit_emails (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL, -- Lowercase
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (user_id, email)
+);
+
+-- Per-user MQTT broker that daily totals and the streak are published to
+-- after each sync, for LED matrices and smart-home displays
+CREATE TABLE mqtt_publishers (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    broker_url VARCHAR(512) NOT NULL, -- mqtt:// or mqtts://host[:port]
+    username VARCHAR(255),
+    password TEXT, -- Encrypted
+    topic VARCHAR(200) NOT NULL, -- Prefix of the published topics
+    home_assistant_discovery BOOLEAN NOT NULL DEFAULT 0,
+    last_published_at TEXT,
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Places hgitmap notifies a user at, like a weekly summary posted to
+-- Mastodon or a webhook called when a sync fails
+-- events is added by migrations/0034_notification_channel_events, which runs
+-- on every database
+CREATE TABLE notification_channels (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL, -- mastodon, bluesky, email, webhook, slack, discord or mqtt
+    instance_url VARCHAR(512) NOT NULL, -- Mastodon instance, Bluesky PDS or webhook; empty for email and MQTT
+    account VARCHAR(255) NOT NULL, -- Handle posted as, or what the user recognizes it by
+    secret TEXT NOT NULL, -- Encrypted token, app password, signing secret or webhook URL; empty when unused
+    last_sent_at TEXT NOT NULL, -- The next summary covers the time since
+    last_error TEXT, -- Of the last attempt; NULL when it succeeded
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_notification_channels_user_id ON notification_channels(user_id);
+
+-- Add triggers for updated_at
+CREATE TRIGGER update_heatmap_themes_updated_at AFTER UPDATE ON heatmap_themes
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE heatmap_themes SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER update_heatmap_generation_settings_updated_at AFTER UPDATE ON heatmap_generation_settings
+    FOR EACH ROW WHEN NEW.updated_at IS OLD.updated_at
+    BEGIN
+        UPDATE heatmap_generation_settings SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+    END;
+
+-- Invalidate generated heatmaps when contributions change
+CREATE TRIGGER invalidate_heatmaps_on_new_contribution
+    AFTER INSERT ON contributions
+    FOR EACH ROW
+    BEGIN
+        -- Mark all generated heatmaps for this user as invalid
+        UPDATE generated_heatmaps
+        SET is_valid = false
+        WHERE user_id IN (
+            SELECT user_id
+            FROM git_platform_accounts
+            WHERE id = NEW.git_platform_account_id
+        );
+    END;
+
+CREATE TRIGGER invalidate_heatmaps_on_changed_contribution
+    AFTER UPDATE ON contributions
+    FOR EACH ROW
+    BEGIN
+        UPDATE generated_heatmaps
+        SET is_valid = false
+        WHERE user_id IN (
+            SELECT user_id
+            FROM git_platform_accounts
+            WHERE id = NEW.git_platform_account_id
+        );
+    END;
+
+-- Create default themes and generation settings for new users
+CREATE TRIGGER create_default_themes_on_user_creation
+    AFTER INSERT ON users
+    FOR EACH ROW
+    BEGIN
+        -- Create default light theme (matches current frontend UI exactly)
+        INSERT INTO heatmap_themes (
+            user_id,
+            name,
+            slug,
+            description,
+            is_default,
+            theme_mode,
+            color_scheme,
+            custom_colors,
+            background_color,
+            border_color,
+            text_color,
+            empty_cell_color,
+            cell_size,
+            cell_gap,
+            cell_border_radius,
+            cell_border_width,
+            cell_border_color,
+            padding_top,
+            padding_right,
+            padding_bottom,
+            padding_left,
+            day_label_width,
+            month_label_height,
+            title_height,
+            legend_height,
+            show_month_labels,
+            show_day_labels,
+            show_legend,
+            show_total_count,
+            show_username,
+            show_watermark,
+            font_family,
+            font_size,
+            output_formats
+        ) VALUES (
+            NEW.id,
+            'Default Light',
+            'default-light',
+            'Classic GitHub contribution graph style (light mode)',
+            true,
+            'light',
+            'custom',
+            '["#eff2f5", "#aceebb", "#4ac26b", "#2da44e", "#116329"]',
+            '#ffffff',
+            '#e1e4e8',
+            '#586069',
+            '#eff2f5',
+            10,
+            3,
+            2,
+            1,
+            '#e1e4e8',
+            20,
+            20,
+            10,
+            28,
+            28,
+            15,
+            30,
+            8,
+            true,
+            true,
+            true,
+            true,
+            false,
+            false,
+            'sans-serif',
+            10,
+            '["png", "svg"]'
+        );
+
+        -- Create default dark theme (matches current frontend dark mode)
+        INSERT INTO heatmap_themes (
+            user_id,
+            name,
+            slug,
+            description,
+            is_default,
+            theme_mode,
+            color_scheme,
+            custom_colors,
+            background_color,
+            border_color,
+            text_color,
+            empty_cell_color,
+            cell_size,
+            cell_gap,
+            cell_border_radius,
+            cell_border_width,
+            cell_border_color,
+            padding_top,
+            padding_right,
+            padding_bottom,
+            padding_left,
+            day_label_width,
+            month_label_height,
+            title_height,
+            legend_height,
+            show_month_labels,
+            show_day_labels,
+            show_legend,
+            show_total_count,
+            show_username,
+            show_watermark,
+            font_family,
+            font_size,
+            output_formats
+        ) VALUES (
+            NEW.id,
+            'Default Dark',
+            'default-dark',
+            'GitHub contribution graph for dark mode',
+            false,
+            'dark',
+            'custom',
+            '["#151b23", "#033a16", "#196c2e", "#2ea043", "#56d364"]',
+            '#0d1117',
+            '#30363d',
+            '#8b949e',
+            '#151b23',
+            10,
+            3,
+            2,
+            1,
+            '#30363d',
+            20,
+            20,
+            10,
+            28,
+            28,
+            15,
+            30,
+            8,
+            true,
+            true,
+            true,
+            true,
+            false,
+            false,
+            'sans-serif',
+            10,
+            '["png", "svg"]'
+        );
+
+        -- Create generation settings with defaults
+        INSERT INTO heatmap_generation_settings (user_id)
+        VALUES (NEW.id);
+
+    END;
+
+-- View for easily querying heatmap status per user
+CREATE VIEW user_heatmap_status AS
+SELECT
+    u.id as user_id,
+    u.username,
+    COUNT(DISTINCT ht.id) as total_themes,
+    COUNT(DISTINCT gh.id) as total_generated,
+    COUNT(DISTINCT CASE WHEN gh.is_valid = true THEN gh.id END) as valid_generated,
+    hgs.auto_generation_enabled,
+    hgs.update_interval_minutes,
+    hgs.next_scheduled_generation_at,
+    MAX(gh.generated_at) as last_generated_at
+FROM users u
+LEFT JOIN heatmap_themes ht ON u.id = ht.user_id
+LEFT JOIN generated_heatmaps gh ON u.id = gh.user_id
+LEFT JOIN heatmap_generation_settings hgs ON u.id = hgs.user_id
+GROUP BY u.id, u.username, hgs.auto_generation_enabled, hgs.update_interval_minutes, hgs.next_scheduled_generation_at;
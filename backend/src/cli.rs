@@ -0,0 +1,425 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use sea_orm::*;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::handlers::auth::create_user;
+use crate::models::{git_platform_account, heatmap_theme, platform_sync_job, user};
+use crate::services::backup::{self, RestoreOptions};
+use crate::services::demo_seed::{self, DemoOptions};
+use crate::services::heatmap_generator::{HeatmapGenerator, HEATMAP_BASE_DIR};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::job_limits::MANUAL_JOB_PRIORITY;
+use crate::services::key_rotation;
+use crate::services::platform_sync::PlatformSyncService;
+use crate::services::username_redirects;
+use crate::utils::{
+    auth::hash_password,
+    config::{self, Config, ConfigError},
+    db, validators,
+};
+
+/// hgitmap backend. Runs the server when no command is given.
+#[derive(Debug, Parser)]
+#[command(name = "hgitmap", version)]
+pub struct Cli {
+    /// Validate the configuration and storage directory, then exit
+    #[arg(long)]
+    pub check_config: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the HTTP server and background jobs (the default)
+    Serve,
+    /// Apply pending database migrations
+    Migrate,
+    /// Recovery and maintenance tasks
+    #[command(subcommand)]
+    Admin(AdminCommand),
+    /// Create a demo user with synthetic contributions, activities and themes
+    SeedDemo {
+        #[arg(long, default_value = "demo")]
+        username: String,
+        #[arg(long, default_value = "hgitmap-demo")]
+        password: String,
+        /// Years of history to generate
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=10))]
+        years: u32,
+        /// Replace the user if it already exists
+        #[arg(long)]
+        reset: bool,
+    },
+    /// Write the database to a .tar.gz archive; safe while the server runs
+    Backup {
+        /// Defaults to hgitmap-backup-<date>-<time>.tar.gz
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Load a backup archive into a new, empty instance and regenerate its heatmaps
+    Restore {
+        archive: PathBuf,
+        /// Restore even if the archive was made with a different ENCRYPTION_KEY
+        #[arg(long)]
+        ignore_key_mismatch: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// Create a user; the password is read from HGITMAP_PASSWORD or stdin
+    CreateUser {
+        username: String,
+        #[arg(long)]
+        email: Option<String>,
+        /// Make the user an admin
+        #[arg(long)]
+        admin: bool,
+    },
+    /// Sync a user's platform accounts now
+    Resync {
+        /// Username or user ID
+        #[arg(long)]
+        user: String,
+        /// Queue a sync of every year for the running server instead of
+        /// syncing the current year here
+        #[arg(long)]
+        all_years: bool,
+    },
+    /// Regenerate heatmap files for every theme
+    RegenerateHeatmaps {
+        /// Username or user ID; all users when omitted
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Re-encrypt platform tokens, OAuth app secrets, MQTT passwords and
+    /// channel secrets with the first ENCRYPTION_KEY entry, so older entries
+    /// can be removed
+    RotateEncryptionKey {
+        /// Only count what would be re-encrypted
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Load the configuration. The server (and `--check-config`) also needs to
+/// write heatmaps, so `check_storage` adds that to the problems reported.
+pub fn load_config(check_storage: bool) -> Result<Config, ConfigError> {
+    let loaded = Config::from_env();
+    if !check_storage {
+        return loaded;
+    }
+
+    let storage = config::check_writable("Heatmap storage directory", Path::new(HEATMAP_BASE_DIR));
+    match (loaded, storage) {
+        (loaded, Ok(())) => loaded,
+        (Ok(_), Err(problem)) => Err(ConfigError::new(vec![problem])),
+        (Err(mut e), Err(problem)) => {
+            e.problems.push(problem);
+            Err(e)
+        }
+    }
+}
+
+/// Run a command other than `serve`
+pub async fn run(command: Command, config: Config) -> Result<()> {
+    let db = db::establish_connection(&config.database_url)
+        .await
+        .context("Failed to connect to database")?;
+
+    match command {
+        Command::Serve => unreachable!("serve is handled by main"),
+        Command::Migrate => migrate(&db).await,
+        Command::Admin(AdminCommand::CreateUser { username, email, admin }) => {
+            create_admin_user(&db, &username, email, admin).await
+        }
+        Command::Admin(AdminCommand::Resync { user, all_years }) => {
+            resync(&db, &config, &user, all_years).await
+        }
+        Command::Admin(AdminCommand::RegenerateHeatmaps { user }) => {
+            regenerate_heatmaps(&db, user.as_deref()).await
+        }
+        Command::Admin(AdminCommand::RotateEncryptionKey { dry_run }) => {
+            rotate_encryption_key(&db, &config, dry_run).await
+        }
+        Command::SeedDemo { username, password, years, reset } => {
+            seed_demo(&db, DemoOptions { username, password, years, reset }).await
+        }
+        Command::Backup { output } => create_backup(&db, &config, output).await,
+        Command::Restore { archive, ignore_key_mismatch } => {
+            restore_backup(&db, &config, &archive, RestoreOptions { ignore_key_mismatch }).await
+        }
+    }
+}
+
+async fn migrate(db: &DatabaseConnection) -> Result<()> {
+    let applied = db::run_migrations(db).await.context("Migration failed")?;
+
+    if applied.is_empty() {
+        println!("✅ Database schema is up to date");
+    } else {
+        println!("✅ Applied {} migration(s): {}", applied.len(), applied.join(", "));
+    }
+    Ok(())
+}
+
+/// Find a user by ID or username
+async fn find_user(db: &DatabaseConnection, user: &str) -> Result<user::Model> {
+    let query = match Uuid::parse_str(user) {
+        Ok(id) => user::Entity::find_by_id(id),
+        Err(_) => user::Entity::find().filter(user::Column::Username.eq(user)),
+    };
+
+    query
+        .one(db)
+        .await?
+        .with_context(|| format!("User '{}' not found", user))
+}
+
+fn read_password() -> Result<String> {
+    if let Ok(password) = std::env::var("HGITMAP_PASSWORD") {
+        return Ok(password);
+    }
+
+    eprint!("Password: ");
+    std::io::stderr().flush()?;
+    let mut password = String::new();
+    std::io::stdin().lock().read_line(&mut password)?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn create_admin_user(
+    db: &DatabaseConnection,
+    username: &str,
+    email: Option<String>,
+    admin: bool,
+) -> Result<()> {
+    validators::validate_username(username)?;
+    if let Some(email) = &email {
+        validators::validate_email(email)?;
+    }
+
+    let taken = user::Entity::find()
+        .filter(user::Column::Username.eq(username))
+        .one(db)
+        .await?
+        .is_some();
+    if taken || username_redirects::is_reserved(db, username, None).await? {
+        bail!("Username '{}' already exists", username);
+    }
+
+    let password = read_password()?;
+    validators::validate_password(&password)?;
+    let password_hash = hash_password(&password).context("Failed to hash password")?;
+
+    let created = create_user(db, username, Some(password_hash), email).await?;
+    let created = if admin && !created.is_admin {
+        let mut active: user::ActiveModel = created.into();
+        active.is_admin = Set(true);
+        active.updated_at = Set(Utc::now());
+        active.update(db).await?
+    } else {
+        created
+    };
+
+    println!(
+        "✅ Created {} '{}' ({})",
+        if created.is_admin { "admin" } else { "user" },
+        created.username,
+        created.id
+    );
+    Ok(())
+}
+
+async fn resync(db: &DatabaseConnection, config: &Config, user: &str, all_years: bool) -> Result<()> {
+    let user = find_user(db, user).await?;
+    let settings = InstanceSettingsService::new(db.clone(), config).current().await;
+
+    if !all_years {
+        let sync_service = PlatformSyncService::new(db.clone(), config.clone())
+            .with_activities(settings.sync_activities);
+        let result = sync_service.sync_user_data(user.id).await?;
+
+        println!(
+            "✅ Synced {} platform(s) for '{}': {} contributions added, {} updated",
+            result.platforms_synced, user.username, result.contributions_added, result.contributions_updated
+        );
+        for error in &result.errors {
+            println!("   ⚠️  {}", error);
+        }
+        return Ok(());
+    }
+
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user.id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .all(db)
+        .await?;
+
+    let now = Utc::now();
+    for account in &accounts {
+        let job = platform_sync_job::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user.id),
+            platform_account_id: Set(account.id),
+            status: Set(platform_sync_job::SyncJobStatus::Pending),
+            sync_all_years: Set(true),
+            specific_year: Set(None),
+            sync_contributions: Set(account.sync_contributions),
+            sync_activities: Set(account.sync_contributions && settings.sync_activities),
+            sync_profile: Set(account.sync_profile),
+            scheduled_at: Set(now),
+            started_at: Set(None),
+            completed_at: Set(None),
+            error_message: Set(None),
+            retry_count: Set(0),
+            max_retries: Set(3),
+            contributions_synced: Set(None),
+            activities_synced: Set(None),
+            years_completed: Set(None),
+            total_years: Set(None),
+            is_manual: Set(true),
+            priority: Set(MANUAL_JOB_PRIORITY),
+            created_at: Set(now),
+        };
+        platform_sync_job::Entity::insert(job).exec(db).await?;
+    }
+
+    println!(
+        "✅ Queued full sync jobs for {} platform account(s) of '{}'; the server will process them",
+        accounts.len(),
+        user.username
+    );
+    Ok(())
+}
+
+async fn regenerate_heatmaps(db: &DatabaseConnection, user: Option<&str>) -> Result<()> {
+    let mut themes = heatmap_theme::Entity::find().order_by_asc(heatmap_theme::Column::UserId);
+    if let Some(user) = user {
+        let user = find_user(db, user).await?;
+        themes = themes.filter(heatmap_theme::Column::UserId.eq(user.id));
+    }
+
+    let generator = HeatmapGenerator::new(db.clone());
+    let (mut files, mut failed) = (0, 0);
+
+    for theme in themes.all(db).await? {
+        match generator.generate_for_theme(theme.user_id, &theme).await {
+            Ok(generated) => files += generated.len(),
+            Err(e) => {
+                failed += 1;
+                println!("   ⚠️  Theme '{}' of user {}: {:#}", theme.slug, theme.user_id, e);
+            }
+        }
+    }
+
+    println!("✅ Regenerated {} heatmap file(s), {} theme(s) failed", files, failed);
+    if failed > 0 {
+        bail!("{} theme(s) could not be regenerated", failed);
+    }
+    Ok(())
+}
+
+async fn rotate_encryption_key(db: &DatabaseConnection, config: &Config, dry_run: bool) -> Result<()> {
+    let report = key_rotation::run(db, &config.encryption_key, dry_run).await?;
+
+    for key in &report.keys {
+        println!(
+            "   🔑 Key {}{}: {} secret(s)",
+            key.key_id,
+            if key.current { " (current)" } else { "" },
+            key.secrets
+        );
+    }
+    println!(
+        "✅ {} {} secret(s) with key {}",
+        if dry_run { "Would re-encrypt" } else { "Re-encrypted" },
+        report.re_encrypted,
+        report.current_key_id
+    );
+    if report.undecryptable > 0 {
+        println!(
+            "   ⚠️  {} secret(s) could not be decrypted with any configured key and were left alone",
+            report.undecryptable
+        );
+    } else if !report.retirable_key_ids.is_empty() {
+        println!(
+            "   ℹ️  {} key(s) {} can be removed from ENCRYPTION_KEY{}",
+            report.retirable_key_ids.len(),
+            report.retirable_key_ids.join(", "),
+            if dry_run { " after a rotation" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+async fn seed_demo(db: &DatabaseConnection, options: DemoOptions) -> Result<()> {
+    validators::validate_username(&options.username)?;
+    validators::validate_password(&options.password)?;
+
+    let summary = demo_seed::seed(db, &options).await?;
+
+    println!(
+        "✅ Created demo user '{}' ({}) with {} contribution days, {} activities and {} themes ({} heatmap files)",
+        options.username,
+        summary.user_id,
+        summary.contributions,
+        summary.activities,
+        summary.themes,
+        summary.files_generated
+    );
+    if summary.is_admin {
+        println!("   ℹ️  This is the first user, so it is an admin");
+    }
+    println!("   Log in as '{}' with password '{}'", options.username, options.password);
+    Ok(())
+}
+
+async fn create_backup(db: &DatabaseConnection, config: &Config, output: Option<PathBuf>) -> Result<()> {
+    let output = output.unwrap_or_else(|| {
+        PathBuf::from(format!("hgitmap-backup-{}.tar.gz", Utc::now().format("%Y%m%d-%H%M%S")))
+    });
+
+    let manifest = backup::backup(db, &output, &config.encryption_key).await?;
+
+    let rows: u64 = manifest.tables.iter().map(|t| t.rows).sum();
+    println!(
+        "✅ Wrote {}: {} rows from {} tables, {} generated heatmap files listed",
+        output.display(),
+        rows,
+        manifest.tables.len(),
+        manifest.generated_files.len()
+    );
+    println!("   Keep ENCRYPTION_KEY with it; stored platform tokens only decrypt with the same key");
+    Ok(())
+}
+
+async fn restore_backup(
+    db: &DatabaseConnection,
+    config: &Config,
+    archive: &Path,
+    options: RestoreOptions,
+) -> Result<()> {
+    db::run_migrations(db).await.context("Migration failed")?;
+
+    let manifest = backup::restore(db, archive, &config.encryption_key, &options).await?;
+
+    let rows: u64 = manifest.tables.iter().map(|t| t.rows).sum();
+    println!(
+        "✅ Restored {} rows from a backup taken {} (hgitmap {}, {})",
+        rows,
+        manifest.created_at.format("%Y-%m-%d %H:%M UTC"),
+        manifest.app_version,
+        manifest.database
+    );
+    println!(
+        "   Regenerating heatmaps ({} files at backup time)",
+        manifest.generated_files.len()
+    );
+    regenerate_heatmaps(db, None).await
+}
@@ -0,0 +1,86 @@
+// GraphQL API served at /graphql alongside the REST endpoints.
+//
+// The schema is read-only and rooted at the authenticated user (`me`) or a
+// user looked up by name (`user`). Nested fields resolve lazily, so clients
+// only pay for the relations they select.
+
+mod types;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Result, Schema};
+use sea_orm::{sea_query::{Expr, Func}, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::models::user;
+use crate::services::profile_visibility;
+use types::UserNode;
+
+pub type AppSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Maximum nesting depth of a single query
+const MAX_QUERY_DEPTH: usize = 10;
+/// Maximum complexity (roughly the number of selected fields) of a single query
+const MAX_QUERY_COMPLEXITY: usize = 1000;
+
+/// The user making the request, taken from the JWT claims
+#[derive(Debug, Clone, Copy)]
+pub struct Viewer {
+    pub user_id: Uuid,
+}
+
+pub fn build_schema(db: DatabaseConnection) -> AppSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The authenticated user
+    async fn me(&self, ctx: &Context<'_>) -> Result<UserNode> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let viewer = ctx.data::<Viewer>()?;
+
+        let user = user::Entity::find_by_id(viewer.user_id)
+            .one(db)
+            .await
+            .map_err(db_error)?
+            .ok_or("User not found")?;
+
+        Ok(UserNode::new(user, true))
+    }
+
+    /// Look up a user by username (case-insensitive); private profiles of
+    /// other users are not found
+    async fn user(&self, ctx: &Context<'_>, username: String) -> Result<Option<UserNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let viewer = ctx.data::<Viewer>()?;
+
+        let user = user::Entity::find()
+            .filter(Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(username.to_lowercase()))
+            .one(db)
+            .await
+            .map_err(db_error)?;
+
+        let Some(user) = user else {
+            return Ok(None);
+        };
+
+        // Private profiles are only visible to their owner
+        let is_viewer = user.id == viewer.user_id;
+        if !is_viewer && profile_visibility::find(db, user.id).await.map_err(db_error)? == profile_visibility::PRIVATE {
+            return Ok(None);
+        }
+
+        Ok(Some(UserNode::new(user, is_viewer)))
+    }
+}
+
+/// Log the underlying database error and return a generic one to the client
+fn db_error(e: DbErr) -> async_graphql::Error {
+    log::error!("Database error: {}", e);
+    async_graphql::Error::new("Database error")
+}
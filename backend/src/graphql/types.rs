@@ -0,0 +1,680 @@
+use async_graphql::{ComplexObject, Context, Json, Object, Result, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::{ActiveEnum, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::{db_error, Viewer};
+use crate::handlers::activities::strip_excluded_repositories;
+use crate::models::{activity, contribution, generated_heatmap, git_platform_account, heatmap_theme, user, user_setting};
+use crate::services::platform_visibility;
+use crate::utils::repo_exclusion;
+
+/// Default and maximum number of activities returned by a single field
+const DEFAULT_ACTIVITY_LIMIT: u64 = 50;
+const MAX_ACTIVITY_LIMIT: u64 = 200;
+
+/// Privacy settings of the user whose data is being resolved
+#[derive(Debug, Clone)]
+struct Privacy {
+    show_private_contributions: bool,
+    hide_private_repo_names: bool,
+    excluded_repositories: Vec<String>,
+}
+
+async fn load_privacy(db: &DatabaseConnection, user_id: Uuid) -> Result<Privacy> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Privacy {
+        show_private_contributions: settings.as_ref().map(|s| s.show_private_contributions).unwrap_or(true),
+        hide_private_repo_names: settings.as_ref().map(|s| s.hide_private_repo_names).unwrap_or(false),
+        excluded_repositories: settings.map(|s| s.excluded_repositories).unwrap_or_default().into(),
+    })
+}
+
+/// The user's active accounts. `public_only` leaves out the ones they hid
+/// from their public profile, as the REST listings do for other users.
+async fn active_accounts(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    platform: Option<&str>,
+    public_only: bool,
+) -> Result<Vec<git_platform_account::Model>> {
+    let mut query = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true));
+
+    if public_only {
+        let hidden = platform_visibility::hidden_accounts(db, user_id).await.map_err(db_error)?;
+        query = query.filter(git_platform_account::Column::Id.is_not_in(hidden));
+    }
+
+    if let Some(platform) = platform {
+        let platform = git_platform_account::GitPlatform::try_from_value(&platform.to_lowercase())
+            .map_err(|_| "Invalid platform. Must be github, gitea or gitlab")?;
+        query = query.filter(git_platform_account::Column::PlatformType.eq(platform));
+    }
+
+    query.all(db).await.map_err(db_error)
+}
+
+async fn query_contributions(
+    db: &DatabaseConnection,
+    account_ids: Vec<Uuid>,
+    privacy: &Privacy,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<ContributionNode>> {
+    if account_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids));
+    if let Some(from) = from {
+        query = query.filter(contribution::Column::ContributionDate.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(contribution::Column::ContributionDate.lte(to));
+    }
+    if !privacy.show_private_contributions {
+        query = query.filter(contribution::Column::IsPrivateRepo.eq(false));
+    }
+    query = query.filter(repo_exclusion::not_excluded(
+        contribution::Column::RepositoryName,
+        &privacy.excluded_repositories,
+    ));
+
+    let contributions = query
+        .order_by_asc(contribution::Column::ContributionDate)
+        .all(db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(contributions
+        .into_iter()
+        .map(|c| ContributionNode {
+            date: c.contribution_date,
+            count: c.count,
+            repository_name: if c.is_private_repo && privacy.hide_private_repo_names {
+                None
+            } else {
+                c.repository_name
+            },
+            is_private_repo: c.is_private_repo,
+            platform_account_id: c.git_platform_account_id,
+        })
+        .collect())
+}
+
+/// Sum per-repository contributions into one entry per day
+fn contribution_days(contributions: &[ContributionNode]) -> Vec<ContributionDay> {
+    let mut by_date: BTreeMap<NaiveDate, i32> = BTreeMap::new();
+    for c in contributions {
+        *by_date.entry(c.date).or_insert(0) += c.count;
+    }
+
+    by_date
+        .into_iter()
+        .map(|(date, count)| ContributionDay { date, count })
+        .collect()
+}
+
+async fn query_activities(
+    db: &DatabaseConnection,
+    account_ids: Vec<Uuid>,
+    privacy: &Privacy,
+    filter: ActivityFilter,
+) -> Result<Vec<ActivityNode>> {
+    if account_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids));
+    if let Some(from) = filter.from {
+        query = query.filter(activity::Column::ActivityDate.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(activity::Column::ActivityDate.lte(to));
+    }
+    if let Some(types) = filter.activity_types {
+        let types = types
+            .iter()
+            .map(|t| {
+                activity::ActivityType::try_from_value(&t.to_lowercase())
+                    .map_err(|_| format!("Invalid activity type: {}", t))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        query = query.filter(activity::Column::ActivityType.is_in(types));
+    }
+    if !privacy.show_private_contributions {
+        query = query.filter(activity::Column::IsPrivateRepo.eq(false));
+    }
+    query = query.filter(repo_exclusion::not_excluded(
+        activity::Column::RepositoryName,
+        &privacy.excluded_repositories,
+    ));
+
+    let limit = filter.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).min(MAX_ACTIVITY_LIMIT);
+    let activities = query
+        .order_by_desc(activity::Column::ActivityDate)
+        .order_by_desc(activity::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(activities
+        .into_iter()
+        .filter_map(|mut a| {
+            strip_excluded_repositories(&mut a, &privacy.excluded_repositories)
+                .then(|| ActivityNode::new(a, privacy))
+        })
+        .collect())
+}
+
+/// Arguments shared by every `activities` field
+#[derive(Debug, Default)]
+struct ActivityFilter {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    activity_types: Option<Vec<String>>,
+    limit: Option<u64>,
+}
+
+fn viewer_only(is_viewer: bool) -> Result<()> {
+    if is_viewer {
+        Ok(())
+    } else {
+        Err("Only available for the authenticated user".into())
+    }
+}
+
+pub struct UserNode {
+    user: user::Model,
+    is_viewer: bool,
+}
+
+impl UserNode {
+    pub fn new(user: user::Model, is_viewer: bool) -> Self {
+        Self { user, is_viewer }
+    }
+}
+
+#[Object(name = "User")]
+impl UserNode {
+    async fn id(&self) -> Uuid {
+        self.user.id
+    }
+
+    async fn username(&self) -> &str {
+        &self.user.username
+    }
+
+    /// Only visible to the user themselves
+    async fn email(&self) -> Option<&str> {
+        if self.is_viewer {
+            self.user.email.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Only visible to the user themselves
+    async fn is_admin(&self) -> Option<bool> {
+        self.is_viewer.then_some(self.user.is_admin)
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.user.created_at
+    }
+
+    /// Active platform accounts, optionally limited to one platform. Other
+    /// users do not see accounts hidden from the public profile.
+    async fn platform_accounts(
+        &self,
+        ctx: &Context<'_>,
+        platform: Option<String>,
+    ) -> Result<Vec<PlatformAccountNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let privacy = load_privacy(db, self.user.id).await?;
+        let accounts = active_accounts(db, self.user.id, platform.as_deref(), !self.is_viewer).await?;
+        let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+        let visibility = platform_visibility::for_accounts(db, &account_ids)
+            .await
+            .map_err(db_error)?;
+
+        Ok(accounts
+            .into_iter()
+            .map(|account| PlatformAccountNode {
+                show_last_synced_at: self.is_viewer
+                    || visibility.get(&account.id).is_some_and(|v| v.show_last_synced_at),
+                account,
+                privacy: privacy.clone(),
+            })
+            .collect())
+    }
+
+    /// Per-repository contributions across all active accounts
+    async fn contributions(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        platform: Option<String>,
+    ) -> Result<Vec<ContributionNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let privacy = load_privacy(db, self.user.id).await?;
+        let accounts = active_accounts(db, self.user.id, platform.as_deref(), false).await?;
+        let ids = accounts.into_iter().map(|a| a.id).collect();
+
+        query_contributions(db, ids, &privacy, from, to).await
+    }
+
+    /// Daily contribution totals across all active accounts
+    async fn contribution_calendar(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        platform: Option<String>,
+    ) -> Result<Vec<ContributionDay>> {
+        let contributions = self.contributions(ctx, from, to, platform).await?;
+        Ok(contribution_days(&contributions))
+    }
+
+    /// Activities across all active accounts, newest first. Other users do
+    /// not see those of accounts hidden from the public profile.
+    async fn activities(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        activity_types: Option<Vec<String>>,
+        limit: Option<u64>,
+    ) -> Result<Vec<ActivityNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let privacy = load_privacy(db, self.user.id).await?;
+        let accounts = active_accounts(db, self.user.id, None, !self.is_viewer).await?;
+        let ids = accounts.into_iter().map(|a| a.id).collect();
+
+        query_activities(db, ids, &privacy, ActivityFilter { from, to, activity_types, limit }).await
+    }
+
+    /// Heatmap themes; only available for the authenticated user
+    async fn themes(&self, ctx: &Context<'_>) -> Result<Vec<ThemeNode>> {
+        viewer_only(self.is_viewer)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let themes = heatmap_theme::Entity::find()
+            .filter(heatmap_theme::Column::UserId.eq(self.user.id))
+            .order_by_desc(heatmap_theme::Column::IsDefault)
+            .order_by_asc(heatmap_theme::Column::Name)
+            .all(db)
+            .await
+            .map_err(db_error)?;
+
+        Ok(themes.into_iter().map(ThemeNode).collect())
+    }
+
+    /// Generated heatmap files; only available for the authenticated user
+    async fn generated_heatmaps(&self, ctx: &Context<'_>) -> Result<Vec<GeneratedHeatmapNode>> {
+        viewer_only(self.is_viewer)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let heatmaps = generated_heatmap::Entity::find()
+            .filter(generated_heatmap::Column::UserId.eq(self.user.id))
+            .order_by_desc(generated_heatmap::Column::GeneratedAt)
+            .all(db)
+            .await
+            .map_err(db_error)?;
+
+        Ok(heatmaps.into_iter().map(GeneratedHeatmapNode).collect())
+    }
+}
+
+pub struct PlatformAccountNode {
+    account: git_platform_account::Model,
+    privacy: Privacy,
+    /// The owner is viewing, or made the sync time public
+    show_last_synced_at: bool,
+}
+
+#[Object(name = "PlatformAccount")]
+impl PlatformAccountNode {
+    async fn id(&self) -> Uuid {
+        self.account.id
+    }
+
+    /// github, gitea or gitlab
+    async fn platform(&self) -> String {
+        self.account.platform_type.to_value()
+    }
+
+    async fn platform_username(&self) -> &str {
+        &self.account.platform_username
+    }
+
+    async fn platform_url(&self) -> Option<&str> {
+        self.account.platform_url.as_deref()
+    }
+
+    async fn display_name(&self) -> Option<&str> {
+        self.account.display_name.as_deref()
+    }
+
+    async fn avatar_url(&self) -> Option<&str> {
+        self.account.avatar_url.as_deref()
+    }
+
+    async fn profile_url(&self) -> Option<&str> {
+        self.account.profile_url.as_deref()
+    }
+
+    async fn bio(&self) -> Option<&str> {
+        self.account.bio.as_deref()
+    }
+
+    async fn followers_count(&self) -> Option<i32> {
+        self.account.followers_count
+    }
+
+    async fn following_count(&self) -> Option<i32> {
+        self.account.following_count
+    }
+
+    /// Only visible to other users when the owner shows it
+    async fn last_synced_at(&self) -> Option<DateTime<Utc>> {
+        self.account.last_synced_at.filter(|_| self.show_last_synced_at)
+    }
+
+    /// Per-repository contributions of this account
+    async fn contributions(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<ContributionNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        query_contributions(db, vec![self.account.id], &self.privacy, from, to).await
+    }
+
+    /// Daily contribution totals of this account
+    async fn contribution_calendar(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<ContributionDay>> {
+        let contributions = self.contributions(ctx, from, to).await?;
+        Ok(contribution_days(&contributions))
+    }
+
+    /// Activities of this account, newest first
+    async fn activities(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        activity_types: Option<Vec<String>>,
+        limit: Option<u64>,
+    ) -> Result<Vec<ActivityNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let filter = ActivityFilter { from, to, activity_types, limit };
+        query_activities(db, vec![self.account.id], &self.privacy, filter).await
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Contribution")]
+pub struct ContributionNode {
+    date: NaiveDate,
+    count: i32,
+    /// Hidden for private repositories when the user hides private repo names
+    repository_name: Option<String>,
+    is_private_repo: bool,
+    platform_account_id: Uuid,
+}
+
+#[derive(SimpleObject)]
+pub struct ContributionDay {
+    date: NaiveDate,
+    count: i32,
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Activity", complex)]
+pub struct ActivityNode {
+    id: Uuid,
+    /// commit, pull_request, issue, review, ...
+    activity_type: String,
+    date: NaiveDate,
+    repository_name: Option<String>,
+    repository_url: Option<String>,
+    is_private_repo: bool,
+    count: i32,
+    primary_language: Option<String>,
+    organization_name: Option<String>,
+    organization_avatar_url: Option<String>,
+    metadata: Json<serde_json::Value>,
+    #[graphql(skip)]
+    platform_account_id: Uuid,
+}
+
+impl ActivityNode {
+    fn new(a: activity::Model, privacy: &Privacy) -> Self {
+        let hide_name = a.is_private_repo && privacy.hide_private_repo_names;
+        let mut metadata = a.metadata;
+        if hide_name {
+            if let Some(repos) = metadata.get_mut("repositories").and_then(|r| r.as_array_mut()) {
+                for repo in repos.iter_mut().filter_map(|r| r.as_object_mut()) {
+                    repo.insert("name".to_string(), serde_json::json!("Private Repository"));
+                }
+            }
+        }
+
+        Self {
+            id: a.id,
+            activity_type: a.activity_type.to_value(),
+            date: a.activity_date,
+            repository_name: if hide_name { None } else { a.repository_name },
+            repository_url: if hide_name { None } else { a.repository_url },
+            is_private_repo: a.is_private_repo,
+            count: a.count,
+            primary_language: a.primary_language,
+            organization_name: a.organization_name,
+            organization_avatar_url: a.organization_avatar_url,
+            metadata: Json(metadata),
+            platform_account_id: a.git_platform_account_id,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ActivityNode {
+    /// The platform account this activity was synced from
+    async fn platform_account(&self, ctx: &Context<'_>) -> Result<Option<PlatformAccountNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let account = git_platform_account::Entity::find_by_id(self.platform_account_id)
+            .one(db)
+            .await
+            .map_err(db_error)?;
+
+        match account {
+            Some(account) => {
+                let privacy = load_privacy(db, account.user_id).await?;
+                let show_last_synced_at = account.user_id == ctx.data::<Viewer>()?.user_id
+                    || platform_visibility::find(db, account.id)
+                        .await
+                        .map_err(db_error)?
+                        .show_last_synced_at;
+                Ok(Some(PlatformAccountNode { account, privacy, show_last_synced_at }))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+pub struct ThemeNode(heatmap_theme::Model);
+
+#[Object(name = "Theme")]
+impl ThemeNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn is_default(&self) -> bool {
+        self.0.is_default
+    }
+
+    /// light or dark
+    async fn theme_mode(&self) -> String {
+        self.0.theme_mode.to_value()
+    }
+
+    async fn color_scheme(&self) -> String {
+        self.0.color_scheme.to_value()
+    }
+
+    async fn custom_colors(&self) -> Option<Json<serde_json::Value>> {
+        self.0.custom_colors.clone().map(Json)
+    }
+
+    async fn background_color(&self) -> &str {
+        &self.0.background_color
+    }
+
+    async fn border_color(&self) -> &str {
+        &self.0.border_color
+    }
+
+    async fn text_color(&self) -> &str {
+        &self.0.text_color
+    }
+
+    async fn empty_cell_color(&self) -> &str {
+        &self.0.empty_cell_color
+    }
+
+    async fn cell_size(&self) -> i32 {
+        self.0.cell_size
+    }
+
+    async fn cell_gap(&self) -> i32 {
+        self.0.cell_gap
+    }
+
+    async fn cell_border_radius(&self) -> i32 {
+        self.0.cell_border_radius
+    }
+
+    async fn font_family(&self) -> &str {
+        &self.0.font_family
+    }
+
+    async fn font_size(&self) -> i32 {
+        self.0.font_size
+    }
+
+    async fn show_legend(&self) -> bool {
+        self.0.show_legend
+    }
+
+    async fn output_formats(&self) -> Vec<String> {
+        self.0.output_formats.iter().map(|f| f.to_value()).collect()
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Heatmaps generated with this theme
+    async fn generated_heatmaps(&self, ctx: &Context<'_>) -> Result<Vec<GeneratedHeatmapNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let heatmaps = generated_heatmap::Entity::find()
+            .filter(generated_heatmap::Column::ThemeId.eq(self.0.id))
+            .order_by_desc(generated_heatmap::Column::GeneratedAt)
+            .all(db)
+            .await
+            .map_err(db_error)?;
+
+        Ok(heatmaps.into_iter().map(GeneratedHeatmapNode).collect())
+    }
+}
+
+pub struct GeneratedHeatmapNode(generated_heatmap::Model);
+
+#[Object(name = "GeneratedHeatmap")]
+impl GeneratedHeatmapNode {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    /// svg, png, jpeg or webp
+    async fn format(&self) -> String {
+        self.0.format.to_value()
+    }
+
+    async fn file_path(&self) -> &str {
+        &self.0.file_path
+    }
+
+    async fn file_size_bytes(&self) -> Option<i64> {
+        self.0.file_size_bytes
+    }
+
+    async fn contribution_count(&self) -> i32 {
+        self.0.contribution_count
+    }
+
+    async fn date_range_start(&self) -> NaiveDate {
+        self.0.date_range_start
+    }
+
+    async fn date_range_end(&self) -> NaiveDate {
+        self.0.date_range_end
+    }
+
+    async fn generated_at(&self) -> DateTime<Utc> {
+        self.0.generated_at
+    }
+
+    async fn is_valid(&self) -> bool {
+        self.0.is_valid
+    }
+
+    async fn access_count(&self) -> i32 {
+        self.0.access_count
+    }
+
+    /// The theme this heatmap was rendered with
+    async fn theme(&self, ctx: &Context<'_>) -> Result<Option<ThemeNode>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let theme = heatmap_theme::Entity::find_by_id(self.0.theme_id)
+            .one(db)
+            .await
+            .map_err(db_error)?;
+
+        Ok(theme.map(ThemeNode))
+    }
+}
@@ -0,0 +1,507 @@
+use actix_web::{web, HttpResponse};
+use chrono::{Duration, Utc};
+use sea_orm::{*, sea_query::{Expr, Func}};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{email_change, generated_heatmap, user, username_redirect};
+use crate::services::account_deletion::{self, DeletionReceipt};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::mailer::Mailer;
+use crate::services::sessions;
+use crate::services::username_redirects;
+use crate::utils::api_token::hash_token;
+use crate::utils::auth::{generate_refresh_token, hash_password, verify_password};
+use crate::utils::config::Config;
+use crate::utils::validators;
+
+/// How long the link sent to a new email address stays valid
+const EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+/// How recently users without a password must have signed in to change
+/// their credentials or delete their account
+const RECENT_SIGN_IN_MINUTES: i64 = 10;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    /// Required when the account already has a password
+    pub current_password: Option<String>,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangeEmailRequest {
+    pub new_email: String,
+    /// Required when the account has a password
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConfirmEmailQuery {
+    pub token: String,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+async fn find_user(db: &DatabaseConnection, user_claims: &crate::middleware::auth::Claims) -> Result<user::Model, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    user::Entity::find_by_id(user_id)
+        .one(db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))
+}
+
+/// Whether the request comes from the account owner rather than just
+/// someone holding an access token: `password` matches the user's password,
+/// or for users without one, they signed in to this session within
+/// `RECENT_SIGN_IN_MINUTES`
+async fn reauthenticated(
+    db: &DatabaseConnection,
+    user: &user::Model,
+    user_claims: &crate::middleware::auth::Claims,
+    password: Option<&str>,
+) -> Result<bool, actix_web::Error> {
+    if let Some(hash) = &user.password_hash {
+        return Ok(password.is_some_and(|password| verify_password(password, hash).unwrap_or(false)));
+    }
+
+    let Some(session_id) = user_claims.sid.as_deref().and_then(|sid| Uuid::parse_str(sid).ok()) else {
+        return Ok(false);
+    };
+    sessions::signed_in_within(db, session_id, user.id, RECENT_SIGN_IN_MINUTES)
+        .await
+        .map_err(db_error)
+}
+
+/// The 403 for a request that did not re-authenticate
+fn not_reauthenticated(user: &user::Model) -> HttpResponse {
+    let error = if user.password_hash.is_some() {
+        "Incorrect password".to_string()
+    } else {
+        format!("Sign in again and retry within {} minutes to confirm this", RECENT_SIGN_IN_MINUTES)
+    };
+    HttpResponse::Forbidden().json(serde_json::json!({ "error": error }))
+}
+
+/// PUT /api/settings/password
+/// Change (or set a first) password. Every other session is signed out.
+#[utoipa::path(
+    put,
+    path = "/settings/password",
+    tag = "settings",
+    request_body = ChangePasswordRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 400, description = "New password too short or too long"),
+        (status = 403, description = "Current password is wrong, or an account without one did not sign in recently")
+    )
+)]
+pub async fn change_password(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(db.as_ref(), &user_claims).await?;
+
+    if !reauthenticated(db.as_ref(), &user, &user_claims, body.current_password.as_deref()).await? {
+        log::warn!("❌ Password change for '{}' not re-authenticated", user.username);
+        return Ok(not_reauthenticated(&user));
+    }
+
+    if let Err(e) = validators::validate_password(&body.new_password) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e.to_string()
+        })));
+    }
+
+    let password_hash = hash_password(&body.new_password).map_err(|e| {
+        log::error!("❌ Failed to hash password: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to hash password")
+    })?;
+
+    let user_id = user.id;
+    let username = user.username.clone();
+    let mut active: user::ActiveModel = user.into();
+    active.password_hash = Set(Some(password_hash));
+    active.updated_at = Set(Utc::now());
+    active.update(db.as_ref()).await.map_err(db_error)?;
+
+    let current_session = user_claims.sid.as_deref().and_then(|sid| Uuid::parse_str(sid).ok());
+    let revoked = sessions::revoke_user_sessions(db.as_ref(), user_id, current_session)
+        .await
+        .map_err(db_error)?;
+
+    log::info!("🔑 Password changed for '{}', signed out {} other sessions", username, revoked);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/settings/email
+/// Start an email change; a confirmation link is sent to the new address
+#[utoipa::path(
+    post,
+    path = "/settings/email",
+    tag = "settings",
+    request_body = ChangeEmailRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Confirmation email sent"),
+        (status = 400, description = "Invalid email address"),
+        (status = 403, description = "Password is wrong, or an account without one did not sign in recently"),
+        (status = 503, description = "Email is not configured on this instance")
+    )
+)]
+pub async fn request_email_change(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<ChangeEmailRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(db.as_ref(), &user_claims).await?;
+
+    if !reauthenticated(db.as_ref(), &user, &user_claims, body.password.as_deref()).await? {
+        log::warn!("❌ Email change for '{}' not re-authenticated", user.username);
+        return Ok(not_reauthenticated(&user));
+    }
+
+    let new_email = body.new_email.trim().to_string();
+    if let Err(e) = validators::validate_email(&new_email) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e.to_string()
+        })));
+    }
+
+    if !mailer.is_enabled() {
+        return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "error": "Email is not configured on this instance"
+        })));
+    }
+
+    // The token doubles as a random secret; only its hash is stored
+    let token = generate_refresh_token();
+
+    // Replace any earlier pending change
+    email_change::Entity::delete_many()
+        .filter(email_change::Column::UserId.eq(user.id))
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    email_change::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user.id),
+        new_email: Set(new_email.clone()),
+        token_hash: Set(hash_token(&token)),
+        created_at: Set(Utc::now()),
+        expires_at: Set(Utc::now() + Duration::hours(EMAIL_CHANGE_TTL_HOURS)),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    let link = format!(
+        "{}/auth/email/confirm?token={}",
+        config.base_url,
+        urlencoding::encode(&token)
+    );
+    let message = format!(
+        "Hi {},\n\nOpen this link to use {} as the email address of your hgitmap account:\n\n{}\n\nThe link expires in {} hours. If you did not ask for this, ignore this email.\n",
+        user.username, new_email, link, EMAIL_CHANGE_TTL_HOURS
+    );
+
+    mailer
+        .send(&new_email, "Confirm your new email address", message)
+        .await
+        .map_err(|e| {
+            log::error!("❌ Failed to send confirmation email: {}", e);
+            actix_web::error::ErrorBadGateway("Failed to send confirmation email")
+        })?;
+
+    log::info!("📧 Email change requested for '{}'", user.username);
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "message": format!("A confirmation link was sent to {}", new_email)
+    })))
+}
+
+/// GET /api/auth/email/confirm
+/// Apply an email change from the link in the confirmation email
+#[utoipa::path(
+    get,
+    path = "/auth/email/confirm",
+    tag = "auth",
+    params(ConfirmEmailQuery),
+    responses((status = 302, description = "Redirect to the frontend settings page"))
+)]
+pub async fn confirm_email_change(
+    db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
+    query: web::Query<ConfirmEmailQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let frontend_url = instance.current().await.frontend_url;
+    let redirect = |result: &str| {
+        HttpResponse::Found()
+            .append_header(("Location", format!("{}/settings?email={}", frontend_url, result)))
+            .finish()
+    };
+
+    let Some(change) = email_change::Entity::find()
+        .filter(email_change::Column::TokenHash.eq(hash_token(&query.token)))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(redirect("invalid"));
+    };
+
+    email_change::Entity::delete_by_id(change.id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if change.expires_at < Utc::now() {
+        return Ok(redirect("expired"));
+    }
+
+    user::ActiveModel {
+        id: Set(change.user_id),
+        email: Set(Some(change.new_email)),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    log::info!("✅ Email address confirmed for user {}", change.user_id);
+
+    Ok(redirect("confirmed"))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangeUsernameRequest {
+    pub username: String,
+    /// Required when the account has a password
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChangeUsernameResponse {
+    pub username: String,
+    pub previous_username: String,
+    /// Until when profile and embed URLs with the previous username redirect;
+    /// null when only the capitalization changed
+    pub redirect_expires_at: Option<String>,
+}
+
+/// PUT /api/settings/username
+/// Rename the account. Old profile and embed URLs redirect for a grace period.
+#[utoipa::path(
+    put,
+    path = "/settings/username",
+    tag = "settings",
+    request_body = ChangeUsernameRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Username changed", body = ChangeUsernameResponse),
+        (status = 400, description = "Invalid or unavailable username"),
+        (status = 403, description = "Password is wrong, or an account without one did not sign in recently"),
+        (status = 429, description = "Username was changed too recently")
+    )
+)]
+pub async fn change_username(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<ChangeUsernameRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = find_user(db.as_ref(), &user_claims).await?;
+
+    if !reauthenticated(db.as_ref(), &user, &user_claims, body.password.as_deref()).await? {
+        log::warn!("❌ Username change for '{}' not re-authenticated", user.username);
+        return Ok(not_reauthenticated(&user));
+    }
+
+    let new_username = body.username.trim().to_string();
+    if let Err(e) = validators::validate_username(&new_username) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e.to_string()
+        })));
+    }
+    if new_username == user.username {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "This is already your username"
+        })));
+    }
+
+    if let Some(changed_at) = user.username_changed_at {
+        let next_change = changed_at + Duration::days(instance.current().await.username_change_cooldown_days);
+        if next_change > Utc::now() {
+            return Ok(HttpResponse::TooManyRequests().json(serde_json::json!({
+                "error": format!("Your username can be changed again on {}", next_change.format("%Y-%m-%d")),
+                "next_change_at": next_change.to_rfc3339()
+            })));
+        }
+    }
+
+    let taken = user::Entity::find()
+        .filter(Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(new_username.to_lowercase()))
+        .filter(user::Column::Id.ne(user.id))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .is_some();
+    let reserved = username_redirects::is_reserved(db.as_ref(), &new_username, Some(user.id))
+        .await
+        .map_err(db_error)?;
+    if taken || reserved {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Username already exists"
+        })));
+    }
+
+    let now = Utc::now();
+    let user_id = user.id;
+    let previous_username = user.username.clone();
+    let case_only = previous_username.to_lowercase() == new_username.to_lowercase();
+    let redirect_expires_at = (!case_only).then(|| now + Duration::days(config.username_redirect_days));
+
+    let txn = db.begin().await.map_err(db_error)?;
+
+    let mut active: user::ActiveModel = user.into();
+    active.username = Set(new_username.clone());
+    active.username_changed_at = Set(Some(now));
+    active.updated_at = Set(now);
+    active.update(&txn).await.map_err(db_error)?;
+
+    // Taking back one of your own old names ends its redirect
+    username_redirect::Entity::delete_many()
+        .filter(
+            username_redirect::Column::OldUsername
+                .is_in([new_username.to_lowercase(), previous_username.to_lowercase()]),
+        )
+        .exec(&txn)
+        .await
+        .map_err(db_error)?;
+
+    if let Some(expires_at) = redirect_expires_at {
+        username_redirect::ActiveModel {
+            old_username: Set(previous_username.to_lowercase()),
+            user_id: Set(user_id),
+            created_at: Set(now),
+            expires_at: Set(expires_at),
+        }
+        .insert(&txn)
+        .await
+        .map_err(db_error)?;
+    }
+
+    // Generated heatmaps show the username; heatmap files are stored by user
+    // ID, so nothing on disk has to move
+    generated_heatmap::Entity::update_many()
+        .filter(generated_heatmap::Column::UserId.eq(user_id))
+        .col_expr(generated_heatmap::Column::IsValid, Expr::value(false))
+        .exec(&txn)
+        .await
+        .map_err(db_error)?;
+
+    txn.commit().await.map_err(db_error)?;
+
+    log::info!("✏️  Username changed from '{}' to '{}'", previous_username, new_username);
+
+    Ok(HttpResponse::Ok().json(ChangeUsernameResponse {
+        username: new_username,
+        previous_username,
+        redirect_expires_at: redirect_expires_at.map(|d| d.to_rfc3339()),
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    /// Current password; required for accounts that have one
+    pub password: Option<String>,
+    /// The account's username; required for accounts without a password
+    pub confirm_username: Option<String>,
+}
+
+/// DELETE /api/settings/account
+/// Permanently delete the account and all of its data
+#[utoipa::path(
+    delete,
+    path = "/settings/account",
+    tag = "settings",
+    request_body = DeleteAccountRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Account deleted", body = DeletionReceipt),
+        (status = 400, description = "The last admin cannot be deleted"),
+        (status = 403, description = "Password or username confirmation is wrong, or an account without a password did not sign in recently")
+    )
+)]
+pub async fn delete_account(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<DeleteAccountRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let user = user::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    // Re-authenticate: a stolen access token alone must not be enough
+    if !reauthenticated(db.as_ref(), &user, &user_claims, body.password.as_deref()).await? {
+        log::warn!("❌ Account deletion for '{}' not re-authenticated", user.username);
+        return Ok(not_reauthenticated(&user));
+    }
+    if user.password_hash.is_none() && body.confirm_username.as_deref() != Some(user.username.as_str()) {
+        log::warn!("❌ Account deletion for '{}' not confirmed", user.username);
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Username confirmation does not match"
+        })));
+    }
+
+    if user.is_admin {
+        let other_admins = user::Entity::find()
+            .filter(user::Column::IsAdmin.eq(true))
+            .filter(user::Column::Id.ne(user_id))
+            .count(db.as_ref())
+            .await
+            .map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        if other_admins == 0 {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "You are the only admin. Make another user an admin before deleting your account."
+            })));
+        }
+    }
+
+    let receipt = account_deletion::delete_account(db.as_ref(), &config.encryption_key, user)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(receipt))
+}
@@ -0,0 +1,82 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::NaiveDate;
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::handlers::auth::ErrorResponse;
+use crate::services::{achievements, profile_visibility, username_redirects};
+use crate::utils::config::Config;
+
+/// An achievement the user holds
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AchievementResponse {
+    /// `night_owl`, `early_bird`, `polyglot` or `marathoner`
+    pub key: String,
+    pub emoji: String,
+    pub title: String,
+    pub description: String,
+    /// The day the user first qualified
+    pub earned_on: NaiveDate,
+}
+
+/// GET /api/users/:username/achievements
+/// Public endpoint to get the achievements a user holds, as of their last
+/// sync
+#[utoipa::path(
+    get,
+    path = "/users/{username}/achievements",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "Achievements, in display order", body = [AchievementResponse]),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_user_achievements(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let username = path.into_inner();
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = profile_visibility::find(db.as_ref(), user_model.id)
+        .await
+        .map_err(db_error)?;
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_model.id, &visibility)
+        .await
+        .map_err(db_error)?
+    {
+        return Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "User not found".to_string(),
+        }));
+    }
+
+    let response: Vec<AchievementResponse> = achievements::list(db.as_ref(), user_model.id)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|(achievement, earned_on)| AchievementResponse {
+            key: achievement.key.to_string(),
+            emoji: achievement.emoji.to_string(),
+            title: achievement.title.to_string(),
+            description: achievement.description.to_string(),
+            earned_on,
+        })
+        .collect();
+
+    let mut response = HttpResponse::Ok().json(response);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
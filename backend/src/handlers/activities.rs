@@ -1,21 +1,45 @@
-use actix_web::{web, HttpResponse, Responder};
-use sea_orm::sea_query::{Expr, Func};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use sea_orm::sea_query::{Expr, Func, LikeExpr};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use crate::models::{activity, git_platform_account, user, user_setting};
+use crate::handlers::annotations::{annotations_between, AnnotationResponse};
+use crate::models::{activity, follow, git_platform_account, user, user_setting};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::platform_visibility;
+use crate::services::profile_visibility;
+use crate::services::username_redirects;
+use crate::utils::config::Config;
+use crate::utils::pagination;
+use crate::utils::repo_exclusion;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ActivitiesQuery {
     pub from: Option<String>,
     pub to: Option<String>,
     pub limit: Option<i32>,
     pub offset: Option<i32>,
     pub platform: Option<String>,
+    /// Comma-separated activity types, e.g. "pull_request,issue"
+    pub activity_type: Option<String>,
+    pub repository: Option<String>,
+    pub organization: Option<String>,
+    /// Case-insensitive text search over titles and repository names
+    pub q: Option<String>,
+    /// Opaque cursor returned as `next_cursor` by a previous page
+    pub cursor: Option<String>,
+    /// "-date" (newest first, default) or "date"; "desc" and "asc" are also accepted
+    pub sort: Option<String>,
+    /// Comma-separated list of fields to include in each activity
+    pub fields: Option<String>,
+    /// "day", "week" or "month" to return pre-grouped timeline sections
+    pub group_by: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ActivityResponse {
     pub id: String,
     pub activity_type: String,
@@ -34,15 +58,71 @@ pub struct ActivityResponse {
     pub platform_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ActivitiesResponse {
     pub activities: Vec<ActivityResponse>,
     pub total: i32,
     pub has_more: bool,
+    pub next_cursor: Option<String>,
+    /// Present when `group_by` is set; activities are then listed per section
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sections: Option<Vec<ActivitySection>>,
+    /// The user's own annotations within `from`/`to`, on the first page of
+    /// their timeline only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<AnnotationResponse>>,
+}
+
+/// An activity of a followed user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedActivity {
+    pub username: String,
+    #[serde(flatten)]
+    pub activity: ActivityResponse,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedResponse {
+    pub activities: Vec<FeedActivity>,
+    pub total: i32,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+/// A period of the timeline with a summary of what happened in it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivitySection {
+    pub period: String,
+    pub label: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub summary: String,
+    pub activity_counts: std::collections::BTreeMap<String, i32>,
+    pub total_commits: i32,
+    pub repository_count: i32,
+    pub activities: Vec<ActivityResponse>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TimelineGrouping {
+    Day,
+    Week,
+    Month,
 }
 
 /// GET /api/activities
 /// Get user's activity timeline
+#[utoipa::path(
+    get,
+    path = "/activities",
+    tag = "activities",
+    params(ActivitiesQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Activity timeline page", body = ActivitiesResponse),
+        (status = 400, description = "Invalid filter")
+    )
+)]
 pub async fn get_activities(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -51,6 +131,28 @@ pub async fn get_activities(
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
+    let grouping = match parse_grouping(query.group_by.as_deref()) {
+        Ok(grouping) => grouping,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
+    // Annotations span the whole range, so they come with the first page only
+    let annotations = if query.cursor.is_none() && query.offset.unwrap_or(0) == 0 {
+        let from = query.from.as_deref().and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let to = query.to.as_deref().and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let annotations = annotations_between(db.as_ref(), user_id, from, to)
+            .await
+            .map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        Some(annotations)
+    } else {
+        None
+    };
+
     // Get user settings for privacy filtering
     let settings = user_setting::Entity::find()
         .filter(user_setting::Column::UserId.eq(user_id))
@@ -102,6 +204,9 @@ pub async fn get_activities(
             activities: vec![],
             total: 0,
             has_more: false,
+            next_cursor: None,
+            sections: grouping.map(|_| Vec::new()),
+            annotations,
         }));
     }
 
@@ -113,49 +218,25 @@ pub async fn get_activities(
 
     // Build query for activities
     let mut activity_query = activity::Entity::find()
-        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
-        .order_by_desc(activity::Column::ActivityDate);
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids));
 
     // Apply privacy filter
     if !show_private_contributions {
         activity_query = activity_query.filter(activity::Column::IsPrivateRepo.eq(false));
     }
 
-    // Apply date range if provided
-    if let Some(from_str) = &query.from {
-        if let Ok(from_date) = chrono::NaiveDate::parse_from_str(from_str, "%Y-%m-%d") {
-            activity_query = activity_query.filter(activity::Column::ActivityDate.gte(from_date));
+    let page = match fetch_activity_page(db.as_ref(), activity_query, &query).await? {
+        Ok(page) => page,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
         }
-    }
-
-    if let Some(to_str) = &query.to {
-        if let Ok(to_date) = chrono::NaiveDate::parse_from_str(to_str, "%Y-%m-%d") {
-            activity_query = activity_query.filter(activity::Column::ActivityDate.lte(to_date));
-        }
-    }
-
-    // Count total activities before pagination
-    let total = activity_query
-        .clone()
-        .count(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?;
-
-    // Apply pagination
-    let limit = query.limit.unwrap_or(50).min(100) as u64;
-    let offset = query.offset.unwrap_or(0).max(0) as u64;
-
-    activity_query = activity_query.limit(limit).offset(offset);
-
-    let activities = activity_query.all(db.as_ref()).await.map_err(|e| {
-        log::error!("Database error: {}", e);
-        actix_web::error::ErrorInternalServerError("Database error")
-    })?;
-
-    let has_more = (offset + limit) < total;
+    };
+    let ActivityPage {
+        activities,
+        total,
+        has_more,
+        next_cursor,
+    } = page;
 
     let activity_responses: Vec<ActivityResponse> = activities
         .into_iter()
@@ -163,88 +244,64 @@ pub async fn get_activities(
             // Get platform account info for this activity
             let account = accounts_map.get(&a.git_platform_account_id)?;
 
-            // Hide repository name if it's private and user wants to hide private repo names
-            let repository_name = if a.is_private_repo && hide_private_repo_names {
-                None
-            } else {
-                a.repository_name
-            };
-
-            // Sanitize metadata to hide repository names in the repositories array for Commit activities
-            let metadata = if a.is_private_repo && hide_private_repo_names {
-                // Clone and modify metadata to hide repository names
-                let mut sanitized_metadata = a.metadata.clone();
-                if let Some(repos) = sanitized_metadata.get_mut("repositories") {
-                    if let Some(repos_array) = repos.as_array_mut() {
-                        for repo in repos_array.iter_mut() {
-                            if let Some(repo_obj) = repo.as_object_mut() {
-                                repo_obj.insert(
-                                    "name".to_string(),
-                                    serde_json::json!("Private Repository"),
-                                );
-                            }
-                        }
-                    }
-                }
-                sanitized_metadata
-            } else {
-                a.metadata
-            };
-
-            Some(ActivityResponse {
-                id: a.id.to_string(),
-                activity_type: format!("{:?}", a.activity_type),
-                date: a.activity_date.format("%Y-%m-%d").to_string(),
-                metadata,
-                repository_name,
-                repository_url: a.repository_url,
-                is_private: a.is_private_repo,
-                count: a.count,
-                primary_language: a.primary_language,
-                organization_name: a.organization_name,
-                organization_avatar_url: a.organization_avatar_url,
-                platform: format!("{:?}", account.platform_type).to_lowercase(),
-                platform_username: account.platform_username.clone(),
-                platform_url: account.platform_url.clone(),
-            })
+            Some(activity_response(a, account, hide_private_repo_names))
         })
         .collect();
 
-    Ok(HttpResponse::Ok().json(ActivitiesResponse {
+    // When grouping, the activities move into their sections
+    let (activity_responses, sections) = match grouping {
+        Some(grouping) => (Vec::new(), Some(group_activities(activity_responses, grouping))),
+        None => (activity_responses, None),
+    };
+
+    Ok(ActivitiesResponse {
         activities: activity_responses,
         total: total as i32,
         has_more,
-    }))
+        next_cursor,
+        sections,
+        annotations,
+    }
+    .into_response(query.fields.as_deref()))
 }
 
 /// GET /api/users/:username/activities
 /// Public endpoint to get user's activity timeline by username
+#[utoipa::path(
+    get,
+    path = "/users/{username}/activities",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)"), ActivitiesQuery),
+    responses(
+        (status = 200, description = "Activity timeline page", body = ActivitiesResponse),
+        (status = 404, description = "User not found")
+    )
+)]
 pub async fn get_user_activities(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
     path: web::Path<String>,
     query: web::Query<ActivitiesQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     let username = path.into_inner();
 
+    let grouping = match parse_grouping(query.group_by.as_deref()) {
+        Ok(grouping) => grouping,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
     // Find user by username (case-insensitive)
-    let user_model = user::Entity::find()
-        .filter(
-            Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(username.to_lowercase()),
-        )
-        .one(db.as_ref())
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
-        })?;
-
-    let user_model = match user_model {
-        Some(u) => u,
-        None => {
-            return Ok(HttpResponse::NotFound().json(serde_json::json!({
-                "error": "User not found"
-            })));
-        }
+        })? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
     };
 
     let user_id = user_model.id;
@@ -259,6 +316,19 @@ pub async fn get_user_activities(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    let visibility = profile_visibility::of(settings.as_ref()).to_string();
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_id, &visibility)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+    {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "User not found"
+        })));
+    }
+
     let show_private_contributions = settings
         .as_ref()
         .map(|s| s.show_private_contributions)
@@ -269,10 +339,22 @@ pub async fn get_user_activities(
         .map(|s| s.hide_private_repo_names)
         .unwrap_or(false);
 
-    // Get all active platform accounts for this user
+    let excluded_repositories = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.clone())
+        .unwrap_or_default();
+
+    // Get the active platform accounts this user shows publicly
+    let hidden_accounts = platform_visibility::hidden_accounts(db.as_ref(), user_id)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
     let mut accounts_query = git_platform_account::Entity::find()
         .filter(git_platform_account::Column::UserId.eq(user_id))
-        .filter(git_platform_account::Column::IsActive.eq(true));
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(git_platform_account::Column::Id.is_not_in(hidden_accounts));
 
     // Filter by platform type if specified
     if let Some(platform_filter) = &query.platform {
@@ -300,6 +382,9 @@ pub async fn get_user_activities(
             activities: vec![],
             total: 0,
             has_more: false,
+            next_cursor: None,
+            sections: grouping.map(|_| Vec::new()),
+            annotations: None,
         }));
     }
 
@@ -311,14 +396,331 @@ pub async fn get_user_activities(
 
     // Build query for activities
     let mut activity_query = activity::Entity::find()
-        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
-        .order_by_desc(activity::Column::ActivityDate);
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids));
 
     // Apply privacy filter
     if !show_private_contributions {
         activity_query = activity_query.filter(activity::Column::IsPrivateRepo.eq(false));
     }
 
+    // Hide excluded repositories without deleting their data
+    activity_query = activity_query.filter(repo_exclusion::not_excluded(
+        activity::Column::RepositoryName,
+        &excluded_repositories,
+    ));
+
+    let page = match fetch_activity_page(db.as_ref(), activity_query, &query).await? {
+        Ok(page) => page,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+    let ActivityPage {
+        activities,
+        total,
+        has_more,
+        next_cursor,
+    } = page;
+
+    let activity_responses: Vec<ActivityResponse> = activities
+        .into_iter()
+        .filter_map(|mut a| {
+            if !strip_excluded_repositories(&mut a, &excluded_repositories) {
+                return None;
+            }
+
+            // Get platform account info for this activity
+            let account = accounts_map.get(&a.git_platform_account_id)?;
+
+            Some(activity_response(a, account, hide_private_repo_names))
+        })
+        .collect();
+
+    // When grouping, the activities move into their sections
+    let (activity_responses, sections) = match grouping {
+        Some(grouping) => (Vec::new(), Some(group_activities(activity_responses, grouping))),
+        None => (activity_responses, None),
+    };
+
+    let mut response = ActivitiesResponse {
+        activities: activity_responses,
+        total: total as i32,
+        has_more,
+        next_cursor,
+        sections,
+        annotations: None,
+    }
+    .into_response(query.fields.as_deref());
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// GET /api/feed
+/// Public activities of the users the current user follows, newest first
+#[utoipa::path(
+    get,
+    path = "/feed",
+    tag = "activities",
+    params(ActivitiesQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feed page", body = FeedResponse),
+        (status = 400, description = "Invalid filter")
+    )
+)]
+pub async fn get_feed(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<ActivitiesQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    if query.group_by.is_some() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "group_by is not supported by the feed"
+        })));
+    }
+
+    let platform_type = match query.platform.as_deref().map(str::to_lowercase).as_deref() {
+        None => None,
+        Some("github") => Some(git_platform_account::GitPlatform::GitHub),
+        Some("gitea") => Some(git_platform_account::GitPlatform::Gitea),
+        Some("gitlab") => Some(git_platform_account::GitPlatform::GitLab),
+        Some(other) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Invalid platform filter: {}", other)
+            })));
+        }
+    };
+
+    let followee_ids: Vec<Uuid> = follow::Entity::find()
+        .select_only()
+        .column(follow::Column::FolloweeId)
+        .filter(follow::Column::FollowerId.eq(user_id))
+        .into_tuple()
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let followees: std::collections::HashMap<Uuid, user::Model> = user::Entity::find()
+        .filter(user::Column::Id.is_in(followee_ids.iter().copied()))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|u| (u.id, u))
+        .collect();
+    let settings: std::collections::HashMap<Uuid, user_setting::Model> = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.is_in(followee_ids.iter().copied()))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|s| (s.user_id, s))
+        .collect();
+
+    // Private profiles drop out of the feed until they are public again
+    let visible: Vec<Uuid> = followees
+        .keys()
+        .copied()
+        .filter(|id| profile_visibility::of(settings.get(id)) != profile_visibility::PRIVATE)
+        .collect();
+
+    let mut accounts_query = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.is_in(visible))
+        .filter(git_platform_account::Column::IsActive.eq(true));
+    if let Some(platform_type) = platform_type {
+        accounts_query = accounts_query.filter(git_platform_account::Column::PlatformType.eq(platform_type));
+    }
+    let accounts = accounts_query.all(db.as_ref()).await.map_err(db_error)?;
+    let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+    let account_visibility = platform_visibility::for_accounts(db.as_ref(), &account_ids)
+        .await
+        .map_err(db_error)?;
+    let accounts_map: std::collections::HashMap<Uuid, &git_platform_account::Model> = accounts
+        .iter()
+        .filter(|a| account_visibility.get(&a.id).is_none_or(|v| v.is_public))
+        .map(|a| (a.id, a))
+        .collect();
+
+    // Each followed user's own privacy settings apply to their activities
+    let mut condition = Condition::any();
+    for followee_id in followees.keys() {
+        let ids: Vec<Uuid> = accounts_map
+            .values()
+            .filter(|a| a.user_id == *followee_id)
+            .map(|a| a.id)
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+
+        let user_settings = settings.get(followee_id);
+        let mut user_condition =
+            Condition::all().add(activity::Column::GitPlatformAccountId.is_in(ids));
+        if !user_settings.is_none_or(|s| s.show_private_contributions) {
+            user_condition = user_condition.add(activity::Column::IsPrivateRepo.eq(false));
+        }
+        if let Some(s) = user_settings {
+            user_condition = user_condition.add(repo_exclusion::not_excluded(
+                activity::Column::RepositoryName,
+                &s.excluded_repositories,
+            ));
+        }
+        condition = condition.add(user_condition);
+    }
+
+    if condition.is_empty() {
+        return Ok(HttpResponse::Ok().json(FeedResponse {
+            activities: vec![],
+            total: 0,
+            has_more: false,
+            next_cursor: None,
+        }));
+    }
+
+    let page = match fetch_activity_page(db.as_ref(), activity::Entity::find().filter(condition), &query).await? {
+        Ok(page) => page,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
+    let activities: Vec<FeedActivity> = page
+        .activities
+        .into_iter()
+        .filter_map(|mut a| {
+            let account = accounts_map.get(&a.git_platform_account_id)?;
+            let owner = followees.get(&account.user_id)?;
+            let user_settings = settings.get(&owner.id);
+
+            let excluded = user_settings.map(|s| s.excluded_repositories.as_slice()).unwrap_or_default();
+            if !strip_excluded_repositories(&mut a, excluded) {
+                return None;
+            }
+
+            let hide_private_repo_names = user_settings.is_some_and(|s| s.hide_private_repo_names);
+            Some(FeedActivity {
+                username: owner.username.clone(),
+                activity: activity_response(a, account, hide_private_repo_names),
+            })
+        })
+        .collect();
+
+    let response = FeedResponse {
+        activities,
+        total: page.total as i32,
+        has_more: page.has_more,
+        next_cursor: page.next_cursor,
+    };
+
+    let Some(fields) = query.fields.as_deref() else {
+        return Ok(HttpResponse::Ok().json(response));
+    };
+    // The username is always kept, so entries can be told apart
+    let mut value = serde_json::to_value(&response).unwrap_or_default();
+    if let Some(activities) = value.get_mut("activities") {
+        pagination::select_fields(activities, &format!("{},username", fields));
+    }
+    Ok(HttpResponse::Ok().json(value))
+}
+
+/// An activity as returned by the API, with private repository names hidden
+/// if their owner asked for it
+fn activity_response(
+    a: activity::Model,
+    account: &git_platform_account::Model,
+    hide_private_repo_names: bool,
+) -> ActivityResponse {
+    // Hide repository name if it's private and user wants to hide private repo names
+    let repository_name = if a.is_private_repo && hide_private_repo_names {
+        None
+    } else {
+        a.repository_name
+    };
+
+    // Sanitize metadata to hide repository names in the repositories array for Commit activities
+    let metadata = if a.is_private_repo && hide_private_repo_names {
+        // Clone and modify metadata to hide repository names
+        let mut sanitized_metadata = a.metadata.clone();
+        if let Some(repos) = sanitized_metadata.get_mut("repositories") {
+            if let Some(repos_array) = repos.as_array_mut() {
+                for repo in repos_array.iter_mut() {
+                    if let Some(repo_obj) = repo.as_object_mut() {
+                        repo_obj.insert(
+                            "name".to_string(),
+                            serde_json::json!("Private Repository"),
+                        );
+                    }
+                }
+            }
+        }
+        sanitized_metadata
+    } else {
+        a.metadata
+    };
+
+    ActivityResponse {
+        id: a.id.to_string(),
+        activity_type: format!("{:?}", a.activity_type),
+        date: a.activity_date.format("%Y-%m-%d").to_string(),
+        metadata,
+        repository_name,
+        repository_url: a.repository_url,
+        is_private: a.is_private_repo,
+        count: a.count,
+        primary_language: a.primary_language,
+        organization_name: a.organization_name,
+        organization_avatar_url: a.organization_avatar_url,
+        platform: format!("{:?}", account.platform_type).to_lowercase(),
+        platform_username: account.platform_username.clone(),
+        platform_url: account.platform_url.clone(),
+    }
+}
+
+impl ActivitiesResponse {
+    /// Serialize, keeping only the requested fields on each activity
+    fn into_response(self, fields: Option<&str>) -> HttpResponse {
+        let Some(fields) = fields else {
+            return HttpResponse::Ok().json(self);
+        };
+
+        let mut value = serde_json::to_value(&self).unwrap_or_default();
+        if let Some(activities) = value.get_mut("activities") {
+            pagination::select_fields(activities, fields);
+        }
+        if let Some(sections) = value.get_mut("sections").and_then(|s| s.as_array_mut()) {
+            for section in sections {
+                if let Some(activities) = section.get_mut("activities") {
+                    pagination::select_fields(activities, fields);
+                }
+            }
+        }
+        HttpResponse::Ok().json(value)
+    }
+}
+
+/// One page of filtered activities
+struct ActivityPage {
+    activities: Vec<activity::Model>,
+    total: u64,
+    has_more: bool,
+    next_cursor: Option<String>,
+}
+
+/// Apply the search filters, sort order and pagination from the query string.
+/// The outer error is a database failure; the inner one is a bad parameter.
+async fn fetch_activity_page(
+    db: &DatabaseConnection,
+    mut activity_query: Select<activity::Entity>,
+    query: &ActivitiesQuery,
+) -> Result<Result<ActivityPage, String>, actix_web::Error> {
     // Apply date range if provided
     if let Some(from_str) = &query.from {
         if let Ok(from_date) = chrono::NaiveDate::parse_from_str(from_str, "%Y-%m-%d") {
@@ -332,85 +734,710 @@ pub async fn get_user_activities(
         }
     }
 
+    if let Some(types) = &query.activity_type {
+        let mut parsed = Vec::new();
+        for t in types.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            match parse_activity_type(t) {
+                Some(activity_type) => parsed.push(activity_type),
+                None => return Ok(Err(format!("Invalid activity_type: {}", t))),
+            }
+        }
+        if !parsed.is_empty() {
+            activity_query = activity_query.filter(activity::Column::ActivityType.is_in(parsed));
+        }
+    }
+
+    if let Some(repository) = query.repository.as_deref().filter(|r| !r.is_empty()) {
+        let repository = repository.to_lowercase();
+        // Commit activities are aggregated per month and list their repositories in metadata
+        activity_query = activity_query.filter(
+            Condition::any()
+                .add(
+                    Expr::expr(Func::lower(Expr::col(activity::Column::RepositoryName)))
+                        .eq(repository.clone()),
+                )
+                .add(Expr::cust_with_values(
+                    match db.get_database_backend() {
+                        DbBackend::Sqlite => "EXISTS (SELECT 1 FROM json_each(CASE WHEN json_type(metadata, '$.repositories') = 'array' THEN json_extract(metadata, '$.repositories') ELSE '[]' END) AS r WHERE LOWER(json_extract(r.value, '$.name')) = $1)",
+                        DbBackend::MySql => "JSON_CONTAINS(LOWER(JSON_EXTRACT(metadata, '$.repositories[*].name')), JSON_QUOTE(?))",
+                        _ => "EXISTS (SELECT 1 FROM jsonb_array_elements(CASE WHEN jsonb_typeof(metadata->'repositories') = 'array' THEN metadata->'repositories' ELSE '[]'::jsonb END) AS r WHERE LOWER(r->>'name') = $1)",
+                    },
+                    [repository],
+                )),
+        );
+    }
+
+    if let Some(organization) = query.organization.as_deref().filter(|o| !o.is_empty()) {
+        let organization = organization.to_lowercase();
+        activity_query = activity_query.filter(
+            Condition::any()
+                .add(
+                    Expr::expr(Func::lower(Expr::col(activity::Column::OrganizationName)))
+                        .eq(organization.clone()),
+                )
+                .add(
+                    Expr::expr(Func::lower(Expr::col(activity::Column::RepositoryName)))
+                        .like(LikeExpr::new(format!("{}/%", escape_like(&organization))).escape('\\')),
+                ),
+        );
+    }
+
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        let pattern = format!("%{}%", escape_like(&q.to_lowercase()));
+        activity_query = activity_query.filter(
+            Condition::any()
+                .add(Expr::cust_with_values(
+                    match db.get_database_backend() {
+                        // MariaDB has no ->> and MySQL string literals escape backslashes
+                        DbBackend::MySql => "LOWER(JSON_UNQUOTE(JSON_EXTRACT(metadata, '$.title'))) LIKE ? ESCAPE '\\\\'",
+                        _ => "LOWER(metadata->>'title') LIKE $1 ESCAPE '\\'",
+                    },
+                    [pattern.clone()],
+                ))
+                .add(
+                    Expr::expr(Func::lower(Expr::col(activity::Column::RepositoryName)))
+                        .like(LikeExpr::new(pattern.clone()).escape('\\')),
+                )
+                .add(
+                    Expr::expr(Func::lower(Expr::col(activity::Column::OrganizationName)))
+                        .like(LikeExpr::new(pattern).escape('\\')),
+                ),
+        );
+    }
+
+    let ascending = match pagination::parse_sort(query.sort.as_deref(), &["date"], "-date") {
+        Ok(order) => order.ascending,
+        Err(e) => return Ok(Err(e)),
+    };
+
     // Count total activities before pagination
-    let total = activity_query
-        .clone()
-        .count(db.as_ref())
+    let total = activity_query.clone().count(db).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    // Keyset pagination on (activity_date, id) keeps pages stable while new data arrives
+    if let Some(cursor) = &query.cursor {
+        let Some((cursor_date, cursor_id)) = decode_cursor(cursor) else {
+            return Ok(Err("Invalid cursor".to_string()));
+        };
+        let (date_cmp, id_cmp) = if ascending {
+            (
+                activity::Column::ActivityDate.gt(cursor_date),
+                activity::Column::Id.gt(cursor_id),
+            )
+        } else {
+            (
+                activity::Column::ActivityDate.lt(cursor_date),
+                activity::Column::Id.lt(cursor_id),
+            )
+        };
+        activity_query = activity_query.filter(
+            Condition::any().add(date_cmp).add(
+                Condition::all()
+                    .add(activity::Column::ActivityDate.eq(cursor_date))
+                    .add(id_cmp),
+            ),
+        );
+    }
+
+    let order = if ascending { Order::Asc } else { Order::Desc };
+    activity_query = activity_query
+        .order_by(activity::Column::ActivityDate, order.clone())
+        .order_by(activity::Column::Id, order);
+
+    // Apply pagination
+    let limit = query.limit.unwrap_or(50).clamp(1, 100) as u64;
+    let offset = if query.cursor.is_some() {
+        0
+    } else {
+        query.offset.unwrap_or(0).max(0) as u64
+    };
+
+    // Fetch one extra row to learn whether another page exists
+    let mut activities = activity_query
+        .limit(limit + 1)
+        .offset(offset)
+        .all(db)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
-    // Apply pagination
-    let limit = query.limit.unwrap_or(50).min(100) as u64;
-    let offset = query.offset.unwrap_or(0).max(0) as u64;
+    let has_more = activities.len() as u64 > limit;
+    activities.truncate(limit as usize);
 
-    activity_query = activity_query.limit(limit).offset(offset);
+    let next_cursor = if has_more {
+        activities
+            .last()
+            .map(|a| encode_cursor(a.activity_date, a.id))
+    } else {
+        None
+    };
 
-    let activities = activity_query.all(db.as_ref()).await.map_err(|e| {
-        log::error!("Database error: {}", e);
-        actix_web::error::ErrorInternalServerError("Database error")
-    })?;
+    Ok(Ok(ActivityPage {
+        activities,
+        total,
+        has_more,
+        next_cursor,
+    }))
+}
 
-    let has_more = (offset + limit) < total;
+/// Parse the `group_by` query parameter
+fn parse_grouping(group_by: Option<&str>) -> Result<Option<TimelineGrouping>, String> {
+    match group_by.map(|g| g.to_lowercase()).as_deref() {
+        None => Ok(None),
+        Some("day") => Ok(Some(TimelineGrouping::Day)),
+        Some("week") => Ok(Some(TimelineGrouping::Week)),
+        Some("month") => Ok(Some(TimelineGrouping::Month)),
+        Some(other) => Err(format!("Invalid group_by: {}", other)),
+    }
+}
 
-    let activity_responses: Vec<ActivityResponse> = activities
-        .into_iter()
-        .filter_map(|a| {
-            // Get platform account info for this activity
-            let account = accounts_map.get(&a.git_platform_account_id)?;
+/// Group a page of activities into consecutive timeline sections, keeping the page order
+fn group_activities(
+    activities: Vec<ActivityResponse>,
+    grouping: TimelineGrouping,
+) -> Vec<ActivitySection> {
+    use chrono::Datelike;
 
-            // Hide repository name if it's private and user wants to hide private repo names
-            let repository_name = if a.is_private_repo && hide_private_repo_names {
-                None
-            } else {
-                a.repository_name
-            };
-
-            // Sanitize metadata to hide repository names in the repositories array for Commit activities
-            let metadata = if a.is_private_repo && hide_private_repo_names {
-                // Clone and modify metadata to hide repository names
-                let mut sanitized_metadata = a.metadata.clone();
-                if let Some(repos) = sanitized_metadata.get_mut("repositories") {
-                    if let Some(repos_array) = repos.as_array_mut() {
-                        for repo in repos_array.iter_mut() {
-                            if let Some(repo_obj) = repo.as_object_mut() {
-                                repo_obj.insert(
-                                    "name".to_string(),
-                                    serde_json::json!("Private Repository"),
-                                );
-                            }
-                        }
-                    }
+    let mut sections: Vec<ActivitySection> = Vec::new();
+
+    for activity in activities {
+        let Ok(date) = chrono::NaiveDate::parse_from_str(&activity.date, "%Y-%m-%d") else {
+            continue;
+        };
+
+        let (period, label, start, end) = match grouping {
+            TimelineGrouping::Day => (
+                date.format("%Y-%m-%d").to_string(),
+                date.format("%B %-d, %Y").to_string(),
+                date,
+                date,
+            ),
+            TimelineGrouping::Week => {
+                let start = date
+                    - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+                let week = date.iso_week();
+                (
+                    format!("{}-W{:02}", week.year(), week.week()),
+                    format!("Week of {}", start.format("%B %-d, %Y")),
+                    start,
+                    start + chrono::Duration::days(6),
+                )
+            }
+            TimelineGrouping::Month => {
+                let start = date.with_day(1).unwrap_or(date);
+                let next_month = if start.month() == 12 {
+                    chrono::NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    chrono::NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                };
+                (
+                    date.format("%Y-%m").to_string(),
+                    date.format("%B %Y").to_string(),
+                    start,
+                    next_month.map(|d| d.pred_opt().unwrap_or(d)).unwrap_or(date),
+                )
+            }
+        };
+
+        if sections.last().map(|s| s.period != period).unwrap_or(true) {
+            sections.push(ActivitySection {
+                period,
+                label,
+                start_date: start.format("%Y-%m-%d").to_string(),
+                end_date: end.format("%Y-%m-%d").to_string(),
+                summary: String::new(),
+                activity_counts: std::collections::BTreeMap::new(),
+                total_commits: 0,
+                repository_count: 0,
+                activities: Vec::new(),
+            });
+        }
+
+        if let Some(section) = sections.last_mut() {
+            section.activities.push(activity);
+        }
+    }
+
+    for section in &mut sections {
+        let mut repositories = std::collections::HashSet::new();
+
+        for activity in &section.activities {
+            let key = activity_type_key(&activity.activity_type);
+            *section.activity_counts.entry(key.to_string()).or_insert(0) += 1;
+
+            if key == "commit" {
+                section.total_commits += activity.count;
+            }
+
+            if let Some(name) = &activity.repository_name {
+                repositories.insert(name.to_lowercase());
+            }
+            if let Some(repos) = activity.metadata.get("repositories").and_then(|r| r.as_array()) {
+                for name in repos.iter().filter_map(|r| r.get("name")?.as_str()) {
+                    repositories.insert(name.to_lowercase());
                 }
-                sanitized_metadata
-            } else {
-                a.metadata
-            };
-
-            Some(ActivityResponse {
-                id: a.id.to_string(),
-                activity_type: format!("{:?}", a.activity_type),
-                date: a.activity_date.format("%Y-%m-%d").to_string(),
-                metadata,
-                repository_name,
-                repository_url: a.repository_url,
-                is_private: a.is_private_repo,
-                count: a.count,
-                primary_language: a.primary_language,
-                organization_name: a.organization_name,
-                organization_avatar_url: a.organization_avatar_url,
-                platform: format!("{:?}", account.platform_type).to_lowercase(),
-                platform_username: account.platform_username.clone(),
-                platform_url: account.platform_url.clone(),
-            })
-        })
+            }
+        }
+
+        section.repository_count = repositories.len() as i32;
+        section.summary = section_summary(section);
+    }
+
+    sections
+}
+
+/// Map the Debug-formatted activity type used in responses to its snake_case key
+fn activity_type_key(activity_type: &str) -> &'static str {
+    match activity_type {
+        "Commit" => "commit",
+        "RepositoryCreated" => "repository_created",
+        "PullRequest" => "pull_request",
+        "Issue" => "issue",
+        "Review" => "review",
+        "OrganizationJoined" => "organization_joined",
+        "Fork" => "fork",
+        "Release" => "release",
+        "Star" => "star",
+        "Milestone" => "milestone",
+        _ => "other",
+    }
+}
+
+/// Build a one-line summary such as "4 pull requests, 120 commits across 6 repositories"
+fn section_summary(section: &ActivitySection) -> String {
+    let plural = |n: i32, singular: &str, plural: &str| {
+        format!("{} {}", n, if n == 1 { singular } else { plural })
+    };
+
+    let mut parts = Vec::new();
+    for (key, count) in &section.activity_counts {
+        let part = match key.as_str() {
+            // Commit activities carry their commit total; show commits rather than entries
+            "commit" => plural(section.total_commits, "commit", "commits"),
+            "repository_created" => plural(*count, "repository created", "repositories created"),
+            "pull_request" => plural(*count, "pull request", "pull requests"),
+            "issue" => plural(*count, "issue", "issues"),
+            "review" => plural(*count, "review", "reviews"),
+            "organization_joined" => plural(*count, "organization joined", "organizations joined"),
+            "fork" => plural(*count, "fork", "forks"),
+            "release" => plural(*count, "release", "releases"),
+            "star" => plural(*count, "star", "stars"),
+            _ => plural(*count, "activity", "activities"),
+        };
+        parts.push(part);
+    }
+
+    let mut summary = parts.join(", ");
+    if section.repository_count > 0 {
+        summary.push_str(&format!(
+            " across {}",
+            plural(section.repository_count, "repository", "repositories")
+        ));
+    }
+    summary
+}
+
+/// Parse a snake_case activity type name
+fn parse_activity_type(value: &str) -> Option<activity::ActivityType> {
+    match value.to_lowercase().as_str() {
+        "commit" => Some(activity::ActivityType::Commit),
+        "repository_created" => Some(activity::ActivityType::RepositoryCreated),
+        "pull_request" => Some(activity::ActivityType::PullRequest),
+        "issue" => Some(activity::ActivityType::Issue),
+        "review" => Some(activity::ActivityType::Review),
+        "organization_joined" => Some(activity::ActivityType::OrganizationJoined),
+        "fork" => Some(activity::ActivityType::Fork),
+        "release" => Some(activity::ActivityType::Release),
+        "star" => Some(activity::ActivityType::Star),
+        "milestone" => Some(activity::ActivityType::Milestone),
+        _ => None,
+    }
+}
+
+/// Escape LIKE wildcards so user input is matched literally
+pub(crate) fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn encode_cursor(date: chrono::NaiveDate, id: Uuid) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(format!("{}|{}", date.format("%Y-%m-%d"), id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<(chrono::NaiveDate, Uuid)> {
+    use base64::Engine;
+    let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (date, id) = decoded.split_once('|')?;
+    Some((
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+        Uuid::parse_str(id).ok()?,
+    ))
+}
+
+/// Maximum number of entries included in the Atom feed
+const FEED_ENTRY_LIMIT: u64 = 50;
+
+/// GET /api/users/:username/activities.atom
+/// Public Atom feed of user's activity timeline
+#[utoipa::path(
+    get,
+    path = "/users/{username}/activities.atom",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "Atom feed", content_type = "application/atom+xml", body = String),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_user_activities_feed(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let username = path.into_inner();
+
+    // Find user by username (case-insensitive)
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    // Get user settings for privacy filtering
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_model.id))
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let visibility = profile_visibility::of(settings.as_ref()).to_string();
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_model.id, &visibility)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+    {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "User not found"
+        })));
+    }
+
+    let show_private_contributions = settings
+        .as_ref()
+        .map(|s| s.show_private_contributions)
+        .unwrap_or(true);
+
+    let hide_private_repo_names = settings
+        .as_ref()
+        .map(|s| s.hide_private_repo_names)
+        .unwrap_or(false);
+
+    let excluded_repositories = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.clone())
+        .unwrap_or_default();
+
+    let hidden_accounts = platform_visibility::hidden_accounts(db.as_ref(), user_model.id)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_model.id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(git_platform_account::Column::Id.is_not_in(hidden_accounts))
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+
+    let mut activity_query = activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
+        .order_by_desc(activity::Column::ActivityDate)
+        .order_by_desc(activity::Column::UpdatedAt);
+
+    if !show_private_contributions {
+        activity_query = activity_query.filter(activity::Column::IsPrivateRepo.eq(false));
+    }
+
+    // Hide excluded repositories without deleting their data
+    activity_query = activity_query.filter(repo_exclusion::not_excluded(
+        activity::Column::RepositoryName,
+        &excluded_repositories,
+    ));
+
+    let activities = if accounts.is_empty() {
+        Vec::new()
+    } else {
+        activity_query
+            .limit(FEED_ENTRY_LIMIT)
+            .all(db.as_ref())
+            .await
+            .map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?
+    };
+    let activities: Vec<activity::Model> = activities
+        .into_iter()
+        .filter_map(|mut a| strip_excluded_repositories(&mut a, &excluded_repositories).then_some(a))
         .collect();
 
-    Ok(HttpResponse::Ok().json(ActivitiesResponse {
-        activities: activity_responses,
-        total: total as i32,
-        has_more,
-    }))
+    let base_url = config.base_url.trim_end_matches('/');
+    let feed_url = format!("{}/users/{}/activities.atom", base_url, user_model.username);
+    let profile_url = format!(
+        "{}/{}",
+        instance.current().await.frontend_url.trim_end_matches('/'),
+        user_model.username
+    );
+    let tag_authority = url::Url::parse(base_url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_else(|| "localhost".to_string());
+
+    // The feed is as fresh as its most recently updated entry
+    let feed_updated = activities
+        .iter()
+        .map(|a| a.updated_at)
+        .max()
+        .unwrap_or(user_model.created_at);
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    xml.push_str(&format!(
+        "<id>tag:{},2025:users/{}/activities</id>",
+        xml_escape(&tag_authority),
+        user_model.id
+    ));
+    xml.push_str(&format!(
+        "<title>{}'s activity</title>",
+        xml_escape(&user_model.username)
+    ));
+    xml.push_str(&format!("<updated>{}</updated>", feed_updated.to_rfc3339()));
+    xml.push_str(&format!(
+        r#"<link rel="self" type="application/atom+xml" href="{}"/>"#,
+        xml_escape(&feed_url)
+    ));
+    xml.push_str(&format!(
+        r#"<link rel="alternate" type="text/html" href="{}"/>"#,
+        xml_escape(&profile_url)
+    ));
+    xml.push_str(&format!(
+        "<author><name>{}</name><uri>{}</uri></author>",
+        xml_escape(&user_model.username),
+        xml_escape(&profile_url)
+    ));
+    xml.push_str("<generator>hgitmap</generator>");
+
+    for a in &activities {
+        let hide_name = a.is_private_repo && hide_private_repo_names;
+        let title = activity_title(a, hide_name);
+        let published = a
+            .activity_date
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc();
+
+        xml.push_str("<entry>");
+        xml.push_str(&format!(
+            "<id>tag:{},2025:activity/{}</id>",
+            xml_escape(&tag_authority),
+            activity_entry_key(a)
+        ));
+        xml.push_str(&format!("<title>{}</title>", xml_escape(&title)));
+        xml.push_str(&format!("<published>{}</published>", published.to_rfc3339()));
+        xml.push_str(&format!("<updated>{}</updated>", a.updated_at.to_rfc3339()));
+        if !hide_name {
+            let link = a
+                .metadata
+                .get("url")
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .or_else(|| a.repository_url.clone());
+            if let Some(link) = link {
+                xml.push_str(&format!(
+                    r#"<link rel="alternate" href="{}"/>"#,
+                    xml_escape(&link)
+                ));
+            }
+        }
+        xml.push_str(&format!(
+            r#"<category term="{}"/>"#,
+            xml_escape(&format!("{:?}", a.activity_type))
+        ));
+        xml.push_str(&format!(
+            r#"<content type="text">{}</content>"#,
+            xml_escape(&title)
+        ));
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>");
+
+    let mut response = HttpResponse::Ok()
+        .content_type("application/atom+xml; charset=utf-8")
+        .body(xml);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// Drop excluded repositories from a monthly commit activity's repository
+/// list, adjusting its counts. Returns false when no repository is left.
+pub(crate) fn strip_excluded_repositories(a: &mut activity::Model, patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return true;
+    }
+    let Some(repos) = a.metadata.get_mut("repositories").and_then(|r| r.as_array_mut()) else {
+        return true;
+    };
+
+    let listed = repos.len();
+    let mut removed = 0;
+    repos.retain(|repo| {
+        let excluded = repo
+            .get("name")
+            .and_then(|n| n.as_str())
+            .is_some_and(|name| repo_exclusion::is_excluded(patterns, name));
+        if excluded {
+            removed += repo.get("commit_count").and_then(|c| c.as_i64()).unwrap_or(0);
+        }
+        !excluded
+    });
+    if repos.is_empty() && listed > 0 {
+        return false;
+    }
+
+    if removed > 0 {
+        a.count -= removed as i32;
+        if let Some(total) = a.metadata.get_mut("total_count") {
+            *total = serde_json::json!(a.count);
+        }
+    }
+    true
+}
+
+/// Build a stable entry key from an activity's natural identity.
+/// Activities are re-created on every sync, so the row id cannot be used.
+fn activity_entry_key(a: &activity::Model) -> String {
+    use sha2::{Digest, Sha256};
+
+    // Commit activities are aggregated per month, so the month identifies them
+    let period = match a.activity_type {
+        activity::ActivityType::Commit => a.activity_date.format("%Y-%m").to_string(),
+        _ => a.activity_date.format("%Y-%m-%d").to_string(),
+    };
+    let detail = a
+        .metadata
+        .get("url")
+        .or_else(|| a.metadata.get("number"))
+        .map(|v| v.to_string())
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(a.git_platform_account_id.as_bytes());
+    hasher.update(format!("{:?}", a.activity_type).as_bytes());
+    hasher.update(period.as_bytes());
+    hasher.update(a.repository_name.as_deref().unwrap_or("").as_bytes());
+    hasher.update(a.organization_name.as_deref().unwrap_or("").as_bytes());
+    hasher.update(detail.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Human-readable one-line summary of an activity
+pub fn activity_title(a: &activity::Model, hide_name: bool) -> String {
+    let repo = if hide_name {
+        "a private repository".to_string()
+    } else {
+        a.repository_name
+            .clone()
+            .unwrap_or_else(|| "a repository".to_string())
+    };
+    let item_title = a
+        .metadata
+        .get("title")
+        .and_then(|v| v.as_str())
+        .filter(|_| !hide_name);
+
+    match a.activity_type {
+        activity::ActivityType::Commit => {
+            let repo_count = a
+                .metadata
+                .get("repositories")
+                .and_then(|v| v.as_array())
+                .map(|r| r.len())
+                .unwrap_or(1);
+            format!(
+                "Created {} commit{} in {} repositor{} ({})",
+                a.count,
+                if a.count == 1 { "" } else { "s" },
+                repo_count,
+                if repo_count == 1 { "y" } else { "ies" },
+                a.activity_date.format("%B %Y")
+            )
+        }
+        activity::ActivityType::RepositoryCreated => format!("Created repository {}", repo),
+        activity::ActivityType::PullRequest => match item_title {
+            Some(t) => format!("Opened pull request in {}: {}", repo, t),
+            None => format!("Opened a pull request in {}", repo),
+        },
+        activity::ActivityType::Issue => match item_title {
+            Some(t) => format!("Opened issue in {}: {}", repo, t),
+            None => format!("Opened an issue in {}", repo),
+        },
+        activity::ActivityType::Review => format!("Reviewed a pull request in {}", repo),
+        activity::ActivityType::OrganizationJoined => match &a.organization_name {
+            Some(org) => format!("Joined the {} organization", org),
+            None => "Joined an organization".to_string(),
+        },
+        activity::ActivityType::Fork => format!("Forked {}", repo),
+        activity::ActivityType::Release => format!("Published a release in {}", repo),
+        activity::ActivityType::Star => format!("Starred {}", repo),
+        activity::ActivityType::Milestone => match item_title {
+            Some(title) => format!("Reached a milestone: {}", title),
+            None => "Reached a milestone".to_string(),
+        },
+    }
+}
+
+/// Escape text for inclusion in XML content or attribute values
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
 }
@@ -0,0 +1,226 @@
+use actix_web::web::Bytes;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::activity_import::{self, SyncJobStatus};
+use crate::models::git_platform_account;
+use crate::services::activity_import::{self as import_service, MAX_UPLOAD_BYTES};
+use crate::services::event_bus::EventBus;
+use crate::services::response_cache::ResponseCache;
+
+/// Imports listed by GET /platforms/imports
+const LIST_LIMIT: u64 = 50;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportResponse {
+    pub import_id: String,
+    pub platform_account_id: String,
+    /// pending, processing, completed or failed
+    pub status: String,
+    /// Every event in the file
+    pub events_read: i32,
+    /// The account's events that map to activities
+    pub events_matched: i32,
+    /// Activities built from them; imported plus skipped reach it when done
+    pub activities_total: i32,
+    pub activities_imported: i32,
+    /// Activities already stored, e.g. by a sync or an earlier import
+    pub duplicates_skipped: i32,
+    pub error_message: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    /// Poll this for progress; also sent as the Location header
+    pub status_url: String,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// URL of an import under the API prefix (/api/v1 or none) of `req`
+fn import_status_url(req: &HttpRequest, import_id: Uuid) -> String {
+    let prefix = if req.path().starts_with("/api/v1/") { "/api/v1" } else { "" };
+    format!("{}/platforms/imports/{}", prefix, import_id)
+}
+
+fn import_response(req: &HttpRequest, import: activity_import::Model) -> ImportResponse {
+    ImportResponse {
+        status_url: import_status_url(req, import.id),
+        import_id: import.id.to_string(),
+        platform_account_id: import.platform_account_id.to_string(),
+        status: format!("{:?}", import.status).to_lowercase(),
+        events_read: import.events_read,
+        events_matched: import.events_matched,
+        activities_total: import.activities_total,
+        activities_imported: import.activities_imported,
+        duplicates_skipped: import.duplicates_skipped,
+        error_message: import.error_message,
+        created_at: import.created_at.to_rfc3339(),
+        started_at: import.started_at.map(|t| t.to_rfc3339()),
+        completed_at: import.completed_at.map(|t| t.to_rfc3339()),
+    }
+}
+
+/// POST /api/platforms/:id/import
+/// Backfill a GitHub account's activities from a GH Archive file or a saved
+/// Events API response in the request body, in the background
+#[utoipa::path(
+    post,
+    path = "/platforms/{id}/import",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    request_body(
+        content = String,
+        description = "Newline-delimited events (GH Archive) or a JSON array of events, optionally gzipped; at most 100 MB",
+        content_type = "application/octet-stream"
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Import started; poll the Location header", body = ImportResponse),
+        (status = 400, description = "Empty file, or not a GitHub account"),
+        (status = 404, description = "Platform account not found"),
+        (status = 409, description = "Another import is still running"),
+        (status = 413, description = "File larger than 100 MB")
+    )
+)]
+pub async fn import_activities(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    events: web::Data<EventBus>,
+    cache: web::Data<ResponseCache>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: Bytes,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let account_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid account ID: {}", e)))?;
+
+    let account = git_platform_account::Entity::find_by_id(account_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .filter(|a| a.user_id == user_id)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
+
+    if account.platform_type != git_platform_account::GitPlatform::GitHub {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Only GitHub events can be imported"
+        })));
+    }
+
+    if body.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "The request body must be the event file"
+        })));
+    }
+
+    // Uploads are held in memory until imported, so one at a time
+    let running = activity_import::Entity::find()
+        .filter(activity_import::Column::UserId.eq(user_id))
+        .filter(activity_import::Column::Status.is_in([SyncJobStatus::Pending, SyncJobStatus::Processing]))
+        .count(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if running > 0 {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Another import is still running; wait for it to finish"
+        })));
+    }
+
+    log::info!(
+        "📥 [Import] Importing {} bytes of events into {} ({})",
+        body.len(),
+        account.platform_username,
+        account.id
+    );
+
+    let import = import_service::start(db.as_ref(), events.as_ref(), cache.as_ref(), &account, body)
+        .await
+        .map_err(db_error)?;
+
+    let response = import_response(&req, import);
+    Ok(HttpResponse::Accepted()
+        .insert_header((header::LOCATION, response.status_url.clone()))
+        .json(response))
+}
+
+/// GET /api/platforms/imports/:import_id
+/// Progress of an import
+#[utoipa::path(
+    get,
+    path = "/platforms/imports/{import_id}",
+    tag = "platforms",
+    params(("import_id" = String, Path, description = "Import ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Import progress", body = ImportResponse),
+        (status = 404, description = "Import not found")
+    )
+)]
+pub async fn get_import(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let import_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid import ID: {}", e)))?;
+
+    let import = activity_import::Entity::find_by_id(import_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .filter(|i| i.user_id == user_id)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Import not found"))?;
+
+    Ok(HttpResponse::Ok().json(import_response(&req, import)))
+}
+
+/// GET /api/platforms/imports
+/// The user's latest imports, newest first
+#[utoipa::path(
+    get,
+    path = "/platforms/imports",
+    tag = "platforms",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Up to 50 imports, newest first", body = [ImportResponse]))
+)]
+pub async fn list_imports(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let imports = activity_import::Entity::find()
+        .filter(activity_import::Column::UserId.eq(user_id))
+        .order_by_desc(activity_import::Column::CreatedAt)
+        .limit(LIST_LIMIT)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let response: Vec<ImportResponse> = imports
+        .into_iter()
+        .map(|import| import_response(&req, import))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Lets the import endpoint take bodies up to [`MAX_UPLOAD_BYTES`]
+pub fn payload_config() -> web::PayloadConfig {
+    web::PayloadConfig::new(MAX_UPLOAD_BYTES)
+}
@@ -0,0 +1,596 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::models::{
+    heatmap_generation_job::{self, GenerationJobStatus},
+    platform_sync_job::{self, SyncJobStatus},
+    user, user_job_limit,
+};
+use crate::handlers::heatmap_generation::{job_status_name, parse_job_status};
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::services::job_processor::{self, RetryPolicy};
+use crate::utils::config::Config;
+use crate::utils::pagination::{self, Page, PageParams};
+
+const MAX_CONCURRENCY_OVERRIDE: i32 = 100;
+
+const CANCELLED_MESSAGE: &str = "Cancelled by admin";
+
+/// Which job queue a request is about
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    Generation,
+    Sync,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AdminJobsQuery {
+    /// pending, processing, completed or failed; generation jobs also
+    /// dead_letter
+    pub status: Option<String>,
+    /// Only jobs of this user
+    pub user_id: Option<Uuid>,
+}
+
+/// A heatmap generation or platform sync job of any user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminJobResponse {
+    /// `generation` or `sync`
+    pub kind: String,
+    pub id: String,
+    pub user_id: String,
+    /// None if the user could not be found
+    pub username: Option<String>,
+    pub status: String,
+    /// Generation jobs: the theme, or None for all themes
+    pub theme_id: Option<String>,
+    /// Sync jobs: the platform account being synced
+    pub platform_account_id: Option<String>,
+    pub scheduled_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub error_message: Option<String>,
+    pub retry_count: i32,
+    pub max_retries: i32,
+    pub is_manual: bool,
+    pub priority: i32,
+    pub contributions_synced: Option<i32>,
+    pub activities_synced: Option<i32>,
+}
+
+impl From<heatmap_generation_job::Model> for AdminJobResponse {
+    fn from(model: heatmap_generation_job::Model) -> Self {
+        Self {
+            kind: "generation".to_string(),
+            id: model.id.to_string(),
+            user_id: model.user_id.to_string(),
+            username: None,
+            status: job_status_name(&model.status).to_string(),
+            theme_id: model.theme_id.map(|id| id.to_string()),
+            platform_account_id: None,
+            scheduled_at: model.scheduled_at.to_rfc3339(),
+            started_at: model.started_at.map(|t| t.to_rfc3339()),
+            completed_at: model.completed_at.map(|t| t.to_rfc3339()),
+            error_message: model.error_message,
+            retry_count: model.retry_count,
+            max_retries: model.max_retries,
+            is_manual: model.is_manual,
+            priority: model.priority,
+            contributions_synced: None,
+            activities_synced: None,
+        }
+    }
+}
+
+impl From<platform_sync_job::Model> for AdminJobResponse {
+    fn from(model: platform_sync_job::Model) -> Self {
+        Self {
+            kind: "sync".to_string(),
+            id: model.id.to_string(),
+            user_id: model.user_id.to_string(),
+            username: None,
+            status: format!("{:?}", model.status).to_lowercase(),
+            theme_id: None,
+            platform_account_id: Some(model.platform_account_id.to_string()),
+            scheduled_at: model.scheduled_at.to_rfc3339(),
+            started_at: model.started_at.map(|t| t.to_rfc3339()),
+            completed_at: model.completed_at.map(|t| t.to_rfc3339()),
+            error_message: model.error_message,
+            retry_count: model.retry_count,
+            max_retries: model.max_retries,
+            is_manual: model.is_manual,
+            priority: model.priority,
+            contributions_synced: model.contributions_synced,
+            activities_synced: model.activities_synced,
+        }
+    }
+}
+
+/// Sets or clears a user's concurrency override
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct JobConcurrencyRequest {
+    /// Jobs of this user picked up at once (1-100); null restores the default
+    pub max_concurrent_jobs: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobConcurrencyOverride {
+    pub user_id: String,
+    pub username: Option<String>,
+    pub max_concurrent_jobs: i32,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobConcurrencyResponse {
+    /// JOB_CONCURRENCY_PER_USER; 0 means unlimited
+    pub default: u32,
+    pub overrides: Vec<JobConcurrencyOverride>,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+fn bad_request(message: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({ "error": message }))
+}
+
+/// Usernames of the given users
+async fn usernames(
+    db: &DatabaseConnection,
+    user_ids: Vec<Uuid>,
+) -> Result<HashMap<Uuid, String>, DbErr> {
+    if user_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let users = user::Entity::find()
+        .filter(user::Column::Id.is_in(user_ids))
+        .all(db)
+        .await?;
+
+    Ok(users.into_iter().map(|u| (u.id, u.username)).collect())
+}
+
+/// Turn a page of jobs into the admin representation, filling in usernames
+async fn with_usernames<T: Into<AdminJobResponse>>(
+    db: &DatabaseConnection,
+    page: Page<T>,
+) -> Result<Page<AdminJobResponse>, DbErr> {
+    let page = page.map(Into::<AdminJobResponse>::into);
+    let user_ids = page
+        .items
+        .iter()
+        .filter_map(|job| Uuid::parse_str(&job.user_id).ok())
+        .collect();
+    let names = usernames(db, user_ids).await?;
+
+    Ok(page.map(|mut job| {
+        job.username = Uuid::parse_str(&job.user_id)
+            .ok()
+            .and_then(|id| names.get(&id).cloned());
+        job
+    }))
+}
+
+/// GET /api/admin/jobs/:kind
+/// List generation or sync jobs across all users (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/jobs/{kind}",
+    tag = "admin",
+    params(
+        ("kind" = String, Path, description = "generation or sync"),
+        AdminJobsQuery,
+        PageParams
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Jobs, newest first; total in X-Total-Count, next page in X-Next-Cursor", body = [AdminJobResponse]),
+        (status = 400, description = "Invalid status, sort or cursor"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn list_jobs(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<JobKind>,
+    query: web::Query<AdminJobsQuery>,
+    page: web::Query<PageParams>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let jobs = match path.into_inner() {
+        JobKind::Generation => {
+            let mut select = heatmap_generation_job::Entity::find();
+            if let Some(status) = &query.status {
+                let Some(status) = parse_job_status(status) else {
+                    return Ok(bad_request("Invalid status"));
+                };
+                select = select.filter(heatmap_generation_job::Column::Status.eq(status));
+            }
+            if let Some(owner) = query.user_id {
+                select = select.filter(heatmap_generation_job::Column::UserId.eq(owner));
+            }
+
+            let page = match pagination::fetch_page(
+                db.as_ref(),
+                select,
+                &page,
+                &[
+                    ("created_at", heatmap_generation_job::Column::CreatedAt),
+                    ("scheduled_at", heatmap_generation_job::Column::ScheduledAt),
+                    ("completed_at", heatmap_generation_job::Column::CompletedAt),
+                    ("priority", heatmap_generation_job::Column::Priority),
+                ],
+                "-created_at",
+                heatmap_generation_job::Column::Id,
+            )
+            .await
+            .map_err(db_error)?
+            {
+                Ok(p) => p,
+                Err(e) => return Ok(bad_request(&e)),
+            };
+            with_usernames(db.as_ref(), page).await.map_err(db_error)?
+        }
+        JobKind::Sync => {
+            let mut select = platform_sync_job::Entity::find();
+            if let Some(status) = &query.status {
+                let status = match status.as_str() {
+                    "pending" => SyncJobStatus::Pending,
+                    "processing" => SyncJobStatus::Processing,
+                    "completed" => SyncJobStatus::Completed,
+                    "failed" => SyncJobStatus::Failed,
+                    _ => return Ok(bad_request("Invalid status")),
+                };
+                select = select.filter(platform_sync_job::Column::Status.eq(status));
+            }
+            if let Some(owner) = query.user_id {
+                select = select.filter(platform_sync_job::Column::UserId.eq(owner));
+            }
+
+            let page = match pagination::fetch_page(
+                db.as_ref(),
+                select,
+                &page,
+                &[
+                    ("created_at", platform_sync_job::Column::CreatedAt),
+                    ("scheduled_at", platform_sync_job::Column::ScheduledAt),
+                    ("completed_at", platform_sync_job::Column::CompletedAt),
+                    ("priority", platform_sync_job::Column::Priority),
+                ],
+                "-created_at",
+                platform_sync_job::Column::Id,
+            )
+            .await
+            .map_err(db_error)?
+            {
+                Ok(p) => p,
+                Err(e) => return Ok(bad_request(&e)),
+            };
+            with_usernames(db.as_ref(), page).await.map_err(db_error)?
+        }
+    };
+
+    Ok(jobs.into_response(page.fields.as_deref()))
+}
+
+/// POST /api/admin/jobs/:kind/:id/cancel
+/// Cancel a pending or running job of any user (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/jobs/{kind}/{id}/cancel",
+    tag = "admin",
+    params(
+        ("kind" = String, Path, description = "generation or sync"),
+        ("id" = String, Path, description = "Job ID")
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Job cancelled", body = AdminJobResponse),
+        (status = 400, description = "Job already finished"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn cancel_job(
+    db: web::Data<DatabaseConnection>,
+    events: web::Data<EventBus>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<(JobKind, String)>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let (kind, job_id) = path.into_inner();
+    let job_id = Uuid::parse_str(&job_id)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid job ID: {}", e)))?;
+
+    let cancelled: AdminJobResponse = match kind {
+        JobKind::Generation => {
+            let job = heatmap_generation_job::Entity::find_by_id(job_id)
+                .one(db.as_ref())
+                .await
+                .map_err(db_error)?
+                .ok_or_else(|| actix_web::error::ErrorNotFound("Job not found"))?;
+
+            if !matches!(job.status, GenerationJobStatus::Pending | GenerationJobStatus::Processing) {
+                return Ok(bad_request("Can only cancel pending or processing jobs"));
+            }
+
+            let mut active: heatmap_generation_job::ActiveModel = job.into();
+            active.status = Set(GenerationJobStatus::Failed);
+            active.completed_at = Set(Some(Utc::now()));
+            active.error_message = Set(Some(CANCELLED_MESSAGE.to_string()));
+            let job = active.update(db.as_ref()).await.map_err(db_error)?;
+
+            events.publish(
+                job.user_id,
+                DashboardEvent::GenerationFinished {
+                    job_id: job.id,
+                    theme_id: job.theme_id,
+                    status: job.status.clone(),
+                    error: job.error_message.clone(),
+                },
+            );
+            job.into()
+        }
+        JobKind::Sync => {
+            let job = platform_sync_job::Entity::find_by_id(job_id)
+                .one(db.as_ref())
+                .await
+                .map_err(db_error)?
+                .ok_or_else(|| actix_web::error::ErrorNotFound("Job not found"))?;
+
+            if !matches!(job.status, SyncJobStatus::Pending | SyncJobStatus::Processing) {
+                return Ok(bad_request("Can only cancel pending or processing jobs"));
+            }
+
+            let mut active: platform_sync_job::ActiveModel = job.into();
+            active.status = Set(SyncJobStatus::Failed);
+            active.completed_at = Set(Some(Utc::now()));
+            active.error_message = Set(Some(CANCELLED_MESSAGE.to_string()));
+            let job = active.update(db.as_ref()).await.map_err(db_error)?;
+
+            events.publish(
+                job.user_id,
+                DashboardEvent::SyncFinished {
+                    job_id: job.id,
+                    platform_account_id: job.platform_account_id,
+                    status: job.status.clone(),
+                    contributions_synced: job.contributions_synced.unwrap_or(0),
+                    activities_synced: job.activities_synced.unwrap_or(0),
+                    error: job.error_message.clone(),
+                },
+            );
+            job.into()
+        }
+    };
+
+    log::info!("🚫 [Admin] {:?} job {} cancelled by {}", kind, job_id, user_id);
+
+    Ok(HttpResponse::Ok().json(cancelled))
+}
+
+/// POST /api/admin/jobs/:kind/:id/retry
+/// Queue a failed or dead-lettered job again with a fresh retry budget (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/jobs/{kind}/{id}/retry",
+    tag = "admin",
+    params(
+        ("kind" = String, Path, description = "generation or sync"),
+        ("id" = String, Path, description = "Job ID")
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Job queued again", body = AdminJobResponse),
+        (status = 400, description = "Job has not failed or been dead-lettered"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn retry_job(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<(JobKind, String)>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let (kind, job_id) = path.into_inner();
+    let job_id = Uuid::parse_str(&job_id)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid job ID: {}", e)))?;
+
+    let now = Utc::now();
+    let queued: AdminJobResponse = match kind {
+        JobKind::Generation => {
+            let job = heatmap_generation_job::Entity::find_by_id(job_id)
+                .one(db.as_ref())
+                .await
+                .map_err(db_error)?
+                .ok_or_else(|| actix_web::error::ErrorNotFound("Job not found"))?;
+
+            if !matches!(job.status, GenerationJobStatus::Failed | GenerationJobStatus::DeadLetter) {
+                return Ok(bad_request("Can only retry failed or dead-lettered jobs"));
+            }
+
+            job_processor::requeue(db.as_ref(), job, RetryPolicy::from_config(&config))
+                .await
+                .map_err(db_error)?
+                .into()
+        }
+        JobKind::Sync => {
+            let job = platform_sync_job::Entity::find_by_id(job_id)
+                .one(db.as_ref())
+                .await
+                .map_err(db_error)?
+                .ok_or_else(|| actix_web::error::ErrorNotFound("Job not found"))?;
+
+            if job.status != SyncJobStatus::Failed {
+                return Ok(bad_request("Can only retry failed jobs"));
+            }
+
+            let mut active: platform_sync_job::ActiveModel = job.into();
+            active.status = Set(SyncJobStatus::Pending);
+            active.scheduled_at = Set(now);
+            active.started_at = Set(None);
+            active.completed_at = Set(None);
+            active.error_message = Set(None);
+            active.retry_count = Set(0);
+            active.contributions_synced = Set(None);
+            active.activities_synced = Set(None);
+            active.years_completed = Set(None);
+            active.update(db.as_ref()).await.map_err(db_error)?.into()
+        }
+    };
+
+    log::info!("🔁 [Admin] {:?} job {} queued again by {}", kind, job_id, user_id);
+
+    Ok(HttpResponse::Ok().json(queued))
+}
+
+/// GET /api/admin/jobs/concurrency
+/// The default per-user job concurrency and the users that override it (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/jobs/concurrency",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Default and per-user concurrency", body = JobConcurrencyResponse),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn get_job_concurrency(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let limits = user_job_limit::Entity::find()
+        .order_by_desc(user_job_limit::Column::UpdatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    let names = usernames(db.as_ref(), limits.iter().map(|l| l.user_id).collect())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(JobConcurrencyResponse {
+        default: config.job_concurrency_per_user,
+        overrides: limits
+            .into_iter()
+            .map(|limit| JobConcurrencyOverride {
+                user_id: limit.user_id.to_string(),
+                username: names.get(&limit.user_id).cloned(),
+                max_concurrent_jobs: limit.max_concurrent_jobs,
+                updated_at: limit.updated_at.to_rfc3339(),
+            })
+            .collect(),
+    }))
+}
+
+/// PUT /api/admin/jobs/concurrency/:user_id
+/// Override or restore a user's job concurrency (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/jobs/concurrency/{user_id}",
+    tag = "admin",
+    params(("user_id" = String, Path, description = "User ID")),
+    request_body = JobConcurrencyRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Override saved or removed"),
+        (status = 400, description = "Invalid concurrency"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn set_job_concurrency(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: web::Json<JobConcurrencyRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let target_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    user::Entity::find_by_id(target_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    match body.max_concurrent_jobs {
+        None => {
+            user_job_limit::Entity::delete_by_id(target_id)
+                .exec(db.as_ref())
+                .await
+                .map_err(db_error)?;
+
+            log::info!("⚙️  [Admin] Job concurrency of {} reset by {}", target_id, user_id);
+        }
+        Some(max) => {
+            if !(1..=MAX_CONCURRENCY_OVERRIDE).contains(&max) {
+                return Ok(bad_request(&format!(
+                    "max_concurrent_jobs must be between 1 and {}",
+                    MAX_CONCURRENCY_OVERRIDE
+                )));
+            }
+
+            let limit = user_job_limit::ActiveModel {
+                user_id: Set(target_id),
+                max_concurrent_jobs: Set(max),
+                updated_by: Set(Some(user_id)),
+                updated_at: Set(Utc::now()),
+            };
+            user_job_limit::Entity::insert(limit)
+                .on_conflict(
+                    OnConflict::column(user_job_limit::Column::UserId)
+                        .update_columns([
+                            user_job_limit::Column::MaxConcurrentJobs,
+                            user_job_limit::Column::UpdatedBy,
+                            user_job_limit::Column::UpdatedAt,
+                        ])
+                        .to_owned(),
+                )
+                .exec(db.as_ref())
+                .await
+                .map_err(db_error)?;
+
+            log::info!("⚙️  [Admin] Job concurrency of {} set to {} by {}", target_id, max, user_id);
+        }
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
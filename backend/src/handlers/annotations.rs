@@ -0,0 +1,256 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::annotation;
+use crate::utils::annotations::{self, MAX_ANNOTATIONS};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnnotationsQuery {
+    /// First date to include (YYYY-MM-DD)
+    pub from: Option<NaiveDate>,
+    /// Last date to include (YYYY-MM-DD)
+    pub to: Option<NaiveDate>,
+}
+
+/// Creates an annotation, or replaces it when updating
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AnnotationRequest {
+    pub date: NaiveDate,
+    /// Up to 200 characters, like "conference" or "shipped v2.0"
+    pub text: String,
+    /// A single emoji shown next to the text; blank or missing for none
+    pub emoji: Option<String>,
+}
+
+/// A private note on a date of the user's graph
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnnotationResponse {
+    pub id: String,
+    pub date: NaiveDate,
+    pub text: String,
+    pub emoji: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<annotation::Model> for AnnotationResponse {
+    fn from(model: annotation::Model) -> Self {
+        Self {
+            id: model.id.to_string(),
+            date: model.date,
+            text: model.text,
+            emoji: model.emoji,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// Check the text and emoji, returning them trimmed or a client error
+fn validate(body: &AnnotationRequest) -> Result<(String, Option<String>), String> {
+    let text = annotations::normalize_text(&body.text)?;
+    let emoji = annotations::normalize_emoji(body.emoji.as_deref())?;
+    Ok((text, emoji))
+}
+
+/// The user's annotations between two dates (inclusive, either open), by
+/// date and then creation time
+pub async fn annotations_between(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<AnnotationResponse>, DbErr> {
+    let mut query = annotation::Entity::find().filter(annotation::Column::UserId.eq(user_id));
+    if let Some(from) = from {
+        query = query.filter(annotation::Column::Date.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(annotation::Column::Date.lte(to));
+    }
+
+    let annotations = query
+        .order_by_asc(annotation::Column::Date)
+        .order_by_asc(annotation::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    Ok(annotations.into_iter().map(AnnotationResponse::from).collect())
+}
+
+/// An annotation of the user, or 404
+async fn find_annotation(
+    db: &DatabaseConnection,
+    id: &str,
+    user_id: Uuid,
+) -> Result<annotation::Model, actix_web::Error> {
+    let id = Uuid::parse_str(id).map_err(|_| actix_web::error::ErrorNotFound("Annotation not found"))?;
+
+    annotation::Entity::find_by_id(id)
+        .filter(annotation::Column::UserId.eq(user_id))
+        .one(db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Annotation not found"))
+}
+
+/// GET /api/annotations
+/// The user's annotations, optionally within a date range
+#[utoipa::path(
+    get,
+    path = "/annotations",
+    tag = "annotations",
+    params(AnnotationsQuery),
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Annotations, by date", body = [AnnotationResponse]))
+)]
+pub async fn list_annotations(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<AnnotationsQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let annotations = annotations_between(db.as_ref(), user_id, query.from, query.to)
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(annotations))
+}
+
+/// POST /api/annotations
+/// Mark a date of the user's graph
+#[utoipa::path(
+    post,
+    path = "/annotations",
+    tag = "annotations",
+    request_body = AnnotationRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Annotation created", body = AnnotationResponse),
+        (status = 400, description = "Invalid text or emoji, or too many annotations")
+    )
+)]
+pub async fn create_annotation(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<AnnotationRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let (text, emoji) = match validate(&body) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let existing = annotation::Entity::find()
+        .filter(annotation::Column::UserId.eq(user_id))
+        .count(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if existing >= MAX_ANNOTATIONS {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("At most {} annotations can be kept", MAX_ANNOTATIONS)
+        })));
+    }
+
+    let now = Utc::now();
+    let created = annotation::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        date: Set(body.date),
+        text: Set(text),
+        emoji: Set(emoji),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Created().json(AnnotationResponse::from(created)))
+}
+
+/// PUT /api/annotations/:id
+/// Replace an annotation's date, text and emoji
+#[utoipa::path(
+    put,
+    path = "/annotations/{id}",
+    tag = "annotations",
+    params(("id" = String, Path, description = "Annotation ID")),
+    request_body = AnnotationRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Annotation updated", body = AnnotationResponse),
+        (status = 400, description = "Invalid text or emoji"),
+        (status = 404, description = "Annotation not found")
+    )
+)]
+pub async fn update_annotation(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: web::Json<AnnotationRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let existing = find_annotation(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    let (text, emoji) = match validate(&body) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let mut active: annotation::ActiveModel = existing.into();
+    active.date = Set(body.date);
+    active.text = Set(text);
+    active.emoji = Set(emoji);
+    active.updated_at = Set(Utc::now());
+    let updated = active.update(db.as_ref()).await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(AnnotationResponse::from(updated)))
+}
+
+/// DELETE /api/annotations/:id
+/// Remove an annotation
+#[utoipa::path(
+    delete,
+    path = "/annotations/{id}",
+    tag = "annotations",
+    params(("id" = String, Path, description = "Annotation ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Annotation deleted"),
+        (status = 404, description = "Annotation not found")
+    )
+)]
+pub async fn delete_annotation(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let existing = find_annotation(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    annotation::Entity::delete_by_id(existing.id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,322 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::models::announcement;
+use crate::utils::pagination::{self, PageParams};
+
+/// Values of `announcements.severity`
+const SEVERITIES: &[&str] = &["info", "warning", "critical"];
+
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Creates an announcement, or replaces one when updating
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AnnouncementRequest {
+    /// Plain text shown in the banner
+    pub message: String,
+    /// `info` (default), `warning` or `critical`
+    #[serde(default = "default_severity")]
+    pub severity: String,
+    /// When to start showing it; omit to show it right away
+    pub starts_at: Option<DateTime<Utc>>,
+    /// When to stop showing it; omit to show it until deleted
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Whether users may hide it; defaults to true
+    #[serde(default = "default_dismissible")]
+    pub dismissible: bool,
+}
+
+fn default_severity() -> String {
+    "info".to_string()
+}
+
+fn default_dismissible() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnnouncementResponse {
+    pub id: String,
+    pub message: String,
+    pub severity: String,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub dismissible: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<announcement::Model> for AnnouncementResponse {
+    fn from(model: announcement::Model) -> Self {
+        Self {
+            id: model.id.to_string(),
+            message: model.message,
+            severity: model.severity,
+            starts_at: model.starts_at.map(|d| d.to_rfc3339()),
+            ends_at: model.ends_at.map(|d| d.to_rfc3339()),
+            dismissible: model.dismissible,
+            created_at: model.created_at.to_rfc3339(),
+            updated_at: model.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// Check an announcement, returning its trimmed message or a client error
+fn validate(body: &AnnouncementRequest) -> Result<String, String> {
+    let message = body.message.trim();
+    if message.is_empty() || message.len() > MAX_MESSAGE_LENGTH {
+        return Err(format!("message must be between 1 and {} characters", MAX_MESSAGE_LENGTH));
+    }
+    if !SEVERITIES.contains(&body.severity.as_str()) {
+        return Err(format!("severity must be one of: {}", SEVERITIES.join(", ")));
+    }
+    if let (Some(starts_at), Some(ends_at)) = (body.starts_at, body.ends_at) {
+        if ends_at <= starts_at {
+            return Err("ends_at must be after starts_at".to_string());
+        }
+    }
+    Ok(message.to_string())
+}
+
+/// GET /api/announcements
+/// Announcements currently shown to users, most severe first
+#[utoipa::path(
+    get,
+    path = "/announcements",
+    tag = "announcements",
+    responses((status = 200, description = "Active announcements", body = [AnnouncementResponse]))
+)]
+pub async fn list_active_announcements(
+    db: web::Data<DatabaseConnection>,
+) -> Result<impl Responder, actix_web::Error> {
+    let now = Utc::now();
+
+    let mut announcements = announcement::Entity::find()
+        .filter(
+            Condition::any()
+                .add(announcement::Column::StartsAt.is_null())
+                .add(announcement::Column::StartsAt.lte(now)),
+        )
+        .filter(
+            Condition::any()
+                .add(announcement::Column::EndsAt.is_null())
+                .add(announcement::Column::EndsAt.gt(now)),
+        )
+        .order_by_desc(announcement::Column::CreatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let rank = |severity: &str| SEVERITIES.iter().position(|&s| s == severity).unwrap_or(0);
+    announcements.sort_by_key(|a| std::cmp::Reverse(rank(&a.severity)));
+
+    Ok(HttpResponse::Ok().json(
+        announcements
+            .into_iter()
+            .map(AnnouncementResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// GET /api/admin/announcements
+/// List all announcements, including scheduled and expired ones (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/announcements",
+    tag = "admin",
+    params(PageParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Announcements; total in X-Total-Count, next page in X-Next-Cursor", body = [AnnouncementResponse]),
+        (status = 400, description = "Invalid sort or cursor"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn list_announcements(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    page: web::Query<PageParams>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let announcements = match pagination::fetch_page(
+        db.as_ref(),
+        announcement::Entity::find(),
+        &page,
+        &[
+            ("created_at", announcement::Column::CreatedAt),
+            ("starts_at", announcement::Column::StartsAt),
+            ("ends_at", announcement::Column::EndsAt),
+        ],
+        "created_at",
+        announcement::Column::Id,
+    )
+    .await
+    .map_err(db_error)?
+    {
+        Ok(p) => p,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    Ok(announcements
+        .map(AnnouncementResponse::from)
+        .into_response(page.fields.as_deref()))
+}
+
+/// POST /api/admin/announcements
+/// Create an announcement (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/announcements",
+    tag = "admin",
+    request_body = AnnouncementRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Announcement created", body = AnnouncementResponse),
+        (status = 400, description = "Invalid message, severity or time window"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn create_announcement(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<AnnouncementRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let message = match validate(&body) {
+        Ok(message) => message,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let model = announcement::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        message: Set(message),
+        severity: Set(body.severity.clone()),
+        starts_at: Set(body.starts_at),
+        ends_at: Set(body.ends_at),
+        dismissible: Set(body.dismissible),
+        created_by: Set(Some(user_id)),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    log::info!("📢 [Admin] Announcement {} created by {}", model.id, user_id);
+
+    Ok(HttpResponse::Created().json(AnnouncementResponse::from(model)))
+}
+
+/// PUT /api/admin/announcements/:id
+/// Replace an announcement (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/announcements/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "Announcement ID")),
+    request_body = AnnouncementRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Announcement updated", body = AnnouncementResponse),
+        (status = 400, description = "Invalid message, severity or time window"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Announcement not found")
+    )
+)]
+pub async fn update_announcement(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: web::Json<AnnouncementRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let announcement_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid announcement ID: {}", e)))?;
+
+    let message = match validate(&body) {
+        Ok(message) => message,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let existing = announcement::Entity::find_by_id(announcement_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Announcement not found"))?;
+
+    let mut active: announcement::ActiveModel = existing.into();
+    active.message = Set(message);
+    active.severity = Set(body.severity.clone());
+    active.starts_at = Set(body.starts_at);
+    active.ends_at = Set(body.ends_at);
+    active.dismissible = Set(body.dismissible);
+    active.updated_at = Set(Utc::now());
+    let updated = active.update(db.as_ref()).await.map_err(db_error)?;
+
+    log::info!("📢 [Admin] Announcement {} updated by {}", updated.id, user_id);
+
+    Ok(HttpResponse::Ok().json(AnnouncementResponse::from(updated)))
+}
+
+/// DELETE /api/admin/announcements/:id
+/// Delete an announcement (admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/announcements/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "Announcement ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Announcement deleted"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Announcement not found")
+    )
+)]
+pub async fn delete_announcement(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let announcement_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid announcement ID: {}", e)))?;
+
+    let result = announcement::Entity::delete_by_id(announcement_id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected == 0 {
+        return Err(actix_web::error::ErrorNotFound("Announcement not found"));
+    }
+
+    log::info!("🗑️  [Admin] Announcement {} deleted by {}", announcement_id, user_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,200 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::api_token;
+use crate::utils::api_token as api_token_utils;
+
+/// Longest lifetime a token can be created with
+const MAX_TOKEN_LIFETIME_DAYS: i64 = 365;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiTokenRequest {
+    pub name: Option<String>,
+    /// Scopes to grant, e.g. ["read:contributions"]
+    pub scopes: Vec<String>,
+    /// Days until the token expires; omit for a token that never expires
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTokenResponse {
+    pub id: String,
+    pub name: Option<String>,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiTokenResponse {
+    /// The token itself. It is only returned once and cannot be retrieved later.
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiTokenResponse,
+}
+
+impl From<api_token::Model> for ApiTokenResponse {
+    fn from(model: api_token::Model) -> Self {
+        Self {
+            id: model.id.to_string(),
+            name: model.name,
+            scopes: model.scopes.into(),
+            created_at: model.created_at.to_rfc3339(),
+            expires_at: model.expires_at.map(|d| d.to_rfc3339()),
+            last_used_at: model.last_used_at.map(|d| d.to_rfc3339()),
+        }
+    }
+}
+
+/// GET /api/tokens
+/// List the user's API tokens with their granted scopes
+#[utoipa::path(
+    get,
+    path = "/tokens",
+    tag = "tokens",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "API tokens, newest first", body = [ApiTokenResponse]))
+)]
+pub async fn list_tokens(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let tokens = api_token::Entity::find()
+        .filter(api_token::Column::UserId.eq(user_id))
+        .order_by_desc(api_token::Column::CreatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let response: Vec<ApiTokenResponse> = tokens.into_iter().map(ApiTokenResponse::from).collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// POST /api/tokens
+/// Create a scoped API token
+#[utoipa::path(
+    post,
+    path = "/tokens",
+    tag = "tokens",
+    request_body = CreateApiTokenRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Token created", body = CreatedApiTokenResponse),
+        (status = 400, description = "Unknown scope or invalid expiry")
+    )
+)]
+pub async fn create_token(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<CreateApiTokenRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let scopes = match api_token_utils::normalize_scopes(&body.scopes) {
+        Ok(s) => s,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e,
+                "available_scopes": api_token_utils::SCOPES,
+            })));
+        }
+    };
+
+    let expires_at = match body.expires_in_days {
+        Some(days) if !(1..=MAX_TOKEN_LIFETIME_DAYS).contains(&days) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("expires_in_days must be between 1 and {}", MAX_TOKEN_LIFETIME_DAYS)
+            })));
+        }
+        Some(days) => Some(Utc::now() + Duration::days(days)),
+        None => None,
+    };
+
+    let name = body
+        .name
+        .as_ref()
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    let token = api_token_utils::generate_token();
+
+    let model = api_token::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        token: Set(api_token_utils::hash_token(&token)),
+        name: Set(name),
+        scopes: Set(scopes.into()),
+        created_at: Set(Utc::now()),
+        expires_at: Set(expires_at),
+        last_used_at: Set(None),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    log::info!("🔑 Created API token {} with scopes {:?}", model.id, model.scopes);
+
+    Ok(HttpResponse::Created().json(CreatedApiTokenResponse {
+        token,
+        info: ApiTokenResponse::from(model),
+    }))
+}
+
+/// DELETE /api/tokens/{id}
+/// Revoke an API token
+#[utoipa::path(
+    delete,
+    path = "/tokens/{id}",
+    tag = "tokens",
+    params(("id" = Uuid, Path, description = "Token ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "Token not found")
+    )
+)]
+pub async fn delete_token(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let token_id = path.into_inner();
+
+    let result = api_token::Entity::delete_many()
+        .filter(api_token::Column::Id.eq(token_id))
+        .filter(api_token::Column::UserId.eq(user_id))
+        .exec(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    if result.rows_affected == 0 {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Token not found"
+        })));
+    }
+
+    log::info!("🗑️  Revoked API token {}", token_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,48 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::handlers::platform_accounts::ApiUsageQuery;
+use crate::services::api_usage::{self, AccountUsage};
+
+/// GET /api/admin/api-usage
+/// Platform accounts that made the most API calls, to spot accounts that
+/// sync far more than others (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/api-usage",
+    tag = "admin",
+    params(ApiUsageQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Up to 50 accounts, most calls first", body = Vec<AccountUsage>),
+        (status = 400, description = "Invalid number of hours"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn list_api_usage(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<ApiUsageQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let hours = query.hours.unwrap_or(24);
+    if !(1..=api_usage::RETENTION_HOURS).contains(&hours) {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "hours must be between 1 and {}",
+            api_usage::RETENTION_HOURS
+        )));
+    }
+
+    let accounts = api_usage::top_accounts(db.as_ref(), hours).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    Ok(HttpResponse::Ok().json(accounts))
+}
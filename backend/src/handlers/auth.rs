@@ -1,54 +1,256 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set, ColumnTrait, QueryFilter, PaginatorTrait};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, Set, ColumnTrait, QueryFilter, PaginatorTrait};
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema};
 use uuid::Uuid;
 
 use crate::models::{user, user_setting};
+use crate::services::captcha::{CaptchaProvider, CaptchaVerifier};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::invitations;
+use crate::services::profile_visibility;
+use crate::services::sessions::{self, Refresh};
+use crate::services::username_redirects;
 use crate::utils::auth::{create_jwt, hash_password, verify_password};
+use crate::utils::client_ip::client_ip;
 use crate::utils::config::Config;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RegisterRequest {
     pub username: String,
     pub password: String,
     pub email: Option<String>,
+    /// Invitation code, required while open registration is disabled
+    pub invite_code: Option<String>,
+    /// CAPTCHA widget response, required when a CAPTCHA is configured
+    pub captcha_token: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// CAPTCHA widget response, required when a CAPTCHA is configured
+    pub captcha_token: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CaptchaConfigResponse {
+    pub enabled: bool,
+    pub provider: Option<CaptchaProvider>,
+    /// Public key to render the widget with
+    pub site_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
+    /// Short-lived access token
     pub token: String,
+    /// Seconds until `token` expires
+    pub expires_in: i64,
+    /// Renews the access token at POST /auth/refresh; replaced on every use
+    pub refresh_token: String,
     pub user_id: String,
     pub username: String,
     pub is_admin: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
 }
 
+fn auth_response(
+    config: &Config,
+    user: &user::Model,
+    session_id: Uuid,
+    refresh_token: String,
+) -> Result<AuthResponse, jsonwebtoken::errors::Error> {
+    let token = create_jwt(user.id, session_id, &config.jwt_secret, config.access_token_ttl_minutes)?;
+    Ok(AuthResponse {
+        token,
+        expires_in: config.access_token_ttl_minutes * 60,
+        refresh_token,
+        user_id: user.id.to_string(),
+        username: user.username.clone(),
+        is_admin: user.is_admin,
+    })
+}
+
+/// Start a login session for the client making `req` and issue its tokens
+pub(crate) async fn start_session(
+    db: &DatabaseConnection,
+    config: &Config,
+    user: &user::Model,
+    req: &HttpRequest,
+) -> Result<AuthResponse, String> {
+    let user_agent = req
+        .headers()
+        .get(actix_web::http::header::USER_AGENT)
+        .and_then(|h| h.to_str().ok());
+    let ip_address = client_ip(req, &config.trusted_proxies).map(|ip| ip.to_string());
+
+    let (session, refresh_token) = sessions::create_session(
+        db,
+        user.id,
+        config.refresh_token_ttl_days,
+        user_agent,
+        ip_address.as_deref(),
+    )
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let response = auth_response(config, user, session.id, refresh_token)
+        .map_err(|e| format!("Failed to generate token: {}", e))?;
+
+    log::info!("🎫 JWT token generated for user '{}'", user.username);
+
+    Ok(response)
+}
+
+/// Verify the CAPTCHA sent with a registration or login, returning the error
+/// response when it does not pass
+async fn check_captcha(
+    captcha: &CaptchaVerifier,
+    config: &Config,
+    token: Option<&str>,
+    req: &HttpRequest,
+) -> Option<HttpResponse> {
+    let ip_address = client_ip(req, &config.trusted_proxies).map(|ip| ip.to_string());
+
+    match captcha.verify(token, ip_address.as_deref()).await {
+        Ok(true) => None,
+        Ok(false) => Some(HttpResponse::BadRequest().json(ErrorResponse {
+            error: "CAPTCHA verification failed".to_string(),
+        })),
+        Err(e) => {
+            log::error!("❌ Failed to verify CAPTCHA: {}", e);
+            Some(HttpResponse::BadGateway().json(ErrorResponse {
+                error: "CAPTCHA verification is unavailable".to_string(),
+            }))
+        }
+    }
+}
+
+/// GET /api/auth/captcha
+/// Which CAPTCHA, if any, the registration and login forms must show
+#[utoipa::path(
+    get,
+    path = "/auth/captcha",
+    tag = "auth",
+    responses((status = 200, description = "CAPTCHA settings for the frontend", body = CaptchaConfigResponse))
+)]
+pub async fn captcha_config(captcha: web::Data<CaptchaVerifier>) -> impl Responder {
+    HttpResponse::Ok().json(CaptchaConfigResponse {
+        enabled: captcha.is_enabled(),
+        provider: captcha.provider(),
+        site_key: captcha.site_key().map(str::to_string),
+    })
+}
+
+/// Create a user with default settings. The first user on the instance
+/// becomes the admin.
+pub(crate) async fn create_user(
+    db: &DatabaseConnection,
+    username: &str,
+    password_hash: Option<String>,
+    email: Option<String>,
+) -> Result<user::Model, DbErr> {
+    let is_first_user = user::Entity::find().count(db).await? == 0;
+
+    if is_first_user {
+        log::info!("👑 First user - granting admin privileges");
+    }
+
+    let user = user::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        username: Set(username.to_string()),
+        password_hash: Set(password_hash),
+        email: Set(email),
+        is_admin: Set(is_first_user),
+        oidc_subject: Set(None),
+        username_changed_at: Set(None),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+    }
+    .insert(db)
+    .await?;
+
+    log::info!("✅ User '{}' created successfully (ID: {})", user.username, user.id);
+
+    // Create default user settings
+    let user_settings = user_setting::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user.id),
+        show_private_contributions: Set(true),
+        hide_private_repo_names: Set(false),
+        heatmap_color_scheme: Set("green".to_string()),
+        heatmap_size: Set("medium".to_string()),
+        dark_mode_enabled: Set(false),
+        profile_visibility: Set(profile_visibility::PUBLIC.to_string()),
+        excluded_repositories: Set(Vec::new().into()),
+        embed_allowed_domains: Set(Vec::new().into()),
+        activity_retention_days: Set(None),
+        listed_in_directory: Set(false),
+        away_periods: Set(Vec::new().into()),
+        hatch_away_days: Set(false),
+        milestone_webhook_url: Set(None),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+    };
+
+    if let Err(e) = user_settings.insert(db).await {
+        log::error!("⚠️  Failed to create user settings: {}", e);
+    } else {
+        log::info!("✅ User settings created for '{}'", user.username);
+    }
+
+    Ok(user)
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "Account created", body = AuthResponse),
+        (status = 400, description = "Username already exists, invalid invitation code or failed CAPTCHA", body = ErrorResponse),
+        (status = 403, description = "Registration disabled and no invitation code given", body = ErrorResponse),
+        (status = 502, description = "The CAPTCHA provider could not be reached", body = ErrorResponse)
+    )
+)]
 pub async fn register(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
+    captcha: web::Data<CaptchaVerifier>,
+    instance: web::Data<InstanceSettingsService>,
+    http_req: HttpRequest,
     req: web::Json<RegisterRequest>,
 ) -> impl Responder {
     log::info!("📝 Registration attempt for username: {}", req.username);
 
-    // Check if registration is allowed
-    if !config.allow_registration {
+    let invite_code = req.invite_code.as_deref().map(str::trim).filter(|c| !c.is_empty());
+
+    // Check if registration is allowed; an invitation lets people in anyway
+    if !instance.current().await.allow_registration && invite_code.is_none() {
         log::warn!("❌ Registration attempt rejected - registration is disabled");
         return HttpResponse::Forbidden().json(ErrorResponse {
             error: "Registration is currently disabled".to_string(),
         });
     }
 
+    if let Some(response) = check_captcha(&captcha, &config, req.captcha_token.as_deref(), &http_req).await {
+        log::warn!("❌ Registration for '{}' rejected - CAPTCHA not passed", req.username);
+        return response;
+    }
+
     // Check if username already exists
     let existing_user = user::Entity::find()
         .filter(user::Column::Username.eq(&req.username))
@@ -71,6 +273,23 @@ pub async fn register(
         _ => {}
     }
 
+    // Recently renamed users keep their old name for a while
+    match username_redirects::is_reserved(db.get_ref(), &req.username, None).await {
+        Ok(true) => {
+            log::warn!("❌ Registration failed - username '{}' is reserved by a rename", req.username);
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                error: "Username already exists".to_string(),
+            });
+        }
+        Err(e) => {
+            log::error!("❌ Database error during registration: {}", e);
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            });
+        }
+        Ok(false) => {}
+    }
+
     // Hash password
     let password_hash = match hash_password(&req.password) {
         Ok(hash) => hash,
@@ -82,76 +301,43 @@ pub async fn register(
         }
     };
 
-    // Check if this is the first user (make them admin)
-    let user_count = user::Entity::find()
-        .count(db.get_ref())
-        .await
-        .unwrap_or(0);
-
-    let is_first_user = user_count == 0;
-
-    if is_first_user {
-        log::info!("👑 First user - granting admin privileges");
+    // Use up the invitation last, so a rejected sign-up does not spend it
+    if let Some(code) = invite_code {
+        match invitations::redeem(db.get_ref(), code, req.email.as_deref()).await {
+            Ok(true) => log::info!("🎟️  Invitation accepted for '{}'", req.username),
+            Ok(false) => {
+                log::warn!("❌ Registration failed - invalid invitation code for '{}'", req.username);
+                return HttpResponse::BadRequest().json(ErrorResponse {
+                    error: "Invalid or expired invitation code".to_string(),
+                });
+            }
+            Err(e) => {
+                log::error!("❌ Database error during registration: {}", e);
+                return HttpResponse::InternalServerError().json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                });
+            }
+        }
     }
 
     // Create user
     log::info!("💾 Creating user '{}'...", req.username);
-    let user_id = Uuid::new_v4();
-    let new_user = user::ActiveModel {
-        id: Set(user_id),
-        username: Set(req.username.clone()),
-        password_hash: Set(password_hash),
-        email: Set(req.email.clone()),
-        is_admin: Set(is_first_user),
-        created_at: Set(Utc::now()),
-        updated_at: Set(Utc::now()),
-    };
-
-    let user_result = new_user.insert(db.get_ref()).await;
+    let user_result = create_user(
+        db.get_ref(),
+        &req.username,
+        Some(password_hash),
+        req.email.clone(),
+    )
+    .await;
 
     match user_result {
-        Ok(user) => {
-            log::info!("✅ User '{}' created successfully (ID: {})", user.username, user.id);
-
-            // Create default user settings
-            let user_settings = user_setting::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                user_id: Set(user.id),
-                show_private_contributions: Set(true),
-                hide_private_repo_names: Set(false),
-                heatmap_color_scheme: Set("green".to_string()),
-                heatmap_size: Set("medium".to_string()),
-                dark_mode_enabled: Set(false),
-                created_at: Set(Utc::now()),
-                updated_at: Set(Utc::now()),
-            };
-
-            if let Err(e) = user_settings.insert(db.get_ref()).await {
-                log::error!("⚠️  Failed to create user settings: {}", e);
-            } else {
-                log::info!("✅ User settings created for '{}'", user.username);
+        Ok(user) => match start_session(db.get_ref(), &config, &user, &http_req).await {
+            Ok(response) => HttpResponse::Created().json(response),
+            Err(e) => {
+                log::error!("❌ Failed to start session: {}", e);
+                HttpResponse::InternalServerError().json(ErrorResponse { error: e })
             }
-
-            // Generate JWT
-            let token = match create_jwt(user.id, &config.jwt_secret, config.jwt_expiration_hours) {
-                Ok(t) => t,
-                Err(e) => {
-                    log::error!("❌ Failed to generate token: {}", e);
-                    return HttpResponse::InternalServerError().json(ErrorResponse {
-                        error: format!("Failed to generate token: {}", e),
-                    });
-                }
-            };
-
-            log::info!("🎫 JWT token generated for user '{}'", user.username);
-
-            HttpResponse::Created().json(AuthResponse {
-                token,
-                user_id: user.id.to_string(),
-                username: user.username,
-                is_admin: user.is_admin,
-            })
-        }
+        },
         Err(e) => {
             log::error!("❌ Failed to create user: {}", e);
             HttpResponse::InternalServerError().json(ErrorResponse {
@@ -161,13 +347,32 @@ pub async fn register(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in", body = AuthResponse),
+        (status = 400, description = "CAPTCHA failed", body = ErrorResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 502, description = "The CAPTCHA provider could not be reached", body = ErrorResponse)
+    )
+)]
 pub async fn login(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
+    captcha: web::Data<CaptchaVerifier>,
+    http_req: HttpRequest,
     req: web::Json<LoginRequest>,
 ) -> impl Responder {
     log::info!("🔐 Login attempt for username: {}", req.username);
 
+    if let Some(response) = check_captcha(&captcha, &config, req.captcha_token.as_deref(), &http_req).await {
+        log::warn!("❌ Login for '{}' rejected - CAPTCHA not passed", req.username);
+        return response;
+    }
+
     // Find user by username
     let user = user::Entity::find()
         .filter(user::Column::Username.eq(&req.username))
@@ -178,30 +383,26 @@ pub async fn login(
         Ok(Some(user)) => {
             log::info!("👤 User '{}' found, verifying password...", req.username);
 
+            // Users created through GitHub/GitLab sign-in have no password
+            let Some(password_hash) = user.password_hash.as_deref() else {
+                log::warn!("❌ User '{}' has no password set", req.username);
+                return HttpResponse::Unauthorized().json(ErrorResponse {
+                    error: "Invalid credentials".to_string(),
+                });
+            };
+
             // Verify password
-            match verify_password(&req.password, &user.password_hash) {
+            match verify_password(&req.password, password_hash) {
                 Ok(true) => {
                     log::info!("✅ Password verified for user '{}'", req.username);
 
-                    // Generate JWT
-                    let token = match create_jwt(user.id, &config.jwt_secret, config.jwt_expiration_hours) {
-                        Ok(t) => t,
+                    match start_session(db.get_ref(), &config, &user, &http_req).await {
+                        Ok(response) => HttpResponse::Ok().json(response),
                         Err(e) => {
-                            log::error!("❌ Failed to generate token: {}", e);
-                            return HttpResponse::InternalServerError().json(ErrorResponse {
-                                error: format!("Failed to generate token: {}", e),
-                            });
+                            log::error!("❌ Failed to start session: {}", e);
+                            HttpResponse::InternalServerError().json(ErrorResponse { error: e })
                         }
-                    };
-
-                    log::info!("🎫 JWT token generated for user '{}'", req.username);
-
-                    HttpResponse::Ok().json(AuthResponse {
-                        token,
-                        user_id: user.id.to_string(),
-                        username: user.username,
-                        is_admin: user.is_admin,
-                    })
+                    }
                 }
                 Ok(false) => {
                     log::warn!("❌ Invalid password for user '{}'", req.username);
@@ -231,3 +432,76 @@ pub async fn login(
         }
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New access and refresh tokens", body = AuthResponse),
+        (status = 401, description = "Invalid, expired or revoked refresh token", body = ErrorResponse)
+    )
+)]
+pub async fn refresh(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    req: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let outcome = sessions::refresh_session(db.get_ref(), &req.refresh_token, config.refresh_token_ttl_days)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let (session, refresh_token) = match outcome {
+        Refresh::Rotated { session, refresh_token } => (session, refresh_token),
+        Refresh::Reused | Refresh::Invalid => {
+            return Ok(HttpResponse::Unauthorized().json(ErrorResponse {
+                error: "Invalid or expired refresh token".to_string(),
+            }));
+        }
+    };
+
+    let user = user::Entity::find_by_id(session.user_id)
+        .one(db.get_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+    let response = auth_response(&config, &user, session.id, refresh_token).map_err(|e| {
+        log::error!("❌ Failed to generate token: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to generate token")
+    })?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses((status = 204, description = "Session ended"))
+)]
+pub async fn logout(
+    db: web::Data<DatabaseConnection>,
+    req: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let revoked = sessions::revoke_by_refresh_token(db.get_ref(), &req.refresh_token)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    if revoked {
+        log::info!("👋 Session ended by logout");
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
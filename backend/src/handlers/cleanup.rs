@@ -0,0 +1,76 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::services::cleanup::{self, CleanupReport};
+use crate::utils::config::Config;
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// GET /api/admin/cleanup
+/// Dry run of the cleanup job: what it would remove and the space it would reclaim (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/cleanup",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Files and records that would be removed", body = CleanupReport),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn preview_cleanup(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let report = cleanup::run(db.as_ref(), &config, true)
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// POST /api/admin/cleanup/run
+/// Run the cleanup job now instead of waiting for the daily run (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/cleanup/run",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Files and records removed and space reclaimed", body = CleanupReport),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn run_cleanup(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let report = cleanup::run(db.as_ref(), &config, false)
+        .await
+        .map_err(db_error)?;
+
+    log::info!(
+        "🧹 [Admin] Cleanup run by {} reclaimed {} bytes",
+        user_id,
+        report.reclaimed_bytes
+    );
+
+    Ok(HttpResponse::Ok().json(report))
+}
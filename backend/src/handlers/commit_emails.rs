@@ -0,0 +1,345 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{commit_email, user};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::mailer::Mailer;
+use crate::utils::api_token::hash_token;
+use crate::utils::auth::generate_refresh_token;
+use crate::utils::config::Config;
+use crate::utils::validators;
+
+/// Most commit emails a user can add
+const MAX_COMMIT_EMAILS: u64 = 20;
+
+/// How long the link sent to a commit email stays valid
+const VERIFICATION_TTL_HOURS: i64 = 24;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddCommitEmailRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConfirmCommitEmailQuery {
+    pub token: String,
+}
+
+/// An email address the user commits with. Once verified, detailed sync
+/// counts commits authored with it, or with a Co-authored-by trailer naming
+/// it, as the user's.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CommitEmailResponse {
+    /// Lowercase
+    pub email: String,
+    pub verified: bool,
+    pub verified_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<commit_email::Model> for CommitEmailResponse {
+    fn from(model: commit_email::Model) -> Self {
+        Self {
+            email: model.email,
+            verified: model.verified_at.is_some(),
+            verified_at: model.verified_at.map(|t| t.to_rfc3339()),
+            created_at: model.created_at.to_rfc3339(),
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// Store a new confirmation token for the email and mail the link to it
+async fn send_verification(
+    db: &DatabaseConnection,
+    config: &Config,
+    mailer: &Mailer,
+    email: commit_email::Model,
+) -> Result<(), actix_web::Error> {
+    // The token doubles as a random secret; only its hash is stored
+    let token = generate_refresh_token();
+    let address = email.email.clone();
+    let user_id = email.user_id;
+
+    let mut active: commit_email::ActiveModel = email.into();
+    active.token_hash = Set(Some(hash_token(&token)));
+    active.token_expires_at = Set(Some(Utc::now() + Duration::hours(VERIFICATION_TTL_HOURS)));
+    active.update(db).await.map_err(db_error)?;
+
+    let username = user::Entity::find_by_id(user_id)
+        .one(db)
+        .await
+        .map_err(db_error)?
+        .map(|u| u.username)
+        .unwrap_or_default();
+
+    let link = format!(
+        "{}/auth/commit-emails/confirm?token={}",
+        config.base_url,
+        urlencoding::encode(&token)
+    );
+    let message = format!(
+        "Hi {},\n\nOpen this link to count commits made with {} as yours on hgitmap:\n\n{}\n\nThe link expires in {} hours. If you did not ask for this, ignore this email.\n",
+        username, address, link, VERIFICATION_TTL_HOURS
+    );
+
+    mailer
+        .send(&address, "Confirm your commit email address", message)
+        .await
+        .map_err(|e| {
+            log::error!("❌ Failed to send commit email confirmation: {}", e);
+            actix_web::error::ErrorBadGateway("Failed to send confirmation email")
+        })
+}
+
+/// GET /api/settings/commit-emails
+/// The emails the user added for commit attribution, verified or not
+#[utoipa::path(
+    get,
+    path = "/settings/commit-emails",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Commit emails, alphabetically", body = [CommitEmailResponse]))
+)]
+pub async fn list_commit_emails(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let emails = commit_email::Entity::find()
+        .filter(commit_email::Column::UserId.eq(user_id))
+        .order_by_asc(commit_email::Column::Email)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(emails.into_iter().map(CommitEmailResponse::from).collect::<Vec<_>>()))
+}
+
+/// POST /api/settings/commit-emails
+/// Add an email the user commits with; a confirmation link is sent to it
+#[utoipa::path(
+    post,
+    path = "/settings/commit-emails",
+    tag = "settings",
+    request_body = AddCommitEmailRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Email added, confirmation email sent", body = CommitEmailResponse),
+        (status = 400, description = "Invalid email address, or too many emails"),
+        (status = 409, description = "Email already added"),
+        (status = 503, description = "Email is not configured on this instance")
+    )
+)]
+pub async fn add_commit_email(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<AddCommitEmailRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let email = body.email.trim().to_lowercase();
+    if let Err(e) = validators::validate_email(&email) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e.to_string()
+        })));
+    }
+
+    if !mailer.is_enabled() {
+        return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "error": "Email is not configured on this instance"
+        })));
+    }
+
+    let existing = commit_email::Entity::find()
+        .filter(commit_email::Column::UserId.eq(user_id))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if existing.iter().any(|row| row.email == email) {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Email already added"
+        })));
+    }
+    if existing.len() as u64 >= MAX_COMMIT_EMAILS {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("At most {} commit emails can be added", MAX_COMMIT_EMAILS)
+        })));
+    }
+
+    let created = commit_email::ActiveModel {
+        user_id: Set(user_id),
+        email: Set(email),
+        created_at: Set(Utc::now()),
+        verified_at: Set(None),
+        token_hash: Set(None),
+        token_expires_at: Set(None),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    // A failed mail leaves the email unverified; the link can be resent
+    send_verification(db.as_ref(), &config, &mailer, created.clone()).await?;
+
+    log::info!("📧 Commit email added for user {}", user_id);
+
+    Ok(HttpResponse::Created().json(CommitEmailResponse::from(created)))
+}
+
+/// POST /api/settings/commit-emails/:email/verify
+/// Send a new confirmation link to an unverified commit email
+#[utoipa::path(
+    post,
+    path = "/settings/commit-emails/{email}/verify",
+    tag = "settings",
+    params(("email" = String, Path, description = "Email address")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Confirmation email sent"),
+        (status = 404, description = "Email not found"),
+        (status = 409, description = "Email already verified"),
+        (status = 503, description = "Email is not configured on this instance")
+    )
+)]
+pub async fn resend_commit_email_verification(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let Some(email) = commit_email::Entity::find_by_id((user_id, path.into_inner().trim().to_lowercase()))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Email not found"
+        })));
+    };
+
+    if email.verified_at.is_some() {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Email already verified"
+        })));
+    }
+
+    if !mailer.is_enabled() {
+        return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "error": "Email is not configured on this instance"
+        })));
+    }
+
+    let address = email.email.clone();
+    send_verification(db.as_ref(), &config, &mailer, email).await?;
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "message": format!("A confirmation link was sent to {}", address)
+    })))
+}
+
+/// GET /api/auth/commit-emails/confirm
+/// Verify a commit email from the link in the confirmation email
+#[utoipa::path(
+    get,
+    path = "/auth/commit-emails/confirm",
+    tag = "auth",
+    params(ConfirmCommitEmailQuery),
+    responses((status = 302, description = "Redirect to the frontend settings page"))
+)]
+pub async fn confirm_commit_email(
+    db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
+    query: web::Query<ConfirmCommitEmailQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let frontend_url = instance.current().await.frontend_url;
+    let redirect = |result: &str| {
+        HttpResponse::Found()
+            .append_header(("Location", format!("{}/settings?commit_email={}", frontend_url, result)))
+            .finish()
+    };
+
+    let Some(email) = commit_email::Entity::find()
+        .filter(commit_email::Column::TokenHash.eq(hash_token(&query.token)))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(redirect("invalid"));
+    };
+
+    let expired = email.token_expires_at.is_none_or(|expires_at| expires_at < Utc::now());
+    let user_id = email.user_id;
+
+    let mut active: commit_email::ActiveModel = email.into();
+    active.token_hash = Set(None);
+    active.token_expires_at = Set(None);
+    if !expired {
+        active.verified_at = Set(Some(Utc::now()));
+    }
+    active.update(db.as_ref()).await.map_err(db_error)?;
+
+    if expired {
+        return Ok(redirect("expired"));
+    }
+
+    log::info!("✅ Commit email confirmed for user {}", user_id);
+
+    Ok(redirect("confirmed"))
+}
+
+/// DELETE /api/settings/commit-emails/:email
+/// Remove a commit email. Commits already attributed through it stay until
+/// detailed sync is turned off.
+#[utoipa::path(
+    delete,
+    path = "/settings/commit-emails/{email}",
+    tag = "settings",
+    params(("email" = String, Path, description = "Email address")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Email removed"),
+        (status = 404, description = "Email not found")
+    )
+)]
+pub async fn delete_commit_email(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let deleted = commit_email::Entity::delete_many()
+        .filter(commit_email::Column::UserId.eq(user_id))
+        .filter(commit_email::Column::Email.eq(path.into_inner().trim().to_lowercase()))
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if deleted.rows_affected == 0 {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Email not found"
+        })));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
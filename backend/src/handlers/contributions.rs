@@ -1,41 +1,155 @@
-use actix_web::{web, HttpResponse, Responder};
-use sea_orm::sea_query::{Expr, Func};
+use actix_web::web::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, Func, LikeExpr};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::models::{contribution, git_platform_account, user, user_setting};
+use crate::handlers::annotations::{annotations_between, AnnotationResponse};
+use crate::models::{activity, contribution, git_platform_account, user, user_setting};
+use crate::models::user_setting::AwayPeriod;
+use crate::services::commit_details::{self, HourCount};
+use crate::services::profile_visibility;
+use crate::services::response_cache::ResponseCache;
+use crate::services::username_redirects;
+use crate::utils::config::Config;
+use crate::utils::conditional_get::Validators;
+use crate::utils::code_frequency::WeekLines;
+use crate::utils::{away_periods, repo_exclusion};
+
+/// A public profile response in the [`ResponseCache`], keyed by endpoint,
+/// username and query string
+#[derive(Serialize, Deserialize)]
+struct CachedProfileResponse {
+    validators: Validators,
+    body: String,
+}
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ContributionsQuery {
     pub from: Option<String>,
     pub to: Option<String>,
     pub platform: Option<String>, // Filter by platform: "github", "gitea", etc.
+    pub group_by: Option<String>, // "platform" to split counts per connected account
+    pub organization: Option<String>, // Only count repositories owned by this organization
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrganizationContributionsQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub platform: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CodeFrequencyQuery {
+    /// First day, `YYYY-MM-DD`; 52 weeks before `to` by default
+    pub from: Option<String>,
+    /// Last day, `YYYY-MM-DD`; today by default
+    pub to: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ContributionDay {
     pub date: String,
     pub count: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ContributionsResponse {
     pub contributions: Vec<ContributionDay>,
     pub total_count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<PlatformContributions>>,
+    /// The user's own annotations within `from`/`to`, for the heatmap tooltip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<AnnotationResponse>>,
 }
 
-#[derive(Debug, Serialize)]
+/// Daily contributions of a single connected platform account
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PlatformContributions {
+    pub account_id: String,
+    pub platform: String,
+    pub platform_username: String,
+    pub platform_url: Option<String>,
+    pub contributions: Vec<ContributionDay>,
+    pub total_count: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ContributionStatsResponse {
     pub total_contributions: i32,
     pub current_streak: i32,
     pub longest_streak: i32,
     pub active_platforms: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<PlatformStats>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RepositoryContribution {
+    pub name: String,
+    pub count: i32,
+}
+
+/// Contributions aggregated for one organization (repository owner)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrganizationContribution {
+    pub organization: String,
+    pub avatar_url: Option<String>,
+    pub is_personal: bool,
+    pub total_contributions: i32,
+    pub active_days: i32,
+    pub first_contribution: String,
+    pub last_contribution: String,
+    pub repositories: Vec<RepositoryContribution>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrganizationContributionsResponse {
+    pub organizations: Vec<OrganizationContribution>,
+    pub total_count: i32,
+    pub unattributed_count: i32,
+}
+
+/// Contribution statistics of a single connected platform account
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PlatformStats {
+    pub account_id: String,
+    pub platform: String,
+    pub platform_username: String,
+    pub platform_url: Option<String>,
+    pub total_contributions: i32,
+    pub current_streak: i32,
+    pub longest_streak: i32,
 }
 
 /// GET /api/contributions
 /// Get aggregated contribution data for heatmap
+#[utoipa::path(
+    get,
+    path = "/contributions",
+    tag = "contributions",
+    params(ContributionsQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Daily contribution counts", body = ContributionsResponse),
+        (status = 400, description = "Invalid filter")
+    )
+)]
 pub async fn get_contributions(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -59,6 +173,22 @@ pub async fn get_contributions(
         .map(|s| s.show_private_contributions)
         .unwrap_or(true);
 
+    let from = query.from.as_deref().and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let to = query.to.as_deref().and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let annotations = annotations_between(db.as_ref(), user_id, from, to)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let group_by_platform = match parse_group_by(query.group_by.as_deref()) {
+        Ok(grouped) => grouped,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
     // Get all active platform accounts for this user
     let mut accounts_query = git_platform_account::Entity::find()
         .filter(git_platform_account::Column::UserId.eq(user_id))
@@ -90,6 +220,8 @@ pub async fn get_contributions(
         return Ok(HttpResponse::Ok().json(ContributionsResponse {
             contributions: vec![],
             total_count: 0,
+            platforms: group_by_platform.then(Vec::new),
+            annotations: Some(annotations),
         }));
     }
 
@@ -105,19 +237,20 @@ pub async fn get_contributions(
             contribution_query.filter(contribution::Column::IsPrivateRepo.eq(false));
     }
 
+    // Restrict to repositories owned by an organization if requested
+    if let Some(organization) = &query.organization {
+        contribution_query = contribution_query.filter(organization_filter(organization));
+    }
+
     // Apply date range if provided
-    if let Some(from_str) = &query.from {
-        if let Ok(from_date) = chrono::NaiveDate::parse_from_str(from_str, "%Y-%m-%d") {
-            contribution_query =
-                contribution_query.filter(contribution::Column::ContributionDate.gte(from_date));
-        }
+    if let Some(from_date) = from {
+        contribution_query =
+            contribution_query.filter(contribution::Column::ContributionDate.gte(from_date));
     }
 
-    if let Some(to_str) = &query.to {
-        if let Ok(to_date) = chrono::NaiveDate::parse_from_str(to_str, "%Y-%m-%d") {
-            contribution_query =
-                contribution_query.filter(contribution::Column::ContributionDate.lte(to_date));
-        }
+    if let Some(to_date) = to {
+        contribution_query =
+            contribution_query.filter(contribution::Column::ContributionDate.lte(to_date));
     }
 
     let contributions = contribution_query.all(db.as_ref()).await.map_err(|e| {
@@ -125,37 +258,38 @@ pub async fn get_contributions(
         actix_web::error::ErrorInternalServerError("Database error")
     })?;
 
-    // Aggregate contributions by date
-    let mut contribution_map: HashMap<chrono::NaiveDate, i32> = HashMap::new();
-    for contrib in contributions {
-        *contribution_map
-            .entry(contrib.contribution_date)
-            .or_insert(0) += contrib.count;
-    }
+    let platforms = group_by_platform
+        .then(|| group_contributions_by_platform(&accounts, &contributions));
 
-    let mut contribution_days: Vec<ContributionDay> = contribution_map
-        .iter()
-        .map(|(date, count)| ContributionDay {
-            date: date.format("%Y-%m-%d").to_string(),
-            count: *count,
-        })
-        .collect();
-
-    contribution_days.sort_by(|a, b| a.date.cmp(&b.date));
+    let contribution_days = aggregate_by_date(contributions.iter());
 
     let total_count: i32 = contribution_days.iter().map(|c| c.count).sum();
 
     Ok(HttpResponse::Ok().json(ContributionsResponse {
         contributions: contribution_days,
         total_count,
+        platforms,
+        annotations: Some(annotations),
     }))
 }
 
 /// GET /api/contributions/stats
 /// Get contribution statistics
+#[utoipa::path(
+    get,
+    path = "/contributions/stats",
+    tag = "contributions",
+    params(StatsQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Contribution statistics", body = ContributionStatsResponse),
+        (status = 400, description = "Invalid parameter")
+    )
+)]
 pub async fn get_stats(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<StatsQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
@@ -175,6 +309,18 @@ pub async fn get_stats(
         .map(|s| s.show_private_contributions)
         .unwrap_or(true);
 
+    let away_periods = settings
+        .as_ref()
+        .map(|s| s.away_periods.to_vec())
+        .unwrap_or_default();
+
+    let group_by_platform = match parse_group_by(query.group_by.as_deref()) {
+        Ok(grouped) => grouped,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
     // Get all active platform accounts
     let accounts = git_platform_account::Entity::find()
         .filter(git_platform_account::Column::UserId.eq(user_id))
@@ -194,6 +340,7 @@ pub async fn get_stats(
             current_streak: 0,
             longest_streak: 0,
             active_platforms: 0,
+            platforms: group_by_platform.then(Vec::new),
         }));
     }
 
@@ -218,22 +365,414 @@ pub async fn get_stats(
     let total_contributions: i32 = contributions.iter().map(|c| c.count).sum();
 
     // Calculate streaks
-    let (current_streak, longest_streak) = calculate_streaks(&contributions);
+    let (current_streak, longest_streak) = calculate_streaks(&contributions, &away_periods);
+
+    let platforms = group_by_platform.then(|| platform_stats(&accounts, &contributions, &away_periods));
 
     Ok(HttpResponse::Ok().json(ContributionStatsResponse {
         total_contributions,
         current_streak,
         longest_streak,
         active_platforms,
+        platforms,
     }))
 }
 
-/// Calculate current and longest contribution streaks
-fn calculate_streaks(contributions: &[contribution::Model]) -> (i32, i32) {
-    if contributions.is_empty() {
-        return (0, 0);
+/// GET /api/contributions/hours
+/// Commits per hour of the day in the user's timezone, from the accounts
+/// with detailed sync. All zero without it.
+#[utoipa::path(
+    get,
+    path = "/contributions/hours",
+    tag = "contributions",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Commits for each hour, 0 to 23", body = [HourCount])
+    )
+)]
+pub async fn get_hours(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let hours = commit_details::hours(db.as_ref(), user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    Ok(HttpResponse::Ok().json(hours))
+}
+
+/// GET /api/contributions/code-frequency
+/// Lines added and removed per week, from the commits of accounts with
+/// detailed sync
+#[utoipa::path(
+    get,
+    path = "/contributions/code-frequency",
+    tag = "contributions",
+    params(CodeFrequencyQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Weeks from the one of `from` to the one of `to`, oldest first", body = [WeekLines]),
+        (status = 400, description = "from is after to")
+    )
+)]
+pub async fn get_code_frequency(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<CodeFrequencyQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let to = query
+        .to
+        .as_deref()
+        .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let from = query
+        .from
+        .as_deref()
+        .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .unwrap_or(to - chrono::Duration::weeks(52));
+    if from > to {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "from must not be after to"
+        })));
+    }
+
+    let weeks = commit_details::code_frequency(db.as_ref(), user_id, from, to)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(weeks))
+}
+
+/// GET /api/contributions/by-organization
+/// Get contributions aggregated per organization (repository owner)
+#[utoipa::path(
+    get,
+    path = "/contributions/by-organization",
+    tag = "contributions",
+    params(OrganizationContributionsQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Contributions per organization", body = OrganizationContributionsResponse),
+        (status = 400, description = "Invalid filter")
+    )
+)]
+pub async fn get_contributions_by_organization(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<OrganizationContributionsQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    // Get user settings for privacy filtering
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let show_private_contributions = settings
+        .as_ref()
+        .map(|s| s.show_private_contributions)
+        .unwrap_or(true);
+
+    // Get all active platform accounts for this user
+    let mut accounts_query = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true));
+
+    // Filter by platform type if specified
+    if let Some(platform_filter) = &query.platform {
+        let platform_type = match platform_filter.to_lowercase().as_str() {
+            "github" => git_platform_account::GitPlatform::GitHub,
+            "gitea" => git_platform_account::GitPlatform::Gitea,
+            "gitlab" => git_platform_account::GitPlatform::GitLab,
+            _ => {
+                log::warn!("Invalid platform filter: {}", platform_filter);
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": format!("Invalid platform: {}", platform_filter)
+                })));
+            }
+        };
+        accounts_query =
+            accounts_query.filter(git_platform_account::Column::PlatformType.eq(platform_type));
     }
 
+    let accounts = accounts_query.all(db.as_ref()).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    if accounts.is_empty() {
+        return Ok(HttpResponse::Ok().json(OrganizationContributionsResponse {
+            organizations: vec![],
+            total_count: 0,
+            unattributed_count: 0,
+        }));
+    }
+
+    let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+
+    let mut contribution_query = contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids.clone()));
+
+    if !show_private_contributions {
+        contribution_query =
+            contribution_query.filter(contribution::Column::IsPrivateRepo.eq(false));
+    }
+
+    if let Some(from_str) = &query.from {
+        if let Ok(from_date) = chrono::NaiveDate::parse_from_str(from_str, "%Y-%m-%d") {
+            contribution_query =
+                contribution_query.filter(contribution::Column::ContributionDate.gte(from_date));
+        }
+    }
+
+    if let Some(to_str) = &query.to {
+        if let Ok(to_date) = chrono::NaiveDate::parse_from_str(to_str, "%Y-%m-%d") {
+            contribution_query =
+                contribution_query.filter(contribution::Column::ContributionDate.lte(to_date));
+        }
+    }
+
+    let contributions = contribution_query.all(db.as_ref()).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    // Organization avatars come from the org-join activities recorded during sync
+    let org_activities = activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
+        .filter(activity::Column::OrganizationName.is_not_null())
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let avatars: HashMap<String, String> = org_activities
+        .into_iter()
+        .filter_map(|a| Some((a.organization_name?.to_lowercase(), a.organization_avatar_url?)))
+        .collect();
+
+    let usernames: std::collections::HashSet<String> = accounts
+        .iter()
+        .map(|a| a.platform_username.to_lowercase())
+        .collect();
+
+    let (organizations, unattributed_count) =
+        aggregate_by_organization(&contributions, &usernames, &avatars);
+
+    let total_count = organizations.iter().map(|o| o.total_contributions).sum::<i32>()
+        + unattributed_count;
+
+    Ok(HttpResponse::Ok().json(OrganizationContributionsResponse {
+        organizations,
+        total_count,
+        unattributed_count,
+    }))
+}
+
+/// Group contributions by the owner segment of their repository name.
+/// Returns the organizations sorted by total contributions and the count
+/// of contributions that have no repository to attribute them to.
+fn aggregate_by_organization(
+    contributions: &[contribution::Model],
+    usernames: &std::collections::HashSet<String>,
+    avatars: &HashMap<String, String>,
+) -> (Vec<OrganizationContribution>, i32) {
+    struct OrgAccumulator {
+        name: String,
+        total: i32,
+        days: std::collections::HashSet<chrono::NaiveDate>,
+        first: chrono::NaiveDate,
+        last: chrono::NaiveDate,
+        repositories: HashMap<String, i32>,
+    }
+
+    let mut orgs: HashMap<String, OrgAccumulator> = HashMap::new();
+    let mut unattributed_count = 0;
+
+    for contrib in contributions {
+        let Some((owner, _)) = contrib
+            .repository_name
+            .as_deref()
+            .and_then(|name| name.split_once('/'))
+        else {
+            unattributed_count += contrib.count;
+            continue;
+        };
+
+        let entry = orgs
+            .entry(owner.to_lowercase())
+            .or_insert_with(|| OrgAccumulator {
+                name: owner.to_string(),
+                total: 0,
+                days: std::collections::HashSet::new(),
+                first: contrib.contribution_date,
+                last: contrib.contribution_date,
+                repositories: HashMap::new(),
+            });
+
+        entry.total += contrib.count;
+        entry.days.insert(contrib.contribution_date);
+        entry.first = entry.first.min(contrib.contribution_date);
+        entry.last = entry.last.max(contrib.contribution_date);
+        *entry
+            .repositories
+            .entry(contrib.repository_name.clone().unwrap_or_default())
+            .or_insert(0) += contrib.count;
+    }
+
+    let mut organizations: Vec<OrganizationContribution> = orgs
+        .into_iter()
+        .map(|(key, acc)| {
+            let mut repositories: Vec<RepositoryContribution> = acc
+                .repositories
+                .into_iter()
+                .map(|(name, count)| RepositoryContribution { name, count })
+                .collect();
+            repositories.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+
+            OrganizationContribution {
+                organization: acc.name,
+                avatar_url: avatars.get(&key).cloned(),
+                is_personal: usernames.contains(&key),
+                total_contributions: acc.total,
+                active_days: acc.days.len() as i32,
+                first_contribution: acc.first.format("%Y-%m-%d").to_string(),
+                last_contribution: acc.last.format("%Y-%m-%d").to_string(),
+                repositories,
+            }
+        })
+        .collect();
+
+    organizations.sort_by(|a, b| {
+        b.total_contributions
+            .cmp(&a.total_contributions)
+            .then(a.organization.cmp(&b.organization))
+    });
+
+    (organizations, unattributed_count)
+}
+
+/// Match contributions to repositories owned by the given organization (case-insensitive)
+fn organization_filter(organization: &str) -> sea_orm::sea_query::SimpleExpr {
+    let escaped = organization
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    Expr::expr(Func::lower(Expr::col(contribution::Column::RepositoryName)))
+        .like(LikeExpr::new(format!("{}/%", escaped)).escape('\\'))
+}
+
+/// Parse the `group_by` query parameter, returning whether to split by platform
+fn parse_group_by(group_by: Option<&str>) -> Result<bool, String> {
+    match group_by.map(|g| g.to_lowercase()) {
+        None => Ok(false),
+        Some(g) if g == "platform" => Ok(true),
+        Some(g) => Err(format!("Invalid group_by: {}", g)),
+    }
+}
+
+/// Sum contribution counts per date, sorted ascending
+fn aggregate_by_date<'a>(
+    contributions: impl Iterator<Item = &'a contribution::Model>,
+) -> Vec<ContributionDay> {
+    let mut contribution_map: HashMap<chrono::NaiveDate, i32> = HashMap::new();
+    for contrib in contributions {
+        *contribution_map
+            .entry(contrib.contribution_date)
+            .or_insert(0) += contrib.count;
+    }
+
+    let mut contribution_days: Vec<ContributionDay> = contribution_map
+        .iter()
+        .map(|(date, count)| ContributionDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            count: *count,
+        })
+        .collect();
+
+    contribution_days.sort_by(|a, b| a.date.cmp(&b.date));
+    contribution_days
+}
+
+/// Split contributions into a daily series per platform account
+fn group_contributions_by_platform(
+    accounts: &[git_platform_account::Model],
+    contributions: &[contribution::Model],
+) -> Vec<PlatformContributions> {
+    accounts
+        .iter()
+        .map(|account| {
+            let days = aggregate_by_date(
+                contributions
+                    .iter()
+                    .filter(|c| c.git_platform_account_id == account.id),
+            );
+            let total_count = days.iter().map(|c| c.count).sum();
+
+            PlatformContributions {
+                account_id: account.id.to_string(),
+                platform: format!("{:?}", account.platform_type).to_lowercase(),
+                platform_username: account.platform_username.clone(),
+                platform_url: account.platform_url.clone(),
+                contributions: days,
+                total_count,
+            }
+        })
+        .collect()
+}
+
+/// Compute totals and streaks per platform account
+fn platform_stats(
+    accounts: &[git_platform_account::Model],
+    contributions: &[contribution::Model],
+    away: &[AwayPeriod],
+) -> Vec<PlatformStats> {
+    accounts
+        .iter()
+        .map(|account| {
+            let account_contributions: Vec<contribution::Model> = contributions
+                .iter()
+                .filter(|c| c.git_platform_account_id == account.id)
+                .cloned()
+                .collect();
+            let (current_streak, longest_streak) = calculate_streaks(&account_contributions, away);
+
+            PlatformStats {
+                account_id: account.id.to_string(),
+                platform: format!("{:?}", account.platform_type).to_lowercase(),
+                platform_username: account.platform_username.clone(),
+                platform_url: account.platform_url.clone(),
+                total_contributions: account_contributions.iter().map(|c| c.count).sum(),
+                current_streak,
+                longest_streak,
+            }
+        })
+        .collect()
+}
+
+/// Calculate current and longest contribution streaks. A gap made only of
+/// away days does not break a streak.
+fn calculate_streaks(contributions: &[contribution::Model], away: &[AwayPeriod]) -> (i32, i32) {
     // Group by date and sum counts
     let mut contribution_map: HashMap<chrono::NaiveDate, i32> = HashMap::new();
     for contrib in contributions {
@@ -245,71 +784,121 @@ fn calculate_streaks(contributions: &[contribution::Model]) -> (i32, i32) {
     let mut dates: Vec<chrono::NaiveDate> = contribution_map.keys().copied().collect();
     dates.sort();
 
-    let today = chrono::Utc::now().date_naive();
-    let mut current_streak = 0;
     let mut longest_streak = 0;
-    let mut temp_streak = 0;
+    let mut streak = 0;
     let mut last_date: Option<chrono::NaiveDate> = None;
 
-    for date in dates.iter().rev() {
-        if let Some(prev_date) = last_date {
-            if (*date + chrono::Duration::days(1)) == prev_date {
-                temp_streak += 1;
-            } else {
-                longest_streak = longest_streak.max(temp_streak);
-                temp_streak = 1;
-            }
-        } else {
-            temp_streak = 1;
-        }
-        last_date = Some(*date);
+    for &date in &dates {
+        streak = match last_date {
+            Some(prev_date) if away_periods::bridges(away, prev_date, date) => streak + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(streak);
+        last_date = Some(date);
     }
 
-    longest_streak = longest_streak.max(temp_streak);
+    // The current streak is the last one, if it reaches today or yesterday
+    let today = chrono::Utc::now().date_naive();
+    let current_streak = match last_date {
+        Some(last_date) if away_periods::bridges(away, last_date, today) => streak,
+        _ => 0,
+    };
 
-    // Calculate current streak (must include today or yesterday)
-    if let Some(&last_date) = dates.last() {
-        if last_date == today || last_date == today - chrono::Duration::days(1) {
-            current_streak = 1;
-            let mut check_date = last_date - chrono::Duration::days(1);
-            while contribution_map.contains_key(&check_date) {
-                current_streak += 1;
-                check_date = check_date - chrono::Duration::days(1);
-            }
-        }
+    (current_streak, longest_streak)
+}
+
+/// Validators for a public profile response. They change when a contribution
+/// is added, updated or removed, when the visible accounts or privacy settings
+/// change, or when the request (`key`) differs.
+async fn profile_validators(
+    db: &DatabaseConnection,
+    key: &str,
+    user_model: &user::Model,
+    settings: Option<&user_setting::Model>,
+    account_ids: &[Uuid],
+    show_private_contributions: bool,
+) -> Result<Validators, DbErr> {
+    let mut query = contribution::Entity::find()
+        .select_only()
+        .column_as(contribution::Column::UpdatedAt.max(), "last_updated")
+        .column_as(contribution::Column::Id.count(), "rows")
+        .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids.to_vec()));
+
+    if !show_private_contributions {
+        query = query.filter(contribution::Column::IsPrivateRepo.eq(false));
     }
 
-    (current_streak, longest_streak)
+    // Hidden repositories, so the validators change when the list does
+    if let Some(settings) = settings {
+        query = query.filter(repo_exclusion::not_excluded(
+            contribution::Column::RepositoryName,
+            &settings.excluded_repositories,
+        ));
+    }
+
+    let (last_updated, rows): (Option<DateTime<Utc>>, i64) =
+        query.into_tuple().one(db).await?.unwrap_or((None, 0));
+
+    let last_modified = [Some(user_model.created_at), last_updated, settings.map(|s| s.updated_at)]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(user_model.created_at);
+
+    let accounts = account_ids
+        .iter()
+        .map(Uuid::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    Ok(Validators::new(
+        &[
+            key,
+            &accounts,
+            &show_private_contributions.to_string(),
+            &rows.to_string(),
+            &last_modified.to_rfc3339(),
+        ],
+        last_modified,
+    ))
+}
+
+fn profile_cache_key(endpoint: &str, username: &str, req: &HttpRequest) -> String {
+    format!("{}|{}|{}", endpoint, username.to_lowercase(), req.query_string())
 }
 
 /// GET /api/users/:username/contributions
 /// Public endpoint to get contributions for a specific user by username
+#[utoipa::path(
+    get,
+    path = "/users/{username}/contributions",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)"), ContributionsQuery),
+    responses(
+        (status = 200, description = "Daily contribution counts, with ETag and Last-Modified", body = ContributionsResponse),
+        (status = 304, description = "Not modified since the ETag or date sent in If-None-Match / If-Modified-Since"),
+        (status = 404, description = "User not found")
+    )
+)]
 pub async fn get_user_contributions(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    cache: web::Data<ResponseCache>,
     path: web::Path<String>,
     query: web::Query<ContributionsQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     let username = path.into_inner();
 
     // Find user by username (case-insensitive)
-    let user_model = user::Entity::find()
-        .filter(
-            Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(username.to_lowercase()),
-        )
-        .one(db.as_ref())
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
-        })?;
-
-    let user_model = match user_model {
-        Some(u) => u,
-        None => {
-            return Ok(HttpResponse::NotFound().json(serde_json::json!({
-                "error": "User not found"
-            })));
-        }
+        })? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
     };
 
     let user_id = user_model.id;
@@ -324,11 +913,44 @@ pub async fn get_user_contributions(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    let visibility = profile_visibility::of(settings.as_ref()).to_string();
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_id, &visibility)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+    {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "User not found"
+        })));
+    }
+
+    // Checked after visibility, so only allowed viewers reach the cache
+    let cache_key = profile_cache_key("contributions", &username, &req);
+    if let Some(cached) = cache.get::<CachedProfileResponse>(user_id, &cache_key).await {
+        let mut response = cached.validators.respond(req.headers(), Bytes::from(cached.body));
+        profile_visibility::mark_unlisted(&mut response, &visibility);
+        return Ok(response);
+    }
+
     let show_private_contributions = settings
         .as_ref()
         .map(|s| s.show_private_contributions)
         .unwrap_or(true);
 
+    let excluded_repositories = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.clone())
+        .unwrap_or_default();
+
+    let group_by_platform = match parse_group_by(query.group_by.as_deref()) {
+        Ok(grouped) => grouped,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
     // Get all active platform accounts for this user
     let mut accounts_query = git_platform_account::Entity::find()
         .filter(git_platform_account::Column::UserId.eq(user_id))
@@ -360,11 +982,33 @@ pub async fn get_user_contributions(
         return Ok(HttpResponse::Ok().json(ContributionsResponse {
             contributions: vec![],
             total_count: 0,
+            platforms: group_by_platform.then(Vec::new),
+            annotations: None,
         }));
     }
 
     let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
 
+    let validators = profile_validators(
+        db.as_ref(),
+        &cache_key,
+        &user_model,
+        settings.as_ref(),
+        &account_ids,
+        show_private_contributions,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    if validators.is_fresh(req.headers()) {
+        let mut response = validators.respond(req.headers(), Bytes::new());
+        profile_visibility::mark_unlisted(&mut response, &visibility);
+        return Ok(response);
+    }
+
     // Build query for contributions
     let mut contribution_query = contribution::Entity::find()
         .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids));
@@ -375,6 +1019,17 @@ pub async fn get_user_contributions(
             contribution_query.filter(contribution::Column::IsPrivateRepo.eq(false));
     }
 
+    // Hide excluded repositories without deleting their data
+    contribution_query = contribution_query.filter(repo_exclusion::not_excluded(
+        contribution::Column::RepositoryName,
+        &excluded_repositories,
+    ));
+
+    // Restrict to repositories owned by an organization if requested
+    if let Some(organization) = &query.organization {
+        contribution_query = contribution_query.filter(organization_filter(organization));
+    }
+
     // Apply date range if provided
     if let Some(from_str) = &query.from {
         if let Ok(from_date) = chrono::NaiveDate::parse_from_str(from_str, "%Y-%m-%d") {
@@ -395,59 +1050,71 @@ pub async fn get_user_contributions(
         actix_web::error::ErrorInternalServerError("Database error")
     })?;
 
-    // Aggregate contributions by date
-    let mut contribution_map: HashMap<chrono::NaiveDate, i32> = HashMap::new();
-    for contrib in contributions {
-        *contribution_map
-            .entry(contrib.contribution_date)
-            .or_insert(0) += contrib.count;
-    }
-
-    let mut contribution_days: Vec<ContributionDay> = contribution_map
-        .iter()
-        .map(|(date, count)| ContributionDay {
-            date: date.format("%Y-%m-%d").to_string(),
-            count: *count,
-        })
-        .collect();
+    let platforms = group_by_platform
+        .then(|| group_contributions_by_platform(&accounts, &contributions));
 
-    contribution_days.sort_by(|a, b| a.date.cmp(&b.date));
+    let contribution_days = aggregate_by_date(contributions.iter());
 
     let total_count: i32 = contribution_days.iter().map(|c| c.count).sum();
 
-    Ok(HttpResponse::Ok().json(ContributionsResponse {
+    let body = serde_json::to_string(&ContributionsResponse {
         contributions: contribution_days,
         total_count,
-    }))
+        platforms,
+        annotations: None,
+    })
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    cache
+        .insert(
+            user_id,
+            &cache_key,
+            &CachedProfileResponse {
+                validators: validators.clone(),
+                body: body.clone(),
+            },
+        )
+        .await;
+
+    let mut response = validators.respond(req.headers(), Bytes::from(body));
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
 }
 
 /// GET /api/users/:username/contributions/stats
 /// Public endpoint to get contribution statistics for a specific user
+#[utoipa::path(
+    get,
+    path = "/users/{username}/contributions/stats",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)"), StatsQuery),
+    responses(
+        (status = 200, description = "Contribution statistics, with ETag and Last-Modified", body = ContributionStatsResponse),
+        (status = 304, description = "Not modified since the ETag or date sent in If-None-Match / If-Modified-Since"),
+        (status = 404, description = "User not found")
+    )
+)]
 pub async fn get_user_stats(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    cache: web::Data<ResponseCache>,
     path: web::Path<String>,
+    query: web::Query<StatsQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     let username = path.into_inner();
 
+    // Streaks depend on the current date, so the day is part of the key
+    let today = Utc::now().date_naive();
     // Find user by username (case-insensitive)
-    let user_model = user::Entity::find()
-        .filter(
-            Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(username.to_lowercase()),
-        )
-        .one(db.as_ref())
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
-        })?;
-
-    let user_model = match user_model {
-        Some(u) => u,
-        None => {
-            return Ok(HttpResponse::NotFound().json(serde_json::json!({
-                "error": "User not found"
-            })));
-        }
+        })? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
     };
 
     let user_id = user_model.id;
@@ -462,11 +1129,49 @@ pub async fn get_user_stats(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    let visibility = profile_visibility::of(settings.as_ref()).to_string();
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_id, &visibility)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+    {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "User not found"
+        })));
+    }
+
+    // Checked after visibility, so only allowed viewers reach the cache
+    let cache_key = format!("{}|{}", profile_cache_key("stats", &username, &req), today);
+    if let Some(cached) = cache.get::<CachedProfileResponse>(user_id, &cache_key).await {
+        let mut response = cached.validators.respond(req.headers(), Bytes::from(cached.body));
+        profile_visibility::mark_unlisted(&mut response, &visibility);
+        return Ok(response);
+    }
+
     let show_private_contributions = settings
         .as_ref()
         .map(|s| s.show_private_contributions)
         .unwrap_or(true);
 
+    let away_periods = settings
+        .as_ref()
+        .map(|s| s.away_periods.to_vec())
+        .unwrap_or_default();
+
+    let excluded_repositories = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.clone())
+        .unwrap_or_default();
+
+    let group_by_platform = match parse_group_by(query.group_by.as_deref()) {
+        Ok(grouped) => grouped,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+    };
+
     // Get all active platform accounts
     let accounts = git_platform_account::Entity::find()
         .filter(git_platform_account::Column::UserId.eq(user_id))
@@ -486,11 +1191,34 @@ pub async fn get_user_stats(
             current_streak: 0,
             longest_streak: 0,
             active_platforms: 0,
+            platforms: group_by_platform.then(Vec::new),
         }));
     }
 
     let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
 
+    let mut validators = profile_validators(
+        db.as_ref(),
+        &cache_key,
+        &user_model,
+        settings.as_ref(),
+        &account_ids,
+        show_private_contributions,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    let start_of_today = today.and_hms_opt(0, 0, 0).expect("valid time").and_utc();
+    validators.last_modified = validators.last_modified.max(start_of_today);
+
+    if validators.is_fresh(req.headers()) {
+        let mut response = validators.respond(req.headers(), Bytes::new());
+        profile_visibility::mark_unlisted(&mut response, &visibility);
+        return Ok(response);
+    }
+
     // Get contributions
     let mut contribution_query = contribution::Entity::find()
         .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids))
@@ -501,6 +1229,12 @@ pub async fn get_user_stats(
             contribution_query.filter(contribution::Column::IsPrivateRepo.eq(false));
     }
 
+    // Hide excluded repositories without deleting their data
+    contribution_query = contribution_query.filter(repo_exclusion::not_excluded(
+        contribution::Column::RepositoryName,
+        &excluded_repositories,
+    ));
+
     let contributions = contribution_query.all(db.as_ref()).await.map_err(|e| {
         log::error!("Database error: {}", e);
         actix_web::error::ErrorInternalServerError("Database error")
@@ -510,12 +1244,31 @@ pub async fn get_user_stats(
     let total_contributions: i32 = contributions.iter().map(|c| c.count).sum();
 
     // Calculate streaks
-    let (current_streak, longest_streak) = calculate_streaks(&contributions);
+    let (current_streak, longest_streak) = calculate_streaks(&contributions, &away_periods);
 
-    Ok(HttpResponse::Ok().json(ContributionStatsResponse {
+    let platforms = group_by_platform.then(|| platform_stats(&accounts, &contributions, &away_periods));
+
+    let body = serde_json::to_string(&ContributionStatsResponse {
         total_contributions,
         current_streak,
         longest_streak,
         active_platforms,
-    }))
+        platforms,
+    })
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    cache
+        .insert(
+            user_id,
+            &cache_key,
+            &CachedProfileResponse {
+                validators: validators.clone(),
+                body: body.clone(),
+            },
+        )
+        .await;
+
+    let mut response = validators.respond(req.headers(), Bytes::from(body));
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
 }
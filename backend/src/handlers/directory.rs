@@ -0,0 +1,148 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::sea_query::{Expr, Func, LikeExpr};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::handlers::activities::escape_like;
+use crate::models::{generated_heatmap, user, user_setting};
+use crate::services::profile_visibility;
+use crate::utils::pagination::{self, Page};
+
+/// Page size of the directory, smaller than elsewhere to slow down scraping
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 50;
+
+/// Most users one search can page through; narrower searches reach the rest
+const MAX_RESULTS: u64 = 1000;
+
+const MAX_SEARCH_LENGTH: usize = 39;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DirectoryQuery {
+    /// Part of a username, case-insensitive (up to 39 characters)
+    pub search: Option<String>,
+    /// `recent` (newest users first, default) or `total` (most contributions
+    /// first)
+    pub sort: Option<String>,
+    /// Page size (default 20, max 50)
+    pub limit: Option<u64>,
+    /// Opaque cursor taken from the `X-Next-Cursor` header of a previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DirectoryEntry {
+    pub username: String,
+    pub joined_at: String,
+    /// Contributions on the user's latest generated heatmap; null before the
+    /// first one
+    pub total_contributions: Option<i32>,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// GET /api/users
+/// Users who opted in to the directory and have a public profile
+#[utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    params(DirectoryQuery),
+    responses(
+        (status = 200, description = "Users; total in X-Total-Count, next page in X-Next-Cursor", body = [DirectoryEntry]),
+        (status = 400, description = "Invalid search, sort or cursor")
+    )
+)]
+pub async fn list_users(
+    db: web::Data<DatabaseConnection>,
+    query: web::Query<DirectoryQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let bad_request = |e: String| Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+
+    let by_total = match query.sort.as_deref().unwrap_or("recent") {
+        "recent" => false,
+        "total" => true,
+        _ => return bad_request("sort must be recent or total".to_string()),
+    };
+
+    let offset = match query.cursor.as_deref() {
+        Some(cursor) => match pagination::decode_offset_cursor(cursor) {
+            Some(offset) if offset < MAX_RESULTS => offset,
+            _ => return bad_request("Invalid cursor".to_string()),
+        },
+        None => 0,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut users = user::Entity::find()
+        .inner_join(user_setting::Entity)
+        .filter(user_setting::Column::ListedInDirectory.eq(true))
+        .filter(user_setting::Column::ProfileVisibility.eq(profile_visibility::PUBLIC));
+
+    if let Some(search) = query.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        if search.chars().count() > MAX_SEARCH_LENGTH {
+            return bad_request(format!("search must be at most {} characters", MAX_SEARCH_LENGTH));
+        }
+        let pattern = format!("%{}%", escape_like(&search.to_lowercase()));
+        users = users.filter(
+            Expr::expr(Func::lower(Expr::col(user::Column::Username)))
+                .like(LikeExpr::new(pattern).escape('\\')),
+        );
+    }
+
+    let users = users
+        .order_by_desc(user::Column::CreatedAt)
+        .order_by_asc(user::Column::Id)
+        .limit(MAX_RESULTS)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let totals: HashMap<Uuid, i32> = generated_heatmap::Entity::find()
+        .select_only()
+        .column(generated_heatmap::Column::UserId)
+        .column_as(generated_heatmap::Column::ContributionCount.max(), "total")
+        .filter(generated_heatmap::Column::UserId.is_in(users.iter().map(|u| u.id)))
+        .group_by(generated_heatmap::Column::UserId)
+        .into_tuple::<(Uuid, i32)>()
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .collect();
+
+    let mut entries: Vec<DirectoryEntry> = users
+        .into_iter()
+        .map(|u| DirectoryEntry {
+            total_contributions: totals.get(&u.id).copied(),
+            username: u.username,
+            joined_at: u.created_at.to_rfc3339(),
+        })
+        .collect();
+    if by_total {
+        // Stable, so equal totals stay newest first
+        entries.sort_by_key(|e| std::cmp::Reverse(e.total_contributions.unwrap_or(0)));
+    }
+
+    let total_count = entries.len() as u64;
+    let items: Vec<DirectoryEntry> = entries
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    let next_offset = offset + items.len() as u64;
+
+    Ok(Page {
+        items,
+        total_count,
+        next_cursor: (next_offset < total_count).then(|| pagination::encode_offset_cursor(next_offset)),
+    }
+    .into_response(None))
+}
@@ -0,0 +1,66 @@
+use actix_web::http::header;
+use actix_web::web::{self, Bytes};
+use actix_web::HttpResponse;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::services::event_bus::{DashboardEvent, EventBus};
+
+/// Comment line sent when idle so proxies keep the connection open
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+fn sse_message(event: &DashboardEvent) -> Bytes {
+    let data = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event.name(), data))
+}
+
+/// GET /api/events
+/// Server-sent event stream of the user's sync progress, job completions and new activities
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "text/event-stream of sync_progress, sync_finished, generation_finished, new_activities and import_progress events. A resync event means some events were missed and the client should refetch.", content_type = "text/event-stream")
+    )
+)]
+pub async fn events(
+    bus: web::Data<EventBus>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    log::info!("📡 [Events] User {} subscribed", user_id);
+
+    let receiver = bus.subscribe();
+    let keepalive = interval(KEEPALIVE_INTERVAL);
+
+    let stream = futures_util::stream::unfold((receiver, keepalive), move |(mut receiver, mut keepalive)| async move {
+        loop {
+            let message = tokio::select! {
+                _ = keepalive.tick() => Bytes::from_static(b": keepalive\n\n"),
+                received = receiver.recv() => match received {
+                    Ok(event) if event.user_id == user_id => sse_message(&event.event),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("⚠️  [Events] User {} missed {} events", user_id, skipped);
+                        Bytes::from_static(b"event: resync\ndata: {}\n\n")
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+            };
+            return Some((Ok::<_, actix_web::Error>(message), (receiver, keepalive)));
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Disable response buffering in nginx
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream))
+}
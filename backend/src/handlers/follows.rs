@@ -0,0 +1,187 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, Func, OnConflict};
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{follow, user};
+use crate::services::profile_visibility;
+
+/// Most users one can follow, which keeps the feed query bounded
+pub const MAX_FOLLOWING: u64 = 500;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FollowedUser {
+    pub user_id: String,
+    pub username: String,
+    pub followed_at: String,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// The user with this username (case-insensitive)
+async fn find_user(db: &DatabaseConnection, username: &str) -> Result<Option<user::Model>, DbErr> {
+    user::Entity::find()
+        .filter(Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(username.to_lowercase()))
+        .one(db)
+        .await
+}
+
+/// GET /api/following
+/// Users the current user follows, most recently followed first
+#[utoipa::path(
+    get,
+    path = "/following",
+    tag = "activities",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Followed users", body = [FollowedUser]))
+)]
+pub async fn list_following(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let follows = follow::Entity::find()
+        .filter(follow::Column::FollowerId.eq(user_id))
+        .order_by_desc(follow::Column::CreatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let usernames: std::collections::HashMap<Uuid, String> = user::Entity::find()
+        .filter(user::Column::Id.is_in(follows.iter().map(|f| f.followee_id)))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|u| (u.id, u.username))
+        .collect();
+
+    let following: Vec<FollowedUser> = follows
+        .into_iter()
+        .filter_map(|f| {
+            Some(FollowedUser {
+                user_id: f.followee_id.to_string(),
+                username: usernames.get(&f.followee_id)?.clone(),
+                followed_at: f.created_at.to_rfc3339(),
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(following))
+}
+
+/// PUT /api/following/:username
+/// Follow a user. Following someone twice is not an error.
+#[utoipa::path(
+    put,
+    path = "/following/{username}",
+    tag = "activities",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Following the user"),
+        (status = 400, description = "Following yourself, or following too many users"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn follow_user(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let followee = find_user(db.as_ref(), &path.into_inner())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    if followee.id == user_id {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "You cannot follow yourself"
+        })));
+    }
+
+    // Private profiles are answered as missing, as on their public pages
+    let visibility = profile_visibility::find(db.as_ref(), followee.id)
+        .await
+        .map_err(db_error)?;
+    if visibility == profile_visibility::PRIVATE {
+        return Err(actix_web::error::ErrorNotFound("User not found"));
+    }
+
+    let following = follow::Entity::find()
+        .filter(follow::Column::FollowerId.eq(user_id))
+        .count(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if following >= MAX_FOLLOWING {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("You can follow at most {} users", MAX_FOLLOWING)
+        })));
+    }
+
+    let row = follow::ActiveModel {
+        follower_id: Set(user_id),
+        followee_id: Set(followee.id),
+        created_at: Set(Utc::now()),
+    };
+    follow::Entity::insert(row)
+        .on_conflict(
+            OnConflict::columns([follow::Column::FollowerId, follow::Column::FolloweeId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// DELETE /api/following/:username
+/// Stop following a user
+#[utoipa::path(
+    delete,
+    path = "/following/{username}",
+    tag = "activities",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "No longer following the user"),
+        (status = 404, description = "Not following this user")
+    )
+)]
+pub async fn unfollow_user(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let followee = find_user(db.as_ref(), &path.into_inner())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Not following this user"))?;
+
+    let result = follow::Entity::delete_by_id((user_id, followee.id))
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected == 0 {
+        return Err(actix_web::error::ErrorNotFound("Not following this user"));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
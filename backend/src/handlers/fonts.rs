@@ -1,14 +1,15 @@
 use actix_web::{HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct FontInfo {
     pub name: String,
     pub display_name: String,
     pub category: String, // "sans-serif", "serif", "monospace"
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AvailableFontsResponse {
     pub fonts: Vec<FontInfo>,
     pub default_font: String,
@@ -16,6 +17,13 @@ pub struct AvailableFontsResponse {
 
 /// Get list of available fonts for heatmap generation
 /// These fonts are commonly available on Linux systems (Debian/Ubuntu)
+#[utoipa::path(
+    get,
+    path = "/heatmap/fonts",
+    tag = "heatmap",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Fonts usable in themes", body = AvailableFontsResponse))
+)]
 pub async fn get_available_fonts() -> impl Responder {
     let fonts = vec![
         // Sans-serif fonts (Linux standard)
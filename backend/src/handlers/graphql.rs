@@ -0,0 +1,19 @@
+use actix_web::web;
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use uuid::Uuid;
+
+use crate::graphql::{AppSchema, Viewer};
+
+/// POST /api/graphql
+/// Execute a GraphQL query as the authenticated user
+pub async fn graphql(
+    schema: web::Data<AppSchema>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    request: GraphQLRequest,
+) -> Result<GraphQLResponse, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let request = request.into_inner().data(Viewer { user_id });
+    Ok(schema.execute(request).await.into())
+}
@@ -0,0 +1,174 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+use utoipa::ToSchema;
+
+use crate::services::health::{self, Heartbeat};
+use crate::services::heatmap_generator::HEATMAP_BASE_DIR;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LivenessResponse {
+    pub status: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckResult {
+    /// "ok" or "error"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_heartbeat: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessChecks {
+    pub database: CheckResult,
+    pub heatmap_job_processor: CheckResult,
+    pub sync_job_processor: CheckResult,
+    pub storage: CheckResult,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// "ready" or "not_ready"
+    pub status: String,
+    pub checked_at: String,
+    pub checks: ReadinessChecks,
+}
+
+impl CheckResult {
+    fn ok() -> Self {
+        Self {
+            status: "ok".to_string(),
+            latency_ms: None,
+            last_heartbeat: None,
+            path: None,
+            error: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            status: "error".to_string(),
+            error: Some(message.into()),
+            ..Self::ok()
+        }
+    }
+
+    fn is_ok(&self) -> bool {
+        self.status == "ok"
+    }
+}
+
+async fn check_database(db: &DatabaseConnection) -> CheckResult {
+    let started = Instant::now();
+    let result = db
+        .execute_unprepared("SELECT 1")
+        .await
+        .map_err(|e| e.to_string());
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+
+    match result {
+        Ok(_) => CheckResult { latency_ms, ..CheckResult::ok() },
+        Err(e) => {
+            log::error!("❌ Readiness: database check failed: {}", e);
+            CheckResult { latency_ms, ..CheckResult::error("Database unreachable") }
+        }
+    }
+}
+
+fn check_heartbeat(heartbeat: &Heartbeat) -> CheckResult {
+    let last_heartbeat = heartbeat.last_beat().map(|t| t.to_rfc3339());
+    let result = if heartbeat.is_alive() {
+        CheckResult::ok()
+    } else if last_heartbeat.is_none() {
+        CheckResult::error("Not started")
+    } else {
+        CheckResult::error("No recent heartbeat")
+    };
+
+    CheckResult { last_heartbeat, ..result }
+}
+
+/// Verify the heatmap directory exists (or can be created) and accepts writes
+async fn check_storage() -> CheckResult {
+    let dir = Path::new(HEATMAP_BASE_DIR);
+    let probe = dir.join(format!(".readyz-{}", uuid::Uuid::new_v4()));
+
+    let result = async {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&probe, b"ok").await?;
+        tokio::fs::remove_file(&probe).await
+    }
+    .await;
+
+    let path = Some(HEATMAP_BASE_DIR.to_string());
+    match result {
+        Ok(()) => CheckResult { path, ..CheckResult::ok() },
+        Err(e) => {
+            log::error!("❌ Readiness: storage path {} is not writable: {}", HEATMAP_BASE_DIR, e);
+            CheckResult { path, ..CheckResult::error("Storage path not writable") }
+        }
+    }
+}
+
+/// GET /healthz
+/// Liveness probe: the process is up and serving requests
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "Server is alive", body = LivenessResponse))
+)]
+pub async fn healthz() -> impl Responder {
+    HttpResponse::Ok().json(LivenessResponse {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+/// GET /readyz
+/// Readiness probe: database, background processors and storage are usable
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "All checks passed", body = ReadinessResponse),
+        (status = 503, description = "At least one check failed", body = ReadinessResponse)
+    )
+)]
+pub async fn readyz(db: web::Data<DatabaseConnection>) -> impl Responder {
+    let checks = ReadinessChecks {
+        database: check_database(db.as_ref()).await,
+        heatmap_job_processor: check_heartbeat(&health::HEATMAP_JOB_PROCESSOR),
+        sync_job_processor: check_heartbeat(&health::SYNC_JOB_PROCESSOR),
+        storage: check_storage().await,
+    };
+
+    let ready = checks.database.is_ok()
+        && checks.heatmap_job_processor.is_ok()
+        && checks.sync_job_processor.is_ok()
+        && checks.storage.is_ok();
+
+    let response = ReadinessResponse {
+        status: if ready { "ready" } else { "not_ready" }.to_string(),
+        checked_at: Utc::now().to_rfc3339(),
+        checks,
+    };
+
+    if ready {
+        HttpResponse::Ok().json(response)
+    } else {
+        HttpResponse::ServiceUnavailable().json(response)
+    }
+}
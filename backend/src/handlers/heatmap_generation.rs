@@ -2,23 +2,52 @@ use actix_web::{web, HttpResponse, Responder};
 use chrono::Utc;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::models::{
     generated_heatmap, git_platform_account, heatmap_generation_job, heatmap_generation_setting,
-    heatmap_theme, user,
+    heatmap_share_link, heatmap_theme, user,
+};
+use crate::handlers::static_files::generate_heatmap_on_demand;
+use crate::services::heatmap_analytics::{self, HeatmapAnalytics};
+use crate::handlers::heatmap_themes::{
+    check_custom_template, parse_custom_template, parse_layout, parse_mini_days,
 };
 use crate::services::heatmap_generator::HeatmapGenerator;
+use crate::services::heatmap_renderers;
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::job_limits::MANUAL_JOB_PRIORITY;
+use crate::services::job_processor::{self, RetryPolicy};
+use crate::services::preview_cache::PreviewCache;
+use crate::services::quotas;
+use crate::services::render_lanes::Lane;
+use crate::utils::config::Config;
+use crate::utils::generation_schedule::{self, GenerationSchedule};
+use crate::utils::pagination::{self, PageParams};
+use crate::utils::share_token;
+
+/// Longest lifetime a share link can be created with
+const MAX_SHARE_LINK_DAYS: i64 = 365;
+
+/// Highest view limit of a share link
+const MAX_SHARE_LINK_VIEWS: i32 = 10_000;
 
 // ============ Request/Response DTOs ============
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GenerationSettingsResponse {
     pub update_interval_minutes: i32,
     pub auto_generation_enabled: bool,
     pub date_range_days: i32,
     pub include_private_contributions: bool,
     pub storage_path: Option<String>,
+    /// IANA timezone of the window and cron expression
+    pub timezone: String,
+    /// Daily window scheduled runs start in, e.g. `02:00-05:00`
+    pub generation_window: Option<String>,
+    /// Cron expression scheduled runs start at; replaces the interval
+    pub generation_cron: Option<String>,
     pub last_scheduled_generation_at: Option<String>,
     pub next_scheduled_generation_at: Option<String>,
     pub updated_at: String,
@@ -32,6 +61,18 @@ impl From<heatmap_generation_setting::Model> for GenerationSettingsResponse {
             date_range_days: model.date_range_days,
             include_private_contributions: model.include_private_contributions,
             storage_path: model.storage_path,
+            timezone: model.timezone.unwrap_or_else(|| "UTC".to_string()),
+            generation_window: model
+                .generation_window_start_minutes
+                .zip(model.generation_window_end_minutes)
+                .map(|(start, end)| {
+                    format!(
+                        "{}-{}",
+                        generation_schedule::format_time_of_day(start),
+                        generation_schedule::format_time_of_day(end)
+                    )
+                }),
+            generation_cron: model.generation_cron,
             last_scheduled_generation_at: model
                 .last_scheduled_generation_at
                 .map(|dt| dt.to_rfc3339()),
@@ -43,16 +84,71 @@ impl From<heatmap_generation_setting::Model> for GenerationSettingsResponse {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateGenerationSettingsRequest {
     pub update_interval_minutes: Option<i32>,
     pub auto_generation_enabled: Option<bool>,
     pub date_range_days: Option<i32>,
     pub include_private_contributions: Option<bool>,
     pub storage_path: Option<String>,
+    /// IANA timezone such as `Europe/Berlin`; empty for UTC
+    pub timezone: Option<String>,
+    /// `HH:MM-HH:MM` in the timezone; the end may be past midnight. Empty
+    /// clears it.
+    pub generation_window: Option<String>,
+    /// Five-field cron expression in the timezone, e.g. `5 0 * * *`. Empty
+    /// clears it.
+    pub generation_cron: Option<String>,
+}
+
+/// Longest cron expression that can be stored
+const MAX_CRON_LENGTH: usize = 128;
+
+/// The new value of an optional text setting: None when the request leaves
+/// it alone, `Some(None)` when it sends an empty string to clear it
+fn optional_text(value: &Option<String>) -> Option<Option<&str>> {
+    value
+        .as_deref()
+        .map(|value| Some(value.trim()).filter(|value| !value.is_empty()))
+}
+
+/// Parse an `HH:MM-HH:MM` generation window into minutes after midnight
+fn parse_generation_window(value: &str) -> Result<(i32, i32), actix_web::Error> {
+    value
+        .split_once('-')
+        .and_then(|(start, end)| {
+            Some((
+                generation_schedule::parse_time_of_day(start)?,
+                generation_schedule::parse_time_of_day(end)?,
+            ))
+        })
+        .ok_or_else(|| actix_web::error::ErrorBadRequest("Generation window must look like 02:00-05:00"))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GenerationJobsQuery {
+    /// pending, processing, completed or failed
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnalyticsQuery {
+    /// Days to cover, today included (1-365, default 30)
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderQuery {
+    /// Theme slug; defaults to the user's default theme
+    pub theme: Option<String>,
+    /// svg, png, jpeg or webp (default png)
+    pub format: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GeneratedHeatmapInfo {
     pub id: String,
     pub theme_id: String,
@@ -92,7 +188,48 @@ impl From<generated_heatmap::Model> for GeneratedHeatmapInfo {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateShareLinkRequest {
+    /// Days until the link expires (1-365); omit for a link that never expires
+    pub expires_in_days: Option<i64>,
+    /// Views before the link stops working (1-10000); omit for no limit
+    pub max_views: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShareLinkResponse {
+    pub id: String,
+    pub theme_id: String,
+    pub format: String,
+    /// Public URL of the image; anyone holding it can view the heatmap
+    pub url: String,
+    pub expires_at: Option<String>,
+    pub max_views: Option<i32>,
+    pub view_count: i32,
+    pub created_at: String,
+}
+
+impl ShareLinkResponse {
+    fn new(link: heatmap_share_link::Model, config: &Config) -> Result<Self, actix_web::Error> {
+        let token = share_token::create(link.id, &config.jwt_secret, link.expires_at).map_err(|e| {
+            log::error!("❌ Failed to sign share link: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to create share link")
+        })?;
+
+        Ok(Self {
+            id: link.id.to_string(),
+            theme_id: link.theme_id.to_string(),
+            format: link.format.to_value(),
+            url: format!("{}/share/{}", config.base_url.trim_end_matches('/'), token),
+            expires_at: link.expires_at.map(|dt| dt.to_rfc3339()),
+            max_views: link.max_views,
+            view_count: link.view_count,
+            created_at: link.created_at.to_rfc3339(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GenerationJobResponse {
     pub id: String,
     pub theme_id: Option<String>,
@@ -101,24 +238,48 @@ pub struct GenerationJobResponse {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub error_message: Option<String>,
+    /// Failed attempts so far
+    pub retry_count: i32,
+    /// Retries before the job is dead-lettered
+    pub max_retries: i32,
     pub is_manual: bool,
 }
 
+/// Status of a generation job as the API names it
+pub(crate) fn job_status_name(status: &heatmap_generation_job::GenerationJobStatus) -> &'static str {
+    match status {
+        heatmap_generation_job::GenerationJobStatus::Pending => "pending",
+        heatmap_generation_job::GenerationJobStatus::Processing => "processing",
+        heatmap_generation_job::GenerationJobStatus::Completed => "completed",
+        heatmap_generation_job::GenerationJobStatus::Failed => "failed",
+        heatmap_generation_job::GenerationJobStatus::DeadLetter => "dead_letter",
+    }
+}
+
+/// Status named by a `status` query parameter
+pub(crate) fn parse_job_status(status: &str) -> Option<heatmap_generation_job::GenerationJobStatus> {
+    match status {
+        "pending" => Some(heatmap_generation_job::GenerationJobStatus::Pending),
+        "processing" => Some(heatmap_generation_job::GenerationJobStatus::Processing),
+        "completed" => Some(heatmap_generation_job::GenerationJobStatus::Completed),
+        "failed" => Some(heatmap_generation_job::GenerationJobStatus::Failed),
+        "dead_letter" => Some(heatmap_generation_job::GenerationJobStatus::DeadLetter),
+        _ => None,
+    }
+}
+
 impl From<heatmap_generation_job::Model> for GenerationJobResponse {
     fn from(model: heatmap_generation_job::Model) -> Self {
         Self {
             id: model.id.to_string(),
             theme_id: model.theme_id.map(|id| id.to_string()),
-            status: match model.status {
-                heatmap_generation_job::GenerationJobStatus::Pending => "pending".to_string(),
-                heatmap_generation_job::GenerationJobStatus::Processing => "processing".to_string(),
-                heatmap_generation_job::GenerationJobStatus::Completed => "completed".to_string(),
-                heatmap_generation_job::GenerationJobStatus::Failed => "failed".to_string(),
-            },
+            status: job_status_name(&model.status).to_string(),
             scheduled_at: model.scheduled_at.to_rfc3339(),
             started_at: model.started_at.map(|dt| dt.to_rfc3339()),
             completed_at: model.completed_at.map(|dt| dt.to_rfc3339()),
             error_message: model.error_message,
+            retry_count: model.retry_count,
+            max_retries: model.max_retries,
             is_manual: model.is_manual,
         }
     }
@@ -128,8 +289,16 @@ impl From<heatmap_generation_job::Model> for GenerationJobResponse {
 
 /// GET /api/heatmap/settings
 /// Get generation settings
+#[utoipa::path(
+    get,
+    path = "/heatmap/settings",
+    tag = "heatmap",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Generation settings", body = GenerationSettingsResponse))
+)]
 pub async fn get_generation_settings(
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
@@ -151,11 +320,15 @@ pub async fn get_generation_settings(
             let new_settings = heatmap_generation_setting::ActiveModel {
                 id: Set(Uuid::new_v4()),
                 user_id: Set(user_id),
-                update_interval_minutes: Set(60),
+                update_interval_minutes: Set(instance.current().await.default_sync_interval_minutes),
                 auto_generation_enabled: Set(false),
                 date_range_days: Set(365),
                 include_private_contributions: Set(true),
                 storage_path: Set(None),
+                timezone: Set(None),
+                generation_window_start_minutes: Set(None),
+                generation_window_end_minutes: Set(None),
+                generation_cron: Set(None),
                 last_scheduled_generation_at: Set(None),
                 next_scheduled_generation_at: Set(None),
                 created_at: Set(chrono::Utc::now()),
@@ -177,8 +350,20 @@ pub async fn get_generation_settings(
 
 /// PUT /api/heatmap/settings
 /// Update generation settings
+#[utoipa::path(
+    put,
+    path = "/heatmap/settings",
+    tag = "heatmap",
+    request_body = UpdateGenerationSettingsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Settings updated", body = GenerationSettingsResponse),
+        (status = 400, description = "Invalid settings")
+    )
+)]
 pub async fn update_generation_settings(
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     payload: web::Json<UpdateGenerationSettingsRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
@@ -187,7 +372,7 @@ pub async fn update_generation_settings(
 
     // Validate update_interval_minutes if provided
     if let Some(interval) = payload.update_interval_minutes {
-        if interval < 15 || interval > 1440 {
+        if !(15..=1440).contains(&interval) {
             return Err(actix_web::error::ErrorBadRequest(
                 "Update interval must be between 15 and 1440 minutes",
             ));
@@ -196,7 +381,7 @@ pub async fn update_generation_settings(
 
     // Validate date_range_days if provided
     if let Some(days) = payload.date_range_days {
-        if days < 1 || days > 730 {
+        if !(1..=730).contains(&days) {
             return Err(actix_web::error::ErrorBadRequest(
                 "Date range must be between 1 and 730 days",
             ));
@@ -238,10 +423,56 @@ pub async fn update_generation_settings(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    // The schedule as it will be stored, checked as a whole
+    let timezone = match optional_text(&payload.timezone) {
+        Some(timezone) => timezone.map(str::to_string),
+        None => settings.as_ref().and_then(|s| s.timezone.clone()),
+    };
+    let window = match optional_text(&payload.generation_window) {
+        Some(Some(window)) => Some(parse_generation_window(window)?),
+        Some(None) => None,
+        None => settings
+            .as_ref()
+            .and_then(|s| s.generation_window_start_minutes.zip(s.generation_window_end_minutes)),
+    };
+    let cron = match optional_text(&payload.generation_cron) {
+        Some(cron) => cron.map(str::to_string),
+        None => settings.as_ref().and_then(|s| s.generation_cron.clone()),
+    };
+    if cron.as_ref().is_some_and(|cron| cron.len() > MAX_CRON_LENGTH) {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "Cron expression must be at most {} characters",
+            MAX_CRON_LENGTH
+        )));
+    }
+    let schedule = GenerationSchedule::new(timezone.as_deref(), window, cron.as_deref())
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    let schedule_changed = payload.timezone.is_some()
+        || payload.generation_window.is_some()
+        || payload.generation_cron.is_some();
+
     let updated_settings = match settings {
         Some(settings) => {
+            let now = chrono::Utc::now();
+            let next_run = match settings.last_scheduled_generation_at {
+                Some(last) => schedule.next_run(
+                    last,
+                    chrono::Duration::minutes(
+                        payload.update_interval_minutes.unwrap_or(settings.update_interval_minutes) as i64,
+                    ),
+                ),
+                None => schedule.first_run(now),
+            };
             let mut active_settings: heatmap_generation_setting::ActiveModel = settings.into();
 
+            if schedule_changed {
+                active_settings.timezone = Set(timezone);
+                active_settings.generation_window_start_minutes = Set(window.map(|(start, _)| start));
+                active_settings.generation_window_end_minutes = Set(window.map(|(_, end)| end));
+                active_settings.generation_cron = Set(cron);
+                active_settings.next_scheduled_generation_at = Set(Some(next_run));
+            }
+
             if let Some(interval) = payload.update_interval_minutes {
                 active_settings.update_interval_minutes = Set(interval);
             }
@@ -262,7 +493,7 @@ pub async fn update_generation_settings(
                 active_settings.storage_path = Set(Some(path.clone()));
             }
 
-            active_settings.updated_at = Set(chrono::Utc::now());
+            active_settings.updated_at = Set(now);
 
             active_settings.update(db.as_ref()).await.map_err(|e| {
                 log::error!("Failed to update settings: {}", e);
@@ -274,13 +505,20 @@ pub async fn update_generation_settings(
             let new_settings = heatmap_generation_setting::ActiveModel {
                 id: Set(Uuid::new_v4()),
                 user_id: Set(user_id),
-                update_interval_minutes: Set(payload.update_interval_minutes.unwrap_or(60)),
+                update_interval_minutes: Set(match payload.update_interval_minutes {
+                    Some(minutes) => minutes,
+                    None => instance.current().await.default_sync_interval_minutes,
+                }),
                 auto_generation_enabled: Set(payload.auto_generation_enabled.unwrap_or(false)),
                 date_range_days: Set(payload.date_range_days.unwrap_or(365)),
                 include_private_contributions: Set(payload
                     .include_private_contributions
                     .unwrap_or(true)),
                 storage_path: Set(payload.storage_path.clone()),
+                timezone: Set(timezone),
+                generation_window_start_minutes: Set(window.map(|(start, _)| start)),
+                generation_window_end_minutes: Set(window.map(|(_, end)| end)),
+                generation_cron: Set(cron),
                 last_scheduled_generation_at: Set(None),
                 next_scheduled_generation_at: Set(None),
                 created_at: Set(chrono::Utc::now()),
@@ -304,13 +542,32 @@ pub async fn update_generation_settings(
 
 /// POST /api/heatmap/generate
 /// Manually trigger heatmap generation for all themes
+#[utoipa::path(
+    post,
+    path = "/heatmap/generate",
+    tag = "heatmap",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Generation job queued", body = GenerationJobResponse),
+        (status = 403, description = "Storage quota reached")
+    )
+)]
 pub async fn trigger_generation(
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
+    if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        return Ok(exceeded.response());
+    }
+
     // Create a generation job
     let job = heatmap_generation_job::ActiveModel {
         id: Set(Uuid::new_v4()),
@@ -322,9 +579,9 @@ pub async fn trigger_generation(
         completed_at: Set(None),
         error_message: Set(None),
         retry_count: Set(0),
-        max_retries: Set(3),
+        max_retries: Set(config.generation_max_retries),
         is_manual: Set(true),
-        priority: Set(10), // Higher priority for manual triggers
+        priority: Set(MANUAL_JOB_PRIORITY),
         created_at: Set(chrono::Utc::now()),
     };
 
@@ -341,13 +598,34 @@ pub async fn trigger_generation(
 
 /// POST /api/heatmap/generate/:theme_slug
 /// Manually trigger heatmap generation for a specific theme
+#[utoipa::path(
+    post,
+    path = "/heatmap/generate/{slug}",
+    tag = "heatmap",
+    params(("slug" = String, Path, description = "Theme slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Generation job queued", body = GenerationJobResponse),
+        (status = 403, description = "Storage quota reached"),
+        (status = 404, description = "Theme not found")
+    )
+)]
 pub async fn trigger_theme_generation(
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        return Ok(exceeded.response());
+    }
     let theme_slug = path.into_inner();
 
     // Find the theme
@@ -377,9 +655,9 @@ pub async fn trigger_theme_generation(
         completed_at: Set(None),
         error_message: Set(None),
         retry_count: Set(0),
-        max_retries: Set(3),
+        max_retries: Set(config.generation_max_retries),
         is_manual: Set(true),
-        priority: Set(10), // Higher priority for manual triggers
+        priority: Set(MANUAL_JOB_PRIORITY),
         created_at: Set(chrono::Utc::now()),
     };
 
@@ -394,18 +672,267 @@ pub async fn trigger_theme_generation(
     Ok(HttpResponse::Accepted().json(GenerationJobResponse::from(job)))
 }
 
+/// POST /api/heatmap/render
+/// Regenerate one heatmap and return the file, so CI jobs can commit it to a
+/// repository with a single request
+#[utoipa::path(
+    post,
+    path = "/heatmap/render",
+    tag = "heatmap",
+    params(RenderQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The freshly generated heatmap file"),
+        (status = 400, description = "Unsupported format"),
+        (status = 404, description = "Theme not found"),
+        (status = 403, description = "Storage quota used up")
+    )
+)]
+pub async fn render_heatmap(
+    db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<RenderQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let format = match query.format.as_deref().unwrap_or("png") {
+        "svg" => heatmap_theme::HeatmapFormat::Svg,
+        "png" => heatmap_theme::HeatmapFormat::Png,
+        "jpg" | "jpeg" => heatmap_theme::HeatmapFormat::Jpeg,
+        "webp" => heatmap_theme::HeatmapFormat::WebP,
+        _ => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "format must be svg, png, jpeg or webp"
+            })))
+        }
+    };
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(db_error)? {
+        return Ok(exceeded.response());
+    }
+
+    let themes = heatmap_theme::Entity::find().filter(heatmap_theme::Column::UserId.eq(user_id));
+    let theme = match &query.theme {
+        Some(slug) => themes.filter(heatmap_theme::Column::Slug.eq(slug.as_str())),
+        None => themes.filter(heatmap_theme::Column::IsDefault.eq(true)),
+    }
+    .one(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    let Some(theme) = theme else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": "Theme not found" })));
+    };
+
+    let path = generate_heatmap_on_demand(db.as_ref(), &user_id, &theme, &format).await?;
+    let content = tokio::fs::read(&path).await.map_err(|e| {
+        log::error!("Failed to read rendered heatmap: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to read heatmap file")
+    })?;
+
+    let (content_type, extension) = match format {
+        heatmap_theme::HeatmapFormat::Svg => ("image/svg+xml", "svg"),
+        heatmap_theme::HeatmapFormat::Png => ("image/png", "png"),
+        heatmap_theme::HeatmapFormat::Jpeg => ("image/jpeg", "jpeg"),
+        heatmap_theme::HeatmapFormat::WebP => ("image/webp", "webp"),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((
+            actix_web::http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", theme.slug, extension),
+        ))
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-store"))
+        .body(content))
+}
+
 /// GET /api/heatmap/generated
 /// List all generated heatmaps for the user
+#[utoipa::path(
+    get,
+    path = "/heatmap/generated",
+    tag = "heatmap",
+    params(PageParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Generated heatmap files; total in X-Total-Count, next page in X-Next-Cursor", body = [GeneratedHeatmapInfo]),
+        (status = 400, description = "Invalid sort or cursor")
+    )
+)]
 pub async fn list_generated_heatmaps(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    page: web::Query<PageParams>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let query = generated_heatmap::Entity::find()
+        .filter(generated_heatmap::Column::UserId.eq(user_id));
+
+    let heatmaps = match pagination::fetch_page(
+        db.as_ref(),
+        query,
+        &page,
+        &[
+            ("generated_at", generated_heatmap::Column::GeneratedAt),
+            ("file_size_bytes", generated_heatmap::Column::FileSizeBytes),
+            ("access_count", generated_heatmap::Column::AccessCount),
+        ],
+        "-generated_at",
+        generated_heatmap::Column::Id,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        Ok(p) => p,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    Ok(heatmaps
+        .map(GeneratedHeatmapInfo::from)
+        .into_response(page.fields.as_deref()))
+}
+
+/// GET /api/heatmap/analytics
+/// Views of the user's heatmap files and embeds per day, with the top
+/// referring sites of each theme
+#[utoipa::path(
+    get,
+    path = "/heatmap/analytics",
+    tag = "heatmap",
+    params(AnalyticsQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "View counts", body = HeatmapAnalytics),
+        (status = 400, description = "Invalid number of days")
+    )
+)]
+pub async fn get_heatmap_analytics(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    query: web::Query<AnalyticsQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let days = query.days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": "days must be between 1 and 365" })));
+    }
+
+    let analytics = heatmap_analytics::summary(db.as_ref(), user_id, days)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(analytics))
+}
+
+/// POST /api/heatmap/generated/{id}/share
+/// Create a signed link to a generated heatmap that works even while the
+/// profile is private. The link follows the heatmap's theme and format, so
+/// it keeps working after the heatmap is regenerated.
+#[utoipa::path(
+    post,
+    path = "/heatmap/generated/{id}/share",
+    tag = "heatmap",
+    params(("id" = Uuid, Path, description = "Generated heatmap ID")),
+    request_body = CreateShareLinkRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Share link created", body = ShareLinkResponse),
+        (status = 400, description = "Invalid lifetime or view limit"),
+        (status = 404, description = "Generated heatmap not found")
+    )
+)]
+pub async fn share_generated_heatmap(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+    payload: web::Json<CreateShareLinkRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
-    let heatmaps = generated_heatmap::Entity::find()
+    if payload.expires_in_days.is_some_and(|days| !(1..=MAX_SHARE_LINK_DAYS).contains(&days)) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("expires_in_days must be between 1 and {}", MAX_SHARE_LINK_DAYS)
+        })));
+    }
+    if payload.max_views.is_some_and(|views| !(1..=MAX_SHARE_LINK_VIEWS).contains(&views)) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("max_views must be between 1 and {}", MAX_SHARE_LINK_VIEWS)
+        })));
+    }
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let Some(generated) = generated_heatmap::Entity::find_by_id(path.into_inner())
         .filter(generated_heatmap::Column::UserId.eq(user_id))
-        .order_by_desc(generated_heatmap::Column::GeneratedAt)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": "Generated heatmap not found" })));
+    };
+
+    let now = Utc::now();
+    let link = heatmap_share_link::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        theme_id: Set(generated.theme_id),
+        format: Set(generated.format),
+        expires_at: Set(payload.expires_in_days.map(|days| now + chrono::Duration::days(days))),
+        max_views: Set(payload.max_views),
+        view_count: Set(0),
+        created_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    log::info!("🔗 Share link {} created for user {}", link.id, user_id);
+
+    Ok(HttpResponse::Created().json(ShareLinkResponse::new(link, &config)?))
+}
+
+/// GET /api/heatmap/share-links
+/// List the user's share links
+#[utoipa::path(
+    get,
+    path = "/heatmap/share-links",
+    tag = "heatmap",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Share links, newest first", body = [ShareLinkResponse]))
+)]
+pub async fn list_share_links(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let links = heatmap_share_link::Entity::find()
+        .filter(heatmap_share_link::Column::UserId.eq(user_id))
+        .order_by_desc(heatmap_share_link::Column::CreatedAt)
         .all(db.as_ref())
         .await
         .map_err(|e| {
@@ -413,64 +940,189 @@ pub async fn list_generated_heatmaps(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
-    let response: Vec<GeneratedHeatmapInfo> = heatmaps
+    let response = links
         .into_iter()
-        .map(GeneratedHeatmapInfo::from)
-        .collect();
+        .map(|link| ShareLinkResponse::new(link, &config))
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+/// DELETE /api/heatmap/share-links/{id}
+/// Revoke a share link
+#[utoipa::path(
+    delete,
+    path = "/heatmap/share-links/{id}",
+    tag = "heatmap",
+    params(("id" = Uuid, Path, description = "Share link ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Share link revoked"),
+        (status = 404, description = "Share link not found")
+    )
+)]
+pub async fn delete_share_link(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let link_id = path.into_inner();
+
+    let result = heatmap_share_link::Entity::delete_many()
+        .filter(heatmap_share_link::Column::Id.eq(link_id))
+        .filter(heatmap_share_link::Column::UserId.eq(user_id))
+        .exec(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    if result.rows_affected == 0 {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({ "error": "Share link not found" })));
+    }
+
+    log::info!("🗑️  Revoked share link {}", link_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
 /// GET /api/heatmap/jobs
 /// List generation jobs for the user
+#[utoipa::path(
+    get,
+    path = "/heatmap/jobs",
+    tag = "heatmap",
+    params(GenerationJobsQuery, PageParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Generation jobs, newest first; total in X-Total-Count, next page in X-Next-Cursor", body = [GenerationJobResponse]),
+        (status = 400, description = "Invalid status, sort or cursor")
+    )
+)]
 pub async fn list_generation_jobs(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
-    query: web::Query<std::collections::HashMap<String, String>>,
+    query: web::Query<GenerationJobsQuery>,
+    page: web::Query<PageParams>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
-    let limit: u64 = query
-        .get("limit")
-        .and_then(|l| l.parse().ok())
-        .unwrap_or(50);
-
     let mut query_builder = heatmap_generation_job::Entity::find()
-        .filter(heatmap_generation_job::Column::UserId.eq(user_id))
-        .order_by_desc(heatmap_generation_job::Column::CreatedAt);
+        .filter(heatmap_generation_job::Column::UserId.eq(user_id));
 
     // Filter by status if provided
-    if let Some(status_str) = query.get("status") {
-        let status = match status_str.as_str() {
-            "pending" => heatmap_generation_job::GenerationJobStatus::Pending,
-            "processing" => heatmap_generation_job::GenerationJobStatus::Processing,
-            "completed" => heatmap_generation_job::GenerationJobStatus::Completed,
-            "failed" => heatmap_generation_job::GenerationJobStatus::Failed,
-            _ => return Err(actix_web::error::ErrorBadRequest("Invalid status")),
-        };
+    if let Some(status_str) = &query.status {
+        let status = parse_job_status(status_str)
+            .ok_or_else(|| actix_web::error::ErrorBadRequest("Invalid status"))?;
         query_builder = query_builder.filter(heatmap_generation_job::Column::Status.eq(status));
     }
 
-    let jobs = query_builder
-        .limit(limit)
-        .all(db.as_ref())
+    let jobs = match pagination::fetch_page(
+        db.as_ref(),
+        query_builder,
+        &page,
+        &[
+            ("created_at", heatmap_generation_job::Column::CreatedAt),
+            ("scheduled_at", heatmap_generation_job::Column::ScheduledAt),
+            ("completed_at", heatmap_generation_job::Column::CompletedAt),
+            ("priority", heatmap_generation_job::Column::Priority),
+        ],
+        "-created_at",
+        heatmap_generation_job::Column::Id,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        Ok(p) => p,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    Ok(jobs
+        .map(GenerationJobResponse::from)
+        .into_response(page.fields.as_deref()))
+}
+
+/// POST /api/heatmap/jobs/:id/retry
+/// Queue a dead-lettered generation job again with a fresh retry budget
+#[utoipa::path(
+    post,
+    path = "/heatmap/jobs/{id}/retry",
+    tag = "heatmap",
+    params(("id" = String, Path, description = "Job ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Job queued again", body = GenerationJobResponse),
+        (status = 400, description = "Job has not run out of retries"),
+        (status = 403, description = "Storage quota reached"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn retry_generation_job(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let job_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid job ID: {}", e)))?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let job = heatmap_generation_job::Entity::find_by_id(job_id)
+        .filter(heatmap_generation_job::Column::UserId.eq(user_id))
+        .one(db.as_ref())
         .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?;
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Job not found"))?;
+
+    // Failed jobs were cancelled by an admin, who can queue them again
+    if job.status != heatmap_generation_job::GenerationJobStatus::DeadLetter {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Only jobs that ran out of retries can be queued again"
+        })));
+    }
 
-    let response: Vec<GenerationJobResponse> =
-        jobs.into_iter().map(GenerationJobResponse::from).collect();
+    if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(db_error)? {
+        return Ok(exceeded.response());
+    }
 
-    Ok(HttpResponse::Ok().json(response))
+    let job = job_processor::requeue(db.as_ref(), job, RetryPolicy::from_config(&config))
+        .await
+        .map_err(db_error)?;
+
+    log::info!("🔁 Generation job {} queued again by its owner", job.id);
+
+    Ok(HttpResponse::Accepted().json(GenerationJobResponse::from(job)))
 }
 
 /// POST /api/heatmap/preview
 /// Generate a preview SVG for theme configuration using real user data
+#[utoipa::path(
+    post,
+    path = "/heatmap/preview",
+    tag = "heatmap",
+    request_body = PreviewThemeRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Rendered preview image", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "Invalid theme parameters")
+    )
+)]
 pub async fn preview_theme(
     db: web::Data<DatabaseConnection>,
+    preview_cache: web::Data<PreviewCache>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     payload: web::Json<PreviewThemeRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
@@ -545,11 +1197,22 @@ pub async fn preview_theme(
             .legend_position
             .clone()
             .unwrap_or("bottom".to_string()),
-        output_formats: vec![],
+        layout: match payload.layout {
+            Some(ref layout) => parse_layout(layout)?,
+            None => heatmap_renderers::DEFAULT_LAYOUT.to_string(),
+        },
+        custom_template: parse_custom_template(&payload.custom_template).flatten(),
+        mini_days: parse_mini_days(payload.mini_days)?.flatten(),
+        show_achievements: payload.show_achievements.unwrap_or(false),
+        show_code_frequency: payload.show_code_frequency.unwrap_or(false),
+        output_formats: Default::default(),
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
 
+    // Report template errors to the editor instead of failing the preview
+    check_custom_template(&theme)?;
+
     // Fetch user to get username
     let user_model = user::Entity::find_by_id(user_id)
         .one(db.as_ref())
@@ -580,6 +1243,10 @@ pub async fn preview_theme(
             date_range_days: 365,
             include_private_contributions: true,
             storage_path: None,
+            timezone: None,
+            generation_window_start_minutes: None,
+            generation_window_end_minutes: None,
+            generation_cron: None,
             last_scheduled_generation_at: None,
             next_scheduled_generation_at: None,
             created_at: Utc::now(),
@@ -601,7 +1268,7 @@ pub async fn preview_theme(
         // Calculate the number of days
         let days = (to - from).num_days() + 1;
 
-        if days < 1 || days > 730 {
+        if !(1..=730).contains(&days) {
             return Err(actix_web::error::ErrorBadRequest(
                 "Preview date range must be between 1 and 730 days",
             ));
@@ -632,13 +1299,24 @@ pub async fn preview_theme(
         _ => return Err(actix_web::error::ErrorBadRequest("Invalid preview format")),
     };
 
-    // Generate in the requested format with username
-    let content = generator
-        .generate_heatmap_with_username(&theme, &heatmap_data, &format, Some(&user_model.username))
-        .map_err(|e| {
-            log::error!("Failed to generate preview: {}", e);
-            actix_web::error::ErrorInternalServerError("Failed to generate preview")
-        })?;
+    // The same theme on the same data renders the same image
+    let theme_json = serde_json::to_vec(&*payload).map_err(actix_web::error::ErrorInternalServerError)?;
+    let cache_key = PreviewCache::key(&theme_json, &heatmap_data, &format, &user_model.username);
+    let content = match preview_cache.get(&cache_key) {
+        Some(content) => content,
+        None => {
+            // Previews have a lane of their own, so they are not held up by
+            // generation jobs
+            let content = generator
+                .render(Lane::Interactive, &theme, &heatmap_data, &format, Some(&user_model.username))
+                .await
+                .map_err(|e| {
+                    log::error!("Failed to generate preview: {}", e);
+                    actix_web::error::ErrorInternalServerError("Failed to generate preview")
+                })?;
+            preview_cache.insert(cache_key, content)
+        }
+    };
 
     // Set appropriate content type
     let content_type = match format {
@@ -648,10 +1326,10 @@ pub async fn preview_theme(
         heatmap_theme::HeatmapFormat::WebP => "image/webp",
     };
 
-    Ok(HttpResponse::Ok().content_type(content_type).body(content))
+    Ok(HttpResponse::Ok().content_type(content_type).body(content.to_vec()))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PreviewThemeRequest {
     pub theme_mode: String,
     pub color_scheme: String,
@@ -684,6 +1362,14 @@ pub struct PreviewThemeRequest {
     pub font_family: Option<String>,
     pub font_size: Option<i32>,
     pub legend_position: Option<String>,
+    /// Defaults to `grid`
+    pub layout: Option<String>,
+    /// Drawn instead of the layout when not empty
+    pub custom_template: Option<String>,
+    /// Days the `mini` layout shows, 30 by default
+    pub mini_days: Option<i32>,
+    pub show_achievements: Option<bool>,
+    pub show_code_frequency: Option<bool>,
     pub preview_from_date: Option<String>,
     pub preview_to_date: Option<String>,
     pub preview_format: Option<String>, // svg, png, jpeg, webp
@@ -2,13 +2,19 @@ use actix_web::{web, HttpResponse, Responder};
 use sea_orm::sea_query::Expr;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema};
 use uuid::Uuid;
 
 use crate::models::{generated_heatmap, heatmap_theme};
+use crate::services::heatmap_renderers::{self, mini::MINI_DAYS};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::quotas;
+use crate::utils::config::Config;
+use crate::utils::regeneration;
 
 // ============ Request/Response DTOs ============
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HeatmapThemeResponse {
     pub id: String,
     pub name: String,
@@ -46,6 +52,16 @@ pub struct HeatmapThemeResponse {
     pub font_family: String,
     pub font_size: i32,
     pub legend_position: String,
+    /// Renderer that draws the heatmap: `grid`, `skyline`, `stats_card` or `badge`
+    pub layout: String,
+    /// SVG template drawn instead of the layout
+    pub custom_template: Option<String>,
+    /// Days of the `{slug}-mini.{ext}` files, null when there are none
+    pub mini_days: Option<i32>,
+    /// Whether the stats card lists the user's achievements
+    pub show_achievements: bool,
+    /// Whether a grid draws lines added and removed per week under the cells
+    pub show_code_frequency: bool,
     pub output_formats: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
@@ -104,10 +120,15 @@ impl From<heatmap_theme::Model> for HeatmapThemeResponse {
             font_family: model.font_family,
             font_size: model.font_size,
             legend_position: model.legend_position,
+            layout: model.layout,
+            custom_template: model.custom_template,
+            mini_days: model.mini_days,
+            show_achievements: model.show_achievements,
+            show_code_frequency: model.show_code_frequency,
             output_formats: model
                 .output_formats
                 .iter()
-                .map(|f| format_to_string_out(f))
+                .map(format_to_string_out)
                 .collect(),
             created_at: model.created_at.to_rfc3339(),
             updated_at: model.updated_at.to_rfc3339(),
@@ -124,7 +145,7 @@ fn format_to_string_out(format: &heatmap_theme::HeatmapFormat) -> String {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateThemeRequest {
     pub name: String,
     pub slug: String,
@@ -160,6 +181,16 @@ pub struct CreateThemeRequest {
     pub font_family: Option<String>,
     pub font_size: Option<i32>,
     pub legend_position: Option<String>,
+    /// Defaults to `grid`
+    pub layout: Option<String>,
+    /// minijinja template producing the whole SVG; empty for none
+    pub custom_template: Option<String>,
+    /// 30 or 90 to also generate `{slug}-mini.{ext}`; 0 or missing for none
+    pub mini_days: Option<i32>,
+    /// List the user's achievements on the stats card; off by default
+    pub show_achievements: Option<bool>,
+    /// Draw lines added and removed per week under a grid; off by default
+    pub show_code_frequency: Option<bool>,
     pub output_formats: Option<Vec<String>>,
 }
 
@@ -185,7 +216,50 @@ fn parse_output_formats(
     Ok(parsed)
 }
 
-#[derive(Debug, Deserialize)]
+/// Check that a renderer draws the layout
+pub fn parse_layout(layout: &str) -> Result<String, actix_web::Error> {
+    if heatmap_renderers::find(layout).is_none() {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "Invalid layout: {} (expected one of {})",
+            layout,
+            heatmap_renderers::layouts().join(", ")
+        )));
+    }
+    Ok(layout.to_string())
+}
+
+/// A template from a request: None to keep the current one, Some(None) to
+/// remove it, which a blank template does
+pub fn parse_custom_template(value: &Option<String>) -> Option<Option<String>> {
+    value
+        .as_ref()
+        .map(|template| Some(template.clone()).filter(|template| !template.trim().is_empty()))
+}
+
+/// Mini heatmap days from a request: None to keep the current ones,
+/// Some(None) to generate no mini heatmap, which 0 asks for
+pub fn parse_mini_days(value: Option<i32>) -> Result<Option<Option<i32>>, actix_web::Error> {
+    match value {
+        None => Ok(None),
+        Some(0) => Ok(Some(None)),
+        Some(days) if MINI_DAYS.contains(&days) => Ok(Some(Some(days))),
+        Some(days) => Err(actix_web::error::ErrorBadRequest(format!(
+            "Invalid mini_days: {} (expected 30, 90 or 0 for none)",
+            days
+        ))),
+    }
+}
+
+/// Refuse a theme whose custom template does not compile, fails or draws an
+/// unsafe SVG
+pub fn check_custom_template(theme: &heatmap_theme::Model) -> Result<(), actix_web::Error> {
+    if theme.custom_template.is_none() {
+        return Ok(());
+    }
+    heatmap_renderers::template::check(theme).map_err(actix_web::error::ErrorBadRequest)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateThemeRequest {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -220,6 +294,13 @@ pub struct UpdateThemeRequest {
     pub font_family: Option<String>,
     pub font_size: Option<i32>,
     pub legend_position: Option<String>,
+    pub layout: Option<String>,
+    /// An empty string removes the template
+    pub custom_template: Option<String>,
+    /// 30 or 90 days; 0 stops generating the mini heatmap
+    pub mini_days: Option<i32>,
+    pub show_achievements: Option<bool>,
+    pub show_code_frequency: Option<bool>,
     pub output_formats: Option<Vec<String>>,
 }
 
@@ -227,6 +308,13 @@ pub struct UpdateThemeRequest {
 
 /// GET /api/heatmap/themes
 /// List all themes for the authenticated user
+#[utoipa::path(
+    get,
+    path = "/heatmap/themes",
+    tag = "heatmap",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Themes of the current user", body = [HeatmapThemeResponse]))
+)]
 pub async fn list_themes(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -252,6 +340,17 @@ pub async fn list_themes(
 
 /// GET /api/heatmap/themes/:slug
 /// Get a specific theme by slug
+#[utoipa::path(
+    get,
+    path = "/heatmap/themes/{slug}",
+    tag = "heatmap",
+    params(("slug" = String, Path, description = "Theme slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Theme", body = HeatmapThemeResponse),
+        (status = 404, description = "Theme not found")
+    )
+)]
 pub async fn get_theme(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -279,14 +378,42 @@ pub async fn get_theme(
 
 /// POST /api/heatmap/themes
 /// Create a new theme
+#[utoipa::path(
+    post,
+    path = "/heatmap/themes",
+    tag = "heatmap",
+    request_body = CreateThemeRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Theme created", body = HeatmapThemeResponse),
+        (status = 400, description = "Invalid theme"),
+        (status = 403, description = "Theme or storage quota reached"),
+        (status = 409, description = "Slug already in use")
+    )
+)]
 pub async fn create_theme(
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     payload: web::Json<CreateThemeRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
+    if let Some(exceeded) = quotas::check_themes(db.as_ref(), &config, user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        return Ok(exceeded.response());
+    }
+    if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        return Ok(exceeded.response());
+    }
+
     // Check if slug already exists for this user
     let existing = heatmap_theme::Entity::find()
         .filter(heatmap_theme::Column::UserId.eq(user_id))
@@ -331,6 +458,11 @@ pub async fn create_theme(
         .as_ref()
         .map(|colors| serde_json::to_value(colors).unwrap());
 
+    let layout = match payload.layout {
+        Some(ref layout) => parse_layout(layout)?,
+        None => heatmap_renderers::DEFAULT_LAYOUT.to_string(),
+    };
+
     // Parse output formats
     let output_formats = if let Some(ref formats) = payload.output_formats {
         parse_output_formats(formats)?
@@ -403,11 +535,22 @@ pub async fn create_theme(
             .legend_position
             .clone()
             .unwrap_or_else(|| "bottom".to_string())),
-        output_formats: Set(output_formats),
+        layout: Set(layout),
+        custom_template: Set(parse_custom_template(&payload.custom_template).flatten()),
+        mini_days: Set(parse_mini_days(payload.mini_days)?.flatten()),
+        show_achievements: Set(payload.show_achievements.unwrap_or(false)),
+        show_code_frequency: Set(payload.show_code_frequency.unwrap_or(false)),
+        output_formats: Set(output_formats.into()),
         created_at: Set(chrono::Utc::now()),
         updated_at: Set(chrono::Utc::now()),
     };
 
+    let model = new_theme.clone().try_into_model().map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    check_custom_template(&model)?;
+
     let theme = heatmap_theme::Entity::insert(new_theme)
         .exec_with_returning(db.as_ref())
         .await
@@ -421,8 +564,22 @@ pub async fn create_theme(
 
 /// PUT /api/heatmap/themes/:slug
 /// Update an existing theme
+#[utoipa::path(
+    put,
+    path = "/heatmap/themes/{slug}",
+    tag = "heatmap",
+    params(("slug" = String, Path, description = "Theme slug")),
+    request_body = UpdateThemeRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Theme updated", body = HeatmapThemeResponse),
+        (status = 403, description = "Storage quota reached when adding a format"),
+        (status = 404, description = "Theme not found")
+    )
+)]
 pub async fn update_theme(
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
     payload: web::Json<UpdateThemeRequest>,
@@ -446,6 +603,8 @@ pub async fn update_theme(
         None => return Err(actix_web::error::ErrorNotFound("Theme not found")),
     };
 
+    let current_formats = theme.output_formats.clone();
+    let previous_theme = theme.clone();
     let mut active_theme: heatmap_theme::ActiveModel = theme.into();
 
     if let Some(name) = &payload.name {
@@ -539,10 +698,45 @@ pub async fn update_theme(
     update_field!(show_total_count, bool);
     update_field!(show_username, bool);
     update_field!(show_watermark, bool);
+    update_field!(show_achievements, bool);
+    update_field!(show_code_frequency, bool);
+
+    if let Some(ref layout) = payload.layout {
+        active_theme.layout = Set(parse_layout(layout)?);
+    }
+
+    if let Some(mini_days) = parse_mini_days(payload.mini_days)? {
+        active_theme.mini_days = Set(mini_days);
+    }
+
+    let template_changed = payload.custom_template.is_some();
+    if let Some(template) = parse_custom_template(&payload.custom_template) {
+        active_theme.custom_template = Set(template);
+    }
 
     if let Some(ref formats) = payload.output_formats {
         let parsed_formats = parse_output_formats(formats)?;
-        active_theme.output_formats = Set(parsed_formats);
+
+        // Adding a format generates new files, which the storage quota covers
+        let adds_format = parsed_formats.iter().any(|f| !current_formats.contains(f));
+        if adds_format {
+            if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })? {
+                return Ok(exceeded.response());
+            }
+        }
+
+        active_theme.output_formats = Set(parsed_formats.into());
+    }
+
+    if template_changed {
+        let model = active_theme.clone().try_into_model().map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+        check_custom_template(&model)?;
     }
 
     active_theme.updated_at = Set(chrono::Utc::now());
@@ -552,18 +746,32 @@ pub async fn update_theme(
         actix_web::error::ErrorInternalServerError("Failed to update theme")
     })?;
 
-    // Invalidate generated heatmaps for this theme
-    let _ = generated_heatmap::Entity::update_many()
-        .filter(generated_heatmap::Column::ThemeId.eq(updated_theme.id))
-        .col_expr(generated_heatmap::Column::IsValid, Expr::value(false))
-        .exec(db.as_ref())
-        .await;
+    // Invalidate generated heatmaps for this theme, unless only its name,
+    // description or formats changed
+    if regeneration::draws_differently(&previous_theme, &updated_theme) {
+        let _ = generated_heatmap::Entity::update_many()
+            .filter(generated_heatmap::Column::ThemeId.eq(updated_theme.id))
+            .col_expr(generated_heatmap::Column::IsValid, Expr::value(false))
+            .exec(db.as_ref())
+            .await;
+    }
 
     Ok(HttpResponse::Ok().json(HeatmapThemeResponse::from(updated_theme)))
 }
 
 /// DELETE /api/heatmap/themes/:slug
 /// Delete a theme
+#[utoipa::path(
+    delete,
+    path = "/heatmap/themes/{slug}",
+    tag = "heatmap",
+    params(("slug" = String, Path, description = "Theme slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Theme deleted"),
+        (status = 404, description = "Theme not found")
+    )
+)]
 pub async fn delete_theme(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -608,6 +816,17 @@ pub async fn delete_theme(
 
 /// POST /api/heatmap/themes/:slug/set-default
 /// Set a theme as the default
+#[utoipa::path(
+    post,
+    path = "/heatmap/themes/{slug}/set-default",
+    tag = "heatmap",
+    params(("slug" = String, Path, description = "Theme slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Theme is now the default", body = HeatmapThemeResponse),
+        (status = 404, description = "Theme not found")
+    )
+)]
 pub async fn set_default_theme(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -670,7 +889,7 @@ pub async fn set_default_theme(
     Ok(HttpResponse::Ok().json(HeatmapThemeResponse::from(updated_theme)))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct DuplicateThemeRequest {
     pub new_name: String,
     pub new_slug: String,
@@ -678,14 +897,42 @@ pub struct DuplicateThemeRequest {
 
 /// POST /api/heatmap/themes/:slug/duplicate
 /// Duplicate an existing theme
+#[utoipa::path(
+    post,
+    path = "/heatmap/themes/{slug}/duplicate",
+    tag = "heatmap",
+    params(("slug" = String, Path, description = "Slug of the theme to copy")),
+    request_body = DuplicateThemeRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Theme duplicated", body = HeatmapThemeResponse),
+        (status = 403, description = "Theme or storage quota reached"),
+        (status = 404, description = "Theme not found")
+    )
+)]
 pub async fn duplicate_theme(
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
     payload: web::Json<DuplicateThemeRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    if let Some(exceeded) = quotas::check_themes(db.as_ref(), &config, user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        return Ok(exceeded.response());
+    }
+    if let Some(exceeded) = quotas::check_storage(db.as_ref(), &instance, user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        return Ok(exceeded.response());
+    }
     let source_slug = path.into_inner();
 
     // Find the source theme
@@ -760,6 +1007,11 @@ pub async fn duplicate_theme(
         font_family: Set(source_theme.font_family.clone()),
         font_size: Set(source_theme.font_size),
         legend_position: Set(source_theme.legend_position.clone()),
+        layout: Set(source_theme.layout.clone()),
+        custom_template: Set(source_theme.custom_template.clone()),
+        mini_days: Set(source_theme.mini_days),
+        show_achievements: Set(source_theme.show_achievements),
+        show_code_frequency: Set(source_theme.show_code_frequency),
         output_formats: Set(source_theme.output_formats.clone()),
         created_at: Set(chrono::Utc::now()),
         updated_at: Set(chrono::Utc::now()),
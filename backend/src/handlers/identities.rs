@@ -0,0 +1,128 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{user, user_identity};
+use crate::services::identity::provider_name;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IdentityResponse {
+    pub id: String,
+    /// GitHub or GitLab
+    pub provider: String,
+    /// Empty for github.com
+    pub instance_url: String,
+    pub provider_username: String,
+    pub email: Option<String>,
+    pub created_at: String,
+    pub last_login_at: Option<String>,
+}
+
+impl From<user_identity::Model> for IdentityResponse {
+    fn from(model: user_identity::Model) -> Self {
+        Self {
+            id: model.id.to_string(),
+            provider: provider_name(&model.provider).to_string(),
+            instance_url: model.instance_url,
+            provider_username: model.provider_username,
+            email: model.email,
+            created_at: model.created_at.to_rfc3339(),
+            last_login_at: model.last_login_at.map(|d| d.to_rfc3339()),
+        }
+    }
+}
+
+/// GET /api/identities
+/// List the GitHub and GitLab identities the user can sign in with
+#[utoipa::path(
+    get,
+    path = "/identities",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Linked identities, oldest first", body = [IdentityResponse]))
+)]
+pub async fn list_identities(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let identities = user_identity::Entity::find()
+        .filter(user_identity::Column::UserId.eq(user_id))
+        .order_by_asc(user_identity::Column::CreatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let response: Vec<IdentityResponse> = identities.into_iter().map(IdentityResponse::from).collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// DELETE /api/identities/{id}
+/// Unlink an identity. The last one cannot be unlinked from a user without a password.
+#[utoipa::path(
+    delete,
+    path = "/identities/{id}",
+    tag = "auth",
+    params(("id" = Uuid, Path, description = "Identity ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Identity unlinked"),
+        (status = 400, description = "It is the user's only way to sign in"),
+        (status = 404, description = "Identity not found")
+    )
+)]
+pub async fn delete_identity(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let identity_id = path.into_inner();
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let user = user::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    let identities = user_identity::Entity::find()
+        .filter(user_identity::Column::UserId.eq(user_id))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if !identities.iter().any(|i| i.id == identity_id) {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Identity not found"
+        })));
+    }
+
+    if user.password_hash.is_none() && identities.len() == 1 {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "This is your only way to sign in. Link another identity or set a password first."
+        })));
+    }
+
+    user_identity::Entity::delete_by_id(identity_id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    log::info!("🗑️  Unlinked identity {} from user {}", identity_id, user_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
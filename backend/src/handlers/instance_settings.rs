@@ -0,0 +1,98 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::services::instance_settings::{InstanceSettings, InstanceSettingsService};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InstanceSettingsResponse {
+    /// Settings in effect
+    pub settings: InstanceSettings,
+    /// Values from the environment, used for keys that are not overridden
+    pub defaults: InstanceSettings,
+    /// Keys whose value is stored in the database
+    pub overridden: Vec<String>,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+async fn settings_response(instance: &InstanceSettingsService) -> Result<HttpResponse, actix_web::Error> {
+    let (settings, overridden) = instance.load().await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(InstanceSettingsResponse {
+        settings,
+        defaults: instance.defaults().clone(),
+        overridden,
+    }))
+}
+
+/// GET /api/admin/settings
+/// Get the instance settings and which of them override the environment (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/settings",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Instance settings", body = InstanceSettingsResponse),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn get_instance_settings(
+    db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    settings_response(&instance).await
+}
+
+/// PUT /api/admin/settings
+/// Change instance settings without a restart; `null` restores the
+/// environment value (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/settings",
+    tag = "admin",
+    request_body(content = HashMap<String, Object>, description = "Settings to change, e.g. {\"allow_registration\": false}"),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated instance settings", body = InstanceSettingsResponse),
+        (status = 400, description = "Unknown setting or invalid value"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn update_instance_settings(
+    db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<Map<String, Value>>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    if let Err(e) = instance.update(&body, user_id).await.map_err(db_error)? {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+    }
+
+    log::info!(
+        "⚙️  [Admin] Instance settings {} changed by {}",
+        body.keys().cloned().collect::<Vec<_>>().join(", "),
+        user_id
+    );
+
+    settings_response(&instance).await
+}
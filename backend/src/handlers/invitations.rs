@@ -0,0 +1,326 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::models::invitation;
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::invitations;
+use crate::services::mailer::Mailer;
+use crate::utils::api_token::hash_token;
+use crate::utils::pagination::{self, PageParams};
+use crate::utils::validators;
+
+/// Longest an invitation can stay valid
+const MAX_INVITATION_LIFETIME_DAYS: i64 = 365;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateInvitationRequest {
+    /// Restrict the code to this address and email it there when SMTP is configured
+    pub email: Option<String>,
+    /// How many accounts may register with the code; defaults to 1, null for unlimited
+    #[serde(default = "default_max_uses")]
+    pub max_uses: Option<i32>,
+    /// Days until the code expires; omit for a code that never expires
+    pub expires_in_days: Option<i64>,
+}
+
+fn default_max_uses() -> Option<i32> {
+    Some(1)
+}
+
+/// Replaces an invitation's limits; omitted fields remove the limit
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateInvitationRequest {
+    pub max_uses: Option<i32>,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub email: Option<String>,
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    pub expires_at: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedInvitationResponse {
+    /// The code itself. It is only returned once and cannot be retrieved later.
+    pub code: String,
+    /// Whether the code was emailed to `email`
+    pub email_sent: bool,
+    #[serde(flatten)]
+    pub info: InvitationResponse,
+}
+
+impl From<invitation::Model> for InvitationResponse {
+    fn from(model: invitation::Model) -> Self {
+        Self {
+            id: model.id.to_string(),
+            email: model.email,
+            max_uses: model.max_uses,
+            use_count: model.use_count,
+            expires_at: model.expires_at.map(|d| d.to_rfc3339()),
+            created_by: model.created_by.map(|id| id.to_string()),
+            created_at: model.created_at.to_rfc3339(),
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// Check the requested limits, returning the expiry time or a client error
+fn parse_limits(max_uses: Option<i32>, expires_in_days: Option<i64>) -> Result<Option<DateTime<Utc>>, String> {
+    if max_uses.is_some_and(|uses| uses < 1) {
+        return Err("max_uses must be at least 1".to_string());
+    }
+    match expires_in_days {
+        Some(days) if !(1..=MAX_INVITATION_LIFETIME_DAYS).contains(&days) => Err(format!(
+            "expires_in_days must be between 1 and {}",
+            MAX_INVITATION_LIFETIME_DAYS
+        )),
+        Some(days) => Ok(Some(Utc::now() + Duration::days(days))),
+        None => Ok(None),
+    }
+}
+
+/// GET /api/admin/invitations
+/// List invitations with how often each was used (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/invitations",
+    tag = "admin",
+    params(PageParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invitations; total in X-Total-Count, next page in X-Next-Cursor", body = [InvitationResponse]),
+        (status = 400, description = "Invalid sort or cursor"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn list_invitations(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    page: web::Query<PageParams>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let invitations = match pagination::fetch_page(
+        db.as_ref(),
+        invitation::Entity::find(),
+        &page,
+        &[
+            ("created_at", invitation::Column::CreatedAt),
+            ("expires_at", invitation::Column::ExpiresAt),
+            ("use_count", invitation::Column::UseCount),
+        ],
+        "created_at",
+        invitation::Column::Id,
+    )
+    .await
+    .map_err(db_error)?
+    {
+        Ok(p) => p,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    Ok(invitations
+        .map(InvitationResponse::from)
+        .into_response(page.fields.as_deref()))
+}
+
+/// POST /api/admin/invitations
+/// Create an invitation code, emailing it when an address is given (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/invitations",
+    tag = "admin",
+    request_body = CreateInvitationRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Invitation created; the code is only shown once", body = CreatedInvitationResponse),
+        (status = 400, description = "Invalid email or limits"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn create_invitation(
+    db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<CreateInvitationRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let email = body
+        .email
+        .as_ref()
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+    if let Some(email) = &email {
+        if let Err(e) = validators::validate_email(email) {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e.to_string() })));
+        }
+    }
+
+    let expires_at = match parse_limits(body.max_uses, body.expires_in_days) {
+        Ok(expires_at) => expires_at,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let code = invitations::generate_code();
+
+    let model = invitation::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        code_hash: Set(hash_token(&code)),
+        email: Set(email.clone()),
+        max_uses: Set(body.max_uses),
+        use_count: Set(0),
+        expires_at: Set(expires_at),
+        created_by: Set(Some(user_id)),
+        created_at: Set(Utc::now()),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    log::info!("🎟️  [Admin] Invitation {} created by {}", model.id, user_id);
+
+    // A failed email is not fatal; the admin can still pass the code on
+    let mut email_sent = false;
+    if let Some(email) = &email {
+        if mailer.is_enabled() {
+            let link = format!(
+                "{}/register?invite={}",
+                instance.current().await.frontend_url,
+                urlencoding::encode(&code)
+            );
+            let expiry = match &model.expires_at {
+                Some(expires_at) => format!("The invitation expires on {}.", expires_at.format("%Y-%m-%d")),
+                None => "The invitation does not expire.".to_string(),
+            };
+            let message = format!(
+                "Hi,\n\nYou have been invited to create an account on hgitmap. Open this link to sign up:\n\n{}\n\nOr register with the invitation code {}\n\n{}\n",
+                link, code, expiry
+            );
+            match mailer.send(email, "You're invited to hgitmap", message).await {
+                Ok(()) => email_sent = true,
+                Err(e) => log::warn!("⚠️  Failed to send invitation email: {}", e),
+            }
+        }
+    }
+
+    Ok(HttpResponse::Created().json(CreatedInvitationResponse {
+        code,
+        email_sent,
+        info: model.into(),
+    }))
+}
+
+/// PUT /api/admin/invitations/:id
+/// Change an invitation's usage limit and expiry (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/invitations/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "Invitation ID")),
+    request_body = UpdateInvitationRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invitation updated", body = InvitationResponse),
+        (status = 400, description = "Invalid limits"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Invitation not found")
+    )
+)]
+pub async fn update_invitation(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: web::Json<UpdateInvitationRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let invitation_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid invitation ID: {}", e)))?;
+
+    let expires_at = match parse_limits(body.max_uses, body.expires_in_days) {
+        Ok(expires_at) => expires_at,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let existing = invitation::Entity::find_by_id(invitation_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Invitation not found"))?;
+
+    let mut active: invitation::ActiveModel = existing.into();
+    active.max_uses = Set(body.max_uses);
+    active.expires_at = Set(expires_at);
+    let updated = active.update(db.as_ref()).await.map_err(db_error)?;
+
+    log::info!("🎟️  [Admin] Invitation {} updated by {}", updated.id, user_id);
+
+    Ok(HttpResponse::Ok().json(InvitationResponse::from(updated)))
+}
+
+/// DELETE /api/admin/invitations/:id
+/// Revoke an invitation; accounts created with it are kept (admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/invitations/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "Invitation ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Invitation revoked"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Invitation not found")
+    )
+)]
+pub async fn delete_invitation(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let invitation_id = Uuid::parse_str(&path.into_inner())
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid invitation ID: {}", e)))?;
+
+    let result = invitation::Entity::delete_by_id(invitation_id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected == 0 {
+        return Err(actix_web::error::ErrorNotFound("Invitation not found"));
+    }
+
+    log::info!("🗑️  [Admin] Invitation {} revoked by {}", invitation_id, user_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
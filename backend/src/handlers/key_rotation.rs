@@ -0,0 +1,77 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::services::key_rotation::{self, RotationReport};
+use crate::utils::config::Config;
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// GET /api/admin/encryption-keys
+/// Stored secrets per encryption key, and how many a rotation would re-encrypt (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/encryption-keys",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Secrets per key and what a rotation would re-encrypt", body = RotationReport),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn preview_key_rotation(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let report = key_rotation::run(db.as_ref(), &config.encryption_key, true)
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// POST /api/admin/encryption-keys/rotate
+/// Re-encrypt every platform token and OAuth app secret with the newest key (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/encryption-keys/rotate",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Secrets re-encrypted and the keys that can now be retired", body = RotationReport),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn run_key_rotation(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let report = key_rotation::run(db.as_ref(), &config.encryption_key, false)
+        .await
+        .map_err(db_error)?;
+
+    log::info!(
+        "🔑 [Admin] Key rotation by {} re-encrypted {} secret(s) with key {}",
+        user_id,
+        report.re_encrypted,
+        report.current_key_id
+    );
+
+    Ok(HttpResponse::Ok().json(report))
+}
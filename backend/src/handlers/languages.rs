@@ -0,0 +1,59 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use sea_orm::*;
+
+use crate::handlers::auth::ErrorResponse;
+use crate::services::{profile_visibility, repository_languages, username_redirects};
+use crate::utils::config::Config;
+use crate::utils::languages::LanguageShare;
+
+/// GET /api/users/:username/languages
+/// Public endpoint to get the languages of the repositories a user
+/// contributed to, by bytes of code, as of their last syncs
+#[utoipa::path(
+    get,
+    path = "/users/{username}/languages",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "Languages, largest first", body = [LanguageShare]),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_user_languages(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let username = path.into_inner();
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = profile_visibility::find(db.as_ref(), user_model.id)
+        .await
+        .map_err(db_error)?;
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_model.id, &visibility)
+        .await
+        .map_err(db_error)?
+    {
+        return Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "User not found".to_string(),
+        }));
+    }
+
+    let languages = repository_languages::for_user(db.as_ref(), user_model.id)
+        .await
+        .map_err(db_error)?;
+
+    let mut response = HttpResponse::Ok().json(languages);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
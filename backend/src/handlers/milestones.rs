@@ -0,0 +1,64 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::NaiveDate;
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::milestone;
+
+/// A milestone the user reached
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MilestoneResponse {
+    pub id: String,
+    /// `contributions`, `streak` or `organization`
+    pub kind: String,
+    /// The threshold reached, or the organization in lowercase
+    pub key: String,
+    pub title: String,
+    pub achieved_on: NaiveDate,
+    /// When it was detected, usually after the sync that found it
+    pub recorded_at: String,
+}
+
+/// GET /api/milestones
+/// Milestones the user reached, newest first
+#[utoipa::path(
+    get,
+    path = "/milestones",
+    tag = "activities",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Milestones, newest first", body = [MilestoneResponse]))
+)]
+pub async fn list_milestones(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let milestones = milestone::Entity::find()
+        .filter(milestone::Column::UserId.eq(user_id))
+        .order_by_desc(milestone::Column::AchievedOn)
+        .order_by_desc(milestone::Column::CreatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let response: Vec<MilestoneResponse> = milestones
+        .into_iter()
+        .map(|m| MilestoneResponse {
+            id: m.id.to_string(),
+            kind: m.kind,
+            key: m.milestone_key,
+            title: m.title,
+            achieved_on: m.achieved_on,
+            recorded_at: m.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
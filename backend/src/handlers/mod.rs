@@ -1,13 +1,41 @@
+pub mod account;
+pub mod admin_jobs;
+pub mod achievements;
 pub mod activities;
+pub mod activity_imports;
+pub mod annotations;
+pub mod announcements;
+pub mod api_tokens;
+pub mod api_usage;
 pub mod auth;
+pub mod cleanup;
+pub mod commit_emails;
 pub mod contributions;
+pub mod directory;
+pub mod events;
+pub mod follows;
 pub mod fonts;
+pub mod graphql;
+pub mod health;
 pub mod heatmap_generation;
 pub mod heatmap_themes;
+pub mod identities;
+pub mod instance_settings;
+pub mod invitations;
+pub mod key_rotation;
+pub mod languages;
+pub mod milestones;
+pub mod mqtt;
+pub mod notification_channels;
 pub mod oauth;
 pub mod oauth_apps;
+pub mod oidc;
 pub mod platform_accounts;
 pub mod platform_sync_jobs;
+pub mod retention;
+pub mod sessions;
 pub mod settings;
 pub mod static_files;
 pub mod sync;
+pub mod teams;
+pub mod today;
@@ -0,0 +1,272 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::mqtt_publisher;
+use crate::services::mqtt_publisher::{self as publisher, ENABLED};
+use crate::utils::config::Config;
+use crate::utils::{encryption, mqtt};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMqttRequest {
+    /// `mqtt://host[:port]`, or `mqtts://` for TLS. Ports default to 1883
+    /// and 8883.
+    pub broker_url: String,
+    /// Prefix of the published topics, like `hgitmap/alice`
+    pub topic: String,
+    pub username: Option<String>,
+    /// Left out to keep the stored password, empty to remove it
+    pub password: Option<String>,
+    /// Also publish Home Assistant discovery configs, so the sensors show up
+    /// there on their own
+    #[serde(default)]
+    pub home_assistant_discovery: bool,
+}
+
+/// Where the user's daily totals are published. The password is never
+/// returned.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MqttSettingsResponse {
+    /// Whether this instance was built with MQTT support
+    pub available: bool,
+    /// Whether a broker is set up; the other fields are null when not
+    pub configured: bool,
+    pub broker_url: Option<String>,
+    pub topic: Option<String>,
+    pub username: Option<String>,
+    pub has_password: bool,
+    pub home_assistant_discovery: bool,
+    pub last_published_at: Option<String>,
+    /// Why the last publish failed; null after a successful one
+    pub last_error: Option<String>,
+}
+
+impl From<Option<mqtt_publisher::Model>> for MqttSettingsResponse {
+    fn from(model: Option<mqtt_publisher::Model>) -> Self {
+        match model {
+            Some(model) => Self {
+                available: ENABLED,
+                configured: true,
+                broker_url: Some(model.broker_url),
+                topic: Some(model.topic),
+                username: model.username,
+                has_password: model.password.is_some(),
+                home_assistant_discovery: model.home_assistant_discovery,
+                last_published_at: model.last_published_at.map(|t| t.to_rfc3339()),
+                last_error: model.last_error,
+            },
+            None => Self {
+                available: ENABLED,
+                configured: false,
+                broker_url: None,
+                topic: None,
+                username: None,
+                has_password: false,
+                home_assistant_discovery: false,
+                last_published_at: None,
+                last_error: None,
+            },
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+fn unavailable() -> HttpResponse {
+    HttpResponse::ServiceUnavailable().json(serde_json::json!({
+        "error": "MQTT is not available on this instance"
+    }))
+}
+
+/// GET /api/settings/mqtt
+/// Get the MQTT broker your daily totals are published to
+#[utoipa::path(
+    get,
+    path = "/settings/mqtt",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "MQTT publishing settings", body = MqttSettingsResponse))
+)]
+pub async fn get_mqtt(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let publisher = mqtt_publisher::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(MqttSettingsResponse::from(publisher)))
+}
+
+/// PUT /api/settings/mqtt
+/// Publish your contributions today, streak and last year of daily counts
+/// to an MQTT broker after each sync, for LED matrices and smart-home
+/// displays
+#[utoipa::path(
+    put,
+    path = "/settings/mqtt",
+    tag = "settings",
+    request_body = UpdateMqttRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated MQTT publishing settings", body = MqttSettingsResponse),
+        (status = 400, description = "Invalid broker URL or topic"),
+        (status = 503, description = "MQTT is not available on this instance")
+    )
+)]
+pub async fn update_mqtt(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    payload: web::Json<UpdateMqttRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    if !ENABLED {
+        return Ok(unavailable());
+    }
+
+    let payload = payload.into_inner();
+    let broker_url = payload.broker_url.trim().to_string();
+    let topic = match mqtt::parse_broker_url(&broker_url).and_then(|_| mqtt::normalize_topic(&payload.topic)) {
+        Ok(topic) => topic,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    };
+    let username = payload
+        .username
+        .map(|username| username.trim().to_string())
+        .filter(|username| !username.is_empty());
+
+    let existing = mqtt_publisher::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let password = match payload.password {
+        None => existing.as_ref().and_then(|p| p.password.clone()),
+        Some(password) if password.is_empty() => None,
+        Some(password) => Some(encryption::encrypt(&password, &config.encryption_key).map_err(|e| {
+            log::error!("Failed to encrypt MQTT password: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to store password")
+        })?),
+    };
+
+    let now = Utc::now();
+    let saved = match existing {
+        Some(existing) => {
+            let mut active: mqtt_publisher::ActiveModel = existing.into();
+            active.broker_url = Set(broker_url);
+            active.topic = Set(topic);
+            active.username = Set(username);
+            active.password = Set(password);
+            active.home_assistant_discovery = Set(payload.home_assistant_discovery);
+            // Earlier results were for the old broker
+            active.last_error = Set(None);
+            active.updated_at = Set(now);
+            active.update(db.as_ref()).await.map_err(db_error)?
+        }
+        None => mqtt_publisher::ActiveModel {
+            user_id: Set(user_id),
+            broker_url: Set(broker_url),
+            topic: Set(topic),
+            username: Set(username),
+            password: Set(password),
+            home_assistant_discovery: Set(payload.home_assistant_discovery),
+            last_published_at: Set(None),
+            last_error: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db.as_ref())
+        .await
+        .map_err(db_error)?,
+    };
+
+    log::info!("📡 MQTT publishing set up for user {}", user_id);
+
+    Ok(HttpResponse::Ok().json(MqttSettingsResponse::from(Some(saved))))
+}
+
+/// DELETE /api/settings/mqtt
+/// Stop publishing to your MQTT broker. Retained messages stay on the broker
+/// until cleared there.
+#[utoipa::path(
+    delete,
+    path = "/settings/mqtt",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 204, description = "MQTT publishing turned off"))
+)]
+pub async fn delete_mqtt(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    mqtt_publisher::Entity::delete_by_id(user_id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/settings/mqtt/test
+/// Publish your current numbers right away, to check the broker settings
+#[utoipa::path(
+    post,
+    path = "/settings/mqtt/test",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Published", body = MqttSettingsResponse),
+        (status = 404, description = "No broker set up"),
+        (status = 502, description = "The broker could not be reached or refused the messages"),
+        (status = 503, description = "MQTT is not available on this instance")
+    )
+)]
+pub async fn test_mqtt(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    if !ENABLED {
+        return Ok(unavailable());
+    }
+
+    let Some(published) = publisher::publish_for_user(db.as_ref(), &config.encryption_key, user_id)
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No MQTT broker set up"
+        })));
+    };
+
+    if let Some(error) = published.last_error {
+        return Ok(HttpResponse::BadGateway().json(serde_json::json!({
+            "error": error
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(MqttSettingsResponse::from(Some(published))))
+}
@@ -0,0 +1,466 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use rand::RngCore;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::json_list::JsonList;
+use crate::models::{notification_channel, user};
+use crate::services::mailer::Mailer;
+use crate::services::notification_channels::{
+    self, bluesky, ChannelContext, ChannelEvent, Email, Mqtt, NotificationChannel, Payload, BLUESKY, DISCORD,
+    EMAIL, EVENTS, KINDS, MASTODON, MQTT, SLACK, WEBHOOK, WEEKLY_SUMMARY,
+};
+use crate::services::weekly_summary::next_post_at;
+use crate::utils::config::Config;
+use crate::utils::notification_channels::{is_discord_webhook_url, is_slack_webhook_url, normalize_events};
+use crate::utils::{encryption, validators};
+
+/// Most channels a user can connect
+const MAX_CHANNELS: u64 = 10;
+
+/// Longest webhook URL, as `notification_channels.instance_url` holds it
+const MAX_WEBHOOK_URL_LENGTH: usize = 512;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateNotificationChannelRequest {
+    /// `mastodon`, `bluesky`, `email`, `webhook`, `slack`, `discord` or `mqtt`
+    pub kind: String,
+    /// The Mastodon instance, like `https://mastodon.social`; for Bluesky
+    /// the PDS, `https://bsky.social` when left out. The webhook URL for
+    /// `webhook`, `slack` and `discord`.
+    pub instance_url: Option<String>,
+    /// The Bluesky handle, like `alice.bsky.social`; not used otherwise
+    pub account: Option<String>,
+    /// A Mastodon access token with the `write:statuses` and `write:media`
+    /// scopes, or a Bluesky app password; not used otherwise
+    pub token: Option<String>,
+    /// The events sent to the channel, from `weekly_summary`, `milestone`
+    /// and `sync_failed`. Mastodon and Bluesky get the weekly summary when
+    /// left out, other kinds every event.
+    pub events: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateNotificationChannelRequest {
+    /// The events sent to the channel; an empty list pauses it
+    pub events: Vec<String>,
+}
+
+/// A place the user is notified at. Tokens and webhook URLs are never
+/// returned.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationChannelResponse {
+    pub id: Uuid,
+    /// `mastodon`, `bluesky`, `email`, `webhook`, `slack`, `discord` or `mqtt`
+    pub kind: String,
+    /// The Mastodon instance, Bluesky PDS or webhook URL; the host of Slack
+    /// and Discord webhooks; empty for email and MQTT
+    pub instance_url: String,
+    /// The handle posted as, the email address, the Discord webhook's name
+    /// or the MQTT topic events go under
+    pub account: String,
+    /// The events sent to the channel
+    pub events: Vec<String>,
+    /// When the next weekly summary goes out, if the channel gets them
+    pub next_post_at: Option<String>,
+    /// Why the last send failed; null after a successful one
+    pub last_error: Option<String>,
+    pub created_at: String,
+    /// The secret webhook bodies are signed with, in `X-Hgitmap-Signature`.
+    /// Only returned when a webhook is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+}
+
+impl From<notification_channel::Model> for NotificationChannelResponse {
+    fn from(model: notification_channel::Model) -> Self {
+        Self {
+            id: model.id,
+            next_post_at: model
+                .events
+                .iter()
+                .any(|e| e == WEEKLY_SUMMARY)
+                .then(|| next_post_at(&model).to_rfc3339()),
+            kind: model.kind,
+            instance_url: model.instance_url,
+            account: model.account,
+            events: model.events.to_vec(),
+            last_error: model.last_error,
+            created_at: model.created_at.to_rfc3339(),
+            signing_secret: None,
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+fn encrypt_secret(secret: &str, encryption_key: &str) -> Result<String, actix_web::Error> {
+    encryption::encrypt(secret, encryption_key).map_err(|e| {
+        log::error!("Failed to encrypt channel secret: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to store token")
+    })
+}
+
+/// GET /api/settings/channels
+/// The places you are notified at
+#[utoipa::path(
+    get,
+    path = "/settings/channels",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Notification channels, oldest first", body = [NotificationChannelResponse]))
+)]
+pub async fn list_notification_channels(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let channels = notification_channel::Entity::find()
+        .filter(notification_channel::Column::UserId.eq(user_id))
+        .order_by_asc(notification_channel::Column::CreatedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(
+        channels
+            .into_iter()
+            .map(NotificationChannelResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// POST /api/settings/channels
+/// Connect a place to be notified at. The channel is checked first: social
+/// accounts by their credentials, Slack by a message saying it is connected.
+/// The first weekly summary goes out a week later.
+#[utoipa::path(
+    post,
+    path = "/settings/channels",
+    tag = "settings",
+    request_body = CreateNotificationChannelRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Channel connected; webhooks include their signing secret", body = NotificationChannelResponse),
+        (status = 400, description = "Unknown kind or event, invalid URL, missing handle or token, failed check, or too many channels")
+    )
+)]
+pub async fn create_notification_channel(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<CreateNotificationChannelRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let bad_request = |error: String| HttpResponse::BadRequest().json(serde_json::json!({ "error": error }));
+
+    let body = body.into_inner();
+    let kind = body.kind.trim().to_lowercase();
+    if !KINDS.contains(&kind.as_str()) {
+        return Ok(bad_request(format!("kind must be one of: {}", KINDS.join(", "))));
+    }
+
+    let events = match &body.events {
+        Some(events) => match normalize_events(events, EVENTS) {
+            Ok(events) => events,
+            Err(e) => return Ok(bad_request(e)),
+        },
+        None if kind == MASTODON || kind == BLUESKY => vec![WEEKLY_SUMMARY.to_string()],
+        None => EVENTS.iter().map(|e| String::from(*e)).collect(),
+    };
+
+    let existing = notification_channel::Entity::find()
+        .filter(notification_channel::Column::UserId.eq(user_id))
+        .count(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if existing >= MAX_CHANNELS {
+        return Ok(bad_request(format!("At most {} channels can be connected", MAX_CHANNELS)));
+    }
+
+    let url = body
+        .instance_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+    let token = body.token.as_deref().map(str::trim).unwrap_or_default().to_string();
+
+    // What is stored for each kind, and the client that checks it
+    let (instance_url, account, secret, client): (String, String, String, Box<dyn NotificationChannel>) =
+        match kind.as_str() {
+            MASTODON | BLUESKY => {
+                let instance_url = match (url, kind.as_str()) {
+                    (Some(url), _) => url.to_string(),
+                    (None, BLUESKY) => bluesky::DEFAULT_SERVICE_URL.to_string(),
+                    _ => return Ok(bad_request("instance_url is required for Mastodon".to_string())),
+                };
+                let instance_url = match validators::validate_instance_url(&instance_url) {
+                    Ok(url) => url,
+                    Err(e) => return Ok(bad_request(e.to_string())),
+                };
+                let account = body
+                    .account
+                    .as_deref()
+                    .map(|a| a.trim().trim_start_matches('@').to_string())
+                    .unwrap_or_default();
+                if kind == BLUESKY && account.is_empty() {
+                    return Ok(bad_request("account is required for Bluesky".to_string()));
+                }
+                if token.is_empty() {
+                    return Ok(bad_request("token is required".to_string()));
+                }
+                let client = notification_channels::build(&kind, &instance_url, &account, token.clone())
+                    .map_err(actix_web::error::ErrorInternalServerError)?;
+                (instance_url, account, token, client)
+            }
+            EMAIL => {
+                let address = user::Entity::find_by_id(user_id)
+                    .one(db.as_ref())
+                    .await
+                    .map_err(db_error)?
+                    .and_then(|u| u.email);
+                let Some(address) = address else {
+                    return Ok(bad_request("Add an email address to your account first".to_string()));
+                };
+                let client = Box::new(Email::new(mailer.clone().into_inner(), address.clone()));
+                (String::new(), address, String::new(), client)
+            }
+            MQTT => {
+                let client = Box::new(Mqtt::new(db.get_ref().clone(), config.encryption_key.clone(), user_id));
+                (String::new(), String::new(), String::new(), client)
+            }
+            _ => {
+                let Some(url) = url else {
+                    return Ok(bad_request(format!("instance_url is required for {}", kind)));
+                };
+                if url.len() > MAX_WEBHOOK_URL_LENGTH {
+                    return Ok(bad_request(format!(
+                        "instance_url must be at most {} characters",
+                        MAX_WEBHOOK_URL_LENGTH
+                    )));
+                }
+                if let Err(e) = validators::validate_url(url) {
+                    return Ok(bad_request(e.to_string()));
+                }
+                match kind.as_str() {
+                    SLACK if !is_slack_webhook_url(url) => {
+                        return Ok(bad_request(
+                            "instance_url must be a Slack webhook, https://hooks.slack.com/services/...".to_string(),
+                        ));
+                    }
+                    DISCORD if !is_discord_webhook_url(url) => {
+                        return Ok(bad_request(
+                            "instance_url must be a Discord webhook, https://discord.com/api/webhooks/...".to_string(),
+                        ));
+                    }
+                    _ => {}
+                }
+
+                // Webhooks are keyed by a secret of ours; Slack and Discord
+                // webhook URLs are the secret, so only their host is shown
+                let (instance_url, secret) = if kind == WEBHOOK {
+                    let mut bytes = [0u8; 32];
+                    rand::thread_rng().fill_bytes(&mut bytes);
+                    (url.to_string(), hex::encode(bytes))
+                } else {
+                    let host = validators::validate_instance_url(url).map_err(actix_web::error::ErrorBadRequest)?;
+                    (host, url.to_string())
+                };
+                let client = notification_channels::build(&kind, &instance_url, "", secret.clone())
+                    .map_err(actix_web::error::ErrorInternalServerError)?;
+                (instance_url, String::new(), secret, client)
+            }
+        };
+
+    // Only working channels are stored; most also tell what they are known by
+    let account = match client.verify().await {
+        Ok(known_as) if kind != BLUESKY && kind != EMAIL => known_as,
+        Ok(_) => account,
+        Err(e) => return Ok(bad_request(format!("{:#}", e))),
+    };
+
+    // Email and MQTT keep nothing secret here
+    let stored_secret = if secret.is_empty() {
+        String::new()
+    } else {
+        encrypt_secret(&secret, &config.encryption_key)?
+    };
+
+    let now = Utc::now();
+    let created = notification_channel::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        kind: Set(kind),
+        instance_url: Set(instance_url),
+        account: Set(account),
+        secret: Set(stored_secret),
+        last_sent_at: Set(now),
+        last_error: Set(None),
+        created_at: Set(now),
+        events: Set(JsonList(events)),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    log::info!("📣 User {} connected a {} channel", user_id, created.kind);
+
+    let is_webhook = created.kind == WEBHOOK;
+    let mut response = NotificationChannelResponse::from(created);
+    if is_webhook {
+        response.signing_secret = Some(secret);
+    }
+    Ok(HttpResponse::Created().json(response))
+}
+
+/// PUT /api/settings/channels/:id
+/// Choose the events sent to a channel
+#[utoipa::path(
+    put,
+    path = "/settings/channels/{id}",
+    tag = "settings",
+    params(("id" = Uuid, Path, description = "Channel ID")),
+    request_body = UpdateNotificationChannelRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Channel updated", body = NotificationChannelResponse),
+        (status = 400, description = "Unknown event"),
+        (status = 404, description = "Channel not found")
+    )
+)]
+pub async fn update_notification_channel(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+    body: web::Json<UpdateNotificationChannelRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let events = match normalize_events(&body.events, EVENTS) {
+        Ok(events) => events,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+
+    let Some(channel) = notification_channel::Entity::find_by_id(path.into_inner())
+        .filter(notification_channel::Column::UserId.eq(user_id))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Channel not found"
+        })));
+    };
+
+    let mut active: notification_channel::ActiveModel = channel.into();
+    active.events = Set(JsonList(events));
+    let updated = active.update(db.as_ref()).await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(NotificationChannelResponse::from(updated)))
+}
+
+/// POST /api/settings/channels/:id/test
+/// Send a test notification to a channel. On Mastodon and Bluesky this is a
+/// public post.
+#[utoipa::path(
+    post,
+    path = "/settings/channels/{id}/test",
+    tag = "settings",
+    params(("id" = Uuid, Path, description = "Channel ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Sent", body = NotificationChannelResponse),
+        (status = 404, description = "Channel not found"),
+        (status = 502, description = "The channel could not be reached; the error is returned")
+    )
+)]
+pub async fn test_notification_channel(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let Some(channel) = notification_channel::Entity::find_by_id(path.into_inner())
+        .filter(notification_channel::Column::UserId.eq(user_id))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+    else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Channel not found"
+        })));
+    };
+
+    let ctx = ChannelContext {
+        db: db.get_ref().clone(),
+        encryption_key: config.encryption_key.clone(),
+        mailer: mailer.into_inner(),
+    };
+    let payload = Payload {
+        title: "hgitmap test notification".to_string(),
+        text: "This is a test notification from hgitmap.".to_string(),
+        image: None,
+        data: serde_json::json!({}),
+        idempotency_key: format!("hgitmap-test-{}", Uuid::new_v4()),
+    };
+    let channel = notification_channels::send_to(&ctx, channel, ChannelEvent::Test, &payload)
+        .await
+        .map_err(db_error)?;
+
+    match &channel.last_error {
+        None => Ok(HttpResponse::Ok().json(NotificationChannelResponse::from(channel))),
+        Some(error) => Ok(HttpResponse::BadGateway().json(serde_json::json!({ "error": error }))),
+    }
+}
+
+/// DELETE /api/settings/channels/:id
+/// Stop notifying a channel and forget its secrets
+#[utoipa::path(
+    delete,
+    path = "/settings/channels/{id}",
+    tag = "settings",
+    params(("id" = Uuid, Path, description = "Channel ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Channel removed"),
+        (status = 404, description = "Channel not found")
+    )
+)]
+pub async fn delete_notification_channel(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let deleted = notification_channel::Entity::delete_many()
+        .filter(notification_channel::Column::Id.eq(path.into_inner()))
+        .filter(notification_channel::Column::UserId.eq(user_id))
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    if deleted.rows_affected == 0 {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Channel not found"
+        })));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,20 +1,27 @@
 use actix_web::{web, HttpResponse, Responder};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sea_orm::*;
 use uuid::Uuid;
 use chrono::{Duration, Utc};
 
-use crate::models::{git_platform_account, oauth_application, oauth_state};
+use crate::handlers::auth::{start_session, AuthResponse, ErrorResponse};
+use crate::models::{git_platform_account, oauth_application, oauth_application_setting, oauth_state, user};
 use crate::services::git_platforms::{github::GitHubClient, gitea::GiteaClient, gitlab::GitLabClient, GitPlatform, PlatformConfig};
+use crate::services::identity::{self, IdentityError};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::quotas;
 use crate::utils::{config::Config, encryption};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct OAuthAuthorizeQuery {
     pub instance_url: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GitHubCallbackQuery {
     pub code: Option<String>,
     pub state: Option<String>,
@@ -42,13 +49,44 @@ fn transform_error_message(message: &str) -> String {
     }
 }
 
+/// Scopes requested when the OAuth app sets none
+pub(crate) fn default_scopes(platform: &git_platform_account::GitPlatform) -> &'static str {
+    match platform {
+        // read:user for profile, repo for private contributions. 'repo'
+        // includes write access but we only use it to read contribution data
+        git_platform_account::GitPlatform::GitHub => "read:user,repo",
+        git_platform_account::GitPlatform::GitLab => "read_user read_api",
+        git_platform_account::GitPlatform::Gitea => "read:repository,read:user,read:organization",
+    }
+}
+
+/// Redirect URI sent when the OAuth app sets none: the callback route under
+/// BASE_URL
+pub(crate) fn default_redirect_uri(config: &Config, platform: &git_platform_account::GitPlatform) -> String {
+    let platform = match platform {
+        git_platform_account::GitPlatform::GitHub => "github",
+        git_platform_account::GitPlatform::GitLab => "gitlab",
+        git_platform_account::GitPlatform::Gitea => "gitea",
+    };
+    format!("{}/oauth/{}/callback", config.base_url, platform)
+}
+
+/// An enabled OAuth app's credentials, with its scopes and redirect URI
+/// resolved against the defaults
+struct OAuthCredentials {
+    client_id: String,
+    client_secret: String,
+    scopes: String,
+    redirect_uri: String,
+}
+
 /// Helper function to get OAuth credentials for a platform from database
 async fn get_oauth_credentials(
     db: &DatabaseConnection,
     config: &Config,
     platform: git_platform_account::GitPlatform,
     instance_url: &str,
-) -> Result<(String, String), actix_web::Error> {
+) -> Result<OAuthCredentials, actix_web::Error> {
     // Get from database
     let oauth_app = oauth_application::Entity::find()
         .filter(oauth_application::Column::Platform.eq(platform.clone()))
@@ -70,8 +108,24 @@ async fn get_oauth_credentials(
                 actix_web::error::ErrorInternalServerError("Failed to decrypt credentials")
             })?;
 
+        let settings = oauth_application_setting::Entity::find_by_id(app.id)
+            .one(db)
+            .await
+            .map_err(|e| {
+                log::error!("Database error fetching OAuth app settings: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        let (scopes, redirect_uri) = settings
+            .map(|s| (s.scopes, s.redirect_uri))
+            .unwrap_or_default();
+
         log::info!("Using OAuth app '{}' from database", app.instance_name);
-        Ok((app.client_id, client_secret))
+        Ok(OAuthCredentials {
+            client_id: app.client_id,
+            client_secret,
+            scopes: scopes.unwrap_or_else(|| default_scopes(&platform).to_string()),
+            redirect_uri: redirect_uri.unwrap_or_else(|| default_redirect_uri(config, &platform)),
+        })
     } else {
         // No OAuth app configured in database
         let platform_name = match platform {
@@ -92,40 +146,46 @@ async fn get_oauth_credentials(
     }
 }
 
-/// GET /oauth/github/authorize
-/// Returns GitHub OAuth authorization URL with state token
-pub async fn github_authorize(
-    db: web::Data<DatabaseConnection>,
-    config: web::Data<Config>,
-    query: web::Query<OAuthAuthorizeQuery>,
-    user_claims: web::ReqData<crate::middleware::auth::Claims>,
-) -> Result<impl Responder, actix_web::Error> {
-    log::info!("🔐 GitHub OAuth authorize request received");
-    log::debug!("Query params: {:?}", query);
+/// Validate the instance URL GitLab flows require (including gitlab.com)
+fn gitlab_instance_url(instance_url: Option<&str>) -> Result<String, actix_web::Error> {
+    let instance_url = instance_url.map(str::trim).unwrap_or_default();
 
-    // Get user ID from JWT
-    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
-        log::error!("❌ Invalid user ID: {}", e);
-        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
-    })?;
+    // Reject missing or empty instance URLs
+    if instance_url.is_empty() {
+        log::error!("❌ Missing instance_url for GitLab");
+        return Err(actix_web::error::ErrorBadRequest(
+            "GitLab requires an explicit instance_url parameter (e.g., https://gitlab.com)"
+        ));
+    }
 
-    log::info!("User ID from JWT: {}", user_id);
+    log::info!("GitLab instance URL: {}", instance_url);
 
-    // GitHub only supports github.com, not self-hosted
-    if query.instance_url.is_some() {
-        log::warn!("GitHub does not support custom instance URLs");
-        return Ok(HttpResponse::BadRequest().json(OAuthErrorResponse {
-            error: "GitHub does not support custom instance URLs".to_string(),
-        }));
-    }
+    // Validate instance URL
+    crate::utils::validators::validate_url(instance_url).map_err(|e| {
+        log::error!("❌ Invalid instance URL: {}", e);
+        actix_web::error::ErrorBadRequest(format!("Invalid instance URL: {}", e))
+    })?;
 
-    // Get OAuth credentials from database
+    Ok(instance_url.to_string())
+}
+
+/// Store a state token and build the GitHub or GitLab authorization URL.
+/// `user_id` is None when signing in, since the user is not known yet.
+async fn authorization_url(
+    db: &DatabaseConnection,
+    config: &Config,
+    platform: git_platform_account::GitPlatform,
+    instance_url: Option<String>,
+    user_id: Option<Uuid>,
+    purpose: &str,
+) -> Result<String, actix_web::Error> {
+    // Get OAuth credentials from database ("" for official GitHub)
     log::info!("Fetching OAuth credentials from database");
-    let (client_id, _) = get_oauth_credentials(
-        db.as_ref(),
-        &config,
-        git_platform_account::GitPlatform::GitHub,
-        "", // Empty string for official GitHub
+    let credentials = get_oauth_credentials(
+        db,
+        config,
+        platform.clone(),
+        instance_url.as_deref().unwrap_or(""),
     ).await?;
 
     // Generate state token for CSRF protection
@@ -137,14 +197,16 @@ pub async fn github_authorize(
         id: Set(Uuid::new_v4()),
         state_token: Set(state_token.clone()),
         user_id: Set(user_id),
-        platform: Set(git_platform_account::GitPlatform::GitHub),
+        platform: Set(Some(platform.clone())),
         created_at: Set(Utc::now()),
         expires_at: Set(Utc::now() + Duration::minutes(10)),
-        instance_url: Set(None), // GitHub doesn't support custom instances
+        instance_url: Set(instance_url.clone()),
+        purpose: Set(purpose.to_string()),
+        nonce: Set(None),
     };
 
     oauth_state::Entity::insert(oauth_state)
-        .exec(db.as_ref())
+        .exec(db)
         .await
         .map_err(|e| {
             log::error!("❌ Failed to store OAuth state: {}", e);
@@ -153,22 +215,109 @@ pub async fn github_authorize(
 
     log::info!("💾 OAuth state stored in database");
 
-    let redirect_uri = format!("{}/oauth/github/callback", config.base_url);
-    log::info!("📍 Redirect URI: {}", redirect_uri);
+    let auth_url = match (&platform, instance_url) {
+        (git_platform_account::GitPlatform::GitLab, Some(instance_url)) => {
+            log::info!("📍 Redirect URI: {}", credentials.redirect_uri);
+
+            format!(
+                "{}/oauth/authorize?client_id={}&redirect_uri={}&response_type=code&state={}&scope={}",
+                instance_url.trim_end_matches('/'),
+                urlencoding::encode(&credentials.client_id),
+                urlencoding::encode(&credentials.redirect_uri),
+                urlencoding::encode(&state_token),
+                urlencoding::encode(&credentials.scopes)
+            )
+        }
+        _ => {
+            log::info!("📍 Redirect URI: {}", credentials.redirect_uri);
+
+            format!(
+                "https://github.com/login/oauth/authorize?client_id={}&redirect_uri={}&scope={}&state={}",
+                credentials.client_id,
+                urlencoding::encode(&credentials.redirect_uri),
+                urlencoding::encode(&credentials.scopes),
+                urlencoding::encode(&state_token)
+            )
+        }
+    };
 
-    // Scopes: read:user for profile, repo for private contributions
-    // Note: 'repo' scope includes write access but we only use it to read contribution data
-    let scopes = "read:user,repo";
+    log::info!("🔗 Generated {} authorization URL", identity::provider_name(&platform));
+    Ok(auth_url)
+}
 
-    let auth_url = format!(
-        "https://github.com/login/oauth/authorize?client_id={}&redirect_uri={}&scope={}&state={}",
-        client_id,
-        urlencoding::encode(&redirect_uri),
-        urlencoding::encode(scopes),
-        urlencoding::encode(&state_token)
+/// Redirect the browser back to the frontend's OAuth callback page
+pub(crate) fn frontend_redirect(config: &Config, query: &str) -> HttpResponse {
+    let redirect_url = format!(
+        "{}/oauth/callback?{}",
+        config.base_url.replace(":3000", ":5173"),
+        query
     );
+    HttpResponse::Found()
+        .append_header(("Location", redirect_url))
+        .finish()
+}
+
+/// Query string for the final redirect of a successful callback. Sign-ins
+/// get a one-time code the frontend exchanges for a JWT.
+async fn callback_success_query(
+    db: &DatabaseConnection,
+    purpose: &str,
+    user_id: Uuid,
+) -> Result<String, actix_web::Error> {
+    if purpose != identity::PURPOSE_LOGIN {
+        return Ok("oauth=success".to_string());
+    }
+
+    let code = identity::create_login_code(db, user_id).await.map_err(|e| {
+        log::error!("❌ Failed to store login code: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    Ok(format!("login_code={}", urlencoding::encode(&code)))
+}
 
-    log::info!("🔗 Generated GitHub authorization URL");
+/// GET /oauth/github/authorize
+/// Returns GitHub OAuth authorization URL with state token
+#[utoipa::path(
+    get,
+    path = "/oauth/github/authorize",
+    tag = "oauth",
+    params(OAuthAuthorizeQuery),
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Authorization URL to redirect the browser to", body = Object))
+)]
+pub async fn github_authorize(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    query: web::Query<OAuthAuthorizeQuery>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    log::info!("🔐 GitHub OAuth authorize request received");
+    log::debug!("Query params: {:?}", query);
+
+    // Get user ID from JWT
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        log::error!("❌ Invalid user ID: {}", e);
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    log::info!("User ID from JWT: {}", user_id);
+
+    // GitHub only supports github.com, not self-hosted
+    if query.instance_url.is_some() {
+        log::warn!("GitHub does not support custom instance URLs");
+        return Ok(HttpResponse::BadRequest().json(OAuthErrorResponse {
+            error: "GitHub does not support custom instance URLs".to_string(),
+        }));
+    }
+
+    let auth_url = authorization_url(
+        db.as_ref(),
+        &config,
+        git_platform_account::GitPlatform::GitHub,
+        None, // GitHub doesn't support custom instances
+        Some(user_id),
+        identity::PURPOSE_CONNECT,
+    ).await?;
 
     // Return the URL as JSON instead of redirecting
     Ok(HttpResponse::Ok().json(serde_json::json!({
@@ -178,9 +327,17 @@ pub async fn github_authorize(
 
 /// GET /oauth/github/callback
 /// Handles OAuth callback from GitHub
+#[utoipa::path(
+    get,
+    path = "/oauth/github/callback",
+    tag = "oauth",
+    params(GitHubCallbackQuery),
+    responses((status = 302, description = "Redirect back to the frontend"))
+)]
 pub async fn github_callback(
     config: web::Data<Config>,
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     query: web::Query<GitHubCallbackQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     log::info!("🔄 GitHub OAuth callback received");
@@ -226,6 +383,7 @@ pub async fn github_callback(
     // Find and validate state in database
     let oauth_state = oauth_state::Entity::find()
         .filter(oauth_state::Column::StateToken.eq(state_token))
+        .filter(oauth_state::Column::Purpose.ne(identity::PURPOSE_LOGIN_CODE))
         .one(db.as_ref())
         .await
         .map_err(|e| {
@@ -248,8 +406,7 @@ pub async fn github_callback(
         return Err(actix_web::error::ErrorBadRequest("State token has expired"));
     }
 
-    let user_id = oauth_state.user_id;
-    log::info!("✅ State validated for user: {}", user_id);
+    log::info!("✅ State validated ({})", oauth_state.purpose);
 
     // Delete the used state token
     oauth_state::Entity::delete_by_id(oauth_state.id)
@@ -263,7 +420,7 @@ pub async fn github_callback(
 
     // Get OAuth credentials from database
     log::info!("Fetching OAuth credentials for token exchange");
-    let (client_id, client_secret) = get_oauth_credentials(
+    let OAuthCredentials { client_id, client_secret, .. } = get_oauth_credentials(
         db.as_ref(),
         &config,
         git_platform_account::GitPlatform::GitHub,
@@ -296,6 +453,25 @@ pub async fn github_callback(
 
     log::info!("✅ GitHub user validated: {}", user_info.username);
 
+    let user_id = match identity::resolve_user(
+        db.as_ref(),
+        instance.current().await.allow_registration,
+        &oauth_state,
+        git_platform_account::GitPlatform::GitHub,
+        "",
+        &user_info,
+    ).await {
+        Ok(user_id) => user_id,
+        Err(IdentityError::Database(e)) => {
+            log::error!("❌ Database error: {}", e);
+            return Err(actix_web::error::ErrorInternalServerError("Database error"));
+        }
+        Err(e) => {
+            log::warn!("❌ GitHub sign-in refused: {}", e);
+            return Ok(frontend_redirect(&config, &format!("error={}", urlencoding::encode(&e.to_string()))));
+        }
+    };
+
     // Encrypt the access token
     log::info!("🔒 Encrypting access token");
     let encrypted_token = encryption::encrypt(&token_result.access_token, &config.encryption_key)
@@ -317,6 +493,19 @@ pub async fn github_callback(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    if existing_account.is_none() {
+        let exceeded = quotas::check_platform_accounts(db.as_ref(), &config, user_id)
+            .await
+            .map_err(|e| {
+                log::error!("❌ Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        if let Some(exceeded) = exceeded {
+            log::warn!("❌ Platform account quota reached for user {}", user_id);
+            return Ok(frontend_redirect(&config, &format!("error={}", urlencoding::encode(&exceeded.message))));
+        }
+    }
+
     if let Some(account) = existing_account {
         log::info!("📝 Updating existing account");
         // Update existing account
@@ -357,6 +546,9 @@ pub async fn github_callback(
             following_count: Set(None),
             sync_profile: Set(true),
             sync_contributions: Set(true),
+            detailed_sync: Set(false),
+            commit_retention_days: Set(None),
+            earliest_sync_year: Set(instance.current().await.default_earliest_sync_year),
             auth_type: Set(git_platform_account::AuthType::OAuth),
         };
 
@@ -371,19 +563,21 @@ pub async fn github_callback(
     }
 
     // Redirect to frontend with success
-    let redirect_url = format!("{}/oauth/callback?oauth=success", config.base_url.replace(":3000", ":5173"));
-    log::info!("🎉 OAuth flow complete! Redirecting to: {}", redirect_url);
+    let query = callback_success_query(
+        db.as_ref(),
+        &oauth_state.purpose,
+        user_id,
+    ).await?;
+    log::info!("🎉 OAuth flow complete! Redirecting to frontend");
 
-    Ok(HttpResponse::Found()
-        .append_header(("Location", redirect_url))
-        .finish())
+    Ok(frontend_redirect(&config, &query))
 }
 
 /// Exchange GitHub authorization code for access token
 async fn exchange_github_code(code: &str, client_id: &str, client_secret: &str) -> Result<GitHubTokenResponse> {
     log::info!("📤 Sending token exchange request to GitHub");
 
-    let client = crate::utils::http_client::create_http_client();
+    let client = crate::utils::http_client::shared_client();
 
     let params = [
         ("client_id", client_id),
@@ -451,6 +645,14 @@ struct GitHubTokenResponse {
 
 /// GET /oauth/gitea/authorize
 /// Returns Gitea OAuth authorization URL with state token
+#[utoipa::path(
+    get,
+    path = "/oauth/gitea/authorize",
+    tag = "oauth",
+    params(OAuthAuthorizeQuery),
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Authorization URL to redirect the browser to", body = Object))
+)]
 pub async fn gitea_authorize(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
@@ -484,7 +686,7 @@ pub async fn gitea_authorize(
 
     // Get OAuth credentials from database for this instance
     log::info!("Fetching OAuth credentials from database");
-    let (client_id, _) = get_oauth_credentials(
+    let credentials = get_oauth_credentials(
         db.as_ref(),
         &config,
         git_platform_account::GitPlatform::Gitea,
@@ -500,11 +702,13 @@ pub async fn gitea_authorize(
     let oauth_state = oauth_state::ActiveModel {
         id: Set(Uuid::new_v4()),
         state_token: Set(state_token.clone()),
-        user_id: Set(user_id),
-        platform: Set(git_platform_account::GitPlatform::Gitea),
+        user_id: Set(Some(user_id)),
+        platform: Set(Some(git_platform_account::GitPlatform::Gitea)),
         created_at: Set(Utc::now()),
         expires_at: Set(Utc::now() + Duration::minutes(10)),
         instance_url: Set(Some(instance_url.clone())),
+        purpose: Set(identity::PURPOSE_CONNECT.to_string()),
+        nonce: Set(None),
     };
 
     oauth_state::Entity::insert(oauth_state)
@@ -517,20 +721,16 @@ pub async fn gitea_authorize(
 
     log::info!("💾 OAuth state stored in database");
 
-    let redirect_uri = format!("{}/oauth/gitea/callback", config.base_url);
-    log::info!("📍 Redirect URI: {}", redirect_uri);
-
-    // Scopes for Gitea: read:repository, read:user, read:organization
-    let scopes = "read:repository,read:user,read:organization";
+    log::info!("📍 Redirect URI: {}", credentials.redirect_uri);
 
     // Gitea OAuth authorize endpoint
     let auth_url = format!(
         "{}/login/oauth/authorize?client_id={}&redirect_uri={}&response_type=code&state={}&scope={}",
         instance_url.trim_end_matches('/'),
-        urlencoding::encode(&client_id),
-        urlencoding::encode(&redirect_uri),
+        urlencoding::encode(&credentials.client_id),
+        urlencoding::encode(&credentials.redirect_uri),
         urlencoding::encode(&state_token),
-        urlencoding::encode(scopes)
+        urlencoding::encode(&credentials.scopes)
     );
 
     log::info!("🔗 Generated Gitea authorization URL for instance: {}", instance_url);
@@ -541,7 +741,8 @@ pub async fn gitea_authorize(
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GiteaCallbackQuery {
     pub code: Option<String>,
     pub state: Option<String>,
@@ -551,9 +752,17 @@ pub struct GiteaCallbackQuery {
 
 /// GET /oauth/gitea/callback
 /// Handles OAuth callback from Gitea
+#[utoipa::path(
+    get,
+    path = "/oauth/gitea/callback",
+    tag = "oauth",
+    params(GiteaCallbackQuery),
+    responses((status = 302, description = "Redirect back to the frontend"))
+)]
 pub async fn gitea_callback(
     config: web::Data<Config>,
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     query: web::Query<GiteaCallbackQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     log::info!("🔄 Gitea OAuth callback received");
@@ -599,6 +808,7 @@ pub async fn gitea_callback(
     // Find and validate state in database
     let oauth_state = oauth_state::Entity::find()
         .filter(oauth_state::Column::StateToken.eq(state_token))
+        .filter(oauth_state::Column::Purpose.ne(identity::PURPOSE_LOGIN_CODE))
         .one(db.as_ref())
         .await
         .map_err(|e| {
@@ -621,7 +831,10 @@ pub async fn gitea_callback(
         return Err(actix_web::error::ErrorBadRequest("State token has expired"));
     }
 
-    let user_id = oauth_state.user_id;
+    let user_id = oauth_state.user_id.ok_or_else(|| {
+        log::error!("❌ Missing user in OAuth state");
+        actix_web::error::ErrorBadRequest("Invalid OAuth state")
+    })?;
     let instance_url = oauth_state.instance_url.clone().ok_or_else(|| {
         log::error!("❌ Missing instance_url in OAuth state");
         actix_web::error::ErrorInternalServerError("Invalid OAuth state")
@@ -641,7 +854,7 @@ pub async fn gitea_callback(
 
     // Get OAuth credentials from database
     log::info!("Fetching OAuth credentials for token exchange");
-    let (client_id, client_secret) = get_oauth_credentials(
+    let OAuthCredentials { client_id, client_secret, .. } = get_oauth_credentials(
         db.as_ref(),
         &config,
         git_platform_account::GitPlatform::Gitea,
@@ -696,6 +909,19 @@ pub async fn gitea_callback(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    if existing_account.is_none() {
+        let exceeded = quotas::check_platform_accounts(db.as_ref(), &config, user_id)
+            .await
+            .map_err(|e| {
+                log::error!("❌ Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        if let Some(exceeded) = exceeded {
+            log::warn!("❌ Platform account quota reached for user {}", user_id);
+            return Ok(frontend_redirect(&config, &format!("error={}", urlencoding::encode(&exceeded.message))));
+        }
+    }
+
     if let Some(account) = existing_account {
         log::info!("📝 Updating existing account");
         // Update existing account
@@ -736,6 +962,9 @@ pub async fn gitea_callback(
             following_count: Set(None),
             sync_profile: Set(true),
             sync_contributions: Set(true),
+            detailed_sync: Set(false),
+            commit_retention_days: Set(None),
+            earliest_sync_year: Set(instance.current().await.default_earliest_sync_year),
             auth_type: Set(git_platform_account::AuthType::OAuth),
         };
 
@@ -762,7 +991,7 @@ pub async fn gitea_callback(
 async fn exchange_gitea_code(code: &str, client_id: &str, client_secret: &str, instance_url: &str) -> Result<GiteaTokenResponse> {
     log::info!("📤 Sending token exchange request to Gitea instance: {}", instance_url);
 
-    let client = crate::utils::http_client::create_http_client();
+    let client = crate::utils::http_client::shared_client();
 
     let token_url = format!("{}/login/oauth/access_token", instance_url.trim_end_matches('/'));
 
@@ -831,15 +1060,41 @@ struct GiteaTokenResponse {
 // OAuth Instance Discovery
 // ============================================================================
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OAuthInstanceResponse {
     pub instance_url: String,
     pub instance_name: String,
+    /// What to label the instance with: the display name an admin set, or
+    /// the instance name
+    pub display_name: String,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    /// Help to show with the button, e.g. which account to sign in with
+    pub login_hint: Option<String>,
     pub is_default: bool,
+    pub is_featured: bool,
+}
+
+/// The order instances are listed in: featured first, then by the order
+/// admins set, the default app before others in the same place
+pub(crate) fn listing_order(query: Select<oauth_application::Entity>) -> Select<oauth_application::Entity> {
+    query
+        .order_by_desc(oauth_application::Column::IsFeatured)
+        .order_by_asc(oauth_application::Column::SortOrder)
+        .order_by_desc(oauth_application::Column::IsDefault)
+        .order_by_asc(oauth_application::Column::InstanceName)
 }
 
 /// GET /oauth/instances/:platform
-/// List available OAuth instances for a platform (public endpoint)
+/// List available OAuth instances for a platform (public endpoint), in the
+/// order the connect screen shows them
+#[utoipa::path(
+    get,
+    path = "/oauth/instances/{platform}",
+    tag = "oauth",
+    params(("platform" = String, Path, description = "github, gitea or gitlab")),
+    responses((status = 200, description = "Enabled OAuth instances, featured first, then by sort order", body = [OAuthInstanceResponse]))
+)]
 pub async fn list_oauth_instances(
     db: web::Data<DatabaseConnection>,
     path: web::Path<String>,
@@ -861,10 +1116,9 @@ pub async fn list_oauth_instances(
     };
 
     // Find all enabled OAuth apps for this platform
-    let apps = oauth_application::Entity::find()
+    let apps = listing_order(oauth_application::Entity::find())
         .filter(oauth_application::Column::Platform.eq(platform))
         .filter(oauth_application::Column::IsEnabled.eq(true))
-        .order_by_desc(oauth_application::Column::IsDefault)
         .all(db.as_ref())
         .await
         .map_err(|e| {
@@ -880,9 +1134,14 @@ pub async fn list_oauth_instances(
     let instances: Vec<OAuthInstanceResponse> = apps
         .into_iter()
         .map(|app| OAuthInstanceResponse {
+            display_name: app.display_name.unwrap_or_else(|| app.instance_name.clone()),
             instance_url: app.instance_url,
             instance_name: app.instance_name,
+            icon_url: app.icon_url,
+            description: app.description,
+            login_hint: app.login_hint,
             is_default: app.is_default,
+            is_featured: app.is_featured,
         })
         .collect();
 
@@ -897,6 +1156,14 @@ pub async fn list_oauth_instances(
 
 /// GET /oauth/gitlab/authorize
 /// Returns GitLab OAuth authorization URL with state token
+#[utoipa::path(
+    get,
+    path = "/oauth/gitlab/authorize",
+    tag = "oauth",
+    params(OAuthAuthorizeQuery),
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Authorization URL to redirect the browser to", body = Object))
+)]
 pub async fn gitlab_authorize(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
@@ -915,86 +1182,25 @@ pub async fn gitlab_authorize(
     log::info!("User ID from JWT: {}", user_id);
 
     // GitLab requires explicit instance URL (including gitlab.com)
-    let instance_url = query.instance_url.as_ref().ok_or_else(|| {
-        log::error!("❌ Missing instance_url for GitLab");
-        actix_web::error::ErrorBadRequest("GitLab requires an explicit instance_url parameter (e.g., https://gitlab.com)")
-    })?;
-
-    // Reject empty instance URLs
-    if instance_url.trim().is_empty() {
-        log::error!("❌ Empty instance_url for GitLab");
-        return Err(actix_web::error::ErrorBadRequest(
-            "GitLab requires an explicit instance_url parameter (e.g., https://gitlab.com)"
-        ));
-    }
+    let instance_url = gitlab_instance_url(query.instance_url.as_deref())?;
 
-    log::info!("GitLab instance URL: {}", instance_url);
-
-    // Validate instance URL
-    crate::utils::validators::validate_url(instance_url).map_err(|e| {
-        log::error!("❌ Invalid instance URL: {}", e);
-        actix_web::error::ErrorBadRequest(format!("Invalid instance URL: {}", e))
-    })?;
-
-    // Get OAuth credentials from database for this instance
-    log::info!("Fetching OAuth credentials from database");
-    let (client_id, _) = get_oauth_credentials(
+    let auth_url = authorization_url(
         db.as_ref(),
         &config,
         git_platform_account::GitPlatform::GitLab,
-        instance_url,
+        Some(instance_url),
+        Some(user_id),
+        identity::PURPOSE_CONNECT,
     ).await?;
 
-    // Generate state token for CSRF protection
-    let state_token = format!("{}", Uuid::new_v4());
-    log::info!("🎲 Generated state token: {}", state_token);
-
-    // Store state in database with 10 minute expiration
-    let oauth_state = oauth_state::ActiveModel {
-        id: Set(Uuid::new_v4()),
-        state_token: Set(state_token.clone()),
-        user_id: Set(user_id),
-        platform: Set(git_platform_account::GitPlatform::GitLab),
-        created_at: Set(Utc::now()),
-        expires_at: Set(Utc::now() + Duration::minutes(10)),
-        instance_url: Set(Some(instance_url.to_string())),
-    };
-
-    oauth_state::Entity::insert(oauth_state)
-        .exec(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("❌ Failed to store OAuth state: {}", e);
-            actix_web::error::ErrorInternalServerError("Failed to create OAuth state")
-        })?;
-
-    log::info!("💾 OAuth state stored in database");
-
-    let redirect_uri = format!("{}/oauth/gitlab/callback", config.base_url);
-    log::info!("📍 Redirect URI: {}", redirect_uri);
-
-    // Scopes for GitLab: read_user, read_api
-    let scopes = "read_user read_api";
-
-    // GitLab OAuth authorize endpoint
-    let auth_url = format!(
-        "{}/oauth/authorize?client_id={}&redirect_uri={}&response_type=code&state={}&scope={}",
-        instance_url.trim_end_matches('/'),
-        urlencoding::encode(&client_id),
-        urlencoding::encode(&redirect_uri),
-        urlencoding::encode(&state_token),
-        urlencoding::encode(scopes)
-    );
-
-    log::info!("🔗 Generated GitLab authorization URL for instance: {}", instance_url);
-
     // Return the URL as JSON instead of redirecting
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "authorization_url": auth_url
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GitLabCallbackQuery {
     pub code: Option<String>,
     pub state: Option<String>,
@@ -1004,9 +1210,17 @@ pub struct GitLabCallbackQuery {
 
 /// GET /oauth/gitlab/callback
 /// Handles OAuth callback from GitLab
+#[utoipa::path(
+    get,
+    path = "/oauth/gitlab/callback",
+    tag = "oauth",
+    params(GitLabCallbackQuery),
+    responses((status = 302, description = "Redirect back to the frontend"))
+)]
 pub async fn gitlab_callback(
     config: web::Data<Config>,
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     query: web::Query<GitLabCallbackQuery>,
 ) -> Result<impl Responder, actix_web::Error> {
     log::info!("🔄 GitLab OAuth callback received");
@@ -1052,6 +1266,7 @@ pub async fn gitlab_callback(
     // Find and validate state in database
     let oauth_state = oauth_state::Entity::find()
         .filter(oauth_state::Column::StateToken.eq(state_token))
+        .filter(oauth_state::Column::Purpose.ne(identity::PURPOSE_LOGIN_CODE))
         .one(db.as_ref())
         .await
         .map_err(|e| {
@@ -1074,13 +1289,12 @@ pub async fn gitlab_callback(
         return Err(actix_web::error::ErrorBadRequest("State token has expired"));
     }
 
-    let user_id = oauth_state.user_id;
     let instance_url = oauth_state.instance_url.clone().ok_or_else(|| {
         log::error!("❌ Missing instance_url in OAuth state");
         actix_web::error::ErrorInternalServerError("Invalid OAuth state: missing instance URL")
     })?;
 
-    log::info!("✅ State validated for user: {} on instance: {}", user_id, instance_url);
+    log::info!("✅ State validated ({}) on instance: {}", oauth_state.purpose, instance_url);
 
     // Delete the used state token
     oauth_state::Entity::delete_by_id(oauth_state.id)
@@ -1094,7 +1308,7 @@ pub async fn gitlab_callback(
 
     // Get OAuth credentials from database
     log::info!("Fetching OAuth credentials for token exchange");
-    let (client_id, client_secret) = get_oauth_credentials(
+    let OAuthCredentials { client_id, client_secret, redirect_uri, .. } = get_oauth_credentials(
         db.as_ref(),
         &config,
         git_platform_account::GitPlatform::GitLab,
@@ -1105,7 +1319,7 @@ pub async fn gitlab_callback(
 
     // Exchange code for access token
     log::info!("🔑 Exchanging authorization code for access token");
-    let token_result = exchange_gitlab_code(code, &client_id, &client_secret, &instance_url, &config.base_url).await.map_err(|e| {
+    let token_result = exchange_gitlab_code(code, &client_id, &client_secret, &instance_url, &redirect_uri).await.map_err(|e| {
         log::error!("❌ Failed to exchange GitLab code: {}", e);
         actix_web::error::ErrorInternalServerError(format!("OAuth exchange failed: {}", e))
     })?;
@@ -1127,6 +1341,25 @@ pub async fn gitlab_callback(
 
     log::info!("✅ GitLab user validated: {}", user_info.username);
 
+    let user_id = match identity::resolve_user(
+        db.as_ref(),
+        instance.current().await.allow_registration,
+        &oauth_state,
+        git_platform_account::GitPlatform::GitLab,
+        &instance_url,
+        &user_info,
+    ).await {
+        Ok(user_id) => user_id,
+        Err(IdentityError::Database(e)) => {
+            log::error!("❌ Database error: {}", e);
+            return Err(actix_web::error::ErrorInternalServerError("Database error"));
+        }
+        Err(e) => {
+            log::warn!("❌ GitLab sign-in refused: {}", e);
+            return Ok(frontend_redirect(&config, &format!("error={}", urlencoding::encode(&e.to_string()))));
+        }
+    };
+
     // Encrypt the access token
     log::info!("🔒 Encrypting access token");
     let encrypted_token = encryption::encrypt(&token_result.access_token, &config.encryption_key)
@@ -1149,6 +1382,19 @@ pub async fn gitlab_callback(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    if existing_account.is_none() {
+        let exceeded = quotas::check_platform_accounts(db.as_ref(), &config, user_id)
+            .await
+            .map_err(|e| {
+                log::error!("❌ Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        if let Some(exceeded) = exceeded {
+            log::warn!("❌ Platform account quota reached for user {}", user_id);
+            return Ok(frontend_redirect(&config, &format!("error={}", urlencoding::encode(&exceeded.message))));
+        }
+    }
+
     if let Some(account) = existing_account {
         log::info!("📝 Updating existing account");
         // Update existing account
@@ -1189,6 +1435,9 @@ pub async fn gitlab_callback(
             following_count: Set(None),
             sync_profile: Set(true),
             sync_contributions: Set(true),
+            detailed_sync: Set(false),
+            commit_retention_days: Set(None),
+            earliest_sync_year: Set(instance.current().await.default_earliest_sync_year),
             auth_type: Set(git_platform_account::AuthType::OAuth),
         };
 
@@ -1203,29 +1452,30 @@ pub async fn gitlab_callback(
     }
 
     // Redirect to frontend with success
-    let redirect_url = format!("{}/oauth/callback?oauth=success", config.base_url.replace(":3000", ":5173"));
-    log::info!("🎉 OAuth flow complete! Redirecting to: {}", redirect_url);
+    let query = callback_success_query(
+        db.as_ref(),
+        &oauth_state.purpose,
+        user_id,
+    ).await?;
+    log::info!("🎉 OAuth flow complete! Redirecting to frontend");
 
-    Ok(HttpResponse::Found()
-        .append_header(("Location", redirect_url))
-        .finish())
+    Ok(frontend_redirect(&config, &query))
 }
 
-/// Exchange GitLab authorization code for access token
-async fn exchange_gitlab_code(code: &str, client_id: &str, client_secret: &str, instance_url: &str, redirect_base: &str) -> Result<GitLabTokenResponse> {
+/// Exchange GitLab authorization code for access token. `redirect_uri` must
+/// be the one the authorization request was sent with.
+async fn exchange_gitlab_code(code: &str, client_id: &str, client_secret: &str, instance_url: &str, redirect_uri: &str) -> Result<GitLabTokenResponse> {
     log::info!("📤 Sending token exchange request to GitLab instance: {}", instance_url);
 
-    let client = crate::utils::http_client::create_http_client();
+    let client = crate::utils::http_client::shared_client();
 
     let token_url = format!("{}/oauth/token", instance_url.trim_end_matches('/'));
-    let redirect_uri = format!("{}/oauth/gitlab/callback", redirect_base);
-
     let params = [
         ("client_id", client_id),
         ("client_secret", client_secret),
         ("code", code),
         ("grant_type", "authorization_code"),
-        ("redirect_uri", &redirect_uri),
+        ("redirect_uri", redirect_uri),
     ];
 
     log::debug!("Request params: client_id={}, code={}...",
@@ -1284,3 +1534,163 @@ struct GitLabTokenResponse {
     error: Option<String>,
     error_description: Option<String>,
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LoginCodeRequest {
+    pub code: String,
+}
+
+/// Map the `{platform}` path segment of the sign-in routes to a provider and
+/// its validated instance URL (None for GitHub)
+fn identity_provider(
+    platform: &str,
+    instance_url: Option<&str>,
+) -> Result<(git_platform_account::GitPlatform, Option<String>), actix_web::Error> {
+    match platform {
+        "github" => {
+            if instance_url.is_some() {
+                return Err(actix_web::error::ErrorBadRequest(
+                    "GitHub does not support custom instance URLs",
+                ));
+            }
+            Ok((git_platform_account::GitPlatform::GitHub, None))
+        }
+        "gitlab" => Ok((
+            git_platform_account::GitPlatform::GitLab,
+            Some(gitlab_instance_url(instance_url)?),
+        )),
+        _ => Err(actix_web::error::ErrorBadRequest(
+            "Sign-in is supported with github and gitlab",
+        )),
+    }
+}
+
+/// GET /auth/oauth/{platform}/authorize
+/// Returns the authorization URL for signing in (or signing up) with GitHub or GitLab
+#[utoipa::path(
+    get,
+    path = "/auth/oauth/{platform}/authorize",
+    tag = "auth",
+    params(("platform" = String, Path, description = "github or gitlab"), OAuthAuthorizeQuery),
+    responses(
+        (status = 200, description = "Authorization URL to redirect the browser to", body = Object),
+        (status = 400, description = "Unsupported platform or invalid instance URL")
+    )
+)]
+pub async fn login_authorize(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+    query: web::Query<OAuthAuthorizeQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    log::info!("🔐 Sign-in with {} requested", path);
+
+    let (platform, instance_url) = identity_provider(&path, query.instance_url.as_deref())?;
+    let auth_url = authorization_url(
+        db.as_ref(),
+        &config,
+        platform,
+        instance_url,
+        None,
+        identity::PURPOSE_LOGIN,
+    ).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "authorization_url": auth_url
+    })))
+}
+
+/// GET /auth/oauth/{platform}/link
+/// Returns the authorization URL for linking a GitHub or GitLab identity to the current user
+#[utoipa::path(
+    get,
+    path = "/auth/oauth/{platform}/link",
+    tag = "auth",
+    params(("platform" = String, Path, description = "github or gitlab"), OAuthAuthorizeQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Authorization URL to redirect the browser to", body = Object),
+        (status = 400, description = "Unsupported platform or invalid instance URL")
+    )
+)]
+pub async fn link_authorize(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+    query: web::Query<OAuthAuthorizeQuery>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        log::error!("❌ Invalid user ID: {}", e);
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    log::info!("🔗 User {} linking a {} identity", user_id, path);
+
+    let (platform, instance_url) = identity_provider(&path, query.instance_url.as_deref())?;
+    let auth_url = authorization_url(
+        db.as_ref(),
+        &config,
+        platform,
+        instance_url,
+        Some(user_id),
+        identity::PURPOSE_LINK,
+    ).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "authorization_url": auth_url
+    })))
+}
+
+/// POST /auth/oauth/exchange
+/// Exchanges the one-time code from a sign-in redirect for a JWT
+#[utoipa::path(
+    post,
+    path = "/auth/oauth/exchange",
+    tag = "auth",
+    request_body = LoginCodeRequest,
+    responses(
+        (status = 200, description = "Logged in", body = AuthResponse),
+        (status = 401, description = "Invalid or expired code", body = ErrorResponse)
+    )
+)]
+pub async fn exchange_login_code(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    http_req: actix_web::HttpRequest,
+    req: web::Json<LoginCodeRequest>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user_id = identity::redeem_login_code(db.as_ref(), &req.code)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let user = match user_id {
+        Some(user_id) => user::Entity::find_by_id(user_id)
+            .one(db.as_ref())
+            .await
+            .map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?,
+        None => None,
+    };
+
+    let Some(user) = user else {
+        log::warn!("❌ Invalid or expired login code");
+        return Ok(HttpResponse::Unauthorized().json(ErrorResponse {
+            error: "Invalid or expired login code".to_string(),
+        }));
+    };
+
+    let response = start_session(db.as_ref(), &config, &user, &http_req).await.map_err(|e| {
+        log::error!("❌ Failed to start session: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to start session")
+    })?;
+
+    log::info!("✅ User '{}' signed in via OAuth", user.username);
+
+    Ok(HttpResponse::Ok().json(response))
+}
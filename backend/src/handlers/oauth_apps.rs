@@ -1,12 +1,16 @@
 use actix_web::{web, HttpResponse, Responder};
 use sea_orm::{*, sea_query::Expr};
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema};
 use uuid::Uuid;
 
-use crate::models::{oauth_application, user, git_platform_account};
-use crate::utils::{config::Config, encryption};
+use crate::handlers::oauth::{default_redirect_uri, default_scopes, listing_order};
+use crate::models::{oauth_application, oauth_application_setting, user, git_platform_account};
+use crate::services::instance_check::{self, OAuthAppReport};
+use crate::utils::pagination::{self, PageParams};
+use crate::utils::{config::Config, encryption, validators};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateOAuthAppRequest {
     pub platform: String,
     pub instance_url: Option<String>,
@@ -14,18 +18,59 @@ pub struct CreateOAuthAppRequest {
     pub client_id: String,
     pub client_secret: String,
     pub is_default: Option<bool>,
+    /// Requested instead of the default scopes, in the platform's syntax
+    pub scopes: Option<String>,
+    /// Sent instead of the callback route under BASE_URL, e.g. behind a
+    /// proxy that rewrites paths
+    pub redirect_uri: Option<String>,
+    /// Shown in the connect screen instead of the instance name
+    pub display_name: Option<String>,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    /// Help shown with the button, e.g. which account to sign in with
+    pub login_hint: Option<String>,
+    pub is_featured: Option<bool>,
+    /// Position in the connect screen, lowest first; 0 when left out
+    pub sort_order: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateOAuthAppRequest {
     pub instance_name: Option<String>,
     pub client_id: Option<String>,
     pub client_secret: Option<String>,
     pub is_enabled: Option<bool>,
     pub is_default: Option<bool>,
+    /// An empty string goes back to the default scopes
+    pub scopes: Option<String>,
+    /// An empty string goes back to the default redirect URI
+    pub redirect_uri: Option<String>,
+    /// An empty string goes back to the instance name
+    pub display_name: Option<String>,
+    /// An empty string removes the icon; the same for `description` and
+    /// `login_hint`
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    pub login_hint: Option<String>,
+    pub is_featured: Option<bool>,
+    pub sort_order: Option<i32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OAuthAppOrderEntry {
+    pub id: Uuid,
+    /// Left out keeps whether the app is featured
+    pub is_featured: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReorderOAuthAppsRequest {
+    /// Apps in the order the connect screen shows them. Apps left out keep
+    /// their place.
+    pub apps: Vec<OAuthAppOrderEntry>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OAuthAppResponse {
     pub id: String,
     pub platform: String,
@@ -35,16 +80,69 @@ pub struct OAuthAppResponse {
     pub client_secret_preview: String, // Only show first/last few chars
     pub is_enabled: bool,
     pub is_default: bool,
+    /// Null when the default scopes are requested
+    pub scopes: Option<String>,
+    pub default_scopes: String,
+    /// Null when the default redirect URI is sent
+    pub redirect_uri: Option<String>,
+    pub default_redirect_uri: String,
+    /// Null when the instance name is shown
+    pub display_name: Option<String>,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    pub login_hint: Option<String>,
+    pub is_featured: bool,
+    pub sort_order: i32,
     pub created_at: String,
 }
 
+impl OAuthAppResponse {
+    fn new(
+        config: &Config,
+        app: oauth_application::Model,
+        settings: Option<oauth_application_setting::Model>,
+        client_secret_preview: String,
+    ) -> Self {
+        let platform_str = match app.platform {
+            git_platform_account::GitPlatform::GitHub => "github",
+            git_platform_account::GitPlatform::GitLab => "gitlab",
+            git_platform_account::GitPlatform::Gitea => "gitea",
+        };
+        let (scopes, redirect_uri) = settings
+            .map(|s| (s.scopes, s.redirect_uri))
+            .unwrap_or_default();
+
+        Self {
+            id: app.id.to_string(),
+            platform: platform_str.to_string(),
+            default_scopes: default_scopes(&app.platform).to_string(),
+            default_redirect_uri: default_redirect_uri(config, &app.platform),
+            instance_url: app.instance_url,
+            instance_name: app.instance_name,
+            client_id: app.client_id,
+            client_secret_preview,
+            is_enabled: app.is_enabled,
+            is_default: app.is_default,
+            scopes,
+            redirect_uri,
+            display_name: app.display_name,
+            icon_url: app.icon_url,
+            description: app.description,
+            login_hint: app.login_hint,
+            is_featured: app.is_featured,
+            sort_order: app.sort_order,
+            created_at: app.created_at.to_rfc3339(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ErrorResponse {
     error: String,
 }
 
 /// Middleware-like function to check if user is admin
-async fn require_admin(
+pub(crate) async fn require_admin(
     db: &DatabaseConnection,
     user_id: Uuid,
 ) -> Result<(), actix_web::Error> {
@@ -64,6 +162,139 @@ async fn require_admin(
     Ok(())
 }
 
+/// Scopes an admin entered; None for empty, which means the default
+fn parse_scopes(scopes: &str) -> Result<Option<String>, String> {
+    let scopes = scopes.trim();
+    if scopes.is_empty() {
+        return Ok(None);
+    }
+    if scopes.len() > 1000 || scopes.chars().any(char::is_control) {
+        return Err("Scopes must be at most 1000 characters on one line".to_string());
+    }
+    Ok(Some(scopes.to_string()))
+}
+
+/// Redirect URI an admin entered; None for empty, which means the default
+fn parse_redirect_uri(redirect_uri: &str) -> Result<Option<String>, String> {
+    let redirect_uri = redirect_uri.trim();
+    if redirect_uri.is_empty() {
+        return Ok(None);
+    }
+    validators::validate_url(redirect_uri).map_err(|e| format!("Invalid redirect URI: {}", e))?;
+    Ok(Some(redirect_uri.to_string()))
+}
+
+/// Store an app's scopes and redirect URI, dropping the row once both are
+/// back to the default
+async fn save_settings(
+    db: &DatabaseConnection,
+    application_id: Uuid,
+    scopes: Option<String>,
+    redirect_uri: Option<String>,
+) -> Result<Option<oauth_application_setting::Model>, DbErr> {
+    if scopes.is_none() && redirect_uri.is_none() {
+        oauth_application_setting::Entity::delete_by_id(application_id)
+            .exec(db)
+            .await?;
+        return Ok(None);
+    }
+
+    let row = oauth_application_setting::ActiveModel {
+        application_id: Set(application_id),
+        scopes: Set(scopes),
+        redirect_uri: Set(redirect_uri),
+        updated_at: Set(chrono::Utc::now()),
+    };
+    oauth_application_setting::Entity::insert(row)
+        .on_conflict(
+            sea_query::OnConflict::column(oauth_application_setting::Column::ApplicationId)
+                .update_columns([
+                    oauth_application_setting::Column::Scopes,
+                    oauth_application_setting::Column::RedirectUri,
+                    oauth_application_setting::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+    oauth_application_setting::Entity::find_by_id(application_id)
+        .one(db)
+        .await
+}
+
+/// One line of text an admin entered; None for empty
+fn parse_label(field: &str, value: &str, max_length: usize) -> Result<Option<String>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if value.chars().count() > max_length || value.chars().any(char::is_control) {
+        return Err(format!("{} must be at most {} characters on one line", field, max_length));
+    }
+    Ok(Some(value.to_string()))
+}
+
+/// How an app is listed in the connect screen, as an admin sent it. A field
+/// left out is None; an empty string is Some(None), which clears it.
+#[derive(Debug, Default)]
+struct ListingUpdate {
+    display_name: Option<Option<String>>,
+    icon_url: Option<Option<String>>,
+    description: Option<Option<String>>,
+    login_hint: Option<Option<String>>,
+}
+
+impl ListingUpdate {
+    fn parse(
+        display_name: Option<&str>,
+        icon_url: Option<&str>,
+        description: Option<&str>,
+        login_hint: Option<&str>,
+    ) -> Result<Self, String> {
+        let icon_url = icon_url
+            .map(|url| {
+                let url = parse_label("Icon URL", url, 512)?;
+                if let Some(url) = &url {
+                    validators::validate_url(url).map_err(|e| format!("Invalid icon URL: {}", e))?;
+                }
+                Ok::<_, String>(url)
+            })
+            .transpose()?;
+        let description = description
+            .map(|description| {
+                let description = description.trim();
+                if description.chars().count() > 1000 {
+                    return Err("Description must be at most 1000 characters".to_string());
+                }
+                Ok(Some(description.to_string()).filter(|d| !d.is_empty()))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            display_name: display_name.map(|name| parse_label("Display name", name, 255)).transpose()?,
+            icon_url,
+            description,
+            login_hint: login_hint.map(|hint| parse_label("Login hint", hint, 255)).transpose()?,
+        })
+    }
+
+    fn apply(self, app: &mut oauth_application::ActiveModel) {
+        if let Some(display_name) = self.display_name {
+            app.display_name = Set(display_name);
+        }
+        if let Some(icon_url) = self.icon_url {
+            app.icon_url = Set(icon_url);
+        }
+        if let Some(description) = self.description {
+            app.description = Set(description);
+        }
+        if let Some(login_hint) = self.login_hint {
+            app.login_hint = Set(login_hint);
+        }
+    }
+}
+
 fn mask_secret(secret: &str) -> String {
     if secret.len() <= 8 {
         return "••••••••".to_string();
@@ -75,9 +306,23 @@ fn mask_secret(secret: &str) -> String {
 
 /// GET /api/admin/oauth-apps
 /// List all OAuth applications (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/oauth-apps",
+    tag = "admin",
+    params(PageParams),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Configured OAuth applications; total in X-Total-Count, next page in X-Next-Cursor", body = [OAuthAppResponse]),
+        (status = 400, description = "Invalid sort or cursor"),
+        (status = 403, description = "Admin access required")
+    )
+)]
 pub async fn list_oauth_apps(
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    page: web::Query<PageParams>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
         actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
@@ -85,42 +330,65 @@ pub async fn list_oauth_apps(
 
     require_admin(db.as_ref(), user_id).await?;
 
-    let apps = oauth_application::Entity::find()
-        .all(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?;
-
-    let response: Vec<OAuthAppResponse> = apps
-        .into_iter()
-        .map(|app| {
-            let platform_str = match app.platform {
-                git_platform_account::GitPlatform::GitHub => "github",
-                git_platform_account::GitPlatform::GitLab => "gitlab",
-                git_platform_account::GitPlatform::Gitea => "gitea",
-            };
-
-            OAuthAppResponse {
-                id: app.id.to_string(),
-                platform: platform_str.to_string(),
-                instance_url: app.instance_url,
-                instance_name: app.instance_name,
-                client_id: app.client_id,
-                client_secret_preview: mask_secret(&app.client_secret),
-                is_enabled: app.is_enabled,
-                is_default: app.is_default,
-                created_at: app.created_at.to_rfc3339(),
-            }
-        })
-        .collect();
+    let apps = match pagination::fetch_page(
+        db.as_ref(),
+        oauth_application::Entity::find(),
+        &page,
+        &[
+            ("created_at", oauth_application::Column::CreatedAt),
+            ("instance_name", oauth_application::Column::InstanceName),
+            ("platform", oauth_application::Column::Platform),
+            ("sort_order", oauth_application::Column::SortOrder),
+        ],
+        "created_at",
+        oauth_application::Column::Id,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        Ok(p) => p,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
 
-    Ok(HttpResponse::Ok().json(response))
+    let app_ids: Vec<Uuid> = apps.items.iter().map(|app| app.id).collect();
+    let mut settings: std::collections::HashMap<Uuid, oauth_application_setting::Model> =
+        oauth_application_setting::Entity::find()
+            .filter(oauth_application_setting::Column::ApplicationId.is_in(app_ids))
+            .all(db.as_ref())
+            .await
+            .map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?
+            .into_iter()
+            .map(|s| (s.application_id, s))
+            .collect();
+
+    let response = apps.map(|app| {
+        let preview = mask_secret(&app.client_secret);
+        let app_settings = settings.remove(&app.id);
+        OAuthAppResponse::new(&config, app, app_settings, preview)
+    });
+
+    Ok(response.into_response(page.fields.as_deref()))
 }
 
 /// POST /admin/oauth-apps
 /// Create a new OAuth application (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/oauth-apps",
+    tag = "admin",
+    request_body = CreateOAuthAppRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "OAuth application created", body = OAuthAppResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 403, description = "Admin access required")
+    )
+)]
 pub async fn create_oauth_app(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
@@ -154,6 +422,24 @@ pub async fn create_oauth_app(
         }
     };
 
+    let overrides = parse_scopes(payload.scopes.as_deref().unwrap_or_default()).and_then(|scopes| {
+        Ok((scopes, parse_redirect_uri(payload.redirect_uri.as_deref().unwrap_or_default())?))
+    });
+    let (scopes, redirect_uri) = match overrides {
+        Ok(overrides) => overrides,
+        Err(error) => return Ok(HttpResponse::BadRequest().json(ErrorResponse { error })),
+    };
+
+    let listing = match ListingUpdate::parse(
+        payload.display_name.as_deref(),
+        payload.icon_url.as_deref(),
+        payload.description.as_deref(),
+        payload.login_hint.as_deref(),
+    ) {
+        Ok(listing) => listing,
+        Err(error) => return Ok(HttpResponse::BadRequest().json(ErrorResponse { error })),
+    };
+
     // Normalize instance URL
     let instance_url = payload.instance_url.clone().unwrap_or_default();
     log::info!("Instance URL: '{}'", instance_url);
@@ -203,7 +489,7 @@ pub async fn create_oauth_app(
     }
 
     log::info!("💾 Creating new OAuth app in database");
-    let new_app = oauth_application::ActiveModel {
+    let mut new_app = oauth_application::ActiveModel {
         id: Set(Uuid::new_v4()),
         platform: Set(platform),
         instance_url: Set(instance_url),
@@ -215,7 +501,14 @@ pub async fn create_oauth_app(
         created_by: Set(Some(user_id)),
         created_at: Set(chrono::Utc::now()),
         updated_at: Set(chrono::Utc::now()),
+        display_name: Set(None),
+        icon_url: Set(None),
+        description: Set(None),
+        login_hint: Set(None),
+        sort_order: Set(payload.sort_order.unwrap_or(0)),
+        is_featured: Set(payload.is_featured.unwrap_or(false)),
     };
+    listing.apply(&mut new_app);
 
     let app = oauth_application::Entity::insert(new_app)
         .exec_with_returning(db.as_ref())
@@ -225,29 +518,38 @@ pub async fn create_oauth_app(
             actix_web::error::ErrorInternalServerError("Failed to create OAuth app")
         })?;
 
-    log::info!("✅ OAuth app created successfully: {}", app.id);
+    let settings = save_settings(db.as_ref(), app.id, scopes, redirect_uri)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to save OAuth app settings: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to create OAuth app")
+        })?;
 
-    let platform_str = match app.platform {
-        git_platform_account::GitPlatform::GitHub => "github",
-        git_platform_account::GitPlatform::GitLab => "gitlab",
-        git_platform_account::GitPlatform::Gitea => "gitea",
-    };
+    log::info!("✅ OAuth app created successfully: {}", app.id);
 
-    Ok(HttpResponse::Ok().json(OAuthAppResponse {
-        id: app.id.to_string(),
-        platform: platform_str.to_string(),
-        instance_url: app.instance_url,
-        instance_name: app.instance_name,
-        client_id: app.client_id,
-        client_secret_preview: mask_secret(&payload.client_secret),
-        is_enabled: app.is_enabled,
-        is_default: app.is_default,
-        created_at: app.created_at.to_rfc3339(),
-    }))
+    Ok(HttpResponse::Ok().json(OAuthAppResponse::new(
+        &config,
+        app,
+        settings,
+        mask_secret(&payload.client_secret),
+    )))
 }
 
 /// PUT /api/admin/oauth-apps/:id
 /// Update an OAuth application (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/oauth-apps/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "OAuth application ID")),
+    request_body = UpdateOAuthAppRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "OAuth application updated", body = OAuthAppResponse),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "OAuth application not found")
+    )
+)]
 pub async fn update_oauth_app(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
@@ -274,7 +576,51 @@ pub async fn update_oauth_app(
         })?
         .ok_or_else(|| actix_web::error::ErrorNotFound("OAuth app not found"))?;
 
+    let current = oauth_application_setting::Entity::find_by_id(app.id)
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+    let (current_scopes, current_redirect_uri) = current
+        .map(|s| (s.scopes, s.redirect_uri))
+        .unwrap_or_default();
+    let overrides = payload
+        .scopes
+        .as_deref()
+        .map_or(Ok(current_scopes), parse_scopes)
+        .and_then(|scopes| {
+            let redirect_uri = payload
+                .redirect_uri
+                .as_deref()
+                .map_or(Ok(current_redirect_uri), parse_redirect_uri)?;
+            Ok((scopes, redirect_uri))
+        });
+    let (scopes, redirect_uri) = match overrides {
+        Ok(overrides) => overrides,
+        Err(error) => return Ok(HttpResponse::BadRequest().json(ErrorResponse { error })),
+    };
+    let listing = match ListingUpdate::parse(
+        payload.display_name.as_deref(),
+        payload.icon_url.as_deref(),
+        payload.description.as_deref(),
+        payload.login_hint.as_deref(),
+    ) {
+        Ok(listing) => listing,
+        Err(error) => return Ok(HttpResponse::BadRequest().json(ErrorResponse { error })),
+    };
+
     let mut app: oauth_application::ActiveModel = app.into();
+    listing.apply(&mut app);
+
+    if let Some(is_featured) = payload.is_featured {
+        app.is_featured = Set(is_featured);
+    }
+
+    if let Some(sort_order) = payload.sort_order {
+        app.sort_order = Set(sort_order);
+    }
 
     if let Some(instance_name) = &payload.instance_name {
         app.instance_name = Set(instance_name.clone());
@@ -324,27 +670,127 @@ pub async fn update_oauth_app(
         actix_web::error::ErrorInternalServerError("Failed to update OAuth app")
     })?;
 
-    let platform_str = match updated_app.platform {
-        git_platform_account::GitPlatform::GitHub => "github",
-        git_platform_account::GitPlatform::GitLab => "gitlab",
-        git_platform_account::GitPlatform::Gitea => "gitea",
+    let settings = save_settings(db.as_ref(), updated_app.id, scopes, redirect_uri)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to save OAuth app settings: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to update OAuth app")
+        })?;
+
+    let preview = mask_secret(&updated_app.client_secret);
+    Ok(HttpResponse::Ok().json(OAuthAppResponse::new(&config, updated_app, settings, preview)))
+}
+
+/// PUT /api/admin/oauth-apps/order
+/// Set the order OAuth apps are listed in on the connect screen, and which
+/// are featured (admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/oauth-apps/order",
+    tag = "admin",
+    request_body = ReorderOAuthAppsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Every OAuth application, in listing order", body = [OAuthAppResponse]),
+        (status = 400, description = "An app is listed twice or does not exist"),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn reorder_oauth_apps(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    payload: web::Json<ReorderOAuthAppsRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
     };
 
-    Ok(HttpResponse::Ok().json(OAuthAppResponse {
-        id: updated_app.id.to_string(),
-        platform: platform_str.to_string(),
-        instance_url: updated_app.instance_url,
-        instance_name: updated_app.instance_name,
-        client_id: updated_app.client_id,
-        client_secret_preview: mask_secret(&updated_app.client_secret),
-        is_enabled: updated_app.is_enabled,
-        is_default: updated_app.is_default,
-        created_at: updated_app.created_at.to_rfc3339(),
-    }))
+    let mut seen = std::collections::HashSet::new();
+    if let Some(entry) = payload.apps.iter().find(|entry| !seen.insert(entry.id)) {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+            error: format!("OAuth app {} is listed more than once", entry.id),
+        }));
+    }
+
+    let txn = db.begin().await.map_err(db_error)?;
+    let existing: std::collections::HashSet<Uuid> = oauth_application::Entity::find()
+        .filter(oauth_application::Column::Id.is_in(seen))
+        .all(&txn)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|app| app.id)
+        .collect();
+    if let Some(entry) = payload.apps.iter().find(|entry| !existing.contains(&entry.id)) {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+            error: format!("OAuth app {} not found", entry.id),
+        }));
+    }
+
+    for (position, entry) in payload.apps.iter().enumerate() {
+        let mut update = oauth_application::Entity::update_many()
+            .col_expr(oauth_application::Column::SortOrder, Expr::value(position as i32))
+            .col_expr(oauth_application::Column::UpdatedAt, Expr::value(chrono::Utc::now()));
+        if let Some(is_featured) = entry.is_featured {
+            update = update.col_expr(oauth_application::Column::IsFeatured, Expr::value(is_featured));
+        }
+        update
+            .filter(oauth_application::Column::Id.eq(entry.id))
+            .exec(&txn)
+            .await
+            .map_err(db_error)?;
+    }
+    txn.commit().await.map_err(db_error)?;
+
+    log::info!("🔧 [Admin] Reordered {} OAuth app(s)", payload.apps.len());
+
+    let apps = listing_order(oauth_application::Entity::find())
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    let mut settings: std::collections::HashMap<Uuid, oauth_application_setting::Model> =
+        oauth_application_setting::Entity::find()
+            .all(db.as_ref())
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .map(|s| (s.application_id, s))
+            .collect();
+
+    let response: Vec<OAuthAppResponse> = apps
+        .into_iter()
+        .map(|app| {
+            let preview = mask_secret(&app.client_secret);
+            let app_settings = settings.remove(&app.id);
+            OAuthAppResponse::new(&config, app, app_settings, preview)
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
 }
 
 /// DELETE /api/admin/oauth-apps/:id
 /// Delete an OAuth application (admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/oauth-apps/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "OAuth application ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "OAuth application deleted"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "OAuth application not found")
+    )
+)]
 pub async fn delete_oauth_app(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -372,3 +818,58 @@ pub async fn delete_oauth_app(
         "message": "OAuth application deleted successfully"
     })))
 }
+
+/// POST /api/admin/oauth-apps/:id/test
+/// Check that an OAuth application's secret, redirect URI and credentials work (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/oauth-apps/{id}/test",
+    tag = "admin",
+    params(("id" = String, Path, description = "OAuth application ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Result of each check", body = OAuthAppReport),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "OAuth application not found")
+    )
+)]
+pub async fn test_oauth_app(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let app_id = Uuid::parse_str(&path.into_inner()).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid app ID: {}", e))
+    })?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let app = oauth_application::Entity::find_by_id(app_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("OAuth app not found"))?;
+
+    let report = instance_check::test_oauth_app(db.as_ref(), &config, &app)
+        .await
+        .map_err(db_error)?;
+
+    log::info!(
+        "🔍 [Admin] OAuth app '{}' tested by {}: {}",
+        app.instance_name,
+        user_id,
+        if report.ok { "ok" } else { "failed" }
+    );
+
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -0,0 +1,192 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::handlers::oauth::frontend_redirect;
+use crate::models::oauth_state;
+use crate::services::identity;
+use crate::services::oidc::OidcClient;
+use crate::utils::config::Config;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OidcStatusResponse {
+    pub enabled: bool,
+    /// Label for the sign-in button
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+fn redirect_uri(config: &Config) -> String {
+    format!("{}/auth/oidc/callback", config.base_url)
+}
+
+fn error_redirect(config: &Config, message: &str) -> HttpResponse {
+    frontend_redirect(config, &format!("error={}", urlencoding::encode(message)))
+}
+
+/// GET /auth/oidc
+/// Whether single sign-on is configured, for showing the sign-in button
+#[utoipa::path(
+    get,
+    path = "/auth/oidc",
+    tag = "auth",
+    responses((status = 200, description = "Single sign-on availability", body = OidcStatusResponse))
+)]
+pub async fn oidc_status(oidc: web::Data<OidcClient>) -> impl Responder {
+    HttpResponse::Ok().json(OidcStatusResponse {
+        enabled: oidc.is_enabled(),
+        display_name: oidc.display_name().to_string(),
+    })
+}
+
+/// GET /auth/oidc/authorize
+/// Returns the identity provider's login URL
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/authorize",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Authorization URL to redirect the browser to", body = Object),
+        (status = 404, description = "Single sign-on is not configured"),
+        (status = 502, description = "The identity provider could not be reached")
+    )
+)]
+pub async fn oidc_authorize(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    oidc: web::Data<OidcClient>,
+) -> Result<HttpResponse, actix_web::Error> {
+    if !oidc.is_enabled() {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Single sign-on is not configured"
+        })));
+    }
+
+    let state_token = Uuid::new_v4().to_string();
+    let nonce = Uuid::new_v4().to_string();
+
+    let auth_url = oidc
+        .authorization_url(&redirect_uri(&config), &state_token, &nonce)
+        .await
+        .map_err(|e| {
+            log::error!("❌ [OIDC] Failed to build authorization URL: {}", e);
+            actix_web::error::ErrorBadGateway("Identity provider unavailable")
+        })?;
+
+    oauth_state::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        state_token: Set(state_token),
+        user_id: Set(None),
+        platform: Set(None),
+        created_at: Set(Utc::now()),
+        expires_at: Set(Utc::now() + Duration::minutes(10)),
+        instance_url: Set(None),
+        purpose: Set(identity::PURPOSE_OIDC.to_string()),
+        nonce: Set(Some(nonce)),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(|e| {
+        log::error!("❌ Failed to store OAuth state: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to create OAuth state")
+    })?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "authorization_url": auth_url
+    })))
+}
+
+/// GET /auth/oidc/callback
+/// Handles the identity provider's redirect and signs the user in
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/callback",
+    tag = "auth",
+    params(OidcCallbackQuery),
+    responses((status = 302, description = "Redirect to the frontend with a login code or an error"))
+)]
+pub async fn oidc_callback(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    oidc: web::Data<OidcClient>,
+    query: web::Query<OidcCallbackQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    log::info!("🔄 [OIDC] Callback received");
+
+    if let Some(error) = &query.error {
+        let message = query.error_description.as_deref().unwrap_or(error);
+        log::warn!("❌ [OIDC] Provider returned error: {}", message);
+        return Ok(error_redirect(&config, message));
+    }
+
+    let (Some(code), Some(state_token)) = (&query.code, &query.state) else {
+        return Ok(error_redirect(&config, "Missing code or state"));
+    };
+
+    let state = oauth_state::Entity::find()
+        .filter(oauth_state::Column::StateToken.eq(state_token))
+        .filter(oauth_state::Column::Purpose.eq(identity::PURPOSE_OIDC))
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let Some(state) = state else {
+        log::warn!("❌ [OIDC] Unknown state token");
+        return Ok(error_redirect(&config, "Invalid or expired sign-in attempt"));
+    };
+
+    // Single use, whatever happens next
+    oauth_state::Entity::delete_by_id(state.id)
+        .exec(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    if state.expires_at < Utc::now() {
+        log::warn!("❌ [OIDC] State token expired");
+        return Ok(error_redirect(&config, "Invalid or expired sign-in attempt"));
+    }
+
+    let user_info = match oidc
+        .authenticate(code, &redirect_uri(&config), state.nonce.as_deref().unwrap_or_default())
+        .await
+    {
+        Ok(user_info) => user_info,
+        Err(e) => {
+            log::error!("❌ [OIDC] Sign-in failed: {}", e);
+            return Ok(error_redirect(&config, "Single sign-on failed"));
+        }
+    };
+
+    let user_id = identity::resolve_oidc_user(db.as_ref(), &user_info, oidc.is_admin(&user_info))
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let code = identity::create_login_code(db.as_ref(), user_id).await.map_err(|e| {
+        log::error!("❌ Failed to store login code: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    log::info!("✅ [OIDC] Subject {} signed in as {}", user_info.subject, user_id);
+
+    Ok(frontend_redirect(&config, &format!("login_code={}", urlencoding::encode(&code))))
+}
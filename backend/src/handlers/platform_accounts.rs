@@ -1,30 +1,67 @@
-use actix_web::{web, HttpResponse, Responder};
-use chrono::{Datelike, Utc};
-use sea_orm::sea_query::{Expr, Func};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Datelike;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use crate::models::{contribution, git_platform_account};
+use crate::models::{
+    git_platform_account, organization_membership, platform_account_disconnection,
+    platform_account_label, platform_sync_job,
+};
+use crate::services::api_usage::{self, ApiUsage};
+use crate::services::commit_details::{self, CommitStorage};
+use crate::services::instance_check::{self, InstanceReport};
+use crate::services::instance_settings::{InstanceSettingsService, MIN_SYNC_YEAR};
+use crate::services::platform_disconnect;
+use crate::services::platform_merge::{self, MergeReport};
+use crate::services::platform_visibility::{self, AccountVisibility};
+use crate::services::profile_visibility;
+use crate::services::quotas;
+use crate::services::response_cache::ResponseCache;
 use crate::services::git_platforms::{
-    gitea::GiteaClient, github::GitHubClient, gitlab::GitLabClient, GitPlatform, PlatformConfig,
+    gitea::GiteaClient, github::GitHubClient, gitlab::GitLabClient, Capabilities, GitPlatform,
+    PlatformConfig,
 };
+use crate::services::username_redirects;
+use crate::utils::pagination::{self, PageParams};
 use crate::utils::{config::Config, encryption, validators};
 
-#[derive(Debug, Deserialize)]
+/// Bounds of an account's commit detail retention, as for activities
+const MIN_COMMIT_RETENTION_DAYS: i32 = 30;
+const MAX_COMMIT_RETENTION_DAYS: i32 = 36500;
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ConnectPlatformRequest {
     pub platform: String,
     pub access_token: String,
     pub instance_url: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ValidateInstanceRequest {
+    /// `gitea` (also for Forgejo) or `gitlab`
+    pub platform: String,
+    pub instance_url: String,
+    /// Lets the GitLab version be read; not stored
+    pub access_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateSyncPreferencesRequest {
     pub sync_profile: bool,
     pub sync_contributions: bool, // When enabled, syncs both contributions and activities
+    /// Also store every commit, for hour-of-day and lines-changed stats.
+    /// Takes roughly 250 bytes per commit; turning it off deletes them.
+    /// Unchanged when omitted.
+    pub detailed_sync: Option<bool>,
+    /// Days of commit details kept; 0 keeps them. Unchanged when omitted.
+    pub commit_retention_days: Option<i32>,
+    /// First year all-years syncs go back to. Unchanged when omitted.
+    pub earliest_sync_year: Option<i32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PlatformAccountResponse {
     pub id: String,
     pub platform: String,
@@ -46,10 +83,147 @@ pub struct PlatformAccountResponse {
     // Sync preferences
     pub sync_profile: bool,
     pub sync_contributions: bool, // When enabled, syncs both contributions and activities
+    pub detailed_sync: bool,
+    /// Days of commit details kept; null keeps them
+    pub commit_retention_days: Option<i32>,
+    /// First year all-years syncs go back to
+    pub earliest_sync_year: i32,
     // Authentication method
     pub auth_type: String, // "oauth" or "personal_access_token"
+    /// Name the user gave the account, if any
+    pub label: Option<String>,
+}
+
+impl PlatformAccountResponse {
+    fn new(account: git_platform_account::Model, label: Option<String>) -> Self {
+        let auth_type_str = match account.auth_type {
+            git_platform_account::AuthType::OAuth => "oauth",
+            git_platform_account::AuthType::PersonalAccessToken => "personal_access_token",
+        };
+
+        Self {
+            id: account.id.to_string(),
+            platform: platform_name(&account.platform_type).to_string(),
+            platform_username: account.platform_username,
+            platform_url: account.platform_url,
+            is_active: account.is_active,
+            last_synced_at: account.last_synced_at.map(|dt| dt.to_rfc3339()),
+            created_at: account.created_at.to_rfc3339(),
+            updated_at: account.updated_at.to_rfc3339(),
+            avatar_url: account.avatar_url,
+            display_name: account.display_name,
+            bio: account.bio,
+            profile_url: account.profile_url,
+            location: account.location,
+            company: account.company,
+            followers_count: account.followers_count,
+            following_count: account.following_count,
+            sync_profile: account.sync_profile,
+            sync_contributions: account.sync_contributions,
+            detailed_sync: account.detailed_sync,
+            commit_retention_days: account.commit_retention_days,
+            earliest_sync_year: account.earliest_sync_year,
+            auth_type: auth_type_str.to_string(),
+            label,
+        }
+    }
+}
+
+/// A disconnected account, restorable until `purge_after`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DisconnectedPlatformResponse {
+    #[serde(flatten)]
+    pub account: PlatformAccountResponse,
+    pub disconnected_at: String,
+    /// When the cleanup job deletes the account and its data
+    pub purge_after: String,
+}
+
+impl DisconnectedPlatformResponse {
+    fn new(
+        account: git_platform_account::Model,
+        disconnection: platform_account_disconnection::Model,
+        label: Option<String>,
+    ) -> Self {
+        Self {
+            account: PlatformAccountResponse::new(account, label),
+            disconnected_at: disconnection.disconnected_at.to_rfc3339(),
+            purge_after: disconnection.purge_after.to_rfc3339(),
+        }
+    }
+}
+
+fn platform_name(platform: &git_platform_account::GitPlatform) -> &'static str {
+    match platform {
+        git_platform_account::GitPlatform::GitHub => "github",
+        git_platform_account::GitPlatform::GitLab => "gitlab",
+        git_platform_account::GitPlatform::Gitea => "gitea",
+    }
+}
+
+/// A platform account as public profile endpoints show it: no sync
+/// preferences or authentication details, and the sync time only when the
+/// owner allows it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PublicPlatformAccountResponse {
+    pub id: String,
+    pub platform: String,
+    pub platform_username: String,
+    pub platform_url: Option<String>,
+    /// Name the user gave the account, if any
+    pub label: Option<String>,
+    pub avatar_url: Option<String>,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub profile_url: Option<String>,
+    pub location: Option<String>,
+    pub company: Option<String>,
+    pub followers_count: Option<i32>,
+    pub following_count: Option<i32>,
+    /// Left out unless `show_last_synced_at` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_synced_at: Option<String>,
 }
 
+/// An organization one of the user's public accounts belongs to
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrganizationResponse {
+    pub account_id: String,
+    pub platform: String,
+    pub login: String,
+    pub avatar_url: Option<String>,
+    /// The organization's page on the platform
+    pub url: String,
+    /// YYYY-MM-DD, when it could be determined
+    pub joined_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateVisibilityRequest {
+    pub is_public: Option<bool>,
+    pub show_last_synced_at: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MergeAccountsRequest {
+    /// Account whose history moves over; it is deleted afterwards
+    pub source_account_id: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateLabelRequest {
+    /// Up to 100 characters; null or empty removes the label
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LabelResponse {
+    pub label: Option<String>,
+}
+
+/// Longest account label in characters
+const MAX_LABEL_LEN: usize = 100;
+
 #[derive(Debug, Serialize)]
 struct ErrorResponse {
     error: String,
@@ -57,9 +231,22 @@ struct ErrorResponse {
 
 /// POST /api/platforms/connect
 /// Connect a git platform account using a personal access token
+#[utoipa::path(
+    post,
+    path = "/platforms/connect",
+    tag = "platforms",
+    request_body = ConnectPlatformRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Account connected", body = PlatformAccountResponse),
+        (status = 400, description = "Invalid token or platform"),
+        (status = 403, description = "Platform account quota reached")
+    )
+)]
 pub async fn connect_platform(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     payload: web::Json<ConnectPlatformRequest>,
 ) -> Result<impl Responder, actix_web::Error> {
@@ -181,6 +368,18 @@ pub async fn connect_platform(
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
+    if existing_account.is_none() {
+        let exceeded = quotas::check_platform_accounts(db.as_ref(), &config, user_id)
+            .await
+            .map_err(|e| {
+                log::error!("Database error: {}", e);
+                actix_web::error::ErrorInternalServerError("Database error")
+            })?;
+        if let Some(exceeded) = exceeded {
+            return Ok(exceeded.response());
+        }
+    }
+
     let account = if let Some(account) = existing_account {
         // Update existing account
         let mut account: git_platform_account::ActiveModel = account.into();
@@ -217,6 +416,9 @@ pub async fn connect_platform(
             following_count: Set(None),
             sync_profile: Set(true),
             sync_contributions: Set(true),
+            detailed_sync: Set(false),
+            commit_retention_days: Set(None),
+            earliest_sync_year: Set(instance.current().await.default_earliest_sync_year),
             auth_type: Set(git_platform_account::AuthType::PersonalAccessToken),
         };
 
@@ -229,227 +431,271 @@ pub async fn connect_platform(
             })?
     };
 
-    let platform_str = match account.platform_type {
-        git_platform_account::GitPlatform::GitHub => "github",
-        git_platform_account::GitPlatform::GitLab => "gitlab",
-        git_platform_account::GitPlatform::Gitea => "gitea",
-    };
+    // Reconnecting keeps the label the account had
+    let label = find_label(db.as_ref(), account.id).await?;
+
+    Ok(HttpResponse::Ok().json(PlatformAccountResponse::new(account, label)))
+}
 
-    let auth_type_str = match account.auth_type {
-        git_platform_account::AuthType::OAuth => "oauth",
-        git_platform_account::AuthType::PersonalAccessToken => "personal_access_token",
+/// POST /api/platforms/validate-instance
+/// Check a self-hosted instance before connecting it, so problems with the
+/// URL, certificate or API show up before an access token is entered
+#[utoipa::path(
+    post,
+    path = "/platforms/validate-instance",
+    tag = "platforms",
+    request_body = ValidateInstanceRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Diagnostics; `ok` tells whether every check passed", body = InstanceReport),
+        (status = 400, description = "Platform has no self-hosted instances")
+    )
+)]
+pub async fn validate_instance(
+    db: web::Data<DatabaseConnection>,
+    payload: web::Json<ValidateInstanceRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let platform = match payload.platform.as_str() {
+        "gitea" | "forgejo" => git_platform_account::GitPlatform::Gitea,
+        "gitlab" => git_platform_account::GitPlatform::GitLab,
+        _ => {
+            return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+                error: format!("Only Gitea, Forgejo and GitLab instances can be checked, not {}", payload.platform),
+            }));
+        }
     };
 
-    Ok(HttpResponse::Ok().json(PlatformAccountResponse {
-        id: account.id.to_string(),
-        platform: platform_str.to_string(),
-        platform_username: account.platform_username,
-        platform_url: account.platform_url,
-        is_active: account.is_active,
-        last_synced_at: account.last_synced_at.map(|dt| dt.to_rfc3339()),
-        created_at: account.created_at.to_rfc3339(),
-        updated_at: account.updated_at.to_rfc3339(),
-        avatar_url: account.avatar_url,
-        display_name: account.display_name,
-        bio: account.bio,
-        profile_url: account.profile_url,
-        location: account.location,
-        company: account.company,
-        followers_count: account.followers_count,
-        following_count: account.following_count,
-        sync_profile: account.sync_profile,
-        sync_contributions: account.sync_contributions,
-        auth_type: auth_type_str.to_string(),
-    }))
+    let access_token = payload.access_token.as_deref().filter(|t| !t.is_empty());
+    let report = instance_check::validate(db.as_ref(), platform, &payload.instance_url, access_token)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    log::info!(
+        "🔎 Checked {} instance {}: {}",
+        report.platform,
+        report.instance_url,
+        if report.ok { "ok" } else { "problems found" }
+    );
+
+    Ok(HttpResponse::Ok().json(report))
 }
 
 /// GET /api/platforms
 /// List all connected platform accounts for the current user
+#[utoipa::path(
+    get,
+    path = "/platforms",
+    tag = "platforms",
+    security(("bearer_auth" = [])),
+    params(PageParams),
+    responses(
+        (status = 200, description = "Connected accounts; total in X-Total-Count, next page in X-Next-Cursor", body = [PlatformAccountResponse]),
+        (status = 400, description = "Invalid sort or cursor")
+    )
+)]
 pub async fn list_platforms(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    page: web::Query<PageParams>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
-    let accounts = git_platform_account::Entity::find()
+    let query = git_platform_account::Entity::find()
         .filter(git_platform_account::Column::UserId.eq(user_id))
-        .filter(git_platform_account::Column::IsActive.eq(true))
-        .all(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?;
+        .filter(git_platform_account::Column::IsActive.eq(true));
+
+    let accounts = match pagination::fetch_page(
+        db.as_ref(),
+        query,
+        &page,
+        &[
+            ("created_at", git_platform_account::Column::CreatedAt),
+            ("platform_username", git_platform_account::Column::PlatformUsername),
+            ("last_synced_at", git_platform_account::Column::LastSyncedAt),
+        ],
+        "created_at",
+        git_platform_account::Column::Id,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })? {
+        Ok(p) => p,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
 
-    let response: Vec<PlatformAccountResponse> = accounts
-        .into_iter()
-        .map(|account| {
-            let platform_str = match account.platform_type {
-                git_platform_account::GitPlatform::GitHub => "github",
-                git_platform_account::GitPlatform::GitLab => "gitlab",
-                git_platform_account::GitPlatform::Gitea => "gitea",
-            };
-
-            let auth_type_str = match account.auth_type {
-                git_platform_account::AuthType::OAuth => "oauth",
-                git_platform_account::AuthType::PersonalAccessToken => "personal_access_token",
-            };
-
-            PlatformAccountResponse {
-                id: account.id.to_string(),
-                platform: platform_str.to_string(),
-                platform_username: account.platform_username,
-                platform_url: account.platform_url,
-                is_active: account.is_active,
-                last_synced_at: account.last_synced_at.map(|dt| dt.to_rfc3339()),
-                created_at: account.created_at.to_rfc3339(),
-                updated_at: account.updated_at.to_rfc3339(),
-                avatar_url: account.avatar_url,
-                display_name: account.display_name,
-                bio: account.bio,
-                profile_url: account.profile_url,
-                location: account.location,
-                company: account.company,
-                followers_count: account.followers_count,
-                following_count: account.following_count,
-                sync_profile: account.sync_profile,
-                sync_contributions: account.sync_contributions,
-                auth_type: auth_type_str.to_string(),
-            }
-        })
-        .collect();
+    let account_ids: Vec<Uuid> = accounts.items.iter().map(|a| a.id).collect();
+    let mut labels = find_labels(db.as_ref(), &account_ids).await?;
+    let response = accounts.map(|account| {
+        let label = labels.remove(&account.id);
+        PlatformAccountResponse::new(account, label)
+    });
 
-    Ok(HttpResponse::Ok().json(response))
+    Ok(response.into_response(page.fields.as_deref()))
 }
 
 /// DELETE /api/platforms/:id
-/// Disconnect a platform account
+/// Disconnect a platform account. It can be restored until it is purged
+/// after DISCONNECTED_ACCOUNT_RETENTION_DAYS; disconnecting it again deletes
+/// it right away.
+#[utoipa::path(
+    delete,
+    path = "/platforms/{id}",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Account disconnected and kept until purge_after", body = DisconnectedPlatformResponse),
+        (status = 204, description = "Account and its data deleted"),
+        (status = 404, description = "Platform account not found")
+    )
+)]
 pub async fn disconnect_platform(
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
 ) -> Result<impl Responder, actix_web::Error> {
-    let user_id = Uuid::parse_str(&user_claims.sub)
-        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let account = find_own_account(db.as_ref(), &user_claims, &path.into_inner()).await?;
 
-    let account_id = Uuid::parse_str(&path.into_inner())
-        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid account ID: {}", e)))?;
+    log::info!("🗑️  Disconnecting platform account: {}", account.id);
+
+    let db_error = |e: DbErr| {
+        log::error!("Failed to disconnect account: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to disconnect account")
+    };
+
+    let account_id = account.id;
+    let purged = platform_disconnect::disconnect(
+        db.as_ref(),
+        &config.encryption_key,
+        config.disconnected_account_retention_days,
+        account,
+    )
+    .await
+    .map_err(db_error)?
+    .is_none();
+    if purged {
+        return Ok(HttpResponse::NoContent().finish());
+    }
 
-    // Find the account
     let account = git_platform_account::Entity::find_by_id(account_id)
         .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Platform account not found"))?;
+    let disconnection = platform_account_disconnection::Entity::find_by_id(account_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Platform account not found"))?;
+    let label = find_label(db.as_ref(), account_id).await?;
+
+    Ok(HttpResponse::Ok().json(DisconnectedPlatformResponse::new(account, disconnection, label)))
+}
+
+/// GET /api/platforms/disconnected
+/// Disconnected platform accounts that can still be restored
+#[utoipa::path(
+    get,
+    path = "/platforms/disconnected",
+    tag = "platforms",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Disconnected accounts, most recent first", body = [DisconnectedPlatformResponse]))
+)]
+pub async fn list_disconnected_platforms(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let disconnected = platform_disconnect::list(db.as_ref(), user_id)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
-        })?
-        .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
+        })?;
 
-    // Verify ownership
-    if account.user_id != user_id {
-        return Err(actix_web::error::ErrorForbidden("Not authorized"));
-    }
+    let account_ids: Vec<Uuid> = disconnected.iter().map(|(account, _)| account.id).collect();
+    let mut labels = find_labels(db.as_ref(), &account_ids).await?;
+    let response: Vec<DisconnectedPlatformResponse> = disconnected
+        .into_iter()
+        .map(|(account, disconnection)| {
+            let label = labels.remove(&account.id);
+            DisconnectedPlatformResponse::new(account, disconnection, label)
+        })
+        .collect();
 
-    log::info!("🗑️  Disconnecting platform account: {}", account_id);
-
-    // Revoke the OAuth token on the platform before deleting
-    if let Some(encrypted_token) = &account.access_token {
-        log::info!("🔓 Decrypting access token for revocation");
-
-        match encryption::decrypt(encrypted_token, &config.encryption_key) {
-            Ok(access_token) => {
-                log::info!("🔒 Revoking token on platform: {:?}", account.platform_type);
-
-                // Revoke token based on platform type
-                let revoke_result = match account.platform_type {
-                    git_platform_account::GitPlatform::GitHub => {
-                        // Get OAuth app credentials for GitHub
-                        let oauth_app = crate::models::oauth_application::Entity::find()
-                            .filter(
-                                crate::models::oauth_application::Column::Platform
-                                    .eq(account.platform_type.clone()),
-                            )
-                            .filter(crate::models::oauth_application::Column::InstanceUrl.eq(""))
-                            .filter(crate::models::oauth_application::Column::IsEnabled.eq(true))
-                            .one(db.as_ref())
-                            .await
-                            .ok()
-                            .flatten();
-
-                        if let Some(app) = oauth_app {
-                            let client_secret =
-                                encryption::decrypt(&app.client_secret, &config.encryption_key)
-                                    .unwrap_or_default();
-
-                            let github_client = GitHubClient::new();
-                            github_client
-                                .revoke_token(&app.client_id, &client_secret, &access_token)
-                                .await
-                        } else {
-                            log::warn!("No GitHub OAuth app configured, skipping token revocation");
-                            Ok(())
-                        }
-                    }
-                    git_platform_account::GitPlatform::GitLab => {
-                        let instance_url = account
-                            .platform_url
-                            .as_deref()
-                            .unwrap_or("https://gitlab.com");
-                        let gitlab_client = GitLabClient::new();
-                        gitlab_client
-                            .revoke_token(instance_url, &access_token)
-                            .await
-                    }
-                    git_platform_account::GitPlatform::Gitea => {
-                        if let Some(instance_url) = &account.platform_url {
-                            let gitea_client = GiteaClient::new();
-                            gitea_client.revoke_token(instance_url, &access_token).await
-                        } else {
-                            log::warn!(
-                                "No instance URL for Gitea account, skipping token revocation"
-                            );
-                            Ok(())
-                        }
-                    }
-                };
-
-                if let Err(e) = revoke_result {
-                    log::warn!("⚠️  Token revocation failed (non-fatal): {}", e);
-                    // Continue with deletion even if revocation fails
-                }
-            }
-            Err(e) => {
-                log::warn!("⚠️  Failed to decrypt token for revocation: {}", e);
-                // Continue with deletion even if we can't decrypt the token
-            }
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// POST /api/platforms/:id/restore
+/// Restore a disconnected platform account with its contributions and
+/// activities. Its token was revoked, so connect it again to resume syncing.
+#[utoipa::path(
+    post,
+    path = "/platforms/{id}/restore",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Account restored", body = PlatformAccountResponse),
+        (status = 403, description = "Platform account quota reached"),
+        (status = 404, description = "No disconnected account with this ID")
+    )
+)]
+pub async fn restore_platform(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let account = find_own_account(db.as_ref(), &user_claims, &path.into_inner()).await?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    if !account.is_active {
+        if let Some(exceeded) = quotas::check_platform_accounts(db.as_ref(), &config, account.user_id)
+            .await
+            .map_err(db_error)?
+        {
+            return Ok(exceeded.response());
         }
-    } else {
-        log::info!("No access token to revoke");
     }
 
-    log::info!(
-        "🗑️  Deleting platform account and all related data for account ID: {}",
-        account_id
-    );
-
-    // Delete the account (CASCADE will delete all contributions and activities)
-    git_platform_account::Entity::delete_by_id(account_id)
-        .exec(db.as_ref())
+    let account = platform_disconnect::restore(db.as_ref(), account)
         .await
-        .map_err(|e| {
-            log::error!("Failed to delete account: {}", e);
-            actix_web::error::ErrorInternalServerError("Failed to disconnect account")
-        })?;
-
-    log::info!("✅ Platform account deleted successfully. All related contributions and activities have been removed.");
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Account is not disconnected"))?;
 
-    Ok(HttpResponse::NoContent().finish())
+    let label = find_label(db.as_ref(), account.id).await?;
+    Ok(HttpResponse::Ok().json(PlatformAccountResponse::new(account, label)))
 }
 
 /// PUT /api/platforms/:id/sync-preferences
 /// Update sync preferences for a platform account
+#[utoipa::path(
+    put,
+    path = "/platforms/{id}/sync-preferences",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    request_body = UpdateSyncPreferencesRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Preferences updated", body = PlatformAccountResponse),
+        (status = 400, description = "No sync type enabled, or invalid commit retention or earliest sync year"),
+        (status = 404, description = "Platform account not found")
+    )
+)]
 pub async fn update_sync_preferences(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -484,69 +730,287 @@ pub async fn update_sync_preferences(
         ));
     }
 
+    let commit_retention_days = match payload.commit_retention_days {
+        None => account.commit_retention_days,
+        Some(0) => None,
+        Some(days) if !(MIN_COMMIT_RETENTION_DAYS..=MAX_COMMIT_RETENTION_DAYS).contains(&days) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!(
+                    "commit_retention_days must be 0 or between {} and {}",
+                    MIN_COMMIT_RETENTION_DAYS, MAX_COMMIT_RETENTION_DAYS
+                )
+            })));
+        }
+        Some(days) => Some(days),
+    };
+    let current_year = chrono::Utc::now().year();
+    let earliest_sync_year = match payload.earliest_sync_year {
+        None => account.earliest_sync_year,
+        Some(year) if !(MIN_SYNC_YEAR..=current_year).contains(&year) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("earliest_sync_year must be between {} and {}", MIN_SYNC_YEAR, current_year)
+            })));
+        }
+        Some(year) => year,
+    };
+    let detailed_sync = payload.detailed_sync.unwrap_or(account.detailed_sync);
+    let turned_off = account.detailed_sync && !detailed_sync;
+
     // Update sync preferences
     let mut account: git_platform_account::ActiveModel = account.into();
     account.sync_profile = Set(payload.sync_profile);
     account.sync_contributions = Set(payload.sync_contributions);
+    account.detailed_sync = Set(detailed_sync);
+    account.commit_retention_days = Set(commit_retention_days);
+    account.earliest_sync_year = Set(earliest_sync_year);
     account.updated_at = Set(chrono::Utc::now());
 
-    let updated_account = account.update(db.as_ref()).await.map_err(|e| {
+    let txn = db.begin().await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    let updated_account = account.update(&txn).await.map_err(|e| {
         log::error!("Failed to update sync preferences: {}", e);
         actix_web::error::ErrorInternalServerError("Failed to update sync preferences")
     })?;
+    // Stored commits go with detailed sync, so turning it off frees the space
+    if turned_off {
+        let deleted = commit_details::delete_all(&txn, updated_account.id)
+            .await
+            .map_err(|e| {
+                log::error!("Failed to delete commit details: {}", e);
+                actix_web::error::ErrorInternalServerError("Failed to update sync preferences")
+            })?;
+        log::info!("🗑️  Deleted {} commit details of {}", deleted, updated_account.id);
+    }
+    txn.commit().await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
 
-    let platform_str = match updated_account.platform_type {
-        git_platform_account::GitPlatform::GitHub => "github",
-        git_platform_account::GitPlatform::GitLab => "gitlab",
-        git_platform_account::GitPlatform::Gitea => "gitea",
+    let label = find_label(db.as_ref(), updated_account.id).await?;
+
+    Ok(HttpResponse::Ok().json(PlatformAccountResponse::new(updated_account, label)))
+}
+
+/// GET /api/users/:username/platforms
+/// Public endpoint to get platform accounts for a user by username
+#[utoipa::path(
+    get,
+    path = "/users/{username}/platforms",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "Connected accounts the user made public", body = [PublicPlatformAccountResponse]),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_user_platforms(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let username = path.into_inner();
+
+    // Find user by username (case-insensitive)
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
     };
 
-    let auth_type_str = match updated_account.auth_type {
-        git_platform_account::AuthType::OAuth => "oauth",
-        git_platform_account::AuthType::PersonalAccessToken => "personal_access_token",
+    let user_id = user_model.id;
+
+    let visibility = profile_visibility::find(db.as_ref(), user_id).await.map_err(|e| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_id, &visibility)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+    {
+        return Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "User not found".to_string(),
+        }));
+    }
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
     };
 
-    Ok(HttpResponse::Ok().json(PlatformAccountResponse {
-        id: updated_account.id.to_string(),
-        platform: platform_str.to_string(),
-        platform_username: updated_account.platform_username,
-        platform_url: updated_account.platform_url,
-        is_active: updated_account.is_active,
-        last_synced_at: updated_account.last_synced_at.map(|dt| dt.to_rfc3339()),
-        created_at: updated_account.created_at.to_rfc3339(),
-        updated_at: updated_account.updated_at.to_rfc3339(),
-        avatar_url: updated_account.avatar_url,
-        display_name: updated_account.display_name,
-        bio: updated_account.bio,
-        profile_url: updated_account.profile_url,
-        location: updated_account.location,
-        company: updated_account.company,
-        followers_count: updated_account.followers_count,
-        following_count: updated_account.following_count,
-        sync_profile: updated_account.sync_profile,
-        sync_contributions: updated_account.sync_contributions,
-        auth_type: auth_type_str.to_string(),
-    }))
+    // Active accounts the owner did not hide
+    let hidden = platform_visibility::hidden_accounts(db.as_ref(), user_id)
+        .await
+        .map_err(db_error)?;
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(git_platform_account::Column::Id.is_not_in(hidden))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+    let visibility_by_account = platform_visibility::for_accounts(db.as_ref(), &account_ids)
+        .await
+        .map_err(db_error)?;
+    let mut labels = find_labels(db.as_ref(), &account_ids).await?;
+
+    let response: Vec<PublicPlatformAccountResponse> = accounts
+        .into_iter()
+        .map(|account| {
+            let show_last_synced_at = visibility_by_account
+                .get(&account.id)
+                .is_some_and(|v| v.show_last_synced_at);
+
+            PublicPlatformAccountResponse {
+                id: account.id.to_string(),
+                platform: platform_name(&account.platform_type).to_string(),
+                label: labels.remove(&account.id),
+                platform_username: account.platform_username,
+                platform_url: account.platform_url,
+                avatar_url: account.avatar_url,
+                display_name: account.display_name,
+                bio: account.bio,
+                profile_url: account.profile_url,
+                location: account.location,
+                company: account.company,
+                followers_count: account.followers_count,
+                following_count: account.following_count,
+                last_synced_at: account
+                    .last_synced_at
+                    .filter(|_| show_last_synced_at)
+                    .map(|dt| dt.to_rfc3339()),
+            }
+        })
+        .collect();
+
+    let mut response = HttpResponse::Ok().json(response);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
 }
 
-/// POST /api/platforms/:id/sync?all_years=true
-/// Manually trigger a sync for a platform account
-pub async fn sync_platform(
+/// GET /api/users/:username/organizations
+/// Public endpoint to get the organizations of a user's public platform
+/// accounts, as of their last sync
+#[utoipa::path(
+    get,
+    path = "/users/{username}/organizations",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "Organizations, earliest joined first", body = [OrganizationResponse]),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_user_organizations(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
-    user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
-    query: web::Query<std::collections::HashMap<String, String>>,
 ) -> Result<impl Responder, actix_web::Error> {
+    let username = path.into_inner();
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    // Find user by username (case-insensitive)
+    let user_model = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let user_id = user_model.id;
+
+    let visibility = profile_visibility::find(db.as_ref(), user_id)
+        .await
+        .map_err(db_error)?;
+    if !profile_visibility::can_view(db.as_ref(), &config, &req, user_id, &visibility)
+        .await
+        .map_err(db_error)?
+    {
+        return Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "User not found".to_string(),
+        }));
+    }
+
+    // Memberships of the active accounts the owner did not hide
+    let hidden = platform_visibility::hidden_accounts(db.as_ref(), user_id)
+        .await
+        .map_err(db_error)?;
+    let rows = organization_membership::Entity::find()
+        .find_also_related(git_platform_account::Entity)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(git_platform_account::Column::Id.is_not_in(hidden))
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let mut organizations: Vec<_> = rows
+        .into_iter()
+        .filter_map(|(membership, account)| Some((membership, account?)))
+        .collect();
+    // Earliest joined first; unknown join dates last
+    organizations.sort_by(|(a, _), (b, _)| {
+        (a.joined_at.is_none(), a.joined_at, a.login.to_lowercase())
+            .cmp(&(b.joined_at.is_none(), b.joined_at, b.login.to_lowercase()))
+    });
+
+    let response: Vec<OrganizationResponse> = organizations
+        .into_iter()
+        .map(|(membership, account)| OrganizationResponse {
+            account_id: account.id.to_string(),
+            platform: platform_name(&account.platform_type).to_string(),
+            url: format!("{}/{}", platform_web_url(&account), membership.login),
+            login: membership.login,
+            avatar_url: membership.avatar_url,
+            joined_at: membership.joined_at.map(|d| d.format("%Y-%m-%d").to_string()),
+        })
+        .collect();
+
+    let mut response = HttpResponse::Ok().json(response);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// Web address of the account's platform instance, without a trailing slash
+fn platform_web_url(account: &git_platform_account::Model) -> String {
+    let url = match (&account.platform_url, &account.platform_type) {
+        (Some(url), _) => url.clone(),
+        (None, git_platform_account::GitPlatform::GitHub) => PlatformConfig::github().instance_url,
+        (None, git_platform_account::GitPlatform::GitLab) => PlatformConfig::gitlab().instance_url,
+        // Gitea accounts always have an instance URL
+        (None, git_platform_account::GitPlatform::Gitea) => String::new(),
+    };
+    url.trim_end_matches('/').to_string()
+}
+
+/// Find one of the current user's platform accounts
+async fn find_own_account(
+    db: &DatabaseConnection,
+    user_claims: &crate::middleware::auth::Claims,
+    account_id: &str,
+) -> Result<git_platform_account::Model, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
 
-    let account_id = Uuid::parse_str(&path.into_inner())
+    let account_id = Uuid::parse_str(account_id)
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid account ID: {}", e)))?;
 
-    // Find the account
     let account = git_platform_account::Entity::find_by_id(account_id)
-        .one(db.as_ref())
+        .one(db)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
@@ -554,960 +1018,394 @@ pub async fn sync_platform(
         })?
         .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
 
-    // Verify ownership
     if account.user_id != user_id {
         return Err(actix_web::error::ErrorForbidden("Not authorized"));
     }
 
-    if !account.is_active {
-        return Err(actix_web::error::ErrorBadRequest("Account is not active"));
-    }
+    Ok(account)
+}
 
-    // Get the access token
-    let encrypted_token = account
-        .access_token
-        .clone()
-        .ok_or_else(|| actix_web::error::ErrorInternalServerError("No access token found"))?;
-
-    // Decrypt the access token
-    let access_token =
-        encryption::decrypt(&encrypted_token, &config.encryption_key).map_err(|e| {
-            log::error!("Failed to decrypt token: {}", e);
-            actix_web::error::ErrorInternalServerError("Decryption failed")
+/// GET /api/platforms/:id/visibility
+/// What the public profile shows of a platform account
+#[utoipa::path(
+    get,
+    path = "/platforms/{id}/visibility",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Public visibility", body = AccountVisibility),
+        (status = 404, description = "Platform account not found")
+    )
+)]
+pub async fn get_visibility(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let account = find_own_account(db.as_ref(), &user_claims, &path).await?;
+
+    let visibility = platform_visibility::find(db.as_ref(), account.id)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
-    log::info!("🔄 [Sync] Starting sync for account: {}", account_id);
+    Ok(HttpResponse::Ok().json(visibility))
+}
+
+/// PUT /api/platforms/:id/visibility
+/// Hide a platform account from the public profile, or show when it last
+/// synced. Fields left out keep their value.
+#[utoipa::path(
+    put,
+    path = "/platforms/{id}/visibility",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    request_body = UpdateVisibilityRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Visibility updated", body = AccountVisibility),
+        (status = 404, description = "Platform account not found")
+    )
+)]
+pub async fn update_visibility(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    payload: web::Json<UpdateVisibilityRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let account = find_own_account(db.as_ref(), &user_claims, &path).await?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let current = platform_visibility::find(db.as_ref(), account.id)
+        .await
+        .map_err(db_error)?;
+    let visibility = AccountVisibility {
+        is_public: payload.is_public.unwrap_or(current.is_public),
+        show_last_synced_at: payload.show_last_synced_at.unwrap_or(current.show_last_synced_at),
+    };
+
+    platform_visibility::save(db.as_ref(), account.id, visibility)
+        .await
+        .map_err(db_error)?;
+
     log::info!(
-        "🔄 [Sync] Platform: {:?}, Username: {}",
-        account.platform_type,
-        account.platform_username
+        "👁️  Platform account {} ({}) is now {} on the public profile",
+        account.id,
+        account.platform_username,
+        if visibility.is_public { "shown" } else { "hidden" }
     );
 
-    // Check if this is profile-only sync
-    let profile_only = query
-        .get("profile_only")
-        .map(|v| v == "true")
-        .unwrap_or(false);
+    Ok(HttpResponse::Ok().json(visibility))
+}
 
-    if profile_only {
-        log::info!("👤 [Sync] Profile-only mode enabled - skipping contributions sync");
-    }
+/// GET /api/platforms/:id/capabilities
+/// What the account's platform instance supports, so unsupported options
+/// can be left out
+#[utoipa::path(
+    get,
+    path = "/platforms/{id}/capabilities",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Supported features", body = Capabilities),
+        (status = 404, description = "Platform account not found")
+    )
+)]
+pub async fn get_capabilities(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let account = find_own_account(db.as_ref(), &user_claims, &path).await?;
 
-    // Check sync mode: all_years, specific year, or current year (default)
-    let sync_all_years = query.get("all_years").map(|v| v == "true").unwrap_or(false);
-
-    let specific_year = query.get("year").and_then(|v| v.parse::<i32>().ok());
-
-    // Sync contributions only if not profile_only mode
-    if !profile_only {
-        // Fetch contributions from GitHub
-        match account.platform_type {
-            git_platform_account::GitPlatform::GitHub => {
-                let github_client = GitHubClient::new();
-                let platform_config = PlatformConfig::github();
-
-                let current_year = Utc::now().year();
-
-                let (start_year, end_year) = if sync_all_years {
-                    log::info!("🔄 [Sync] Mode: ALL YEARS (2020 to {})", current_year);
-                    (2020, current_year)
-                } else if let Some(year) = specific_year {
-                    log::info!("🔄 [Sync] Mode: SPECIFIC YEAR ({})", year);
-                    (year, year)
-                } else {
-                    log::info!("🔄 [Sync] Mode: CURRENT YEAR ({})", current_year);
-                    (current_year, current_year)
-                };
-
-                let mut all_contributions = Vec::new();
-                let mut total_inserted = 0;
-
-                for year in start_year..=end_year {
-                    let from_date = chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
-                    let from = from_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-
-                    let to_date = if year == current_year {
-                        // For current year, fetch up to today
-                        Utc::now().date_naive()
-                    } else {
-                        // For past years, fetch the whole year
-                        chrono::NaiveDate::from_ymd_opt(year, 12, 31).unwrap()
-                    };
-                    let to = to_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
-
-                    log::info!(
-                        "🔄 [Sync] Fetching year {}: {} to {}",
-                        year,
-                        from.format("%Y-%m-%d"),
-                        to.format("%Y-%m-%d")
-                    );
-
-                    let contributions = github_client
-                        .fetch_contributions(
-                            &platform_config,
-                            &account.platform_username,
-                            &access_token,
-                            from,
-                            to,
-                        )
-                        .await
-                        .map_err(|e| {
-                            log::error!(
-                                "❌ [Sync] Failed to fetch contributions for year {}: {}",
-                                year,
-                                e
-                            );
-                            actix_web::error::ErrorInternalServerError(format!(
-                                "Failed to fetch contributions for year {}: {}",
-                                year, e
-                            ))
-                        })?;
-
-                    log::info!(
-                        "✅ [Sync] Fetched {} contribution days for year {}",
-                        contributions.len(),
-                        year
-                    );
-                    all_contributions.extend(contributions);
-                }
-
-                log::info!(
-                    "✅ [Sync] Total fetched: {} contribution days across all years",
-                    all_contributions.len()
-                );
-
-                // Delete existing contributions in the synced date range
-                let delete_from = chrono::NaiveDate::from_ymd_opt(start_year, 1, 1).unwrap();
-                let delete_to = if end_year == current_year {
-                    Utc::now().date_naive()
-                } else {
-                    chrono::NaiveDate::from_ymd_opt(end_year, 12, 31).unwrap()
-                };
-
-                log::info!(
-                    "🗑️  [Sync] Deleting existing contributions from {} to {}",
-                    delete_from,
-                    delete_to
-                );
-
-                let deleted = contribution::Entity::delete_many()
-                    .filter(contribution::Column::GitPlatformAccountId.eq(account_id))
-                    .filter(contribution::Column::ContributionDate.gte(delete_from))
-                    .filter(contribution::Column::ContributionDate.lte(delete_to))
-                    .exec(db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        log::error!("Failed to delete existing contributions: {}", e);
-                        actix_web::error::ErrorInternalServerError(
-                            "Failed to delete existing contributions",
-                        )
-                    })?;
-
-                log::info!(
-                    "🗑️  [Sync] Deleted {} existing contributions in date range",
-                    deleted.rows_affected
-                );
-
-                // Insert all fresh contributions
-                for contrib in all_contributions {
-                    let new_contrib = contribution::ActiveModel {
-                        id: Set(Uuid::new_v4()),
-                        git_platform_account_id: Set(account_id),
-                        contribution_date: Set(contrib.date),
-                        count: Set(contrib.count),
-                        repository_name: Set(contrib.repository_name),
-                        is_private_repo: Set(contrib.is_private),
-                        created_at: Set(Utc::now()),
-                        updated_at: Set(Utc::now()),
-                    };
-
-                    contribution::Entity::insert(new_contrib)
-                        .exec(db.as_ref())
-                        .await
-                        .map_err(|e| {
-                            log::error!("Failed to insert contribution: {}", e);
-                            actix_web::error::ErrorInternalServerError(
-                                "Failed to insert contribution",
-                            )
-                        })?;
-                    total_inserted += 1;
-                }
-
-                log::info!(
-                    "💾 [Sync] Stored contributions: {} inserted",
-                    total_inserted
-                );
-
-                // Sync profile data if enabled (synced only once, regardless of date range)
-                let account_for_profile = git_platform_account::Entity::find_by_id(account_id)
-                    .one(db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        log::error!("Database error: {}", e);
-                        actix_web::error::ErrorInternalServerError("Database error")
-                    })?
-                    .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
-
-                if account_for_profile.sync_profile {
-                    log::info!("👤 [Sync] Syncing profile data for {} (one-time, independent of date range)", account.platform_username);
-                    match github_client
-                        .fetch_user_profile(
-                            &platform_config,
-                            &account.platform_username,
-                            &access_token,
-                        )
-                        .await
-                    {
-                        Ok(profile_data) => {
-                            log::info!("✅ [Sync] Fetched profile data successfully");
-
-                            // Extract profile fields from the JSON response
-                            let avatar_url = profile_data
-                                .get("avatar_url")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let display_name = profile_data
-                                .get("name")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let bio = profile_data
-                                .get("bio")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let profile_url = profile_data
-                                .get("html_url")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let location = profile_data
-                                .get("location")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let company = profile_data
-                                .get("company")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let followers_count = profile_data
-                                .get("followers")
-                                .and_then(|v| v.as_i64())
-                                .map(|n| n as i32);
-
-                            let following_count = profile_data
-                                .get("following")
-                                .and_then(|v| v.as_i64())
-                                .map(|n| n as i32);
-
-                            // Update account with profile data
-                            let profile_account =
-                                git_platform_account::Entity::find_by_id(account_id)
-                                    .one(db.as_ref())
-                                    .await
-                                    .map_err(|e| {
-                                        log::error!("Database error: {}", e);
-                                        actix_web::error::ErrorInternalServerError("Database error")
-                                    })?
-                                    .ok_or_else(|| {
-                                        actix_web::error::ErrorNotFound("Account not found")
-                                    })?;
-
-                            let mut profile_active: git_platform_account::ActiveModel =
-                                profile_account.into();
-                            profile_active.avatar_url = Set(avatar_url);
-                            profile_active.display_name = Set(display_name);
-                            profile_active.bio = Set(bio);
-                            profile_active.profile_url = Set(profile_url);
-                            profile_active.location = Set(location);
-                            profile_active.company = Set(company);
-                            profile_active.followers_count = Set(followers_count);
-                            profile_active.following_count = Set(following_count);
-                            profile_active.updated_at = Set(Utc::now());
-
-                            profile_active.update(db.as_ref()).await.map_err(|e| {
-                                log::error!("Failed to update profile data: {}", e);
-                                actix_web::error::ErrorInternalServerError(
-                                    "Failed to update profile",
-                                )
-                            })?;
-
-                            log::info!("💾 [Sync] Stored profile data successfully");
-                        }
-                        Err(e) => {
-                            log::warn!(
-                                "⚠️  [Sync] Failed to fetch profile data (continuing sync): {}",
-                                e
-                            );
-                            // Don't fail the entire sync if profile fetch fails
-                        }
-                    }
-                } else {
-                    log::debug!("[Sync] Profile sync disabled for this account");
-                }
-            }
-            git_platform_account::GitPlatform::Gitea => {
-                let gitea_client = GiteaClient::new();
-                let instance_url = account.platform_url.as_ref().ok_or_else(|| {
-                    actix_web::error::ErrorInternalServerError("Gitea instance URL not found")
-                })?;
-                let platform_config = PlatformConfig::gitea_custom(instance_url);
-
-                let current_year = Utc::now().year();
-
-                let (start_year, end_year) = if sync_all_years {
-                    log::info!("🔄 [Sync] Mode: ALL YEARS (2020 to {})", current_year);
-                    (2020, current_year)
-                } else if let Some(year) = specific_year {
-                    log::info!("🔄 [Sync] Mode: SPECIFIC YEAR ({})", year);
-                    (year, year)
-                } else {
-                    log::info!("🔄 [Sync] Mode: CURRENT YEAR ({})", current_year);
-                    (current_year, current_year)
-                };
-
-                let mut all_contributions = Vec::new();
-                let mut total_inserted = 0;
-
-                for year in start_year..=end_year {
-                    let from_date = chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
-                    let from = from_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-
-                    let to_date = if year == current_year {
-                        Utc::now().date_naive()
-                    } else {
-                        chrono::NaiveDate::from_ymd_opt(year, 12, 31).unwrap()
-                    };
-                    let to = to_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
-
-                    log::info!(
-                        "🔄 [Sync] Fetching year {}: {} to {}",
-                        year,
-                        from.format("%Y-%m-%d"),
-                        to.format("%Y-%m-%d")
-                    );
-
-                    let contributions = gitea_client
-                        .fetch_contributions(
-                            &platform_config,
-                            &account.platform_username,
-                            &access_token,
-                            from,
-                            to,
-                        )
-                        .await
-                        .map_err(|e| {
-                            log::error!(
-                                "❌ [Sync] Failed to fetch Gitea contributions for year {}: {}",
-                                year,
-                                e
-                            );
-                            actix_web::error::ErrorInternalServerError(format!(
-                                "Failed to fetch contributions for year {}: {}",
-                                year, e
-                            ))
-                        })?;
-
-                    log::info!(
-                        "✅ [Sync] Fetched {} contribution days for year {}",
-                        contributions.len(),
-                        year
-                    );
-                    all_contributions.extend(contributions);
-                }
-
-                log::info!(
-                    "✅ [Sync] Total fetched: {} contribution days across all years",
-                    all_contributions.len()
-                );
-
-                // Delete existing contributions in the synced date range (same as GitHub)
-                let delete_from = chrono::NaiveDate::from_ymd_opt(start_year, 1, 1).unwrap();
-                let delete_to = if end_year == current_year {
-                    Utc::now().date_naive()
-                } else {
-                    chrono::NaiveDate::from_ymd_opt(end_year, 12, 31).unwrap()
-                };
-
-                log::info!(
-                    "🗑️  [Sync] Deleting existing contributions from {} to {}",
-                    delete_from,
-                    delete_to
-                );
-
-                let deleted = contribution::Entity::delete_many()
-                    .filter(contribution::Column::GitPlatformAccountId.eq(account_id))
-                    .filter(contribution::Column::ContributionDate.gte(delete_from))
-                    .filter(contribution::Column::ContributionDate.lte(delete_to))
-                    .exec(db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        log::error!("Failed to delete existing contributions: {}", e);
-                        actix_web::error::ErrorInternalServerError(
-                            "Failed to delete existing contributions",
-                        )
-                    })?;
-
-                log::info!(
-                    "🗑️  [Sync] Deleted {} existing contributions in date range",
-                    deleted.rows_affected
-                );
-
-                // Insert all fresh contributions
-                for contrib in all_contributions {
-                    let new_contrib = contribution::ActiveModel {
-                        id: Set(Uuid::new_v4()),
-                        git_platform_account_id: Set(account_id),
-                        contribution_date: Set(contrib.date),
-                        count: Set(contrib.count),
-                        repository_name: Set(contrib.repository_name),
-                        is_private_repo: Set(contrib.is_private),
-                        created_at: Set(Utc::now()),
-                        updated_at: Set(Utc::now()),
-                    };
-
-                    contribution::Entity::insert(new_contrib)
-                        .exec(db.as_ref())
-                        .await
-                        .map_err(|e| {
-                            log::error!("Failed to insert contribution: {}", e);
-                            actix_web::error::ErrorInternalServerError(
-                                "Failed to insert contribution",
-                            )
-                        })?;
-                    total_inserted += 1;
-                }
-
-                log::info!(
-                    "💾 [Sync] Stored contributions: {} inserted",
-                    total_inserted
-                );
-
-                // Sync profile data if enabled (synced only once, regardless of date range)
-                let account_for_profile = git_platform_account::Entity::find_by_id(account_id)
-                    .one(db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        log::error!("Database error: {}", e);
-                        actix_web::error::ErrorInternalServerError("Database error")
-                    })?
-                    .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
-
-                if account_for_profile.sync_profile {
-                    log::info!("👤 [Sync] Syncing Gitea profile data for {} (one-time, independent of date range)", account.platform_username);
-                    match gitea_client
-                        .fetch_user_profile(&platform_config, &access_token)
-                        .await
-                    {
-                        Ok(profile_data) => {
-                            log::info!("✅ [Sync] Fetched Gitea profile data successfully");
-
-                            let avatar_url = profile_data
-                                .get("avatar_url")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let display_name = profile_data
-                                .get("full_name")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let bio = profile_data
-                                .get("description")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let profile_url =
-                                Some(format!("{}/{}", instance_url, account.platform_username));
-
-                            let location = profile_data
-                                .get("location")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let followers_count = profile_data
-                                .get("followers_count")
-                                .and_then(|v| v.as_i64())
-                                .map(|n| n as i32);
-
-                            let following_count = profile_data
-                                .get("following_count")
-                                .and_then(|v| v.as_i64())
-                                .map(|n| n as i32);
-
-                            // Update account with profile data
-                            let account_for_update =
-                                git_platform_account::Entity::find_by_id(account_id)
-                                    .one(db.as_ref())
-                                    .await
-                                    .map_err(|e| {
-                                        log::error!("Database error: {}", e);
-                                        actix_web::error::ErrorInternalServerError("Database error")
-                                    })?
-                                    .ok_or_else(|| {
-                                        actix_web::error::ErrorNotFound("Account not found")
-                                    })?;
-
-                            let mut account_update: git_platform_account::ActiveModel =
-                                account_for_update.into();
-
-                            if let Some(url) = avatar_url {
-                                account_update.avatar_url = Set(Some(url));
-                            }
-                            if let Some(name) = display_name {
-                                account_update.display_name = Set(Some(name));
-                            }
-                            if let Some(b) = bio {
-                                account_update.bio = Set(Some(b));
-                            }
-                            if let Some(url) = profile_url {
-                                account_update.profile_url = Set(Some(url));
-                            }
-                            if let Some(loc) = location {
-                                account_update.location = Set(Some(loc));
-                            }
-                            if let Some(count) = followers_count {
-                                account_update.followers_count = Set(Some(count));
-                            }
-                            if let Some(count) = following_count {
-                                account_update.following_count = Set(Some(count));
-                            }
-
-                            account_update.updated_at = Set(Utc::now());
-                            account_update.update(db.as_ref()).await.map_err(|e| {
-                                log::error!("Failed to update account: {}", e);
-                                actix_web::error::ErrorInternalServerError(
-                                    "Failed to update account",
-                                )
-                            })?;
-
-                            log::info!("💾 [Sync] Stored Gitea profile data successfully");
-                        }
-                        Err(e) => {
-                            log::warn!("⚠️  [Sync] Failed to fetch Gitea profile data (continuing sync): {}", e);
-                        }
-                    }
-                } else {
-                    log::debug!("[Sync] Profile sync disabled for this account");
-                }
-            }
-            git_platform_account::GitPlatform::GitLab => {
-                let gitlab_client = GitLabClient::new();
-                let instance_url = account.platform_url.as_ref().ok_or_else(|| {
-                    actix_web::error::ErrorInternalServerError("GitLab instance URL not found")
-                })?;
-                let platform_config = PlatformConfig::gitlab_custom(instance_url);
-
-                let current_year = Utc::now().year();
-
-                let (start_year, end_year) = if sync_all_years {
-                    log::info!("🔄 [Sync] Mode: ALL YEARS (2020 to {})", current_year);
-                    (2020, current_year)
-                } else if let Some(year) = specific_year {
-                    log::info!("🔄 [Sync] Mode: SPECIFIC YEAR ({})", year);
-                    (year, year)
-                } else {
-                    log::info!("🔄 [Sync] Mode: CURRENT YEAR ({})", current_year);
-                    (current_year, current_year)
-                };
-
-                let mut all_contributions = Vec::new();
-                let mut total_inserted = 0;
-
-                for year in start_year..=end_year {
-                    let from_date = chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
-                    let from = from_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-
-                    let to_date = if year == current_year {
-                        Utc::now().date_naive()
-                    } else {
-                        chrono::NaiveDate::from_ymd_opt(year, 12, 31).unwrap()
-                    };
-                    let to = to_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
-
-                    log::info!(
-                        "🔄 [Sync] Fetching year {}: {} to {}",
-                        year,
-                        from.format("%Y-%m-%d"),
-                        to.format("%Y-%m-%d")
-                    );
-
-                    let contributions = gitlab_client
-                        .fetch_contributions(
-                            &platform_config,
-                            &account.platform_username,
-                            &access_token,
-                            from,
-                            to,
-                        )
-                        .await
-                        .map_err(|e| {
-                            log::error!(
-                                "❌ [Sync] Failed to fetch GitLab contributions for year {}: {}",
-                                year,
-                                e
-                            );
-                            actix_web::error::ErrorInternalServerError(format!(
-                                "Failed to fetch contributions for year {}: {}",
-                                year, e
-                            ))
-                        })?;
-
-                    log::info!(
-                        "✅ [Sync] Fetched {} contribution days for year {}",
-                        contributions.len(),
-                        year
-                    );
-                    all_contributions.extend(contributions);
-                }
-
-                log::info!(
-                    "✅ [Sync] Total fetched: {} contribution days across all years",
-                    all_contributions.len()
-                );
-
-                // Delete existing contributions in the synced date range
-                let delete_from = chrono::NaiveDate::from_ymd_opt(start_year, 1, 1).unwrap();
-                let delete_to = if end_year == current_year {
-                    Utc::now().date_naive()
-                } else {
-                    chrono::NaiveDate::from_ymd_opt(end_year, 12, 31).unwrap()
-                };
-
-                log::info!(
-                    "🗑️  [Sync] Deleting existing contributions from {} to {}",
-                    delete_from,
-                    delete_to
-                );
-
-                let deleted = contribution::Entity::delete_many()
-                    .filter(contribution::Column::GitPlatformAccountId.eq(account_id))
-                    .filter(contribution::Column::ContributionDate.gte(delete_from))
-                    .filter(contribution::Column::ContributionDate.lte(delete_to))
-                    .exec(db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        log::error!("Failed to delete existing contributions: {}", e);
-                        actix_web::error::ErrorInternalServerError(
-                            "Failed to delete existing contributions",
-                        )
-                    })?;
-
-                log::info!(
-                    "🗑️  [Sync] Deleted {} existing contributions in date range",
-                    deleted.rows_affected
-                );
-
-                // Insert all fresh contributions
-                for contrib in all_contributions {
-                    let new_contrib = contribution::ActiveModel {
-                        id: Set(Uuid::new_v4()),
-                        git_platform_account_id: Set(account_id),
-                        contribution_date: Set(contrib.date),
-                        count: Set(contrib.count),
-                        repository_name: Set(contrib.repository_name),
-                        is_private_repo: Set(contrib.is_private),
-                        created_at: Set(Utc::now()),
-                        updated_at: Set(Utc::now()),
-                    };
-
-                    contribution::Entity::insert(new_contrib)
-                        .exec(db.as_ref())
-                        .await
-                        .map_err(|e| {
-                            log::error!("Failed to insert contribution: {}", e);
-                            actix_web::error::ErrorInternalServerError(
-                                "Failed to insert contribution",
-                            )
-                        })?;
-                    total_inserted += 1;
-                }
-
-                log::info!(
-                    "💾 [Sync] Stored contributions: {} inserted",
-                    total_inserted
-                );
-
-                // Sync profile data if enabled (synced only once, regardless of date range)
-                let account_for_profile = git_platform_account::Entity::find_by_id(account_id)
-                    .one(db.as_ref())
-                    .await
-                    .map_err(|e| {
-                        log::error!("Database error: {}", e);
-                        actix_web::error::ErrorInternalServerError("Database error")
-                    })?
-                    .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
-
-                if account_for_profile.sync_profile {
-                    log::info!("👤 [Sync] Syncing GitLab profile data for {} (one-time, independent of date range)", account.platform_username);
-                    match gitlab_client
-                        .fetch_user_profile(&platform_config, &access_token)
-                        .await
-                    {
-                        Ok(profile_data) => {
-                            log::info!("✅ [Sync] Fetched GitLab profile data successfully");
-
-                            let avatar_url = profile_data
-                                .get("avatar_url")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let display_name = profile_data
-                                .get("name")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let bio = profile_data
-                                .get("bio")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let profile_url = profile_data
-                                .get("web_url")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let location = profile_data
-                                .get("location")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            let company = profile_data
-                                .get("organization")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-
-                            // Note: GitLab API doesn't provide followers/following in basic user endpoint
-                            // Would need separate API calls to get this data
-
-                            // Update account with profile data
-                            let account_for_update =
-                                git_platform_account::Entity::find_by_id(account_id)
-                                    .one(db.as_ref())
-                                    .await
-                                    .map_err(|e| {
-                                        log::error!("Database error: {}", e);
-                                        actix_web::error::ErrorInternalServerError("Database error")
-                                    })?
-                                    .ok_or_else(|| {
-                                        actix_web::error::ErrorNotFound("Account not found")
-                                    })?;
-
-                            let mut account_update: git_platform_account::ActiveModel =
-                                account_for_update.into();
-
-                            if let Some(url) = avatar_url {
-                                account_update.avatar_url = Set(Some(url));
-                            }
-                            if let Some(name) = display_name {
-                                account_update.display_name = Set(Some(name));
-                            }
-                            if let Some(b) = bio {
-                                account_update.bio = Set(Some(b));
-                            }
-                            if let Some(url) = profile_url {
-                                account_update.profile_url = Set(Some(url));
-                            }
-                            if let Some(loc) = location {
-                                account_update.location = Set(Some(loc));
-                            }
-                            if let Some(org) = company {
-                                account_update.company = Set(Some(org));
-                            }
-
-                            account_update.updated_at = Set(Utc::now());
-                            account_update.update(db.as_ref()).await.map_err(|e| {
-                                log::error!("Failed to update account: {}", e);
-                                actix_web::error::ErrorInternalServerError(
-                                    "Failed to update account",
-                                )
-                            })?;
-
-                            log::info!("💾 [Sync] Stored GitLab profile data successfully");
-                        }
-                        Err(e) => {
-                            log::warn!("⚠️  [Sync] Failed to fetch GitLab profile data (continuing sync): {}", e);
-                        }
-                    }
-                } else {
-                    log::debug!("[Sync] Profile sync disabled for this account");
-                }
-            }
-        } // End of match account.platform_type
+    let encrypted_token = account
+        .access_token
+        .as_ref()
+        .ok_or_else(|| actix_web::error::ErrorBadRequest("Account has no access token"))?;
+    let token = encryption::decrypt(encrypted_token, &config.encryption_key).map_err(|e| {
+        log::error!("Failed to decrypt access token: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to decrypt access token")
+    })?;
 
-        // Sync activities if contributions are enabled (they always sync together)
-        let account_for_activities = git_platform_account::Entity::find_by_id(account_id)
-            .one(db.as_ref())
-            .await
-            .map_err(|e| {
-                log::error!("Database error: {}", e);
-                actix_web::error::ErrorInternalServerError("Database error")
-            })?
-            .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
-
-        if account_for_activities.sync_contributions {
-            log::info!(
-                "📅 [Sync] Syncing activities for timeline (always syncs with contributions)..."
-            );
-
-            // Use the activity aggregation service
-            use crate::services::activity_aggregation::ActivityAggregationService;
-            let activity_service =
-                ActivityAggregationService::new(db.as_ref().clone(), config.encryption_key.clone());
-
-            // Determine date range from query parameters
-            let current_year = Utc::now().year();
-            let (start_year, end_year) = if sync_all_years {
-                (2020, current_year)
-            } else if let Some(year) = specific_year {
-                (year, year)
-            } else {
-                (current_year, current_year)
-            };
-
-            // Calculate date range for activity sync
-            let from_date = chrono::NaiveDate::from_ymd_opt(start_year, 1, 1).unwrap();
-            let to_date = if end_year == current_year {
-                Utc::now().date_naive()
-            } else {
-                chrono::NaiveDate::from_ymd_opt(end_year, 12, 31).unwrap()
-            };
-            let from = from_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-            let to = to_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
-
-            match activity_service
-                .sync_single_platform_activity(account_id, from, to)
-                .await
-            {
-                Ok(_) => {
-                    log::info!("✅ [Sync] Activities synced successfully");
-                }
-                Err(e) => {
-                    log::warn!("⚠️  [Sync] Failed to sync activities (continuing): {}", e);
-                    // Don't fail the entire sync if activity sync fails
-                }
-            }
-        } else {
-            log::debug!(
-                "[Sync] Contribution sync disabled, skipping activities (always synced together)"
-            );
-        }
-    } // End of if !profile_only
+    let platform_url = account.platform_url.as_deref();
+    let (client, platform_config): (Box<dyn GitPlatform>, PlatformConfig) = match account.platform_type {
+        git_platform_account::GitPlatform::GitHub => (Box::new(GitHubClient::new()), PlatformConfig::github()),
+        git_platform_account::GitPlatform::GitLab => (
+            Box::new(GitLabClient::new()),
+            platform_url.map_or_else(PlatformConfig::gitlab, PlatformConfig::gitlab_custom),
+        ),
+        git_platform_account::GitPlatform::Gitea => (
+            Box::new(GiteaClient::new()),
+            PlatformConfig::gitea_custom(platform_url.unwrap_or("")),
+        ),
+    };
 
-    // Update last_synced_at timestamp
-    let account_for_timestamp = git_platform_account::Entity::find_by_id(account_id)
-        .one(db.as_ref())
+    let capabilities = api_usage::track(
+        db.as_ref(),
+        account.id,
+        client.capabilities(&platform_config, &account.platform_username, &token),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(capabilities))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ApiUsageQuery {
+    /// Hours to cover, the current one included (1-168, default 24)
+    pub hours: Option<i64>,
+}
+
+/// GET /api/platforms/:id/api-usage
+/// Platform API calls made for the account per hour, with the rate limit
+/// the platform reported last
+#[utoipa::path(
+    get,
+    path = "/platforms/{id}/api-usage",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID"), ApiUsageQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "API calls by kind of API and hour", body = ApiUsage),
+        (status = 400, description = "Invalid number of hours"),
+        (status = 404, description = "Platform account not found")
+    )
+)]
+pub async fn get_api_usage(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    query: web::Query<ApiUsageQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let account = find_own_account(db.as_ref(), &user_claims, &path).await?;
+
+    let hours = query.hours.unwrap_or(24);
+    if !(1..=api_usage::RETENTION_HOURS).contains(&hours) {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "hours must be between 1 and {}",
+            api_usage::RETENTION_HOURS
+        )));
+    }
+
+    let usage = api_usage::summary(db.as_ref(), account.id, hours)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
-        })?
-        .ok_or_else(|| actix_web::error::ErrorNotFound("Account not found"))?;
-
-    let mut account: git_platform_account::ActiveModel = account_for_timestamp.into();
-    account.last_synced_at = Set(Some(Utc::now()));
-    account.updated_at = Set(Utc::now());
-
-    account.update(db.as_ref()).await.map_err(|e| {
-        log::error!("Failed to update sync timestamp: {}", e);
-        actix_web::error::ErrorInternalServerError("Failed to update sync status")
-    })?;
-
-    log::info!(
-        "✅ [Sync] Sync completed successfully for account: {}",
-        account_id
-    );
+        })?;
 
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "message": "Contributions synced successfully"
-    })))
+    Ok(HttpResponse::Ok().json(usage))
 }
 
-/// GET /api/users/:username/platforms
-/// Public endpoint to get platform accounts for a user by username
-pub async fn get_user_platforms(
+/// GET /api/platforms/:id/commit-details
+/// How many commits detailed sync stored for the account and roughly how
+/// much space they take
+#[utoipa::path(
+    get,
+    path = "/platforms/{id}/commit-details",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Stored commits", body = CommitStorage),
+        (status = 404, description = "Platform account not found")
+    )
+)]
+pub async fn get_commit_storage(
     db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
 ) -> Result<impl Responder, actix_web::Error> {
-    let username = path.into_inner();
+    let account = find_own_account(db.as_ref(), &user_claims, &path).await?;
 
-    // Find user by username (case-insensitive)
-    let user_model = crate::models::user::Entity::find()
-        .filter(
-            Expr::expr(Func::lower(Expr::col(
-                crate::models::user::Column::Username,
-            )))
-            .eq(username.to_lowercase()),
-        )
-        .one(db.as_ref())
+    let storage = commit_details::storage(db.as_ref(), account.id)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
-    let user_model = match user_model {
-        Some(u) => u,
-        None => {
-            return Ok(HttpResponse::NotFound().json(ErrorResponse {
-                error: "User not found".to_string(),
-            }));
-        }
-    };
+    Ok(HttpResponse::Ok().json(storage))
+}
 
-    let user_id = user_model.id;
+async fn find_label(db: &DatabaseConnection, account_id: Uuid) -> Result<Option<String>, actix_web::Error> {
+    Ok(find_labels(db, &[account_id]).await?.remove(&account_id))
+}
 
-    // Get all active platform accounts
-    let accounts = git_platform_account::Entity::find()
-        .filter(git_platform_account::Column::UserId.eq(user_id))
-        .filter(git_platform_account::Column::IsActive.eq(true))
-        .all(db.as_ref())
+/// Labels of those of `account_ids` that have one
+async fn find_labels(
+    db: &DatabaseConnection,
+    account_ids: &[Uuid],
+) -> Result<std::collections::HashMap<Uuid, String>, actix_web::Error> {
+    let labels = platform_account_label::Entity::find()
+        .filter(platform_account_label::Column::AccountId.is_in(account_ids.iter().copied()))
+        .all(db)
         .await
         .map_err(|e| {
             log::error!("Database error: {}", e);
             actix_web::error::ErrorInternalServerError("Database error")
         })?;
 
-    // Map to response (excluding sensitive data like access tokens)
-    let response: Vec<PlatformAccountResponse> = accounts
-        .into_iter()
-        .map(|account| {
-            let platform_str = match account.platform_type {
-                git_platform_account::GitPlatform::GitHub => "github",
-                git_platform_account::GitPlatform::GitLab => "gitlab",
-                git_platform_account::GitPlatform::Gitea => "gitea",
-            };
-
-            let auth_type_str = match account.auth_type {
-                git_platform_account::AuthType::OAuth => "oauth",
-                git_platform_account::AuthType::PersonalAccessToken => "personal_access_token",
-            };
-
-            PlatformAccountResponse {
-                id: account.id.to_string(),
-                platform: platform_str.to_string(),
-                platform_username: account.platform_username,
-                platform_url: account.platform_url,
-                is_active: account.is_active,
-                last_synced_at: account.last_synced_at.map(|dt| dt.to_rfc3339()),
-                created_at: account.created_at.to_rfc3339(),
-                updated_at: account.updated_at.to_rfc3339(),
-                avatar_url: account.avatar_url,
-                display_name: account.display_name,
-                bio: account.bio,
-                profile_url: account.profile_url,
-                location: account.location,
-                company: account.company,
-                followers_count: account.followers_count,
-                following_count: account.following_count,
-                sync_profile: account.sync_profile,
-                sync_contributions: account.sync_contributions,
-                auth_type: auth_type_str.to_string(),
-            }
-        })
-        .collect();
+    Ok(labels.into_iter().map(|l| (l.account_id, l.label)).collect())
+}
 
-    Ok(HttpResponse::Ok().json(response))
+/// PUT /api/platforms/:id/label
+/// Name a platform account, e.g. to tell two accounts on the same platform
+/// apart
+#[utoipa::path(
+    put,
+    path = "/platforms/{id}/label",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account ID")),
+    request_body = UpdateLabelRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Label updated", body = LabelResponse),
+        (status = 400, description = "Label too long"),
+        (status = 404, description = "Platform account not found")
+    )
+)]
+pub async fn update_label(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    payload: web::Json<UpdateLabelRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let account = find_own_account(db.as_ref(), &user_claims, &path).await?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let label = payload
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+
+    let Some(label) = label else {
+        platform_account_label::Entity::delete_by_id(account.id)
+            .exec(db.as_ref())
+            .await
+            .map_err(db_error)?;
+        return Ok(HttpResponse::Ok().json(LabelResponse { label: None }));
+    };
+
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+            error: format!("label must be at most {} characters", MAX_LABEL_LEN),
+        }));
+    }
+
+    platform_account_label::Entity::insert(platform_account_label::ActiveModel {
+        account_id: Set(account.id),
+        label: Set(label.to_string()),
+        updated_at: Set(chrono::Utc::now()),
+    })
+    .on_conflict(
+        sea_query::OnConflict::column(platform_account_label::Column::AccountId)
+            .update_columns([
+                platform_account_label::Column::Label,
+                platform_account_label::Column::UpdatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec_without_returning(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(LabelResponse {
+        label: Some(label.to_string()),
+    }))
+}
+
+/// POST /api/platforms/:id/merge
+/// Move another account's contributions and activities into this one, e.g.
+/// after renaming the account on the platform, and delete the other account.
+/// History both accounts have is kept once.
+#[utoipa::path(
+    post,
+    path = "/platforms/{id}/merge",
+    tag = "platforms",
+    params(("id" = String, Path, description = "Platform account that keeps the history")),
+    request_body = MergeAccountsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Accounts merged", body = MergeReport),
+        (status = 400, description = "Same account, or accounts on different platforms"),
+        (status = 404, description = "Platform account not found"),
+        (status = 409, description = "One of the accounts is syncing")
+    )
+)]
+pub async fn merge_accounts(
+    db: web::Data<DatabaseConnection>,
+    cache: web::Data<ResponseCache>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    payload: web::Json<MergeAccountsRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let target = find_own_account(db.as_ref(), &user_claims, &path).await?;
+    let source = find_own_account(db.as_ref(), &user_claims, &payload.source_account_id).await?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    if source.id == target.id {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Cannot merge an account into itself".to_string(),
+        }));
+    }
+    if source.platform_type != target.platform_type || source.platform_url != target.platform_url {
+        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Only accounts on the same platform instance can be merged".to_string(),
+        }));
+    }
+
+    // A running sync would write to the source while its rows move
+    let syncing = platform_sync_job::Entity::find()
+        .filter(platform_sync_job::Column::PlatformAccountId.is_in([source.id, target.id]))
+        .filter(platform_sync_job::Column::Status.is_in([
+            platform_sync_job::SyncJobStatus::Pending,
+            platform_sync_job::SyncJobStatus::Processing,
+        ]))
+        .count(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if syncing > 0 {
+        return Ok(HttpResponse::Conflict().json(ErrorResponse {
+            error: "Wait for the accounts to finish syncing".to_string(),
+        }));
+    }
+
+    let report = platform_merge::merge(db.as_ref(), &source, &target)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to merge platform accounts: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to merge accounts")
+        })?;
+    cache.invalidate_user(target.user_id).await;
+
+    log::info!(
+        "🔀 Merged platform account {} ({}) into {} ({}): {:?}",
+        source.id,
+        source.platform_username,
+        target.id,
+        target.platform_username,
+        report
+    );
+
+    Ok(HttpResponse::Ok().json(report))
 }
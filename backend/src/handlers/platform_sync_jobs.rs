@@ -1,24 +1,178 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use chrono::Datelike;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
-    QuerySelect,
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QuerySelect,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema};
 use uuid::Uuid;
 
 use crate::models::{git_platform_account, platform_sync_job};
+use crate::services::instance_settings::{InstanceSettings, InstanceSettingsService, MIN_SYNC_YEAR};
+use crate::services::job_limits::MANUAL_JOB_PRIORITY;
+use crate::services::quotas;
+use crate::services::rate_limiter::RateLimiter;
+use crate::utils::config::Config;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct SyncJobResponse {
     pub job_id: String,
     pub status: String,
     pub message: String,
+    /// Poll this for progress; also sent as the Location header
+    pub status_url: String,
 }
 
-/// POST /api/platforms/:id/sync-async?all_years=true
-/// Create an async sync job for a platform account (new non-blocking endpoint)
-pub async fn sync_platform_async(
+/// What a manual sync covers
+#[derive(Debug, Default)]
+pub struct ManualSync {
+    pub all_years: bool,
+    pub year: Option<i32>,
+    pub profile_only: bool,
+}
+
+impl ManualSync {
+    fn from_query(query: &std::collections::HashMap<String, String>) -> Self {
+        Self {
+            all_years: query.get("all_years").map(|v| v == "true").unwrap_or(false),
+            year: query.get("year").and_then(|v| v.parse::<i32>().ok()),
+            profile_only: query
+                .get("profile_only")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+        }
+    }
+
+    /// Why the instance does not allow this sync, if it does not
+    fn refused(&self, settings: &InstanceSettings) -> Option<String> {
+        if self.all_years && !self.profile_only && !settings.allow_all_years_sync {
+            return Some("Syncing all years is turned off on this instance; sync one year at a time".to_string());
+        }
+        let current_year = chrono::Utc::now().year();
+        match self.year {
+            Some(year) if !(MIN_SYNC_YEAR..=current_year).contains(&year) => {
+                Some(format!("year must be between {} and {}", MIN_SYNC_YEAR, current_year))
+            }
+            _ => None,
+        }
+    }
+
+    fn describe(&self, account: &git_platform_account::Model) -> String {
+        if self.profile_only {
+            "profile".to_string()
+        } else if self.all_years {
+            format!("all years ({}-present)", account.earliest_sync_year)
+        } else if let Some(year) = self.year {
+            format!("year {}", year)
+        } else {
+            "current year".to_string()
+        }
+    }
+}
+
+/// Queue a manual sync of `account` for the sync job processor. Returns the
+/// job, and whether it is new rather than one already pending or running
+/// for the account. Activities are left out when `settings` turn them off.
+pub async fn queue_manual_sync(
+    db: &DatabaseConnection,
+    settings: &InstanceSettings,
+    account: &git_platform_account::Model,
+    sync: &ManualSync,
+) -> Result<(platform_sync_job::Model, bool), DbErr> {
+    let existing_job = platform_sync_job::Entity::find()
+        .filter(platform_sync_job::Column::PlatformAccountId.eq(account.id))
+        .filter(platform_sync_job::Column::Status.is_in([
+            platform_sync_job::SyncJobStatus::Pending,
+            platform_sync_job::SyncJobStatus::Processing,
+        ]))
+        .one(db)
+        .await?;
+
+    if let Some(job) = existing_job {
+        return Ok((job, false));
+    }
+
+    // Activities sync when contributions are enabled
+    let sync_contributions = !sync.profile_only && account.sync_contributions;
+    let sync_profile = sync.profile_only || account.sync_profile;
+
+    let now = chrono::Utc::now();
+    let job = platform_sync_job::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(account.user_id),
+        platform_account_id: Set(account.id),
+        status: Set(platform_sync_job::SyncJobStatus::Pending),
+        sync_all_years: Set(sync.all_years),
+        specific_year: Set(sync.year),
+        sync_contributions: Set(sync_contributions),
+        sync_activities: Set(sync_contributions && settings.sync_activities),
+        sync_profile: Set(sync_profile),
+        scheduled_at: Set(now),
+        started_at: Set(None),
+        completed_at: Set(None),
+        error_message: Set(None),
+        retry_count: Set(0),
+        max_retries: Set(3),
+        contributions_synced: Set(None),
+        activities_synced: Set(None),
+        years_completed: Set(None),
+        total_years: Set(None),
+        is_manual: Set(true),
+        priority: Set(MANUAL_JOB_PRIORITY),
+        created_at: Set(now),
+    };
+
+    Ok((job.insert(db).await?, true))
+}
+
+/// URL of a sync job under the API prefix (/api/v1 or none) of `req`
+pub fn job_status_url(req: &HttpRequest, job_id: Uuid) -> String {
+    let prefix = if req.path().starts_with("/api/v1/") { "/api/v1" } else { "" };
+    format!("{}/platforms/sync-jobs/{}", prefix, job_id)
+}
+
+/// 202 response for a queued (or already queued) job
+pub fn job_accepted(req: &HttpRequest, job: &platform_sync_job::Model, message: String) -> HttpResponse {
+    let status_url = job_status_url(req, job.id);
+    HttpResponse::Accepted()
+        .insert_header((header::LOCATION, status_url.clone()))
+        .json(SyncJobResponse {
+            job_id: job.id.to_string(),
+            status: format!("{:?}", job.status).to_lowercase(),
+            message,
+            status_url,
+        })
+}
+
+/// POST /api/platforms/:id/sync?all_years=true
+/// Queue a sync of a platform account for the background sync processor and
+/// return the job to poll. Also served at the older /sync-async path.
+#[utoipa::path(
+    post,
+    path = "/platforms/{id}/sync",
+    tag = "platforms",
+    params(
+        ("id" = String, Path, description = "Platform account ID"),
+        ("all_years" = Option<bool>, Query, description = "Sync every year since the account's earliest sync year, if the instance allows it"),
+        ("year" = Option<i32>, Query, description = "Sync only this year"),
+        ("profile_only" = Option<bool>, Query, description = "Only refresh the profile")
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Sync job queued; poll the Location header", body = SyncJobResponse),
+        (status = 400, description = "All-years syncs are turned off on this instance, or the year is out of range"),
+        (status = 404, description = "Platform account not found"),
+        (status = 429, description = "Manual sync quota reached")
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_platform(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    limiter: web::Data<RateLimiter>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
     path: web::Path<String>,
     query: web::Query<std::collections::HashMap<String, String>>,
@@ -29,6 +183,16 @@ pub async fn sync_platform_async(
     let account_id = Uuid::parse_str(&path.into_inner())
         .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid account ID: {}", e)))?;
 
+    let settings = instance.current().await;
+    let sync = ManualSync::from_query(&query);
+    if let Some(error) = sync.refused(&settings) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": error })));
+    }
+
+    if let Some(exceeded) = quotas::check_manual_sync(&limiter, &config, user_id).await {
+        return Ok(exceeded.response());
+    }
+
     // Find the account
     let account = git_platform_account::Entity::find_by_id(account_id)
         .one(db.as_ref())
@@ -49,129 +213,54 @@ pub async fn sync_platform_async(
     }
 
     log::info!(
-        "🔄 [Sync] Creating async sync job for account: {}",
-        account_id
-    );
-    log::info!(
-        "🔄 [Sync] Platform: {:?}, Username: {}",
+        "🔄 [Sync] Queueing sync job for account: {} ({:?}, {})",
+        account_id,
         account.platform_type,
         account.platform_username
     );
 
-    // Check if this is profile-only sync
-    let profile_only = query
-        .get("profile_only")
-        .map(|v| v == "true")
-        .unwrap_or(false);
-
-    // Check sync mode: all_years, specific year, or current year (default)
-    let sync_all_years = query.get("all_years").map(|v| v == "true").unwrap_or(false);
-
-    let specific_year = query.get("year").and_then(|v| v.parse::<i32>().ok());
-
-    // Determine what to sync
-    let sync_contributions = if profile_only {
-        false
-    } else {
-        account.sync_contributions
-    };
-
-    let sync_activities = if profile_only {
-        false
-    } else {
-        account.sync_contributions // Activities sync when contributions are enabled
-    };
-
-    let sync_profile = profile_only || account.sync_profile;
-
-    // Check if there's already a pending or processing job for this account
-    let existing_job = platform_sync_job::Entity::find()
-        .filter(platform_sync_job::Column::PlatformAccountId.eq(account_id))
-        .filter(platform_sync_job::Column::Status.is_in([
-            platform_sync_job::SyncJobStatus::Pending,
-            platform_sync_job::SyncJobStatus::Processing,
-        ]))
-        .one(db.as_ref())
+    let (job, created) = queue_manual_sync(db.as_ref(), &settings, &account, &sync)
         .await
         .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
+            log::error!("Failed to create sync job: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to create sync job")
         })?;
 
-    if let Some(job) = existing_job {
+    if !created {
         log::info!("⏳ [Sync] Job already exists for this account: {}", job.id);
-        return Ok(HttpResponse::Accepted().json(SyncJobResponse {
-            job_id: job.id.to_string(),
-            status: format!("{:?}", job.status).to_lowercase(),
-            message:
-                "A sync job is already running for this account. Please wait for it to complete."
-                    .to_string(),
-        }));
+        return Ok(job_accepted(
+            &req,
+            &job,
+            "A sync job is already running for this account. Please wait for it to complete."
+                .to_string(),
+        ));
     }
 
-    // Create a new sync job
-    let job_id = Uuid::new_v4();
-    let job = platform_sync_job::ActiveModel {
-        id: Set(job_id),
-        user_id: Set(user_id),
-        platform_account_id: Set(account_id),
-        status: Set(platform_sync_job::SyncJobStatus::Pending),
-        sync_all_years: Set(sync_all_years),
-        specific_year: Set(specific_year),
-        sync_contributions: Set(sync_contributions),
-        sync_activities: Set(sync_activities),
-        sync_profile: Set(sync_profile),
-        scheduled_at: Set(chrono::Utc::now()),
-        started_at: Set(None),
-        completed_at: Set(None),
-        error_message: Set(None),
-        retry_count: Set(0),
-        max_retries: Set(3),
-        contributions_synced: Set(None),
-        activities_synced: Set(None),
-        years_completed: Set(None),
-        total_years: Set(None),
-        is_manual: Set(true),
-        priority: Set(10), // Higher priority for manual triggers
-        created_at: Set(chrono::Utc::now()),
-    };
-
-    platform_sync_job::Entity::insert(job)
-        .exec(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Failed to create sync job: {}", e);
-            actix_web::error::ErrorInternalServerError("Failed to create sync job")
-        })?;
-
-    log::info!("✅ [Sync] Created async sync job: {}", job_id);
-
-    let sync_type = if profile_only {
-        "profile"
-    } else if sync_all_years {
-        "all years (2020-present)"
-    } else if let Some(year) = specific_year {
-        return Ok(HttpResponse::Accepted().json(SyncJobResponse {
-            job_id: job_id.to_string(),
-            status: "pending".to_string(),
-            message: format!(
-                "Sync job created for year {}. The sync will run in the background.",
-                year
-            ),
-        }));
-    } else {
-        "current year"
-    };
+    log::info!("✅ [Sync] Created sync job: {}", job.id);
 
-    Ok(HttpResponse::Accepted().json(SyncJobResponse {
-        job_id: job_id.to_string(),
-        status: "pending".to_string(),
-        message: format!("Sync job created for {}. The sync will run in the background and may take several minutes.", sync_type),
-    }))
+    Ok(job_accepted(
+        &req,
+        &job,
+        format!(
+            "Sync job created for {}. The sync will run in the background and may take several minutes.",
+            sync.describe(&account)
+        ),
+    ))
 }
 
 /// GET /api/platforms/sync-jobs/:job_id
 /// Get the status of a sync job
+#[utoipa::path(
+    get,
+    path = "/platforms/sync-jobs/{job_id}",
+    tag = "platforms",
+    params(("job_id" = String, Path, description = "Sync job ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Sync job progress", body = Object),
+        (status = 404, description = "Job not found")
+    )
+)]
 pub async fn get_sync_job_status(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -208,6 +297,8 @@ pub async fn get_sync_job_status(
         retry_count: i32,
         contributions_synced: Option<i32>,
         activities_synced: Option<i32>,
+        years_completed: Option<i32>,
+        total_years: Option<i32>,
     }
 
     Ok(HttpResponse::Ok().json(JobStatusResponse {
@@ -220,11 +311,24 @@ pub async fn get_sync_job_status(
         retry_count: job.retry_count,
         contributions_synced: job.contributions_synced,
         activities_synced: job.activities_synced,
+        years_completed: job.years_completed,
+        total_years: job.total_years,
     }))
 }
 
 /// GET /api/platforms/sync-jobs?status=pending&limit=50
 /// List sync jobs for the current user
+#[utoipa::path(
+    get,
+    path = "/platforms/sync-jobs",
+    tag = "platforms",
+    params(
+        ("status" = Option<String>, Query, description = "pending, processing, completed or failed"),
+        ("limit" = Option<u64>, Query, description = "Maximum number of jobs (max 100)")
+    ),
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Sync jobs, newest first", body = [Object]))
+)]
 pub async fn list_sync_jobs(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -316,6 +420,17 @@ pub async fn list_sync_jobs(
 
 /// DELETE /api/platforms/sync-jobs/:id
 /// Cancel a pending or processing sync job
+#[utoipa::path(
+    delete,
+    path = "/platforms/sync-jobs/{job_id}",
+    tag = "platforms",
+    params(("job_id" = String, Path, description = "Sync job ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Job cancelled"),
+        (status = 404, description = "Job not found")
+    )
+)]
 pub async fn cancel_sync_job(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -371,6 +486,17 @@ pub async fn cancel_sync_job(
 
 /// DELETE /api/platforms/sync-jobs/:id/delete
 /// Delete a completed or failed sync job
+#[utoipa::path(
+    delete,
+    path = "/platforms/sync-jobs/{job_id}/delete",
+    tag = "platforms",
+    params(("job_id" = String, Path, description = "Sync job ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Job deleted"),
+        (status = 404, description = "Job not found")
+    )
+)]
 pub async fn delete_sync_job(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
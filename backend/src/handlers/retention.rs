@@ -0,0 +1,76 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::handlers::oauth_apps::require_admin;
+use crate::services::retention::{self, RetentionReport};
+use crate::utils::config::Config;
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// GET /api/admin/retention
+/// Dry run of activity pruning: what the next run would delete (admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/retention",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Activities that would be deleted, per user", body = RetentionReport),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn preview_retention(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let report = retention::prune(db.as_ref(), &config, true)
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// POST /api/admin/retention/run
+/// Prune expired activities now instead of waiting for the daily run (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/retention/run",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Activities deleted, per user", body = RetentionReport),
+        (status = 403, description = "Admin access required")
+    )
+)]
+pub async fn run_retention(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    require_admin(db.as_ref(), user_id).await?;
+
+    let report = retention::prune(db.as_ref(), &config, false)
+        .await
+        .map_err(db_error)?;
+
+    log::info!(
+        "🧹 [Admin] Retention run by {} pruned {} activities",
+        user_id,
+        report.total_activities
+    );
+
+    Ok(HttpResponse::Ok().json(report))
+}
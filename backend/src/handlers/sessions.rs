@@ -0,0 +1,106 @@
+use actix_web::{web, HttpResponse, Responder};
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::session;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub expires_at: String,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+/// GET /api/settings/sessions
+/// List the user's active login sessions
+#[utoipa::path(
+    get,
+    path = "/settings/sessions",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Active sessions, most recently used first", body = [SessionResponse]))
+)]
+pub async fn list_sessions(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let sessions = session::Entity::find()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::ExpiresAt.gt(chrono::Utc::now()))
+        .order_by_desc(session::Column::LastUsedAt)
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let current = user_claims.sid.as_deref();
+    let response: Vec<SessionResponse> = sessions
+        .into_iter()
+        .map(|s| SessionResponse {
+            current: current == Some(s.id.to_string().as_str()),
+            id: s.id.to_string(),
+            user_agent: s.user_agent,
+            ip_address: s.ip_address,
+            created_at: s.created_at.to_rfc3339(),
+            last_used_at: s.last_used_at.to_rfc3339(),
+            expires_at: s.expires_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// DELETE /api/settings/sessions/{id}
+/// Sign a session out; its access and refresh tokens stop working immediately
+#[utoipa::path(
+    delete,
+    path = "/settings/sessions/{id}",
+    tag = "settings",
+    params(("id" = Uuid, Path, description = "Session ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "Session not found")
+    )
+)]
+pub async fn revoke_session(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+    let session_id = path.into_inner();
+
+    let result = session::Entity::delete_many()
+        .filter(session::Column::Id.eq(session_id))
+        .filter(session::Column::UserId.eq(user_id))
+        .exec(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    if result.rows_affected == 0 {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Session not found"
+        })));
+    }
+
+    log::info!("🗑️  Revoked session {}", session_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
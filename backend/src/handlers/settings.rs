@@ -1,25 +1,129 @@
 use actix_web::{web, HttpResponse, Responder};
-use sea_orm::*;
+use sea_orm::{*, sea_query::Expr};
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema};
 use uuid::Uuid;
 
-use crate::models::user_setting;
+use crate::models::{email_digest, generated_heatmap, user, user_setting};
+use crate::models::user_setting::AwayPeriod;
+use crate::services::email_digest::{self as digests, MONTHLY, WEEKLY};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::mailer::Mailer;
+use crate::services::profile_visibility;
+use crate::services::quotas::{self, Usage};
+use crate::services::retention::{self, UserRetention};
+use crate::utils::config::Config;
+use crate::utils::{away_periods, embed_domains, embed_token, repo_exclusion, validators};
 
-#[derive(Debug, Serialize)]
+/// Longest an embed token can stay valid
+const MAX_EMBED_TOKEN_DAYS: i64 = 365;
+
+/// Bounds of a user's activity retention
+const MIN_RETENTION_DAYS: i32 = 30;
+const MAX_RETENTION_DAYS: i32 = 36500;
+
+/// Longest milestone webhook URL, as stored
+const MAX_WEBHOOK_URL_LENGTH: usize = 2048;
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserSettingsResponse {
     pub show_private_contributions: bool,
     pub hide_private_repo_names: bool,
+    /// `public`, `unlisted` (reachable by link, not indexed) or `private`
+    /// (only visible to you)
+    pub profile_visibility: String,
+    /// Repositories hidden from the public profile, e.g. `acme/*`
+    pub excluded_repositories: Vec<String>,
+    /// Sites allowed to embed your heatmap images; empty allows any site
+    pub embed_allowed_domains: Vec<String>,
+    /// Days of activities to keep; null follows the instance policy
+    pub activity_retention_days: Option<i32>,
+    /// Listed in the public user directory while the profile is public
+    pub listed_in_directory: bool,
+    /// Date ranges you were away; they do not break a streak
+    pub away_periods: Vec<AwayPeriod>,
+    /// Heatmaps draw away days without contributions hatched
+    pub hatch_away_days: bool,
+    /// Receives a POST for each milestone reached
+    pub milestone_webhook_url: Option<String>,
     pub updated_at: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateEmbedTokenRequest {
+    /// Days until the token expires; defaults to 30
+    #[serde(default = "default_embed_token_days")]
+    pub expires_in_days: i64,
+}
+
+fn default_embed_token_days() -> i64 {
+    30
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmbedTokenResponse {
+    /// Append as `?token=...` to `/embed` and `/static/heatmaps` URLs
+    pub token: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DigestSettingsResponse {
+    /// `off`, `weekly` or `monthly`
+    pub frequency: String,
+    /// When the next digest goes out; null while off
+    pub next_digest_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateDigestRequest {
+    /// `off`, `weekly` or `monthly`
+    pub frequency: String,
+}
+
+impl From<Option<email_digest::Model>> for DigestSettingsResponse {
+    fn from(digest: Option<email_digest::Model>) -> Self {
+        match digest {
+            Some(digest) => Self {
+                next_digest_at: Some(digests::next_digest_at(&digest).to_rfc3339()),
+                frequency: digest.frequency,
+            },
+            None => Self {
+                frequency: "off".to_string(),
+                next_digest_at: None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateSettingsRequest {
     pub show_private_contributions: Option<bool>,
     pub hide_private_repo_names: Option<bool>,
+    pub profile_visibility: Option<String>,
+    /// Replaces the exclusion list; `*` matches any characters
+    pub excluded_repositories: Option<Vec<String>>,
+    /// Replaces the embed allowlist; subdomains of a listed domain are allowed
+    pub embed_allowed_domains: Option<Vec<String>>,
+    /// Days of activities to keep; 0 follows the instance policy again
+    pub activity_retention_days: Option<i32>,
+    pub listed_in_directory: Option<bool>,
+    /// Replaces the away periods; overlapping ones are merged
+    pub away_periods: Option<Vec<AwayPeriod>>,
+    pub hatch_away_days: Option<bool>,
+    /// http(s) URL to POST milestones to; an empty string removes it
+    pub milestone_webhook_url: Option<String>,
 }
 
 /// GET /api/settings
 /// Get user settings
+#[utoipa::path(
+    get,
+    path = "/settings",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Privacy settings", body = UserSettingsResponse))
+)]
 pub async fn get_settings(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -41,6 +145,14 @@ pub async fn get_settings(
         Some(settings) => Ok(HttpResponse::Ok().json(UserSettingsResponse {
             show_private_contributions: settings.show_private_contributions,
             hide_private_repo_names: settings.hide_private_repo_names,
+            profile_visibility: settings.profile_visibility,
+            excluded_repositories: settings.excluded_repositories.into(),
+            embed_allowed_domains: settings.embed_allowed_domains.into(),
+            activity_retention_days: settings.activity_retention_days,
+            listed_in_directory: settings.listed_in_directory,
+            away_periods: settings.away_periods.into(),
+            hatch_away_days: settings.hatch_away_days,
+            milestone_webhook_url: settings.milestone_webhook_url,
             updated_at: settings.updated_at.to_rfc3339(),
         })),
         None => {
@@ -48,6 +160,14 @@ pub async fn get_settings(
             Ok(HttpResponse::Ok().json(UserSettingsResponse {
                 show_private_contributions: true,
                 hide_private_repo_names: false,
+                profile_visibility: profile_visibility::PUBLIC.to_string(),
+                excluded_repositories: Vec::new(),
+                embed_allowed_domains: Vec::new(),
+                activity_retention_days: None,
+                listed_in_directory: false,
+                away_periods: Vec::new(),
+                hatch_away_days: false,
+                milestone_webhook_url: None,
                 updated_at: chrono::Utc::now().to_rfc3339(),
             }))
         }
@@ -56,6 +176,17 @@ pub async fn get_settings(
 
 /// PUT /api/settings
 /// Update user settings
+#[utoipa::path(
+    put,
+    path = "/settings",
+    tag = "settings",
+    request_body = UpdateSettingsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated settings", body = UserSettingsResponse),
+        (status = 400, description = "Invalid profile visibility, repository pattern, domain, retention, away period or webhook URL")
+    )
+)]
 pub async fn update_settings(
     db: web::Data<DatabaseConnection>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
@@ -65,6 +196,75 @@ pub async fn update_settings(
         actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
     })?;
 
+    let profile_visibility = payload.profile_visibility.as_deref().map(str::to_lowercase);
+    let profile_visibility = match profile_visibility.as_deref() {
+        Some(v) => match profile_visibility::VALUES.iter().find(|&&known| known == v) {
+            Some(&known) => Some(known),
+            None => {
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": format!("profile_visibility must be one of: {}", profile_visibility::VALUES.join(", "))
+                })));
+            }
+        },
+        None => None,
+    };
+
+    let excluded_repositories = match payload.excluded_repositories.as_deref().map(repo_exclusion::normalize) {
+        Some(Ok(patterns)) => Some(patterns),
+        Some(Err(e)) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+        None => None,
+    };
+
+    let embed_allowed_domains = match payload.embed_allowed_domains.as_deref().map(embed_domains::normalize) {
+        Some(Ok(domains)) => Some(domains),
+        Some(Err(e)) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+        None => None,
+    };
+
+    let activity_retention_days = match payload.activity_retention_days {
+        Some(0) => Some(None),
+        Some(days) if !(MIN_RETENTION_DAYS..=MAX_RETENTION_DAYS).contains(&days) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!(
+                    "activity_retention_days must be 0 or between {} and {}",
+                    MIN_RETENTION_DAYS, MAX_RETENTION_DAYS
+                )
+            })));
+        }
+        Some(days) => Some(Some(days)),
+        None => None,
+    };
+
+    let away_periods = match payload.away_periods.as_deref().map(away_periods::normalize) {
+        Some(Ok(periods)) => Some(periods),
+        Some(Err(e)) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e })));
+        }
+        None => None,
+    };
+
+    let milestone_webhook_url = match payload.milestone_webhook_url.as_deref().map(str::trim) {
+        Some("") => Some(None),
+        Some(url) if url.len() > MAX_WEBHOOK_URL_LENGTH => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("milestone_webhook_url must be at most {} characters", MAX_WEBHOOK_URL_LENGTH)
+            })));
+        }
+        Some(url) => match validators::validate_url(url) {
+            Ok(_) => Some(Some(url.to_string())),
+            Err(e) => {
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": format!("milestone_webhook_url: {}", e)
+                })));
+            }
+        },
+        None => None,
+    };
+
     // Check if settings exist
     let existing_settings = user_setting::Entity::find()
         .filter(user_setting::Column::UserId.eq(user_id))
@@ -87,6 +287,38 @@ pub async fn update_settings(
             settings.hide_private_repo_names = Set(hide_private_repo_names);
         }
 
+        if let Some(profile_visibility) = profile_visibility {
+            settings.profile_visibility = Set(profile_visibility.to_string());
+        }
+
+        if let Some(excluded_repositories) = excluded_repositories {
+            settings.excluded_repositories = Set(excluded_repositories.into());
+        }
+
+        if let Some(embed_allowed_domains) = embed_allowed_domains {
+            settings.embed_allowed_domains = Set(embed_allowed_domains.into());
+        }
+
+        if let Some(activity_retention_days) = activity_retention_days {
+            settings.activity_retention_days = Set(activity_retention_days);
+        }
+
+        if let Some(listed_in_directory) = payload.listed_in_directory {
+            settings.listed_in_directory = Set(listed_in_directory);
+        }
+
+        if let Some(away_periods) = away_periods {
+            settings.away_periods = Set(away_periods.into());
+        }
+
+        if let Some(hatch_away_days) = payload.hatch_away_days {
+            settings.hatch_away_days = Set(hatch_away_days);
+        }
+
+        if let Some(milestone_webhook_url) = milestone_webhook_url {
+            settings.milestone_webhook_url = Set(milestone_webhook_url);
+        }
+
         settings.updated_at = Set(chrono::Utc::now());
 
         settings.update(db.as_ref()).await.map_err(|e| {
@@ -105,6 +337,14 @@ pub async fn update_settings(
             heatmap_color_scheme: Set("github".to_string()),
             heatmap_size: Set("medium".to_string()),
             dark_mode_enabled: Set(false),
+            profile_visibility: Set(profile_visibility.unwrap_or(profile_visibility::PUBLIC).to_string()),
+            excluded_repositories: Set(excluded_repositories.unwrap_or_default().into()),
+            embed_allowed_domains: Set(embed_allowed_domains.unwrap_or_default().into()),
+            activity_retention_days: Set(activity_retention_days.flatten()),
+            listed_in_directory: Set(payload.listed_in_directory.unwrap_or(false)),
+            away_periods: Set(away_periods.unwrap_or_default().into()),
+            hatch_away_days: Set(payload.hatch_away_days.unwrap_or(false)),
+            milestone_webhook_url: Set(milestone_webhook_url.flatten()),
             created_at: Set(chrono::Utc::now()),
             updated_at: Set(chrono::Utc::now()),
         };
@@ -118,9 +358,263 @@ pub async fn update_settings(
             })?
     };
 
+    // Generated heatmaps still show the previously visible repositories,
+    // streaks or hatching
+    if payload.excluded_repositories.is_some()
+        || payload.away_periods.is_some()
+        || payload.hatch_away_days.is_some()
+    {
+        let _ = generated_heatmap::Entity::update_many()
+            .filter(generated_heatmap::Column::UserId.eq(user_id))
+            .col_expr(generated_heatmap::Column::IsValid, Expr::value(false))
+            .exec(db.as_ref())
+            .await;
+    }
+
     Ok(HttpResponse::Ok().json(UserSettingsResponse {
         show_private_contributions: updated_settings.show_private_contributions,
         hide_private_repo_names: updated_settings.hide_private_repo_names,
+        profile_visibility: updated_settings.profile_visibility,
+        excluded_repositories: updated_settings.excluded_repositories.into(),
+        embed_allowed_domains: updated_settings.embed_allowed_domains.into(),
+        activity_retention_days: updated_settings.activity_retention_days,
+        listed_in_directory: updated_settings.listed_in_directory,
+        away_periods: updated_settings.away_periods.into(),
+        hatch_away_days: updated_settings.hatch_away_days,
+        milestone_webhook_url: updated_settings.milestone_webhook_url,
         updated_at: updated_settings.updated_at.to_rfc3339(),
     }))
 }
+
+/// POST /api/settings/embed-token
+/// Create a signed, expiring token for embedding your heatmaps even while
+/// the profile is private
+#[utoipa::path(
+    post,
+    path = "/settings/embed-token",
+    tag = "settings",
+    request_body = CreateEmbedTokenRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Embed token created", body = EmbedTokenResponse),
+        (status = 400, description = "Invalid lifetime")
+    )
+)]
+pub async fn create_embed_token(
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    payload: web::Json<CreateEmbedTokenRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    if !(1..=MAX_EMBED_TOKEN_DAYS).contains(&payload.expires_in_days) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("expires_in_days must be between 1 and {}", MAX_EMBED_TOKEN_DAYS)
+        })));
+    }
+
+    let (token, expires_at) = embed_token::create(
+        user_id,
+        &config.jwt_secret,
+        chrono::Duration::days(payload.expires_in_days),
+    )
+    .map_err(|e| {
+        log::error!("❌ Failed to sign embed token: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to create embed token")
+    })?;
+
+    log::info!("🔗 Embed token created for user {} (expires {})", user_id, expires_at.format("%Y-%m-%d"));
+
+    Ok(HttpResponse::Created().json(EmbedTokenResponse {
+        token,
+        expires_at: expires_at.to_rfc3339(),
+    }))
+}
+
+/// GET /api/settings/retention
+/// Dry run of the activity retention that applies to you: how many of your
+/// activities the next pruning run would delete
+#[utoipa::path(
+    get,
+    path = "/settings/retention",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Effective retention and expired activities", body = UserRetention))
+)]
+pub async fn get_retention(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let user = user::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    let preview = retention::preview_user(db.as_ref(), &config, &user)
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(preview))
+}
+
+/// GET /api/settings/usage
+/// Your generated heatmap storage, themes and platform accounts, with the
+/// instance's limits
+#[utoipa::path(
+    get,
+    path = "/settings/usage",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Usage and limits", body = Usage))
+)]
+pub async fn get_usage(
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    let usage = quotas::usage(db.as_ref(), &config, &instance, user_id)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(usage))
+}
+
+/// GET /api/settings/digest
+/// Get your email digest subscription
+#[utoipa::path(
+    get,
+    path = "/settings/digest",
+    tag = "settings",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Digest subscription", body = DigestSettingsResponse))
+)]
+pub async fn get_digest(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    let digest = email_digest::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(DigestSettingsResponse::from(digest)))
+}
+
+/// PUT /api/settings/digest
+/// Subscribe to a weekly or monthly summary email with your heatmap attached,
+/// or turn it off. The first digest goes out one period after subscribing.
+#[utoipa::path(
+    put,
+    path = "/settings/digest",
+    tag = "settings",
+    request_body = UpdateDigestRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated digest subscription", body = DigestSettingsResponse),
+        (status = 400, description = "Unknown frequency, or the account has no email address"),
+        (status = 503, description = "Email is not configured on this instance")
+    )
+)]
+pub async fn update_digest(
+    db: web::Data<DatabaseConnection>,
+    mailer: web::Data<Mailer>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    payload: web::Json<UpdateDigestRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
+    })?;
+
+    let db_error = |e: DbErr| {
+        log::error!("Database error: {}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let frequency = payload.frequency.to_lowercase();
+    if frequency == "off" {
+        email_digest::Entity::delete_by_id(user_id)
+            .exec(db.as_ref())
+            .await
+            .map_err(db_error)?;
+        return Ok(HttpResponse::Ok().json(DigestSettingsResponse::from(None)));
+    }
+
+    if frequency != WEEKLY && frequency != MONTHLY {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "frequency must be one of: off, weekly, monthly"
+        })));
+    }
+
+    if !mailer.is_enabled() {
+        return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "error": "Email is not configured on this instance"
+        })));
+    }
+
+    let user = user::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    if user.email.is_none() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Add an email address to your account first"
+        })));
+    }
+
+    let existing = email_digest::Entity::find_by_id(user_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    // Changing the frequency keeps the period that is already running
+    let digest = match existing {
+        Some(digest) => {
+            let mut digest: email_digest::ActiveModel = digest.into();
+            digest.frequency = Set(frequency);
+            digest.update(db.as_ref()).await.map_err(db_error)?
+        }
+        None => email_digest::ActiveModel {
+            user_id: Set(user_id),
+            frequency: Set(frequency),
+            last_sent_at: Set(chrono::Utc::now()),
+            created_at: Set(chrono::Utc::now()),
+        }
+        .insert(db.as_ref())
+        .await
+        .map_err(db_error)?,
+    };
+
+    log::info!("📬 User {} subscribed to a {} digest", user.username, digest.frequency);
+
+    Ok(HttpResponse::Ok().json(DigestSettingsResponse::from(Some(digest))))
+}
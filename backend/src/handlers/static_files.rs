@@ -1,23 +1,298 @@
-use actix_web::{web, Responder};
+use actix_web::http::header;
+use actix_web::{web, CustomizeResponder, Either, HttpRequest, HttpResponse, Responder};
 use actix_files::NamedFile;
 use sea_orm::*;
-use sea_orm::sea_query::{Expr, Func};
+use sea_orm::sea_query::Expr;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use crate::models::{generated_heatmap, heatmap_generation_setting, user};
-use crate::services::heatmap_generator::HeatmapGenerator;
+use crate::models::{
+    generated_heatmap, git_platform_account, heatmap_generation_setting, heatmap_share_link, heatmap_theme, user,
+    user_setting,
+};
+use crate::services::heatmap_analytics;
+use crate::services::heatmap_renderers;
+use crate::services::heatmap_generator::{
+    self, ColorPalette, HeatmapData, HeatmapGenerator, HEATMAP_BASE_DIR, MINI_SUFFIX,
+};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::platform_visibility;
+use crate::services::profile_visibility;
+use crate::services::render_lanes::Lane;
+use crate::services::response_cache::ResponseCache;
+use crate::services::text_heatmap;
+use crate::services::username_redirects;
+use crate::utils::config::Config;
+use crate::utils::{access_source, embed_domains, embed_token, share_token};
+
+#[derive(Debug, Deserialize)]
+struct EmbedQuery {
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TextHeatmapQuery {
+    /// Theme whose colors are used; defaults to the user's default theme
+    pub theme: Option<String>,
+    /// `ansi` for colored cells or `none` for block characters; defaults to
+    /// `ansi` for curl, Wget and HTTPie and `none` for everything else
+    pub color: Option<String>,
+}
+
+/// The generated file behind an embed URL, cached to skip the theme and
+/// file lookups
+#[derive(Serialize, Deserialize)]
+struct CachedEmbed {
+    generated_id: Uuid,
+    theme_id: Uuid,
+    file_path: String,
+}
+
+/// The interactive widget served at /embed.js
+const EMBED_SCRIPT: &str = include_str!("../../assets/embed.js");
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmojiHeatmapQuery {
+    /// 2-5 comma-separated symbols, one per level from no contributions up;
+    /// defaults to `⬜,🟩`
+    pub symbols: Option<String>,
+    /// Most recent weeks to show (1-53, default 53)
+    pub weeks: Option<usize>,
+    /// `text` (default) or `json`
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DiffHeatmapQuery {
+    /// Theme whose size, font and background are used; defaults to the
+    /// user's default theme
+    pub theme: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmojiHeatmapResponse {
+    pub username: String,
+    pub weeks: usize,
+    /// Contributions in the shown weeks
+    pub total_count: i32,
+    /// One line per weekday, Sunday first
+    pub rows: Vec<String>,
+    /// The rows joined with newlines, ready to paste
+    pub text: String,
+}
+
+/// Heatmap cells split by the platform and instance their contributions
+/// came from
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HeatmapJson {
+    pub username: String,
+    pub total_count: i32,
+    pub max_count: i32,
+    /// Sunday on or before the first day of the user's date range
+    pub date_range_start: String,
+    pub date_range_end: String,
+    /// Every day from `date_range_start` to `date_range_end`
+    pub days: Vec<HeatmapJsonDay>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HeatmapJsonDay {
+    pub date: String,
+    pub count: i32,
+    /// Intensity from 0 (no contributions) to 4, as in the heatmap images
+    pub level: usize,
+    /// Contributions per source, largest first; their counts add up to
+    /// `count`
+    pub segments: Vec<HeatmapSegment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HeatmapSegment {
+    /// github, gitlab or gitea; null for accounts hidden from the profile
+    pub platform: Option<String>,
+    /// e.g. `https://github.com` or a self-hosted instance; null with
+    /// `platform`
+    pub instance_url: Option<String>,
+    pub count: i32,
+}
+
+/// Contribution days and theme colors the embed.js widget draws from
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EmbedData {
+    pub username: String,
+    pub total_count: i32,
+    /// Sunday on or before the first day of the user's date range
+    pub date_range_start: String,
+    pub date_range_end: String,
+    /// Every day from `date_range_start` to `date_range_end`
+    pub days: Vec<EmbedDay>,
+    /// The user's themes, their default theme first
+    pub themes: Vec<EmbedTheme>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EmbedDay {
+    pub date: String,
+    pub count: i32,
+    /// Intensity from 0 (no contributions) to 4, as in the heatmap images
+    pub level: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EmbedTheme {
+    pub slug: String,
+    pub name: String,
+    pub is_default: bool,
+    pub background_color: String,
+    pub text_color: String,
+    /// Cell color per level, starting with days without contributions
+    pub colors: Vec<String>,
+    pub cell_size: i32,
+    pub cell_gap: i32,
+    pub cell_border_radius: i32,
+    pub font_family: String,
+}
+
+impl From<heatmap_theme::Model> for EmbedTheme {
+    fn from(theme: heatmap_theme::Model) -> Self {
+        let colors = ColorPalette::cell_colors(&theme);
+
+        Self {
+            slug: theme.slug,
+            name: theme.name,
+            is_default: theme.is_default,
+            background_color: theme.background_color,
+            text_color: theme.text_color,
+            colors,
+            cell_size: theme.cell_size,
+            cell_gap: theme.cell_gap,
+            cell_border_radius: theme.cell_border_radius,
+            font_family: theme.font_family,
+        }
+    }
+}
+
+/// Referrer host and kind of client of a view, for the daily analytics
+struct AccessSource {
+    referrer_host: String,
+    client: &'static str,
+}
+
+impl AccessSource {
+    fn of(req: &HttpRequest) -> Self {
+        let header = |name| req.headers().get(name).and_then(|h| h.to_str().ok());
+        Self {
+            referrer_host: access_source::stored_host(header(header::REFERER).and_then(embed_domains::referrer_host)),
+            client: access_source::client_category(header(header::USER_AGENT)),
+        }
+    }
+}
+
+/// 404 unless the request may see the owner's heatmaps, 403 when it is
+/// embedded on a site outside the owner's allowlist; returns the owner's
+/// visibility
+async fn check_access(
+    db: &DatabaseConnection,
+    config: &Config,
+    instance: &InstanceSettingsService,
+    req: &HttpRequest,
+    owner_id: Uuid,
+) -> Result<String, actix_web::Error> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(owner_id))
+        .one(db)
+        .await
+        .map_err(db_error)?;
+    let visibility = profile_visibility::of(settings.as_ref()).to_string();
+
+    // A signed embed token opens a private profile's heatmaps
+    let has_token = web::Query::<EmbedQuery>::from_query(req.query_string())
+        .ok()
+        .and_then(|q| q.into_inner().token)
+        .is_some_and(|token| embed_token::verify(&token, &config.jwt_secret, owner_id));
+    if !has_token
+        && !profile_visibility::can_view(db, config, req, owner_id, &visibility)
+            .await
+            .map_err(db_error)?
+    {
+        return Err(actix_web::error::ErrorNotFound("User not found"));
+    }
+
+    // Requests without a Referer (direct links, strict referrer policies)
+    // are let through; the allowlist only stops other sites hotlinking
+    let allowed_domains = settings.map(|s| s.embed_allowed_domains).unwrap_or_default();
+    let referrer = req
+        .headers()
+        .get(header::REFERER)
+        .and_then(|h| h.to_str().ok())
+        .and_then(embed_domains::referrer_host);
+    if let Some(host) = referrer {
+        let frontend_url = instance.current().await.frontend_url;
+        let own_site = [&frontend_url, &config.base_url]
+            .into_iter()
+            .any(|url| embed_domains::referrer_host(url).as_deref() == Some(host.as_str()));
+        if !allowed_domains.is_empty() && !own_site && !embed_domains::is_allowed(&allowed_domains, &host) {
+            log::warn!("🚫 Embed of user {} blocked on {}", owner_id, host);
+            return Err(actix_web::error::ErrorForbidden("Embedding is not allowed on this site"));
+        }
+    }
+
+    Ok(visibility)
+}
+
+/// Serve a heatmap file, asking search engines to skip unlisted profiles
+fn serve_file(file_path: PathBuf, visibility: &str) -> Result<CustomizeResponder<NamedFile>, actix_web::Error> {
+    let named_file = NamedFile::open(file_path).map_err(|e| {
+        log::error!("Failed to open file: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to serve file")
+    })?;
+
+    let mut response = named_file.customize();
+    if visibility == profile_visibility::UNLISTED {
+        response = response.insert_header(("X-Robots-Tag", "noindex"));
+    }
+    Ok(response)
+}
 
 /// GET /static/heatmaps/:user_id/:filename
 /// Serve generated heatmap files
+#[utoipa::path(
+    get,
+    path = "/static/heatmaps/{user_id}/{filename}",
+    tag = "embed",
+    params(
+        ("user_id" = String, Path, description = "Owner user ID"),
+        ("filename" = String, Path, description = "Generated file, e.g. default.svg"),
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Heatmap image"),
+        (status = 403, description = "Embedding is not allowed on the referring site"),
+        (status = 404, description = "File not found")
+    )
+)]
 pub async fn serve_heatmap(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
     path: web::Path<(String, String)>,
 ) -> Result<impl Responder, actix_web::Error> {
     let (user_id_str, filename) = path.into_inner();
 
+    // Heatmaps are only ever stored under the owner's ID
+    let owner_id = Uuid::parse_str(&user_id_str)
+        .map_err(|_| actix_web::error::ErrorNotFound("Heatmap not found"))?;
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, owner_id).await?;
+
     // Construct file path (using default directory)
-    let base_dir = "static/heatmaps";
+    let base_dir = HEATMAP_BASE_DIR;
 
     let file_path = PathBuf::from(&base_dir)
         .join(&user_id_str)
@@ -28,28 +303,69 @@ pub async fn serve_heatmap(
         return Err(actix_web::error::ErrorNotFound("Heatmap not found"));
     }
 
-    // Update access count in database (optional, async)
-    let db_clone = db.clone();
-    let file_path_str = file_path.to_string_lossy().to_string();
-
-    tokio::spawn(async move {
-        let _ = update_access_count(&db_clone, &file_path_str).await;
-    });
-
-    // Serve the file
-    let named_file = NamedFile::open(file_path).map_err(|e| {
-        log::error!("Failed to open file: {}", e);
-        actix_web::error::ErrorInternalServerError("Failed to serve file")
-    })?;
+    let generated = generated_heatmap::Entity::find()
+        .filter(generated_heatmap::Column::FilePath.eq(file_path.to_string_lossy()))
+        .one(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+    let source = AccessSource::of(&req);
 
-    Ok(named_file)
+    match generated {
+        // A rarely used format left out of its theme's last regeneration
+        Some(generated) if !generated.is_valid => {
+            let theme = heatmap_theme::Entity::find_by_id(generated.theme_id)
+                .one(db.as_ref())
+                .await
+                .map_err(|e| {
+                    log::error!("Database error: {}", e);
+                    actix_web::error::ErrorInternalServerError("Database error")
+                })?
+                .ok_or_else(|| actix_web::error::ErrorNotFound("Heatmap not found"))?;
+            log::info!("Heatmap outdated for user {}, theme {}, regenerating", owner_id, theme.slug);
+            let path = generate_heatmap_on_demand(db.as_ref(), &owner_id, &theme, &generated.format).await?;
+            record_view(db.as_ref().clone(), owner_id, theme.id, source);
+            serve_file(path, &visibility)
+        }
+        Some(generated) => {
+            // Update access count in database (optional, async)
+            let db_clone = db.clone();
+            tokio::spawn(async move {
+                let _ = increment_access_count(&db_clone, generated.id, generated.user_id, generated.theme_id, source)
+                    .await;
+            });
+            serve_file(file_path, &visibility)
+        }
+        None => serve_file(file_path, &visibility),
+    }
 }
 
 /// GET /embed/:username/:theme_slug.{format}
 /// Public endpoint for embedding heatmaps (e.g., in GitHub README)
 /// Generates the heatmap on-demand if it doesn't exist yet
+#[utoipa::path(
+    get,
+    path = "/embed/{username}/{theme_file}",
+    tag = "embed",
+    params(
+        ("username" = String, Path, description = "Username (case-insensitive)"),
+        ("theme_file" = String, Path, description = "Theme slug with format extension, e.g. default.svg"),
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Heatmap image"),
+        (status = 403, description = "Embedding is not allowed on the referring site"),
+        (status = 404, description = "User or theme not found")
+    )
+)]
 pub async fn serve_embed(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    cache: web::Data<ResponseCache>,
     path: web::Path<(String, String)>,
 ) -> Result<impl Responder, actix_web::Error> {
     let (username, theme_file) = path.into_inner();
@@ -63,38 +379,48 @@ pub async fn serve_embed(
     let format_str = parts[0];
     let theme_slug = parts[1];
 
-    // Find user by username (case-insensitive)
-    let user = crate::models::user::Entity::find()
-        .filter(Expr::expr(Func::lower(Expr::col(crate::models::user::Column::Username))).eq(username.to_lowercase()))
-        .one(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?
-        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+    let user = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(Either::Left(response)),
+    };
 
-    // Find theme
-    let theme = crate::models::heatmap_theme::Entity::find()
-        .filter(crate::models::heatmap_theme::Column::UserId.eq(user.id))
-        .filter(crate::models::heatmap_theme::Column::Slug.eq(theme_slug))
-        .one(db.as_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?
-        .ok_or_else(|| actix_web::error::ErrorNotFound("Theme not found"))?;
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, user.id).await?;
+
+    let cache_key = format!("embed|{}", theme_file);
+    if let Some(cached) = cache.get::<CachedEmbed>(user.id, &cache_key).await {
+        let path = PathBuf::from(cached.file_path);
+        if path.exists() {
+            let db_clone = db.clone();
+            let source = AccessSource::of(&req);
+            tokio::spawn(async move {
+                let _ = increment_access_count(&db_clone, cached.generated_id, user.id, cached.theme_id, source).await;
+            });
+            return serve_file(path, &visibility).map(Either::Right);
+        }
+    }
 
-    // Parse format
-    let format = match format_str {
-        "svg" => crate::models::heatmap_theme::HeatmapFormat::Svg,
-        "png" => crate::models::heatmap_theme::HeatmapFormat::Png,
-        "jpg" | "jpeg" => crate::models::heatmap_theme::HeatmapFormat::Jpeg,
-        "webp" => crate::models::heatmap_theme::HeatmapFormat::WebP,
-        _ => return Err(actix_web::error::ErrorBadRequest("Unsupported format")),
+    // Find theme. `{slug}-mini` is the theme's mini heatmap, unless a theme
+    // has that slug itself.
+    let theme = match find_theme(db.as_ref(), user.id, theme_slug).await.map_err(db_error)? {
+        Some(theme) => theme,
+        None => {
+            let mini_theme = match theme_slug.strip_suffix(MINI_SUFFIX) {
+                Some(slug) => find_theme(db.as_ref(), user.id, slug)
+                    .await
+                    .map_err(db_error)?
+                    .filter(|theme| theme.mini_days.is_some()),
+                None => None,
+            };
+            let theme = mini_theme.ok_or_else(|| actix_web::error::ErrorNotFound("Theme not found"))?;
+            let format = parse_format(format_str)?;
+            return serve_mini(db.as_ref(), &req, user.id, &theme, &format, &visibility)
+                .await
+                .map(Either::Right);
+        }
     };
 
+    let format = parse_format(format_str)?;
+
     // Try to find existing valid generated heatmap
     let existing_generated = generated_heatmap::Entity::find()
         .filter(generated_heatmap::Column::UserId.eq(user.id))
@@ -117,33 +443,640 @@ pub async fn serve_embed(
             // Update access count
             let db_clone = db.clone();
             let generated_id = generated.id;
+            let (user_id, theme_id) = (user.id, theme.id);
+            let source = AccessSource::of(&req);
             tokio::spawn(async move {
-                let _ = increment_access_count(&db_clone, generated_id).await;
+                let _ = increment_access_count(&db_clone, generated_id, user_id, theme_id, source).await;
             });
 
+            cache
+                .insert(
+                    user.id,
+                    &cache_key,
+                    &CachedEmbed {
+                        generated_id,
+                        theme_id: theme.id,
+                        file_path: generated.file_path,
+                    },
+                )
+                .await;
             path
         } else {
             // File missing, regenerate
             log::warn!("Heatmap file missing for user {}, theme {}, regenerating", user.id, theme.id);
-            generate_heatmap_on_demand(db.as_ref(), &user.id, &theme, &format).await?
+            let path = generate_heatmap_on_demand(db.as_ref(), &user.id, &theme, &format).await?;
+            record_view(db.as_ref().clone(), user.id, theme.id, AccessSource::of(&req));
+            path
         }
     } else {
         // No generated heatmap found, generate on-demand
         log::info!("Heatmap not found for user {}, theme {} ({}), generating on-demand", user.id, theme.slug, format_str);
-        generate_heatmap_on_demand(db.as_ref(), &user.id, &theme, &format).await?
+        let path = generate_heatmap_on_demand(db.as_ref(), &user.id, &theme, &format).await?;
+        record_view(db.as_ref().clone(), user.id, theme.id, AccessSource::of(&req));
+        path
     };
 
     // Serve the file
-    let named_file = NamedFile::open(file_path).map_err(|e| {
-        log::error!("Failed to open file: {}", e);
-        actix_web::error::ErrorInternalServerError("Failed to serve file")
-    })?;
+    serve_file(file_path, &visibility).map(Either::Right)
+}
+
+async fn find_theme(db: &DatabaseConnection, user_id: Uuid, slug: &str) -> Result<Option<heatmap_theme::Model>, DbErr> {
+    heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user_id))
+        .filter(heatmap_theme::Column::Slug.eq(slug))
+        .one(db)
+        .await
+}
 
-    Ok(named_file)
+/// Format of an embed URL's extension
+fn parse_format(extension: &str) -> Result<heatmap_theme::HeatmapFormat, actix_web::Error> {
+    match extension {
+        "svg" => Ok(heatmap_theme::HeatmapFormat::Svg),
+        "png" => Ok(heatmap_theme::HeatmapFormat::Png),
+        "jpg" | "jpeg" => Ok(heatmap_theme::HeatmapFormat::Jpeg),
+        "webp" => Ok(heatmap_theme::HeatmapFormat::WebP),
+        _ => Err(actix_web::error::ErrorBadRequest("Unsupported format")),
+    }
+}
+
+/// Serve a theme's `{slug}-mini.{ext}` file, generating the theme's files in
+/// that format when it is missing. Only the theme's output formats have one.
+async fn serve_mini(
+    db: &DatabaseConnection,
+    req: &HttpRequest,
+    user_id: Uuid,
+    theme: &heatmap_theme::Model,
+    format: &heatmap_theme::HeatmapFormat,
+    visibility: &str,
+) -> Result<CustomizeResponder<NamedFile>, actix_web::Error> {
+    if !theme.output_formats.contains(format) {
+        return Err(actix_web::error::ErrorNotFound("Heatmap not found"));
+    }
+
+    let file_path = PathBuf::from(HEATMAP_BASE_DIR)
+        .join(user_id.to_string())
+        .join(heatmap_generator::file_name(&format!("{}{}", theme.slug, MINI_SUFFIX), format));
+    if !file_path.exists() {
+        log::info!("Mini heatmap not found for user {}, theme {}, generating on-demand", user_id, theme.slug);
+        HeatmapGenerator::new(db.clone())
+            .generate_formats(user_id, theme, std::slice::from_ref(format))
+            .await
+            .map_err(|e| {
+                log::error!("Failed to generate heatmap: {}", e);
+                actix_web::error::ErrorInternalServerError("Failed to generate heatmap")
+            })?;
+    }
+
+    record_view(db.clone(), user_id, theme.id, AccessSource::of(req));
+    serve_file(file_path, visibility)
+}
+
+/// GET /embed.js
+/// Script that renders an interactive heatmap on any site, from a tag like
+/// `<script src=".../embed.js" data-user="alice"></script>`
+#[utoipa::path(
+    get,
+    path = "/embed.js",
+    tag = "embed",
+    responses((status = 200, description = "Widget script", content_type = "application/javascript"))
+)]
+pub async fn serve_embed_script() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/javascript; charset=utf-8")
+        .insert_header((header::CACHE_CONTROL, "public, max-age=3600"))
+        .body(EMBED_SCRIPT)
+}
+
+/// GET /embed/:username/data.json
+/// Contribution days and theme colors for the embed.js widget. Access rules
+/// are those of the image embeds.
+#[utoipa::path(
+    get,
+    path = "/embed/{username}/data.json",
+    tag = "embed",
+    params(
+        ("username" = String, Path, description = "Username (case-insensitive)"),
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Widget data", body = EmbedData),
+        (status = 403, description = "Embedding is not allowed on the referring site"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn serve_embed_data(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    cache: web::Data<ResponseCache>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let username = path.into_inner();
+
+    let user = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, user.id).await?;
+
+    // Checked after access, so only allowed viewers reach the cache
+    let body = match cache.get::<String>(user.id, "embed-data").await {
+        Some(body) => body,
+        None => {
+            let body = embed_data(db.as_ref(), &user).await?;
+            cache.insert(user.id, "embed-data", &body).await;
+            body
+        }
+    };
+
+    let mut response = HttpResponse::Ok()
+        .content_type("application/json")
+        .body(body);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// Serialized [`EmbedData`] for a user
+async fn embed_data(db: &DatabaseConnection, user: &user::Model) -> Result<String, actix_web::Error> {
+    let heatmap_data = contribution_data(db, user.id).await?;
+
+    let themes = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user.id))
+        .order_by_desc(heatmap_theme::Column::IsDefault)
+        .order_by_asc(heatmap_theme::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let days = heatmap_data
+        .weeks
+        .iter()
+        .flatten()
+        // Padding after the last day
+        .filter(|day| day.count >= 0)
+        .map(|day| EmbedDay {
+            date: day.date.to_string(),
+            count: day.count,
+            level: ColorPalette::level_for_count(day.count, heatmap_data.max_count),
+        })
+        .collect();
+
+    serde_json::to_string(&EmbedData {
+        username: user.username.clone(),
+        total_count: heatmap_data.total_count,
+        date_range_start: heatmap_data.date_range_start.to_string(),
+        date_range_end: heatmap_data.date_range_end.to_string(),
+        days,
+        themes: themes.into_iter().map(EmbedTheme::from).collect(),
+    })
+    .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+/// GET /users/:username/heatmap.txt
+/// The contribution grid as text for terminals, e.g.
+/// `curl https://hgitmap.example.com/users/alice/heatmap.txt`
+#[utoipa::path(
+    get,
+    path = "/users/{username}/heatmap.txt",
+    tag = "users",
+    params(
+        ("username" = String, Path, description = "Username (case-insensitive)"),
+        TextHeatmapQuery,
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Heatmap as text", content_type = "text/plain"),
+        (status = 400, description = "Invalid color mode"),
+        (status = 404, description = "User or theme not found")
+    )
+)]
+pub async fn serve_text_heatmap(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    path: web::Path<String>,
+    query: web::Query<TextHeatmapQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let username = path.into_inner();
+
+    let ansi = match query.color.as_deref() {
+        Some("ansi") => true,
+        Some("none") => false,
+        Some(_) => return Err(actix_web::error::ErrorBadRequest("color must be ansi or none")),
+        None => text_heatmap::is_terminal_client(
+            req.headers().get(header::USER_AGENT).and_then(|h| h.to_str().ok()),
+        ),
+    };
+
+    let user = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, user.id).await?;
+
+    let mut themes = heatmap_theme::Entity::find().filter(heatmap_theme::Column::UserId.eq(user.id));
+    themes = match &query.theme {
+        Some(slug) => themes.filter(heatmap_theme::Column::Slug.eq(slug.as_str())),
+        None => themes.order_by_desc(heatmap_theme::Column::IsDefault),
+    };
+    let theme = themes.one(db.as_ref()).await.map_err(db_error)?;
+    if query.theme.is_some() && theme.is_none() {
+        return Err(actix_web::error::ErrorNotFound("Theme not found"));
+    }
+
+    let heatmap_data = contribution_data(db.as_ref(), user.id).await?;
+
+    // Users without themes get the GitHub colors
+    let colors = match theme {
+        Some(theme) => ColorPalette::cell_colors(&theme),
+        None => ColorPalette::from_scheme(&heatmap_theme::HeatmapColorScheme::GitHubGreen).colors,
+    };
+    let body = text_heatmap::render(&user.username, &heatmap_data, ansi.then_some(colors.as_slice()));
+
+    let mut response = HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(body);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// GET /users/:username/heatmap.json
+/// The heatmap's cells with the platform and instance of their
+/// contributions, for frontends drawing cells stacked by source
+#[utoipa::path(
+    get,
+    path = "/users/{username}/heatmap.json",
+    tag = "users",
+    params(
+        ("username" = String, Path, description = "Username (case-insensitive)"),
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Heatmap cells with their sources", body = HeatmapJson),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn serve_heatmap_json(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    cache: web::Data<ResponseCache>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let username = path.into_inner();
+
+    let user = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, user.id).await?;
+
+    // Checked after access, so only allowed viewers reach the cache
+    let body = match cache.get::<String>(user.id, "heatmap-json").await {
+        Some(body) => body,
+        None => {
+            let body = heatmap_json(db.as_ref(), &user).await?;
+            cache.insert(user.id, "heatmap-json", &body).await;
+            body
+        }
+    };
+
+    let mut response = HttpResponse::Ok()
+        .content_type("application/json")
+        .body(body);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// Serialized [`HeatmapJson`] for a user
+async fn heatmap_json(db: &DatabaseConnection, user: &user::Model) -> Result<String, actix_web::Error> {
+    let heatmap_data = contribution_data(db, user.id).await?;
+    let (accounts, sources) = HeatmapGenerator::new(db.clone())
+        .fetch_contribution_sources(user.id, &heatmap_data)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to fetch contribution sources: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to fetch contribution data")
+        })?;
+
+    // Hidden accounts still count, without saying where from
+    let hidden = platform_visibility::hidden_accounts(db, user.id)
+        .await
+        .map_err(db_error)?;
+    let source_of: HashMap<Uuid, (Option<String>, Option<String>)> = accounts
+        .iter()
+        .map(|account| {
+            let source = if hidden.contains(&account.id) {
+                (None, None)
+            } else {
+                (
+                    Some(format!("{:?}", account.platform_type).to_lowercase()),
+                    instance_url(account),
+                )
+            };
+            (account.id, source)
+        })
+        .collect();
+
+    let days = heatmap_data
+        .weeks
+        .iter()
+        .flatten()
+        // Padding after the last day
+        .filter(|day| day.count >= 0)
+        .map(|day| {
+            // Accounts on the same instance share a segment
+            let mut counts: BTreeMap<(Option<String>, Option<String>), i32> = BTreeMap::new();
+            for (account_id, count) in sources.get(&day.date).into_iter().flatten() {
+                if let Some(source) = source_of.get(account_id) {
+                    *counts.entry(source.clone()).or_insert(0) += count;
+                }
+            }
+            let mut segments: Vec<HeatmapSegment> = counts
+                .into_iter()
+                .filter(|(_, count)| *count > 0)
+                .map(|((platform, instance_url), count)| HeatmapSegment {
+                    platform,
+                    instance_url,
+                    count,
+                })
+                .collect();
+            segments.sort_by_key(|s| std::cmp::Reverse(s.count));
+
+            HeatmapJsonDay {
+                date: day.date.to_string(),
+                count: day.count,
+                level: ColorPalette::level_for_count(day.count, heatmap_data.max_count),
+                segments,
+            }
+        })
+        .collect();
+
+    serde_json::to_string(&HeatmapJson {
+        username: user.username.clone(),
+        total_count: heatmap_data.total_count,
+        max_count: heatmap_data.max_count,
+        date_range_start: heatmap_data.date_range_start.to_string(),
+        date_range_end: heatmap_data.date_range_end.to_string(),
+        days,
+    })
+    .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+/// Web URL of an account's instance; GitHub and GitLab accounts connected
+/// without one are on the public instances
+fn instance_url(account: &git_platform_account::Model) -> Option<String> {
+    match (&account.platform_url, &account.platform_type) {
+        (Some(url), _) => Some(url.trim_end_matches('/').to_string()),
+        (None, git_platform_account::GitPlatform::GitHub) => Some("https://github.com".to_string()),
+        (None, git_platform_account::GitPlatform::GitLab) => Some("https://gitlab.com".to_string()),
+        (None, _) => None,
+    }
+}
+
+/// GET /users/:username/heatmap/emoji
+/// The heatmap as a grid of emoji or other symbols, as text or JSON
+#[utoipa::path(
+    get,
+    path = "/users/{username}/heatmap/emoji",
+    tag = "users",
+    params(
+        ("username" = String, Path, description = "Username (case-insensitive)"),
+        EmojiHeatmapQuery,
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Symbol grid as text/plain, or JSON with format=json", body = EmojiHeatmapResponse),
+        (status = 400, description = "Invalid symbols, weeks or format"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn serve_emoji_heatmap(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    path: web::Path<String>,
+    query: web::Query<EmojiHeatmapQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let username = path.into_inner();
+
+    let symbols = match &query.symbols {
+        Some(symbols) => match text_heatmap::parse_symbols(symbols) {
+            Ok(symbols) => symbols,
+            Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+        },
+        None => text_heatmap::DEFAULT_SYMBOLS.map(String::from).to_vec(),
+    };
+    let weeks = query.weeks.unwrap_or(53);
+    if !(1..=53).contains(&weeks) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": "weeks must be between 1 and 53" })));
+    }
+    let json = match query.format.as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(_) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": "format must be text or json" }))),
+    };
+
+    let user = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, user.id).await?;
+
+    let heatmap_data = contribution_data(db.as_ref(), user.id).await?;
+    let grid = text_heatmap::symbol_grid(&heatmap_data, weeks, &symbols);
+    let text = grid.rows.join("\n");
+
+    let mut response = if json {
+        HttpResponse::Ok().json(EmojiHeatmapResponse {
+            username: user.username,
+            weeks,
+            total_count: grid.total_count,
+            rows: grid.rows,
+            text,
+        })
+    } else {
+        HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(text + "\n")
+    };
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// GET /users/:username/heatmap/diff
+/// The heatmap with each day colored by how it compares to the same weekday
+/// a year before
+#[utoipa::path(
+    get,
+    path = "/users/{username}/heatmap/diff",
+    tag = "users",
+    params(
+        ("username" = String, Path, description = "Username (case-insensitive)"),
+        DiffHeatmapQuery,
+        ("token" = Option<String>, Query, description = "Signed embed token for a private profile")
+    ),
+    responses(
+        (status = 200, description = "Diff heatmap", content_type = "image/svg+xml"),
+        (status = 404, description = "User or theme not found")
+    )
+)]
+pub async fn serve_diff_heatmap(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    path: web::Path<String>,
+    query: web::Query<DiffHeatmapQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let username = path.into_inner();
+
+    let user = match username_redirects::find_user(db.as_ref(), &req, &username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(response),
+    };
+
+    let visibility = check_access(db.as_ref(), &config, &instance, &req, user.id).await?;
+
+    let mut themes = heatmap_theme::Entity::find().filter(heatmap_theme::Column::UserId.eq(user.id));
+    themes = match &query.theme {
+        Some(slug) => themes.filter(heatmap_theme::Column::Slug.eq(slug.as_str())),
+        None => themes.order_by_desc(heatmap_theme::Column::IsDefault),
+    };
+    let theme = themes
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Theme not found"))?;
+
+    // The same window, 52 weeks earlier
+    let settings = generation_settings(db.as_ref(), user.id).await?;
+    let generator = HeatmapGenerator::new(db.as_ref().clone());
+    let current = contribution_data(db.as_ref(), user.id).await?;
+    let previous = generator
+        .fetch_contribution_data_until(
+            user.id,
+            &settings,
+            current.date_range_end - chrono::Duration::days(heatmap_renderers::diff::COMPARED_DAYS),
+        )
+        .await
+        .map_err(|e| {
+            log::error!("Failed to fetch contribution data: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to fetch contribution data")
+        })?;
+
+    let svg = heatmap_renderers::diff::render(&theme, &current, &previous, Some(&user.username));
+
+    let mut response = HttpResponse::Ok().content_type("image/svg+xml").body(svg);
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+/// GET /share/:token
+/// Serve the heatmap behind a signed share link, whatever the owner's
+/// profile visibility or embed allowlist. Every request counts against the
+/// link's view limit.
+#[utoipa::path(
+    get,
+    path = "/share/{token}",
+    tag = "embed",
+    params(("token" = String, Path, description = "Signed share link token")),
+    responses(
+        (status = 200, description = "Heatmap image"),
+        (status = 404, description = "Invalid or revoked link"),
+        (status = 410, description = "The link expired or reached its view limit")
+    )
+)]
+pub async fn serve_share_link(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let link_id = share_token::verify(&path.into_inner(), &config.jwt_secret)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Share link not found"))?;
+    let link = heatmap_share_link::Entity::find_by_id(link_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Share link not found"))?;
+
+    let now = chrono::Utc::now();
+    if link.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err(actix_web::error::ErrorGone("Share link expired"));
+    }
+
+    // Counting and checking the limit in one statement keeps concurrent
+    // requests from going over it
+    let counted = heatmap_share_link::Entity::update_many()
+        .col_expr(
+            heatmap_share_link::Column::ViewCount,
+            Expr::col(heatmap_share_link::Column::ViewCount).add(1),
+        )
+        .filter(heatmap_share_link::Column::Id.eq(link.id))
+        .filter(
+            Condition::any()
+                .add(heatmap_share_link::Column::MaxViews.is_null())
+                .add(Expr::col(heatmap_share_link::Column::ViewCount).lt(Expr::col(heatmap_share_link::Column::MaxViews))),
+        )
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if counted.rows_affected == 0 {
+        return Err(actix_web::error::ErrorGone("Share link reached its view limit"));
+    }
+
+    let theme = heatmap_theme::Entity::find_by_id(link.theme_id)
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Theme not found"))?;
+
+    let generated = generated_heatmap::Entity::find()
+        .filter(generated_heatmap::Column::UserId.eq(link.user_id))
+        .filter(generated_heatmap::Column::ThemeId.eq(theme.id))
+        .filter(generated_heatmap::Column::Format.eq(link.format.clone()))
+        .filter(generated_heatmap::Column::IsValid.eq(true))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let source = AccessSource::of(&req);
+    let file_path = match generated.filter(|g| PathBuf::from(&g.file_path).exists()) {
+        Some(generated) => {
+            let db_clone = db.clone();
+            let (user_id, theme_id) = (link.user_id, theme.id);
+            tokio::spawn(async move {
+                let _ = increment_access_count(&db_clone, generated.id, user_id, theme_id, source).await;
+            });
+            PathBuf::from(generated.file_path)
+        }
+        None => {
+            let path = generate_heatmap_on_demand(db.as_ref(), &link.user_id, &theme, &link.format).await?;
+            record_view(db.as_ref().clone(), link.user_id, theme.id, source);
+            path
+        }
+    };
+
+    // Shared heatmaps stay out of search engines and shared caches, which
+    // would otherwise serve them past the link's expiry or view limit
+    Ok(serve_file(file_path, profile_visibility::UNLISTED)?
+        .insert_header((header::CACHE_CONTROL, "private, no-store")))
 }
 
 /// Generate a heatmap on-demand and save it to disk and database
-async fn generate_heatmap_on_demand(
+pub async fn generate_heatmap_on_demand(
     db: &DatabaseConnection,
     user_id: &Uuid,
     theme: &crate::models::heatmap_theme::Model,
@@ -154,30 +1087,7 @@ async fn generate_heatmap_on_demand(
     use std::io::Write;
 
     // Get user's generation settings
-    let settings = heatmap_generation_setting::Entity::find()
-        .filter(heatmap_generation_setting::Column::UserId.eq(*user_id))
-        .one(db)
-        .await
-        .map_err(|e| {
-            log::error!("Database error: {}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?
-        .unwrap_or_else(|| {
-            // Default settings
-            heatmap_generation_setting::Model {
-                id: Uuid::new_v4(),
-                user_id: *user_id,
-                update_interval_minutes: 60,
-                auto_generation_enabled: true,
-                date_range_days: 365,
-                include_private_contributions: true,
-                storage_path: None,
-                last_scheduled_generation_at: None,
-                next_scheduled_generation_at: None,
-                created_at: Utc::now(),
-                updated_at: Utc::now(),
-            }
-        });
+    let settings = generation_settings(db, *user_id).await?;
 
     // Create generator and fetch data
     let generator = HeatmapGenerator::new(db.clone());
@@ -206,14 +1116,15 @@ async fn generate_heatmap_on_demand(
         })?;
 
     // Generate the heatmap in the requested format with username
-    let content = generator.generate_heatmap_with_username(theme, &heatmap_data, format, Some(&user_model.username))
+    let content = generator.render(Lane::Interactive, theme, &heatmap_data, format, Some(&user_model.username))
+        .await
         .map_err(|e| {
             log::error!("Failed to generate heatmap: {}", e);
             actix_web::error::ErrorInternalServerError("Failed to generate heatmap")
         })?;
 
     // Determine storage path
-    let base_dir = settings.storage_path.as_deref().unwrap_or("static/heatmaps");
+    let base_dir = settings.storage_path.as_deref().unwrap_or(HEATMAP_BASE_DIR);
     let user_dir = PathBuf::from(base_dir).join(user_id.to_string());
 
     // Create directory if it doesn't exist
@@ -316,25 +1227,71 @@ async fn generate_heatmap_on_demand(
     Ok(file_path)
 }
 
-/// Update access count for a generated heatmap
-async fn update_access_count(db: &DatabaseConnection, file_path: &str) -> Result<(), DbErr> {
-    let heatmap = generated_heatmap::Entity::find()
-        .filter(generated_heatmap::Column::FilePath.eq(file_path))
-        .one(db)
-        .await?;
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
 
-    if let Some(heatmap) = heatmap {
-        let mut active: generated_heatmap::ActiveModel = heatmap.into();
-        active.access_count = Set(active.access_count.unwrap() + 1);
-        active.last_accessed_at = Set(Some(chrono::Utc::now()));
-        active.update(db).await?;
-    }
+/// A user's contribution days over their generation date range, as drawn in
+/// their heatmaps
+async fn contribution_data(db: &DatabaseConnection, user_id: Uuid) -> Result<HeatmapData, actix_web::Error> {
+    let settings = generation_settings(db, user_id).await?;
+    HeatmapGenerator::new(db.clone())
+        .fetch_contribution_data(user_id, &settings)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to fetch contribution data: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to fetch contribution data")
+        })
+}
+
+/// A user's generation settings, or the defaults if they never saved any
+async fn generation_settings(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> Result<heatmap_generation_setting::Model, actix_web::Error> {
+    use chrono::Utc;
 
-    Ok(())
+    let settings = heatmap_generation_setting::Entity::find()
+        .filter(heatmap_generation_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?
+        .unwrap_or_else(|| {
+            // Default settings
+            heatmap_generation_setting::Model {
+                id: Uuid::new_v4(),
+                user_id,
+                update_interval_minutes: 60,
+                auto_generation_enabled: true,
+                date_range_days: 365,
+                include_private_contributions: true,
+                storage_path: None,
+                timezone: None,
+                generation_window_start_minutes: None,
+                generation_window_end_minutes: None,
+                generation_cron: None,
+                last_scheduled_generation_at: None,
+                next_scheduled_generation_at: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }
+        });
+
+    Ok(settings)
 }
 
-/// Increment access count by ID
-async fn increment_access_count(db: &DatabaseConnection, id: uuid::Uuid) -> Result<(), DbErr> {
+/// Increment access count by ID and count the view in the daily analytics
+async fn increment_access_count(
+    db: &DatabaseConnection,
+    id: uuid::Uuid,
+    user_id: Uuid,
+    theme_id: Uuid,
+    source: AccessSource,
+) -> Result<(), DbErr> {
     generated_heatmap::Entity::update_many()
         .filter(generated_heatmap::Column::Id.eq(id))
         .col_expr(
@@ -348,5 +1305,14 @@ async fn increment_access_count(db: &DatabaseConnection, id: uuid::Uuid) -> Resu
         .exec(db)
         .await?;
 
-    Ok(())
+    heatmap_analytics::record(db, user_id, theme_id, source.referrer_host, source.client).await
+}
+
+/// Count a view of a heatmap generated for this request
+fn record_view(db: DatabaseConnection, user_id: Uuid, theme_id: Uuid, source: AccessSource) {
+    tokio::spawn(async move {
+        if let Err(e) = heatmap_analytics::record(&db, user_id, theme_id, source.referrer_host, source.client).await {
+            log::warn!("Failed to record heatmap view: {}", e);
+        }
+    });
 }
@@ -1,75 +1,112 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use sea_orm::*;
 use serde::Serialize;
+use utoipa::{ToSchema};
 use uuid::Uuid;
 
-use crate::services::platform_sync::PlatformSyncService;
+use crate::handlers::platform_sync_jobs::{
+    job_status_url, queue_manual_sync, ManualSync, SyncJobResponse,
+};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::quotas;
+use crate::services::rate_limiter::RateLimiter;
 use crate::utils::config::Config;
 
 // ============ Response DTOs ============
 
-#[derive(Debug, Serialize)]
-pub struct SyncResponse {
-    pub success: bool,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TriggerSyncResponse {
     pub message: String,
-    pub platforms_synced: i32,
-    pub contributions_added: i32,
-    pub contributions_updated: i32,
-    pub errors: Vec<String>,
+    /// One job per active platform account
+    pub jobs: Vec<SyncJobResponse>,
 }
 
 // ============ Sync Handlers ============
 
 /// POST /api/sync/trigger
-/// Manually trigger sync for current user's all platforms
+/// Queue a current-year sync of all of the current user's platforms
+#[utoipa::path(
+    post,
+    path = "/sync/trigger",
+    tag = "sync",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Sync jobs queued; poll each job's status_url", body = TriggerSyncResponse),
+        (status = 429, description = "Manual sync quota reached")
+    )
+)]
 pub async fn trigger_sync(
+    req: HttpRequest,
     db: web::Data<DatabaseConnection>,
     config: web::Data<Config>,
+    instance: web::Data<InstanceSettingsService>,
+    limiter: web::Data<RateLimiter>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
         actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e))
     })?;
 
+    if let Some(exceeded) = quotas::check_manual_sync(&limiter, &config, user_id).await {
+        return Ok(exceeded.response());
+    }
+
     log::info!("Manual sync triggered for user: {}", user_id);
 
-    let sync_service = PlatformSyncService::new(db.get_ref().clone(), config.get_ref().clone());
-
-    match sync_service.sync_user_data(user_id).await {
-        Ok(result) => {
-            let response = SyncResponse {
-                success: result.errors.is_empty(),
-                message: format!(
-                    "Sync completed: {} platforms synced, {} contributions added, {} updated",
-                    result.platforms_synced, result.contributions_added, result.contributions_updated
-                ),
-                platforms_synced: result.platforms_synced,
-                contributions_added: result.contributions_added,
-                contributions_updated: result.contributions_updated,
-                errors: result.errors,
-            };
-
-            Ok(HttpResponse::Ok().json(response))
-        }
-        Err(e) => {
-            log::error!("Sync failed for user {}: {}", user_id, e);
-            let response = SyncResponse {
-                success: false,
-                message: format!("Sync failed: {}", e),
-                platforms_synced: 0,
-                contributions_added: 0,
-                contributions_updated: 0,
-                errors: vec![e.to_string()],
-            };
-            Ok(HttpResponse::InternalServerError().json(response))
-        }
+    let settings = instance.current().await;
+
+    let accounts = crate::models::git_platform_account::Entity::find()
+        .filter(crate::models::git_platform_account::Column::UserId.eq(user_id))
+        .filter(crate::models::git_platform_account::Column::IsActive.eq(true))
+        .all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    let mut jobs = Vec::new();
+    for account in &accounts {
+        let (job, created) = queue_manual_sync(db.as_ref(), &settings, account, &ManualSync::default())
+            .await
+            .map_err(|e| {
+                log::error!("Failed to create sync job: {}", e);
+                actix_web::error::ErrorInternalServerError("Failed to create sync job")
+            })?;
+
+        jobs.push(SyncJobResponse {
+            job_id: job.id.to_string(),
+            status: format!("{:?}", job.status).to_lowercase(),
+            message: if created {
+                format!("Sync of {} queued", account.platform_username)
+            } else {
+                format!("A sync of {} is already queued or running", account.platform_username)
+            },
+            status_url: job_status_url(&req, job.id),
+        });
     }
+
+    Ok(HttpResponse::Accepted().json(TriggerSyncResponse {
+        message: format!(
+            "Queued sync jobs for {} platforms. They run in the background.",
+            jobs.len()
+        ),
+        jobs,
+    }))
 }
 
 /// GET /api/sync/status
 /// Get sync status for current user
+#[utoipa::path(
+    get,
+    path = "/sync/status",
+    tag = "sync",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Automatic sync status", body = Object))
+)]
 pub async fn get_sync_status(
     db: web::Data<DatabaseConnection>,
+    instance: web::Data<InstanceSettingsService>,
     user_claims: web::ReqData<crate::middleware::auth::Claims>,
 ) -> Result<impl Responder, actix_web::Error> {
     let user_id = Uuid::parse_str(&user_claims.sub).map_err(|e| {
@@ -113,9 +150,10 @@ pub async fn get_sync_status(
         last_synced: Option<String>,
     }
 
+    let default_interval = instance.current().await.default_sync_interval_minutes;
     let status = SyncStatus {
         auto_sync_enabled: settings.as_ref().map(|s| s.auto_generation_enabled).unwrap_or(false),
-        update_interval_minutes: settings.as_ref().map(|s| s.update_interval_minutes).unwrap_or(60),
+        update_interval_minutes: settings.as_ref().map_or(default_interval, |s| s.update_interval_minutes),
         last_sync: settings.as_ref().and_then(|s| s.last_scheduled_generation_at.map(|t| t.to_rfc3339())),
         next_sync: settings.as_ref().and_then(|s| s.next_scheduled_generation_at.map(|t| t.to_rfc3339())),
         platform_accounts: accounts
@@ -0,0 +1,592 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, Func};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{heatmap_theme, team, team_member, user};
+use crate::services::heatmap_generator::HeatmapGenerator;
+use crate::services::render_lanes::Lane;
+use crate::services::teams::{self, MemberStats};
+use crate::utils::validators;
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_DESCRIPTION_LENGTH: usize = 1000;
+
+/// Creates a team, or replaces its details when updating
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TeamRequest {
+    pub name: String,
+    /// Lowercase letters, digits and hyphens; unique on the instance. Fixed
+    /// once the team exists.
+    pub slug: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddMemberRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TeamHeatmapQuery {
+    /// Theme slug of the requesting user; defaults to their default theme
+    pub theme: Option<String>,
+    /// svg (default), png, jpeg or webp
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TeamMemberInfo {
+    pub user_id: String,
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TeamResponse {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub owner_id: String,
+    /// Earliest added first
+    pub members: Vec<TeamMemberInfo>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TeamStatsResponse {
+    pub slug: String,
+    pub total_count: i32,
+    /// Days on which any member contributed
+    pub active_days: i32,
+    pub max_count: i32,
+    pub date_range_start: String,
+    pub date_range_end: String,
+    /// Most contributions first
+    pub members: Vec<MemberStats>,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// Check a team's name and description, returning them trimmed or a client
+/// error
+fn validate(body: &TeamRequest) -> Result<(String, Option<String>), String> {
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("name must be between 1 and {} characters", MAX_NAME_LENGTH));
+    }
+
+    let description = body
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty());
+    if description.is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LENGTH) {
+        return Err(format!(
+            "description must be at most {} characters",
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+
+    Ok((name.to_string(), description.map(str::to_string)))
+}
+
+async fn team_response(db: &DatabaseConnection, team: team::Model) -> Result<TeamResponse, actix_web::Error> {
+    let members = teams::members(db, team.id).await.map_err(db_error)?;
+
+    Ok(TeamResponse {
+        id: team.id.to_string(),
+        name: team.name,
+        slug: team.slug,
+        description: team.description,
+        owner_id: team.owner_id.to_string(),
+        members: members
+            .into_iter()
+            .map(|m| TeamMemberInfo {
+                user_id: m.id.to_string(),
+                username: m.username,
+            })
+            .collect(),
+        created_at: team.created_at.to_rfc3339(),
+        updated_at: team.updated_at.to_rfc3339(),
+    })
+}
+
+/// The team behind `slug` if the user is one of its members. Other users get
+/// a 404, so team slugs do not leak.
+async fn find_team(db: &DatabaseConnection, slug: &str, user_id: Uuid) -> Result<team::Model, actix_web::Error> {
+    let team = team::Entity::find()
+        .filter(team::Column::Slug.eq(slug))
+        .one(db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Team not found"))?;
+
+    if !teams::is_member(db, team.id, user_id).await.map_err(db_error)? {
+        return Err(actix_web::error::ErrorNotFound("Team not found"));
+    }
+
+    Ok(team)
+}
+
+/// `find_team`, limited to the team's owner
+async fn find_owned_team(db: &DatabaseConnection, slug: &str, user_id: Uuid) -> Result<team::Model, actix_web::Error> {
+    let team = find_team(db, slug, user_id).await?;
+    if team.owner_id != user_id {
+        return Err(actix_web::error::ErrorForbidden("Only the team owner can change the team"));
+    }
+    Ok(team)
+}
+
+/// GET /api/teams
+/// Teams the user owns or belongs to
+#[utoipa::path(
+    get,
+    path = "/teams",
+    tag = "teams",
+    security(("bearer_auth" = [])),
+    responses((status = 200, description = "Teams, by name", body = [TeamResponse]))
+)]
+pub async fn list_teams(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let team_list = team::Entity::find()
+        .inner_join(team_member::Entity)
+        .filter(team_member::Column::UserId.eq(user_id))
+        .order_by_asc(team::Column::Name)
+        .all(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    let mut response = Vec::with_capacity(team_list.len());
+    for team in team_list {
+        response.push(team_response(db.as_ref(), team).await?);
+    }
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// POST /api/teams
+/// Create a team with the user as owner and first member
+#[utoipa::path(
+    post,
+    path = "/teams",
+    tag = "teams",
+    request_body = TeamRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Team created", body = TeamResponse),
+        (status = 400, description = "Invalid name, slug or description"),
+        (status = 409, description = "Slug already taken")
+    )
+)]
+pub async fn create_team(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    body: web::Json<TeamRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let (name, description) = match validate(&body) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+    let Some(slug) = body.slug.as_deref().map(str::trim) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": "slug is required" })));
+    };
+    if let Err(e) = validators::validate_slug(slug) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e.to_string() })));
+    }
+
+    let taken = team::Entity::find()
+        .filter(team::Column::Slug.eq(slug))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if taken.is_some() {
+        return Err(actix_web::error::ErrorConflict("Team with this slug already exists"));
+    }
+
+    let now = Utc::now();
+    let txn = db.begin().await.map_err(db_error)?;
+    let team = team::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        owner_id: Set(user_id),
+        name: Set(name),
+        slug: Set(slug.to_string()),
+        description: Set(description),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(&txn)
+    .await
+    .map_err(db_error)?;
+    team_member::ActiveModel {
+        team_id: Set(team.id),
+        user_id: Set(user_id),
+        added_at: Set(now),
+    }
+    .insert(&txn)
+    .await
+    .map_err(db_error)?;
+    txn.commit().await.map_err(db_error)?;
+
+    log::info!("👥 Team '{}' created by {}", team.slug, user_id);
+
+    Ok(HttpResponse::Created().json(team_response(db.as_ref(), team).await?))
+}
+
+/// GET /api/teams/:slug
+/// A team the user belongs to, with its members
+#[utoipa::path(
+    get,
+    path = "/teams/{slug}",
+    tag = "teams",
+    params(("slug" = String, Path, description = "Team slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Team", body = TeamResponse),
+        (status = 404, description = "Team not found or not a member")
+    )
+)]
+pub async fn get_team(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let team = find_team(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    Ok(HttpResponse::Ok().json(team_response(db.as_ref(), team).await?))
+}
+
+/// PUT /api/teams/:slug
+/// Replace a team's name and description (owner only)
+#[utoipa::path(
+    put,
+    path = "/teams/{slug}",
+    tag = "teams",
+    params(("slug" = String, Path, description = "Team slug")),
+    request_body = TeamRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Team updated", body = TeamResponse),
+        (status = 400, description = "Invalid name or description, or a changed slug"),
+        (status = 403, description = "Not the team owner"),
+        (status = 404, description = "Team not found or not a member")
+    )
+)]
+pub async fn update_team(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: web::Json<TeamRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let team = find_owned_team(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    let (name, description) = match validate(&body) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": e }))),
+    };
+    if body.slug.as_deref().is_some_and(|slug| slug.trim() != team.slug) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "slug cannot be changed"
+        })));
+    }
+
+    let mut active: team::ActiveModel = team.into();
+    active.name = Set(name);
+    active.description = Set(description);
+    active.updated_at = Set(Utc::now());
+    let updated = active.update(db.as_ref()).await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(team_response(db.as_ref(), updated).await?))
+}
+
+/// DELETE /api/teams/:slug
+/// Delete a team (owner only). Members keep their own data.
+#[utoipa::path(
+    delete,
+    path = "/teams/{slug}",
+    tag = "teams",
+    params(("slug" = String, Path, description = "Team slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Team deleted"),
+        (status = 403, description = "Not the team owner"),
+        (status = 404, description = "Team not found or not a member")
+    )
+)]
+pub async fn delete_team(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let team = find_owned_team(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    team::Entity::delete_by_id(team.id)
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    log::info!("🗑️  Team '{}' deleted by {}", team.slug, user_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/teams/:slug/members
+/// Add a user to a team by username (owner only)
+#[utoipa::path(
+    post,
+    path = "/teams/{slug}/members",
+    tag = "teams",
+    params(("slug" = String, Path, description = "Team slug")),
+    request_body = AddMemberRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Member added", body = TeamResponse),
+        (status = 400, description = "Team is full"),
+        (status = 403, description = "Not the team owner, or the user's profile is private"),
+        (status = 404, description = "Team or user not found"),
+        (status = 409, description = "Already a member")
+    )
+)]
+pub async fn add_member(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    body: web::Json<AddMemberRequest>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let team = find_owned_team(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    let member = user::Entity::find()
+        .filter(Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(body.username.trim().to_lowercase()))
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("User not found"))?;
+
+    if teams::is_member(db.as_ref(), team.id, member.id).await.map_err(db_error)? {
+        return Err(actix_web::error::ErrorConflict("User is already a member"));
+    }
+
+    // A private profile is only shown to its owner, so it cannot be pulled
+    // into someone else's dashboard
+    let visibility = crate::services::profile_visibility::find(db.as_ref(), member.id)
+        .await
+        .map_err(db_error)?;
+    if visibility == crate::services::profile_visibility::PRIVATE {
+        return Err(actix_web::error::ErrorForbidden("User's profile is private"));
+    }
+
+    let count = team_member::Entity::find()
+        .filter(team_member::Column::TeamId.eq(team.id))
+        .count(db.as_ref())
+        .await
+        .map_err(db_error)?;
+    if count >= teams::MAX_MEMBERS {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("A team can have at most {} members", teams::MAX_MEMBERS)
+        })));
+    }
+
+    team_member::ActiveModel {
+        team_id: Set(team.id),
+        user_id: Set(member.id),
+        added_at: Set(Utc::now()),
+    }
+    .insert(db.as_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Created().json(team_response(db.as_ref(), team).await?))
+}
+
+/// DELETE /api/teams/:slug/members/:username
+/// Remove a member (owner only), or leave a team. The owner cannot be
+/// removed.
+#[utoipa::path(
+    delete,
+    path = "/teams/{slug}/members/{username}",
+    tag = "teams",
+    params(
+        ("slug" = String, Path, description = "Team slug"),
+        ("username" = String, Path, description = "Username of the member (case-insensitive)")
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 400, description = "The owner cannot be removed"),
+        (status = 403, description = "Not the team owner"),
+        (status = 404, description = "Team or member not found")
+    )
+)]
+pub async fn remove_member(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<(String, String)>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let (slug, username) = path.into_inner();
+    let team = find_team(db.as_ref(), &slug, user_id).await?;
+
+    let member = teams::members(db.as_ref(), team.id)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .find(|m| m.username.eq_ignore_ascii_case(&username))
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Member not found"))?;
+
+    if member.id == team.owner_id {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "The owner cannot be removed; delete the team instead"
+        })));
+    }
+    if member.id != user_id && team.owner_id != user_id {
+        return Err(actix_web::error::ErrorForbidden("Only the team owner can change the team"));
+    }
+
+    team_member::Entity::delete_by_id((team.id, member.id))
+        .exec(db.as_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// GET /api/teams/:slug/heatmap
+/// The team's merged heatmap, drawn with one of the requesting user's themes
+#[utoipa::path(
+    get,
+    path = "/teams/{slug}/heatmap",
+    tag = "teams",
+    params(("slug" = String, Path, description = "Team slug"), TeamHeatmapQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Team heatmap in the requested format"),
+        (status = 400, description = "Unsupported format"),
+        (status = 404, description = "Team or theme not found")
+    )
+)]
+pub async fn get_team_heatmap(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+    query: web::Query<TeamHeatmapQuery>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let (format, content_type) = match query.format.as_deref().unwrap_or("svg") {
+        "svg" => (heatmap_theme::HeatmapFormat::Svg, "image/svg+xml"),
+        "png" => (heatmap_theme::HeatmapFormat::Png, "image/png"),
+        "jpg" | "jpeg" => (heatmap_theme::HeatmapFormat::Jpeg, "image/jpeg"),
+        "webp" => (heatmap_theme::HeatmapFormat::WebP, "image/webp"),
+        _ => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "format must be svg, png, jpeg or webp"
+            })))
+        }
+    };
+
+    let team = find_team(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    let mut themes = heatmap_theme::Entity::find().filter(heatmap_theme::Column::UserId.eq(user_id));
+    themes = match &query.theme {
+        Some(slug) => themes.filter(heatmap_theme::Column::Slug.eq(slug.as_str())),
+        None => themes.order_by_desc(heatmap_theme::Column::IsDefault),
+    };
+    let theme = themes
+        .one(db.as_ref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Theme not found"))?;
+
+    let team_heatmap = teams::heatmap(db.as_ref(), &team).await.map_err(|e| {
+        log::error!("Failed to fetch team contribution data: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to fetch contribution data")
+    })?;
+
+    // The slug stands in for the username; it is safe to put in the SVG as is
+    let content = HeatmapGenerator::new(db.as_ref().clone())
+        .render(Lane::Interactive, &theme, &team_heatmap.data, &format, Some(&team.slug))
+        .await
+        .map_err(|e| {
+            log::error!("Failed to render team heatmap: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to render heatmap")
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "private, no-cache"))
+        .body(content))
+}
+
+/// GET /api/teams/:slug/stats
+/// Totals of the team's merged heatmap and each member's share
+#[utoipa::path(
+    get,
+    path = "/teams/{slug}/stats",
+    tag = "teams",
+    params(("slug" = String, Path, description = "Team slug")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Team statistics", body = TeamStatsResponse),
+        (status = 404, description = "Team not found or not a member")
+    )
+)]
+pub async fn get_team_stats(
+    db: web::Data<DatabaseConnection>,
+    user_claims: web::ReqData<crate::middleware::auth::Claims>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let user_id = Uuid::parse_str(&user_claims.sub)
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Invalid user ID: {}", e)))?;
+
+    let team = find_team(db.as_ref(), &path.into_inner(), user_id).await?;
+
+    let team_heatmap = teams::heatmap(db.as_ref(), &team).await.map_err(|e| {
+        log::error!("Failed to fetch team contribution data: {}", e);
+        actix_web::error::ErrorInternalServerError("Failed to fetch contribution data")
+    })?;
+
+    let data = team_heatmap.data;
+    let mut members = team_heatmap.members;
+    members.sort_by_key(|m| std::cmp::Reverse(m.total_count));
+
+    Ok(HttpResponse::Ok().json(TeamStatsResponse {
+        slug: team.slug,
+        total_count: data.total_count,
+        active_days: data.weeks.iter().flatten().filter(|d| d.count > 0).count() as i32,
+        max_count: data.max_count,
+        date_range_start: data.date_range_start.to_string(),
+        date_range_end: data.date_range_end.to_string(),
+        members,
+    }))
+}
@@ -0,0 +1,223 @@
+use actix_web::http::header;
+use actix_web::web::{self, Bytes};
+use actix_web::{HttpRequest, HttpResponse, Responder};
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::handlers::auth::ErrorResponse;
+use crate::models::user;
+use crate::services::event_bus::EventBus;
+use crate::services::response_cache::ResponseCache;
+use crate::services::today::{self, TodayResponse};
+use crate::services::{profile_visibility, username_redirects};
+use crate::utils::conditional_get::Validators;
+use crate::utils::config::Config;
+
+/// How long clients and proxies may reuse the count without asking again
+const TODAY_MAX_AGE_SECS: u32 = 60;
+
+/// Comment line sent when idle so proxies keep the connection open; the
+/// day rollover is checked as often
+const KEEPALIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+const CACHE_KEY: &str = "today";
+
+#[derive(Serialize, Deserialize)]
+struct CachedToday {
+    validators: Validators,
+    today: TodayResponse,
+}
+
+fn db_error(e: DbErr) -> actix_web::Error {
+    log::error!("Database error: {}", e);
+    actix_web::error::ErrorInternalServerError("Database error")
+}
+
+/// The user behind a public URL the request may see, or the response to
+/// send instead: a redirect for a renamed user, or 404
+async fn find_visible_user(
+    db: &DatabaseConnection,
+    config: &Config,
+    req: &HttpRequest,
+    username: &str,
+) -> Result<Result<(user::Model, String), HttpResponse>, actix_web::Error> {
+    let user_model = match username_redirects::find_user(db, req, username).await.map_err(db_error)? {
+        Ok(user) => user,
+        Err(response) => return Ok(Err(response)),
+    };
+
+    let visibility = profile_visibility::find(db, user_model.id).await.map_err(db_error)?;
+    if !profile_visibility::can_view(db, config, req, user_model.id, &visibility)
+        .await
+        .map_err(db_error)?
+    {
+        return Ok(Err(HttpResponse::NotFound().json(ErrorResponse {
+            error: "User not found".to_string(),
+        })));
+    }
+
+    Ok(Ok((user_model, visibility)))
+}
+
+/// Validators of a count: it changes with the body, and at the latest at the
+/// user's midnight or their last sync
+fn validators(today: &TodayResponse) -> Validators {
+    let body = serde_json::to_string(today).unwrap_or_default();
+    let start_of_day = today.resets_at - Duration::days(1);
+    Validators::new(
+        &[CACHE_KEY, &body],
+        today.last_synced_at.map_or(start_of_day, |t| t.max(start_of_day)),
+    )
+}
+
+/// GET /api/users/:username/today
+/// Public endpoint with the user's contributions so far today and their
+/// streak, for status bars, stream overlays and smart-home displays
+#[utoipa::path(
+    get,
+    path = "/users/{username}/today",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "Today's count, cacheable for a minute, with ETag and Last-Modified", body = TodayResponse),
+        (status = 304, description = "Not modified since the ETag or date sent in If-None-Match / If-Modified-Since"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn get_user_today(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    cache: web::Data<ResponseCache>,
+    path: web::Path<String>,
+) -> Result<impl Responder, actix_web::Error> {
+    let username = path.into_inner();
+
+    let (user_model, visibility) = match find_visible_user(db.as_ref(), &config, &req, &username).await? {
+        Ok(found) => found,
+        Err(response) => return Ok(response),
+    };
+
+    // Checked after visibility, so only allowed viewers reach the cache. A
+    // sync drops the entry; midnight outdates it.
+    let cached = cache
+        .get::<CachedToday>(user_model.id, CACHE_KEY)
+        .await
+        .filter(|cached| cached.today.resets_at > Utc::now());
+    let cached = match cached {
+        Some(cached) => cached,
+        None => {
+            let today = today::for_user(db.as_ref(), user_model.id).await.map_err(db_error)?;
+            let fresh = CachedToday {
+                validators: validators(&today),
+                today,
+            };
+            cache.insert(user_model.id, CACHE_KEY, &fresh).await;
+            fresh
+        }
+    };
+
+    let body = serde_json::to_vec(&cached.today).map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut response = cached.validators.respond(req.headers(), Bytes::from(body));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        header::HeaderValue::from_str(&format!("public, max-age={}", TODAY_MAX_AGE_SECS)).expect("valid header"),
+    );
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
+
+fn sse_message(today: &TodayResponse) -> Bytes {
+    let data = serde_json::to_string(today).unwrap_or_else(|_| "{}".to_string());
+    Bytes::from(format!("event: today\ndata: {}\n\n", data))
+}
+
+/// GET /api/users/:username/today/events
+/// Server-sent event stream of the user's count today, so displays update
+/// without polling
+#[utoipa::path(
+    get,
+    path = "/users/{username}/today/events",
+    tag = "users",
+    params(("username" = String, Path, description = "Username (case-insensitive)")),
+    responses(
+        (status = 200, description = "text/event-stream of today events, each carrying the current count. One is sent on connect, then one whenever a sync changes the count and at the user's midnight.", content_type = "text/event-stream"),
+        (status = 404, description = "User not found")
+    )
+)]
+pub async fn user_today_events(
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    config: web::Data<Config>,
+    bus: web::Data<EventBus>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let username = path.into_inner();
+
+    let (user_model, visibility) = match find_visible_user(db.as_ref(), &config, &req, &username).await? {
+        Ok(found) => found,
+        Err(response) => return Ok(response),
+    };
+    let user_id: Uuid = user_model.id;
+
+    // Subscribed before the first count, so no change slips in between
+    let receiver = bus.subscribe();
+    let first = today::for_user(db.as_ref(), user_id).await.map_err(db_error)?;
+    let keepalive = interval(KEEPALIVE_INTERVAL);
+    let db = db.get_ref().clone();
+
+    let initial = futures_util::stream::once(futures_util::future::ready(Ok::<_, actix_web::Error>(
+        sse_message(&first),
+    )));
+    let updates = futures_util::stream::unfold(
+        (receiver, keepalive, first),
+        move |(mut receiver, mut keepalive, mut last)| {
+            let db = db.clone();
+            async move {
+                loop {
+                    let recount = tokio::select! {
+                        _ = keepalive.tick() => last.resets_at <= Utc::now(),
+                        received = receiver.recv() => match received {
+                            Ok(event) if event.user_id == user_id => event.event.updates_profile(),
+                            Ok(_) => continue,
+                            // Missed some events, so the count may be stale
+                            Err(RecvError::Lagged(_)) => true,
+                            Err(RecvError::Closed) => return None,
+                        },
+                    };
+
+                    let message = if recount {
+                        match today::for_user(&db, user_id).await {
+                            Ok(today) if today != last => {
+                                let message = sse_message(&today);
+                                last = today;
+                                message
+                            }
+                            Ok(_) => continue,
+                            Err(e) => {
+                                log::warn!("⚠️  [Today] Failed to count for user {}: {}", user_id, e);
+                                continue;
+                            }
+                        }
+                    } else {
+                        Bytes::from_static(b": keepalive\n\n")
+                    };
+                    return Some((Ok::<_, actix_web::Error>(message), (receiver, keepalive, last)));
+                }
+            }
+        },
+    );
+
+    let mut response = HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Disable response buffering in nginx
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(futures_util::StreamExt::chain(initial, updates));
+    profile_visibility::mark_unlisted(&mut response, &visibility);
+    Ok(response)
+}
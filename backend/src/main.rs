@@ -1,12 +1,31 @@
+mod cli;
+mod graphql;
 mod handlers;
 mod middleware;
 mod models;
+mod openapi;
+mod routes;
 mod services;
 mod utils;
 
 use actix_cors::Cors;
-use actix_web::{middleware::Logger, web, App, HttpServer};
+use clap::Parser;
+use actix_web::{
+    middleware::{from_fn, Logger},
+    web, App, HttpServer,
+};
 use utils::{config::Config, db::establish_connection};
+use tracing_actix_web::TracingLogger;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+/// Print every configuration problem and exit instead of panicking on the first
+fn load_config_or_exit(check_storage: bool) -> Config {
+    cli::load_config(check_storage).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    })
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -16,71 +35,239 @@ async fn main() -> std::io::Result<()> {
     // Initialize logger with default level if RUST_LOG not set
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    // Migrations and admin tasks run instead of the server
+    let cli = cli::Cli::parse();
+    if cli.check_config {
+        load_config_or_exit(true);
+        println!("✅ Configuration is valid");
+        return Ok(());
+    }
+    if let Some(command) = cli.command.filter(|c| !matches!(c, cli::Command::Serve)) {
+        let config = load_config_or_exit(false);
+        if let Err(e) = cli::run(command, config).await {
+            eprintln!("❌ {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     println!("=================================================");
     println!("🚀 hgitmap Backend Server");
     println!("=================================================");
 
     // Load configuration
-    let config = Config::from_env().expect("Failed to load configuration");
+    let config = load_config_or_exit(true);
     let host = config.host.clone();
     let port = config.port;
 
     println!("📝 Configuration loaded:");
     println!(
         "   - Database: {}",
-        config.database_url.split('@').last().unwrap_or("***")
+        config.database_url.split('@').next_back().unwrap_or("***")
     );
     println!("   - Host: {}", host);
     println!("   - Port: {}", port);
+    println!(
+        "   - CORS origins: {}, {}",
+        config.frontend_url,
+        config.cors_allowed_origins.join(", ")
+    );
     println!(
         "   - Registration: {}",
         if config.allow_registration {
             "ENABLED"
+        } else {
+            "DISABLED (invitation only)"
+        }
+    );
+    println!(
+        "   - Single sign-on: {}",
+        config.oidc_issuer_url.as_deref().unwrap_or("DISABLED")
+    );
+    println!(
+        "   - Email: {}",
+        if config.smtp_url.is_some() {
+            "ENABLED"
         } else {
             "DISABLED"
         }
     );
+    println!(
+        "   - CAPTCHA: {}",
+        config.captcha_provider.as_deref().unwrap_or("DISABLED")
+    );
+    println!(
+        "   - Activity retention: {}",
+        config
+            .activity_retention_days
+            .map_or_else(|| "FOREVER".to_string(), |days| format!("{} days", days))
+    );
+    println!(
+        "   - Slow query log: {}",
+        config
+            .slow_query_ms
+            .map_or_else(|| "DISABLED".to_string(), |ms| format!(">= {} ms", ms))
+    );
     println!(
         "   - Log level: {}",
         std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string())
     );
 
+    let telemetry = utils::telemetry::init(&config);
+    let error_reporting_guard = utils::error_reporting::init(&config);
+    println!(
+        "   - Tracing: {}",
+        if telemetry.is_enabled() {
+            "OTLP"
+        } else {
+            "DISABLED"
+        }
+    );
+    println!(
+        "   - Error reporting: {}",
+        if error_reporting_guard.is_some() {
+            "ENABLED"
+        } else {
+            "DISABLED"
+        }
+    );
+
     // Establish database connection
     print!("🔌 Connecting to database... ");
-    let db = establish_connection(&config.database_url)
-        .await
-        .expect("Failed to connect to database");
+    let mut db = match establish_connection(&config.database_url).await {
+        Ok(db) => db,
+        Err(e) => {
+            println!("❌");
+            eprintln!("❌ Could not connect to the database: {}", e);
+            eprintln!("   Check DATABASE_URL and that the database is running and reachable");
+            std::process::exit(1);
+        }
+    };
+    let record_spans = telemetry.is_enabled();
+    let slow_query = config.slow_query_ms.map(std::time::Duration::from_millis);
+    if record_spans || slow_query.is_some() {
+        db.set_metric_callback(move |info| {
+            if record_spans {
+                utils::telemetry::record_db_query(info);
+            }
+            if let Some(threshold) = slow_query {
+                utils::db::log_slow_query(info, threshold);
+            }
+        });
+    }
     println!("✅ Connected!");
 
     log::info!("Database connection established");
 
+    // Live dashboard events published by the background jobs
+    let event_bus = services::event_bus::EventBus::new();
+
+    // Hot public responses, dropped per user when their data changes
+    let response_cache = services::response_cache::ResponseCache::from_config(&config).await;
+    response_cache.invalidate_on_events(&event_bus);
+
+    // Daily totals for users' home displays, sent to their MQTT brokers
+    services::mqtt_publisher::publish_on_events(db.clone(), config.encryption_key.clone(), &event_bus);
+
+    // Milestones and failed syncs, sent to the notification channels users
+    // routed them to
+    services::notification_channels::route_events(
+        services::notification_channels::ChannelContext {
+            db: db.clone(),
+            encryption_key: config.encryption_key.clone(),
+            mailer: std::sync::Arc::new(services::mailer::Mailer::from_config(&config)),
+        },
+        &event_bus,
+    );
+
+    // Settings admins can change at runtime, read by handlers and the job processor
+    let instance_settings = web::Data::new(services::instance_settings::InstanceSettingsService::new(
+        db.clone(),
+        &config,
+    ));
+
+    // Uploaded event files are not kept, so imports cannot resume
+    match services::activity_import::fail_interrupted(&db).await {
+        Ok(0) => {}
+        Ok(count) => log::warn!("Marked {} interrupted activity imports as failed", count),
+        Err(e) => log::error!("Failed to mark interrupted activity imports: {}", e),
+    }
+
+    // Background workers stop taking new work once shutdown is requested
+    let (shutdown_trigger, shutdown) = services::shutdown::channel();
+    let mut workers = Vec::new();
+
     // Start background job processor for heatmap generation
     log::info!("Starting heatmap generation job processor");
-    services::job_processor::start_job_processor(db.clone());
+    workers.push(services::job_processor::start_job_processor(
+        db.clone(),
+        event_bus.clone(),
+        instance_settings.clone().into_inner(),
+        config.job_concurrency_per_user,
+        services::job_processor::RetryPolicy::from_config(&config),
+        shutdown.clone(),
+    ));
 
     // Start sync job processor for platform data syncing
     log::info!("Starting platform sync job processor");
-    services::sync_job_processor::start_sync_job_processor(
+    workers.push(services::sync_job_processor::start_sync_job_processor(
         db.clone(),
         config.encryption_key.clone(),
-    );
+        event_bus.clone(),
+        config.job_concurrency_per_user,
+        shutdown.clone(),
+    ));
 
     // Start sync scheduler for automatic platform data syncing
     log::info!("Starting platform sync scheduler");
     let scheduler = std::sync::Arc::new(services::sync_scheduler::SyncScheduler::new(
         db.clone(),
         config.clone(),
+        instance_settings.clone().into_inner(),
+        response_cache.clone(),
+    ));
+    let scheduler_shutdown = shutdown.clone();
+    workers.push(tokio::spawn(async move {
+        scheduler.start(scheduler_shutdown).await;
+    }));
+
+    // Prune activities past their retention period
+    workers.push(services::retention::start_retention_job(
+        db.clone(),
+        config.clone(),
+        shutdown.clone(),
+    ));
+
+    // Remove orphaned heatmap files and stale records
+    workers.push(services::cleanup::start_cleanup_job(
+        db.clone(),
+        config.clone(),
+        shutdown.clone(),
+    ));
+
+    // Email weekly and monthly digests to users who opted in
+    workers.push(services::email_digest::start_digest_job(
+        db.clone(),
+        config.clone(),
+        instance_settings.clone().into_inner(),
+        shutdown.clone(),
+    ));
+
+    // Post weekly summaries to users' Mastodon and Bluesky accounts
+    workers.push(services::weekly_summary::start_weekly_summary_job(
+        db.clone(),
+        config.clone(),
+        instance_settings.clone().into_inner(),
+        shutdown,
     ));
-    let scheduler_clone = scheduler.clone();
-    tokio::spawn(async move {
-        scheduler_clone.start().await;
-    });
 
     // Start HTTP server
     println!("🌐 Starting HTTP server at http://{}:{}", host, port);
     println!("📍 Available endpoints:");
     println!("   - POST http://{}:{}/auth/register", host, port);
     println!("   - POST http://{}:{}/auth/login", host, port);
+    println!("   - GET  http://{}:{}/auth/oauth/{{github,gitlab}}/authorize (sign in)", host, port);
+    println!("   - GET  http://{}:{}/auth/oidc/authorize (single sign-on)", host, port);
     println!("   - GET  http://{}:{}/oauth/github/authorize", host, port);
     println!("   - GET  http://{}:{}/oauth/github/callback", host, port);
     println!("   - GET  http://{}:{}/oauth/gitea/authorize", host, port);
@@ -100,267 +287,114 @@ async fn main() -> std::io::Result<()> {
         host, port
     );
     println!("   - GET  http://{}:{}/settings (JWT required)", host, port);
+    println!("   - POST http://{}:{}/graphql (JWT required)", host, port);
+    println!("   - GET  http://{}:{}/events (JWT required, server-sent events)", host, port);
+    println!("   - GET  http://{}:{}/docs/ (API documentation)", host, port);
+    println!("   - GET  http://{}:{}/healthz, /readyz (health checks)", host, port);
     println!("=================================================");
 
     log::info!("Server started at http://{}:{}", host, port);
 
-    HttpServer::new(move || {
+    let schema = graphql::build_schema(db.clone());
+    let response_cache = web::Data::new(response_cache);
+    let preview_cache = web::Data::new(services::preview_cache::PreviewCache::from_config(&config));
+    let rate_limiter = web::Data::new(services::rate_limiter::RateLimiter::from_config(&config).await);
+    let oidc_client = web::Data::new(services::oidc::OidcClient::new(&config));
+    let mailer = web::Data::new(services::mailer::Mailer::from_config(&config));
+    let captcha = web::Data::new(services::captcha::CaptchaVerifier::from_config(&config));
+
+    let allowed_origins = std::sync::Arc::new(
+        utils::cors::AllowedOrigins::from_config(&config).expect("Invalid CORS_ALLOWED_ORIGINS"),
+    );
+
+    let shutdown_timeout = std::time::Duration::from_secs(config.shutdown_timeout_secs);
+    let shutdown_db = db.clone();
+
+    let server = HttpServer::new(move || {
         // Strict CORS for authenticated API endpoints
+        let allowed_origins = allowed_origins.clone();
         let cors = Cors::default()
-            .allowed_origin("http://localhost:5173")
-            .allowed_origin("http://localhost:3000")
-            .allowed_origin(&config.frontend_url)
+            .allowed_origin_fn(move |origin, _| {
+                origin
+                    .to_str()
+                    .is_ok_and(|origin| allowed_origins.is_allowed(origin))
+            })
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
             .allowed_headers(vec![
                 actix_web::http::header::AUTHORIZATION,
                 actix_web::http::header::ACCEPT,
                 actix_web::http::header::CONTENT_TYPE,
             ])
+            .expose_headers(vec![
+                utils::pagination::TOTAL_COUNT_HEADER,
+                utils::pagination::NEXT_CURSOR_HEADER,
+                "X-RateLimit-Limit",
+                "X-RateLimit-Remaining",
+                "X-RateLimit-Reset",
+                "Retry-After",
+                "ETag",
+                "Last-Modified",
+                "Location",
+            ])
             .max_age(3600);
 
         App::new()
             .app_data(web::Data::new(db.clone()))
             .app_data(web::Data::new(config.clone()))
+            .app_data(web::Data::new(schema.clone()))
+            .app_data(rate_limiter.clone())
+            .app_data(web::Data::new(event_bus.clone()))
+            .app_data(response_cache.clone())
+            .app_data(preview_cache.clone())
+            .app_data(oidc_client.clone())
+            .app_data(mailer.clone())
+            .app_data(captcha.clone())
+            .app_data(instance_settings.clone())
             .wrap(Logger::default())
             .wrap(cors) // CORS must be wrapped AFTER Logger to ensure headers are added to all responses
-            // Public endpoints (no authentication required)
-            .service(
-                web::scope("/auth")
-                    .route("/register", web::post().to(handlers::auth::register))
-                    .route("/login", web::post().to(handlers::auth::login)),
-            )
-            // OAuth endpoints (authorize requires JWT, callback uses state token)
-            .service(
-                web::scope("/oauth")
-                    .route(
-                        "/github/authorize",
-                        web::get()
-                            .to(handlers::oauth::github_authorize)
-                            .wrap(crate::middleware::auth::JwtMiddleware),
-                    )
-                    .route(
-                        "/github/callback",
-                        web::get().to(handlers::oauth::github_callback),
-                    )
-                    .route(
-                        "/gitea/authorize",
-                        web::get()
-                            .to(handlers::oauth::gitea_authorize)
-                            .wrap(crate::middleware::auth::JwtMiddleware),
-                    )
-                    .route(
-                        "/gitea/callback",
-                        web::get().to(handlers::oauth::gitea_callback),
-                    )
-                    .route(
-                        "/gitlab/authorize",
-                        web::get()
-                            .to(handlers::oauth::gitlab_authorize)
-                            .wrap(crate::middleware::auth::JwtMiddleware),
-                    )
-                    .route(
-                        "/gitlab/callback",
-                        web::get().to(handlers::oauth::gitlab_callback),
-                    )
-                    // Public endpoint to list available OAuth instances
-                    .route(
-                        "/instances/{platform}",
-                        web::get().to(handlers::oauth::list_oauth_instances),
-                    ),
-            )
-            // Protected endpoints (JWT required)
-            .service(
-                web::scope("/platforms")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    .route(
-                        "/connect",
-                        web::post().to(handlers::platform_accounts::connect_platform),
-                    )
-                    .route(
-                        "",
-                        web::get().to(handlers::platform_accounts::list_platforms),
-                    )
-                    .route(
-                        "/{id}",
-                        web::delete().to(handlers::platform_accounts::disconnect_platform),
-                    )
-                    .route(
-                        "/{id}/sync-preferences",
-                        web::put().to(handlers::platform_accounts::update_sync_preferences),
-                    )
-                    .route(
-                        "/{id}/sync",
-                        web::post().to(handlers::platform_accounts::sync_platform),
-                    )
-                    .route(
-                        "/{id}/sync-async",
-                        web::post().to(handlers::platform_sync_jobs::sync_platform_async),
-                    )
-                    .route(
-                        "/sync-jobs/{job_id}",
-                        web::get().to(handlers::platform_sync_jobs::get_sync_job_status),
-                    )
-                    .route(
-                        "/sync-jobs/{job_id}",
-                        web::delete().to(handlers::platform_sync_jobs::cancel_sync_job),
-                    )
-                    .route(
-                        "/sync-jobs/{job_id}/delete",
-                        web::delete().to(handlers::platform_sync_jobs::delete_sync_job),
-                    )
-                    .route(
-                        "/sync-jobs",
-                        web::get().to(handlers::platform_sync_jobs::list_sync_jobs),
-                    ),
-            )
-            .service(
-                web::scope("/contributions")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    .route(
-                        "",
-                        web::get().to(handlers::contributions::get_contributions),
-                    )
-                    .route("/stats", web::get().to(handlers::contributions::get_stats)),
-            )
-            // Public user profile endpoints (no authentication required)
-            .service(
-                web::scope("/users")
-                    .route(
-                        "/{username}/contributions",
-                        web::get().to(handlers::contributions::get_user_contributions),
-                    )
-                    .route(
-                        "/{username}/contributions/stats",
-                        web::get().to(handlers::contributions::get_user_stats),
-                    )
-                    .route(
-                        "/{username}/platforms",
-                        web::get().to(handlers::platform_accounts::get_user_platforms),
-                    )
-                    .route(
-                        "/{username}/activities",
-                        web::get().to(handlers::activities::get_user_activities),
-                    ),
-            )
+            .wrap(sentry_actix::Sentry::new())
+            .wrap(TracingLogger::default())
+            // Liveness and readiness probes (unversioned, no authentication)
+            .route("/healthz", web::get().to(handlers::health::healthz))
+            .route("/readyz", web::get().to(handlers::health::readyz))
+            // API documentation (OpenAPI spec + Swagger UI)
             .service(
-                web::scope("/activities")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    .route("", web::get().to(handlers::activities::get_activities)),
+                SwaggerUi::new("/docs/{_:.*}")
+                    .url("/api/openapi.json", openapi::ApiDoc::openapi()),
             )
             .service(
-                web::scope("/settings")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    .route("", web::get().to(handlers::settings::get_settings))
-                    .route("", web::put().to(handlers::settings::update_settings)),
+                web::scope("/api/v1")
+                    .wrap(from_fn(middleware::versioning::negotiate_version))
+                    .configure(routes::configure),
             )
-            // Sync endpoints (JWT required)
+            // Legacy unversioned aliases, kept until the sunset date
             .service(
-                web::scope("/sync")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    .route("/trigger", web::post().to(handlers::sync::trigger_sync))
-                    .route("/status", web::get().to(handlers::sync::get_sync_status)),
-            )
-            // Heatmap theme and generation endpoints
-            .service(
-                web::scope("/heatmap")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    // Available fonts (public within auth)
-                    .route(
-                        "/fonts",
-                        web::get().to(handlers::fonts::get_available_fonts),
-                    )
-                    // Theme management
-                    .route(
-                        "/themes",
-                        web::get().to(handlers::heatmap_themes::list_themes),
-                    )
-                    .route(
-                        "/themes",
-                        web::post().to(handlers::heatmap_themes::create_theme),
-                    )
-                    .route(
-                        "/themes/{slug}",
-                        web::get().to(handlers::heatmap_themes::get_theme),
-                    )
-                    .route(
-                        "/themes/{slug}",
-                        web::put().to(handlers::heatmap_themes::update_theme),
-                    )
-                    .route(
-                        "/themes/{slug}",
-                        web::delete().to(handlers::heatmap_themes::delete_theme),
-                    )
-                    .route(
-                        "/themes/{slug}/set-default",
-                        web::post().to(handlers::heatmap_themes::set_default_theme),
-                    )
-                    .route(
-                        "/themes/{slug}/duplicate",
-                        web::post().to(handlers::heatmap_themes::duplicate_theme),
-                    )
-                    // Generation settings
-                    .route(
-                        "/settings",
-                        web::get().to(handlers::heatmap_generation::get_generation_settings),
-                    )
-                    .route(
-                        "/settings",
-                        web::put().to(handlers::heatmap_generation::update_generation_settings),
-                    )
-                    // Manual generation triggers
-                    .route(
-                        "/generate",
-                        web::post().to(handlers::heatmap_generation::trigger_generation),
-                    )
-                    .route(
-                        "/generate/{slug}",
-                        web::post().to(handlers::heatmap_generation::trigger_theme_generation),
-                    )
-                    // View generated heatmaps and jobs
-                    .route(
-                        "/generated",
-                        web::get().to(handlers::heatmap_generation::list_generated_heatmaps),
-                    )
-                    .route(
-                        "/jobs",
-                        web::get().to(handlers::heatmap_generation::list_generation_jobs),
-                    )
-                    // Preview theme (POST with theme parameters)
-                    .route(
-                        "/preview",
-                        web::post().to(handlers::heatmap_generation::preview_theme),
-                    ),
-            )
-            // Public static file endpoints (no authentication required, allow embedding anywhere)
-            .service(
-                web::scope("/static/heatmaps")
-                    .wrap(Cors::permissive())
-                    .route(
-                        "/{user_id}/{filename}",
-                        web::get().to(handlers::static_files::serve_heatmap),
-                    ),
-            )
-            .service(web::scope("/embed").wrap(Cors::permissive()).route(
-                "/{username}/{theme_file}",
-                web::get().to(handlers::static_files::serve_embed),
-            ))
-            // Admin endpoints (JWT + admin check required)
-            .service(
-                web::scope("/admin/oauth-apps")
-                    .wrap(crate::middleware::auth::JwtMiddleware)
-                    .route("", web::get().to(handlers::oauth_apps::list_oauth_apps))
-                    .route("", web::post().to(handlers::oauth_apps::create_oauth_app))
-                    .route(
-                        "/{id}",
-                        web::put().to(handlers::oauth_apps::update_oauth_app),
-                    )
-                    .route(
-                        "/{id}",
-                        web::delete().to(handlers::oauth_apps::delete_oauth_app),
-                    ),
+                web::scope("")
+                    .wrap(middleware::versioning::legacy_route_headers(
+                        config.legacy_api_sunset,
+                    ))
+                    .configure(routes::configure),
             )
     })
     .bind((host, port))?
-    .run()
-    .await
+    .disable_signals()
+    .shutdown_timeout(shutdown_timeout.as_secs())
+    .run();
+
+    // On SIGTERM or Ctrl-C stop accepting requests and let the workers wind
+    // down while in-flight requests finish
+    let server_handle = server.handle();
+    let signal_trigger = shutdown_trigger.clone();
+    tokio::spawn(async move {
+        services::shutdown::wait_for_signal().await;
+        log::info!("🛑 Shutdown requested; no longer accepting requests");
+        signal_trigger.trigger();
+        server_handle.stop(true).await;
+    });
+
+    let server = server.await;
+
+    services::shutdown::drain(&shutdown_db, &shutdown_trigger, workers, shutdown_timeout).await;
+    telemetry.shutdown();
+    server
 }
@@ -1,13 +1,19 @@
 use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
-use actix_web::{Error, HttpMessage, HttpResponse, http::header, error::ErrorUnauthorized};
+use actix_web::{Error, HttpMessage, HttpResponse, http::StatusCode, error::ErrorUnauthorized};
 use actix_web::body::EitherBody;
 use futures_util::future::LocalBoxFuture;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use std::future::{ready, Ready};
 use std::rc::Rc;
 use uuid::Uuid;
 
+use crate::models::api_token;
+use crate::services::sessions;
+use crate::utils::api_token as api_token_utils;
 use crate::utils::auth::decode_jwt;
 use crate::utils::config::Config;
+use crate::utils::error_reporting;
 
 // Re-export Claims for use in handlers
 pub use crate::utils::auth::Claims;
@@ -38,9 +44,37 @@ pub fn extract_user_from_request(req: &ServiceRequest, jwt_secret: &str) -> Resu
     Ok(AuthenticatedUser { user_id })
 }
 
-// JWT Middleware implementation
+/// What a route group requires from API tokens. JWT sessions always pass.
+#[derive(Clone, Copy)]
+enum ScopeRequirement {
+    /// API tokens are rejected
+    SessionOnly,
+    /// `read:<resource>` for safe methods, `write:<resource>` otherwise
+    Resource(&'static str),
+    /// A fixed scope regardless of method
+    Scope(&'static str),
+}
+
+// JWT Middleware implementation. Accepts JWT sessions only.
 pub struct JwtMiddleware;
 
+/// Like `JwtMiddleware`, but also accepts API tokens carrying the required scope
+pub struct ScopedAuth {
+    requirement: ScopeRequirement,
+}
+
+impl ScopedAuth {
+    /// Require `read:<resource>` or `write:<resource>` depending on the HTTP method
+    pub fn resource(resource: &'static str) -> Self {
+        Self { requirement: ScopeRequirement::Resource(resource) }
+    }
+
+    /// Require a single scope for every request
+    pub fn scope(scope: &'static str) -> Self {
+        Self { requirement: ScopeRequirement::Scope(scope) }
+    }
+}
+
 impl<S, B> Transform<S, ServiceRequest> for JwtMiddleware
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -56,12 +90,72 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(JwtMiddlewareService {
             service: Rc::new(service),
+            requirement: ScopeRequirement::SessionOnly,
+        }))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScopedAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = JwtMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(JwtMiddlewareService {
+            service: Rc::new(service),
+            requirement: self.requirement,
         }))
     }
 }
 
 pub struct JwtMiddlewareService<S> {
     service: Rc<S>,
+    requirement: ScopeRequirement,
+}
+
+fn error_response(status: StatusCode, message: &str) -> HttpResponse {
+    HttpResponse::build(status).json(serde_json::json!({ "error": message }))
+}
+
+/// Resolve an API token to claims, updating its last-used timestamp
+async fn authenticate_api_token(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<Option<Claims>, sea_orm::DbErr> {
+    let record = match api_token::Entity::find()
+        .filter(api_token::Column::Token.eq(api_token_utils::hash_token(token)))
+        .one(db)
+        .await?
+    {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    let now = Utc::now();
+    if record.expires_at.is_some_and(|exp| exp <= now) {
+        return Ok(None);
+    }
+
+    let claims = Claims {
+        sub: record.user_id.to_string(),
+        exp: record.expires_at.map(|e| e.timestamp()).unwrap_or(0),
+        iat: record.created_at.timestamp(),
+        scopes: Some(record.scopes.to_vec()),
+        sid: None,
+    };
+
+    let mut active: api_token::ActiveModel = record.into();
+    active.last_used_at = Set(Some(now));
+    active.update(db).await?;
+
+    Ok(Some(claims))
 }
 
 impl<S, B> Service<ServiceRequest> for JwtMiddlewareService<S>
@@ -78,6 +172,7 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = Rc::clone(&self.service);
+        let requirement = self.requirement;
 
         Box::pin(async move {
             log::debug!("🔐 [JWT Middleware] Checking authentication for: {} {}", req.method(), req.path());
@@ -87,12 +182,7 @@ where
                 Some(c) => c,
                 None => {
                     log::error!("❌ [JWT Middleware] Configuration not found");
-                    let response = HttpResponse::Unauthorized()
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, "http://localhost:5173"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, PUT, DELETE, OPTIONS"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_HEADERS, "Authorization, Accept, Content-Type"))
-                        .json(serde_json::json!({ "error": "Configuration not found" }));
+                    let response = error_response(StatusCode::UNAUTHORIZED, "Configuration not found");
                     return Ok(req.into_response(response).map_into_right_body());
                 }
             };
@@ -104,12 +194,7 @@ where
                 Some(h) => h,
                 None => {
                     log::warn!("⚠️  [JWT Middleware] Missing authorization header");
-                    let response = HttpResponse::Unauthorized()
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, "http://localhost:5173"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, PUT, DELETE, OPTIONS"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_HEADERS, "Authorization, Accept, Content-Type"))
-                        .json(serde_json::json!({ "error": "Missing authorization header" }));
+                    let response = error_response(StatusCode::UNAUTHORIZED, "Missing authorization header");
                     return Ok(req.into_response(response).map_into_right_body());
                 }
             };
@@ -117,36 +202,106 @@ where
             log::debug!("🔑 [JWT Middleware] Authorization header present");
 
             let token = match auth_header.strip_prefix("Bearer ") {
-                Some(t) => t,
+                Some(t) => t.to_string(),
                 None => {
                     log::error!("❌ [JWT Middleware] Invalid authorization format (missing 'Bearer ')");
-                    let response = HttpResponse::Unauthorized()
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, "http://localhost:5173"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, PUT, DELETE, OPTIONS"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_HEADERS, "Authorization, Accept, Content-Type"))
-                        .json(serde_json::json!({ "error": "Invalid authorization format" }));
+                    let response = error_response(StatusCode::UNAUTHORIZED, "Invalid authorization format");
                     return Ok(req.into_response(response).map_into_right_body());
                 }
             };
 
-            log::debug!("🔍 [JWT Middleware] Decoding JWT token");
-
-            let claims = match decode_jwt(token, jwt_secret) {
-                Ok(c) => c,
-                Err(e) => {
-                    log::error!("❌ [JWT Middleware] JWT decode error: {}", e);
-                    let response = HttpResponse::Unauthorized()
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, "http://localhost:5173"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, PUT, DELETE, OPTIONS"))
-                        .insert_header((header::ACCESS_CONTROL_ALLOW_HEADERS, "Authorization, Accept, Content-Type"))
-                        .json(serde_json::json!({ "error": "Invalid or expired token" }));
+            let claims = if api_token_utils::is_api_token(&token) {
+                log::debug!("🔍 [JWT Middleware] Checking API token");
+
+                let required = match requirement {
+                    ScopeRequirement::SessionOnly => {
+                        log::warn!("⚠️  [JWT Middleware] API token used on session-only endpoint");
+                        let response = error_response(StatusCode::FORBIDDEN, "API tokens are not accepted for this endpoint");
+                        return Ok(req.into_response(response).map_into_right_body());
+                    }
+                    ScopeRequirement::Resource(resource) => api_token_utils::required_scope(resource, req.method()),
+                    ScopeRequirement::Scope(scope) => scope.to_string(),
+                };
+
+                let db = match req.app_data::<actix_web::web::Data<DatabaseConnection>>() {
+                    Some(db) => db.clone(),
+                    None => {
+                        log::error!("❌ [JWT Middleware] Database connection not found");
+                        let response = error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+                        return Ok(req.into_response(response).map_into_right_body());
+                    }
+                };
+
+                let claims = match authenticate_api_token(db.as_ref(), &token).await {
+                    Ok(Some(c)) => c,
+                    Ok(None) => {
+                        log::error!("❌ [JWT Middleware] Unknown or expired API token");
+                        let response = error_response(StatusCode::UNAUTHORIZED, "Invalid or expired token");
+                        return Ok(req.into_response(response).map_into_right_body());
+                    }
+                    Err(e) => {
+                        log::error!("Database error: {}", e);
+                        let response = error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+                        return Ok(req.into_response(response).map_into_right_body());
+                    }
+                };
+
+                let granted = claims.scopes.as_deref().unwrap_or_default();
+                if !api_token_utils::grants(granted, &required) {
+                    log::warn!("⚠️  [JWT Middleware] API token lacks scope: {}", required);
+                    let response = error_response(
+                        StatusCode::FORBIDDEN,
+                        &format!("Token is missing required scope: {}", required),
+                    );
                     return Ok(req.into_response(response).map_into_right_body());
                 }
+
+                claims
+            } else {
+                log::debug!("🔍 [JWT Middleware] Decoding JWT token");
+
+                let claims = match decode_jwt(&token, jwt_secret) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        log::error!("❌ [JWT Middleware] JWT decode error: {}", e);
+                        let response = error_response(StatusCode::UNAUTHORIZED, "Invalid or expired token");
+                        return Ok(req.into_response(response).map_into_right_body());
+                    }
+                };
+
+                // Access tokens stop working as soon as their session is revoked
+                if let Some(sid) = &claims.sid {
+                    let db = match req.app_data::<actix_web::web::Data<DatabaseConnection>>() {
+                        Some(db) => db.clone(),
+                        None => {
+                            log::error!("❌ [JWT Middleware] Database connection not found");
+                            let response = error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+                            return Ok(req.into_response(response).map_into_right_body());
+                        }
+                    };
+
+                    let session_id = Uuid::parse_str(sid).unwrap_or_default();
+                    match sessions::is_active(db.as_ref(), session_id).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            log::warn!("⚠️  [JWT Middleware] Session {} has been revoked", sid);
+                            let response = error_response(StatusCode::UNAUTHORIZED, "Session has been revoked");
+                            return Ok(req.into_response(response).map_into_right_body());
+                        }
+                        Err(e) => {
+                            log::error!("Database error: {}", e);
+                            let response = error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+                            return Ok(req.into_response(response).map_into_right_body());
+                        }
+                    }
+                }
+
+                claims
             };
 
-            log::debug!("✅ [JWT Middleware] JWT validated for user: {}", claims.sub);
+            log::debug!("✅ [JWT Middleware] Authenticated user: {}", claims.sub);
+
+            error_reporting::set_user(&claims.sub);
 
             // Insert claims into request extensions for handlers to access
             req.extensions_mut().insert(claims);
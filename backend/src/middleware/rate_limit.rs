@@ -0,0 +1,146 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::{web, Error, HttpResponse};
+use futures_util::future::LocalBoxFuture;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use crate::services::rate_limiter::{Budget, RateLimiter};
+use crate::utils::auth::decode_jwt;
+use crate::utils::client_ip::client_ip;
+use crate::utils::config::Config;
+use crate::utils::token_bucket::Decision;
+
+/// Limit requests to a route group. Requests are counted per user when a
+/// valid JWT is sent, otherwise per client address. A no-op when rate
+/// limiting is disabled.
+pub struct RateLimit {
+    budget: Budget,
+}
+
+impl RateLimit {
+    pub fn new(budget: Budget) -> Self {
+        Self { budget }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitService {
+            service: Rc::new(service),
+            budget: self.budget,
+        }))
+    }
+}
+
+pub struct RateLimitService<S> {
+    service: Rc<S>,
+    budget: Budget,
+}
+
+/// Who the request is counted against
+fn identity(req: &ServiceRequest) -> String {
+    let config = req.app_data::<web::Data<Config>>();
+    let user = config
+        .and_then(|config| {
+            let token = req
+                .headers()
+                .get(header::AUTHORIZATION)?
+                .to_str()
+                .ok()?
+                .strip_prefix("Bearer ")?;
+            decode_jwt(token, &config.jwt_secret).ok()
+        })
+        .map(|claims| claims.sub);
+
+    match user {
+        Some(user_id) => format!("user:{}", user_id),
+        None => match client_ip(req.request(), config.map_or(&[], |c| &c.trusted_proxies)) {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        },
+    }
+}
+
+fn insert_rate_limit_headers(headers: &mut HeaderMap, decision: &Decision) {
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-limit"),
+        HeaderValue::from(decision.limit),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-remaining"),
+        HeaderValue::from(decision.remaining),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-reset"),
+        HeaderValue::from(decision.reset_after_secs),
+    );
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let budget = self.budget;
+
+        Box::pin(async move {
+            let Some(limiter) = req
+                .app_data::<web::Data<RateLimiter>>()
+                .filter(|l| l.is_enabled())
+                .cloned()
+            else {
+                let res = service.call(req).await?;
+                return Ok(res.map_into_left_body());
+            };
+
+            let identity = identity(&req);
+            let decision = match limiter.check(budget, &identity).await {
+                Ok(d) => d,
+                Err(e) => {
+                    // Fail open: a broken limiter backend must not take the site down
+                    log::error!("❌ [Rate Limit] Failed to check {} budget: {}", budget.name(), e);
+                    let res = service.call(req).await?;
+                    return Ok(res.map_into_left_body());
+                }
+            };
+
+            if !decision.allowed {
+                log::warn!("⚠️  [Rate Limit] {} exceeded {} budget on {}", identity, budget.name(), req.path());
+                let mut response = HttpResponse::TooManyRequests()
+                    .insert_header((header::RETRY_AFTER, decision.retry_after_secs))
+                    .json(serde_json::json!({
+                        "error": "Too many requests",
+                        "retry_after": decision.retry_after_secs,
+                    }));
+                insert_rate_limit_headers(response.headers_mut(), &decision);
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            let mut res = service.call(req).await?;
+            insert_rate_limit_headers(res.headers_mut(), &decision);
+            Ok(res.map_into_left_body())
+        })
+    }
+}
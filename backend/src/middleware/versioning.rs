@@ -0,0 +1,43 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::{DefaultHeaders, Next};
+use actix_web::{Error, HttpResponse};
+use chrono::NaiveDate;
+
+use crate::utils::api_version;
+
+/// Reject requests asking for an unsupported API version and tag every
+/// response with the version that served it. Wrap with `from_fn`.
+pub async fn negotiate_version(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let version = match api_version::negotiate(req.headers()) {
+        Ok(v) => v,
+        Err(e) => {
+            log::warn!("⚠️  Rejected request for {}: {}", req.path(), e);
+            let response = HttpResponse::NotAcceptable().json(serde_json::json!({ "error": e }));
+            return Ok(req.into_response(response));
+        }
+    };
+
+    let mut res = next.call(req).await?.map_into_boxed_body();
+    res.headers_mut().insert(
+        HeaderName::from_static("api-version"),
+        HeaderValue::from(version),
+    );
+    Ok(res)
+}
+
+/// Headers announcing that the unversioned root routes are deprecated in
+/// favour of /api/v1 and will be removed after `sunset`
+pub fn legacy_route_headers(sunset: NaiveDate) -> DefaultHeaders {
+    DefaultHeaders::new()
+        .add((
+            "Deprecation",
+            api_version::structured_date(api_version::legacy_deprecated_since()),
+        ))
+        .add(("Sunset", api_version::http_date(sunset)))
+        .add(("Link", "</api/v1>; rel=\"successor-version\""))
+}
@@ -23,6 +23,9 @@ pub enum ActivityType {
     Release,
     #[sea_orm(string_value = "star")]
     Star,
+    /// Recorded by `services::milestones`, not synced from a platform
+    #[sea_orm(string_value = "milestone")]
+    Milestone,
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -41,6 +44,10 @@ pub struct Model {
     pub primary_language: Option<String>,
     pub organization_name: Option<String>,
     pub organization_avatar_url: Option<String>,
+    /// Identifies the activity on its platform; see `services::activity_store`
+    pub natural_key: Option<String>,
+    /// Backfilled from an uploaded event file; see `services::activity_import`
+    pub imported: bool,
     pub created_at: ChronoDateTimeUtc,
     pub updated_at: ChronoDateTimeUtc,
 }
@@ -0,0 +1,53 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub use super::platform_sync_job::SyncJobStatus;
+
+/// An uploaded event file being turned into activities of a platform account
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "activity_imports")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub platform_account_id: Uuid,
+    pub status: SyncJobStatus,
+
+    // Progress tracking
+    /// Every event in the file
+    pub events_read: i32,
+    /// The account's events that map to activities
+    pub events_matched: i32,
+    /// Activities built from them; imported plus skipped reach it when done
+    pub activities_total: i32,
+    pub activities_imported: i32,
+    /// Activities already stored, e.g. by a sync or an earlier import
+    pub duplicates_skipped: i32,
+
+    pub error_message: Option<String>,
+    pub created_at: ChronoDateTimeUtc,
+    pub started_at: Option<ChronoDateTimeUtc>,
+    pub completed_at: Option<ChronoDateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::git_platform_account::Entity",
+        from = "Column::PlatformAccountId",
+        to = "super::git_platform_account::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    PlatformAccount,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,15 +1,20 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::json_list::JsonList;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "api_tokens")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
     pub user_id: Uuid,
+    /// SHA-256 hash of the token; the token itself is only shown once
     #[sea_orm(unique)]
     pub token: String,
     pub name: Option<String>,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub scopes: JsonList<String>,
     pub created_at: ChronoDateTimeUtc,
     pub expires_at: Option<ChronoDateTimeUtc>,
     pub last_used_at: Option<ChronoDateTimeUtc>,
@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One commit of an account with detailed sync on, see
+/// `services::commit_details`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "commit_details")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub account_id: Uuid,
+    /// `owner/repo`, as in contributions
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub repository_name: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub sha: String,
+    pub committed_at: ChronoDateTimeUtc,
+    /// None when the platform does not tell
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    pub is_private_repo: bool,
+    /// The user is a co-author through a Co-authored-by trailer, not the
+    /// author
+    pub co_authored: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::git_platform_account::Entity",
+        from = "Column::AccountId",
+        to = "super::git_platform_account::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Account,
+}
+
+impl Related<super::git_platform_account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
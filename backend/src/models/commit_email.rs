@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// An email address the user commits with, matched against commit authors
+/// and Co-authored-by trailers by `services::commit_details` once verified
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "commit_emails")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: Uuid,
+    /// Lowercase
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub email: String,
+    pub created_at: ChronoDateTimeUtc,
+    /// None until the link mailed to the address is opened
+    pub verified_at: Option<ChronoDateTimeUtc>,
+    /// SHA-256 of the pending confirmation token
+    pub token_hash: Option<String>,
+    pub token_expires_at: Option<ChronoDateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
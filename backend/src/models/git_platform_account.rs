@@ -48,6 +48,12 @@ pub struct Model {
     // Sync preferences
     pub sync_profile: bool,
     pub sync_contributions: bool, // When enabled, syncs both contributions and activities
+    /// Also store individual commits in commit_details
+    pub detailed_sync: bool,
+    /// Days of commit details kept; None keeps them
+    pub commit_retention_days: Option<i32>,
+    /// First year all-years syncs go back to
+    pub earliest_sync_year: i32,
     // Authentication method
     pub auth_type: AuthType,
 }
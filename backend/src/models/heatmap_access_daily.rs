@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Views of one theme's heatmaps on one day from one referring site and kind
+/// of client, see `services::heatmap_analytics`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "heatmap_access_daily")]
+pub struct Model {
+    pub user_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub theme_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub access_date: Date,
+    /// Empty when the request had no Referer header
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub referrer_host: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub client: String,
+    pub views: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::heatmap_theme::Entity",
+        from = "Column::ThemeId",
+        to = "super::heatmap_theme::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Theme,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::heatmap_theme::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Theme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,6 +12,9 @@ pub enum GenerationJobStatus {
     Completed,
     #[sea_orm(string_value = "failed")]
     Failed,
+    /// Ran out of retries; stays until requeued
+    #[sea_orm(string_value = "dead_letter")]
+    DeadLetter,
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -24,6 +24,13 @@ pub struct Model {
     // Storage path customization
     pub storage_path: Option<String>,
 
+    // When scheduled runs may start: an IANA timezone (UTC if unset), a
+    // daily window in minutes after midnight, or a cron expression
+    pub timezone: Option<String>,
+    pub generation_window_start_minutes: Option<i32>,
+    pub generation_window_end_minutes: Option<i32>,
+    pub generation_cron: Option<String>,
+
     // Scheduling tracking
     pub last_scheduled_generation_at: Option<ChronoDateTimeUtc>,
     pub next_scheduled_generation_at: Option<ChronoDateTimeUtc>,
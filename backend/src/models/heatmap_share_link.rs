@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A signed link to one theme's heatmap in one format. It points at the theme
+/// rather than a generated file, since regenerating replaces the file.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "heatmap_share_links")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub theme_id: Uuid,
+    pub format: super::heatmap_theme::HeatmapFormat,
+    /// Never expires when None
+    pub expires_at: Option<ChronoDateTimeUtc>,
+    /// Unlimited when None
+    pub max_views: Option<i32>,
+    pub view_count: i32,
+    pub created_at: ChronoDateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::heatmap_theme::Entity",
+        from = "Column::ThemeId",
+        to = "super::heatmap_theme::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Theme,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::heatmap_theme::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Theme.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::json_list::{JsonList, JsonListItem};
+
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "heatmap_color_scheme")]
 pub enum HeatmapColorScheme {
@@ -48,6 +50,19 @@ pub enum HeatmapFormat {
     WebP,
 }
 
+/// Stored by database value ("png"), not by serde name
+impl JsonListItem for HeatmapFormat {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::String(self.to_value())
+    }
+
+    fn from_json(value: &serde_json::Value) -> Option<Self> {
+        value
+            .as_str()
+            .and_then(|format| Self::try_from_value(&format.to_string()).ok())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "heatmap_themes")]
 pub struct Model {
@@ -112,8 +127,20 @@ pub struct Model {
     // Legend settings
     pub legend_position: String,
 
+    // Renderer that draws the heatmap, see services::heatmap_renderers
+    pub layout: String,
+    /// minijinja template producing the whole SVG, used instead of the layout
+    pub custom_template: Option<String>,
+    /// Days of the theme's mini heatmap, 30 or 90; None generates none
+    pub mini_days: Option<i32>,
+    /// Whether the stats card lists the user's achievements
+    pub show_achievements: bool,
+    /// Whether a grid draws lines added and removed per week under the cells
+    pub show_code_frequency: bool,
+
     // Output formats (array of formats to generate)
-    pub output_formats: Vec<HeatmapFormat>,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub output_formats: JsonList<HeatmapFormat>,
 
     pub created_at: ChronoDateTimeUtc,
     pub updated_at: ChronoDateTimeUtc,
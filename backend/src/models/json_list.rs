@@ -0,0 +1,130 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::{ArrayType, Nullable, ValueType, ValueTypeErr};
+use sea_orm::{ColIdx, QueryResult, TryGetError, TryGetable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::ops::{Deref, DerefMut};
+
+/// A list column stored as a JSON array rather than a Postgres array, so the
+/// same entities work on every supported database
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonList<T>(pub Vec<T>);
+
+/// How an item of a [`JsonList`] is written to and read from the database
+pub trait JsonListItem: Sized {
+    fn to_json(&self) -> JsonValue;
+    fn from_json(value: &JsonValue) -> Option<Self>;
+}
+
+impl JsonListItem for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.clone())
+    }
+
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+}
+
+impl<T: JsonListItem> JsonList<T> {
+    fn from_json(value: JsonValue) -> Result<Self, String> {
+        let JsonValue::Array(items) = value else {
+            return Err(format!("expected a JSON array, got {}", value));
+        };
+
+        items
+            .iter()
+            .map(|item| T::from_json(item).ok_or_else(|| format!("unexpected list item {}", item)))
+            .collect::<Result<_, _>>()
+            .map(JsonList)
+    }
+}
+
+impl<T> Default for JsonList<T> {
+    fn default() -> Self {
+        JsonList(Vec::new())
+    }
+}
+
+impl<T> Deref for JsonList<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for JsonList<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<T>> for JsonList<T> {
+    fn from(items: Vec<T>) -> Self {
+        JsonList(items)
+    }
+}
+
+impl<T> From<JsonList<T>> for Vec<T> {
+    fn from(list: JsonList<T>) -> Self {
+        list.0
+    }
+}
+
+impl<T> FromIterator<T> for JsonList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JsonList(iter.into_iter().collect())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a JsonList<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T: JsonListItem> From<JsonList<T>> for Value {
+    fn from(list: JsonList<T>) -> Self {
+        let items = list.iter().map(T::to_json).collect();
+        Value::Json(Some(Box::new(JsonValue::Array(items))))
+    }
+}
+
+impl<T: JsonListItem> TryGetable for JsonList<T> {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let value = JsonValue::try_get_by(res, index)?;
+        Self::from_json(value).map_err(|e| TryGetError::DbErr(DbErr::Type(e)))
+    }
+}
+
+impl<T: JsonListItem> ValueType for JsonList<T> {
+    fn try_from(value: Value) -> Result<Self, ValueTypeErr> {
+        match value {
+            Value::Json(Some(json)) => Self::from_json(*json).map_err(|_| ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "JsonList".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::Json
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::JsonBinary
+    }
+}
+
+impl<T> Nullable for JsonList<T> {
+    fn null() -> Value {
+        Value::Json(None)
+    }
+}
@@ -1,13 +1,43 @@
+pub mod achievement;
 pub mod activity;
+pub mod activity_import;
+pub mod annotation;
+pub mod announcement;
 pub mod api_token;
+pub mod commit_detail;
+pub mod commit_email;
 pub mod contribution;
+pub mod email_change;
+pub mod email_digest;
+pub mod follow;
 pub mod generated_heatmap;
 pub mod git_platform_account;
+pub mod heatmap_access_daily;
 pub mod heatmap_generation_job;
 pub mod heatmap_generation_setting;
+pub mod heatmap_share_link;
 pub mod heatmap_theme;
+pub mod instance_setting;
+pub mod invitation;
+pub mod json_list;
+pub mod milestone;
+pub mod mqtt_publisher;
+pub mod notification_channel;
 pub mod oauth_application;
+pub mod oauth_application_setting;
 pub mod oauth_state;
+pub mod organization_membership;
+pub mod platform_account_disconnection;
+pub mod platform_account_label;
+pub mod platform_account_visibility;
+pub mod platform_api_usage_hourly;
 pub mod platform_sync_job;
+pub mod repository_language;
+pub mod session;
+pub mod team;
+pub mod team_member;
 pub mod user;
+pub mod user_identity;
+pub mod user_job_limit;
 pub mod user_setting;
+pub mod username_redirect;
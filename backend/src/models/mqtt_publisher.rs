@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A user's MQTT broker, which `services::mqtt_publisher` sends daily totals
+/// and the streak to after each sync. Users without a row publish nothing.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "mqtt_publishers")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: Uuid,
+    /// `mqtt://` or `mqtts://` with host and optional port
+    pub broker_url: String,
+    pub username: Option<String>,
+    /// Encrypted
+    pub password: Option<String>,
+    /// Prefix of the published topics, without a trailing slash
+    pub topic: String,
+    /// Whether Home Assistant discovery configs are published too
+    pub home_assistant_discovery: bool,
+    pub last_published_at: Option<ChronoDateTimeUtc>,
+    /// Why the last attempt failed; None when it succeeded
+    pub last_error: Option<String>,
+    pub created_at: ChronoDateTimeUtc,
+    pub updated_at: ChronoDateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,57 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::json_list::JsonList;
+
+/// A place the user is notified at through `services::notification_channels`:
+/// the weekly summary from `services::weekly_summary`, and the other events
+/// it routes
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "notification_channels")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// One of `services::notification_channels::KINDS`
+    pub kind: String,
+    /// The Mastodon instance or the Bluesky PDS, without a trailing slash;
+    /// the webhook URL; only the host of Slack and Discord webhooks; empty
+    /// for email and MQTT
+    pub instance_url: String,
+    /// The handle posted as, which Bluesky also signs in with; for other
+    /// kinds what the user recognizes the channel by
+    pub account: String,
+    /// Encrypted Mastodon access token, Bluesky app password, webhook
+    /// signing secret, or Slack or Discord webhook URL; empty for email and
+    /// MQTT
+    pub secret: String,
+    /// End of the week the last summary covered; set to the creation time
+    /// before the first one
+    pub last_sent_at: ChronoDateTimeUtc,
+    /// Why the last post failed; None when it succeeded
+    pub last_error: Option<String>,
+    pub created_at: ChronoDateTimeUtc,
+    /// The `services::notification_channels::EVENTS` sent to the channel
+    #[sea_orm(column_type = "JsonBinary")]
+    pub events: JsonList<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
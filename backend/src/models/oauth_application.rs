@@ -16,6 +16,16 @@ pub struct Model {
     pub created_by: Option<Uuid>,
     pub created_at: ChronoDateTimeUtc,
     pub updated_at: ChronoDateTimeUtc,
+    /// Shown in the connect screen instead of `instance_name`
+    pub display_name: Option<String>,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    /// Help shown with the button, e.g. which account to sign in with
+    pub login_hint: Option<String>,
+    /// Position in the listing, lowest first, after the featured apps
+    pub sort_order: i32,
+    /// Listed first and highlighted in the connect screen
+    pub is_featured: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Scopes and redirect URI an admin set for an OAuth app. Apps without a
+/// row, and NULL fields, use the defaults.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "oauth_application_settings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub application_id: Uuid,
+    /// Requested instead of the scopes hgitmap needs
+    pub scopes: Option<String>,
+    /// Sent instead of the callback route under BASE_URL
+    pub redirect_uri: Option<String>,
+    pub updated_at: ChronoDateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::oauth_application::Entity",
+        from = "Column::ApplicationId",
+        to = "super::oauth_application::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Application,
+}
+
+impl Related<super::oauth_application::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Application.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
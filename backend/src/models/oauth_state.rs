@@ -9,11 +9,17 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: Uuid,
     pub state_token: String,
-    pub user_id: Uuid,
-    pub platform: GitPlatform,
+    /// None for sign-in flows, where the user is not known yet
+    pub user_id: Option<Uuid>,
+    /// None for OIDC sign-in and login codes
+    pub platform: Option<GitPlatform>,
     pub created_at: ChronoDateTimeUtc,
     pub expires_at: ChronoDateTimeUtc,
     pub instance_url: Option<String>, // For self-hosted instances (Gitea/GitLab)
+    /// What the callback should do: connect, login, link, oidc or login_code
+    pub purpose: String,
+    /// Nonce the OIDC ID token must contain
+    pub nonce: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
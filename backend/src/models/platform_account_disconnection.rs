@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A disconnected platform account that can still be restored. The account
+/// stays inactive, without a token, until it is restored or purged.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "platform_account_disconnections")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub platform_account_id: Uuid,
+    pub disconnected_at: ChronoDateTimeUtc,
+    /// The cleanup job deletes the account and its data after this
+    pub purge_after: ChronoDateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::git_platform_account::Entity",
+        from = "Column::PlatformAccountId",
+        to = "super::git_platform_account::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    PlatformAccount,
+}
+
+impl Related<super::git_platform_account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlatformAccount.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
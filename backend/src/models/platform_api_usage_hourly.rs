@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Platform API calls made for one account in one hour to one kind of API,
+/// see `services::api_usage`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "platform_api_usage_hourly")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub platform_account_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub period_start: ChronoDateTimeUtc,
+    /// `rest` or `graphql`
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub api: String,
+    pub requests: i32,
+    /// Requests that got no response or a 5xx
+    pub failed: i32,
+    pub rate_limited: i32,
+    /// The rate limit the platform reported last in the hour, if any
+    pub rate_limit_remaining: Option<i32>,
+    pub rate_limit_limit: Option<i32>,
+    pub rate_limit_reset_at: Option<ChronoDateTimeUtc>,
+    pub last_called_at: ChronoDateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::git_platform_account::Entity",
+        from = "Column::PlatformAccountId",
+        to = "super::git_platform_account::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    PlatformAccount,
+}
+
+impl Related<super::git_platform_account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PlatformAccount.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -8,9 +8,15 @@ pub struct Model {
     pub id: Uuid,
     #[sea_orm(unique)]
     pub username: String,
-    pub password_hash: String,
+    /// None for users who only sign in through an identity provider
+    pub password_hash: Option<String>,
     pub email: Option<String>,
     pub is_admin: bool,
+    /// Subject of the OIDC single sign-on identity, if any
+    #[sea_orm(unique)]
+    pub oidc_subject: Option<String>,
+    /// When the username was last changed, for the rename cooldown
+    pub username_changed_at: Option<ChronoDateTimeUtc>,
     pub created_at: ChronoDateTimeUtc,
     pub updated_at: ChronoDateTimeUtc,
 }
@@ -23,6 +29,10 @@ pub enum Relation {
     UserSettings,
     #[sea_orm(has_many = "super::api_token::Entity")]
     ApiTokens,
+    #[sea_orm(has_many = "super::user_identity::Entity")]
+    Identities,
+    #[sea_orm(has_many = "super::session::Entity")]
+    Sessions,
 }
 
 impl Related<super::git_platform_account::Entity> for Entity {
@@ -43,4 +53,16 @@ impl Related<super::api_token::Entity> for Entity {
     }
 }
 
+impl Related<super::user_identity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Identities.def()
+    }
+}
+
+impl Related<super::session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sessions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
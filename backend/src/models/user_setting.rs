@@ -1,5 +1,26 @@
+use chrono::NaiveDate;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::json_list::{JsonList, JsonListItem};
+
+/// Days the user was away, both ends included, see `utils::away_periods`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub struct AwayPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl JsonListItem for AwayPeriod {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "start": self.start, "end": self.end })
+    }
+
+    fn from_json(value: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(value.clone()).ok()
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "user_settings")]
@@ -13,6 +34,28 @@ pub struct Model {
     pub heatmap_color_scheme: String,
     pub heatmap_size: String,
     pub dark_mode_enabled: bool,
+    /// `public`, `unlisted` or `private`, see `services::profile_visibility`
+    pub profile_visibility: String,
+    /// Repository patterns hidden from the public profile without deleting
+    /// synced data, see `utils::repo_exclusion`
+    #[sea_orm(column_type = "JsonBinary")]
+    pub excluded_repositories: JsonList<String>,
+    /// Referrer domains allowed to embed the heatmap images, see
+    /// `utils::embed_domains`; empty allows any site
+    #[sea_orm(column_type = "JsonBinary")]
+    pub embed_allowed_domains: JsonList<String>,
+    /// Days of activities to keep, capped by the instance policy; None
+    /// follows the instance, see `services::retention`
+    pub activity_retention_days: Option<i32>,
+    /// Listed in the public user directory; only honored for public profiles
+    pub listed_in_directory: bool,
+    /// Days in these periods do not break a streak
+    #[sea_orm(column_type = "JsonBinary")]
+    pub away_periods: JsonList<AwayPeriod>,
+    /// Heatmaps draw away days without contributions hatched
+    pub hatch_away_days: bool,
+    /// Receives a POST for each milestone reached; see `services::milestones`
+    pub milestone_webhook_url: Option<String>,
     pub created_at: ChronoDateTimeUtc,
     pub updated_at: ChronoDateTimeUtc,
 }
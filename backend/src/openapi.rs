@@ -0,0 +1,230 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::handlers;
+
+/// OpenAPI description of the HTTP API, served at /api/openapi.json
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "hgitmap API",
+        description = "Unified contribution heatmaps across GitHub, GitLab and Gitea"
+    ),
+    servers(
+        (url = "/api/v1", description = "Current version"),
+        (url = "/", description = "Deprecated unversioned aliases")
+    ),
+    paths(
+        handlers::health::healthz,
+        handlers::health::readyz,
+        handlers::auth::register,
+        handlers::auth::login,
+        handlers::auth::captcha_config,
+        handlers::auth::refresh,
+        handlers::auth::logout,
+        handlers::oauth::login_authorize,
+        handlers::oauth::link_authorize,
+        handlers::oauth::exchange_login_code,
+        handlers::oidc::oidc_status,
+        handlers::oidc::oidc_authorize,
+        handlers::oidc::oidc_callback,
+        handlers::identities::list_identities,
+        handlers::identities::delete_identity,
+        handlers::oauth::github_authorize,
+        handlers::oauth::github_callback,
+        handlers::oauth::gitea_authorize,
+        handlers::oauth::gitea_callback,
+        handlers::oauth::gitlab_authorize,
+        handlers::oauth::gitlab_callback,
+        handlers::oauth::list_oauth_instances,
+        handlers::platform_accounts::connect_platform,
+        handlers::platform_accounts::validate_instance,
+        handlers::platform_accounts::list_platforms,
+        handlers::platform_accounts::disconnect_platform,
+        handlers::platform_accounts::list_disconnected_platforms,
+        handlers::platform_accounts::restore_platform,
+        handlers::platform_accounts::update_sync_preferences,
+        handlers::platform_accounts::get_user_platforms,
+        handlers::platform_accounts::get_user_organizations,
+        handlers::platform_accounts::get_visibility,
+        handlers::platform_accounts::update_visibility,
+        handlers::platform_accounts::get_capabilities,
+        handlers::platform_accounts::get_api_usage,
+        handlers::platform_accounts::get_commit_storage,
+        handlers::platform_accounts::update_label,
+        handlers::platform_accounts::merge_accounts,
+        handlers::platform_sync_jobs::sync_platform,
+        handlers::platform_sync_jobs::get_sync_job_status,
+        handlers::platform_sync_jobs::list_sync_jobs,
+        handlers::platform_sync_jobs::cancel_sync_job,
+        handlers::platform_sync_jobs::delete_sync_job,
+        handlers::activity_imports::import_activities,
+        handlers::activity_imports::get_import,
+        handlers::activity_imports::list_imports,
+        handlers::contributions::get_contributions,
+        handlers::contributions::get_stats,
+        handlers::contributions::get_hours,
+        handlers::contributions::get_code_frequency,
+        handlers::contributions::get_contributions_by_organization,
+        handlers::directory::list_users,
+        handlers::contributions::get_user_contributions,
+        handlers::contributions::get_user_stats,
+        handlers::activities::get_activities,
+        handlers::achievements::get_user_achievements,
+        handlers::languages::get_user_languages,
+        handlers::today::get_user_today,
+        handlers::today::user_today_events,
+        handlers::activities::get_user_activities,
+        handlers::activities::get_user_activities_feed,
+        handlers::activities::get_feed,
+        handlers::milestones::list_milestones,
+        handlers::follows::list_following,
+        handlers::follows::follow_user,
+        handlers::follows::unfollow_user,
+        handlers::settings::get_settings,
+        handlers::settings::update_settings,
+        handlers::settings::create_embed_token,
+        handlers::settings::get_retention,
+        handlers::settings::get_usage,
+        handlers::settings::get_digest,
+        handlers::settings::update_digest,
+        handlers::commit_emails::list_commit_emails,
+        handlers::commit_emails::add_commit_email,
+        handlers::commit_emails::resend_commit_email_verification,
+        handlers::commit_emails::confirm_commit_email,
+        handlers::commit_emails::delete_commit_email,
+        handlers::mqtt::get_mqtt,
+        handlers::mqtt::update_mqtt,
+        handlers::mqtt::delete_mqtt,
+        handlers::mqtt::test_mqtt,
+        handlers::notification_channels::list_notification_channels,
+        handlers::notification_channels::create_notification_channel,
+        handlers::notification_channels::update_notification_channel,
+        handlers::notification_channels::test_notification_channel,
+        handlers::notification_channels::delete_notification_channel,
+        handlers::sessions::list_sessions,
+        handlers::sessions::revoke_session,
+        handlers::account::delete_account,
+        handlers::account::change_password,
+        handlers::account::change_username,
+        handlers::account::request_email_change,
+        handlers::account::confirm_email_change,
+        handlers::sync::trigger_sync,
+        handlers::sync::get_sync_status,
+        handlers::fonts::get_available_fonts,
+        handlers::heatmap_themes::list_themes,
+        handlers::heatmap_themes::get_theme,
+        handlers::heatmap_themes::create_theme,
+        handlers::heatmap_themes::update_theme,
+        handlers::heatmap_themes::delete_theme,
+        handlers::heatmap_themes::set_default_theme,
+        handlers::heatmap_themes::duplicate_theme,
+        handlers::heatmap_generation::get_generation_settings,
+        handlers::heatmap_generation::update_generation_settings,
+        handlers::heatmap_generation::trigger_generation,
+        handlers::heatmap_generation::trigger_theme_generation,
+        handlers::heatmap_generation::render_heatmap,
+        handlers::heatmap_generation::list_generated_heatmaps,
+        handlers::heatmap_generation::share_generated_heatmap,
+        handlers::heatmap_generation::list_share_links,
+        handlers::heatmap_generation::delete_share_link,
+        handlers::heatmap_generation::list_generation_jobs,
+        handlers::heatmap_generation::retry_generation_job,
+        handlers::heatmap_generation::get_heatmap_analytics,
+        handlers::heatmap_generation::preview_theme,
+        handlers::static_files::serve_heatmap,
+        handlers::static_files::serve_embed,
+        handlers::static_files::serve_embed_script,
+        handlers::static_files::serve_embed_data,
+        handlers::static_files::serve_text_heatmap,
+        handlers::static_files::serve_heatmap_json,
+        handlers::static_files::serve_emoji_heatmap,
+        handlers::static_files::serve_diff_heatmap,
+        handlers::static_files::serve_share_link,
+        handlers::annotations::list_annotations,
+        handlers::annotations::create_annotation,
+        handlers::annotations::update_annotation,
+        handlers::annotations::delete_annotation,
+        handlers::teams::list_teams,
+        handlers::teams::create_team,
+        handlers::teams::get_team,
+        handlers::teams::update_team,
+        handlers::teams::delete_team,
+        handlers::teams::add_member,
+        handlers::teams::remove_member,
+        handlers::teams::get_team_heatmap,
+        handlers::teams::get_team_stats,
+        handlers::events::events,
+        handlers::api_tokens::list_tokens,
+        handlers::api_tokens::create_token,
+        handlers::api_tokens::delete_token,
+        handlers::oauth_apps::list_oauth_apps,
+        handlers::oauth_apps::create_oauth_app,
+        handlers::oauth_apps::update_oauth_app,
+        handlers::oauth_apps::reorder_oauth_apps,
+        handlers::oauth_apps::delete_oauth_app,
+        handlers::oauth_apps::test_oauth_app,
+        handlers::invitations::list_invitations,
+        handlers::invitations::create_invitation,
+        handlers::invitations::update_invitation,
+        handlers::invitations::delete_invitation,
+        handlers::retention::preview_retention,
+        handlers::retention::run_retention,
+        handlers::cleanup::preview_cleanup,
+        handlers::cleanup::run_cleanup,
+        handlers::api_usage::list_api_usage,
+        handlers::key_rotation::preview_key_rotation,
+        handlers::key_rotation::run_key_rotation,
+        handlers::instance_settings::get_instance_settings,
+        handlers::instance_settings::update_instance_settings,
+        handlers::announcements::list_active_announcements,
+        handlers::announcements::list_announcements,
+        handlers::announcements::create_announcement,
+        handlers::announcements::update_announcement,
+        handlers::announcements::delete_announcement,
+        handlers::admin_jobs::list_jobs,
+        handlers::admin_jobs::cancel_job,
+        handlers::admin_jobs::retry_job,
+        handlers::admin_jobs::get_job_concurrency,
+        handlers::admin_jobs::set_job_concurrency,
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "auth", description = "Registration, login and sign-in identities"),
+        (name = "oauth", description = "OAuth flows for connecting platform accounts"),
+        (name = "platforms", description = "Connected platform accounts and sync jobs"),
+        (name = "contributions", description = "Contribution data of the current user"),
+        (name = "activities", description = "Activity timeline of the current user"),
+        (name = "annotations", description = "Private notes on dates of the user's graph"),
+        (name = "users", description = "Public profile data"),
+        (name = "settings", description = "Privacy settings and login sessions"),
+        (name = "sync", description = "Automatic sync"),
+        (name = "heatmap", description = "Themes and heatmap generation"),
+        (name = "teams", description = "Teams and their combined heatmaps"),
+        (name = "events", description = "Live dashboard updates over server-sent events"),
+        (name = "tokens", description = "Scoped API tokens for integrations"),
+        (name = "embed", description = "Public heatmap images"),
+        (name = "announcements", description = "Instance-wide banners"),
+        (name = "admin", description = "Instance administration")
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the JWT bearer scheme referenced by protected endpoints
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
@@ -0,0 +1,668 @@
+use actix_cors::Cors;
+use actix_web::web;
+
+use crate::handlers;
+use crate::middleware::rate_limit::RateLimit;
+use crate::services::rate_limiter::Budget;
+
+/// Register every API route. Mounted once under /api/v1 and once at the root
+/// for clients that predate versioning.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // Public endpoints (no authentication required)
+    cfg.service(
+        web::scope("/auth")
+            .wrap(RateLimit::new(Budget::Auth))
+            .route("/register", web::post().to(handlers::auth::register))
+            .route("/login", web::post().to(handlers::auth::login))
+            .route("/captcha", web::get().to(handlers::auth::captcha_config))
+            .route("/refresh", web::post().to(handlers::auth::refresh))
+            .route("/logout", web::post().to(handlers::auth::logout))
+            .route("/email/confirm", web::get().to(handlers::account::confirm_email_change))
+            .route("/commit-emails/confirm", web::get().to(handlers::commit_emails::confirm_commit_email))
+            // Sign in with GitHub/GitLab
+            .route(
+                "/oauth/exchange",
+                web::post().to(handlers::oauth::exchange_login_code),
+            )
+            .route(
+                "/oauth/{platform}/authorize",
+                web::get().to(handlers::oauth::login_authorize),
+            )
+            .route(
+                "/oauth/{platform}/link",
+                web::get()
+                    .to(handlers::oauth::link_authorize)
+                    .wrap(crate::middleware::auth::JwtMiddleware),
+            )
+            // OIDC single sign-on
+            .route("/oidc", web::get().to(handlers::oidc::oidc_status))
+            .route("/oidc/authorize", web::get().to(handlers::oidc::oidc_authorize))
+            .route("/oidc/callback", web::get().to(handlers::oidc::oidc_callback)),
+    );
+
+    // OAuth endpoints (authorize requires JWT, callback uses state token)
+    cfg.service(
+        web::scope("/oauth")
+            .route(
+                "/github/authorize",
+                web::get()
+                    .to(handlers::oauth::github_authorize)
+                    .wrap(crate::middleware::auth::JwtMiddleware),
+            )
+            .route(
+                "/github/callback",
+                web::get().to(handlers::oauth::github_callback),
+            )
+            .route(
+                "/gitea/authorize",
+                web::get()
+                    .to(handlers::oauth::gitea_authorize)
+                    .wrap(crate::middleware::auth::JwtMiddleware),
+            )
+            .route(
+                "/gitea/callback",
+                web::get().to(handlers::oauth::gitea_callback),
+            )
+            .route(
+                "/gitlab/authorize",
+                web::get()
+                    .to(handlers::oauth::gitlab_authorize)
+                    .wrap(crate::middleware::auth::JwtMiddleware),
+            )
+            .route(
+                "/gitlab/callback",
+                web::get().to(handlers::oauth::gitlab_callback),
+            )
+            // Public endpoint to list available OAuth instances
+            .route(
+                "/instances/{platform}",
+                web::get().to(handlers::oauth::list_oauth_instances),
+            ),
+    );
+
+    // Protected endpoints (JWT required)
+    cfg.service(
+        web::scope("/platforms")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("platforms"))
+            .route(
+                "/connect",
+                web::post().to(handlers::platform_accounts::connect_platform),
+            )
+            .route(
+                "/validate-instance",
+                web::post().to(handlers::platform_accounts::validate_instance),
+            )
+            .route(
+                "",
+                web::get().to(handlers::platform_accounts::list_platforms),
+            )
+            .route(
+                "/disconnected",
+                web::get().to(handlers::platform_accounts::list_disconnected_platforms),
+            )
+            .route(
+                "/imports",
+                web::get().to(handlers::activity_imports::list_imports),
+            )
+            .route(
+                "/imports/{import_id}",
+                web::get().to(handlers::activity_imports::get_import),
+            )
+            .service(
+                web::resource("/{id}/import")
+                    .app_data(handlers::activity_imports::payload_config())
+                    .route(web::post().to(handlers::activity_imports::import_activities)),
+            )
+            .route(
+                "/{id}",
+                web::delete().to(handlers::platform_accounts::disconnect_platform),
+            )
+            .route(
+                "/{id}/restore",
+                web::post().to(handlers::platform_accounts::restore_platform),
+            )
+            .route(
+                "/{id}/sync-preferences",
+                web::put().to(handlers::platform_accounts::update_sync_preferences),
+            )
+            .route(
+                "/{id}/visibility",
+                web::get().to(handlers::platform_accounts::get_visibility),
+            )
+            .route(
+                "/{id}/visibility",
+                web::put().to(handlers::platform_accounts::update_visibility),
+            )
+            .route(
+                "/{id}/capabilities",
+                web::get().to(handlers::platform_accounts::get_capabilities),
+            )
+            .route(
+                "/{id}/api-usage",
+                web::get().to(handlers::platform_accounts::get_api_usage),
+            )
+            .route(
+                "/{id}/commit-details",
+                web::get().to(handlers::platform_accounts::get_commit_storage),
+            )
+            .route(
+                "/{id}/label",
+                web::put().to(handlers::platform_accounts::update_label),
+            )
+            .route(
+                "/{id}/merge",
+                web::post().to(handlers::platform_accounts::merge_accounts),
+            )
+            .route(
+                "/{id}/sync",
+                web::post().to(handlers::platform_sync_jobs::sync_platform),
+            )
+            // Older name of the same endpoint
+            .route(
+                "/{id}/sync-async",
+                web::post().to(handlers::platform_sync_jobs::sync_platform),
+            )
+            .route(
+                "/sync-jobs/{job_id}",
+                web::get().to(handlers::platform_sync_jobs::get_sync_job_status),
+            )
+            .route(
+                "/sync-jobs/{job_id}",
+                web::delete().to(handlers::platform_sync_jobs::cancel_sync_job),
+            )
+            .route(
+                "/sync-jobs/{job_id}/delete",
+                web::delete().to(handlers::platform_sync_jobs::delete_sync_job),
+            )
+            .route(
+                "/sync-jobs",
+                web::get().to(handlers::platform_sync_jobs::list_sync_jobs),
+            ),
+    );
+
+    cfg.service(
+        web::scope("/contributions")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("contributions"))
+            .route(
+                "",
+                web::get().to(handlers::contributions::get_contributions),
+            )
+            .route("/stats", web::get().to(handlers::contributions::get_stats))
+            .route("/hours", web::get().to(handlers::contributions::get_hours))
+            .route(
+                "/code-frequency",
+                web::get().to(handlers::contributions::get_code_frequency),
+            )
+            .route(
+                "/by-organization",
+                web::get().to(handlers::contributions::get_contributions_by_organization),
+            ),
+    );
+
+    // Public user profile endpoints (no authentication required)
+    cfg.service(
+        web::scope("/users")
+            .wrap(RateLimit::new(Budget::Public))
+            .route("", web::get().to(handlers::directory::list_users))
+            .route(
+                "/{username}/contributions",
+                web::get().to(handlers::contributions::get_user_contributions),
+            )
+            .route(
+                "/{username}/contributions/stats",
+                web::get().to(handlers::contributions::get_user_stats),
+            )
+            .route(
+                "/{username}/platforms",
+                web::get().to(handlers::platform_accounts::get_user_platforms),
+            )
+            .route(
+                "/{username}/organizations",
+                web::get().to(handlers::platform_accounts::get_user_organizations),
+            )
+            .route(
+                "/{username}/languages",
+                web::get().to(handlers::languages::get_user_languages),
+            )
+            .route("/{username}/today", web::get().to(handlers::today::get_user_today))
+            .route(
+                "/{username}/today/events",
+                web::get().to(handlers::today::user_today_events),
+            )
+            .route(
+                "/{username}/achievements",
+                web::get().to(handlers::achievements::get_user_achievements),
+            )
+            .route(
+                "/{username}/activities",
+                web::get().to(handlers::activities::get_user_activities),
+            )
+            .route(
+                "/{username}/activities.atom",
+                web::get().to(handlers::activities::get_user_activities_feed),
+            )
+            .route(
+                "/{username}/heatmap.txt",
+                web::get().to(handlers::static_files::serve_text_heatmap),
+            )
+            .route(
+                "/{username}/heatmap.json",
+                web::get().to(handlers::static_files::serve_heatmap_json),
+            )
+            .route(
+                "/{username}/heatmap/emoji",
+                web::get().to(handlers::static_files::serve_emoji_heatmap),
+            )
+            .route(
+                "/{username}/heatmap/diff",
+                web::get().to(handlers::static_files::serve_diff_heatmap),
+            ),
+    );
+
+    cfg.service(
+        web::resource("/announcements")
+            .wrap(RateLimit::new(Budget::Public))
+            .route(web::get().to(handlers::announcements::list_active_announcements)),
+    );
+
+    cfg.service(
+        web::scope("/activities")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("activities"))
+            .route("", web::get().to(handlers::activities::get_activities)),
+    );
+
+    // Milestones reached, also recorded as activities
+    cfg.service(
+        web::scope("/milestones")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("activities"))
+            .route("", web::get().to(handlers::milestones::list_milestones)),
+    );
+
+    // Followed users and their activities
+    cfg.service(
+        web::scope("/following")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("following"))
+            .route("", web::get().to(handlers::follows::list_following))
+            .route("/{username}", web::put().to(handlers::follows::follow_user))
+            .route("/{username}", web::delete().to(handlers::follows::unfollow_user)),
+    );
+
+    cfg.service(
+        web::scope("/feed")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("activities"))
+            .route("", web::get().to(handlers::activities::get_feed)),
+    );
+
+    // Private notes on dates of the user's graph
+    cfg.service(
+        web::scope("/annotations")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("annotations"))
+            .route("", web::get().to(handlers::annotations::list_annotations))
+            .route("", web::post().to(handlers::annotations::create_annotation))
+            .route("/{id}", web::put().to(handlers::annotations::update_annotation))
+            .route("/{id}", web::delete().to(handlers::annotations::delete_annotation)),
+    );
+
+    cfg.service(
+        web::scope("/graphql")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("read:graphql"))
+            .route("", web::post().to(handlers::graphql::graphql)),
+    );
+
+    // Account, credentials and login sessions (session only); registered
+    // before /settings so they match first
+    cfg.service(
+        web::resource("/settings/account")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route(web::delete().to(handlers::account::delete_account)),
+    );
+    cfg.service(
+        web::resource("/settings/password")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route(web::put().to(handlers::account::change_password)),
+    );
+    cfg.service(
+        web::resource("/settings/username")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route(web::put().to(handlers::account::change_username)),
+    );
+    cfg.service(
+        web::resource("/settings/email")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route(web::post().to(handlers::account::request_email_change)),
+    );
+
+    cfg.service(
+        web::scope("/settings/sessions")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route("", web::get().to(handlers::sessions::list_sessions))
+            .route("/{id}", web::delete().to(handlers::sessions::revoke_session)),
+    );
+
+    cfg.service(
+        web::scope("/settings")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("settings"))
+            .route("", web::get().to(handlers::settings::get_settings))
+            .route("", web::put().to(handlers::settings::update_settings))
+            .route("/embed-token", web::post().to(handlers::settings::create_embed_token))
+            .route("/retention", web::get().to(handlers::settings::get_retention))
+            .route("/usage", web::get().to(handlers::settings::get_usage))
+            .route("/digest", web::get().to(handlers::settings::get_digest))
+            .route("/digest", web::put().to(handlers::settings::update_digest))
+            .route("/commit-emails", web::get().to(handlers::commit_emails::list_commit_emails))
+            .route("/commit-emails", web::post().to(handlers::commit_emails::add_commit_email))
+            .route("/commit-emails/{email}", web::delete().to(handlers::commit_emails::delete_commit_email))
+            .route(
+                "/commit-emails/{email}/verify",
+                web::post().to(handlers::commit_emails::resend_commit_email_verification),
+            )
+            .route("/mqtt", web::get().to(handlers::mqtt::get_mqtt))
+            .route("/mqtt", web::put().to(handlers::mqtt::update_mqtt))
+            .route("/mqtt", web::delete().to(handlers::mqtt::delete_mqtt))
+            .route("/mqtt/test", web::post().to(handlers::mqtt::test_mqtt))
+            .route("/channels", web::get().to(handlers::notification_channels::list_notification_channels))
+            .route("/channels", web::post().to(handlers::notification_channels::create_notification_channel))
+            .route(
+                "/channels/{id}",
+                web::put().to(handlers::notification_channels::update_notification_channel),
+            )
+            .route(
+                "/channels/{id}",
+                web::delete().to(handlers::notification_channels::delete_notification_channel),
+            )
+            .route(
+                "/channels/{id}/test",
+                web::post().to(handlers::notification_channels::test_notification_channel),
+            ),
+    );
+
+    // Sync endpoints (JWT required)
+    cfg.service(
+        web::scope("/sync")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("sync"))
+            .route("/trigger", web::post().to(handlers::sync::trigger_sync))
+            .route("/status", web::get().to(handlers::sync::get_sync_status)),
+    );
+
+    // Heatmap theme and generation endpoints
+    cfg.service(
+        web::scope("/heatmap")
+            // Theme management
+            .service(
+                web::scope("/themes")
+                    .wrap(crate::middleware::auth::ScopedAuth::resource("themes"))
+                    .route("", web::get().to(handlers::heatmap_themes::list_themes))
+                    .route("", web::post().to(handlers::heatmap_themes::create_theme))
+                    .route("/{slug}", web::get().to(handlers::heatmap_themes::get_theme))
+                    .route("/{slug}", web::put().to(handlers::heatmap_themes::update_theme))
+                    .route(
+                        "/{slug}",
+                        web::delete().to(handlers::heatmap_themes::delete_theme),
+                    )
+                    .route(
+                        "/{slug}/set-default",
+                        web::post().to(handlers::heatmap_themes::set_default_theme),
+                    )
+                    .route(
+                        "/{slug}/duplicate",
+                        web::post().to(handlers::heatmap_themes::duplicate_theme),
+                    ),
+            )
+            .service(
+                web::scope("")
+                    .wrap(crate::middleware::auth::ScopedAuth::resource("heatmaps"))
+                    // Available fonts (public within auth)
+                    .route(
+                        "/fonts",
+                        web::get().to(handlers::fonts::get_available_fonts),
+                    )
+                    // Generation settings
+                    .route(
+                        "/settings",
+                        web::get().to(handlers::heatmap_generation::get_generation_settings),
+                    )
+                    .route(
+                        "/settings",
+                        web::put().to(handlers::heatmap_generation::update_generation_settings),
+                    )
+                    // Manual generation triggers
+                    .route(
+                        "/generate",
+                        web::post().to(handlers::heatmap_generation::trigger_generation),
+                    )
+                    .route(
+                        "/generate/{slug}",
+                        web::post().to(handlers::heatmap_generation::trigger_theme_generation),
+                    )
+                    // Regenerate and download in one call, for CI
+                    .route(
+                        "/render",
+                        web::post().to(handlers::heatmap_generation::render_heatmap),
+                    )
+                    // View generated heatmaps and jobs
+                    .route(
+                        "/generated",
+                        web::get().to(handlers::heatmap_generation::list_generated_heatmaps),
+                    )
+                    .route(
+                        "/generated/{id}/share",
+                        web::post().to(handlers::heatmap_generation::share_generated_heatmap),
+                    )
+                    .route(
+                        "/jobs",
+                        web::get().to(handlers::heatmap_generation::list_generation_jobs),
+                    )
+                    .route(
+                        "/jobs/{id}/retry",
+                        web::post().to(handlers::heatmap_generation::retry_generation_job),
+                    )
+                    // Signed links to private heatmaps
+                    .route(
+                        "/share-links",
+                        web::get().to(handlers::heatmap_generation::list_share_links),
+                    )
+                    .route(
+                        "/share-links/{id}",
+                        web::delete().to(handlers::heatmap_generation::delete_share_link),
+                    )
+                    // Views of generated heatmaps and embeds
+                    .route(
+                        "/analytics",
+                        web::get().to(handlers::heatmap_generation::get_heatmap_analytics),
+                    )
+                    // Preview theme (POST with theme parameters)
+                    .route(
+                        "/preview",
+                        web::post().to(handlers::heatmap_generation::preview_theme),
+                    ),
+            ),
+    );
+
+    // Teams and their combined heatmaps
+    cfg.service(
+        web::scope("/teams")
+            .wrap(crate::middleware::auth::ScopedAuth::resource("teams"))
+            .route("", web::get().to(handlers::teams::list_teams))
+            .route("", web::post().to(handlers::teams::create_team))
+            .route("/{slug}", web::get().to(handlers::teams::get_team))
+            .route("/{slug}", web::put().to(handlers::teams::update_team))
+            .route("/{slug}", web::delete().to(handlers::teams::delete_team))
+            .route("/{slug}/members", web::post().to(handlers::teams::add_member))
+            .route(
+                "/{slug}/members/{username}",
+                web::delete().to(handlers::teams::remove_member),
+            )
+            .route("/{slug}/heatmap", web::get().to(handlers::teams::get_team_heatmap))
+            .route("/{slug}/stats", web::get().to(handlers::teams::get_team_stats)),
+    );
+
+    // Live dashboard updates (session only)
+    cfg.service(
+        web::scope("/events")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route("", web::get().to(handlers::events::events)),
+    );
+
+    // API token management (session only, API tokens cannot manage tokens)
+    cfg.service(
+        web::scope("/tokens")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route("", web::get().to(handlers::api_tokens::list_tokens))
+            .route("", web::post().to(handlers::api_tokens::create_token))
+            .route("/{id}", web::delete().to(handlers::api_tokens::delete_token)),
+    );
+
+    // Sign-in identities (session only)
+    cfg.service(
+        web::scope("/identities")
+            .wrap(crate::middleware::auth::JwtMiddleware)
+            .route("", web::get().to(handlers::identities::list_identities))
+            .route("/{id}", web::delete().to(handlers::identities::delete_identity)),
+    );
+
+    // Public static file endpoints (no authentication required, allow embedding anywhere)
+    cfg.service(
+        web::scope("/static/heatmaps")
+            .wrap(RateLimit::new(Budget::Embed))
+            .wrap(Cors::permissive())
+            .route(
+                "/{user_id}/{filename}",
+                web::get().to(handlers::static_files::serve_heatmap),
+            ),
+    );
+
+    // Interactive widget, registered before /embed so the scope cannot
+    // claim it
+    cfg.service(
+        web::resource("/embed.js")
+            .wrap(RateLimit::new(Budget::Embed))
+            .wrap(Cors::permissive())
+            .route(web::get().to(handlers::static_files::serve_embed_script)),
+    );
+
+    cfg.service(
+        web::scope("/embed")
+            .wrap(RateLimit::new(Budget::Embed))
+            .wrap(Cors::permissive())
+            .route(
+                "/{username}/data.json",
+                web::get().to(handlers::static_files::serve_embed_data),
+            )
+            .route(
+                "/{username}/{theme_file}",
+                web::get().to(handlers::static_files::serve_embed),
+            ),
+    );
+
+    cfg.service(
+        web::scope("/share")
+            .wrap(RateLimit::new(Budget::Embed))
+            .wrap(Cors::permissive())
+            .route("/{token}", web::get().to(handlers::static_files::serve_share_link)),
+    );
+
+    // Admin endpoints (JWT + admin check required)
+    cfg.service(
+        web::scope("/admin/oauth-apps")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::oauth_apps::list_oauth_apps))
+            .route("", web::post().to(handlers::oauth_apps::create_oauth_app))
+            // Before /{id}, which would take "order" for an ID
+            .route("/order", web::put().to(handlers::oauth_apps::reorder_oauth_apps))
+            .route(
+                "/{id}",
+                web::put().to(handlers::oauth_apps::update_oauth_app),
+            )
+            .route(
+                "/{id}",
+                web::delete().to(handlers::oauth_apps::delete_oauth_app),
+            )
+            .route(
+                "/{id}/test",
+                web::post().to(handlers::oauth_apps::test_oauth_app),
+            ),
+    );
+
+    cfg.service(
+        web::scope("/admin/invitations")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::invitations::list_invitations))
+            .route("", web::post().to(handlers::invitations::create_invitation))
+            .route(
+                "/{id}",
+                web::put().to(handlers::invitations::update_invitation),
+            )
+            .route(
+                "/{id}",
+                web::delete().to(handlers::invitations::delete_invitation),
+            ),
+    );
+
+    cfg.service(
+        web::scope("/admin/retention")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::retention::preview_retention))
+            .route("/run", web::post().to(handlers::retention::run_retention)),
+    );
+
+    cfg.service(
+        web::scope("/admin/api-usage")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::api_usage::list_api_usage)),
+    );
+
+    cfg.service(
+        web::scope("/admin/cleanup")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::cleanup::preview_cleanup))
+            .route("/run", web::post().to(handlers::cleanup::run_cleanup)),
+    );
+
+    cfg.service(
+        web::scope("/admin/encryption-keys")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::key_rotation::preview_key_rotation))
+            .route("/rotate", web::post().to(handlers::key_rotation::run_key_rotation)),
+    );
+
+    cfg.service(
+        web::scope("/admin/settings")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::instance_settings::get_instance_settings))
+            .route("", web::put().to(handlers::instance_settings::update_instance_settings)),
+    );
+
+    cfg.service(
+        web::scope("/admin/announcements")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("", web::get().to(handlers::announcements::list_announcements))
+            .route("", web::post().to(handlers::announcements::create_announcement))
+            .route(
+                "/{id}",
+                web::put().to(handlers::announcements::update_announcement),
+            )
+            .route(
+                "/{id}",
+                web::delete().to(handlers::announcements::delete_announcement),
+            ),
+    );
+
+    cfg.service(
+        web::scope("/admin/jobs")
+            .wrap(crate::middleware::auth::ScopedAuth::scope("admin"))
+            .route("/concurrency", web::get().to(handlers::admin_jobs::get_job_concurrency))
+            .route(
+                "/concurrency/{user_id}",
+                web::put().to(handlers::admin_jobs::set_job_concurrency),
+            )
+            .route("/{kind}", web::get().to(handlers::admin_jobs::list_jobs))
+            .route(
+                "/{kind}/{id}/cancel",
+                web::post().to(handlers::admin_jobs::cancel_job),
+            )
+            .route(
+                "/{kind}/{id}/retry",
+                web::post().to(handlers::admin_jobs::retry_job),
+            ),
+    );
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{
+    activity, contribution, generated_heatmap, git_platform_account, heatmap_generation_setting, heatmap_theme,
+    oauth_application, user,
+};
+use crate::services::git_platforms::{gitea::GiteaClient, github::GitHubClient, gitlab::GitLabClient};
+use crate::services::heatmap_generator::HEATMAP_BASE_DIR;
+use crate::utils::encryption;
+
+/// What was removed when an account was deleted, returned to the user as proof
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeletionReceipt {
+    pub receipt_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub deleted_at: DateTime<Utc>,
+    pub platform_accounts: u64,
+    /// Platform OAuth tokens revoked on GitHub, GitLab or Gitea
+    pub tokens_revoked: u64,
+    pub contributions: u64,
+    pub activities: u64,
+    pub themes: u64,
+    pub generated_files: u64,
+}
+
+/// Revoke a platform account's OAuth token on the platform. Failures are
+/// logged and ignored so an already dead token never blocks a deletion.
+/// Returns whether the token was revoked.
+pub async fn revoke_platform_token(
+    db: &DatabaseConnection,
+    encryption_key: &str,
+    account: &git_platform_account::Model,
+) -> bool {
+    let Some(encrypted_token) = &account.access_token else {
+        log::info!("No access token to revoke");
+        return false;
+    };
+
+    log::info!("🔓 Decrypting access token for revocation");
+
+    let access_token = match encryption::decrypt(encrypted_token, encryption_key) {
+        Ok(token) => token,
+        Err(e) => {
+            log::warn!("⚠️  Failed to decrypt token for revocation: {}", e);
+            return false;
+        }
+    };
+
+    log::info!("🔒 Revoking token on platform: {:?}", account.platform_type);
+
+    let revoke_result = match account.platform_type {
+        git_platform_account::GitPlatform::GitHub => {
+            // Get OAuth app credentials for GitHub
+            let oauth_app = oauth_application::Entity::find()
+                .filter(oauth_application::Column::Platform.eq(account.platform_type.clone()))
+                .filter(oauth_application::Column::InstanceUrl.eq(""))
+                .filter(oauth_application::Column::IsEnabled.eq(true))
+                .one(db)
+                .await
+                .ok()
+                .flatten();
+
+            if let Some(app) = oauth_app {
+                let client_secret = encryption::decrypt(&app.client_secret, encryption_key).unwrap_or_default();
+
+                GitHubClient::new()
+                    .revoke_token(&app.client_id, &client_secret, &access_token)
+                    .await
+            } else {
+                log::warn!("No GitHub OAuth app configured, skipping token revocation");
+                return false;
+            }
+        }
+        git_platform_account::GitPlatform::GitLab => {
+            let instance_url = account.platform_url.as_deref().unwrap_or("https://gitlab.com");
+            GitLabClient::new().revoke_token(instance_url, &access_token).await
+        }
+        git_platform_account::GitPlatform::Gitea => {
+            if let Some(instance_url) = &account.platform_url {
+                GiteaClient::new().revoke_token(instance_url, &access_token).await
+            } else {
+                log::warn!("No instance URL for Gitea account, skipping token revocation");
+                return false;
+            }
+        }
+    };
+
+    match revoke_result {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("⚠️  Token revocation failed (non-fatal): {}", e);
+            false
+        }
+    }
+}
+
+/// Delete a user and everything they own: platform tokens are revoked, the
+/// user row is deleted (cascading to accounts, contributions, activities,
+/// themes, jobs and sessions) and generated heatmap files are removed.
+pub async fn delete_account(
+    db: &DatabaseConnection,
+    encryption_key: &str,
+    user: user::Model,
+) -> Result<DeletionReceipt, DbErr> {
+    let user_id = user.id;
+    log::info!("🗑️  [Account Deletion] Deleting account '{}' ({})", user.username, user_id);
+
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+    let account_ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+
+    let mut tokens_revoked = 0;
+    for account in &accounts {
+        if revoke_platform_token(db, encryption_key, account).await {
+            tokens_revoked += 1;
+        }
+    }
+
+    // Collect file locations before the rows that point at them are gone
+    let generated_paths: Vec<String> = generated_heatmap::Entity::find()
+        .filter(generated_heatmap::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|g| g.file_path)
+        .collect();
+    let mut user_dirs = BTreeSet::from([PathBuf::from(HEATMAP_BASE_DIR).join(user_id.to_string())]);
+    if let Some(storage_path) = heatmap_generation_setting::Entity::find()
+        .filter(heatmap_generation_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .and_then(|s| s.storage_path)
+    {
+        user_dirs.insert(PathBuf::from(storage_path).join(user_id.to_string()));
+    }
+
+    let txn = db.begin().await?;
+
+    let contributions = contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids.clone()))
+        .count(&txn)
+        .await?;
+    let activities = activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
+        .count(&txn)
+        .await?;
+    let themes = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user_id))
+        .count(&txn)
+        .await?;
+
+    user::Entity::delete_by_id(user_id).exec(&txn).await?;
+    txn.commit().await?;
+
+    let generated_files = remove_files(&generated_paths, &user_dirs).await;
+
+    let receipt = DeletionReceipt {
+        receipt_id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        username: user.username,
+        deleted_at: Utc::now(),
+        platform_accounts: accounts.len() as u64,
+        tokens_revoked,
+        contributions,
+        activities,
+        themes,
+        generated_files,
+    };
+
+    // Kept in the logs as the record that the request was carried out
+    log::info!("✅ [Account Deletion] {:?}", receipt);
+
+    Ok(receipt)
+}
+
+/// Remove generated heatmaps and the user's heatmap directories, returning
+/// how many files were deleted
+async fn remove_files(paths: &[String], user_dirs: &BTreeSet<PathBuf>) -> u64 {
+    let mut removed = 0;
+
+    for path in paths {
+        // Stored paths may be URL-style ("/static/heatmaps/...")
+        let path = PathBuf::from(path.trim_start_matches('/'));
+        if user_dirs.iter().any(|dir| path.starts_with(dir)) {
+            continue; // Counted when the directory is removed
+        }
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => removed += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("⚠️  [Account Deletion] Failed to remove {}: {}", path.display(), e),
+        }
+    }
+
+    for dir in user_dirs {
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_type().await.is_ok_and(|t| t.is_file()) {
+                removed += 1;
+            }
+        }
+        if let Err(e) = tokio::fs::remove_dir_all(dir).await {
+            log::warn!("⚠️  [Account Deletion] Failed to remove {}: {}", dir.display(), e);
+        }
+    }
+
+    removed
+}
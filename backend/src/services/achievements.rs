@@ -0,0 +1,159 @@
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::models::{achievement, activity, contribution, git_platform_account, heatmap_generation_setting, user_setting};
+use crate::utils::achievements::{self, Achievement, History};
+use crate::utils::repo_exclusion;
+
+/// The timezone hours of the day are counted in: the one the user's heatmaps
+/// are scheduled in, or UTC
+pub async fn user_timezone(db: &DatabaseConnection, user_id: Uuid) -> Result<Tz, DbErr> {
+    Ok(heatmap_generation_setting::Entity::find()
+        .filter(heatmap_generation_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .and_then(|s| s.timezone)
+        .and_then(|name| name.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC))
+}
+
+/// Re-evaluate the user's achievements from their history and store the
+/// result: new ones are added, ones no longer earned (say, after hiding
+/// private contributions) are removed. Run after a sync.
+pub async fn evaluate(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<achievement::Model>> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+    let show_private_contributions = settings.as_ref().map(|s| s.show_private_contributions).unwrap_or(true);
+    let excluded_repositories: Vec<String> = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.to_vec())
+        .unwrap_or_default();
+    let away = settings.as_ref().map(|s| s.away_periods.to_vec()).unwrap_or_default();
+
+    let timezone = user_timezone(db, user_id).await?;
+
+    let account_ids: Vec<Uuid> = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+
+    // Only what the public profile shows, so achievements never reveal more
+    let mut contributions = contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids.clone()))
+        .filter(repo_exclusion::not_excluded(
+            contribution::Column::RepositoryName,
+            &excluded_repositories,
+        ));
+    let mut activities = activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
+        .filter(repo_exclusion::not_excluded(
+            activity::Column::RepositoryName,
+            &excluded_repositories,
+        ));
+    if !show_private_contributions {
+        contributions = contributions.filter(contribution::Column::IsPrivateRepo.eq(false));
+        activities = activities.filter(activity::Column::IsPrivateRepo.eq(false));
+    }
+    let contributions = contributions.all(db).await?;
+    let activities = activities.all(db).await?;
+
+    let mut days: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    for c in &contributions {
+        *days.entry(c.contribution_date).or_default() += c.count as i64;
+    }
+
+    let history = History {
+        activity_times: activities
+            .iter()
+            .filter_map(|a| a.metadata.get("created_at")?.as_str())
+            .filter_map(|time| achievements::parse_time(time, timezone))
+            .collect(),
+        languages: activities
+            .iter()
+            .filter_map(|a| Some((a.activity_date, a.primary_language.as_deref()?)))
+            .collect(),
+        days: &days,
+        away: &away,
+    };
+    let earned = achievements::evaluate(&history);
+
+    store(db, user_id, &earned).await
+}
+
+/// Replace the user's achievements with `earned`, keeping the date of the
+/// ones they already held
+async fn store(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    earned: &[(Achievement, NaiveDate)],
+) -> Result<Vec<achievement::Model>> {
+    let txn = db.begin().await?;
+    let existing: HashMap<String, achievement::Model> = achievement::Entity::find()
+        .filter(achievement::Column::UserId.eq(user_id))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|a| (a.achievement.clone(), a))
+        .collect();
+
+    achievement::Entity::delete_many()
+        .filter(achievement::Column::UserId.eq(user_id))
+        .filter(achievement::Column::Achievement.is_not_in(earned.iter().map(|(a, _)| a.key)))
+        .exec(&txn)
+        .await?;
+
+    let now = Utc::now();
+    let mut stored = Vec::new();
+    for (achievement, earned_on) in earned {
+        if let Some(held) = existing.get(achievement.key) {
+            stored.push(held.clone());
+            continue;
+        }
+        let model = achievement::Model {
+            user_id,
+            achievement: achievement.key.to_string(),
+            earned_on: *earned_on,
+            updated_at: now,
+        };
+        achievement::Entity::insert(achievement::ActiveModel::from(model.clone()))
+            .on_conflict(
+                OnConflict::columns([achievement::Column::UserId, achievement::Column::Achievement])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+        log::info!("🏅 [Achievements] {} earned {}", user_id, achievement.key);
+        stored.push(model);
+    }
+    txn.commit().await?;
+
+    Ok(stored)
+}
+
+/// The user's achievements in display order
+pub async fn list(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<(Achievement, NaiveDate)>, DbErr> {
+    let held: HashMap<String, NaiveDate> = achievement::Entity::find()
+        .filter(achievement::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| (a.achievement, a.earned_on))
+        .collect();
+
+    Ok(achievements::ALL
+        .iter()
+        .filter_map(|a| Some((*a, *held.get(a.key)?)))
+        .collect())
+}
@@ -1,12 +1,20 @@
 use crate::models::activity::{ActiveModel as ActivityActiveModel, ActivityType as DbActivityType};
 use crate::models::git_platform_account;
+use crate::models::organization_membership::{ActiveModel as OrganizationMembershipActiveModel, Entity as OrganizationMembership};
+use crate::services::{activity_store, commit_details};
 use crate::services::git_platforms::{Activity, ActivityType, GitHubClient, GiteaClient, GitLabClient, GitPlatform, PlatformConfig};
-use crate::utils::encryption;
+use crate::services::repository_languages::{self, Contributed};
+use crate::utils::{db, encryption};
 use anyhow::Result;
 use chrono::{DateTime, Datelike, Utc};
-use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait};
+use futures_util::{StreamExt, TryStreamExt};
+use sea_orm::{ActiveValue, Condition, DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, TransactionTrait};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Month-keyed commit aggregation: (repositories, total count, has private, latest date)
+type MonthlyCommits = std::collections::HashMap<(i32, u32), (Vec<serde_json::Value>, i32, bool, chrono::NaiveDate)>;
+
 pub struct ActivityAggregationService {
     db: DatabaseConnection,
     encryption_key: String,
@@ -65,30 +73,25 @@ impl ActivityAggregationService {
             account.platform_username
         );
 
-        // Delete existing activities in the date range to avoid duplicates
-        let from_date = from.naive_utc().date();
-        let to_date = to.naive_utc().date();
-
-        let deleted = crate::models::activity::Entity::delete_many()
-            .filter(crate::models::activity::Column::GitPlatformAccountId.eq(account.id))
-            .filter(crate::models::activity::Column::ActivityDate.gte(from_date))
-            .filter(crate::models::activity::Column::ActivityDate.lte(to_date))
-            .exec(&self.db)
-            .await?;
-
-        log::info!("🗑️  Deleted {} existing activities in date range", deleted.rows_affected);
+        let capabilities = platform_client
+            .capabilities(&config, &account.platform_username, &token)
+            .await;
 
         // Fetch contributions for accurate commit counts
         log::info!("Fetching contributions for commit counts...");
 
         // For GitHub, we need to fetch year by year due to 1-year API limit
-        let contributions = if matches!(account.platform_type, git_platform_account::GitPlatform::GitHub) {
+        let contributions = if !capabilities.contributions {
+            log::info!("Contributions are not available on this instance, skipping commit counts");
+            Vec::new()
+        } else if matches!(account.platform_type, git_platform_account::GitPlatform::GitHub) {
             let start_year = from.year();
             let end_year = to.year();
 
-            let mut all_contributions = Vec::new();
-
-            for year in start_year..=end_year {
+            // A few years at a time, like the contribution sync
+            let platform_client = &platform_client;
+            let (config, token) = (&config, &token);
+            let years = futures_util::stream::iter(start_year..=end_year).map(|year| async move {
                 let year_from = if year == start_year {
                     from // Use the actual start date for the first year
                 } else {
@@ -113,10 +116,16 @@ impl ActivityAggregationService {
                     year, year_from.format("%Y-%m-%d"), year_to.format("%Y-%m-%d"));
 
                 let year_contributions = platform_client
-                    .fetch_contributions(&config, &account.platform_username, &token, year_from, year_to)
+                    .fetch_contributions(config, &account.platform_username, token, year_from, year_to)
                     .await?;
 
                 log::info!("✅ Fetched {} contribution days for year {}", year_contributions.len(), year);
+                Ok::<_, anyhow::Error>(year_contributions)
+            });
+
+            let mut all_contributions = Vec::new();
+            let mut fetched = years.buffer_unordered(config.year_concurrency());
+            while let Some(year_contributions) = fetched.try_next().await? {
                 all_contributions.extend(year_contributions);
             }
 
@@ -131,14 +140,24 @@ impl ActivityAggregationService {
         let contribution_count = contributions.len();
         log::info!("Fetched {} contribution days (total across all years)", contribution_count);
 
+        // Repositories contributed to, whose languages and commits are refreshed below
+        let mut repositories: HashMap<String, Contributed> = HashMap::new();
+        for contribution in contributions.iter().filter(|c| c.count > 0) {
+            let Some(name) = contribution.repository() else { continue };
+            let entry = repositories.entry(name.to_string()).or_insert(Contributed {
+                contributions: 0,
+                is_private: contribution.is_private,
+            });
+            entry.contributions += contribution.count as i64;
+        }
+
         // Aggregate contributions by MONTH (not just date) to avoid pagination issues
-        use std::collections::HashMap;
-        let mut commits_by_month: HashMap<(i32, u32), (Vec<serde_json::Value>, i32, bool, chrono::NaiveDate)> = HashMap::new();
+        let mut commits_by_month = MonthlyCommits::new();
 
         for contribution in contributions {
             if contribution.count > 0 {
                 // Skip contributions without repository names - they can't be properly attributed
-                let Some(repo_name) = contribution.repository_name.clone() else {
+                let Some(repo_name) = contribution.repository().map(str::to_string) else {
                     log::debug!("Skipping contribution on {} - no repository name available", contribution.date);
                     continue;
                 };
@@ -227,7 +246,7 @@ impl ActivityAggregationService {
         // Note: Currently only implemented for GitHub
         log::info!("Fetching PR and issue activities from GraphQL search...");
         let pr_issue_activities = match account.platform_type {
-            git_platform_account::GitPlatform::GitHub => {
+            git_platform_account::GitPlatform::GitHub if capabilities.pull_requests_and_issues => {
                 let github_client = GitHubClient::new();
                 github_client
                     .fetch_pr_and_issue_activities(&config, &account.platform_username, &token, from, to)
@@ -242,40 +261,30 @@ impl ActivityAggregationService {
 
         log::info!("Fetched {} PR/issue activities from GraphQL", pr_issue_activities.len());
 
-        // Fetch organization memberships and detect new joins (GitHub only)
+        // Fetch organization memberships and detect new joins (GitHub only).
+        // Memberships stay None when they could not be fetched, so the
+        // stored ones are kept.
         log::info!("Fetching organization memberships...");
-        let organization_activities = match account.platform_type {
-            git_platform_account::GitPlatform::GitHub => {
+        let (organization_activities, memberships) = match account.platform_type {
+            git_platform_account::GitPlatform::GitHub if capabilities.organizations => {
                 let github_client = GitHubClient::new();
 
                 // Fetch current public organizations
                 let current_orgs = github_client
                     .fetch_user_organizations(&config, &account.platform_username, &token)
-                    .await
-                    .unwrap_or_else(|e| {
-                        log::warn!("Failed to fetch organizations: {}", e);
-                        Vec::new()
-                    });
-
-                // Delete ALL existing organization join activities for this account
-                // We'll recreate them with correct dates from scraping/events
-                let deleted = crate::models::activity::Entity::delete_many()
-                    .filter(crate::models::activity::Column::GitPlatformAccountId.eq(account.id))
-                    .filter(crate::models::activity::Column::ActivityType.eq(DbActivityType::OrganizationJoined))
-                    .exec(&self.db)
-                    .await
-                    .unwrap_or_else(|e| {
-                        log::warn!("Failed to delete existing org activities: {}", e);
-                        sea_orm::DeleteResult { rows_affected: 0 }
-                    });
-
-                log::info!("🗑️  Deleted {} existing organization join activities (will recreate with correct dates)",
-                    deleted.rows_affected);
+                    .await;
+                let orgs_fetched = current_orgs.is_ok();
+                let current_orgs = current_orgs.unwrap_or_else(|e| {
+                    log::warn!("Failed to fetch organizations: {}", e);
+                    Vec::new()
+                });
 
                 log::info!("Found {} current organizations", current_orgs.len());
 
                 // Create activities for ALL current organizations with correct dates
                 let mut new_org_activities = Vec::new();
+                let mut memberships = Vec::new();
+                let now = Utc::now();
                 let today = chrono::Utc::now().naive_utc().date();
 
                 for (org_name, avatar_url) in current_orgs {
@@ -296,6 +305,14 @@ impl ActivityAggregationService {
                             .flatten();
                     }
 
+                    memberships.push(OrganizationMembershipActiveModel {
+                        account_id: ActiveValue::Set(account.id),
+                        login: ActiveValue::Set(org_name.clone()),
+                        avatar_url: ActiveValue::Set(Some(avatar_url.clone())),
+                        joined_at: ActiveValue::Set(join_date_opt),
+                        synced_at: ActiveValue::Set(now),
+                    });
+
                     let join_date = join_date_opt.unwrap_or_else(|| {
                         log::warn!("⚠️  No join date found for org {}, using current date", org_name);
                         today
@@ -322,9 +339,9 @@ impl ActivityAggregationService {
                 }
 
                 log::info!("Created {} organization join activities with scraped dates", new_org_activities.len());
-                new_org_activities
+                (new_org_activities, orgs_fetched.then_some(memberships))
             }
-            _ => Vec::new(), // Other platforms not yet supported
+            _ => (Vec::new(), None), // Other platforms not yet supported
         };
 
         // Fetch remaining activity types from Events API (forks, stars, etc.)
@@ -353,53 +370,73 @@ impl ActivityAggregationService {
 
         log::info!("Total activities to store: {}", activities.len());
 
-        // Store activities in database
-        let mut stored_count = 0;
-        for activity in activities {
-            match self.store_activity(&account.id, activity).await {
-                Ok(_) => stored_count += 1,
-                Err(e) => log::error!("Failed to store activity: {}", e),
-            }
-        }
+        // Store the activities in one transaction, so a failed sync keeps
+        // the old ones
+        let models: Vec<_> = activities
+            .into_iter()
+            .map(|activity| new_activity(account.id, activity))
+            .collect();
 
-        log::info!("✅ Stored {} activities in database", stored_count);
+        // Activities in the synced range are replaced; organization joins
+        // are kept for every current organization, whatever their date
+        let mut scope = Condition::any().add(
+            Condition::all()
+                .add(crate::models::activity::Column::ActivityDate.gte(from.naive_utc().date()))
+                .add(crate::models::activity::Column::ActivityDate.lte(to.naive_utc().date())),
+        );
+        if memberships.is_some() {
+            scope = scope.add(crate::models::activity::Column::ActivityType.eq(DbActivityType::OrganizationJoined));
+        }
 
-        Ok(())
-    }
+        let txn = self.db.begin().await?;
+        if let Some(memberships) = memberships {
+            OrganizationMembership::delete_many()
+                .filter(crate::models::organization_membership::Column::AccountId.eq(account.id))
+                .exec(&txn)
+                .await?;
+            let stored = db::insert_chunked(&txn, memberships).await?;
+            log::info!("🏢 Stored {} organization memberships", stored);
+        }
 
-    /// Store a single activity in the database
-    async fn store_activity(&self, account_id: &Uuid, activity: Activity) -> Result<()> {
-        // Convert ActivityType to DbActivityType
-        let db_activity_type = match activity.activity_type {
-            ActivityType::Commit => DbActivityType::Commit,
-            ActivityType::RepositoryCreated => DbActivityType::RepositoryCreated,
-            ActivityType::PullRequest => DbActivityType::PullRequest,
-            ActivityType::Issue => DbActivityType::Issue,
-            ActivityType::Review => DbActivityType::Review,
-            ActivityType::OrganizationJoined => DbActivityType::OrganizationJoined,
-            ActivityType::Fork => DbActivityType::Fork,
-            ActivityType::Release => DbActivityType::Release,
-            ActivityType::Star => DbActivityType::Star,
-        };
+        let stats = activity_store::replace(&txn, account.id, scope, models).await?;
+        txn.commit().await?;
+
+        log::info!("🗑️  Removed {} activities the platform no longer returned", stats.removed);
+        log::info!("✅ Stored {} activities in database", stats.stored);
+
+        // Languages are extra, so failing to fetch them keeps the sync going
+        match repository_languages::refresh(
+            &self.db,
+            platform_client.as_ref(),
+            &config,
+            &token,
+            account.id,
+            &repositories,
+        )
+        .await
+        {
+            Ok(refreshed) => log::info!("🔤 Refreshed the languages of {} repositories", refreshed),
+            Err(e) => log::warn!("Failed to store repository languages: {}", e),
+        }
 
-        let activity_model = ActivityActiveModel {
-            id: ActiveValue::Set(Uuid::new_v4()),
-            git_platform_account_id: ActiveValue::Set(*account_id),
-            activity_type: ActiveValue::Set(db_activity_type),
-            activity_date: ActiveValue::Set(activity.date),
-            metadata: ActiveValue::Set(activity.metadata),
-            repository_name: ActiveValue::Set(activity.repository_name),
-            repository_url: ActiveValue::Set(activity.repository_url),
-            is_private_repo: ActiveValue::Set(activity.is_private),
-            count: ActiveValue::Set(activity.count),
-            primary_language: ActiveValue::Set(activity.primary_language),
-            organization_name: ActiveValue::Set(activity.organization_name),
-            organization_avatar_url: ActiveValue::Set(activity.organization_avatar_url),
-            created_at: ActiveValue::Set(chrono::Utc::now()),
-            updated_at: ActiveValue::Set(chrono::Utc::now()),
-        };
+        // Same for commit details, which only accounts with detailed sync keep
+        match commit_details::refresh(
+            &self.db,
+            platform_client.as_ref(),
+            &config,
+            &token,
+            account,
+            &repositories,
+            from,
+            to,
+        )
+        .await
+        {
+            Ok(0) => {}
+            Ok(stored) => log::info!("🧾 Stored {} new commit details", stored),
+            Err(e) => log::warn!("Failed to store commit details: {}", e),
+        }
 
-        activity_model.insert(&self.db).await?;
         Ok(())
     }
 
@@ -436,3 +473,38 @@ impl ActivityAggregationService {
         }
     }
 }
+
+/// Row for an activity fetched from a platform
+fn new_activity(account_id: Uuid, activity: Activity) -> ActivityActiveModel {
+    // Convert ActivityType to DbActivityType
+    let db_activity_type = match activity.activity_type {
+        ActivityType::Commit => DbActivityType::Commit,
+        ActivityType::RepositoryCreated => DbActivityType::RepositoryCreated,
+        ActivityType::PullRequest => DbActivityType::PullRequest,
+        ActivityType::Issue => DbActivityType::Issue,
+        ActivityType::Review => DbActivityType::Review,
+        ActivityType::OrganizationJoined => DbActivityType::OrganizationJoined,
+        ActivityType::Fork => DbActivityType::Fork,
+        ActivityType::Release => DbActivityType::Release,
+        ActivityType::Star => DbActivityType::Star,
+    };
+
+    ActivityActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        git_platform_account_id: ActiveValue::Set(account_id),
+        activity_type: ActiveValue::Set(db_activity_type),
+        activity_date: ActiveValue::Set(activity.date),
+        metadata: ActiveValue::Set(activity.metadata),
+        repository_name: ActiveValue::Set(activity.repository_name),
+        repository_url: ActiveValue::Set(activity.repository_url),
+        is_private_repo: ActiveValue::Set(activity.is_private),
+        count: ActiveValue::Set(activity.count),
+        primary_language: ActiveValue::Set(activity.primary_language),
+        organization_name: ActiveValue::Set(activity.organization_name),
+        organization_avatar_url: ActiveValue::Set(activity.organization_avatar_url),
+        natural_key: ActiveValue::NotSet,
+        imported: ActiveValue::Set(false),
+        created_at: ActiveValue::Set(chrono::Utc::now()),
+        updated_at: ActiveValue::Set(chrono::Utc::now()),
+    }
+}
@@ -0,0 +1,357 @@
+use actix_web::web::Bytes;
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, SecondsFormat, Utc};
+use sea_orm::*;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::models::activity::{self, ActivityType};
+use crate::models::activity_import::{self, SyncJobStatus};
+use crate::models::git_platform_account;
+use crate::services::activity_store;
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::services::response_cache::ResponseCache;
+use crate::utils::event_file::{self, Event};
+
+/// Largest file that can be uploaded, compressed or not
+pub const MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+
+/// Activities stored between two progress updates
+const PROGRESS_CHUNK: usize = 500;
+
+/// Uploads are only held in memory, so an import the server stopped during
+/// cannot resume
+const INTERRUPTED: &str = "Interrupted by a server restart; upload the file again";
+
+/// One month of pushes, stored like the monthly commit summaries of a sync
+#[derive(Default)]
+struct Month {
+    repositories: BTreeMap<String, i32>,
+    total_count: i32,
+    has_private: bool,
+    latest_date: Option<NaiveDate>,
+}
+
+/// Queue an import of `data` into `account` and run it in the background.
+/// Returns the import to poll.
+pub async fn start(
+    db: &DatabaseConnection,
+    events: &EventBus,
+    cache: &ResponseCache,
+    account: &git_platform_account::Model,
+    data: Bytes,
+) -> Result<activity_import::Model, DbErr> {
+    let import = activity_import::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(account.user_id),
+        platform_account_id: Set(account.id),
+        status: Set(SyncJobStatus::Pending),
+        events_read: Set(0),
+        events_matched: Set(0),
+        activities_total: Set(0),
+        activities_imported: Set(0),
+        duplicates_skipped: Set(0),
+        error_message: Set(None),
+        created_at: Set(Utc::now()),
+        started_at: Set(None),
+        completed_at: Set(None),
+    }
+    .insert(db)
+    .await?;
+
+    let (db, events, cache) = (db.clone(), events.clone(), cache.clone());
+    let (job, account) = (import.clone(), account.clone());
+    tokio::spawn(async move {
+        let import_id = job.id;
+        let result = run(&db, &events, job, &account, data).await;
+        let finished = match result {
+            Ok(import) => Ok(import),
+            Err(e) => {
+                log::error!("❌ [Import] Import {} failed: {}", import_id, e);
+                finish(&db, import_id, SyncJobStatus::Failed, Some(e.to_string())).await
+            }
+        };
+
+        match finished {
+            Ok(Some(import)) => {
+                if import.status == SyncJobStatus::Completed {
+                    cache.invalidate_user(import.user_id).await;
+                    if import.activities_imported > 0 {
+                        events.publish(
+                            import.user_id,
+                            DashboardEvent::NewActivities {
+                                platform_account_id: import.platform_account_id,
+                                count: import.activities_imported,
+                            },
+                        );
+                    }
+                }
+                publish_progress(&events, &import);
+            }
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to record the end of import {}: {}", import_id, e),
+        }
+    });
+
+    Ok(import)
+}
+
+/// Mark imports that were queued or running when the server stopped as
+/// failed
+pub async fn fail_interrupted(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let interrupted = activity_import::Entity::find()
+        .filter(activity_import::Column::Status.is_in([SyncJobStatus::Pending, SyncJobStatus::Processing]))
+        .all(db)
+        .await?;
+
+    for import in &interrupted {
+        finish(db, import.id, SyncJobStatus::Failed, Some(INTERRUPTED.to_string())).await?;
+    }
+
+    Ok(interrupted.len() as u64)
+}
+
+/// Read the file, then add its activities in chunks, recording progress
+/// after each. Activities stored before a failure are kept; importing the
+/// file again skips them.
+async fn run(
+    db: &DatabaseConnection,
+    events: &EventBus,
+    import: activity_import::Model,
+    account: &git_platform_account::Model,
+    data: Bytes,
+) -> Result<Option<activity_import::Model>> {
+    let mut job: activity_import::ActiveModel = import.into();
+    job.status = Set(SyncJobStatus::Processing);
+    job.started_at = Set(Some(Utc::now()));
+    let import = job.update(db).await?;
+
+    let login = account.platform_username.clone();
+    let file = tokio::task::spawn_blocking(move || event_file::read(&data, &login))
+        .await?
+        .map_err(anyhow::Error::msg)?;
+
+    let (mut models, matched) = activities(account.id, &file.events);
+    activity_store::assign_keys(&mut models);
+
+    log::info!(
+        "📥 [Import] Import {}: {} of {} events belong to {}, {} activities",
+        import.id,
+        matched,
+        file.read,
+        account.platform_username,
+        models.len()
+    );
+
+    let mut job: activity_import::ActiveModel = import.into();
+    job.events_read = Set(file.read as i32);
+    job.events_matched = Set(matched as i32);
+    job.activities_total = Set(models.len() as i32);
+    let mut import = job.update(db).await?;
+    publish_progress(events, &import);
+
+    let mut models = models.into_iter().peekable();
+    while models.peek().is_some() {
+        let chunk: Vec<_> = models.by_ref().take(PROGRESS_CHUNK).collect();
+        let chunk_len = chunk.len() as i32;
+        let inserted = activity_store::insert_missing(db, chunk).await? as i32;
+
+        let mut job: activity_import::ActiveModel = import.clone().into();
+        job.activities_imported = Set(import.activities_imported + inserted);
+        job.duplicates_skipped = Set(import.duplicates_skipped + chunk_len - inserted);
+        import = job.update(db).await?;
+        publish_progress(events, &import);
+    }
+
+    log::info!(
+        "✅ [Import] Import {} added {} activities, {} were already stored",
+        import.id,
+        import.activities_imported,
+        import.duplicates_skipped
+    );
+
+    Ok(finish(db, import.id, SyncJobStatus::Completed, None).await?)
+}
+
+/// Set the final status of an import; None if it was deleted meanwhile
+async fn finish(
+    db: &DatabaseConnection,
+    import_id: Uuid,
+    status: SyncJobStatus,
+    error_message: Option<String>,
+) -> Result<Option<activity_import::Model>, DbErr> {
+    let Some(import) = activity_import::Entity::find_by_id(import_id).one(db).await? else {
+        return Ok(None);
+    };
+
+    let mut job: activity_import::ActiveModel = import.into();
+    job.status = Set(status);
+    job.error_message = Set(error_message);
+    job.completed_at = Set(Some(Utc::now()));
+    Ok(Some(job.update(db).await?))
+}
+
+fn publish_progress(events: &EventBus, import: &activity_import::Model) {
+    events.publish(
+        import.user_id,
+        DashboardEvent::ImportProgress {
+            import_id: import.id,
+            platform_account_id: import.platform_account_id,
+            status: import.status.clone(),
+            activities_total: import.activities_total,
+            activities_imported: import.activities_imported,
+            duplicates_skipped: import.duplicates_skipped,
+        },
+    );
+}
+
+/// The activities of `events`, and how many events they came from. Each
+/// activity is shaped like the one a sync stores for it, so it gets the same
+/// natural key and an activity a sync already stored is skipped. Pushes
+/// become monthly commit summaries for that reason, and only the opening of
+/// a pull request or issue counts, as a sync stores one activity for each.
+fn activities(account_id: Uuid, events: &[Event]) -> (Vec<activity::ActiveModel>, usize) {
+    let mut models = Vec::new();
+    let mut months: BTreeMap<(i32, u32), Month> = BTreeMap::new();
+    let mut matched = 0;
+
+    for event in events {
+        let date = event.created_at.date_naive();
+        let repo = event.repo.name.as_str();
+        let payload = &event.payload;
+        let action = payload.get("action").and_then(Value::as_str);
+        let field = |object: &str, key: &str| payload.get(object).and_then(|o| o.get(key)).cloned();
+
+        let (activity_type, metadata) = match (event.event_type.as_str(), action) {
+            ("PushEvent", _) => {
+                // Newer events in GH Archive have neither size nor commits
+                let commits = ["distinct_size", "size"]
+                    .iter()
+                    .find_map(|key| payload.get(key).and_then(Value::as_i64))
+                    .or_else(|| payload.get("commits").and_then(Value::as_array).map(|c| c.len() as i64))
+                    .unwrap_or(1) as i32;
+                if commits <= 0 {
+                    continue;
+                }
+
+                let month = months.entry((date.year(), date.month())).or_default();
+                *month.repositories.entry(repo.to_string()).or_default() += commits;
+                month.total_count += commits;
+                month.has_private |= !event.public;
+                month.latest_date = month.latest_date.max(Some(date));
+                matched += 1;
+                continue;
+            }
+            ("CreateEvent", _) if payload.get("ref_type").and_then(Value::as_str) == Some("repository") => (
+                ActivityType::RepositoryCreated,
+                json!({
+                    "name": repo,
+                    "description": payload.get("description"),
+                    "created_at": event.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                }),
+            ),
+            ("PullRequestEvent", Some("opened")) => (
+                ActivityType::PullRequest,
+                json!({
+                    "title": field("pull_request", "title"),
+                    "number": field("pull_request", "number"),
+                    "state": field("pull_request", "state"),
+                    "repository": repo,
+                    "url": field("pull_request", "html_url"),
+                }),
+            ),
+            ("IssuesEvent", Some("opened")) => (
+                ActivityType::Issue,
+                json!({
+                    "title": field("issue", "title"),
+                    "number": field("issue", "number"),
+                    "state": field("issue", "state"),
+                    "repository": repo,
+                    "url": field("issue", "html_url"),
+                }),
+            ),
+            ("PullRequestReviewEvent", _) => (
+                ActivityType::Review,
+                json!({
+                    "title": field("pull_request", "title"),
+                    "number": field("pull_request", "number"),
+                    "state": field("review", "state"),
+                    "repository": repo,
+                    "url": field("review", "html_url"),
+                }),
+            ),
+            ("ReleaseEvent", Some("published")) => (
+                ActivityType::Release,
+                json!({
+                    "title": field("release", "name"),
+                    "tag": field("release", "tag_name"),
+                    "repository": repo,
+                    "url": field("release", "html_url"),
+                }),
+            ),
+            ("ForkEvent", _) => (ActivityType::Fork, json!({ "repository": repo })),
+            ("WatchEvent", Some("started")) => (ActivityType::Star, json!({ "repository": repo })),
+            _ => continue,
+        };
+
+        matched += 1;
+        models.push(new_activity(account_id, activity_type, date, metadata, Some(repo), !event.public, 1));
+    }
+
+    for ((year, month), summary) in months {
+        let repositories: Vec<Value> = summary
+            .repositories
+            .into_iter()
+            .map(|(name, commit_count)| json!({ "name": name, "commit_count": commit_count }))
+            .collect();
+        let metadata = json!({
+            "repositories": repositories,
+            "total_count": summary.total_count,
+            "year": year,
+            "month": month,
+        });
+        let date = summary.latest_date.unwrap_or_default();
+        models.push(new_activity(
+            account_id,
+            ActivityType::Commit,
+            date,
+            metadata,
+            None,
+            summary.has_private,
+            summary.total_count,
+        ));
+    }
+
+    (models, matched)
+}
+
+/// Row for an imported activity
+fn new_activity(
+    account_id: Uuid,
+    activity_type: ActivityType,
+    date: NaiveDate,
+    metadata: Value,
+    repository: Option<&str>,
+    is_private: bool,
+    count: i32,
+) -> activity::ActiveModel {
+    activity::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        git_platform_account_id: Set(account_id),
+        activity_type: Set(activity_type),
+        activity_date: Set(date),
+        metadata: Set(metadata),
+        repository_name: Set(repository.map(str::to_string)),
+        repository_url: Set(repository.map(|r| format!("https://github.com/{}", r))),
+        is_private_repo: Set(is_private),
+        count: Set(count),
+        primary_language: Set(None),
+        organization_name: Set(None),
+        organization_avatar_url: Set(None),
+        natural_key: NotSet,
+        imported: Set(true),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+    }
+}
@@ -0,0 +1,175 @@
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::activity::{self, ActivityType};
+use crate::utils::db::INSERT_CHUNK_SIZE;
+
+/// What storing a sync's activities changed
+#[derive(Debug, Default)]
+pub struct StoreStats {
+    /// Activities added or updated in place
+    pub stored: u64,
+    /// Activities in the synced scope the platform no longer returned
+    pub removed: u64,
+}
+
+/// What identifies an activity on its platform, before hashing. Counts,
+/// titles and other details that change between syncs are left out where
+/// the activity has a stable identity, so syncing again updates it.
+fn identity(a: &activity::ActiveModel) -> String {
+    let text = |value: &ActiveValue<Option<String>>| value.try_as_ref().cloned().flatten().unwrap_or_default();
+    let metadata = a.metadata.try_as_ref().cloned().unwrap_or_default();
+    let meta_str = |key: &str| metadata.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let date = a.activity_date.try_as_ref().map(|d| d.to_string()).unwrap_or_default();
+    let repository = text(&a.repository_name);
+
+    let Some(activity_type) = a.activity_type.try_as_ref() else {
+        return format!("|{}|{}|{}", date, repository, metadata);
+    };
+    let kind = format!("{:?}", activity_type);
+    // Monthly commit summaries carry their month
+    let month = metadata
+        .get("year")
+        .and_then(|v| v.as_i64())
+        .zip(metadata.get("month").and_then(|v| v.as_u64()));
+
+    match (activity_type, month) {
+        (ActivityType::Commit, Some((year, month))) => format!("{}|{}-{:02}|{}", kind, year, month, repository),
+        (ActivityType::Commit, None) => format!("{}|{}|{}|{}", kind, date, repository, meta_str("ref")),
+        (ActivityType::RepositoryCreated, _) if !repository.is_empty() => format!("{}|{}", kind, repository),
+        (ActivityType::RepositoryCreated, _) if !meta_str("name").is_empty() => {
+            format!("{}|{}", kind, meta_str("name"))
+        }
+        // Join dates get more accurate over time; the organization stays
+        (ActivityType::OrganizationJoined, _) => format!("{}|{}", kind, text(&a.organization_name)),
+        _ if !meta_str("url").is_empty() => format!("{}|{}", kind, meta_str("url")),
+        _ => format!(
+            "{}|{}|{}|{}|{}",
+            kind,
+            date,
+            repository,
+            text(&a.organization_name),
+            metadata
+        ),
+    }
+}
+
+/// Set the natural key of each activity: the SHA-256 of its identity. Where
+/// a batch has several activities with the same identity (e.g. two pushes of
+/// the same branch on one day), the repeats are numbered in the order the
+/// platform returned them, which is the same on every sync.
+pub fn assign_keys(models: &mut [activity::ActiveModel]) {
+    let mut seen: HashMap<String, u32> = HashMap::new();
+
+    for model in models {
+        let identity = identity(model);
+        let repeat = seen.entry(identity.clone()).or_default();
+        let identity = match *repeat {
+            0 => identity,
+            n => format!("{}#{}", identity, n),
+        };
+        *repeat += 1;
+
+        model.natural_key = Set(Some(hex::encode(Sha256::digest(identity.as_bytes()))));
+    }
+}
+
+/// Store the activities a sync fetched for an account. Stored activities
+/// with the same natural key are updated in place, keeping their ID, and
+/// new ones are added. Activities matching `scope` (e.g. the synced date
+/// range) that were not fetched again are removed, including ones stored
+/// before natural keys existed, but not imported ones: the platform may
+/// simply no longer return them. Milestones are never removed either, as no
+/// platform returns them. An imported activity fetched again becomes a
+/// synced one. Run it in a transaction so a failed sync keeps the old
+/// activities.
+pub async fn replace<C: ConnectionTrait>(
+    db: &C,
+    account_id: Uuid,
+    scope: Condition,
+    mut models: Vec<activity::ActiveModel>,
+) -> Result<StoreStats, DbErr> {
+    assign_keys(&mut models);
+    let keys: HashSet<String> = models
+        .iter()
+        .filter_map(|m| m.natural_key.try_as_ref().cloned().flatten())
+        .collect();
+
+    let stale: Vec<Uuid> = activity::Entity::find()
+        .select_only()
+        .columns([activity::Column::Id, activity::Column::NaturalKey])
+        .filter(activity::Column::GitPlatformAccountId.eq(account_id))
+        .filter(activity::Column::Imported.eq(false))
+        .filter(activity::Column::ActivityType.ne(ActivityType::Milestone))
+        .filter(scope)
+        .into_tuple::<(Uuid, Option<String>)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|(_, key)| !key.as_ref().is_some_and(|key| keys.contains(key)))
+        .map(|(id, _)| id)
+        .collect();
+
+    let mut stats = StoreStats::default();
+    for ids in stale.chunks(INSERT_CHUNK_SIZE) {
+        stats.removed += activity::Entity::delete_many()
+            .filter(activity::Column::Id.is_in(ids.iter().copied()))
+            .exec(db)
+            .await?
+            .rows_affected;
+    }
+
+    let on_conflict = OnConflict::columns([activity::Column::GitPlatformAccountId, activity::Column::NaturalKey])
+        .update_columns([
+            activity::Column::ActivityDate,
+            activity::Column::Metadata,
+            activity::Column::RepositoryName,
+            activity::Column::RepositoryUrl,
+            activity::Column::IsPrivateRepo,
+            activity::Column::Count,
+            activity::Column::PrimaryLanguage,
+            activity::Column::OrganizationName,
+            activity::Column::OrganizationAvatarUrl,
+            activity::Column::Imported,
+            activity::Column::UpdatedAt,
+        ])
+        .to_owned();
+
+    let mut models = models.into_iter().peekable();
+    while models.peek().is_some() {
+        let chunk: Vec<_> = models.by_ref().take(INSERT_CHUNK_SIZE).collect();
+        stats.stored += chunk.len() as u64;
+        activity::Entity::insert_many(chunk)
+            .on_conflict(on_conflict.clone())
+            .exec_without_returning(db)
+            .await?;
+    }
+
+    Ok(stats)
+}
+
+/// Add the activities whose natural key is not stored yet and leave the
+/// stored ones as they are, so an import never overwrites what a sync (or an
+/// earlier import) found. Keys must already be set with [`assign_keys`],
+/// over the whole batch if it is stored in parts. Returns how many were
+/// added.
+pub async fn insert_missing<C: ConnectionTrait>(db: &C, models: Vec<activity::ActiveModel>) -> Result<u64, DbErr> {
+    let on_conflict = OnConflict::columns([activity::Column::GitPlatformAccountId, activity::Column::NaturalKey])
+        .do_nothing()
+        .to_owned();
+
+    let mut inserted = 0;
+    let mut models = models.into_iter().peekable();
+    while models.peek().is_some() {
+        let chunk: Vec<_> = models.by_ref().take(INSERT_CHUNK_SIZE).collect();
+        inserted += activity::Entity::insert_many(chunk)
+            .on_conflict(on_conflict.clone())
+            .exec_without_returning(db)
+            .await?;
+    }
+
+    Ok(inserted)
+}
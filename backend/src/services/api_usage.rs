@@ -0,0 +1,242 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{git_platform_account, platform_api_usage_hourly, user};
+use crate::utils::api_usage::{Tallies, UsageRecorder};
+
+/// Hours of API usage kept; the cleanup job removes older ones
+pub const RETENTION_HOURS: i64 = 7 * 24;
+
+/// Accounts listed in the admin overview
+const TOP_ACCOUNTS: usize = 50;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HourlyUsage {
+    /// Start of the hour
+    pub period_start: String,
+    /// `rest` or `graphql`
+    pub api: String,
+    pub requests: i32,
+    pub failed: i32,
+    pub rate_limited: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTotals {
+    /// `rest` or `graphql`
+    pub api: String,
+    pub requests: i64,
+    /// Requests that got no response or a 5xx
+    pub failed: i64,
+    /// Requests the platform refused for the rate limit
+    pub rate_limited: i64,
+    /// The rate limit the platform reported last; null if it never did
+    pub rate_limit_remaining: Option<i32>,
+    pub rate_limit_limit: Option<i32>,
+    pub rate_limit_reset_at: Option<String>,
+    pub last_called_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiUsage {
+    pub platform_account_id: String,
+    pub hours: i64,
+    /// One entry per kind of API called in the period
+    pub apis: Vec<ApiTotals>,
+    /// Hours with calls, oldest first
+    pub hourly: Vec<HourlyUsage>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountUsage {
+    pub platform_account_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub platform: String,
+    pub platform_username: String,
+    pub requests: i64,
+    pub failed: i64,
+    pub rate_limited: i64,
+}
+
+/// Run `future`, a sync of one platform account, and store the platform API
+/// calls it made. Failing to store them is logged, not returned.
+pub async fn track<F: Future>(db: &DatabaseConnection, account_id: Uuid, future: F) -> F::Output {
+    let recorder = UsageRecorder::default();
+    let output = recorder.scope(future).await;
+
+    if let Err(e) = save(db, account_id, recorder.take()).await {
+        log::warn!("Failed to save API usage of platform account {}: {}", account_id, e);
+    }
+
+    output
+}
+
+/// Add counted calls to the account's hourly rows
+async fn save(db: &DatabaseConnection, account_id: Uuid, tallies: Tallies) -> Result<(), DbErr> {
+    use platform_api_usage_hourly::{Column, Entity};
+
+    for ((api, period_start), tally) in tallies {
+        let Some(last_called_at) = tally.last_called_at else {
+            continue;
+        };
+
+        let add = |column: Column, count: i32| Expr::col((Entity, column)).add(count);
+        let mut on_conflict = OnConflict::columns([Column::PlatformAccountId, Column::PeriodStart, Column::Api]);
+        on_conflict
+            .value(Column::Requests, add(Column::Requests, tally.requests))
+            .value(Column::Failed, add(Column::Failed, tally.failed))
+            .value(Column::RateLimited, add(Column::RateLimited, tally.rate_limited))
+            .update_column(Column::LastCalledAt);
+        if tally.rate_limit.is_some() {
+            on_conflict.update_columns([
+                Column::RateLimitRemaining,
+                Column::RateLimitLimit,
+                Column::RateLimitResetAt,
+            ]);
+        }
+
+        let rate_limit = tally.rate_limit.unwrap_or_default();
+        Entity::insert(platform_api_usage_hourly::ActiveModel {
+            platform_account_id: Set(account_id),
+            period_start: Set(period_start),
+            api: Set(api.as_str().to_string()),
+            requests: Set(tally.requests),
+            failed: Set(tally.failed),
+            rate_limited: Set(tally.rate_limited),
+            rate_limit_remaining: Set(rate_limit.remaining),
+            rate_limit_limit: Set(rate_limit.limit),
+            rate_limit_reset_at: Set(rate_limit.reset_at),
+            last_called_at: Set(last_called_at),
+        })
+        .on_conflict(on_conflict)
+        .exec_without_returning(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Start of the first hour of a period of `hours` hours ending now
+fn period_start(hours: i64) -> DateTime<Utc> {
+    let now = Utc::now();
+    now.duration_trunc(Duration::hours(1)).unwrap_or(now) - Duration::hours(hours - 1)
+}
+
+/// Platform API calls of one account over the last `hours` hours, the
+/// current hour included
+pub async fn summary(db: &DatabaseConnection, account_id: Uuid, hours: i64) -> Result<ApiUsage, DbErr> {
+    let rows = platform_api_usage_hourly::Entity::find()
+        .filter(platform_api_usage_hourly::Column::PlatformAccountId.eq(account_id))
+        .filter(platform_api_usage_hourly::Column::PeriodStart.gte(period_start(hours)))
+        .order_by_asc(platform_api_usage_hourly::Column::PeriodStart)
+        .order_by_asc(platform_api_usage_hourly::Column::Api)
+        .all(db)
+        .await?;
+
+    let mut apis: BTreeMap<String, ApiTotals> = BTreeMap::new();
+    for row in &rows {
+        let totals = apis.entry(row.api.clone()).or_insert_with(|| ApiTotals {
+            api: row.api.clone(),
+            requests: 0,
+            failed: 0,
+            rate_limited: 0,
+            rate_limit_remaining: None,
+            rate_limit_limit: None,
+            rate_limit_reset_at: None,
+            last_called_at: row.last_called_at.to_rfc3339(),
+        });
+        totals.requests += row.requests as i64;
+        totals.failed += row.failed as i64;
+        totals.rate_limited += row.rate_limited as i64;
+        // Rows are oldest first, so the last reported limit wins
+        totals.last_called_at = row.last_called_at.to_rfc3339();
+        if row.rate_limit_remaining.is_some() || row.rate_limit_limit.is_some() {
+            totals.rate_limit_remaining = row.rate_limit_remaining;
+            totals.rate_limit_limit = row.rate_limit_limit;
+            totals.rate_limit_reset_at = row.rate_limit_reset_at.map(|at| at.to_rfc3339());
+        }
+    }
+
+    Ok(ApiUsage {
+        platform_account_id: account_id.to_string(),
+        hours,
+        apis: apis.into_values().collect(),
+        hourly: rows
+            .into_iter()
+            .map(|row| HourlyUsage {
+                period_start: row.period_start.to_rfc3339(),
+                api: row.api,
+                requests: row.requests,
+                failed: row.failed,
+                rate_limited: row.rate_limited,
+            })
+            .collect(),
+    })
+}
+
+/// The accounts that made the most platform API calls over the last
+/// `hours` hours, most first
+pub async fn top_accounts(db: &DatabaseConnection, hours: i64) -> Result<Vec<AccountUsage>, DbErr> {
+    let rows: Vec<(Uuid, i32, i32, i32)> = platform_api_usage_hourly::Entity::find()
+        .select_only()
+        .columns([
+            platform_api_usage_hourly::Column::PlatformAccountId,
+            platform_api_usage_hourly::Column::Requests,
+            platform_api_usage_hourly::Column::Failed,
+            platform_api_usage_hourly::Column::RateLimited,
+        ])
+        .filter(platform_api_usage_hourly::Column::PeriodStart.gte(period_start(hours)))
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut per_account: HashMap<Uuid, (i64, i64, i64)> = HashMap::new();
+    for (account_id, requests, failed, rate_limited) in rows {
+        let totals = per_account.entry(account_id).or_default();
+        totals.0 += requests as i64;
+        totals.1 += failed as i64;
+        totals.2 += rate_limited as i64;
+    }
+    let mut ranked: Vec<(Uuid, (i64, i64, i64))> = per_account.into_iter().collect();
+    ranked.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(TOP_ACCOUNTS);
+
+    let accounts: HashMap<Uuid, git_platform_account::Model> = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::Id.is_in(ranked.iter().map(|(id, _)| *id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|account| (account.id, account))
+        .collect();
+    let usernames: HashMap<Uuid, String> = user::Entity::find()
+        .filter(user::Column::Id.is_in(accounts.values().map(|account| account.user_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.username))
+        .collect();
+
+    Ok(ranked
+        .into_iter()
+        .filter_map(|(account_id, (requests, failed, rate_limited))| {
+            let account = accounts.get(&account_id)?;
+            Some(AccountUsage {
+                platform_account_id: account_id.to_string(),
+                user_id: account.user_id.to_string(),
+                username: usernames.get(&account.user_id).cloned().unwrap_or_default(),
+                platform: format!("{:?}", account.platform_type).to_lowercase(),
+                platform_username: account.platform_username.clone(),
+                requests,
+                failed,
+                rate_limited,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,408 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sea_orm::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::models::{
+    achievement, activity, announcement, annotation, api_token, commit_detail, commit_email, contribution, email_digest, follow,
+    generated_heatmap, git_platform_account, heatmap_access_daily, heatmap_generation_setting, heatmap_share_link,
+    heatmap_theme, instance_setting, invitation, milestone, mqtt_publisher, notification_channel, oauth_application,
+    oauth_application_setting, organization_membership, platform_account_disconnection, platform_account_label,
+    platform_account_visibility, repository_language, team, team_member, user, user_identity, user_job_limit,
+    user_setting, username_redirect,
+};
+
+/// Bumped when the archive layout changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Rows per query when reading and per INSERT when restoring
+const CHUNK: u64 = 500;
+
+const MANIFEST_PATH: &str = "manifest.json";
+
+/// Tables in the archive, parents before children. Sessions, OAuth states,
+/// pending email changes, the job queues and platform API usage are left
+/// out; heatmap files are regenerated after a restore.
+const TABLES: &[&str] = &[
+    "users",
+    "user_settings",
+    "user_identities",
+    "username_redirects",
+    "email_digests",
+    "commit_emails",
+    "mqtt_publishers",
+    "notification_channels",
+    "git_platform_accounts",
+    "platform_account_visibility",
+    "platform_account_labels",
+    "platform_account_disconnections",
+    "organization_memberships",
+    "repository_languages",
+    "commit_details",
+    "contributions",
+    "activities",
+    "milestones",
+    "achievements",
+    "api_tokens",
+    "invitations",
+    "announcements",
+    "instance_settings",
+    "oauth_applications",
+    "oauth_application_settings",
+    "heatmap_themes",
+    "heatmap_generation_settings",
+    "heatmap_access_daily",
+    "heatmap_share_links",
+    "user_job_limits",
+    "teams",
+    "team_members",
+    "follows",
+    "annotations",
+];
+
+/// Describes an archive; stored as manifest.json, its first entry
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    /// Database the backup was taken from; any supported one can restore it
+    pub database: String,
+    /// Start of the SHA-256 of ENCRYPTION_KEY. Platform tokens and OAuth
+    /// secrets in the archive only decrypt with the same key.
+    pub encryption_key_fingerprint: String,
+    pub tables: Vec<TableEntry>,
+    /// Heatmap files that existed at backup time, for checking a restore
+    pub generated_files: Vec<GeneratedFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TableEntry {
+    pub name: String,
+    pub rows: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GeneratedFile {
+    pub user_id: Uuid,
+    pub theme_id: Uuid,
+    pub format: String,
+    pub file_path: String,
+    pub size_bytes: Option<i64>,
+    pub sha256: Option<String>,
+}
+
+fn key_fingerprint(key: &str) -> String {
+    hex::encode(&Sha256::digest(key.trim().as_bytes())[..8])
+}
+
+/// Fingerprints of the ENCRYPTION_KEY entries, newest first. Archives record
+/// the newest, so a backup taken before a rotation still restores while the
+/// old key is configured.
+fn key_fingerprints(encryption_key: &str) -> Vec<String> {
+    encryption_key.split(',').map(key_fingerprint).collect()
+}
+
+fn table_path(table: &str) -> String {
+    format!("tables/{}.jsonl", table)
+}
+
+/// Write every table to a .tar.gz archive. The tables are read in one
+/// read-only transaction, so the archive is consistent while the server runs.
+pub async fn backup(db: &DatabaseConnection, output: &Path, encryption_key: &str) -> Result<Manifest> {
+    let txn = match db.get_database_backend() {
+        // SQLite transactions already read from one snapshot
+        DbBackend::Sqlite => db.begin().await?,
+        _ => {
+            db.begin_with_config(Some(IsolationLevel::RepeatableRead), Some(AccessMode::ReadOnly))
+                .await?
+        }
+    };
+
+    let mut tables = Vec::new();
+    let mut entries = Vec::new();
+    for table in TABLES {
+        let (rows, data) = dump_table(&txn, table).await?;
+        tables.push(TableEntry {
+            name: (*table).to_owned(),
+            rows,
+        });
+        entries.push((table_path(table), data));
+    }
+
+    let generated_files = generated_heatmap::Entity::find()
+        .order_by_asc(generated_heatmap::Column::UserId)
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|file| GeneratedFile {
+            user_id: file.user_id,
+            theme_id: file.theme_id,
+            format: file.format.to_value(),
+            file_path: file.file_path,
+            size_bytes: file.file_size_bytes,
+            sha256: file.file_hash,
+        })
+        .collect();
+    txn.commit().await?;
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        database: format!("{:?}", db.get_database_backend()),
+        encryption_key_fingerprint: key_fingerprints(encryption_key).swap_remove(0),
+        tables,
+        generated_files,
+    };
+    entries.insert(0, (MANIFEST_PATH.to_string(), serde_json::to_vec_pretty(&manifest)?));
+
+    // Write next to the target and rename, so a failed backup never leaves
+    // a truncated archive under the final name
+    let partial = PathBuf::from(format!("{}.partial", output.display()));
+    write_archive(&partial, &entries)
+        .and_then(|_| std::fs::rename(&partial, output).map_err(Into::into))
+        .inspect_err(|_| {
+            let _ = std::fs::remove_file(&partial);
+        })
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    Ok(manifest)
+}
+
+fn write_archive(path: &Path, entries: &[(String, Vec<u8>)]) -> Result<()> {
+    let mut builder = tar::Builder::new(GzEncoder::new(File::create(path)?, Compression::default()));
+    let mtime = Utc::now().timestamp().max(0) as u64;
+
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        builder.append_data(&mut header, name, data.as_slice())?;
+    }
+
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+async fn dump_table(txn: &DatabaseTransaction, table: &str) -> Result<(u64, Vec<u8>)> {
+    match table {
+        "users" => dump::<user::Entity>(txn).await,
+        "user_settings" => dump::<user_setting::Entity>(txn).await,
+        "user_identities" => dump::<user_identity::Entity>(txn).await,
+        "username_redirects" => dump::<username_redirect::Entity>(txn).await,
+        "email_digests" => dump::<email_digest::Entity>(txn).await,
+        "commit_emails" => dump::<commit_email::Entity>(txn).await,
+        "mqtt_publishers" => dump::<mqtt_publisher::Entity>(txn).await,
+        "notification_channels" => dump::<notification_channel::Entity>(txn).await,
+        "git_platform_accounts" => dump::<git_platform_account::Entity>(txn).await,
+        "platform_account_visibility" => dump::<platform_account_visibility::Entity>(txn).await,
+        "platform_account_labels" => dump::<platform_account_label::Entity>(txn).await,
+        "platform_account_disconnections" => dump::<platform_account_disconnection::Entity>(txn).await,
+        "organization_memberships" => dump::<organization_membership::Entity>(txn).await,
+        "repository_languages" => dump::<repository_language::Entity>(txn).await,
+        "commit_details" => dump::<commit_detail::Entity>(txn).await,
+        "contributions" => dump::<contribution::Entity>(txn).await,
+        "activities" => dump::<activity::Entity>(txn).await,
+        "milestones" => dump::<milestone::Entity>(txn).await,
+        "achievements" => dump::<achievement::Entity>(txn).await,
+        "api_tokens" => dump::<api_token::Entity>(txn).await,
+        "invitations" => dump::<invitation::Entity>(txn).await,
+        "announcements" => dump::<announcement::Entity>(txn).await,
+        "instance_settings" => dump::<instance_setting::Entity>(txn).await,
+        "oauth_applications" => dump::<oauth_application::Entity>(txn).await,
+        "oauth_application_settings" => dump::<oauth_application_setting::Entity>(txn).await,
+        "heatmap_themes" => dump::<heatmap_theme::Entity>(txn).await,
+        "heatmap_generation_settings" => dump::<heatmap_generation_setting::Entity>(txn).await,
+        "heatmap_access_daily" => dump::<heatmap_access_daily::Entity>(txn).await,
+        "heatmap_share_links" => dump::<heatmap_share_link::Entity>(txn).await,
+        "user_job_limits" => dump::<user_job_limit::Entity>(txn).await,
+        "teams" => dump::<team::Entity>(txn).await,
+        "team_members" => dump::<team_member::Entity>(txn).await,
+        "follows" => dump::<follow::Entity>(txn).await,
+        "annotations" => dump::<annotation::Entity>(txn).await,
+        _ => bail!("Unknown table '{}'", table),
+    }
+}
+
+/// One JSON object per line, in primary key order
+async fn dump<E>(txn: &DatabaseTransaction) -> Result<(u64, Vec<u8>)>
+where
+    E: EntityTrait,
+    E::Model: Serialize + Sync,
+{
+    let mut query = E::find();
+    for key in E::PrimaryKey::iter() {
+        query = query.order_by_asc(key.into_column());
+    }
+
+    let mut pages = query.paginate(txn, CHUNK);
+    let (mut rows, mut data) = (0, Vec::new());
+    while let Some(models) = pages.fetch_and_next().await? {
+        for model in models {
+            serde_json::to_writer(&mut data, &model)?;
+            data.push(b'\n');
+            rows += 1;
+        }
+    }
+
+    Ok((rows, data))
+}
+
+pub struct RestoreOptions {
+    /// Restore even though stored secrets will not decrypt
+    pub ignore_key_mismatch: bool,
+}
+
+/// Load an archive made by [`backup`] into an empty database, in one
+/// transaction. Heatmap files are not part of the archive; regenerate them
+/// afterwards.
+pub async fn restore(
+    db: &DatabaseConnection,
+    archive: &Path,
+    encryption_key: &str,
+    options: &RestoreOptions,
+) -> Result<Manifest> {
+    if user::Entity::find().count(db).await? > 0 {
+        bail!("The database already has users; restore into a fresh instance");
+    }
+
+    let (manifest, entries) =
+        read_archive(archive).with_context(|| format!("Failed to read {}", archive.display()))?;
+
+    if manifest.format_version > FORMAT_VERSION {
+        bail!(
+            "The archive was made by hgitmap {} in a newer format; upgrade before restoring",
+            manifest.app_version
+        );
+    }
+    if !key_fingerprints(encryption_key).contains(&manifest.encryption_key_fingerprint) && !options.ignore_key_mismatch {
+        bail!(
+            "The archive was made with a different ENCRYPTION_KEY. Set the old key, or pass \
+             --ignore-key-mismatch to restore anyway (platform tokens and OAuth app secrets will \
+             not decrypt and have to be entered again)"
+        );
+    }
+
+    let txn = db.begin().await?;
+    for table in TABLES {
+        let Some(data) = entries.get(&table_path(table)) else {
+            continue;
+        };
+        let rows = restore_table(&txn, table, data).await?;
+        log::info!("📦 Restored {} rows into {}", rows, table);
+
+        if *table == "users" {
+            // Creating a user adds default themes and generation settings;
+            // the archive brings the real ones
+            heatmap_theme::Entity::delete_many().exec(&txn).await?;
+            heatmap_generation_setting::Entity::delete_many().exec(&txn).await?;
+        }
+    }
+    txn.commit().await?;
+
+    Ok(manifest)
+}
+
+/// The manifest and the other entries of an archive, by path
+fn read_archive(path: &Path) -> Result<(Manifest, HashMap<String, Vec<u8>>)> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+    let mut manifest = None;
+    let mut entries = HashMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        if name == MANIFEST_PATH {
+            manifest = Some(serde_json::from_slice(&data).context("Invalid manifest.json")?);
+        } else {
+            entries.insert(name, data);
+        }
+    }
+
+    let manifest = manifest.context("Not an hgitmap backup: manifest.json is missing")?;
+    Ok((manifest, entries))
+}
+
+async fn restore_table(txn: &DatabaseTransaction, table: &str, data: &[u8]) -> Result<u64> {
+    let rows = match table {
+        "users" => load::<user::ActiveModel>(txn, data).await,
+        "user_settings" => load::<user_setting::ActiveModel>(txn, data).await,
+        "user_identities" => load::<user_identity::ActiveModel>(txn, data).await,
+        "username_redirects" => load::<username_redirect::ActiveModel>(txn, data).await,
+        "email_digests" => load::<email_digest::ActiveModel>(txn, data).await,
+        "commit_emails" => load::<commit_email::ActiveModel>(txn, data).await,
+        "mqtt_publishers" => load::<mqtt_publisher::ActiveModel>(txn, data).await,
+        "notification_channels" => load::<notification_channel::ActiveModel>(txn, data).await,
+        "git_platform_accounts" => load::<git_platform_account::ActiveModel>(txn, data).await,
+        "platform_account_visibility" => load::<platform_account_visibility::ActiveModel>(txn, data).await,
+        "platform_account_labels" => load::<platform_account_label::ActiveModel>(txn, data).await,
+        "platform_account_disconnections" => load::<platform_account_disconnection::ActiveModel>(txn, data).await,
+        "organization_memberships" => load::<organization_membership::ActiveModel>(txn, data).await,
+        "repository_languages" => load::<repository_language::ActiveModel>(txn, data).await,
+        "commit_details" => load::<commit_detail::ActiveModel>(txn, data).await,
+        "contributions" => load::<contribution::ActiveModel>(txn, data).await,
+        "activities" => load::<activity::ActiveModel>(txn, data).await,
+        "milestones" => load::<milestone::ActiveModel>(txn, data).await,
+        "achievements" => load::<achievement::ActiveModel>(txn, data).await,
+        "api_tokens" => load::<api_token::ActiveModel>(txn, data).await,
+        "invitations" => load::<invitation::ActiveModel>(txn, data).await,
+        "announcements" => load::<announcement::ActiveModel>(txn, data).await,
+        "instance_settings" => load::<instance_setting::ActiveModel>(txn, data).await,
+        "oauth_applications" => load::<oauth_application::ActiveModel>(txn, data).await,
+        "oauth_application_settings" => load::<oauth_application_setting::ActiveModel>(txn, data).await,
+        "heatmap_themes" => load::<heatmap_theme::ActiveModel>(txn, data).await,
+        "heatmap_generation_settings" => load::<heatmap_generation_setting::ActiveModel>(txn, data).await,
+        "heatmap_access_daily" => load::<heatmap_access_daily::ActiveModel>(txn, data).await,
+        "heatmap_share_links" => load::<heatmap_share_link::ActiveModel>(txn, data).await,
+        "user_job_limits" => load::<user_job_limit::ActiveModel>(txn, data).await,
+        "teams" => load::<team::ActiveModel>(txn, data).await,
+        "team_members" => load::<team_member::ActiveModel>(txn, data).await,
+        "follows" => load::<follow::ActiveModel>(txn, data).await,
+        "annotations" => load::<annotation::ActiveModel>(txn, data).await,
+        _ => bail!("Unknown table '{}'", table),
+    };
+    rows.with_context(|| format!("Failed to restore {}", table))
+}
+
+async fn load<A>(txn: &DatabaseTransaction, data: &[u8]) -> Result<u64>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Send,
+    <A::Entity as EntityTrait>::Model: DeserializeOwned + IntoActiveModel<A>,
+{
+    let mut batch = Vec::new();
+    let mut rows = 0;
+
+    for (number, line) in data.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let model: <A::Entity as EntityTrait>::Model =
+            serde_json::from_str(&line).with_context(|| format!("Invalid row on line {}", number + 1))?;
+        batch.push(model.into_active_model().reset_all());
+        rows += 1;
+
+        if batch.len() as u64 == CHUNK {
+            A::Entity::insert_many(std::mem::take(&mut batch))
+                .exec_without_returning(txn)
+                .await?;
+        }
+    }
+    if !batch.is_empty() {
+        A::Entity::insert_many(batch).exec_without_returning(txn).await?;
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::config::Config;
+use crate::utils::http_client::{shared_client, SendTraced};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    HCaptcha,
+    Turnstile,
+}
+
+impl CaptchaProvider {
+    fn verify_url(self) -> &'static str {
+        match self {
+            Self::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            Self::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+        }
+    }
+}
+
+/// Both providers answer with the same shape
+#[derive(Debug, Deserialize)]
+struct VerifyResponse {
+    success: bool,
+    #[serde(rename = "error-codes", default)]
+    error_codes: Vec<String>,
+}
+
+struct Settings {
+    provider: CaptchaProvider,
+    site_key: String,
+    secret_key: String,
+}
+
+/// Checks hCaptcha or Turnstile tokens sent with registration and login.
+/// Disabled unless CAPTCHA_PROVIDER is set.
+pub struct CaptchaVerifier {
+    settings: Option<Settings>,
+    http: reqwest::Client,
+}
+
+impl CaptchaVerifier {
+    pub fn from_config(config: &Config) -> Self {
+        let settings = config.captcha_provider.as_deref().map(|provider| {
+            let provider = match provider {
+                "hcaptcha" => CaptchaProvider::HCaptcha,
+                "turnstile" => CaptchaProvider::Turnstile,
+                _ => panic!("CAPTCHA_PROVIDER must be hcaptcha or turnstile"),
+            };
+            assert!(
+                !config.captcha_secret_key.is_empty(),
+                "CAPTCHA_SECRET_KEY must be set when CAPTCHA_PROVIDER is"
+            );
+            Settings {
+                provider,
+                site_key: config.captcha_site_key.clone(),
+                secret_key: config.captcha_secret_key.clone(),
+            }
+        });
+
+        Self {
+            settings,
+            http: shared_client(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.is_some()
+    }
+
+    pub fn provider(&self) -> Option<CaptchaProvider> {
+        self.settings.as_ref().map(|s| s.provider)
+    }
+
+    pub fn site_key(&self) -> Option<&str> {
+        self.settings.as_ref().map(|s| s.site_key.as_str())
+    }
+
+    /// Whether the widget token proves a human solved the challenge. Always
+    /// true when disabled; a missing token fails when enabled.
+    pub async fn verify(&self, token: Option<&str>, remote_ip: Option<&str>) -> Result<bool> {
+        let Some(settings) = &self.settings else {
+            return Ok(true);
+        };
+        let Some(token) = token.map(str::trim).filter(|t| !t.is_empty()) else {
+            return Ok(false);
+        };
+
+        let mut form = vec![
+            ("secret", settings.secret_key.as_str()),
+            ("response", token),
+        ];
+        if let Some(remote_ip) = remote_ip {
+            form.push(("remoteip", remote_ip));
+        }
+        if settings.provider == CaptchaProvider::HCaptcha && !settings.site_key.is_empty() {
+            form.push(("sitekey", settings.site_key.as_str()));
+        }
+
+        let response: VerifyResponse = self
+            .http
+            .post(settings.provider.verify_url())
+            .form(&form)
+            .send_traced()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid CAPTCHA verification response")?;
+
+        if !response.success {
+            log::warn!("🤖 [Captcha] Challenge failed: {}", response.error_codes.join(", "));
+        }
+        Ok(response.success)
+    }
+}
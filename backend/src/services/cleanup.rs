@@ -0,0 +1,272 @@
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::time::{sleep, Duration as TokioDuration};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{
+    generated_heatmap, heatmap_access_daily, heatmap_generation_setting, oauth_state, platform_api_usage_hourly,
+};
+use crate::services::api_usage;
+use crate::services::heatmap_analytics;
+use crate::services::heatmap_generator::{mini_file_path, HEATMAP_BASE_DIR};
+use crate::services::platform_disconnect;
+use crate::services::shutdown::Shutdown;
+use crate::utils::config::Config;
+
+/// How often the cleanup job runs
+const CLEANUP_INTERVAL_HOURS: u64 = 24;
+
+/// Files younger than this are left alone; a generation may not have
+/// recorded them yet
+const FILE_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// What a cleanup run removed (or would remove)
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct CleanupReport {
+    /// True when nothing was deleted
+    pub dry_run: bool,
+    /// Heatmap files no generated heatmap record points at
+    pub orphaned_files: u64,
+    pub orphaned_file_bytes: u64,
+    /// Generated heatmap records whose file is gone
+    pub missing_file_records: u64,
+    /// Invalidated generated heatmaps older than CLEANUP_INVALID_HEATMAP_DAYS
+    pub invalid_heatmaps: u64,
+    pub invalid_heatmap_bytes: u64,
+    /// OAuth states past their expiry
+    pub expired_oauth_states: u64,
+    /// Daily heatmap view counts older than the analytics retention
+    pub old_view_counts: u64,
+    /// Hourly platform API usage older than a week
+    pub old_api_usage: u64,
+    /// Disconnected platform accounts past DISCONNECTED_ACCOUNT_RETENTION_DAYS,
+    /// deleted with their contributions and activities
+    pub purged_platform_accounts: u64,
+    /// Disk space freed in total
+    pub reclaimed_bytes: u64,
+}
+
+/// Stored paths under the default directory may be URL-style
+/// ("/static/heatmaps/..."); custom storage paths may be absolute
+fn stored_path(path: &str) -> PathBuf {
+    match path.strip_prefix('/') {
+        Some(relative) if relative.starts_with(HEATMAP_BASE_DIR) => PathBuf::from(relative),
+        _ => PathBuf::from(path),
+    }
+}
+
+async fn file_size(path: &Path) -> Option<u64> {
+    tokio::fs::metadata(path)
+        .await
+        .ok()
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+}
+
+async fn remove_file(path: &Path) {
+    if let Err(e) = tokio::fs::remove_file(path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("⚠️  [Cleanup] Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Directories generated heatmaps are written to: the default one and any
+/// custom storage paths. Each holds one directory per user.
+async fn storage_roots(db: &DatabaseConnection) -> Result<BTreeSet<PathBuf>, DbErr> {
+    let custom: Vec<Option<String>> = heatmap_generation_setting::Entity::find()
+        .select_only()
+        .column(heatmap_generation_setting::Column::StoragePath)
+        .filter(heatmap_generation_setting::Column::StoragePath.is_not_null())
+        .distinct()
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut roots = BTreeSet::from([PathBuf::from(HEATMAP_BASE_DIR)]);
+    roots.extend(custom.into_iter().flatten().map(PathBuf::from));
+    Ok(roots)
+}
+
+/// Files in the per-user directories of `root` older than the grace period
+async fn stored_files(root: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let Ok(mut user_dirs) = tokio::fs::read_dir(root).await else {
+        return files;
+    };
+
+    while let Ok(Some(user_dir)) = user_dirs.next_entry().await {
+        let is_user_dir = user_dir.file_type().await.is_ok_and(|t| t.is_dir())
+            && user_dir.file_name().to_str().is_some_and(|n| Uuid::parse_str(n).is_ok());
+        if !is_user_dir {
+            continue;
+        }
+
+        let Ok(mut entries) = tokio::fs::read_dir(user_dir.path()).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let Ok(metadata) = entry.metadata().await else {
+                continue;
+            };
+            let settled = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= FILE_GRACE_PERIOD);
+            if metadata.is_file() && settled {
+                files.push((entry.path(), metadata.len()));
+            }
+        }
+    }
+
+    files
+}
+
+/// Remove orphaned heatmap files, records of missing files, old invalidated
+/// heatmaps, expired OAuth states, old view counts and disconnected platform
+/// accounts past their retention. A dry run only counts them.
+pub async fn run(db: &DatabaseConnection, config: &Config, dry_run: bool) -> Result<CleanupReport, DbErr> {
+    let mut report = CleanupReport {
+        dry_run,
+        ..Default::default()
+    };
+    let now = Utc::now();
+    let invalid_cutoff = now - Duration::days(config.cleanup_invalid_heatmap_days);
+    let roots = storage_roots(db).await?;
+
+    let mut referenced = HashSet::new();
+    let mut stale_records = Vec::new();
+
+    for heatmap in generated_heatmap::Entity::find().all(db).await? {
+        let path = stored_path(&heatmap.file_path);
+
+        if !heatmap.is_valid && heatmap.generated_at < invalid_cutoff {
+            let size = file_size(&path).await.unwrap_or(0);
+            report.invalid_heatmaps += 1;
+            report.invalid_heatmap_bytes += size;
+            if !dry_run {
+                remove_file(&path).await;
+            }
+            stale_records.push(heatmap.id);
+            continue;
+        }
+
+        // Leave records alone while their storage is not mounted
+        let root_present = roots
+            .iter()
+            .any(|root| path.starts_with(root) && root.is_dir());
+        if root_present && file_size(&path).await.is_none() {
+            report.missing_file_records += 1;
+            stale_records.push(heatmap.id);
+            continue;
+        }
+
+        // The theme's mini heatmap goes with the main file
+        referenced.insert(mini_file_path(&path));
+        referenced.insert(path);
+    }
+
+    if !dry_run && !stale_records.is_empty() {
+        generated_heatmap::Entity::delete_many()
+            .filter(generated_heatmap::Column::Id.is_in(stale_records))
+            .exec(db)
+            .await?;
+    }
+
+    for root in &roots {
+        for (path, size) in stored_files(root).await {
+            if referenced.contains(&path) {
+                continue;
+            }
+            report.orphaned_files += 1;
+            report.orphaned_file_bytes += size;
+            if !dry_run {
+                remove_file(&path).await;
+                // Only succeeds once the user's directory is empty
+                if let Some(dir) = path.parent() {
+                    let _ = tokio::fs::remove_dir(dir).await;
+                }
+            }
+        }
+    }
+
+    let expired_states = oauth_state::Entity::find().filter(oauth_state::Column::ExpiresAt.lt(now));
+    report.expired_oauth_states = if dry_run {
+        expired_states.count(db).await?
+    } else {
+        oauth_state::Entity::delete_many()
+            .filter(oauth_state::Column::ExpiresAt.lt(now))
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+
+    let views_cutoff = now.date_naive() - Duration::days(heatmap_analytics::RETENTION_DAYS);
+    let old_views = heatmap_access_daily::Entity::find().filter(heatmap_access_daily::Column::AccessDate.lt(views_cutoff));
+    report.old_view_counts = if dry_run {
+        old_views.count(db).await?
+    } else {
+        heatmap_access_daily::Entity::delete_many()
+            .filter(heatmap_access_daily::Column::AccessDate.lt(views_cutoff))
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+
+    let usage_cutoff = now - Duration::hours(api_usage::RETENTION_HOURS);
+    let old_usage = platform_api_usage_hourly::Entity::find()
+        .filter(platform_api_usage_hourly::Column::PeriodStart.lt(usage_cutoff));
+    report.old_api_usage = if dry_run {
+        old_usage.count(db).await?
+    } else {
+        platform_api_usage_hourly::Entity::delete_many()
+            .filter(platform_api_usage_hourly::Column::PeriodStart.lt(usage_cutoff))
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+
+    report.purged_platform_accounts = platform_disconnect::purge_expired(db, dry_run).await?;
+
+    report.reclaimed_bytes = report.orphaned_file_bytes + report.invalid_heatmap_bytes;
+
+    Ok(report)
+}
+
+/// Clean up stale files and records once a day
+pub fn start_cleanup_job(
+    db: DatabaseConnection,
+    config: Config,
+    mut shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        log::info!("Starting cleanup job (every {} hours)", CLEANUP_INTERVAL_HOURS);
+
+        loop {
+            match run(&db, &config, false).await {
+                Ok(report) => log::info!(
+                    "🧹 [Cleanup] Removed {} orphaned files, {} invalid heatmaps, {} records of missing files, {} expired OAuth states, {} old view counts, {} hours of old API usage and {} disconnected platform accounts ({} bytes reclaimed)",
+                    report.orphaned_files,
+                    report.invalid_heatmaps,
+                    report.missing_file_records,
+                    report.expired_oauth_states,
+                    report.old_view_counts,
+                    report.old_api_usage,
+                    report.purged_platform_accounts,
+                    report.reclaimed_bytes
+                ),
+                Err(e) => log::error!("Error in cleanup job: {}", e),
+            }
+
+            tokio::select! {
+                _ = sleep(TokioDuration::from_secs(CLEANUP_INTERVAL_HOURS * 3600)) => {}
+                _ = shutdown.requested() => break,
+            }
+        }
+    })
+}
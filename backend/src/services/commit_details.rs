@@ -0,0 +1,335 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Timelike, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{commit_detail, commit_email, git_platform_account, user_setting};
+use crate::services::achievements::user_timezone;
+use crate::services::git_platforms::{
+    CommitDetail, Contribution, ContributionType, GitPlatform, PlatformConfig,
+};
+use crate::services::platform_visibility;
+use crate::services::repository_languages::Contributed;
+use crate::utils::code_frequency::{self, WeekLines};
+use crate::utils::db::INSERT_CHUNK_SIZE;
+use crate::utils::repo_exclusion;
+
+/// Most repositories whose commits are fetched per sync, busiest first
+const MAX_REPOSITORIES_PER_SYNC: usize = 50;
+
+/// Rough size of one stored commit with its index entries, for the storage
+/// estimate shown before detailed sync is turned on
+pub const BYTES_PER_COMMIT: u64 = 250;
+
+/// What detailed sync stores for one account
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CommitStorage {
+    pub commits: u64,
+    pub oldest: Option<String>,
+    pub newest: Option<String>,
+    /// Approximate space the commits take in the database
+    pub estimated_bytes: u64,
+}
+
+/// Commits made in one hour of the day
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HourCount {
+    /// 0 to 23, in the user's timezone
+    pub hour: u32,
+    pub commits: u64,
+}
+
+/// Fetch and store the account's commits in the repositories it contributed
+/// to, when it has detailed sync on. Commits already stored are kept, and a
+/// repository that fails is skipped.
+#[allow(clippy::too_many_arguments)]
+pub async fn refresh(
+    db: &DatabaseConnection,
+    platform: &dyn GitPlatform,
+    config: &PlatformConfig,
+    token: &str,
+    account: &git_platform_account::Model,
+    repositories: &HashMap<String, Contributed>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<u64> {
+    if !account.detailed_sync {
+        return Ok(0);
+    }
+
+    // Nothing is fetched that retention would prune right away
+    let from = match account.commit_retention_days {
+        Some(days) => from.max(Utc::now() - Duration::days(days.into())),
+        None => from,
+    };
+
+    let user_emails = emails(db, account.user_id).await?;
+
+    let mut busiest: Vec<(&String, &Contributed)> = repositories.iter().collect();
+    busiest.sort_by(|(a_name, a), (b_name, b)| b.contributions.cmp(&a.contributions).then_with(|| a_name.cmp(b_name)));
+
+    let mut stored = 0;
+    for (name, contributed) in busiest.into_iter().take(MAX_REPOSITORIES_PER_SYNC) {
+        let commits = match platform
+            .fetch_commit_details(config, &account.platform_username, token, name, &user_emails, from, to)
+            .await
+        {
+            Ok(commits) => commits,
+            Err(e) => {
+                log::warn!("⚠️  Failed to fetch commits of {}: {}", name, e);
+                continue;
+            }
+        };
+        stored += store(db, account.id, name, contributed.is_private, commits).await?;
+    }
+
+    Ok(stored)
+}
+
+/// Insert commits not stored yet
+async fn store(
+    db: &DatabaseConnection,
+    account_id: Uuid,
+    repository: &str,
+    is_private: bool,
+    commits: Vec<CommitDetail>,
+) -> Result<u64> {
+    let mut inserted = 0;
+    for chunk in commits.chunks(INSERT_CHUNK_SIZE) {
+        let rows = chunk.iter().map(|commit| commit_detail::ActiveModel {
+            account_id: Set(account_id),
+            repository_name: Set(repository.to_string()),
+            sha: Set(commit.sha.clone()),
+            committed_at: Set(commit.committed_at),
+            additions: Set(commit.additions),
+            deletions: Set(commit.deletions),
+            is_private_repo: Set(is_private),
+            co_authored: Set(commit.co_authored),
+        });
+        inserted += commit_detail::Entity::insert_many(rows)
+            .on_conflict(
+                OnConflict::columns([
+                    commit_detail::Column::AccountId,
+                    commit_detail::Column::RepositoryName,
+                    commit_detail::Column::Sha,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(inserted)
+}
+
+/// The verified emails the user commits with, lowercase
+pub async fn emails(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<String>, DbErr> {
+    commit_email::Entity::find()
+        .select_only()
+        .column(commit_email::Column::Email)
+        .filter(commit_email::Column::UserId.eq(user_id))
+        .filter(commit_email::Column::VerifiedAt.is_not_null())
+        .order_by_asc(commit_email::Column::Email)
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+/// Add the account's stored co-authored commits from `from` to `to` to its
+/// contributions, as commits per day and repository. GitHub's calendar
+/// counts co-authored commits already, so GitHub accounts get none.
+pub async fn add_co_authored(
+    db: &DatabaseConnection,
+    account: &git_platform_account::Model,
+    contributions: &mut Vec<Contribution>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<usize, DbErr> {
+    if matches!(account.platform_type, git_platform_account::GitPlatform::GitHub) {
+        return Ok(0);
+    }
+
+    let commits = commit_detail::Entity::find()
+        .filter(commit_detail::Column::AccountId.eq(account.id))
+        .filter(commit_detail::Column::CoAuthored.eq(true))
+        .filter(commit_detail::Column::CommittedAt.gte(from.and_time(Default::default()).and_utc()))
+        .filter(commit_detail::Column::CommittedAt.lt(to.and_time(Default::default()).and_utc() + Duration::days(1)))
+        .all(db)
+        .await?;
+    let added = commits.len();
+
+    let mut days: BTreeMap<(NaiveDate, String), (i32, bool)> = BTreeMap::new();
+    for commit in commits {
+        let day = days
+            .entry((commit.committed_at.date_naive(), commit.repository_name))
+            .or_insert((0, commit.is_private_repo));
+        day.0 += 1;
+    }
+
+    // Contributions are unique per day and repository, so a day the
+    // platform already has for the repository gets the commits added
+    for ((date, repository), (count, is_private)) in days {
+        match contributions
+            .iter_mut()
+            .find(|c| c.date == date && c.repository_name.as_deref() == Some(repository.as_str()))
+        {
+            Some(existing) => existing.count += count,
+            None => contributions.push(Contribution {
+                date,
+                count,
+                repository_name: Some(repository),
+                is_private,
+                contribution_type: ContributionType::Commit,
+            }),
+        }
+    }
+
+    Ok(added)
+}
+
+/// Delete every stored commit of the account, as when detailed sync is
+/// turned off
+pub async fn delete_all<C: ConnectionTrait>(db: &C, account_id: Uuid) -> Result<u64, DbErr> {
+    Ok(commit_detail::Entity::delete_many()
+        .filter(commit_detail::Column::AccountId.eq(account_id))
+        .exec(db)
+        .await?
+        .rows_affected)
+}
+
+/// Delete commits older than their account's retention. Run by the daily
+/// retention job.
+pub async fn prune(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::CommitRetentionDays.is_not_null())
+        .all(db)
+        .await?;
+
+    let mut pruned = 0;
+    for account in accounts {
+        let Some(days) = account.commit_retention_days else { continue };
+        pruned += commit_detail::Entity::delete_many()
+            .filter(commit_detail::Column::AccountId.eq(account.id))
+            .filter(commit_detail::Column::CommittedAt.lt(Utc::now() - Duration::days(days.into())))
+            .exec(db)
+            .await?
+            .rows_affected;
+    }
+    Ok(pruned)
+}
+
+/// How many commits the account has stored and over what time
+pub async fn storage(db: &DatabaseConnection, account_id: Uuid) -> Result<CommitStorage, DbErr> {
+    let of_account = || commit_detail::Entity::find().filter(commit_detail::Column::AccountId.eq(account_id));
+
+    let commits = of_account().count(db).await?;
+    let oldest = of_account()
+        .order_by_asc(commit_detail::Column::CommittedAt)
+        .one(db)
+        .await?;
+    let newest = of_account()
+        .order_by_desc(commit_detail::Column::CommittedAt)
+        .one(db)
+        .await?;
+
+    Ok(CommitStorage {
+        commits,
+        oldest: oldest.map(|c| c.committed_at.to_rfc3339()),
+        newest: newest.map(|c| c.committed_at.to_rfc3339()),
+        estimated_bytes: commits * BYTES_PER_COMMIT,
+    })
+}
+
+/// Commits per hour of the day, in the user's timezone, over their active
+/// accounts with detailed sync. Excluded repositories are left out.
+pub async fn hours(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<HourCount>, DbErr> {
+    let excluded_repositories: Vec<String> = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .map(|s| s.excluded_repositories.to_vec())
+        .unwrap_or_default();
+    let timezone = user_timezone(db, user_id).await?;
+
+    let times: Vec<DateTime<Utc>> = commit_detail::Entity::find()
+        .select_only()
+        .column(commit_detail::Column::CommittedAt)
+        .inner_join(git_platform_account::Entity)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(repo_exclusion::not_excluded(
+            commit_detail::Column::RepositoryName,
+            &excluded_repositories,
+        ))
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut counts = [0u64; 24];
+    for time in times {
+        counts[time.with_timezone(&timezone).hour() as usize] += 1;
+    }
+
+    Ok(counts
+        .into_iter()
+        .enumerate()
+        .map(|(hour, commits)| HourCount { hour: hour as u32, commits })
+        .collect())
+}
+
+/// Lines added and removed per week from `from` to `to`, by the user's
+/// local day, with a week for every heatmap column. Only the accounts and
+/// repositories the public profile shows count, so the heatmap can draw it.
+pub async fn code_frequency(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<WeekLines>, DbErr> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+    let show_private_contributions = settings.as_ref().map(|s| s.show_private_contributions).unwrap_or(true);
+    let excluded_repositories: Vec<String> = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.to_vec())
+        .unwrap_or_default();
+    let timezone = user_timezone(db, user_id).await?;
+
+    // A day either side covers every timezone's local days
+    let hidden = platform_visibility::hidden_accounts(db, user_id).await?;
+    let mut query = commit_detail::Entity::find()
+        .select_only()
+        .columns([
+            commit_detail::Column::CommittedAt,
+            commit_detail::Column::Additions,
+            commit_detail::Column::Deletions,
+        ])
+        .inner_join(git_platform_account::Entity)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(git_platform_account::Column::Id.is_not_in(hidden))
+        .filter(commit_detail::Column::CommittedAt.gte(from.and_time(Default::default()).and_utc() - Duration::days(1)))
+        .filter(commit_detail::Column::CommittedAt.lt(to.and_time(Default::default()).and_utc() + Duration::days(2)))
+        .filter(repo_exclusion::not_excluded(
+            commit_detail::Column::RepositoryName,
+            &excluded_repositories,
+        ));
+    if !show_private_contributions {
+        query = query.filter(commit_detail::Column::IsPrivateRepo.eq(false));
+    }
+    let commits: Vec<(DateTime<Utc>, Option<i32>, Option<i32>)> = query.into_tuple().all(db).await?;
+
+    Ok(code_frequency::weekly(
+        commits
+            .into_iter()
+            .map(|(time, additions, deletions)| (time.with_timezone(&timezone).date_naive(), additions, deletions)),
+        from,
+        to,
+    ))
+}
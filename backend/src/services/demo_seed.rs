@@ -0,0 +1,409 @@
+use anyhow::{bail, Result};
+use chrono::{Datelike, Duration, NaiveDate, Utc, Weekday};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sea_orm::*;
+use serde_json::json;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::handlers::auth::create_user;
+use crate::models::{
+    activity::{self, ActivityType},
+    contribution,
+    git_platform_account::{self, AuthType, GitPlatform},
+    heatmap_generation_setting,
+    heatmap_theme::{self, HeatmapColorScheme, HeatmapFormat, ThemeMode},
+    user,
+};
+use crate::services::activity_store;
+use crate::services::heatmap_generator::{HeatmapGenerator, HEATMAP_BASE_DIR};
+use crate::services::heatmap_renderers;
+use crate::utils::auth::hash_password;
+
+/// Rows per INSERT when writing contributions
+const INSERT_CHUNK: usize = 1000;
+
+/// Repositories the demo activity is spread over: (name, language)
+const DEMO_REPOSITORIES: &[(&str, &str)] = &[
+    ("heatmap-playground", "Rust"),
+    ("dotfiles", "Shell"),
+    ("weekend-web-app", "TypeScript"),
+    ("notebook-experiments", "Python"),
+];
+
+pub struct DemoOptions {
+    pub username: String,
+    pub password: String,
+    /// Years of history, ending today
+    pub years: u32,
+    /// Delete an existing user with the same name first
+    pub reset: bool,
+}
+
+pub struct DemoSummary {
+    pub user_id: Uuid,
+    pub is_admin: bool,
+    pub contributions: usize,
+    pub activities: usize,
+    pub themes: usize,
+    pub files_generated: usize,
+}
+
+/// Create a demo user with synthetic contributions, activities and themes.
+/// The data is derived from the username, so reseeding gives the same profile.
+pub async fn seed(db: &DatabaseConnection, options: &DemoOptions) -> Result<DemoSummary> {
+    if let Some(existing) = user::Entity::find()
+        .filter(user::Column::Username.eq(&options.username))
+        .one(db)
+        .await?
+    {
+        if !options.reset {
+            bail!(
+                "User '{}' already exists; pass --reset to replace it",
+                options.username
+            );
+        }
+        user::Entity::delete_by_id(existing.id).exec(db).await?;
+        let _ = tokio::fs::remove_dir_all(Path::new(HEATMAP_BASE_DIR).join(existing.id.to_string())).await;
+        log::info!("🗑️  Removed previous demo user '{}'", options.username);
+    }
+
+    let seed = options
+        .username
+        .bytes()
+        .fold(0u64, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u64));
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let demo_user = create_user(
+        db,
+        &options.username,
+        Some(hash_password(&options.password)?),
+        None,
+    )
+    .await?;
+
+    let now = Utc::now();
+    let account = git_platform_account::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(demo_user.id),
+        platform_type: Set(GitPlatform::GitHub),
+        platform_username: Set(options.username.clone()),
+        // No token and sync disabled, so the scheduler never contacts GitHub
+        access_token: Set(None),
+        refresh_token: Set(None),
+        platform_url: Set(Some("https://github.com".to_string())),
+        is_active: Set(true),
+        last_synced_at: Set(Some(now)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        avatar_url: Set(None),
+        display_name: Set(Some("Demo User".to_string())),
+        bio: Set(Some("Synthetic profile created by `seed-demo`".to_string())),
+        profile_url: Set(None),
+        location: Set(Some("Somewhere on the Internet".to_string())),
+        company: Set(None),
+        followers_count: Set(Some(42)),
+        following_count: Set(Some(7)),
+        sync_profile: Set(false),
+        sync_contributions: Set(false),
+        detailed_sync: Set(false),
+        commit_retention_days: Set(None),
+        earliest_sync_year: Set(Utc::now().year()),
+        auth_type: Set(AuthType::PersonalAccessToken),
+    }
+    .insert(db)
+    .await?;
+
+    let today = now.date_naive();
+    let start = today - Duration::days(365 * options.years.max(1) as i64);
+
+    let contributions = demo_contributions(&mut rng, account.id, start, today);
+    let contribution_count = contributions.len();
+    for chunk in contributions.chunks(INSERT_CHUNK) {
+        contribution::Entity::insert_many(chunk.to_vec()).exec(db).await?;
+    }
+
+    let mut activities = demo_activities(&mut rng, account.id, start, today);
+    activity_store::assign_keys(&mut activities);
+    let activity_count = activities.len();
+    for chunk in activities.chunks(INSERT_CHUNK) {
+        activity::Entity::insert_many(chunk.to_vec()).exec(db).await?;
+    }
+
+    // The database creates default themes and settings for new users
+    let has_settings = heatmap_generation_setting::Entity::find()
+        .filter(heatmap_generation_setting::Column::UserId.eq(demo_user.id))
+        .one(db)
+        .await?
+        .is_some();
+    if !has_settings {
+        heatmap_generation_setting::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(demo_user.id),
+            update_interval_minutes: Set(60),
+            auto_generation_enabled: Set(false),
+            date_range_days: Set(365),
+            include_private_contributions: Set(true),
+            storage_path: Set(None),
+            timezone: Set(None),
+            generation_window_start_minutes: Set(None),
+            generation_window_end_minutes: Set(None),
+            generation_cron: Set(None),
+            last_scheduled_generation_at: Set(None),
+            next_scheduled_generation_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+    }
+
+    let has_default_theme = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(demo_user.id))
+        .filter(heatmap_theme::Column::IsDefault.eq(true))
+        .one(db)
+        .await?
+        .is_some();
+    for theme in [
+        demo_theme(demo_user.id, "Ocean", "ocean", ThemeMode::Light, HeatmapColorScheme::Ocean, !has_default_theme),
+        demo_theme(demo_user.id, "Sunset", "sunset", ThemeMode::Dark, HeatmapColorScheme::Sunset, false),
+        demo_theme(demo_user.id, "Winter", "winter", ThemeMode::Light, HeatmapColorScheme::Winter, false),
+    ] {
+        theme.insert(db).await?;
+    }
+
+    let themes = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(demo_user.id))
+        .all(db)
+        .await?;
+    let generator = HeatmapGenerator::new(db.clone());
+    let mut files_generated = 0;
+    for theme in &themes {
+        match generator.generate_for_theme(demo_user.id, theme).await {
+            Ok(files) => files_generated += files.len(),
+            Err(e) => log::warn!("⚠️  Failed to generate demo theme '{}': {}", theme.slug, e),
+        }
+    }
+
+    Ok(DemoSummary {
+        user_id: demo_user.id,
+        is_admin: demo_user.is_admin,
+        contributions: contribution_count,
+        activities: activity_count,
+        themes: themes.len(),
+        files_generated,
+    })
+}
+
+/// One contribution row per active day: busier on weekdays, with a few
+/// quiet weeks and the odd burst
+fn demo_contributions(
+    rng: &mut StdRng,
+    account_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<contribution::ActiveModel> {
+    let now = Utc::now();
+    let mut rows = Vec::new();
+    let mut vacation_days = 0;
+
+    for date in start.iter_days().take_while(|d| *d <= end) {
+        if vacation_days > 0 {
+            vacation_days -= 1;
+            continue;
+        }
+        if rng.gen_ratio(1, 120) {
+            vacation_days = rng.gen_range(5..15);
+            continue;
+        }
+
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        let active = if weekend { rng.gen_bool(0.3) } else { rng.gen_bool(0.8) };
+        if !active {
+            continue;
+        }
+
+        let count = if rng.gen_ratio(1, 25) {
+            rng.gen_range(15..40)
+        } else {
+            rng.gen_range(1..10)
+        };
+
+        rows.push(contribution::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            git_platform_account_id: Set(account_id),
+            contribution_date: Set(date),
+            count: Set(count),
+            repository_name: Set(None),
+            is_private_repo: Set(false),
+            created_at: Set(now),
+            updated_at: Set(now),
+        });
+    }
+
+    rows
+}
+
+/// Repositories created at the start, monthly commit summaries, and some
+/// pull requests and issues
+fn demo_activities(
+    rng: &mut StdRng,
+    account_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<activity::ActiveModel> {
+    let now = Utc::now();
+    let row = |activity_type, date, metadata, repo: &str, language: &str, count| activity::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        git_platform_account_id: Set(account_id),
+        activity_type: Set(activity_type),
+        activity_date: Set(date),
+        metadata: Set(metadata),
+        repository_name: Set(Some(repo.to_string())),
+        repository_url: Set(Some(format!("https://github.com/{}", repo))),
+        is_private_repo: Set(false),
+        count: Set(count),
+        primary_language: Set(Some(language.to_string())),
+        organization_name: Set(None),
+        organization_avatar_url: Set(None),
+        natural_key: NotSet,
+        imported: Set(false),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    let mut rows = Vec::new();
+
+    for (i, (repo, language)) in DEMO_REPOSITORIES.iter().enumerate() {
+        let created = start + Duration::days(30 * i as i64);
+        rows.push(row(
+            ActivityType::RepositoryCreated,
+            created,
+            json!({
+                "name": repo,
+                "description": format!("Demo repository written in {}", language),
+                "created_at": created.to_string(),
+            }),
+            repo,
+            language,
+            1,
+        ));
+    }
+
+    let mut month = NaiveDate::from_ymd_opt(start.year(), start.month(), 1).unwrap_or(start);
+    let mut number = 1;
+    while month <= end {
+        for (repo, language) in DEMO_REPOSITORIES {
+            if !rng.gen_bool(0.7) {
+                continue;
+            }
+            let date = (month + Duration::days(rng.gen_range(0..28))).min(end);
+            let commits = rng.gen_range(3..60);
+            rows.push(row(
+                ActivityType::Commit,
+                date,
+                json!({
+                    "repositories": [{ "name": repo, "commit_count": commits }],
+                    "total_count": commits,
+                }),
+                repo,
+                language,
+                commits,
+            ));
+
+            let (activity_type, title) = match rng.gen_range(0..4) {
+                0 => (ActivityType::PullRequest, "Improve error messages"),
+                1 => (ActivityType::PullRequest, "Add dark mode support"),
+                2 => (ActivityType::Issue, "Crash when the config file is empty"),
+                _ => continue,
+            };
+            let kind = if activity_type == ActivityType::PullRequest { "pull" } else { "issues" };
+            rows.push(row(
+                activity_type,
+                date,
+                json!({
+                    "title": title,
+                    "number": number,
+                    "state": if rng.gen_bool(0.8) { "MERGED" } else { "OPEN" },
+                    "repository": repo,
+                    "url": format!("https://github.com/{}/{}/{}", repo, kind, number),
+                    "comment_count": rng.gen_range(0..6),
+                }),
+                repo,
+                language,
+                1,
+            ));
+            number += 1;
+        }
+
+        month = month
+            .checked_add_months(chrono::Months::new(1))
+            .unwrap_or(end + Duration::days(1));
+    }
+
+    rows
+}
+
+fn demo_theme(
+    user_id: Uuid,
+    name: &str,
+    slug: &str,
+    theme_mode: ThemeMode,
+    color_scheme: HeatmapColorScheme,
+    is_default: bool,
+) -> heatmap_theme::ActiveModel {
+    let (background, text, empty) = match theme_mode {
+        ThemeMode::Light => ("#ffffff", "#24292e", "#ebedf0"),
+        ThemeMode::Dark => ("#0d1117", "#c9d1d9", "#161b22"),
+    };
+    let now = Utc::now();
+
+    heatmap_theme::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        name: Set(name.to_string()),
+        slug: Set(slug.to_string()),
+        description: Set(Some("Created by seed-demo".to_string())),
+        is_default: Set(is_default),
+        theme_mode: Set(theme_mode),
+        color_scheme: Set(color_scheme),
+        custom_colors: Set(None),
+        background_color: Set(background.to_string()),
+        border_color: Set("#d1d5da".to_string()),
+        text_color: Set(text.to_string()),
+        empty_cell_color: Set(empty.to_string()),
+        cell_size: Set(10),
+        cell_gap: Set(2),
+        cell_border_radius: Set(2),
+        cell_border_width: Set(0),
+        cell_border_color: Set("#d1d5da".to_string()),
+        heatmap_width: Set(None),
+        heatmap_height: Set(None),
+        padding_top: Set(20),
+        padding_right: Set(20),
+        padding_bottom: Set(17),
+        padding_left: Set(20),
+        day_label_width: Set(28),
+        month_label_height: Set(15),
+        title_height: Set(30),
+        legend_height: Set(8),
+        show_month_labels: Set(true),
+        show_day_labels: Set(true),
+        show_legend: Set(true),
+        show_total_count: Set(true),
+        show_username: Set(true),
+        show_watermark: Set(true),
+        font_family: Set("Nimbus Sans".to_string()),
+        font_size: Set(10),
+        legend_position: Set("bottom".to_string()),
+        layout: Set(heatmap_renderers::DEFAULT_LAYOUT.to_string()),
+        custom_template: Set(None),
+        mini_days: Set(None),
+        show_achievements: Set(false),
+        show_code_frequency: Set(false),
+        output_formats: Set(vec![HeatmapFormat::Svg, HeatmapFormat::Png].into()),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Months, NaiveDate, Utc};
+use sea_orm::*;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration as TokioDuration};
+use uuid::Uuid;
+
+use crate::handlers::activities::activity_title;
+use crate::models::heatmap_theme::HeatmapFormat;
+use crate::models::{activity, email_digest, git_platform_account, heatmap_generation_setting, heatmap_theme, user};
+use crate::services::heatmap_generator::{HeatmapData, HeatmapGenerator};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::mailer::Mailer;
+use crate::services::render_lanes::Lane;
+use crate::services::shutdown::Shutdown;
+use crate::services::text_heatmap::{plural, streaks};
+use crate::utils::config::Config;
+
+pub const WEEKLY: &str = "weekly";
+pub const MONTHLY: &str = "monthly";
+
+/// How often the job looks for digests that are due
+const CHECK_INTERVAL_MINUTES: u64 = 60;
+
+/// Most non-commit activities listed in one digest
+const MAX_DIGEST_ACTIVITIES: u64 = 10;
+
+/// When the next digest of a subscription goes out
+pub fn next_digest_at(digest: &email_digest::Model) -> DateTime<Utc> {
+    match digest.frequency.as_str() {
+        MONTHLY => digest
+            .last_sent_at
+            .checked_add_months(Months::new(1))
+            .unwrap_or(digest.last_sent_at + Duration::days(30)),
+        _ => digest.last_sent_at + Duration::days(7),
+    }
+}
+
+/// Start the background job that emails digests as they fall due. Does
+/// nothing when email is not configured.
+pub fn start_digest_job(
+    db: DatabaseConnection,
+    config: Config,
+    instance_settings: Arc<InstanceSettingsService>,
+    mut shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mailer = Mailer::from_config(&config);
+        if !mailer.is_enabled() {
+            log::info!("Email is not configured, not sending digests");
+            return;
+        }
+
+        log::info!("Starting email digest job (every {} minutes)", CHECK_INTERVAL_MINUTES);
+
+        loop {
+            match send_due(&db, &mailer, &instance_settings).await {
+                Ok(0) => {}
+                Ok(sent) => log::info!("📬 [Digest] Sent {}", plural(sent, "digest")),
+                Err(e) => log::error!("Error in email digest job: {}", e),
+            }
+
+            tokio::select! {
+                _ = sleep(TokioDuration::from_secs(CHECK_INTERVAL_MINUTES * 60)) => {}
+                _ = shutdown.requested() => break,
+            }
+        }
+    })
+}
+
+/// Send every digest that is due. A digest that fails is retried on the
+/// next run.
+async fn send_due(
+    db: &DatabaseConnection,
+    mailer: &Mailer,
+    instance_settings: &InstanceSettingsService,
+) -> Result<i32> {
+    let now = Utc::now();
+
+    // Weekly is the shortest period, so nothing sent since is due
+    let candidates = email_digest::Entity::find()
+        .filter(email_digest::Column::LastSentAt.lte(now - Duration::days(7)))
+        .find_also_related(user::Entity)
+        .all(db)
+        .await?;
+
+    let frontend_url = instance_settings.current().await.frontend_url;
+    let mut sent = 0;
+
+    for (digest, user) in candidates {
+        if next_digest_at(&digest) > now {
+            continue;
+        }
+        let Some(user) = user else { continue };
+        let Some(email) = user.email.clone() else {
+            log::debug!("Skipping digest for {}, who has no email address", user.username);
+            continue;
+        };
+
+        if let Err(e) = send_digest(db, mailer, &user, &email, &digest, &frontend_url).await {
+            log::warn!("⚠️  [Digest] Failed to send digest to {}: {:#}", user.username, e);
+            continue;
+        }
+
+        email_digest::Entity::update_many()
+            .col_expr(email_digest::Column::LastSentAt, sea_query::Expr::value(now))
+            .filter(email_digest::Column::UserId.eq(digest.user_id))
+            .exec(db)
+            .await?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
+
+async fn send_digest(
+    db: &DatabaseConnection,
+    mailer: &Mailer,
+    user: &user::Model,
+    email: &str,
+    digest: &email_digest::Model,
+    frontend_url: &str,
+) -> Result<()> {
+    // Whole days from the last digest up to yesterday; today is not over
+    let from = digest.last_sent_at.date_naive();
+    let to = Utc::now().date_naive() - Duration::days(1);
+
+    let generator = HeatmapGenerator::new(db.clone());
+    let settings = generation_settings(db, user.id).await?;
+    let data = generator.fetch_contribution_data(user.id, &settings).await?;
+    let activities = notable_activities(db, user.id, from, to).await?;
+
+    let theme = default_theme(db, user.id).await?;
+
+    let subject = format!("Your {} contribution digest", digest.frequency);
+    let mut body = digest_body(user, &data, &activities, &digest.frequency, from, to);
+    if theme.is_some() {
+        body.push_str("\nYour current heatmap is attached.\n");
+    }
+    body.push_str(&format!(
+        "\nYou get this email because you subscribed to a {} digest. To change or stop it, visit {}/settings\n",
+        digest.frequency,
+        frontend_url.trim_end_matches('/')
+    ));
+
+    // Without a theme there is nothing to draw the heatmap with
+    let Some(theme) = theme else {
+        return mailer.send(email, &subject, body).await;
+    };
+
+    let image = generator
+        .render(Lane::Bulk, &theme, &data, &HeatmapFormat::Png, Some(&user.username))
+        .await
+        .context("Failed to render heatmap")?;
+    mailer
+        .send_with_attachment(email, &subject, body, "heatmap.png", "image/png", image)
+        .await
+}
+
+fn digest_body(
+    user: &user::Model,
+    data: &HeatmapData,
+    activities: &[activity::Model],
+    frequency: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> String {
+    let in_period: Vec<_> = data
+        .weeks
+        .iter()
+        .flatten()
+        .filter(|day| day.count >= 0 && day.date >= from && day.date <= to)
+        .collect();
+    let total: i32 = in_period.iter().map(|day| day.count).sum();
+    let active_days = in_period.iter().filter(|day| day.count > 0).count() as i32;
+    let busiest = in_period.iter().filter(|day| day.count > 0).max_by_key(|day| day.count);
+    let (current, longest) = streaks(data);
+
+    let mut body = format!(
+        "Hi {},\n\nHere is your {} summary for {} to {}.\n\n",
+        user.username,
+        frequency,
+        from.format("%b %-d"),
+        to.format("%b %-d, %Y")
+    );
+
+    body.push_str(&format!(
+        "Contributions: {} on {}\n",
+        total,
+        plural(active_days, "day")
+    ));
+    if let Some(day) = busiest {
+        body.push_str(&format!(
+            "Busiest day: {} with {}\n",
+            day.date.format("%A, %b %-d"),
+            plural(day.count, "contribution")
+        ));
+    }
+
+    if current > 0 {
+        body.push_str(&format!(
+            "Current streak: {} (longest: {})\n",
+            plural(current, "day"),
+            plural(longest, "day")
+        ));
+    } else {
+        body.push_str(&format!(
+            "No streak running; your longest is {}\n",
+            plural(longest, "day")
+        ));
+    }
+
+    if !activities.is_empty() {
+        body.push_str("\nNotable activity:\n");
+        for a in activities {
+            body.push_str(&format!("- {}\n", activity_title(a, false)));
+        }
+    }
+
+    body
+}
+
+/// Activities other than commits in the period, newest first; commits are
+/// already counted in the totals
+async fn notable_activities(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<activity::Model>, DbErr> {
+    let account_ids: Vec<Uuid> = git_platform_account::Entity::find()
+        .select_only()
+        .column(git_platform_account::Column::Id)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.is_in(account_ids))
+        .filter(activity::Column::ActivityType.ne(activity::ActivityType::Commit))
+        .filter(activity::Column::ActivityDate.between(from, to))
+        .order_by_desc(activity::Column::ActivityDate)
+        .limit(MAX_DIGEST_ACTIVITIES)
+        .all(db)
+        .await
+}
+
+/// The theme the user marked as default, or else their oldest one
+pub async fn default_theme(db: &DatabaseConnection, user_id: Uuid) -> Result<Option<heatmap_theme::Model>, DbErr> {
+    heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user_id))
+        .order_by_desc(heatmap_theme::Column::IsDefault)
+        .order_by_asc(heatmap_theme::Column::CreatedAt)
+        .one(db)
+        .await
+}
+
+/// The user's generation settings, or the defaults if they never saved any
+pub async fn generation_settings(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> Result<heatmap_generation_setting::Model, DbErr> {
+    let settings = heatmap_generation_setting::Entity::find()
+        .filter(heatmap_generation_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+
+    Ok(settings.unwrap_or_else(|| heatmap_generation_setting::Model {
+        id: Uuid::new_v4(),
+        user_id,
+        update_interval_minutes: 60,
+        auto_generation_enabled: true,
+        date_range_days: 365,
+        include_private_contributions: true,
+        storage_path: None,
+        timezone: None,
+        generation_window_start_minutes: None,
+        generation_window_end_minutes: None,
+        generation_cron: None,
+        last_scheduled_generation_at: None,
+        next_scheduled_generation_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }))
+}
@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::models::{heatmap_generation_job::GenerationJobStatus, platform_sync_job::SyncJobStatus};
+
+/// Events kept for slow subscribers before they start missing some
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Something the dashboard should react to without polling
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DashboardEvent {
+    SyncProgress {
+        job_id: Uuid,
+        platform_account_id: Uuid,
+        contributions_synced: i32,
+        activities_synced: i32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        years_completed: Option<i32>,
+    },
+    /// A sync job finished an attempt; `pending` means it will be retried
+    SyncFinished {
+        job_id: Uuid,
+        platform_account_id: Uuid,
+        status: SyncJobStatus,
+        contributions_synced: i32,
+        activities_synced: i32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A heatmap generation job finished an attempt
+    GenerationFinished {
+        job_id: Uuid,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        theme_id: Option<Uuid>,
+        status: GenerationJobStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    NewActivities {
+        platform_account_id: Uuid,
+        count: i32,
+    },
+    /// An activity import stored more activities, or ended
+    ImportProgress {
+        import_id: Uuid,
+        platform_account_id: Uuid,
+        status: SyncJobStatus,
+        activities_total: i32,
+        activities_imported: i32,
+        duplicates_skipped: i32,
+    },
+    /// A milestone was reached, like a 100-day streak
+    MilestoneReached {
+        milestone_id: Uuid,
+        kind: String,
+        title: String,
+        achieved_on: NaiveDate,
+    },
+}
+
+impl DashboardEvent {
+    /// SSE event name, same as the `type` field of the payload
+    pub fn name(&self) -> &'static str {
+        match self {
+            DashboardEvent::SyncProgress { .. } => "sync_progress",
+            DashboardEvent::SyncFinished { .. } => "sync_finished",
+            DashboardEvent::GenerationFinished { .. } => "generation_finished",
+            DashboardEvent::NewActivities { .. } => "new_activities",
+            DashboardEvent::ImportProgress { .. } => "import_progress",
+            DashboardEvent::MilestoneReached { .. } => "milestone_reached",
+        }
+    }
+
+    /// Whether the user's public data changed: a sync, import or heatmap
+    /// generation completed
+    pub fn updates_profile(&self) -> bool {
+        matches!(
+            self,
+            DashboardEvent::SyncFinished { status: SyncJobStatus::Completed, .. }
+                | DashboardEvent::ImportProgress { status: SyncJobStatus::Completed, .. }
+                | DashboardEvent::GenerationFinished {
+                    status: GenerationJobStatus::Completed,
+                    ..
+                }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UserEvent {
+    pub user_id: Uuid,
+    pub event: DashboardEvent,
+}
+
+/// In-process fan-out of dashboard events from the background jobs to
+/// connected `/events` streams. Events are dropped when nobody listens.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<UserEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, user_id: Uuid, event: DashboardEvent) {
+        // Err only means there are no subscribers right now
+        let _ = self.sender.send(UserEvent { user_id, event });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UserEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,13 +1,19 @@
-use super::{Activity, ActivityType, Contribution, ContributionType, GitPlatform, PlatformConfig, Repository, UserInfo};
-use crate::utils::http_client::create_http_client;
+use super::{
+    Activity, ActivityType, Capabilities, CommitDetail, Contribution, ContributionType, GitPlatform,
+    PlatformConfig, Repository, UserInfo, MAX_COMMIT_PAGES,
+};
+use crate::utils::co_authors;
+use crate::utils::http_client::{shared_client, SendTraced};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-pub struct GiteaClient;
+pub struct GiteaClient {
+    http: reqwest::Client,
+}
 
 // Gitea heatmap API response format
 #[derive(Debug, Deserialize)]
@@ -17,8 +23,11 @@ struct GiteaHeatmapEntry {
 }
 
 impl GiteaClient {
+    /// A client on the shared HTTP connection pool
     pub fn new() -> Self {
-        Self
+        Self {
+            http: shared_client(),
+        }
     }
 
     /// Revoke a Gitea OAuth token
@@ -27,7 +36,7 @@ impl GiteaClient {
         instance_url: &str,
         access_token: &str,
     ) -> Result<()> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔒 Revoking Gitea OAuth token for instance: {}", instance_url);
 
@@ -37,7 +46,7 @@ impl GiteaClient {
         let response = client
             .delete(&revoke_url)
             .header("Authorization", format!("token {}", access_token))
-            .send()
+            .send_traced()
             .await?;
 
         if response.status().is_success() || response.status().as_u16() == 404 {
@@ -59,15 +68,15 @@ impl GiteaClient {
         config: &PlatformConfig,
         token: &str,
     ) -> Result<serde_json::Value> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("👤 Fetching Gitea profile");
 
         let response = client
-            .get(&format!("{}/user", config.api_base_url))
+            .get(format!("{}/user", config.api_base_url))
             .header("Authorization", format!("token {}", token))
             .header("accept", "application/json")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -93,7 +102,7 @@ impl GitPlatform for GiteaClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Contribution>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔍 Fetching Gitea contributions for {} using heatmap API", username);
 
@@ -108,7 +117,7 @@ impl GitPlatform for GiteaClient {
             .get(&heatmap_url)
             .header("Authorization", format!("token {}", token))
             .header("accept", "application/json")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -121,7 +130,7 @@ impl GitPlatform for GiteaClient {
         let heatmap_entries: Vec<GiteaHeatmapEntry> = response.json().await?;
         log::info!("📥 Fetched {} heatmap entries from Gitea", heatmap_entries.len());
 
-        if heatmap_entries.len() > 0 {
+        if !heatmap_entries.is_empty() {
             log::info!("First entry: timestamp={}, contributions={}",
                 heatmap_entries[0].timestamp, heatmap_entries[0].contributions);
             log::info!("Last entry: timestamp={}, contributions={}",
@@ -178,11 +187,11 @@ impl GitPlatform for GiteaClient {
         // This endpoint returns activities with authentication, including private repo activities
         loop {
             let response = client
-                .get(&format!("{}/users/{}/activities/feeds", config.api_base_url, username))
+                .get(format!("{}/users/{}/activities/feeds", config.api_base_url, username))
                 .header("Authorization", format!("token {}", token))
                 .header("accept", "application/json")
                 .query(&[("page", &page.to_string()), ("limit", &per_page.to_string())])
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -218,9 +227,7 @@ impl GitPlatform for GiteaClient {
 
                             // Store the most recent repo for each date
                             // (we can't track all repos perfectly from activities, but this gives us something)
-                            if !repo_by_date.contains_key(&naive_date) {
-                                repo_by_date.insert(naive_date, (repo_full_name, repo.private));
-                            }
+                            repo_by_date.entry(naive_date).or_insert((repo_full_name, repo.private));
                         }
                     }
                 }
@@ -267,7 +274,7 @@ impl GitPlatform for GiteaClient {
         }
 
         // Sort by date
-        contributions.sort_by(|a, b| a.date.cmp(&b.date));
+        contributions.sort_by_key(|a| a.date);
 
         let total: i32 = contributions.iter().map(|c| c.count).sum();
         log::info!("📊 Collected {} contributions across {} entries (total count: {})",
@@ -277,16 +284,16 @@ impl GitPlatform for GiteaClient {
     }
 
     async fn validate_token(&self, config: &PlatformConfig, token: &str) -> Result<UserInfo> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔑 Validating Gitea token");
 
         // Use Gitea's user endpoint to validate token and get user info
         let response = client
-            .get(&format!("{}/user", config.api_base_url))
+            .get(format!("{}/user", config.api_base_url))
             .header("Authorization", format!("token {}", token))
             .header("accept", "application/json")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -311,7 +318,7 @@ impl GitPlatform for GiteaClient {
         config: &PlatformConfig,
         token: &str,
     ) -> Result<Vec<Repository>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("📦 Fetching Gitea repositories");
 
@@ -322,11 +329,11 @@ impl GitPlatform for GiteaClient {
         // Fetch user repositories with pagination
         loop {
             let response = client
-                .get(&format!("{}/user/repos", config.api_base_url))
+                .get(format!("{}/user/repos", config.api_base_url))
                 .header("Authorization", format!("token {}", token))
                 .header("accept", "application/json")
                 .query(&[("page", &page.to_string()), ("limit", &per_page.to_string())])
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -372,7 +379,7 @@ impl GitPlatform for GiteaClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔍 Fetching Gitea activities for {} from {} to {}", username, from, to);
 
@@ -385,11 +392,11 @@ impl GitPlatform for GiteaClient {
         // The only available endpoint is /users/{username}/activities/feeds
         loop {
             let response = client
-                .get(&format!("{}/users/{}/activities/feeds", config.api_base_url, username))
+                .get(format!("{}/users/{}/activities/feeds", config.api_base_url, username))
                 .header("Authorization", format!("token {}", token))
                 .header("accept", "application/json")
                 .query(&[("page", &page.to_string()), ("limit", &per_page.to_string())])
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -438,7 +445,7 @@ impl GitPlatform for GiteaClient {
                     // Group commits by date and repository
                     if let Some(ref repo) = activity.repo {
                         let key = format!("{}_{}", naive_date, repo.full_name.as_ref().unwrap_or(&repo.name));
-                        commit_groups.entry(key).or_insert_with(Vec::new).push(activity);
+                        commit_groups.entry(key).or_default().push(activity);
                     }
                 }
                 "create_repo" => {
@@ -546,7 +553,7 @@ impl GitPlatform for GiteaClient {
         }
 
         // Sort by date descending
-        activities.sort_by(|a, b| b.date.cmp(&a.date));
+        activities.sort_by_key(|a| std::cmp::Reverse(a.date));
 
         log::info!("📊 Processed {} activities", activities.len());
 
@@ -561,7 +568,7 @@ impl GitPlatform for GiteaClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("📦 Fetching repository creation activities for {}", username);
 
@@ -585,10 +592,10 @@ impl GitPlatform for GiteaClient {
 
             // Fetch detailed repo info
             let response = client
-                .get(&format!("{}/repos/{}/{}", config.api_base_url, owner, repo_name))
+                .get(format!("{}/repos/{}/{}", config.api_base_url, owner, repo_name))
                 .header("Authorization", format!("token {}", token))
                 .header("accept", "application/json")
-                .send()
+                .send_traced()
                 .await;
 
             if let Ok(resp) = response {
@@ -630,10 +637,165 @@ impl GitPlatform for GiteaClient {
 
         Ok(activities)
     }
+
+    async fn fetch_repository_languages(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        repository: &str,
+    ) -> Result<BTreeMap<String, i64>> {
+        let response = self
+            .http
+            .get(format!("{}/repos/{}/languages", config.api_base_url, repository))
+            .header("Authorization", format!("token {}", token))
+            .header("accept", "application/json")
+            .send_traced()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to fetch languages of {}: status {}", repository, response.status()));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn fetch_commit_details(
+        &self,
+        config: &PlatformConfig,
+        username: &str,
+        token: &str,
+        repository: &str,
+        user_emails: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CommitDetail>> {
+        let per_page = 50;
+        let since = from.to_rfc3339();
+        let until = to.to_rfc3339();
+        let mut commits = Vec::new();
+
+        // Gitea cannot filter commits by author, so they are matched by the
+        // linked account or the user's emails here
+        for page in 1..=MAX_COMMIT_PAGES {
+            let response = self
+                .http
+                .get(format!("{}/repos/{}/commits", config.api_base_url, repository))
+                .header("Authorization", format!("token {}", token))
+                .header("accept", "application/json")
+                .query(&[
+                    ("since", since.as_str()),
+                    ("until", until.as_str()),
+                    ("stat", "true"),
+                    ("files", "false"),
+                    ("verification", "false"),
+                    ("page", &page.to_string()),
+                    ("limit", &per_page.to_string()),
+                ])
+                .send_traced()
+                .await?;
+
+            // An empty repository answers 409
+            if response.status() == reqwest::StatusCode::CONFLICT {
+                break;
+            }
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to fetch commits of {}: status {}", repository, response.status()));
+            }
+
+            let page_commits: Vec<GiteaCommit> = response.json().await?;
+            let fetched = page_commits.len();
+
+            for commit in page_commits {
+                let by_user = commit
+                    .author
+                    .as_ref()
+                    .is_some_and(|author| author.login.eq_ignore_ascii_case(username))
+                    || user_emails.contains(&commit.commit.author.email.to_lowercase());
+                let co_authored = !by_user
+                    && co_authors::emails(&commit.commit.message)
+                        .iter()
+                        .any(|email| user_emails.contains(email));
+                if !by_user && !co_authored {
+                    continue;
+                }
+                let Ok(committed_at) = DateTime::parse_from_rfc3339(&commit.commit.author.date) else {
+                    continue;
+                };
+                commits.push(CommitDetail {
+                    sha: commit.sha,
+                    committed_at: committed_at.with_timezone(&Utc),
+                    additions: commit.stats.as_ref().map(|s| s.additions),
+                    deletions: commit.stats.as_ref().map(|s| s.deletions),
+                    co_authored,
+                });
+            }
+
+            if fetched < per_page {
+                break;
+            }
+        }
+
+        Ok(commits)
+    }
+
+    async fn capabilities(&self, config: &PlatformConfig, username: &str, token: &str) -> Capabilities {
+        // Admins can turn the heatmap off, which leaves no daily counts
+        let heatmap = self
+            .http
+            .get(format!("{}/users/{}/heatmap", config.api_base_url, username))
+            .header("Authorization", format!("token {}", token))
+            .header("accept", "application/json")
+            .send_traced()
+            .await
+            .is_ok_and(|response| response.status().is_success());
+
+        Capabilities {
+            contributions: heatmap,
+            contribution_calendar: heatmap,
+            graphql: false,
+            organizations: false,
+            pull_requests_and_issues: false,
+            token_revocation: true,
+        }
+    }
 }
 
 // Gitea API response types
 
+#[derive(Debug, Deserialize)]
+struct GiteaCommit {
+    sha: String,
+    commit: GiteaCommitInfo,
+    /// The linked account of the author, None when no account has the email
+    author: Option<GiteaCommitUser>,
+    stats: Option<GiteaCommitStats>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommitInfo {
+    author: GiteaCommitSignature,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommitSignature {
+    date: String,
+    #[serde(default)]
+    email: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommitUser {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommitStats {
+    additions: i32,
+    deletions: i32,
+}
+
 #[derive(Debug, Deserialize)]
 struct GiteaUser {
     login: String,
@@ -1,20 +1,150 @@
 use super::{
-    Activity, ActivityType, Contribution, ContributionType, GitPlatform, PlatformConfig,
-    Repository, UserInfo,
+    Activity, ActivityType, Capabilities, CommitDetail, Contribution, ContributionType, GitPlatform,
+    PlatformConfig, Repository, UserInfo, MAX_COMMIT_PAGES, RESTRICTED_REPOSITORY,
 };
-use crate::utils::http_client::create_http_client;
+use crate::utils::co_authors;
+use crate::utils::http_client::{shared_client, SendTraced};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-pub struct GitHubClient;
+pub struct GitHubClient {
+    http: reqwest::Client,
+}
 
 impl GitHubClient {
+    /// A client on the shared HTTP connection pool
     pub fn new() -> Self {
-        Self
+        Self {
+            http: shared_client(),
+        }
+    }
+
+    /// Run a GraphQL query, failing on HTTP errors and on GraphQL errors
+    async fn graphql(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self
+            .http
+            .post(format!("{}/graphql", config.api_base_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("User-Agent", "hgitmap/0.1.0")
+            .json(&json!({
+                "query": query,
+                "variables": variables,
+            }))
+            .send_traced()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("GitHub GraphQL request failed: status {}", response.status()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        if let Some(message) = data
+            .pointer("/errors/0/message")
+            .and_then(|v| v.as_str())
+        {
+            return Err(anyhow!("GitHub GraphQL error: {}", message));
+        }
+        Ok(data)
+    }
+
+    /// Commits on the default branch of `owner/name` authored within a date
+    /// range, filtered by `author` (a GraphQL `CommitAuthor`, or null for
+    /// everyone), at most MAX_COMMIT_PAGES pages
+    #[allow(clippy::too_many_arguments)]
+    async fn commit_history(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        owner: &str,
+        name: &str,
+        author: serde_json::Value,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let history_query = r#"
+            query($owner: String!, $name: String!, $author: CommitAuthor, $since: GitTimestamp!, $until: GitTimestamp!, $cursor: String) {
+                repository(owner: $owner, name: $name) {
+                    defaultBranchRef {
+                        target {
+                            ... on Commit {
+                                history(first: 100, after: $cursor, author: $author, since: $since, until: $until) {
+                                    pageInfo {
+                                        hasNextPage
+                                        endCursor
+                                    }
+                                    nodes {
+                                        oid
+                                        authoredDate
+                                        additions
+                                        deletions
+                                        message
+                                        author {
+                                            email
+                                            user {
+                                                login
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let mut nodes = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_COMMIT_PAGES {
+            let mut data = self
+                .graphql(
+                    config,
+                    token,
+                    history_query,
+                    json!({
+                        "owner": owner,
+                        "name": name,
+                        "author": author,
+                        "since": from.to_rfc3339(),
+                        "until": to.to_rfc3339(),
+                        "cursor": cursor,
+                    }),
+                )
+                .await?;
+
+            // An empty repository has no default branch
+            let Some(history) = data.pointer_mut("/data/repository/defaultBranchRef/target/history") else {
+                break;
+            };
+            if let Some(page) = history.get_mut("nodes").and_then(|n| n.as_array_mut()) {
+                nodes.append(page);
+            }
+
+            let page_info = history.get("pageInfo");
+            let has_next = page_info
+                .and_then(|p| p.get("hasNextPage"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            cursor = page_info
+                .and_then(|p| p.get("endCursor"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            if !has_next || cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(nodes)
     }
 
     /// Revoke a GitHub OAuth token
@@ -24,12 +154,12 @@ impl GitHubClient {
         client_secret: &str,
         access_token: &str,
     ) -> Result<()> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔒 Revoking GitHub OAuth token");
 
         let response = client
-            .delete(&format!(
+            .delete(format!(
                 "https://api.github.com/applications/{}/token",
                 client_id
             ))
@@ -40,7 +170,7 @@ impl GitHubClient {
             .json(&json!({
                 "access_token": access_token
             }))
-            .send()
+            .send_traced()
             .await?;
 
         let status = response.status();
@@ -72,15 +202,15 @@ impl GitHubClient {
         username: &str,
         token: &str,
     ) -> Result<serde_json::Value> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("👤 Fetching GitHub profile for {}", username);
 
         let response = client
-            .get(&format!("{}/user", config.api_base_url))
+            .get(format!("{}/user", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -102,13 +232,13 @@ impl GitHubClient {
         token: &str,
         repo_name: &str,
     ) -> Result<serde_json::Value> {
-        let client = create_http_client();
+        let client = &self.http;
 
         let response = client
-            .get(&format!("{}/repos/{}", config.api_base_url, repo_name))
+            .get(format!("{}/repos/{}", config.api_base_url, repo_name))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -128,7 +258,7 @@ impl GitHubClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         // GraphQL query to get all repositories with creation dates
         let query = r#"
@@ -155,14 +285,14 @@ impl GitHubClient {
         });
 
         let response = client
-            .post(&format!("{}/graphql", config.api_base_url))
+            .post(format!("{}/graphql", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
             .json(&json!({
                 "query": query,
                 "variables": variables,
             }))
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -266,15 +396,15 @@ impl GitHubClient {
         username: &str,
         _token: &str, // Token not needed for public orgs, but kept for API compatibility
     ) -> Result<Vec<(String, String)>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         // Use the public /users/{username}/orgs endpoint
         // This endpoint doesn't require authentication and shows public memberships only
         let response = client
-            .get(&format!("{}/users/{}/orgs", config.api_base_url, username))
+            .get(format!("{}/users/{}/orgs", config.api_base_url, username))
             .header("User-Agent", "hgitmap/0.1.0")
             .header("Accept", "application/vnd.github+json")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -314,7 +444,7 @@ impl GitHubClient {
         username: &str,
         org_login: &str,
     ) -> Result<Option<chrono::NaiveDate>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         // Fetch user events (up to 300 events across 3 pages)
         let mut earliest_date: Option<chrono::NaiveDate> = None;
@@ -329,7 +459,7 @@ impl GitHubClient {
 
         while page <= 3 {
             let response = client
-                .get(&format!(
+                .get(format!(
                     "{}/users/{}/events/public",
                     config.api_base_url, username
                 ))
@@ -339,7 +469,7 @@ impl GitHubClient {
                     ("per_page", &per_page.to_string()),
                     ("page", &page.to_string()),
                 ])
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -421,7 +551,7 @@ impl GitHubClient {
         username: &str,
         org_login: &str,
     ) -> Result<Option<chrono::NaiveDate>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!(
             "🕷️  Scraping org join date for {} in {}",
@@ -450,7 +580,7 @@ impl GitHubClient {
             .header("Sec-Fetch-Site", "same-origin")
             .header("X-Requested-With", "XMLHttpRequest")
             .header("Referer", &format!("https://github.com/{}", username))
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -625,7 +755,7 @@ impl GitHubClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>> {
-        let client = create_http_client();
+        let client = &self.http;
         let mut all_activities = Vec::new();
 
         // Format dates for GitHub search query (YYYY-MM-DD)
@@ -663,7 +793,7 @@ impl GitHubClient {
         );
 
         let pr_response = client
-            .post(&format!("{}/graphql", config.api_base_url))
+            .post(format!("{}/graphql", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
             .json(&json!({
@@ -672,7 +802,7 @@ impl GitHubClient {
                     "searchQuery": pr_search_query,
                 }),
             }))
-            .send()
+            .send_traced()
             .await?;
 
         if pr_response.status().is_success() {
@@ -731,6 +861,7 @@ impl GitHubClient {
                             "repository": repo_name,
                             "url": url,
                             "comment_count": comment_count,
+                            "created_at": created_at,
                         });
 
                         // Only include body if it exists and is not empty
@@ -791,7 +922,7 @@ impl GitHubClient {
         );
 
         let issue_response = client
-            .post(&format!("{}/graphql", config.api_base_url))
+            .post(format!("{}/graphql", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
             .json(&json!({
@@ -800,7 +931,7 @@ impl GitHubClient {
                     "searchQuery": issue_search_query,
                 }),
             }))
-            .send()
+            .send_traced()
             .await?;
 
         if issue_response.status().is_success() {
@@ -859,6 +990,7 @@ impl GitHubClient {
                             "repository": repo_name,
                             "url": url,
                             "comment_count": comment_count,
+                            "created_at": created_at,
                         });
 
                         // Only include body if it exists and is not empty
@@ -898,6 +1030,114 @@ impl GitHubClient {
 
     /// Search for commits on specific dates using GitHub REST API
     /// This is used as a fallback when commitContributionsByRepository doesn't return data
+    /// Per day, the issues, pull requests, reviews and repositories the token
+    /// can see, which the calendar counts along with commits
+    async fn fetch_visible_contribution_days(
+        &self,
+        config: &PlatformConfig,
+        username: &str,
+        token: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<HashMap<chrono::NaiveDate, i32>> {
+        const KINDS: [&str; 4] = [
+            "issueContributions",
+            "pullRequestContributions",
+            "pullRequestReviewContributions",
+            "repositoryContributions",
+        ];
+        // 1000 of each kind, far more than anyone makes in a year
+        const MAX_PAGES: usize = 10;
+
+        let client = &self.http;
+        let mut days: HashMap<chrono::NaiveDate, i32> = HashMap::new();
+
+        for kind in KINDS {
+            let query = format!(
+                r#"
+                query($username: String!, $from: DateTime!, $to: DateTime!, $after: String) {{
+                    user(login: $username) {{
+                        contributionsCollection(from: $from, to: $to) {{
+                            {}(first: 100, after: $after) {{
+                                pageInfo {{
+                                    hasNextPage
+                                    endCursor
+                                }}
+                                nodes {{
+                                    occurredAt
+                                }}
+                            }}
+                        }}
+                    }}
+                }}
+            "#,
+                kind
+            );
+
+            let mut cursor: Option<String> = None;
+            for _ in 0..MAX_PAGES {
+                let response = client
+                    .post(format!("{}/graphql", config.api_base_url))
+                    .header("Authorization", format!("Bearer {}", token))
+                    .header("User-Agent", "hgitmap/0.1.0")
+                    .json(&json!({
+                        "query": query,
+                        "variables": {
+                            "username": username,
+                            "from": from.to_rfc3339(),
+                            "to": to.to_rfc3339(),
+                            "after": cursor,
+                        },
+                    }))
+                    .send_traced()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(anyhow!("Failed to fetch {}: status {}", kind, response.status()));
+                }
+
+                let body: serde_json::Value = response.json().await?;
+                if let Some(errors) = body.get("errors") {
+                    return Err(anyhow!("GitHub GraphQL errors: {}", errors));
+                }
+
+                let connection = body
+                    .pointer(&format!("/data/user/contributionsCollection/{}", kind))
+                    .ok_or_else(|| anyhow!("No {} in GitHub response", kind))?;
+
+                for node in connection
+                    .get("nodes")
+                    .and_then(|n| n.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    let Some(occurred_at) = node.get("occurredAt").and_then(|v| v.as_str()) else {
+                        continue;
+                    };
+                    let date = chrono::DateTime::parse_from_rfc3339(occurred_at)
+                        .map_err(|e| anyhow!("Failed to parse date: {}", e))?
+                        .naive_utc()
+                        .date();
+                    *days.entry(date).or_insert(0) += 1;
+                }
+
+                let has_next_page = connection
+                    .pointer("/pageInfo/hasNextPage")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                cursor = connection
+                    .pointer("/pageInfo/endCursor")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                if !has_next_page || cursor.is_none() {
+                    break;
+                }
+            }
+        }
+
+        Ok(days)
+    }
+
     async fn search_commits_for_dates(
         &self,
         config: &PlatformConfig,
@@ -905,7 +1145,7 @@ impl GitHubClient {
         token: &str,
         dates: &[chrono::NaiveDate],
     ) -> Result<HashMap<chrono::NaiveDate, Vec<String>>> {
-        let client = create_http_client();
+        let client = &self.http;
         let mut date_repos: HashMap<chrono::NaiveDate, Vec<String>> = HashMap::new();
 
         log::info!(
@@ -1003,7 +1243,7 @@ impl GitHubClient {
                         .header("Authorization", format!("Bearer {}", token))
                         .header("User-Agent", "hgitmap/0.1.0")
                         .header("Accept", "application/vnd.github.cloak-preview+json") // Required for commit search
-                        .send()
+                        .send_traced()
                         .await?;
 
                     let status = attempt.status();
@@ -1102,7 +1342,7 @@ impl GitHubClient {
                                     if let Some(repo_full_name) =
                                         repo.get("full_name").and_then(|n| n.as_str())
                                     {
-                                        let entry = date_repos.entry(date).or_insert_with(Vec::new);
+                                        let entry = date_repos.entry(date).or_default();
                                         if !entry.contains(&repo_full_name.to_string()) {
                                             entry.push(repo_full_name.to_string());
                                         }
@@ -1153,13 +1393,13 @@ impl GitHubClient {
         config: &PlatformConfig,
         token: &str,
     ) -> Result<(i64, String)> {
-        let client = create_http_client();
+        let client = &self.http;
 
         let response = client
-            .get(&format!("{}/rate_limit", config.api_base_url))
+            .get(format!("{}/rate_limit", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -1200,13 +1440,14 @@ impl GitPlatform for GitHubClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Contribution>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         // First, fetch the calendar for accurate total counts
         let calendar_query = r#"
             query($username: String!, $from: DateTime!, $to: DateTime!) {
                 user(login: $username) {
                     contributionsCollection(from: $from, to: $to) {
+                        restrictedContributionsCount
                         contributionCalendar {
                             totalContributions
                             weeks {
@@ -1228,14 +1469,14 @@ impl GitPlatform for GitHubClient {
         });
 
         let response = client
-            .post(&format!("{}/graphql", config.api_base_url))
+            .post(format!("{}/graphql", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
             .json(&json!({
                 "query": calendar_query,
                 "variables": variables,
             }))
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -1262,6 +1503,9 @@ impl GitPlatform for GitHubClient {
             .user
             .ok_or_else(|| anyhow!("User not found"))?;
 
+        let restricted_total = calendar_user_data
+            .contributions_collection
+            .restricted_contributions_count;
         let calendar = calendar_user_data
             .contributions_collection
             .contribution_calendar;
@@ -1340,14 +1584,14 @@ impl GitPlatform for GitHubClient {
             };
 
             let repo_response = client
-                .post(&format!("{}/graphql", config.api_base_url))
+                .post(format!("{}/graphql", config.api_base_url))
                 .header("Authorization", format!("Bearer {}", token))
                 .header("User-Agent", "hgitmap/0.1.0")
                 .json(&json!({
                     "query": repo_query,
                     "variables": variables,
                 }))
-                .send()
+                .send_traced()
                 .await?;
 
             if !repo_response.status().is_success() {
@@ -1435,6 +1679,52 @@ impl GitPlatform for GitHubClient {
             page_num
         );
 
+        // Restricted contributions are in the calendar, but the API only
+        // gives their total. What a day has beyond the contributions the
+        // token can see is taken to be restricted, up to that total.
+        let mut restricted_by_day: HashMap<chrono::NaiveDate, i32> = HashMap::new();
+        if restricted_total > 0 {
+            log::info!("🔒 GitHub reports {} restricted contributions", restricted_total);
+
+            match self
+                .fetch_visible_contribution_days(config, username, token, from, to)
+                .await
+            {
+                Ok(visible) => {
+                    let mut remaining = restricted_total;
+                    let days = calendar.weeks.iter().flat_map(|week| &week.contribution_days);
+                    for day in days {
+                        let Ok(date) = chrono::NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+                            continue;
+                        };
+                        let tracked_commits: i32 = date_privacy_map
+                            .get(&date)
+                            .map_or(0, |(_, repos)| repos.iter().map(|(_, count)| count).sum());
+                        let unexplained = day.contribution_count
+                            - tracked_commits
+                            - visible.get(&date).copied().unwrap_or(0);
+
+                        if unexplained > 0 && remaining > 0 {
+                            let count = unexplained.min(remaining);
+                            restricted_by_day.insert(date, count);
+                            remaining -= count;
+                        }
+                    }
+                    log::info!(
+                        "🔒 Attributed {} restricted contributions to {} days",
+                        restricted_total - remaining,
+                        restricted_by_day.len()
+                    );
+                }
+                Err(e) => {
+                    log::warn!(
+                        "⚠️  Failed to tell restricted contributions apart, keeping them with the public ones: {}",
+                        e
+                    );
+                }
+            }
+        }
+
         // Convert calendar data to our Contribution format, enriched with privacy info
         let mut contributions = Vec::new();
         let mut dates_without_repo: Vec<chrono::NaiveDate> = Vec::new();
@@ -1445,6 +1735,20 @@ impl GitPlatform for GitHubClient {
                     let date = chrono::NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
                         .map_err(|e| anyhow!("Failed to parse date: {}", e))?;
 
+                    // Restricted contributions get a row of their own, so
+                    // the rest of the day keeps its privacy
+                    let restricted = restricted_by_day.get(&date).copied().unwrap_or(0);
+                    if restricted > 0 {
+                        contributions.push(Contribution {
+                            date,
+                            count: restricted,
+                            repository_name: Some(RESTRICTED_REPOSITORY.to_string()),
+                            is_private: true,
+                            contribution_type: ContributionType::Other,
+                        });
+                    }
+                    let day_count = day.contribution_count - restricted;
+
                     // Check if we have repo data for this date
                     if let Some((is_private, repos_with_counts)) = date_privacy_map.get(&date) {
                         // We have repository data for this date
@@ -1456,8 +1760,7 @@ impl GitPlatform for GitHubClient {
 
                         // Calendar count includes commits + PRs + issues + reviews
                         // If calendar count > tracked commits, there are non-commit contributions
-                        let non_commit_contributions =
-                            day.contribution_count.saturating_sub(tracked_commits);
+                        let non_commit_contributions = day_count.saturating_sub(tracked_commits);
 
                         // Create separate contribution for each repository
                         for (repo_name, commit_count) in repos_with_counts {
@@ -1474,7 +1777,7 @@ impl GitPlatform for GitHubClient {
                         // This ensures the total matches GitHub's calendar
                         if non_commit_contributions > 0 {
                             log::debug!("📊 Date {}: calendar shows {} total, tracked {} commits, adding {} non-commit contributions",
-                                date, day.contribution_count, tracked_commits, non_commit_contributions);
+                                date, day_count, tracked_commits, non_commit_contributions);
                             // Use NULL for repository since these are non-commit contributions (PRs, issues, reviews)
                             // This avoids violating the unique constraint
                             contributions.push(Contribution {
@@ -1485,11 +1788,11 @@ impl GitPlatform for GitHubClient {
                                 contribution_type: ContributionType::Commit, // Mixed type
                             });
                         }
-                    } else {
+                    } else if day_count > 0 {
                         // No repo info - create one contribution with NULL
                         contributions.push(Contribution {
                             date,
-                            count: day.contribution_count,
+                            count: day_count,
                             repository_name: None,
                             is_private: false,
                             contribution_type: ContributionType::Commit,
@@ -1563,14 +1866,14 @@ impl GitPlatform for GitHubClient {
     }
 
     async fn validate_token(&self, config: &PlatformConfig, token: &str) -> Result<UserInfo> {
-        let client = create_http_client();
+        let client = &self.http;
 
         // Use GitHub's user endpoint to validate token and get user info
         let response = client
-            .get(&format!("{}/user", config.api_base_url))
+            .get(format!("{}/user", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -1593,14 +1896,14 @@ impl GitPlatform for GitHubClient {
         config: &PlatformConfig,
         token: &str,
     ) -> Result<Vec<Repository>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         let response = client
-            .get(&format!("{}/user/repos", config.api_base_url))
+            .get(format!("{}/user/repos", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("User-Agent", "hgitmap/0.1.0")
             .query(&[("per_page", "100"), ("affiliation", "owner,collaborator")])
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -1629,7 +1932,7 @@ impl GitPlatform for GitHubClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>> {
-        let client = create_http_client();
+        let client = &self.http;
 
         // Fetch user events from GitHub API
         let mut all_events = Vec::new();
@@ -1639,7 +1942,7 @@ impl GitPlatform for GitHubClient {
         // GitHub API only allows fetching up to 300 events
         while page <= 3 {
             let response = client
-                .get(&format!(
+                .get(format!(
                     "{}/users/{}/events",
                     config.api_base_url, username
                 ))
@@ -1649,7 +1952,7 @@ impl GitPlatform for GitHubClient {
                     ("per_page", &per_page.to_string()),
                     ("page", &page.to_string()),
                 ])
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -1719,7 +2022,7 @@ impl GitPlatform for GitHubClient {
                     let key = format!("{}_{}", naive_date, event.repo.name);
                     commit_groups
                         .entry(key)
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .push(event);
                 }
                 "CreateEvent" => {
@@ -1909,7 +2212,7 @@ impl GitPlatform for GitHubClient {
         );
 
         // Sort by date descending
-        activities.sort_by(|a, b| b.date.cmp(&a.date));
+        activities.sort_by_key(|a| std::cmp::Reverse(a.date));
 
         Ok(activities)
     }
@@ -1926,6 +2229,122 @@ impl GitPlatform for GitHubClient {
         GitHubClient::fetch_repository_creation_activities(self, config, username, token, from, to)
             .await
     }
+
+    async fn fetch_repository_languages(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        repository: &str,
+    ) -> Result<BTreeMap<String, i64>> {
+        let response = self
+            .http
+            .get(format!("{}/repos/{}/languages", config.api_base_url, repository))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("User-Agent", "hgitmap/0.1.0")
+            .send_traced()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to fetch languages of {}: status {}", repository, response.status()));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn fetch_commit_details(
+        &self,
+        config: &PlatformConfig,
+        username: &str,
+        token: &str,
+        repository: &str,
+        user_emails: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CommitDetail>> {
+        let (owner, name) = repository
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Invalid repository name: {}", repository))?;
+
+        // History filters by the user's node ID rather than their login
+        let user_query = r#"
+            query($login: String!) {
+                user(login: $login) {
+                    id
+                }
+            }
+        "#;
+        let user_data = self
+            .graphql(config, token, user_query, json!({ "login": username }))
+            .await?;
+        let author_id = user_data
+            .pointer("/data/user/id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("GitHub user {} not found", username))?
+            .to_string();
+
+        let mut commits: Vec<CommitDetail> = self
+            .commit_history(config, token, owner, name, json!({ "id": author_id }), from, to)
+            .await?
+            .iter()
+            .filter_map(|node| commit_detail(node, false))
+            .collect();
+
+        // Commits with an email not linked to the account, and the ones the
+        // user co-authored, are only in the unfiltered history
+        if !user_emails.is_empty() {
+            for node in self
+                .commit_history(config, token, owner, name, serde_json::Value::Null, from, to)
+                .await?
+            {
+                let by_user = node
+                    .pointer("/author/user/login")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|login| login.eq_ignore_ascii_case(username));
+                if by_user {
+                    continue;
+                }
+                let by_email = node
+                    .pointer("/author/email")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|email| user_emails.contains(&email.to_lowercase()));
+                let message = node.get("message").and_then(|v| v.as_str()).unwrap_or_default();
+                let co_authored = !by_email
+                    && co_authors::emails(message)
+                        .iter()
+                        .any(|email| user_emails.contains(email));
+                if by_email || co_authored {
+                    commits.extend(commit_detail(&node, co_authored));
+                }
+            }
+        }
+
+        Ok(commits)
+    }
+
+    async fn capabilities(&self, _config: &PlatformConfig, _username: &str, _token: &str) -> Capabilities {
+        Capabilities {
+            contributions: true,
+            contribution_calendar: true,
+            graphql: true,
+            organizations: true,
+            pull_requests_and_issues: true,
+            token_revocation: true,
+        }
+    }
+}
+
+/// A commit of a history node, None when it lacks its SHA or date
+fn commit_detail(node: &serde_json::Value, co_authored: bool) -> Option<CommitDetail> {
+    let sha = node.get("oid").and_then(|v| v.as_str())?;
+    let authored = node.get("authoredDate").and_then(|v| v.as_str())?;
+    let committed_at = DateTime::parse_from_rfc3339(authored).ok()?;
+    Some(CommitDetail {
+        sha: sha.to_string(),
+        committed_at: committed_at.with_timezone(&Utc),
+        additions: node.get("additions").and_then(|v| v.as_i64()).map(|v| v as i32),
+        deletions: node.get("deletions").and_then(|v| v.as_i64()).map(|v| v as i32),
+        co_authored,
+    })
 }
 
 // GitHub API response types
@@ -1951,6 +2370,9 @@ struct GitHubContributionCalendarOnlyUserData {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GitHubContributionCalendarOnlyCollection {
+    /// Contributions in the calendar the token may not see, e.g. to private
+    /// repositories without the `repo` scope
+    restricted_contributions_count: i32,
     contribution_calendar: GitHubContributionCalendar,
 }
 
@@ -1,12 +1,42 @@
-use super::{Activity, ActivityType, Contribution, ContributionType, GitPlatform, PlatformConfig, Repository, UserInfo};
-use crate::utils::http_client::create_http_client;
+use super::{
+    Activity, ActivityType, Capabilities, CommitDetail, Contribution, ContributionType, GitPlatform,
+    PlatformConfig, Repository, UserInfo, MAX_COMMIT_PAGES, RESTRICTED_REPOSITORY,
+};
+use crate::utils::co_authors;
+use crate::utils::http_client::{shared_client, SendTraced};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc, NaiveDate};
+use chrono::{DateTime, Duration, Utc, NaiveDate};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// Days back from today that a user's contribution calendar covers
+const CALENDAR_DAYS: i64 = 365;
+
+/// How long what was detected about an instance is trusted, so that upgrades
+/// and configuration changes are picked up
+const INSTANCE_INFO_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// What was detected about each instance, by instance URL
+static INSTANCES: OnceLock<Mutex<HashMap<String, InstanceInfo>>> = OnceLock::new();
+
+/// What a GitLab instance supports, detected on first use
+#[derive(Clone, Debug)]
+struct InstanceInfo {
+    /// As reported by `/version`, e.g. `16.8.1-ee`; None when the token may
+    /// not read it
+    version: Option<String>,
+    /// Whether `/users/{username}/calendar.json` answers with JSON. Instances
+    /// that restrict public profiles redirect it to the sign-in page.
+    has_calendar: bool,
+    detected_at: Instant,
+}
 
-pub struct GitLabClient;
+pub struct GitLabClient {
+    http: reqwest::Client,
+}
 
 // GitLab API response structures
 
@@ -18,8 +48,25 @@ struct GitLabUser {
     avatar_url: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GitLabCommit {
+    id: String,
+    authored_date: String,
+    author_email: String,
+    #[serde(default)]
+    message: String,
+    stats: Option<GitLabCommitStats>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabCommitStats {
+    additions: i32,
+    deletions: i32,
+}
+
 #[derive(Debug, Deserialize)]
 struct GitLabEvent {
+    project_id: Option<i64>,
     action_name: String,
     target_type: Option<String>,
     target_title: Option<String>,
@@ -45,8 +92,11 @@ struct GitLabProject {
 }
 
 impl GitLabClient {
+    /// A client on the shared HTTP connection pool
     pub fn new() -> Self {
-        Self
+        Self {
+            http: shared_client(),
+        }
     }
 
     /// Revoke a GitLab OAuth token
@@ -55,7 +105,7 @@ impl GitLabClient {
         instance_url: &str,
         access_token: &str,
     ) -> Result<()> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔒 Revoking GitLab OAuth token for instance: {}", instance_url);
 
@@ -64,7 +114,7 @@ impl GitLabClient {
         let response = client
             .post(&revoke_url)
             .form(&[("token", access_token)])
-            .send()
+            .send_traced()
             .await?;
 
         if response.status().is_success() {
@@ -85,15 +135,15 @@ impl GitLabClient {
         config: &PlatformConfig,
         token: &str,
     ) -> Result<serde_json::Value> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("👤 Fetching GitLab profile");
 
         let response = client
-            .get(&format!("{}/user", config.api_base_url))
+            .get(format!("{}/user", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("accept", "application/json")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -110,40 +160,44 @@ impl GitLabClient {
         Ok(profile)
     }
 
-    /// Fetch user's contribution events from GitLab
+    /// Fetch user's events from GitLab between `from` and `to`
     async fn fetch_user_events(
         &self,
         config: &PlatformConfig,
         user_id: i64,
         token: &str,
-        _from: DateTime<Utc>,
-        _to: DateTime<Utc>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
     ) -> Result<Vec<GitLabEvent>> {
-        let client = create_http_client();
+        let client = &self.http;
         let mut all_events = Vec::new();
         let mut page = 1;
         let per_page = 100;
 
+        // Both bounds are exclusive dates
+        let after = (from.date_naive() - Duration::days(1)).format("%Y-%m-%d");
+        let before = (to.date_naive() + Duration::days(1)).format("%Y-%m-%d");
+
         log::info!("📡 Fetching GitLab events for user ID: {}", user_id);
 
         loop {
-            // Temporarily remove date filters to test if events exist
             let url = format!(
-                "{}/users/{}/events?per_page={}&page={}",
+                "{}/users/{}/events?after={}&before={}&sort=asc&per_page={}&page={}",
                 config.api_base_url,
                 user_id,
+                after,
+                before,
                 per_page,
                 page
             );
 
             log::debug!("Requesting page {}: {}", page, url);
-            log::info!("🌐 GitLab API URL (NO DATE FILTER TEST): {}", url);
 
             let response = client
                 .get(&url)
                 .header("Authorization", format!("Bearer {}", token))
                 .header("accept", "application/json")
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -153,28 +207,29 @@ impl GitLabClient {
                 return Err(anyhow!("Failed to fetch GitLab events: status {}", status));
             }
 
+            // Empty on the last page; left out by some proxies
+            let next_page = response.headers().get("x-next-page").map(|v| {
+                v.to_str().ok().and_then(|v| v.parse::<u32>().ok())
+            });
+
             let events: Vec<GitLabEvent> = response.json().await?;
             let event_count = events.len();
 
             log::debug!("📥 Fetched {} events from page {}", event_count, page);
 
-            // Log first event for debugging
-            if !events.is_empty() {
-                log::info!("📋 Sample event action_name: {:?}", events[0].action_name);
-            }
-
             if events.is_empty() {
                 break;
             }
 
             all_events.extend(events);
 
-            // If we got fewer than per_page items, we're done
-            if event_count < per_page {
-                break;
-            }
-
-            page += 1;
+            // Without the header, a short page is the last one
+            page = match next_page {
+                Some(Some(next)) => next,
+                Some(None) => break,
+                None if event_count < per_page => break,
+                None => page + 1,
+            };
 
             // Safety limit to prevent infinite loops
             if page > 100 {
@@ -187,20 +242,184 @@ impl GitLabClient {
 
         Ok(all_events)
     }
+
+    /// What the instance supports, detected once a day. `username` is used
+    /// to probe the calendar.
+    async fn instance_info(
+        &self,
+        config: &PlatformConfig,
+        username: &str,
+        token: &str,
+    ) -> InstanceInfo {
+        let instances = INSTANCES.get_or_init(Default::default);
+        if let Some(info) = instances.lock().unwrap().get(&config.instance_url) {
+            if info.detected_at.elapsed().as_secs() < INSTANCE_INFO_TTL_SECS {
+                return info.clone();
+            }
+        }
+
+        let version = match self
+            .http
+            .get(format!("{}/version", config.api_base_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("accept", "application/json")
+            .send_traced()
+            .await
+        {
+            Ok(response) if response.status().is_success() => response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v.get("version").and_then(|v| v.as_str()).map(str::to_string)),
+            _ => None,
+        };
+
+        // A missing user says nothing about the instance, so only answers
+        // that are not JSON count against the calendar
+        let has_calendar = !matches!(
+            self.fetch_calendar(config, username).await,
+            Err(CalendarError::Unsupported)
+        );
+
+        log::info!(
+            "🔎 GitLab instance {}: version {}, calendar {}",
+            config.instance_url,
+            version.as_deref().unwrap_or("unknown"),
+            if has_calendar { "available" } else { "unavailable" }
+        );
+
+        let info = InstanceInfo {
+            version,
+            has_calendar,
+            detected_at: Instant::now(),
+        };
+        instances
+            .lock()
+            .unwrap()
+            .insert(config.instance_url.clone(), info.clone());
+        info
+    }
+
+    /// Daily totals of the user's contribution graph over the last year, as
+    /// the instance draws it. Days without contributions are left out.
+    async fn fetch_calendar(
+        &self,
+        config: &PlatformConfig,
+        username: &str,
+    ) -> Result<HashMap<NaiveDate, i32>, CalendarError> {
+        // A web route rather than an API one, so it takes no token and only
+        // has private contributions if the user chose to show them
+        let url = format!(
+            "{}/users/{}/calendar.json",
+            config.instance_url.trim_end_matches('/'),
+            username
+        );
+
+        let response = self
+            .http
+            .get(&url)
+            .header("accept", "application/json")
+            .send_traced()
+            .await
+            .map_err(|e| CalendarError::Failed(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(CalendarError::Failed("user not found".to_string()));
+        }
+        if !response.status().is_success() {
+            return Err(CalendarError::Failed(format!("status {}", response.status())));
+        }
+
+        let days: HashMap<String, i32> = response
+            .json()
+            .await
+            .map_err(|_| CalendarError::Unsupported)?;
+
+        Ok(days
+            .into_iter()
+            .filter_map(|(date, count)| {
+                NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| (date, count))
+            })
+            .collect())
+    }
+
+    /// Path and privacy of each project, or None for projects the token may
+    /// not read
+    async fn fetch_project_names(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        project_ids: impl IntoIterator<Item = i64>,
+    ) -> Result<HashMap<i64, Option<(String, bool)>>> {
+        let mut projects = HashMap::new();
+
+        for id in project_ids {
+            let response = self
+                .http
+                .get(format!("{}/projects/{}", config.api_base_url, id))
+                .header("Authorization", format!("Bearer {}", token))
+                .header("accept", "application/json")
+                .send_traced()
+                .await?;
+
+            let project = match response.status() {
+                status if status.is_success() => {
+                    let project: GitLabProject = response.json().await?;
+                    Some((project.path_with_namespace, project.visibility != "public"))
+                }
+                reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::NOT_FOUND => None,
+                status => return Err(anyhow!("Failed to fetch GitLab project {}: status {}", id, status)),
+            };
+            projects.insert(id, project);
+        }
+
+        Ok(projects)
+    }
+}
+
+/// Why the contribution calendar could not be read
+#[derive(Debug)]
+enum CalendarError {
+    /// The instance does not serve it, e.g. because profiles need signing in
+    Unsupported,
+    /// This time or for this user only
+    Failed(String),
+}
+
+impl std::fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalendarError::Unsupported => write!(f, "not served by this instance"),
+            CalendarError::Failed(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// Whether an event counts towards GitLab's contribution graph: pushes,
+/// opening, closing and merging issues and merge requests, comments and
+/// approvals. A push counts once however many commits it has.
+fn is_contribution(event: &GitLabEvent) -> bool {
+    event.action_name.starts_with("pushed")
+        || matches!(
+            event.action_name.as_str(),
+            "opened" | "closed" | "accepted" | "merged" | "commented on" | "approved"
+        )
 }
 
 #[async_trait]
 impl GitPlatform for GitLabClient {
     async fn validate_token(&self, config: &PlatformConfig, token: &str) -> Result<UserInfo> {
-        let client = create_http_client();
+        let client = &self.http;
 
         log::info!("🔍 Validating GitLab token");
 
         let response = client
-            .get(&format!("{}/user", config.api_base_url))
+            .get(format!("{}/user", config.api_base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("accept", "application/json")
-            .send()
+            .send_traced()
             .await?;
 
         if !response.status().is_success() {
@@ -237,14 +456,11 @@ impl GitPlatform for GitLabClient {
         let user_id: i64 = user_info.id.parse()
             .map_err(|e| anyhow!("Failed to parse user ID: {}", e))?;
 
-        // Fetch events
         let events = self.fetch_user_events(config, user_id, token, from, to).await?;
 
-        // Group contributions by date and repository
-        let mut contributions_map: HashMap<(NaiveDate, Option<String>), i32> = HashMap::new();
-
-        for event in events {
-            // Parse the created_at timestamp
+        // Contribution events by day and project
+        let mut events_by_day: HashMap<NaiveDate, HashMap<Option<i64>, i32>> = HashMap::new();
+        for event in events.iter().filter(|event| is_contribution(event)) {
             let event_date = match DateTime::parse_from_rfc3339(&event.created_at) {
                 Ok(dt) => dt.with_timezone(&Utc).date_naive(),
                 Err(e) => {
@@ -252,41 +468,98 @@ impl GitPlatform for GitLabClient {
                     continue;
                 }
             };
-
-            // Filter by date range
             if event_date < from.date_naive() || event_date > to.date_naive() {
                 continue;
             }
+            *events_by_day
+                .entry(event_date)
+                .or_default()
+                .entry(event.project_id)
+                .or_insert(0) += 1;
+        }
 
-            // Only count push events for contributions
-            // GitLab API returns action_name as "pushed to" or "pushed new"
-            if event.action_name.starts_with("pushed") {
-                if let Some(push_data) = &event.push_data {
-                    let count = push_data.commit_count;
-
-                    // Try to extract repository name from event
-                    let repo_name = event.target_title.clone();
-
-                    let key = (event_date, repo_name.clone());
-                    *contributions_map.entry(key).or_insert(0) += count;
-
-                    log::debug!("📊 Push event: {} commits on {} to {:?}", count, event_date, repo_name);
-                } else {
-                    log::warn!("⚠️  Push event has no push_data: action_name={}, target_title={:?}", event.action_name, event.target_title);
+        let mut project_ids: Vec<i64> = events_by_day
+            .values()
+            .flat_map(|projects| projects.keys().flatten().copied())
+            .collect();
+        project_ids.sort_unstable();
+        project_ids.dedup();
+        let projects = self.fetch_project_names(config, token, project_ids).await?;
+
+        // The calendar has the exact daily totals, but only for the last
+        // year and not on every instance; elsewhere the events are counted
+        let calendar_start = Utc::now().date_naive() - Duration::days(CALENDAR_DAYS);
+        let calendar = if to.date_naive() > calendar_start {
+            let instance = self.instance_info(config, username, token).await;
+            if instance.has_calendar {
+                match self.fetch_calendar(config, username).await {
+                    Ok(calendar) => Some(calendar),
+                    Err(e) => {
+                        log::warn!("⚠️  GitLab calendar unavailable, counting events instead: {}", e);
+                        None
+                    }
                 }
             } else {
-                log::debug!("⏭️  Skipping non-push event: action_name={}", event.action_name);
+                log::info!(
+                    "GitLab {} has no contribution calendar, counting events instead",
+                    instance.version.as_deref().unwrap_or("(unknown version)")
+                );
+                None
+            }
+        } else {
+            None
+        };
+
+        // Rows by day and repository; projects the token cannot read share
+        // one private row
+        let mut rows: HashMap<(NaiveDate, Option<String>), (i32, bool)> = HashMap::new();
+        let mut day = from.date_naive();
+        while day <= to.date_naive() {
+            let mut day_events: Vec<(Option<String>, bool, i32)> = events_by_day
+                .remove(&day)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(project_id, count)| match project_id.map(|id| projects.get(&id)) {
+                    Some(Some(Some((path, is_private)))) => (Some(path.clone()), *is_private, count),
+                    Some(_) => (Some(RESTRICTED_REPOSITORY.to_string()), true, count),
+                    None => (None, false, count),
+                })
+                .collect();
+            day_events.sort();
+
+            let event_total: i32 = day_events.iter().map(|(_, _, count)| count).sum();
+            let total = match &calendar {
+                Some(calendar) if day > calendar_start => calendar.get(&day).copied().unwrap_or(0),
+                _ => event_total,
+            };
+
+            // Events are attributed up to the day's total; the rest of the
+            // total has no repository
+            let mut remaining = total;
+            for (repository, is_private, count) in day_events {
+                let count = count.min(remaining);
+                if count == 0 {
+                    continue;
+                }
+                let row = rows.entry((day, repository)).or_insert((0, false));
+                row.0 += count;
+                row.1 |= is_private;
+                remaining -= count;
             }
+            if remaining > 0 {
+                rows.entry((day, None)).or_insert((0, false)).0 += remaining;
+            }
+
+            day += Duration::days(1);
         }
 
-        // Convert map to Vec<Contribution>
-        let contributions: Vec<Contribution> = contributions_map
+        let contributions: Vec<Contribution> = rows
             .into_iter()
-            .map(|((date, repo_name), count)| Contribution {
+            .map(|((date, repository_name), (count, is_private))| Contribution {
                 date,
                 count,
-                repository_name: repo_name,
-                is_private: false, // GitLab events API doesn't easily expose visibility
+                repository_name,
+                is_private,
                 contribution_type: ContributionType::Commit,
             })
             .collect();
@@ -301,7 +574,7 @@ impl GitPlatform for GitLabClient {
         config: &PlatformConfig,
         token: &str,
     ) -> Result<Vec<Repository>> {
-        let client = create_http_client();
+        let client = &self.http;
         let mut all_repos = Vec::new();
         let mut page = 1;
         let per_page = 100;
@@ -320,7 +593,7 @@ impl GitPlatform for GitLabClient {
                 .get(&url)
                 .header("Authorization", format!("Bearer {}", token))
                 .header("accept", "application/json")
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -465,7 +738,7 @@ impl GitPlatform for GitLabClient {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>> {
-        let client = create_http_client();
+        let client = &self.http;
         let mut all_activities = Vec::new();
         let mut page = 1;
         let per_page = 100;
@@ -484,7 +757,7 @@ impl GitPlatform for GitLabClient {
                 .get(&url)
                 .header("Authorization", format!("Bearer {}", token))
                 .header("accept", "application/json")
-                .send()
+                .send_traced()
                 .await?;
 
             if !response.status().is_success() {
@@ -546,4 +819,159 @@ impl GitPlatform for GitLabClient {
 
         Ok(all_activities)
     }
+
+    /// GitLab only tells the share of each language, so the bytes are that
+    /// share of the repository's size, which needs at least reporter access
+    async fn fetch_repository_languages(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        repository: &str,
+    ) -> Result<BTreeMap<String, i64>> {
+        let project_url = format!("{}/projects/{}", config.api_base_url, urlencoding::encode(repository));
+
+        let response = self
+            .http
+            .get(format!("{}/languages", project_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("accept", "application/json")
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to fetch languages of {}: status {}", repository, response.status()));
+        }
+        let shares: BTreeMap<String, f64> = response.json().await?;
+        if shares.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        let response = self
+            .http
+            .get(&project_url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("accept", "application/json")
+            .query(&[("statistics", "true")])
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to fetch project {}: status {}", repository, response.status()));
+        }
+        let project: serde_json::Value = response.json().await?;
+        let size = project
+            .pointer("/statistics/repository_size")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| anyhow!("No repository size for {}", repository))?;
+
+        Ok(shares
+            .into_iter()
+            .map(|(language, percent)| (language, (size as f64 * percent / 100.0).round() as i64))
+            .collect())
+    }
+
+    async fn fetch_commit_details(
+        &self,
+        config: &PlatformConfig,
+        _username: &str,
+        token: &str,
+        repository: &str,
+        user_emails: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CommitDetail>> {
+        // Commits carry no username, only the author's email, so they are
+        // matched against the emails of the token's user and the verified
+        // ones of the user
+        let response = self
+            .http
+            .get(format!("{}/user", config.api_base_url))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("accept", "application/json")
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to fetch GitLab user: status {}", response.status()));
+        }
+        let user: serde_json::Value = response.json().await?;
+        let emails: Vec<String> = ["email", "commit_email", "public_email"]
+            .iter()
+            .filter_map(|field| user.get(*field).and_then(|v| v.as_str()))
+            .filter(|email| !email.is_empty())
+            .map(|email| email.to_lowercase())
+            .chain(user_emails.iter().cloned())
+            .collect();
+        if emails.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let per_page = 100;
+        let since = from.to_rfc3339();
+        let until = to.to_rfc3339();
+        let mut commits = Vec::new();
+
+        for page in 1..=MAX_COMMIT_PAGES {
+            let response = self
+                .http
+                .get(format!(
+                    "{}/projects/{}/repository/commits",
+                    config.api_base_url,
+                    urlencoding::encode(repository)
+                ))
+                .header("Authorization", format!("Bearer {}", token))
+                .header("accept", "application/json")
+                .query(&[
+                    ("since", since.as_str()),
+                    ("until", until.as_str()),
+                    ("with_stats", "true"),
+                    ("page", &page.to_string()),
+                    ("per_page", &per_page.to_string()),
+                ])
+                .send_traced()
+                .await?;
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to fetch commits of {}: status {}", repository, response.status()));
+            }
+
+            let page_commits: Vec<GitLabCommit> = response.json().await?;
+            let fetched = page_commits.len();
+
+            for commit in page_commits {
+                let by_user = emails.contains(&commit.author_email.to_lowercase());
+                let co_authored = !by_user
+                    && co_authors::emails(&commit.message)
+                        .iter()
+                        .any(|email| user_emails.contains(email));
+                if !by_user && !co_authored {
+                    continue;
+                }
+                let Ok(committed_at) = DateTime::parse_from_rfc3339(&commit.authored_date) else {
+                    continue;
+                };
+                commits.push(CommitDetail {
+                    sha: commit.id,
+                    committed_at: committed_at.with_timezone(&Utc),
+                    additions: commit.stats.as_ref().map(|s| s.additions),
+                    deletions: commit.stats.as_ref().map(|s| s.deletions),
+                    co_authored,
+                });
+            }
+
+            if fetched < per_page {
+                break;
+            }
+        }
+
+        Ok(commits)
+    }
+
+    async fn capabilities(&self, config: &PlatformConfig, username: &str, token: &str) -> Capabilities {
+        let instance = self.instance_info(config, username, token).await;
+        Capabilities {
+            contributions: true,
+            contribution_calendar: instance.has_calendar,
+            graphql: false,
+            organizations: false,
+            pull_requests_and_issues: false,
+            token_revocation: true,
+        }
+    }
 }
@@ -11,6 +11,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
 use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
 
 /// Represents a contribution event from a git platform
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -22,6 +23,21 @@ pub struct Contribution {
     pub contribution_type: ContributionType,
 }
 
+/// Repository name of contributions the platform counts but does not let the
+/// token see, such as GitHub's restricted contributions. Such contributions
+/// are always private.
+pub const RESTRICTED_REPOSITORY: &str = "(private contributions)";
+
+impl Contribution {
+    /// The repository the contribution was made in, if known. None for
+    /// restricted contributions, whose repository is not known either.
+    pub fn repository(&self) -> Option<&str> {
+        self.repository_name
+            .as_deref()
+            .filter(|name| *name != RESTRICTED_REPOSITORY)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ContributionType {
@@ -80,6 +96,23 @@ pub struct Repository {
     pub url: String,
 }
 
+/// A commit by the account's user, for detailed sync
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitDetail {
+    pub sha: String,
+    /// When the commit was authored
+    pub committed_at: DateTime<Utc>,
+    /// None when the platform does not report line counts
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+    /// The user is named in a Co-authored-by trailer rather than the author
+    pub co_authored: bool,
+}
+
+/// Pages of commits fetched per repository and sync, so a busy repository
+/// cannot hold up a sync
+pub const MAX_COMMIT_PAGES: usize = 10;
+
 /// Configuration for a specific platform instance
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
@@ -125,6 +158,35 @@ impl PlatformConfig {
             api_base_url: format!("{}/api/v1", instance_url.trim_end_matches('/')),
         }
     }
+
+    /// Years of one account fetched at the same time in a multi-year sync.
+    /// GitHub answers bursts of search requests with secondary rate limits,
+    /// so it gets fewer than GitLab and Gitea.
+    pub fn year_concurrency(&self) -> usize {
+        match self.platform.as_str() {
+            "github" => 2,
+            _ => 3,
+        }
+    }
+}
+
+/// What a platform instance supports, so callers can skip what would fail
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct Capabilities {
+    /// Daily contribution counts can be read at all
+    pub contributions: bool,
+    /// Daily totals match the graph on the user's profile there
+    pub contribution_calendar: bool,
+    /// Queries go through GraphQL, which reaches back further than the
+    /// events the platform keeps
+    pub graphql: bool,
+    /// Public organization memberships and when they were joined
+    pub organizations: bool,
+    /// Pull requests and issues over the whole history, not only those
+    /// still in the events
+    pub pull_requests_and_issues: bool,
+    /// OAuth tokens are revoked when the account is disconnected
+    pub token_revocation: bool,
 }
 
 /// Trait that all git platform integrations must implement
@@ -170,4 +232,34 @@ pub trait GitPlatform: Send + Sync {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<Activity>>;
+
+    /// Bytes of code per language in a repository, by its full name like
+    /// `owner/repo`. Empty when the platform detected no language.
+    async fn fetch_repository_languages(
+        &self,
+        config: &PlatformConfig,
+        token: &str,
+        repository: &str,
+    ) -> Result<BTreeMap<String, i64>>;
+
+    /// Commits on the default branch of a repository, by its full name like
+    /// `owner/repo`, authored within a date range by `username` or with one
+    /// of the user's verified `user_emails` (lowercase), and those with a
+    /// Co-authored-by trailer naming one of them. At most MAX_COMMIT_PAGES
+    /// pages are fetched per pass.
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_commit_details(
+        &self,
+        config: &PlatformConfig,
+        username: &str,
+        token: &str,
+        repository: &str,
+        user_emails: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CommitDetail>>;
+
+    /// What the instance supports for `username`. Checks that need a request
+    /// count as unsupported when the request fails.
+    async fn capabilities(&self, config: &PlatformConfig, username: &str, token: &str) -> Capabilities;
 }
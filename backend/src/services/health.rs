@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Last time a background loop completed an iteration. Used by /readyz to
+/// spot processors that died or got stuck.
+pub struct Heartbeat {
+    last_beat: AtomicI64,
+    /// Seconds without a beat after which the loop counts as stalled
+    stale_after_secs: i64,
+}
+
+impl Heartbeat {
+    pub const fn new(stale_after_secs: i64) -> Self {
+        Self {
+            last_beat: AtomicI64::new(0),
+            stale_after_secs,
+        }
+    }
+
+    pub fn beat(&self) {
+        self.last_beat.store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    /// None until the first beat
+    pub fn last_beat(&self) -> Option<DateTime<Utc>> {
+        match self.last_beat.load(Ordering::Relaxed) {
+            0 => None,
+            ts => DateTime::from_timestamp(ts, 0),
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.last_beat()
+            .is_some_and(|last| (Utc::now() - last).num_seconds() <= self.stale_after_secs)
+    }
+}
+
+/// Heatmap generation job processor (ticks every 30 seconds, but a tick may
+/// spend a while rendering)
+pub static HEATMAP_JOB_PROCESSOR: Heartbeat = Heartbeat::new(600);
+
+/// Platform sync job processor (ticks every 2 seconds, but a tick may run a
+/// long sync)
+pub static SYNC_JOB_PROCESSOR: Heartbeat = Heartbeat::new(900);
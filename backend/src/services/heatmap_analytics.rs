@@ -0,0 +1,170 @@
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{heatmap_access_daily, heatmap_theme};
+
+/// Days of daily view counts kept; the cleanup job removes older ones
+pub const RETENTION_DAYS: i64 = 400;
+
+/// Referring sites listed per theme
+const TOP_REFERRERS: usize = 5;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailyViews {
+    /// YYYY-MM-DD
+    pub date: String,
+    pub views: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReferrerViews {
+    /// Host of the referring page; null for requests without a Referer,
+    /// such as GitHub READMEs and direct links
+    pub referrer: Option<String>,
+    pub views: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThemeViews {
+    pub theme_id: String,
+    pub slug: String,
+    pub name: String,
+    pub views: i64,
+    pub top_referrers: Vec<ReferrerViews>,
+    /// Views by kind of client: browser, github (its README image proxy),
+    /// bot or other
+    pub clients: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HeatmapAnalytics {
+    pub days: i64,
+    pub total_views: i64,
+    /// Every day of the period, oldest first, including days without views
+    pub daily: Vec<DailyViews>,
+    /// Themes with views in the period, most viewed first
+    pub themes: Vec<ThemeViews>,
+}
+
+/// One theme's views while summing up the rows
+#[derive(Default)]
+struct ThemeTally {
+    views: i64,
+    referrers: HashMap<String, i64>,
+    clients: BTreeMap<String, i64>,
+}
+
+/// Count one view of a theme's heatmap. `referrer_host` is empty when the
+/// request had no Referer.
+pub async fn record(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    theme_id: Uuid,
+    referrer_host: String,
+    client: &str,
+) -> Result<(), DbErr> {
+    let views = Expr::col((heatmap_access_daily::Entity, heatmap_access_daily::Column::Views)).add(1);
+
+    heatmap_access_daily::Entity::insert(heatmap_access_daily::ActiveModel {
+        user_id: Set(user_id),
+        theme_id: Set(theme_id),
+        access_date: Set(Utc::now().date_naive()),
+        referrer_host: Set(referrer_host),
+        client: Set(client.to_string()),
+        views: Set(1),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            heatmap_access_daily::Column::ThemeId,
+            heatmap_access_daily::Column::AccessDate,
+            heatmap_access_daily::Column::ReferrerHost,
+            heatmap_access_daily::Column::Client,
+        ])
+        .value(heatmap_access_daily::Column::Views, views)
+        .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Views of a user's heatmaps over the last `days` days, today included
+pub async fn summary(db: &DatabaseConnection, user_id: Uuid, days: i64) -> Result<HeatmapAnalytics, DbErr> {
+    let today = Utc::now().date_naive();
+    let start = today - Duration::days(days - 1);
+
+    let rows = heatmap_access_daily::Entity::find()
+        .filter(heatmap_access_daily::Column::UserId.eq(user_id))
+        .filter(heatmap_access_daily::Column::AccessDate.gte(start))
+        .all(db)
+        .await?;
+    let themes: HashMap<Uuid, heatmap_theme::Model> = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|theme| (theme.id, theme))
+        .collect();
+
+    let mut per_day: HashMap<NaiveDate, i64> = HashMap::new();
+    let mut per_theme: HashMap<Uuid, ThemeTally> = HashMap::new();
+
+    for row in rows {
+        let views = row.views as i64;
+        *per_day.entry(row.access_date).or_insert(0) += views;
+
+        let tally = per_theme.entry(row.theme_id).or_default();
+        tally.views += views;
+        *tally.referrers.entry(row.referrer_host).or_insert(0) += views;
+        *tally.clients.entry(row.client).or_insert(0) += views;
+    }
+
+    let daily: Vec<DailyViews> = (0..days)
+        .map(|offset| start + Duration::days(offset))
+        .map(|date| DailyViews {
+            date: date.to_string(),
+            views: per_day.get(&date).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let mut theme_views: Vec<ThemeViews> = per_theme
+        .into_iter()
+        .filter_map(|(theme_id, tally)| {
+            let theme = themes.get(&theme_id)?;
+
+            let mut top_referrers: Vec<ReferrerViews> = tally
+                .referrers
+                .into_iter()
+                .map(|(host, views)| ReferrerViews {
+                    referrer: Some(host).filter(|h| !h.is_empty()),
+                    views,
+                })
+                .collect();
+            top_referrers.sort_by(|a, b| b.views.cmp(&a.views).then_with(|| a.referrer.cmp(&b.referrer)));
+            top_referrers.truncate(TOP_REFERRERS);
+
+            Some(ThemeViews {
+                theme_id: theme_id.to_string(),
+                slug: theme.slug.clone(),
+                name: theme.name.clone(),
+                views: tally.views,
+                top_referrers,
+                clients: tally.clients,
+            })
+        })
+        .collect();
+    theme_views.sort_by(|a, b| b.views.cmp(&a.views).then_with(|| a.slug.cmp(&b.slug)));
+
+    Ok(HeatmapAnalytics {
+        days,
+        total_views: daily.iter().map(|d| d.views).sum(),
+        daily,
+        themes: theme_views,
+    })
+}
@@ -2,16 +2,31 @@ use anyhow::{Context, Result};
 use chrono::{Datelike, Duration, NaiveDate, Utc};
 use image::{ImageBuffer, ImageEncoder, RgbaImage};
 use sea_orm::*;
+use sea_orm::sea_query::Expr;
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use usvg::{TreeParsing, TreeTextToPath};
 
 use crate::models::{
     contribution, generated_heatmap, git_platform_account, heatmap_generation_setting,
     heatmap_theme::{self, HeatmapFormat},
-    user,
+    user, user_setting,
 };
+use crate::services::{achievements, commit_details};
+use crate::services::heatmap_renderers::{self, HeatmapRenderer};
+use crate::services::render_lanes::{self, Lane};
+use crate::utils::achievements::Achievement;
+use crate::utils::code_frequency::WeekLines;
+use crate::utils::{away_periods, regeneration, repo_exclusion};
+
+/// Directory generated heatmap files are written to, one subdirectory per user
+pub const HEATMAP_BASE_DIR: &str = "static/heatmaps";
+
+/// Appended to the theme's slug in the names of its mini heatmap files,
+/// `{slug}-mini.{ext}`
+pub const MINI_SUFFIX: &str = "-mini";
 
 // Color palette definitions for different schemes
 pub struct ColorPalette {
@@ -96,45 +111,88 @@ impl ColorPalette {
         Self { colors }
     }
 
+    /// The theme's palette: its scheme's colors, or its own for a custom scheme
+    pub fn for_theme(theme: &heatmap_theme::Model) -> Self {
+        let mut palette = Self::from_scheme(&theme.color_scheme);
+
+        // Override with custom colors ONLY if color_scheme is Custom
+        if matches!(
+            theme.color_scheme,
+            heatmap_theme::HeatmapColorScheme::Custom
+        ) {
+            if let Some(custom_colors_json) = &theme.custom_colors {
+                if let Some(colors_array) = custom_colors_json.as_array() {
+                    palette.colors = colors_array
+                        .iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect();
+                }
+            }
+        }
+
+        palette
+    }
+
+    /// Cell color per level, starting with the theme's color for days
+    /// without contributions
+    pub fn cell_colors(theme: &heatmap_theme::Model) -> Vec<String> {
+        std::iter::once(theme.empty_cell_color.clone())
+            .chain(Self::for_theme(theme).colors.into_iter().skip(1))
+            .collect()
+    }
+
     pub fn get_color_for_count(&self, count: i32, max_count: i32) -> &str {
+        &self.colors[Self::level_for_count(count, max_count)]
+    }
+
+    /// Intensity level of a day, 0 for none up to 4 for the busiest days
+    pub fn level_for_count(count: i32, max_count: i32) -> usize {
         if count == 0 {
-            return &self.colors[0];
+            return 0;
         }
 
         if max_count == 0 {
-            return &self.colors[1];
+            return 1;
         }
 
         let ratio = count as f32 / max_count as f32;
-        let index = match ratio {
+        match ratio {
             r if r >= 0.75 => 4,
             r if r >= 0.50 => 3,
             r if r >= 0.25 => 2,
             _ => 1,
-        };
-
-        &self.colors[index]
+        }
     }
 }
 
 // Contribution data for a single day
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash)]
 pub struct DayContribution {
-    #[allow(dead_code)]
     pub date: NaiveDate,
     pub count: i32,
+    /// In one of the user's away periods; without contributions, the day
+    /// does not break a streak
+    pub away: bool,
 }
 
 // Heatmap data organized by weeks
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash)]
 pub struct HeatmapData {
     pub weeks: Vec<Vec<DayContribution>>,
     pub max_count: i32,
     pub total_count: i32,
     pub date_range_start: NaiveDate,
     pub date_range_end: NaiveDate,
+    /// Draw away days without contributions hatched
+    pub hatch_away_days: bool,
+    /// Achievements the user holds, for the stats card
+    pub achievements: Vec<Achievement>,
+    /// Lines changed in each week of `weeks`, for the code frequency row;
+    /// all zero without detailed sync
+    pub code_frequency: Vec<WeekLines>,
 }
 
+#[derive(Clone)]
 pub struct HeatmapGenerator {
     db: DatabaseConnection,
 }
@@ -144,11 +202,57 @@ impl HeatmapGenerator {
         Self { db }
     }
 
-    /// Generate heatmaps for a specific theme
+    /// Generate heatmaps for a specific theme. Rarely used formats, see
+    /// [`regeneration::split_rarely_used`], are only marked invalid, and the
+    /// embed routes render them when they are next requested.
     pub async fn generate_for_theme(
         &self,
         user_id: uuid::Uuid,
         theme: &heatmap_theme::Model,
+    ) -> Result<Vec<generated_heatmap::Model>> {
+        let existing = generated_heatmap::Entity::find()
+            .filter(generated_heatmap::Column::UserId.eq(user_id))
+            .filter(generated_heatmap::Column::ThemeId.eq(theme.id))
+            .all(&self.db)
+            .await?;
+        let (used, rarely_used) = regeneration::split_rarely_used(&theme.output_formats, &existing, Utc::now());
+
+        if !rarely_used.is_empty() {
+            generated_heatmap::Entity::update_many()
+                .col_expr(generated_heatmap::Column::IsValid, Expr::value(false))
+                .filter(generated_heatmap::Column::UserId.eq(user_id))
+                .filter(generated_heatmap::Column::ThemeId.eq(theme.id))
+                .filter(generated_heatmap::Column::Format.is_in(rarely_used.clone()))
+                .exec(&self.db)
+                .await?;
+
+            // The mini route renders a missing file, so a stale one goes
+            let output_dir = self.get_output_directory(user_id)?;
+            let mini_slug = format!("{}{}", theme.slug, MINI_SUFFIX);
+            for format in &rarely_used {
+                let _ = fs::remove_file(self.get_file_path(&output_dir, &mini_slug, format));
+            }
+
+            log::info!(
+                "Leaving {} rarely used format(s) of theme '{}' to render on request",
+                rarely_used.len(),
+                theme.slug
+            );
+        }
+
+        if used.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.generate_formats(user_id, theme, &used).await
+    }
+
+    /// Generate a theme's heatmaps in `formats`, with their mini files
+    #[tracing::instrument(skip_all, fields(user.id = %user_id, theme.slug = %theme.slug))]
+    pub async fn generate_formats(
+        &self,
+        user_id: uuid::Uuid,
+        theme: &heatmap_theme::Model,
+        formats: &[HeatmapFormat],
     ) -> Result<Vec<generated_heatmap::Model>> {
         log::info!(
             "Generating heatmaps for theme: {} (user: {})",
@@ -174,30 +278,45 @@ impl HeatmapGenerator {
         // Fetch contribution data
         let heatmap_data = self.fetch_contribution_data(user_id, &settings).await?;
 
-        // Generate SVG
-        let svg_content = self.generate_svg(theme, &heatmap_data, Some(&user_model.username))?;
-
         // Ensure output directory exists
         let output_dir = self.get_output_directory(user_id)?;
         fs::create_dir_all(&output_dir)?;
 
+        // Render the formats, and their mini files, in the bulk lane
+        let (heatmap_data, rendered) = render_lanes::run(Lane::Bulk, {
+            let generator = self.clone();
+            let theme = theme.clone();
+            let formats = formats.to_vec();
+            let output_dir = output_dir.clone();
+            move || {
+                let svg_content = generator.generate_svg(&theme, &heatmap_data, Some(&user_model.username))?;
+                let rendered = formats
+                    .iter()
+                    .map(|format| Ok((format.clone(), generator.encode(&svg_content, &theme, format)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                generator.write_mini_files(&output_dir, &theme, &heatmap_data, &formats)?;
+                Ok((heatmap_data, rendered))
+            }
+        })
+        .await?;
+
+        let previous = generated_heatmap::Entity::find()
+            .filter(generated_heatmap::Column::UserId.eq(user_id))
+            .filter(generated_heatmap::Column::ThemeId.eq(theme.id))
+            .all(&self.db)
+            .await?;
+
         let mut generated_files = Vec::new();
 
-        // Generate each requested format
-        for format in &theme.output_formats {
+        // Store each requested format
+        for (format, file_content) in &rendered {
             let file_path = self.get_file_path(&output_dir, &theme.slug, format);
-            let file_content = match format {
-                HeatmapFormat::Svg => svg_content.as_bytes().to_vec(),
-                HeatmapFormat::Png => self.svg_to_png(&svg_content, theme)?,
-                HeatmapFormat::Jpeg => self.svg_to_jpeg(&svg_content, theme)?,
-                HeatmapFormat::WebP => self.svg_to_webp(&svg_content, theme)?,
-            };
 
             // Write file
-            fs::write(&file_path, &file_content)?;
+            fs::write(&file_path, file_content)?;
 
             // Calculate file hash
-            let file_hash = self.calculate_hash(&file_content);
+            let file_hash = self.calculate_hash(file_content);
 
             // Create database record
             let generated = generated_heatmap::ActiveModel {
@@ -214,7 +333,7 @@ impl HeatmapGenerator {
                 date_range_start: Set(heatmap_data.date_range_start),
                 date_range_end: Set(heatmap_data.date_range_end),
                 access_count: Set(0),
-                last_accessed_at: Set(None),
+                last_accessed_at: Set(regeneration::last_accessed_at(&previous, format)),
                 is_valid: Set(true),
             };
 
@@ -256,8 +375,17 @@ impl HeatmapGenerator {
         user_id: uuid::Uuid,
         settings: &heatmap_generation_setting::Model,
     ) -> Result<HeatmapData> {
-        let end_date = Utc::now().date_naive();
+        self.fetch_contribution_data_until(user_id, settings, Utc::now().date_naive())
+            .await
+    }
 
+    /// Fetch the contribution data of the window ending on `end_date`
+    pub async fn fetch_contribution_data_until(
+        &self,
+        user_id: uuid::Uuid,
+        settings: &heatmap_generation_setting::Model,
+        end_date: NaiveDate,
+    ) -> Result<HeatmapData> {
         // Calculate the 365-day window start (or whatever date_range_days is set to)
         let window_start = end_date - Duration::days(settings.date_range_days as i64 - 1);
 
@@ -269,22 +397,14 @@ impl HeatmapGenerator {
             window_start
         };
 
-        // Get all platform accounts for user
-        let accounts = git_platform_account::Entity::find()
-            .filter(git_platform_account::Column::UserId.eq(user_id))
-            .filter(git_platform_account::Column::IsActive.eq(true))
-            .all(&self.db)
-            .await?;
-
-        let account_ids: Vec<uuid::Uuid> = accounts.iter().map(|a| a.id).collect();
-
-        // Fetch contributions from the Sunday start date
-        let contributions = contribution::Entity::find()
-            .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids))
-            .filter(contribution::Column::ContributionDate.gte(start_date))
-            .filter(contribution::Column::ContributionDate.lte(end_date))
-            .all(&self.db)
+        let user_settings = self.user_settings(user_id).await?;
+        let (_, contributions) = self
+            .contributions_between(user_id, user_settings.as_ref(), start_date, end_date)
             .await?;
+        let away_periods = user_settings
+            .as_ref()
+            .map(|s| s.away_periods.as_slice())
+            .unwrap_or_default();
 
         // Aggregate by date
         let mut contribution_map: std::collections::HashMap<NaiveDate, i32> =
@@ -326,6 +446,7 @@ impl HeatmapGenerator {
             current_week.push(DayContribution {
                 date: current_date,
                 count,
+                away: away_periods::contains(away_periods, current_date),
             });
 
             if current_week.len() == 7 {
@@ -342,6 +463,7 @@ impl HeatmapGenerator {
                 current_week.push(DayContribution {
                     date: NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
                     count: -1,
+                    away: false,
                 });
             }
             weeks.push(current_week);
@@ -353,7 +475,100 @@ impl HeatmapGenerator {
             total_count,
             date_range_start: start_date,
             date_range_end: end_date,
+            hatch_away_days: user_settings.is_some_and(|s| s.hatch_away_days),
+            achievements: achievements::list(&self.db, user_id)
+                .await?
+                .into_iter()
+                .map(|(achievement, _)| achievement)
+                .collect(),
+            code_frequency: commit_details::code_frequency(&self.db, user_id, start_date, end_date).await?,
+        })
+    }
+
+    /// Contributions of each of the user's platform accounts per day over
+    /// `data`'s date range, filtered as for the cells, so each day's counts
+    /// add up to its cell
+    pub async fn fetch_contribution_sources(
+        &self,
+        user_id: uuid::Uuid,
+        data: &HeatmapData,
+    ) -> Result<(Vec<git_platform_account::Model>, BTreeMap<NaiveDate, BTreeMap<uuid::Uuid, i32>>)> {
+        let user_settings = self.user_settings(user_id).await?;
+        let (accounts, contributions) = self
+            .contributions_between(user_id, user_settings.as_ref(), data.date_range_start, data.date_range_end)
+            .await?;
+
+        let mut days: BTreeMap<NaiveDate, BTreeMap<uuid::Uuid, i32>> = BTreeMap::new();
+        for contrib in contributions {
+            *days
+                .entry(contrib.contribution_date)
+                .or_default()
+                .entry(contrib.git_platform_account_id)
+                .or_insert(0) += contrib.count;
+        }
+
+        Ok((accounts, days))
+    }
+
+    /// The user's active platform accounts and their contributions from
+    /// `start_date` to `end_date`, without the repositories they hid from
+    /// their public profile
+    async fn contributions_between(
+        &self,
+        user_id: uuid::Uuid,
+        user_settings: Option<&user_setting::Model>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<(Vec<git_platform_account::Model>, Vec<contribution::Model>)> {
+        let accounts = git_platform_account::Entity::find()
+            .filter(git_platform_account::Column::UserId.eq(user_id))
+            .filter(git_platform_account::Column::IsActive.eq(true))
+            .all(&self.db)
+            .await?;
+
+        let account_ids: Vec<uuid::Uuid> = accounts.iter().map(|a| a.id).collect();
+        let excluded_repositories = user_settings
+            .map(|s| s.excluded_repositories.as_slice())
+            .unwrap_or_default();
+
+        let contributions = contribution::Entity::find()
+            .filter(contribution::Column::GitPlatformAccountId.is_in(account_ids))
+            .filter(contribution::Column::ContributionDate.gte(start_date))
+            .filter(contribution::Column::ContributionDate.lte(end_date))
+            .filter(repo_exclusion::not_excluded(
+                contribution::Column::RepositoryName,
+                excluded_repositories,
+            ))
+            .all(&self.db)
+            .await?;
+
+        Ok((accounts, contributions))
+    }
+
+    async fn user_settings(&self, user_id: uuid::Uuid) -> Result<Option<user_setting::Model>> {
+        Ok(user_setting::Entity::find()
+            .filter(user_setting::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?)
+    }
+
+    /// Run [`Self::generate_heatmap_with_username`] on a blocking thread once
+    /// `lane` has room
+    pub async fn render(
+        &self,
+        lane: Lane,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        format: &HeatmapFormat,
+        username: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let generator = self.clone();
+        let (theme, data, format) = (theme.clone(), data.clone(), format.clone());
+        let username = username.map(str::to_string);
+        render_lanes::run(lane, move || {
+            generator.generate_heatmap_with_username(&theme, &data, &format, username.as_deref())
         })
+        .await
     }
 
     /// Generate heatmap with username in the requested format (for embed URLs)
@@ -368,302 +583,61 @@ impl HeatmapGenerator {
         let svg_content = self.generate_svg(theme, data, username)?;
 
         // Convert to requested format
-        match format {
-            HeatmapFormat::Svg => Ok(svg_content.as_bytes().to_vec()),
-            HeatmapFormat::Png => self.svg_to_png(&svg_content, theme),
-            HeatmapFormat::Jpeg => self.svg_to_jpeg(&svg_content, theme),
-            HeatmapFormat::WebP => self.svg_to_webp(&svg_content, theme),
-        }
+        self.encode(&svg_content, theme, format)
     }
 
-    /// Generate SVG content (internal)
-    fn generate_svg(
+    /// Write the theme's `{slug}-mini.{ext}` files in `formats`, or remove
+    /// all of them when the theme no longer has a mini heatmap. They are not
+    /// recorded; they go with the main file of the same format.
+    fn write_mini_files(
         &self,
+        output_dir: &Path,
         theme: &heatmap_theme::Model,
         data: &HeatmapData,
-        username: Option<&str>,
-    ) -> Result<String> {
-        let cell_size = theme.cell_size as usize;
-        let cell_gap = theme.cell_gap as usize;
-        let day_label_width = theme.day_label_width as usize;
-        let month_label_height = theme.month_label_height as usize;
-        let title_height = theme.title_height as usize;
-        let legend_height = theme.legend_height as usize;
-        let padding_right = theme.padding_right as usize;
-        let padding_bottom = theme.padding_bottom as usize;
-
-        // Calculate dimensions
-        let num_weeks = data.weeks.len();
-        let num_days = 7;
-
-        let graph_width = num_weeks * (cell_size + cell_gap) - cell_gap;
-        let graph_height = num_days * (cell_size + cell_gap) - cell_gap;
-
-        // Total dimensions including all UI elements
-        let total_width = day_label_width + graph_width + padding_right;
-        let total_height =
-            title_height + month_label_height + graph_height + legend_height + padding_bottom;
-
-        // Get color palette
-        let mut palette = ColorPalette::from_scheme(&theme.color_scheme);
+        formats: &[HeatmapFormat],
+    ) -> Result<()> {
+        let mini_slug = format!("{}{}", theme.slug, MINI_SUFFIX);
 
-        // Override with custom colors ONLY if color_scheme is Custom
-        if matches!(
-            theme.color_scheme,
-            heatmap_theme::HeatmapColorScheme::Custom
-        ) {
-            if let Some(custom_colors_json) = &theme.custom_colors {
-                if let Some(colors_array) = custom_colors_json.as_array() {
-                    palette.colors = colors_array
-                        .iter()
-                        .filter_map(|v| v.as_str().map(String::from))
-                        .collect();
-                    log::info!("Using custom colors from JSON: {:?}", palette.colors);
-                }
+        if theme.mini_days.is_none() {
+            for format in &theme.output_formats {
+                let _ = fs::remove_file(self.get_file_path(output_dir, &mini_slug, format));
             }
-        } else {
-            log::info!(
-                "Using default palette for scheme {:?}: {:?}",
-                theme.color_scheme,
-                palette.colors
-            );
+            return Ok(());
         }
 
-        let mut svg = String::new();
-
-        // Use custom dimensions if provided, otherwise use calculated dimensions
-        let (final_width, final_height) = match (theme.heatmap_width, theme.heatmap_height) {
-            (Some(w), Some(h)) => (w, h),
-            (Some(w), None) => {
-                // Width specified, calculate height to maintain aspect ratio
-                let aspect_ratio = total_height as f64 / total_width as f64;
-                (w, (w as f64 * aspect_ratio) as i32)
-            }
-            (None, Some(h)) => {
-                // Height specified, calculate width to maintain aspect ratio
-                let aspect_ratio = total_width as f64 / total_height as f64;
-                ((h as f64 * aspect_ratio) as i32, h)
-            }
-            (None, None) => (total_width as i32, total_height as i32),
-        };
-
-        // Always use viewBox for proper scaling in browsers
-        // If custom dimensions are specified, use preserveAspectRatio="none" to stretch content
-        if theme.heatmap_width.is_some() || theme.heatmap_height.is_some() {
-            svg.push_str(&format!(
-                r#"<svg width="{}" height="{}" viewBox="0 0 {} {}" preserveAspectRatio="none" xmlns="http://www.w3.org/2000/svg">"#,
-                final_width, final_height, total_width, total_height
-            ));
-        } else {
-            // Include viewBox even for default dimensions to enable CSS scaling
-            svg.push_str(&format!(
-                r#"<svg width="{}" height="{}" viewBox="0 0 {} {}" xmlns="http://www.w3.org/2000/svg">"#,
-                total_width, total_height, total_width, total_height
-            ));
+        let svg_content = heatmap_renderers::MiniRenderer.render(theme, data, None)?;
+        for format in formats {
+            let file_path = self.get_file_path(output_dir, &mini_slug, format);
+            fs::write(&file_path, self.encode(&svg_content, theme, format)?)?;
+            log::info!("Generated mini heatmap: {}", file_path.display());
         }
 
-        // Background
-        svg.push_str(&format!(
-            r#"<rect width="100%" height="100%" fill="{}"/>"#,
-            theme.background_color
-        ));
-
-        // Username at top right if enabled (same level as contribution count)
-        if theme.show_username {
-            if let Some(user) = username {
-                svg.push_str(&format!(
-                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="end">@{}</text>"#,
-                    total_width - 5, // Position at right with small margin
-                    20, // Same level as contribution count
-                    theme.font_family,
-                    theme.font_size + 2, // Same size as contribution count
-                    theme.text_color,
-                    user
-                ));
-            }
-        }
-
-        // Title with contribution count
-        if theme.show_total_count {
-            let title_text = format!("{} contributions in the last year", data.total_count);
-            // No need to push down if username is on the right
-            svg.push_str(&format!(
-                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
-                day_label_width,
-                20, // Default position
-                theme.font_family,
-                theme.font_size + 2, // Slightly larger for title
-                theme.text_color,
-                title_text
-            ));
-        }
-
-        // Month labels
-        if theme.show_month_labels {
-            let month_names = [
-                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
-            ];
-            let mut last_month = -1;
-            let mut month_positions: Vec<(usize, i32)> = Vec::new(); // (week_idx, month)
-
-            // First pass: collect all month positions
-            for (week_idx, week) in data.weeks.iter().enumerate() {
-                // Find the first valid day in the week to determine the month
-                if let Some(first_day) = week.iter().find(|d| d.count != -1) {
-                    let month = first_day.date.month0() as i32; // 0-based month
-
-                    if month != last_month {
-                        month_positions.push((week_idx, month));
-                        last_month = month;
-                    }
-                }
-            }
-
-            // Second pass: render month labels, skipping first if too close to second
-            for (idx, (week_idx, month)) in month_positions.iter().enumerate() {
-                // Skip first month label if there's a second month within 3 weeks
-                if idx == 0 && month_positions.len() > 1 {
-                    let second_week_idx = month_positions[1].0;
-                    if second_week_idx < 3 {
-                        continue; // Skip first month label
-                    }
-                }
-
-                let x = day_label_width + week_idx * (cell_size + cell_gap);
-                let y = title_height + month_label_height - 3;
-
-                svg.push_str(&format!(
-                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
-                    x,
-                    y,
-                    theme.font_family,
-                    theme.font_size,
-                    theme.text_color,
-                    month_names[*month as usize]
-                ));
-            }
-        }
-
-        // Day labels (Mon, Wed, Fri)
-        if theme.show_day_labels {
-            let day_labels = vec![(1, "Mon"), (3, "Wed"), (5, "Fri")];
-
-            for (day_idx, label) in day_labels {
-                let y = title_height
-                    + month_label_height
-                    + day_idx * (cell_size + cell_gap)
-                    + cell_size / 2
-                    + 3;
-                svg.push_str(&format!(
-                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="start">{}</text>"#,
-                    5, // Left margin
-                    y,
-                    theme.font_family,
-                    theme.font_size,
-                    theme.text_color,
-                    label
-                ));
-            }
-        }
-
-        // Draw cells
-        for (week_idx, week) in data.weeks.iter().enumerate() {
-            for (day_idx, day) in week.iter().enumerate() {
-                if day.count == -1 {
-                    continue; // Skip placeholder cells
-                }
-
-                let x = day_label_width + week_idx * (cell_size + cell_gap);
-                let y = title_height + month_label_height + day_idx * (cell_size + cell_gap);
-
-                let color = if day.count == 0 {
-                    &theme.empty_cell_color
-                } else {
-                    palette.get_color_for_count(day.count, data.max_count)
-                };
-
-                svg.push_str(&format!(
-                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" "#,
-                    x, y, cell_size, cell_size, theme.cell_border_radius, color
-                ));
-
-                if theme.cell_border_width > 0 {
-                    svg.push_str(&format!(
-                        r#"stroke="{}" stroke-width="{}" "#,
-                        theme.cell_border_color, theme.cell_border_width
-                    ));
-                }
-
-                svg.push_str("/>");
-            }
-        }
-
-        // Legend at bottom right
-        if theme.show_legend {
-            let legend_y = title_height + month_label_height + graph_height + 8;
-            let legend_start_x =
-                total_width - (40 + palette.colors.len() * (cell_size + 3) + 40 + padding_right);
-
-            // "Less" label
-            svg.push_str(&format!(
-                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="end">{}</text>"#,
-                legend_start_x + 30,
-                legend_y + cell_size / 2 + 3,
-                theme.font_family,
-                theme.font_size,
-                theme.text_color,
-                "Less"
-            ));
-
-            // Color squares
-            for (i, color) in palette.colors.iter().enumerate() {
-                let x = legend_start_x + 35 + i * (cell_size + 3);
-                svg.push_str(&format!(
-                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}"/>"#,
-                    x, legend_y, cell_size, cell_size, theme.cell_border_radius, color
-                ));
-            }
-
-            // "More" label
-            svg.push_str(&format!(
-                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="start">{}</text>"#,
-                legend_start_x + 40 + palette.colors.len() * (cell_size + 3),
-                legend_y + cell_size / 2 + 3,
-                theme.font_family,
-                theme.font_size,
-                theme.text_color,
-                "More"
-            ));
-        }
+        Ok(())
+    }
 
-        // Watermark at bottom left (clickable link in SVG)
-        if theme.show_watermark {
-            let watermark_y = title_height + month_label_height + graph_height + 8;
-            let watermark_text = "Powered by Hgitmap";
-            let watermark_url = "https://github.com/Doublefire-Chen/hgitmap";
-
-            // Wrap in clickable link for SVG format
-            svg.push_str(&format!(
-                r#"<a href="{}" target="_blank" rel="noopener">"#,
-                watermark_url
-            ));
-
-            svg.push_str(&format!(
-                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" opacity="0.6" text-anchor="start">{}</text>"#,
-                day_label_width,
-                watermark_y + cell_size / 2 + 3,
-                theme.font_family,
-                theme.font_size - 1, // Slightly smaller than regular font
-                theme.text_color,
-                watermark_text
-            ));
-
-            svg.push_str("</a>");
+    /// The SVG as a file of `format`
+    fn encode(
+        &self,
+        svg_content: &str,
+        theme: &heatmap_theme::Model,
+        format: &HeatmapFormat,
+    ) -> Result<Vec<u8>> {
+        match format {
+            HeatmapFormat::Svg => Ok(svg_content.as_bytes().to_vec()),
+            HeatmapFormat::Png => self.svg_to_png(svg_content, theme),
+            HeatmapFormat::Jpeg => self.svg_to_jpeg(svg_content, theme),
+            HeatmapFormat::WebP => self.svg_to_webp(svg_content, theme),
         }
+    }
 
-        svg.push_str("</svg>");
-
-        Ok(svg)
+    /// Generate SVG content with the renderer of the theme's layout
+    fn generate_svg(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        heatmap_renderers::for_theme(theme).render(theme, data, username)
     }
 
     /// Convert SVG to PNG
@@ -799,9 +773,7 @@ impl HeatmapGenerator {
     /// Get output directory for user's heatmaps
     fn get_output_directory(&self, user_id: uuid::Uuid) -> Result<PathBuf> {
         // Use default path: static/heatmaps/{user_id}
-        let base_dir = "static/heatmaps";
-
-        let mut path = PathBuf::from(base_dir);
+        let mut path = PathBuf::from(HEATMAP_BASE_DIR);
         path.push(user_id.to_string());
 
         // Create directory if it doesn't exist
@@ -816,18 +788,11 @@ impl HeatmapGenerator {
     /// Get file path for a specific format
     fn get_file_path(
         &self,
-        output_dir: &PathBuf,
+        output_dir: &Path,
         theme_slug: &str,
         format: &HeatmapFormat,
     ) -> PathBuf {
-        let extension = match format {
-            HeatmapFormat::Svg => "svg",
-            HeatmapFormat::Png => "png",
-            HeatmapFormat::Jpeg => "jpg",
-            HeatmapFormat::WebP => "webp",
-        };
-
-        output_dir.join(format!("{}.{}", theme_slug, extension))
+        output_dir.join(file_name(theme_slug, format))
     }
 
     /// Calculate SHA-256 hash of file content
@@ -838,6 +803,29 @@ impl HeatmapGenerator {
     }
 }
 
+/// Name of the file a theme's heatmap of `format` is written to
+pub fn file_name(theme_slug: &str, format: &HeatmapFormat) -> String {
+    let extension = match format {
+        HeatmapFormat::Svg => "svg",
+        HeatmapFormat::Png => "png",
+        HeatmapFormat::Jpeg => "jpg",
+        HeatmapFormat::WebP => "webp",
+    };
+
+    format!("{}.{}", theme_slug, extension)
+}
+
+/// The mini heatmap written next to a main heatmap file
+pub fn mini_file_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut name = format!("{}{}", stem, MINI_SUFFIX);
+    if let Some(extension) = path.extension() {
+        name.push('.');
+        name.push_str(&extension.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
 fn format_to_string(format: &HeatmapFormat) -> &str {
     match format {
         HeatmapFormat::Svg => "SVG",
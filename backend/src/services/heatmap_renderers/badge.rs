@@ -0,0 +1,83 @@
+use anyhow::Result;
+
+use super::{svg_start, text_width, HeatmapRenderer};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, HeatmapData};
+
+const HEIGHT: usize = 20;
+
+/// Space left and right of each half's text
+const TEXT_PADDING: usize = 6;
+
+/// A shields.io-style badge with the contribution count, small enough for a
+/// README's badge row. Labels, legend and watermark are left out.
+pub struct BadgeRenderer;
+
+impl HeatmapRenderer for BadgeRenderer {
+    fn layout(&self) -> &'static str {
+        "badge"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        let font_size = theme.font_size + 1;
+        let (label, message) = match username {
+            Some(user) if theme.show_username => {
+                (format!("@{}", user), format!("{} contributions", data.total_count))
+            }
+            _ => ("contributions".to_string(), data.total_count.to_string()),
+        };
+
+        let label_width = text_width(&label, font_size) + 2 * TEXT_PADDING;
+        let message_width = text_width(&message, font_size) + 2 * TEXT_PADDING;
+        let width = label_width + message_width;
+
+        // The label takes the theme's text color and the message its
+        // strongest contribution color, so the badge inverts with the theme
+        let palette = ColorPalette::for_theme(theme);
+        let accent = palette.colors.last().cloned().unwrap_or_else(|| theme.text_color.clone());
+
+        let mut svg = svg_start(theme, width, HEIGHT);
+        svg.push_str(&format!(
+            r#"<clipPath id="badge"><rect width="{}" height="{}" rx="3"/></clipPath>"#,
+            width, HEIGHT
+        ));
+        svg.push_str(r#"<g clip-path="url(#badge)">"#);
+        svg.push_str(&format!(
+            r#"<rect width="{}" height="{}" fill="{}"/>"#,
+            label_width, HEIGHT, theme.text_color
+        ));
+        svg.push_str(&format!(
+            r#"<rect x="{}" width="{}" height="{}" fill="{}"/>"#,
+            label_width, message_width, HEIGHT, accent
+        ));
+        svg.push_str("</g>");
+
+        let text_y = HEIGHT / 2 + font_size as usize / 2 - 1;
+        svg.push_str(&format!(
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="middle">{}</text>"#,
+            label_width / 2,
+            text_y,
+            theme.font_family,
+            font_size,
+            theme.background_color,
+            label
+        ));
+        svg.push_str(&format!(
+            r##"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="#ffffff" text-anchor="middle">{}</text>"##,
+            label_width + message_width / 2,
+            text_y,
+            theme.font_family,
+            font_size,
+            message
+        ));
+
+        svg.push_str("</svg>");
+
+        Ok(svg)
+    }
+}
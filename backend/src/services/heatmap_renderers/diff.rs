@@ -0,0 +1,185 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+use super::{background, cell_border, month_labels, svg_start, watermark};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::HeatmapData;
+
+/// How far back the compared days are: 52 weeks, so both fall on the same
+/// weekday
+pub const COMPARED_DAYS: i64 = 364;
+
+/// Colors of days with fewer contributions than the year before, from a
+/// small to a large drop
+const FEWER: [&str; 3] = ["#fdd49e", "#fc8d59", "#d73027"];
+
+/// Colors of days with more contributions, from a small to a large rise
+const MORE: [&str; 3] = ["#d9f0a3", "#78c679", "#238443"];
+
+/// Difference of each day of `current` to the day 52 weeks before, from
+/// `previous`, which covers the window ending 52 weeks before `current`'s
+pub fn differences(current: &HeatmapData, previous: &HeatmapData) -> HashMap<NaiveDate, i32> {
+    let before: HashMap<NaiveDate, i32> = previous
+        .weeks
+        .iter()
+        .flatten()
+        .filter(|day| day.count >= 0)
+        .map(|day| (day.date, day.count))
+        .collect();
+
+    current
+        .weeks
+        .iter()
+        .flatten()
+        .filter(|day| day.count >= 0)
+        .map(|day| {
+            let last_year = day.date - Duration::days(COMPARED_DAYS);
+            (day.date, day.count - before.get(&last_year).copied().unwrap_or(0))
+        })
+        .collect()
+}
+
+/// Color of a day that changed by `diff`, the theme's empty cell color for
+/// no change, otherwise three levels each way relative to the largest change
+fn color_for_diff(theme: &heatmap_theme::Model, diff: i32, max_change: i32) -> &str {
+    if diff == 0 || max_change == 0 {
+        return &theme.empty_cell_color;
+    }
+    let ratio = diff.abs() as f32 / max_change as f32;
+    let level = match ratio {
+        r if r >= 0.66 => 2,
+        r if r >= 0.33 => 1,
+        _ => 0,
+    };
+    if diff > 0 {
+        MORE[level]
+    } else {
+        FEWER[level]
+    }
+}
+
+/// The grid of `current` with each day colored by its difference to the
+/// same weekday a year before: green for more contributions, red for fewer
+pub fn render(
+    theme: &heatmap_theme::Model,
+    current: &HeatmapData,
+    previous: &HeatmapData,
+    username: Option<&str>,
+) -> String {
+    let cell_size = theme.cell_size as usize;
+    let cell_gap = theme.cell_gap as usize;
+    let day_label_width = theme.day_label_width as usize;
+    let month_label_height = theme.month_label_height as usize;
+    let title_height = theme.title_height as usize;
+
+    let graph_width = current.weeks.len() * (cell_size + cell_gap) - cell_gap;
+    let graph_height = 7 * (cell_size + cell_gap) - cell_gap;
+    let total_width = day_label_width + graph_width + theme.padding_right as usize;
+    let total_height = title_height
+        + month_label_height
+        + graph_height
+        + theme.legend_height as usize
+        + theme.padding_bottom as usize;
+
+    let diffs = differences(current, previous);
+    let max_change = diffs.values().map(|diff| diff.abs()).max().unwrap_or(0);
+    let change = current.total_count - previous.total_count;
+
+    let text = |x: usize, y: usize, size: i32, anchor: &str, content: &str| {
+        format!(
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="{}">{}</text>"#,
+            x, y, theme.font_family, size, theme.text_color, anchor, content
+        )
+    };
+
+    let mut svg = svg_start(theme, total_width, total_height);
+    svg.push_str(&background(theme));
+
+    if theme.show_total_count {
+        svg.push_str(&text(
+            day_label_width,
+            20,
+            theme.font_size + 2,
+            "start",
+            &format!("{:+} contributions compared to the year before", change),
+        ));
+    }
+    if let Some(user) = username.filter(|_| theme.show_username) {
+        svg.push_str(&text(total_width - 5, 20, theme.font_size + 2, "end", &format!("@{}", user)));
+    }
+
+    if theme.show_month_labels {
+        for (week_idx, month) in month_labels(current) {
+            svg.push_str(&text(
+                day_label_width + week_idx * (cell_size + cell_gap),
+                title_height + month_label_height - 3,
+                theme.font_size,
+                "start",
+                month,
+            ));
+        }
+    }
+
+    if theme.show_day_labels {
+        for (day_idx, label) in [(1, "Mon"), (3, "Wed"), (5, "Fri")] {
+            let y = title_height + month_label_height + day_idx * (cell_size + cell_gap) + cell_size / 2 + 3;
+            svg.push_str(&text(5, y, theme.font_size, "start", label));
+        }
+    }
+
+    let border = cell_border(theme);
+    for (week_idx, week) in current.weeks.iter().enumerate() {
+        for (day_idx, day) in week.iter().enumerate() {
+            let Some(diff) = diffs.get(&day.date).filter(|_| day.count >= 0) else {
+                continue;
+            };
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" {}><title>{}: {:+}</title></rect>"#,
+                day_label_width + week_idx * (cell_size + cell_gap),
+                title_height + month_label_height + day_idx * (cell_size + cell_gap),
+                cell_size,
+                cell_size,
+                theme.cell_border_radius,
+                color_for_diff(theme, *diff, max_change),
+                border,
+                day.date,
+                diff
+            ));
+        }
+    }
+
+    // "Fewer", the drops from large to small, no change, the rises from
+    // small to large and "More"
+    let footer_y = title_height + month_label_height + graph_height + 8;
+    if theme.show_legend {
+        let colors: Vec<&str> = FEWER
+            .iter()
+            .rev()
+            .copied()
+            .chain([theme.empty_cell_color.as_str()])
+            .chain(MORE)
+            .collect();
+        let start_x = total_width - (45 + colors.len() * (cell_size + 3) + 40 + theme.padding_right as usize);
+        let label_y = footer_y + cell_size / 2 + 3;
+        svg.push_str(&text(start_x + 40, label_y, theme.font_size, "end", "Fewer"));
+        for (i, color) in colors.iter().enumerate() {
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}"/>"#,
+                start_x + 45 + i * (cell_size + 3),
+                footer_y,
+                cell_size,
+                cell_size,
+                theme.cell_border_radius,
+                color
+            ));
+        }
+        svg.push_str(&text(start_x + 50 + colors.len() * (cell_size + 3), label_y, theme.font_size, "start", "More"));
+    }
+    if theme.show_watermark {
+        svg.push_str(&watermark(theme, day_label_width, footer_y + cell_size / 2 + 3));
+    }
+
+    svg.push_str("</svg>");
+
+    svg
+}
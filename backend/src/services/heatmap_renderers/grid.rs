@@ -0,0 +1,151 @@
+use anyhow::Result;
+
+use super::{
+    away_pattern, background, cell_border, code_frequency_row, empty_cell_fill, header, legend,
+    month_labels, shows_code_frequency, svg_start, watermark, HeatmapRenderer, CODE_FREQUENCY_HEIGHT,
+};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, HeatmapData};
+
+/// The contribution calendar: a cell per day, a column per week
+pub struct GridRenderer;
+
+impl HeatmapRenderer for GridRenderer {
+    fn layout(&self) -> &'static str {
+        "grid"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        let cell_size = theme.cell_size as usize;
+        let cell_gap = theme.cell_gap as usize;
+        let day_label_width = theme.day_label_width as usize;
+        let month_label_height = theme.month_label_height as usize;
+        let title_height = theme.title_height as usize;
+        let legend_height = theme.legend_height as usize;
+        let padding_right = theme.padding_right as usize;
+        let padding_bottom = theme.padding_bottom as usize;
+
+        // Calculate dimensions
+        let num_weeks = data.weeks.len();
+        let num_days = 7;
+
+        let graph_width = num_weeks * (cell_size + cell_gap) - cell_gap;
+        let graph_height = num_days * (cell_size + cell_gap) - cell_gap;
+
+        // Lines changed per week, under the cells
+        let code_frequency_height = if shows_code_frequency(theme, data) {
+            cell_gap + CODE_FREQUENCY_HEIGHT
+        } else {
+            0
+        };
+
+        // Total dimensions including all UI elements
+        let total_width = day_label_width + graph_width + padding_right;
+        let total_height = title_height
+            + month_label_height
+            + graph_height
+            + code_frequency_height
+            + legend_height
+            + padding_bottom;
+
+        // Get color palette
+        let palette = ColorPalette::for_theme(theme);
+        log::info!("Using palette for scheme {:?}: {:?}", theme.color_scheme, palette.colors);
+
+        let mut svg = svg_start(theme, total_width, total_height);
+        svg.push_str(&background(theme));
+        svg.push_str(&away_pattern(theme, data));
+
+        // Contribution count at top left, username at top right
+        svg.push_str(&header(theme, data, username, day_label_width, total_width));
+
+        // Month labels
+        if theme.show_month_labels {
+            for (week_idx, month) in month_labels(data) {
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
+                    day_label_width + week_idx * (cell_size + cell_gap),
+                    title_height + month_label_height - 3,
+                    theme.font_family,
+                    theme.font_size,
+                    theme.text_color,
+                    month
+                ));
+            }
+        }
+
+        // Day labels (Mon, Wed, Fri)
+        if theme.show_day_labels {
+            let day_labels = vec![(1, "Mon"), (3, "Wed"), (5, "Fri")];
+
+            for (day_idx, label) in day_labels {
+                let y = title_height
+                    + month_label_height
+                    + day_idx * (cell_size + cell_gap)
+                    + cell_size / 2
+                    + 3;
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="start">{}</text>"#,
+                    5, // Left margin
+                    y,
+                    theme.font_family,
+                    theme.font_size,
+                    theme.text_color,
+                    label
+                ));
+            }
+        }
+
+        // Draw cells
+        let border = cell_border(theme);
+        for (week_idx, week) in data.weeks.iter().enumerate() {
+            for (day_idx, day) in week.iter().enumerate() {
+                if day.count == -1 {
+                    continue; // Skip placeholder cells
+                }
+
+                let x = day_label_width + week_idx * (cell_size + cell_gap);
+                let y = title_height + month_label_height + day_idx * (cell_size + cell_gap);
+
+                let color = if day.count == 0 {
+                    empty_cell_fill(theme, data, day.away)
+                } else {
+                    palette.get_color_for_count(day.count, data.max_count)
+                };
+
+                svg.push_str(&format!(
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" {}/>"#,
+                    x, y, cell_size, cell_size, theme.cell_border_radius, color, border
+                ));
+            }
+        }
+
+        if code_frequency_height > 0 {
+            svg.push_str(&code_frequency_row(
+                theme,
+                &palette,
+                data,
+                day_label_width,
+                title_height + month_label_height + graph_height + cell_gap,
+            ));
+        }
+
+        // Legend at bottom right, watermark at bottom left
+        let footer_y = title_height + month_label_height + graph_height + code_frequency_height + 8;
+        if theme.show_legend {
+            svg.push_str(&legend(theme, &palette, total_width, footer_y));
+        }
+        if theme.show_watermark {
+            svg.push_str(&watermark(theme, day_label_width, footer_y + cell_size / 2 + 3));
+        }
+
+        svg.push_str("</svg>");
+
+        Ok(svg)
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use chrono::Datelike;
+
+use super::{background, cell_border, svg_start, text_width, HeatmapRenderer};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, HeatmapData};
+
+/// Days a theme's mini heatmap can cover
+pub const MINI_DAYS: [i32; 2] = [30, 90];
+
+/// Days drawn by the mini layout when the theme sets none
+const DEFAULT_DAYS: i32 = 30;
+
+/// Largest cell, so a big grid theme still gets a small mini heatmap
+const MAX_CELL_SIZE: i32 = 8;
+
+const CELL_GAP: usize = 1;
+
+/// Space around the cells and the totals line
+const PADDING: usize = 2;
+
+/// The last 30 or 90 days in tight cells without labels, legend or
+/// watermark, for forum signatures and email footers. The contribution count
+/// goes on a single line below when the theme shows it.
+pub struct MiniRenderer;
+
+impl HeatmapRenderer for MiniRenderer {
+    fn layout(&self) -> &'static str {
+        "mini"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        _username: Option<&str>,
+    ) -> Result<String> {
+        let days_shown = theme.mini_days.unwrap_or(DEFAULT_DAYS).max(1) as usize;
+        let cell_size = theme.cell_size.clamp(1, MAX_CELL_SIZE) as usize;
+
+        // The last days up to today, without the padding after it
+        let days: Vec<_> = data.weeks.iter().flatten().filter(|day| day.count >= 0).collect();
+        let days = &days[days.len().saturating_sub(days_shown)..];
+        let max_count = days.iter().map(|day| day.count).max().unwrap_or(0);
+        let total: i32 = days.iter().map(|day| day.count).sum();
+
+        // Columns are weeks from Sunday, as in the grid
+        let offset = days
+            .first()
+            .map_or(0, |day| day.date.weekday().num_days_from_sunday() as usize);
+        let num_weeks = (offset + days.len()).div_ceil(7).max(1);
+        let graph_width = num_weeks * (cell_size + CELL_GAP) - CELL_GAP;
+        let graph_height = 7 * (cell_size + CELL_GAP) - CELL_GAP;
+
+        let font_size = (theme.font_size - 2).max(7);
+        let totals = theme
+            .show_total_count
+            .then(|| format!("{} in {} days", total, days.len()));
+        let totals_height = if totals.is_some() { font_size as usize + 2 } else { 0 };
+
+        let width = 2 * PADDING
+            + graph_width.max(totals.as_deref().map_or(0, |line| text_width(line, font_size)));
+        let height = 2 * PADDING + graph_height + totals_height;
+
+        let palette = ColorPalette::for_theme(theme);
+
+        let mut svg = svg_start(theme, width, height);
+        svg.push_str(&background(theme));
+
+        let border = cell_border(theme);
+        for (i, day) in days.iter().enumerate() {
+            let x = PADDING + (offset + i) / 7 * (cell_size + CELL_GAP);
+            let y = PADDING + (offset + i) % 7 * (cell_size + CELL_GAP);
+            let color = if day.count == 0 {
+                &theme.empty_cell_color
+            } else {
+                palette.get_color_for_count(day.count, max_count)
+            };
+
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" {}/>"#,
+                x,
+                y,
+                cell_size,
+                cell_size,
+                theme.cell_border_radius.min(cell_size as i32 / 2),
+                color,
+                border
+            ));
+        }
+
+        if let Some(line) = totals {
+            svg.push_str(&format!(
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
+                PADDING,
+                PADDING + graph_height + totals_height,
+                theme.font_family,
+                font_size,
+                theme.text_color,
+                line
+            ));
+        }
+
+        svg.push_str("</svg>");
+
+        Ok(svg)
+    }
+}
@@ -0,0 +1,332 @@
+//! Renderers draw a user's contribution data as an SVG. A theme picks one by
+//! its `layout`; to add a visualization, implement [`HeatmapRenderer`] in a
+//! module here and list it in [`RENDERERS`]. A theme with a custom template
+//! is drawn by [`TemplateRenderer`] instead. [`diff`] compares two years and
+//! is drawn on its own.
+
+pub mod badge;
+pub mod diff;
+pub mod grid;
+pub mod mini;
+pub mod month_grid;
+pub mod skyline;
+pub mod stats_card;
+pub mod template;
+
+pub use badge::BadgeRenderer;
+pub use grid::GridRenderer;
+pub use mini::MiniRenderer;
+pub use month_grid::MonthGridRenderer;
+pub use skyline::SkylineRenderer;
+pub use stats_card::StatsCardRenderer;
+pub use template::TemplateRenderer;
+
+use anyhow::Result;
+use chrono::Datelike;
+
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, HeatmapData};
+use crate::utils::code_frequency::WeekLines;
+
+/// Layout of themes that do not pick one
+pub const DEFAULT_LAYOUT: &str = "grid";
+
+/// Link of the watermark
+const WATERMARK_URL: &str = "https://github.com/Doublefire-Chen/hgitmap";
+
+/// Height of the code frequency row under a grid, half for lines added and
+/// half for lines removed
+const CODE_FREQUENCY_HEIGHT: usize = 24;
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+pub trait HeatmapRenderer: Send + Sync {
+    /// Value of `heatmap_themes.layout` that selects this renderer
+    fn layout(&self) -> &'static str;
+
+    /// Draw `data` as an SVG document styled by `theme`. `username` is shown
+    /// when the theme asks for it.
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String>;
+}
+
+/// Every renderer a theme can pick
+pub const RENDERERS: &[&dyn HeatmapRenderer] = &[
+    &GridRenderer,
+    &SkylineRenderer,
+    &MonthGridRenderer,
+    &StatsCardRenderer,
+    &BadgeRenderer,
+    &MiniRenderer,
+];
+
+/// The renderer of a layout, None if there is none by that name
+pub fn find(layout: &str) -> Option<&'static dyn HeatmapRenderer> {
+    RENDERERS.iter().copied().find(|renderer| renderer.layout() == layout)
+}
+
+/// The theme's custom template if it has one, otherwise the renderer of its
+/// layout, or the grid if the layout is unknown, as it is after a renderer
+/// was removed
+pub fn for_theme(theme: &heatmap_theme::Model) -> &'static dyn HeatmapRenderer {
+    if theme.custom_template.is_some() {
+        return &TemplateRenderer;
+    }
+    find(&theme.layout).unwrap_or_else(|| {
+        log::warn!(
+            "Theme '{}' has unknown layout '{}'; drawing a grid",
+            theme.slug,
+            theme.layout
+        );
+        &GridRenderer
+    })
+}
+
+/// Names of all layouts, for validating themes and listing the choices
+pub fn layouts() -> Vec<&'static str> {
+    RENDERERS.iter().map(|renderer| renderer.layout()).collect()
+}
+
+/// Opening `<svg>` tag of a drawing `width` by `height` units large. A theme
+/// with a fixed width or height scales the drawing to it; with only one of
+/// them the other keeps the aspect ratio.
+fn svg_start(theme: &heatmap_theme::Model, width: usize, height: usize) -> String {
+    let (final_width, final_height) = match (theme.heatmap_width, theme.heatmap_height) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => {
+            let aspect_ratio = height as f64 / width as f64;
+            (w, (w as f64 * aspect_ratio) as i32)
+        }
+        (None, Some(h)) => {
+            let aspect_ratio = width as f64 / height as f64;
+            ((h as f64 * aspect_ratio) as i32, h)
+        }
+        (None, None) => (width as i32, height as i32),
+    };
+
+    // Always use viewBox for proper scaling in browsers; custom dimensions
+    // stretch the content with preserveAspectRatio="none"
+    if theme.heatmap_width.is_some() || theme.heatmap_height.is_some() {
+        format!(
+            r#"<svg width="{}" height="{}" viewBox="0 0 {} {}" preserveAspectRatio="none" xmlns="http://www.w3.org/2000/svg">"#,
+            final_width, final_height, width, height
+        )
+    } else {
+        format!(
+            r#"<svg width="{}" height="{}" viewBox="0 0 {} {}" xmlns="http://www.w3.org/2000/svg">"#,
+            width, height, width, height
+        )
+    }
+}
+
+fn background(theme: &heatmap_theme::Model) -> String {
+    format!(
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        theme.background_color
+    )
+}
+
+/// The contribution count on the left from `x` and the username on the
+/// right up to `width`, each if the theme shows it
+fn header(
+    theme: &heatmap_theme::Model,
+    data: &HeatmapData,
+    username: Option<&str>,
+    x: usize,
+    width: usize,
+) -> String {
+    let mut svg = String::new();
+
+    if theme.show_username {
+        if let Some(user) = username {
+            svg.push_str(&format!(
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="end">@{}</text>"#,
+                width - 5, // Position at right with small margin
+                20, // Same level as contribution count
+                theme.font_family,
+                theme.font_size + 2, // Same size as contribution count
+                theme.text_color,
+                user
+            ));
+        }
+    }
+
+    if theme.show_total_count {
+        svg.push_str(&format!(
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{} contributions in the last year</text>"#,
+            x,
+            20,
+            theme.font_family,
+            theme.font_size + 2, // Slightly larger for title
+            theme.text_color,
+            data.total_count
+        ));
+    }
+
+    svg
+}
+
+/// Weeks that start a month and the month's name, without the first month
+/// when the second starts within three weeks and the labels would overlap
+fn month_labels(data: &HeatmapData) -> Vec<(usize, &'static str)> {
+    let mut positions: Vec<(usize, usize)> = Vec::new();
+    for (week_idx, week) in data.weeks.iter().enumerate() {
+        // The first real day of the week tells its month
+        if let Some(first_day) = week.iter().find(|d| d.count != -1) {
+            let month = first_day.date.month0() as usize;
+            if positions.last().map(|(_, last)| *last) != Some(month) {
+                positions.push((week_idx, month));
+            }
+        }
+    }
+
+    if positions.len() > 1 && positions[1].0 < 3 {
+        positions.remove(0);
+    }
+
+    positions
+        .into_iter()
+        .map(|(week_idx, month)| (week_idx, MONTH_NAMES[month]))
+        .collect()
+}
+
+/// "Less", a square of each color and "More", ending `padding_right` before
+/// `width`, with the squares' top at `y`
+fn legend(theme: &heatmap_theme::Model, palette: &ColorPalette, width: usize, y: usize) -> String {
+    let cell_size = theme.cell_size as usize;
+    let start_x = width - (40 + palette.colors.len() * (cell_size + 3) + 40 + theme.padding_right as usize);
+    let label = |x: usize, anchor: &str, text: &str| {
+        format!(
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" text-anchor="{}">{}</text>"#,
+            x,
+            y + cell_size / 2 + 3,
+            theme.font_family,
+            theme.font_size,
+            theme.text_color,
+            anchor,
+            text
+        )
+    };
+
+    let mut svg = label(start_x + 30, "end", "Less");
+    for (i, color) in palette.colors.iter().enumerate() {
+        svg.push_str(&format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}"/>"#,
+            start_x + 35 + i * (cell_size + 3),
+            y,
+            cell_size,
+            cell_size,
+            theme.cell_border_radius,
+            color
+        ));
+    }
+    svg.push_str(&label(start_x + 40 + palette.colors.len() * (cell_size + 3), "start", "More"));
+
+    svg
+}
+
+/// Whether the theme draws the code frequency row and there is something in
+/// it
+fn shows_code_frequency(theme: &heatmap_theme::Model, data: &HeatmapData) -> bool {
+    theme.show_code_frequency && data.code_frequency.iter().any(|week| week.additions + week.deletions > 0)
+}
+
+/// Sparkline of lines added (above the middle) and removed (below) per week
+/// column, the first column at `x`, the row's top at `y`
+fn code_frequency_row(theme: &heatmap_theme::Model, palette: &ColorPalette, data: &HeatmapData, x: usize, y: usize) -> String {
+    let column = (theme.cell_size + theme.cell_gap) as f64;
+    let half = CODE_FREQUENCY_HEIGHT as f64 / 2.0;
+    let middle = y as f64 + half;
+    let max = data
+        .code_frequency
+        .iter()
+        .map(|week| week.additions.max(week.deletions))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    let area = |sign: f64, value: fn(&WeekLines) -> i64| {
+        let mut path = format!("M{:.1},{:.1}", x as f64 + theme.cell_size as f64 / 2.0, middle);
+        for (i, week) in data.code_frequency.iter().enumerate() {
+            let center = x as f64 + i as f64 * column + theme.cell_size as f64 / 2.0;
+            path.push_str(&format!(" L{:.1},{:.1}", center, middle + sign * value(week) as f64 / max * half));
+        }
+        let last = x as f64 + (data.code_frequency.len() - 1) as f64 * column + theme.cell_size as f64 / 2.0;
+        path.push_str(&format!(" L{:.1},{:.1} Z", last, middle));
+        path
+    };
+
+    let added: i64 = data.code_frequency.iter().map(|week| week.additions).sum();
+    let removed: i64 = data.code_frequency.iter().map(|week| week.deletions).sum();
+    format!(
+        r#"<g><title>+{} / -{} lines</title><path d="{}" fill="{}" fill-opacity="0.8"/><path d="{}" fill="{}" fill-opacity="0.35"/></g>"#,
+        added,
+        removed,
+        area(-1.0, |week| week.additions),
+        palette.colors.last().map(String::as_str).unwrap_or(&theme.text_color),
+        area(1.0, |week| week.deletions),
+        theme.text_color,
+    )
+}
+
+/// Stroke attributes of a cell or bar, empty when the theme draws no border
+fn cell_border(theme: &heatmap_theme::Model) -> String {
+    if theme.cell_border_width > 0 {
+        format!(
+            r#"stroke="{}" stroke-width="{}" "#,
+            theme.cell_border_color, theme.cell_border_width
+        )
+    } else {
+        String::new()
+    }
+}
+
+/// Hatch pattern for away days without contributions, referenced by
+/// [`empty_cell_fill`]; empty unless the data has such days to hatch
+fn away_pattern(theme: &heatmap_theme::Model, data: &HeatmapData) -> String {
+    let hatched = data.hatch_away_days && data.weeks.iter().flatten().any(|day| day.away && day.count == 0);
+    if !hatched {
+        return String::new();
+    }
+
+    format!(
+        r#"<defs><pattern id="away" width="4" height="4" patternUnits="userSpaceOnUse" patternTransform="rotate(45)"><rect width="4" height="4" fill="{}"/><line x1="0" y1="0" x2="0" y2="4" stroke="{}" stroke-width="1.5" stroke-opacity="0.35"/></pattern></defs>"#,
+        theme.empty_cell_color, theme.text_color
+    )
+}
+
+/// Fill of a day without contributions: hatched when it is an away day the
+/// user wants hatched, else the theme's empty cell color
+fn empty_cell_fill<'a>(theme: &'a heatmap_theme::Model, data: &HeatmapData, away: bool) -> &'a str {
+    if away && data.hatch_away_days {
+        "url(#away)"
+    } else {
+        &theme.empty_cell_color
+    }
+}
+
+/// "Powered by Hgitmap" with its baseline at `y`, linked to the project in
+/// SVG output
+fn watermark(theme: &heatmap_theme::Model, x: usize, y: usize) -> String {
+    format!(
+        r#"<a href="{}" target="_blank" rel="noopener"><text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" opacity="0.6" text-anchor="start">Powered by Hgitmap</text></a>"#,
+        WATERMARK_URL,
+        x,
+        y,
+        theme.font_family,
+        theme.font_size - 1, // Slightly smaller than regular font
+        theme.text_color,
+    )
+}
+
+/// Rough width of `text` at `font_size`, for sizing boxes around it before
+/// the font is known
+fn text_width(text: &str, font_size: i32) -> usize {
+    (text.chars().count() as f64 * font_size as f64 * 0.6).ceil() as usize
+}
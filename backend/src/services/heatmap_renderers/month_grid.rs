@@ -0,0 +1,140 @@
+use anyhow::Result;
+use chrono::{Datelike, Months, NaiveDate};
+use std::collections::HashMap;
+
+use super::{
+    away_pattern, background, cell_border, empty_cell_fill, header, legend, svg_start, watermark,
+    HeatmapRenderer, MONTH_NAMES,
+};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, DayContribution, HeatmapData};
+
+/// Months per row and rows of months, a year like a wall calendar
+const COLUMNS: usize = 4;
+const ROWS: usize = 3;
+
+/// Most weeks a month touches
+const WEEKS_PER_MONTH: usize = 6;
+
+/// A small calendar per month of the last year, a row per week and a column
+/// per weekday, in rows of four months
+pub struct MonthGridRenderer;
+
+impl HeatmapRenderer for MonthGridRenderer {
+    fn layout(&self) -> &'static str {
+        "month_grid"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        let cell_size = theme.cell_size as usize;
+        let cell_gap = theme.cell_gap as usize;
+        let left = theme.padding_left as usize;
+        let month_label_height = theme.month_label_height as usize;
+        let title_height = theme.title_height as usize;
+        // Space between months, so the calendars read as separate blocks
+        let spacing = cell_size * 2;
+
+        let month_width = 7 * (cell_size + cell_gap) - cell_gap;
+        let month_height =
+            month_label_height + WEEKS_PER_MONTH * (cell_size + cell_gap) - cell_gap;
+        let graph_width = COLUMNS * (month_width + spacing) - spacing;
+        let graph_height = ROWS * (month_height + spacing) - spacing;
+
+        let total_width = left + graph_width + theme.padding_right as usize;
+        let total_height = title_height
+            + graph_height
+            + theme.legend_height as usize
+            + theme.padding_bottom as usize;
+
+        let by_date: HashMap<NaiveDate, &DayContribution> = data
+            .weeks
+            .iter()
+            .flatten()
+            .filter(|day| day.count >= 0)
+            .map(|day| (day.date, day))
+            .collect();
+
+        let palette = ColorPalette::for_theme(theme);
+
+        let mut svg = svg_start(theme, total_width, total_height);
+        svg.push_str(&background(theme));
+        svg.push_str(&away_pattern(theme, data));
+        svg.push_str(&header(theme, data, username, left, total_width));
+
+        let border = cell_border(theme);
+        for (i, first) in months(data.date_range_end).into_iter().enumerate() {
+            let month_x = left + (i % COLUMNS) * (month_width + spacing);
+            let month_y = title_height + (i / COLUMNS) * (month_height + spacing);
+
+            if theme.show_month_labels {
+                // The year goes with the first month and each January
+                let name = MONTH_NAMES[first.month0() as usize];
+                let label = if i == 0 || first.month() == 1 {
+                    format!("{} {}", name, first.year())
+                } else {
+                    name.to_string()
+                };
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
+                    month_x,
+                    month_y + month_label_height - 3,
+                    theme.font_family,
+                    theme.font_size,
+                    theme.text_color,
+                    label
+                ));
+            }
+
+            // Days outside the data, before its start or after today, are
+            // left out like the grid's placeholders
+            let offset = first.weekday().num_days_from_sunday() as usize;
+            let days = first.iter_days().take_while(|date| date.month() == first.month());
+            for (day_idx, date) in days.enumerate() {
+                let Some(day) = by_date.get(&date) else {
+                    continue;
+                };
+                let x = month_x + ((offset + day_idx) % 7) * (cell_size + cell_gap);
+                let y = month_y
+                    + month_label_height
+                    + ((offset + day_idx) / 7) * (cell_size + cell_gap);
+
+                let color = if day.count == 0 {
+                    empty_cell_fill(theme, data, day.away)
+                } else {
+                    palette.get_color_for_count(day.count, data.max_count)
+                };
+
+                svg.push_str(&format!(
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" {}/>"#,
+                    x, y, cell_size, cell_size, theme.cell_border_radius, color, border
+                ));
+            }
+        }
+
+        let footer_y = title_height + graph_height + 8;
+        if theme.show_legend {
+            svg.push_str(&legend(theme, &palette, total_width, footer_y));
+        }
+        if theme.show_watermark {
+            svg.push_str(&watermark(theme, left, footer_y + cell_size / 2 + 3));
+        }
+
+        svg.push_str("</svg>");
+
+        Ok(svg)
+    }
+}
+
+/// First days of the twelve months up to the one of `end`, oldest first
+fn months(end: NaiveDate) -> Vec<NaiveDate> {
+    let last = end.with_day(1).unwrap_or(end);
+    (0..COLUMNS * ROWS)
+        .rev()
+        .map(|back| last - Months::new(back as u32))
+        .collect()
+}
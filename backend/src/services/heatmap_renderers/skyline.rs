@@ -0,0 +1,106 @@
+use anyhow::Result;
+
+use super::{
+    background, cell_border, header, legend, month_labels, svg_start, watermark, HeatmapRenderer,
+};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, HeatmapData};
+
+/// Height of the bar of a week without contributions, so the ground line
+/// shows where the weeks are
+const GROUND_HEIGHT: usize = 2;
+
+/// A bar per week, as tall as the week's contributions, over the space the
+/// grid takes
+pub struct SkylineRenderer;
+
+impl HeatmapRenderer for SkylineRenderer {
+    fn layout(&self) -> &'static str {
+        "skyline"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        let cell_size = theme.cell_size as usize;
+        let cell_gap = theme.cell_gap as usize;
+        let left = theme.day_label_width as usize;
+        let month_label_height = theme.month_label_height as usize;
+        let title_height = theme.title_height as usize;
+
+        // Weekly totals, without the padding after the last day
+        let totals: Vec<i32> = data
+            .weeks
+            .iter()
+            .map(|week| week.iter().filter(|day| day.count > 0).map(|day| day.count).sum())
+            .collect();
+        let max_total = totals.iter().copied().max().unwrap_or(0);
+
+        let graph_width = data.weeks.len() * (cell_size + cell_gap) - cell_gap;
+        let graph_height = 7 * (cell_size + cell_gap) - cell_gap;
+        let ground_y = title_height + graph_height;
+
+        let total_width = left + graph_width + theme.padding_right as usize;
+        let total_height = ground_y
+            + month_label_height
+            + theme.legend_height as usize
+            + theme.padding_bottom as usize;
+
+        let palette = ColorPalette::for_theme(theme);
+
+        let mut svg = svg_start(theme, total_width, total_height);
+        svg.push_str(&background(theme));
+        svg.push_str(&header(theme, data, username, left, total_width));
+
+        let border = cell_border(theme);
+        for (week_idx, total) in totals.iter().enumerate() {
+            let (height, color) = if *total == 0 {
+                (GROUND_HEIGHT, theme.empty_cell_color.as_str())
+            } else {
+                let height = (*total as f64 / max_total as f64 * graph_height as f64).round() as usize;
+                (height.max(GROUND_HEIGHT), palette.get_color_for_count(*total, max_total))
+            };
+
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" {}/>"#,
+                left + week_idx * (cell_size + cell_gap),
+                ground_y - height,
+                cell_size,
+                height,
+                theme.cell_border_radius.min(height as i32 / 2),
+                color,
+                border
+            ));
+        }
+
+        // Months below the ground line, where the grid has them above
+        if theme.show_month_labels {
+            for (week_idx, month) in month_labels(data) {
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
+                    left + week_idx * (cell_size + cell_gap),
+                    ground_y + month_label_height,
+                    theme.font_family,
+                    theme.font_size,
+                    theme.text_color,
+                    month
+                ));
+            }
+        }
+
+        let footer_y = ground_y + month_label_height + 8;
+        if theme.show_legend {
+            svg.push_str(&legend(theme, &palette, total_width, footer_y));
+        }
+        if theme.show_watermark {
+            svg.push_str(&watermark(theme, left, footer_y + cell_size / 2 + 3));
+        }
+
+        svg.push_str("</svg>");
+
+        Ok(svg)
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use std::cmp::Reverse;
+
+use super::{background, svg_start, text_width, watermark, HeatmapRenderer};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, HeatmapData};
+use crate::services::text_heatmap::{plural, streaks};
+
+/// Narrowest card, so short values do not make it look cramped
+const MIN_WIDTH: usize = 300;
+
+/// Width of the accent stripe on the left edge
+const STRIPE_WIDTH: usize = 4;
+
+const CORNER_RADIUS: usize = 6;
+
+/// A card with the contribution count, streaks and busiest day instead of
+/// the days themselves
+pub struct StatsCardRenderer;
+
+impl HeatmapRenderer for StatsCardRenderer {
+    fn layout(&self) -> &'static str {
+        "stats_card"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        let padding = theme.padding_left as usize + STRIPE_WIDTH;
+        let font_size = theme.font_size;
+        let title_size = font_size + 4;
+        let row_height = font_size as usize * 2 + 4;
+
+        let title = match username {
+            Some(user) if theme.show_username => format!("@{}", user),
+            _ => "Contributions".to_string(),
+        };
+        let rows = stats(theme, data);
+
+        let widest_row = rows
+            .iter()
+            .map(|(label, value)| text_width(label, font_size) + text_width(value, font_size) + 24)
+            .max()
+            .unwrap_or(0);
+        let width = (padding * 2 + widest_row.max(text_width(&title, title_size))).max(MIN_WIDTH);
+        let title_y = theme.padding_top as usize + title_size as usize;
+        let rows_y = title_y + row_height / 2;
+        let footer_y = rows_y + rows.len() * row_height + 8;
+        let height = footer_y
+            + if theme.show_watermark { font_size as usize + 4 } else { 0 }
+            + theme.padding_bottom as usize;
+
+        let palette = ColorPalette::for_theme(theme);
+        let accent = palette.colors.last().cloned().unwrap_or_else(|| theme.text_color.clone());
+
+        let mut svg = svg_start(theme, width, height);
+        svg.push_str(&background(theme));
+        // The stripe follows the card's rounded corners
+        svg.push_str(&format!(
+            r#"<clipPath id="card"><rect width="{}" height="{}" rx="{}"/></clipPath>"#,
+            width, height, CORNER_RADIUS
+        ));
+        svg.push_str(&format!(
+            r#"<rect width="{}" height="{}" fill="{}" clip-path="url(#card)"/>"#,
+            STRIPE_WIDTH, height, accent
+        ));
+        svg.push_str(&format!(
+            r#"<rect x="0.5" y="0.5" width="{}" height="{}" rx="{}" fill="none" stroke="{}"/>"#,
+            width - 1,
+            height - 1,
+            CORNER_RADIUS,
+            theme.border_color
+        ));
+
+        svg.push_str(&format!(
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" font-weight="bold" fill="{}">{}</text>"#,
+            padding, title_y, theme.font_family, title_size, theme.text_color, title
+        ));
+
+        for (i, (label, value)) in rows.iter().enumerate() {
+            let y = rows_y + (i + 1) * row_height;
+            svg.push_str(&format!(
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" fill="{}" opacity="0.8">{}</text>"#,
+                padding, y, theme.font_family, font_size, theme.text_color, label
+            ));
+            svg.push_str(&format!(
+                r#"<text x="{}" y="{}" font-family="{}" font-size="{}" font-weight="bold" fill="{}" text-anchor="end">{}</text>"#,
+                width - padding,
+                y,
+                theme.font_family,
+                font_size,
+                theme.text_color,
+                value
+            ));
+        }
+
+        if theme.show_watermark {
+            svg.push_str(&watermark(theme, padding, footer_y + font_size as usize));
+        }
+
+        svg.push_str("</svg>");
+
+        Ok(svg)
+    }
+}
+
+/// Label and value of each row of the card
+fn stats(theme: &heatmap_theme::Model, data: &HeatmapData) -> Vec<(String, String)> {
+    let days = data.weeks.iter().flatten().filter(|day| day.count >= 0);
+    let active_days = days.clone().filter(|day| day.count > 0).count();
+    let shown_days = days.clone().count();
+    // The earliest of the busiest days
+    let busiest = days
+        .filter(|day| day.count > 0)
+        .max_by_key(|day| (day.count, Reverse(day.date)));
+    let (current, longest) = streaks(data);
+
+    let mut rows = Vec::new();
+    if theme.show_total_count {
+        rows.push(("Total contributions".to_string(), data.total_count.to_string()));
+    }
+    rows.push(("Current streak".to_string(), plural(current, "day")));
+    rows.push(("Longest streak".to_string(), plural(longest, "day")));
+    rows.push((
+        "Busiest day".to_string(),
+        busiest
+            .map(|day| format!("{} on {}", day.count, day.date.format("%b %-d, %Y")))
+            .unwrap_or_else(|| "-".to_string()),
+    ));
+    rows.push((
+        "Active days".to_string(),
+        format!("{} of {}", active_days, shown_days),
+    ));
+    if theme.show_achievements {
+        let titles: Vec<&str> = data.achievements.iter().map(|a| a.title).collect();
+        rows.push((
+            "Achievements".to_string(),
+            if titles.is_empty() { "-".to_string() } else { titles.join(", ") },
+        ));
+    }
+    rows
+}
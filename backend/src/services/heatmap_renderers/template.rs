@@ -0,0 +1,182 @@
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, Utc};
+use serde::Serialize;
+
+use super::{month_labels, HeatmapRenderer};
+use crate::models::heatmap_theme;
+use crate::services::heatmap_generator::{ColorPalette, DayContribution, HeatmapData};
+use crate::services::text_heatmap::streaks;
+use crate::utils::svg_template;
+
+/// Draws a theme's `custom_template`, a minijinja template that produces the
+/// whole SVG. Themes with a template use it whatever their layout.
+pub struct TemplateRenderer;
+
+impl HeatmapRenderer for TemplateRenderer {
+    fn layout(&self) -> &'static str {
+        "custom"
+    }
+
+    fn render(
+        &self,
+        theme: &heatmap_theme::Model,
+        data: &HeatmapData,
+        username: Option<&str>,
+    ) -> Result<String> {
+        let source = theme
+            .custom_template
+            .as_deref()
+            .ok_or_else(|| anyhow!("Theme '{}' has no custom template", theme.slug))?;
+        svg_template::render(source, TemplateContext::new(theme, data, username))
+            .map_err(|e| anyhow!("Theme '{}': {}", theme.slug, e))
+    }
+}
+
+/// Render the theme's template with a year of made-up contributions, so a
+/// template that fails or produces an unsafe SVG is refused when saved
+pub fn check(theme: &heatmap_theme::Model) -> Result<(), String> {
+    let source = theme.custom_template.as_deref().unwrap_or_default();
+    svg_template::render(source, TemplateContext::new(theme, &sample_data(), Some("octocat"))).map(|_| ())
+}
+
+/// 53 weeks ending today with contributions on most days, padded after
+/// today like real data
+fn sample_data() -> HeatmapData {
+    let end = Utc::now().date_naive();
+    let start = end - Duration::days(364 + end.weekday().num_days_from_sunday() as i64);
+    let mut days: Vec<DayContribution> = (0..=(end - start).num_days())
+        .map(|i| DayContribution {
+            date: start + Duration::days(i),
+            count: (i * 7 % 11) as i32,
+            away: false,
+        })
+        .collect();
+    let max_count = days.iter().map(|day| day.count).max().unwrap_or(0);
+    let total_count = days.iter().map(|day| day.count).sum();
+    while !days.len().is_multiple_of(7) {
+        days.push(DayContribution { date: end, count: -1, away: false });
+    }
+
+    HeatmapData {
+        weeks: days.chunks(7).map(<[DayContribution]>::to_vec).collect(),
+        max_count,
+        total_count,
+        date_range_start: start,
+        date_range_end: end,
+        hatch_away_days: false,
+        achievements: Vec::new(),
+        code_frequency: Vec::new(),
+    }
+}
+
+/// Everything a template can see. Nothing else of the user or the instance
+/// is passed in.
+#[derive(Serialize)]
+pub struct TemplateContext<'a> {
+    /// Weeks from Sunday to Saturday, oldest first; days after the last one
+    /// are null
+    weeks: Vec<Vec<Option<TemplateDay>>>,
+    totals: Totals,
+    /// Color per level, days without contributions first
+    palette: Vec<String>,
+    /// Null when the theme hides it
+    username: Option<&'a str>,
+    /// First day shown and today, as YYYY-MM-DD
+    start_date: String,
+    end_date: String,
+    /// Weeks that start a month, e.g. `{"week": 4, "name": "Feb"}`
+    months: Vec<Month>,
+    theme: ThemeValues<'a>,
+}
+
+#[derive(Serialize)]
+struct TemplateDay {
+    date: String,
+    count: i32,
+    /// 0 for none up to 4 for the busiest days
+    level: usize,
+    color: String,
+    /// In one of the user's away periods
+    away: bool,
+}
+
+#[derive(Serialize)]
+struct Totals {
+    contributions: i32,
+    max_day: i32,
+    active_days: usize,
+    current_streak: i32,
+    longest_streak: i32,
+}
+
+#[derive(Serialize)]
+struct Month {
+    week: usize,
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+struct ThemeValues<'a> {
+    background_color: &'a str,
+    border_color: &'a str,
+    text_color: &'a str,
+    empty_cell_color: &'a str,
+    cell_size: i32,
+    cell_gap: i32,
+    cell_border_radius: i32,
+    font_family: &'a str,
+    font_size: i32,
+    show_legend: bool,
+    show_watermark: bool,
+}
+
+impl<'a> TemplateContext<'a> {
+    pub fn new(theme: &'a heatmap_theme::Model, data: &HeatmapData, username: Option<&'a str>) -> Self {
+        let palette = ColorPalette::cell_colors(theme);
+        let day = |day: &DayContribution| {
+            (day.count >= 0).then(|| {
+                let level = ColorPalette::level_for_count(day.count, data.max_count);
+                TemplateDay {
+                    date: day.date.to_string(),
+                    count: day.count,
+                    level,
+                    color: palette[level.min(palette.len() - 1)].clone(),
+                    away: day.away,
+                }
+            })
+        };
+        let (current_streak, longest_streak) = streaks(data);
+
+        Self {
+            weeks: data.weeks.iter().map(|week| week.iter().map(day).collect()).collect(),
+            totals: Totals {
+                contributions: data.total_count,
+                max_day: data.max_count,
+                active_days: data.weeks.iter().flatten().filter(|day| day.count > 0).count(),
+                current_streak,
+                longest_streak,
+            },
+            palette: palette.clone(),
+            username: username.filter(|_| theme.show_username),
+            start_date: data.date_range_start.to_string(),
+            end_date: data.date_range_end.to_string(),
+            months: month_labels(data)
+                .into_iter()
+                .map(|(week, name)| Month { week, name })
+                .collect(),
+            theme: ThemeValues {
+                background_color: &theme.background_color,
+                border_color: &theme.border_color,
+                text_color: &theme.text_color,
+                empty_cell_color: &theme.empty_cell_color,
+                cell_size: theme.cell_size,
+                cell_gap: theme.cell_gap,
+                cell_border_radius: theme.cell_border_radius,
+                font_family: &theme.font_family,
+                font_size: theme.font_size,
+                show_legend: theme.show_legend,
+                show_watermark: theme.show_watermark,
+            },
+        }
+    }
+}
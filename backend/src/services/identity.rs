@@ -0,0 +1,244 @@
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::{Expr, Func};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::handlers::auth::create_user;
+use crate::models::{git_platform_account::GitPlatform, oauth_state, user, user_identity};
+use crate::services::git_platforms::UserInfo;
+use crate::services::username_redirects;
+use crate::utils::oidc_claims::OidcUser;
+
+/// Values of `oauth_states.purpose`
+pub const PURPOSE_CONNECT: &str = "connect";
+pub const PURPOSE_LOGIN: &str = "login";
+pub const PURPOSE_LINK: &str = "link";
+/// OIDC single sign-on, see `services::oidc`
+pub const PURPOSE_OIDC: &str = "oidc";
+/// One-time code handed to the frontend after a sign-in, exchanged for a JWT
+pub const PURPOSE_LOGIN_CODE: &str = "login_code";
+
+/// How long the frontend has to exchange a login code
+const LOGIN_CODE_TTL_SECONDS: i64 = 120;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityError {
+    #[error("Registration is disabled, so new accounts cannot be created by signing in")]
+    RegistrationDisabled,
+    #[error("This {0} account is already linked to another hgitmap user")]
+    LinkedToAnotherUser(&'static str),
+    #[error("Invalid OAuth state")]
+    InvalidState,
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+pub fn provider_name(platform: &GitPlatform) -> &'static str {
+    match platform {
+        GitPlatform::GitHub => "GitHub",
+        GitPlatform::GitLab => "GitLab",
+        GitPlatform::Gitea => "Gitea",
+    }
+}
+
+/// Work out which hgitmap user an OAuth callback acts for.
+///
+/// - `connect`: the user who started the flow.
+/// - `link`: the user who started the flow, after attaching the identity to
+///   them. An identity already linked to someone else is refused.
+/// - `login`: the user the identity is linked to. Unknown identities get a
+///   new user when registration is open. Existing users are never matched by
+///   username or email, since neither proves ownership; they must sign in
+///   and link the identity instead.
+pub async fn resolve_user(
+    db: &DatabaseConnection,
+    allow_registration: bool,
+    state: &oauth_state::Model,
+    platform: GitPlatform,
+    instance_url: &str,
+    info: &UserInfo,
+) -> Result<Uuid, IdentityError> {
+    let existing = user_identity::Entity::find()
+        .filter(user_identity::Column::Provider.eq(platform.clone()))
+        .filter(user_identity::Column::InstanceUrl.eq(instance_url))
+        .filter(user_identity::Column::ProviderUserId.eq(&info.id))
+        .one(db)
+        .await?;
+
+    match state.purpose.as_str() {
+        PURPOSE_CONNECT => state.user_id.ok_or(IdentityError::InvalidState),
+        PURPOSE_LINK => {
+            let user_id = state.user_id.ok_or(IdentityError::InvalidState)?;
+            match existing {
+                Some(identity) if identity.user_id != user_id => {
+                    log::warn!(
+                        "⚠️  [Identity] {} user {} is already linked to another account",
+                        provider_name(&platform),
+                        info.username
+                    );
+                    Err(IdentityError::LinkedToAnotherUser(provider_name(&platform)))
+                }
+                Some(identity) => {
+                    touch_identity(db, identity, info).await?;
+                    Ok(user_id)
+                }
+                None => {
+                    insert_identity(db, user_id, platform.clone(), instance_url, info).await?;
+                    log::info!("🔗 [Identity] Linked {} user {} to {}", provider_name(&platform), info.username, user_id);
+                    Ok(user_id)
+                }
+            }
+        }
+        PURPOSE_LOGIN => match existing {
+            Some(identity) => {
+                let user_id = identity.user_id;
+                touch_identity(db, identity, info).await?;
+                log::info!("✅ [Identity] {} user {} signed in as {}", provider_name(&platform), info.username, user_id);
+                Ok(user_id)
+            }
+            None => {
+                if !allow_registration {
+                    return Err(IdentityError::RegistrationDisabled);
+                }
+                let username = available_username(db, &info.username).await?;
+                let user = create_user(db, &username, None, info.email.clone()).await?;
+                insert_identity(db, user.id, platform.clone(), instance_url, info).await?;
+                log::info!("✨ [Identity] Created user '{}' from {} user {}", username, provider_name(&platform), info.username);
+                Ok(user.id)
+            }
+        },
+        _ => Err(IdentityError::InvalidState),
+    }
+}
+
+/// Find or provision the user for an OIDC sign-in. Users are matched by
+/// subject only; `is_admin` (from the admin group mapping) is applied on every
+/// sign-in so revoking group membership revokes admin rights.
+pub async fn resolve_oidc_user(db: &DatabaseConnection, info: &OidcUser, is_admin: Option<bool>) -> Result<Uuid, DbErr> {
+    let existing = user::Entity::find()
+        .filter(user::Column::OidcSubject.eq(&info.subject))
+        .one(db)
+        .await?;
+
+    let (user, created) = match existing {
+        Some(user) => (user, false),
+        None => {
+            let username = available_username(db, &info.username).await?;
+            let user = create_user(db, &username, None, info.email.clone()).await?;
+            log::info!("✨ [Identity] Provisioned user '{}' from OIDC subject {}", username, info.subject);
+            (user, true)
+        }
+    };
+
+    let user_id = user.id;
+    let admin_changed = is_admin.is_some_and(|is_admin| is_admin != user.is_admin);
+    if created || admin_changed {
+        if admin_changed {
+            log::info!("👑 [Identity] Setting admin={} for '{}' from OIDC groups", is_admin.unwrap_or_default(), user.username);
+        }
+        let mut active: user::ActiveModel = user.into();
+        active.oidc_subject = Set(Some(info.subject.clone()));
+        if let Some(is_admin) = is_admin {
+            active.is_admin = Set(is_admin);
+        }
+        active.updated_at = Set(Utc::now());
+        active.update(db).await?;
+    }
+
+    Ok(user_id)
+}
+
+/// Store a short-lived, single-use code the frontend exchanges for a JWT,
+/// so the token itself never appears in a redirect URL
+pub async fn create_login_code(db: &DatabaseConnection, user_id: Uuid) -> Result<String, DbErr> {
+    let code = Uuid::new_v4().to_string();
+    oauth_state::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        state_token: Set(code.clone()),
+        user_id: Set(Some(user_id)),
+        platform: Set(None),
+        created_at: Set(Utc::now()),
+        expires_at: Set(Utc::now() + Duration::seconds(LOGIN_CODE_TTL_SECONDS)),
+        instance_url: Set(None),
+        purpose: Set(PURPOSE_LOGIN_CODE.to_string()),
+        nonce: Set(None),
+    }
+    .insert(db)
+    .await?;
+    Ok(code)
+}
+
+/// Consume a login code, returning the user it was issued for
+pub async fn redeem_login_code(db: &DatabaseConnection, code: &str) -> Result<Option<Uuid>, DbErr> {
+    let Some(state) = oauth_state::Entity::find()
+        .filter(oauth_state::Column::StateToken.eq(code))
+        .filter(oauth_state::Column::Purpose.eq(PURPOSE_LOGIN_CODE))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    // Whoever deletes the code redeems it, so two requests racing with the
+    // same code cannot both sign in
+    let deleted = oauth_state::Entity::delete_many()
+        .filter(oauth_state::Column::StateToken.eq(code))
+        .filter(oauth_state::Column::Purpose.eq(PURPOSE_LOGIN_CODE))
+        .exec(db)
+        .await?;
+
+    if deleted.rows_affected != 1 || state.expires_at < Utc::now() {
+        return Ok(None);
+    }
+    Ok(state.user_id)
+}
+
+async fn insert_identity(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    platform: GitPlatform,
+    instance_url: &str,
+    info: &UserInfo,
+) -> Result<(), DbErr> {
+    user_identity::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider: Set(platform),
+        instance_url: Set(instance_url.to_string()),
+        provider_user_id: Set(info.id.clone()),
+        provider_username: Set(info.username.clone()),
+        email: Set(info.email.clone()),
+        created_at: Set(Utc::now()),
+        last_login_at: Set(Some(Utc::now())),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Record the sign-in and pick up username or email changes on the provider
+async fn touch_identity(db: &DatabaseConnection, identity: user_identity::Model, info: &UserInfo) -> Result<(), DbErr> {
+    let mut identity: user_identity::ActiveModel = identity.into();
+    identity.provider_username = Set(info.username.clone());
+    identity.email = Set(info.email.clone());
+    identity.last_login_at = Set(Some(Utc::now()));
+    identity.update(db).await?;
+    Ok(())
+}
+
+/// The provider username if free, otherwise the first free `name-N`
+pub(crate) async fn available_username(db: &DatabaseConnection, base: &str) -> Result<String, DbErr> {
+    let mut candidate = base.to_string();
+    for suffix in 2.. {
+        let taken = user::Entity::find()
+            .filter(Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(candidate.to_lowercase()))
+            .one(db)
+            .await?
+            .is_some();
+        if !taken && !username_redirects::is_reserved(db, &candidate, None).await? {
+            break;
+        }
+        candidate = format!("{}-{}", base, suffix);
+    }
+    Ok(candidate)
+}
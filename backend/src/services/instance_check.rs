@@ -0,0 +1,526 @@
+use reqwest::{Client, ClientBuilder, StatusCode};
+use sea_orm::*;
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::handlers::oauth::{default_redirect_uri, default_scopes};
+use crate::models::{git_platform_account::GitPlatform, oauth_application, oauth_application_setting};
+use crate::utils::config::Config;
+use crate::utils::http_client::{shared_client, SendTraced};
+use crate::utils::{encryption, validators};
+
+/// Per request, so an unreachable instance answers within a few probes
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+static INSECURE_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Only used to tell a bad certificate apart from an unreachable host, and
+/// to keep diagnosing past it. Connecting never uses it.
+fn insecure_client() -> &'static Client {
+    INSECURE_CLIENT.get_or_init(|| {
+        ClientBuilder::new()
+            .timeout(PROBE_TIMEOUT)
+            .user_agent("hgitmap/0.1.0")
+            .danger_accept_invalid_certs(true)
+            .build()
+            .expect("Failed to create HTTP client")
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passed,
+    Warning,
+    Failed,
+    /// Not run because an earlier check failed
+    Skipped,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InstanceCheck {
+    /// `url`, `reachability`, `tls`, `api`, `version` or `oauth_app`
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+/// Diagnostics for a self-hosted instance, gathered before connecting it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InstanceReport {
+    /// The URL to connect with, without a trailing slash
+    pub instance_url: String,
+    pub platform: String,
+    /// `gitea`, `forgejo` or `gitlab`, from the API that answered
+    pub detected_platform: Option<String>,
+    pub version: Option<String>,
+    /// No check failed, so a valid token should connect
+    pub ok: bool,
+    pub checks: Vec<InstanceCheck>,
+}
+
+impl InstanceReport {
+    fn push(&mut self, name: &str, status: CheckStatus, message: impl Into<String>) {
+        self.checks.push(InstanceCheck {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        });
+    }
+
+    fn skip(&mut self, names: &[&str]) {
+        for name in names {
+            self.push(name, CheckStatus::Skipped, "Skipped because an earlier check failed");
+        }
+    }
+}
+
+/// Probe a Gitea, Forgejo or GitLab instance: the URL, whether it answers,
+/// its certificate, its API and version, and whether an OAuth app is set up
+/// for it. Forgejo is connected as Gitea. `access_token` is optional and
+/// only used to read the GitLab version, which GitLab hides from anonymous
+/// requests.
+pub async fn validate(
+    db: &DatabaseConnection,
+    platform: GitPlatform,
+    instance_url: &str,
+    access_token: Option<&str>,
+) -> Result<InstanceReport, DbErr> {
+    let platform_name = match platform {
+        GitPlatform::GitHub => "github",
+        GitPlatform::GitLab => "gitlab",
+        GitPlatform::Gitea => "gitea",
+    };
+    let mut report = InstanceReport {
+        instance_url: instance_url.trim().trim_end_matches('/').to_string(),
+        platform: platform_name.to_string(),
+        detected_platform: None,
+        version: None,
+        ok: false,
+        checks: Vec::new(),
+    };
+
+    let url = match validators::validate_url(&report.instance_url) {
+        Ok(url) => url,
+        Err(e) => {
+            report.push("url", CheckStatus::Failed, format!("{}. Enter the address of the instance, e.g. https://git.example.com", e));
+            report.skip(&["reachability", "tls", "api", "version", "oauth_app"]);
+            return Ok(report);
+        }
+    };
+    let https = url.scheme() == "https";
+    if https {
+        report.push("url", CheckStatus::Passed, "Valid https URL");
+    } else {
+        report.push(
+            "url",
+            CheckStatus::Warning,
+            "Plain http sends the access token unencrypted; use https if the instance supports it",
+        );
+    }
+
+    // Reachability and certificate. After a certificate error the other
+    // probes go through the insecure client, so the report still covers them.
+    let host = url.host_str().unwrap_or_default().to_string();
+    let client = match probe(shared_client(), &report.instance_url).await {
+        Ok(status) => {
+            report.push("reachability", CheckStatus::Passed, format!("{} answered with HTTP {}", host, status.as_u16()));
+            if https {
+                report.push("tls", CheckStatus::Passed, "The certificate is valid");
+            } else {
+                report.push("tls", CheckStatus::Skipped, "Not using https");
+            }
+            Some(shared_client())
+        }
+        Err(e) => match probe(insecure_client().clone(), &report.instance_url).await {
+            Ok(status) if https => {
+                report.push("reachability", CheckStatus::Passed, format!("{} answered with HTTP {}", host, status.as_u16()));
+                report.push(
+                    "tls",
+                    CheckStatus::Failed,
+                    format!(
+                        "The certificate is not trusted ({}). It may be self-signed, expired or issued for another host; \
+                         a certificate from a private CA works once that CA is in the server's system certificate store",
+                        root_cause(&e)
+                    ),
+                );
+                Some(insecure_client().clone())
+            }
+            _ => {
+                let message = if e.is_timeout() {
+                    format!("{} did not answer within {} seconds", host, PROBE_TIMEOUT.as_secs())
+                } else {
+                    format!("Could not connect to {}: {}. Check the address and that the instance is reachable from this server", host, root_cause(&e))
+                };
+                report.push("reachability", CheckStatus::Failed, message);
+                report.skip(&["tls", "api", "version"]);
+                None
+            }
+        },
+    };
+
+    if let Some(client) = client {
+        match platform {
+            GitPlatform::GitLab => check_gitlab(&client, &mut report, access_token).await,
+            _ => check_gitea(&client, &mut report).await,
+        }
+    }
+
+    // OAuth apps are stored with the URL the admin entered
+    let apps = oauth_application::Entity::find()
+        .filter(oauth_application::Column::Platform.eq(platform))
+        .filter(oauth_application::Column::IsEnabled.eq(true))
+        .all(db)
+        .await?;
+    if apps
+        .iter()
+        .any(|app| app.instance_url.trim_end_matches('/').eq_ignore_ascii_case(&report.instance_url))
+    {
+        report.push("oauth_app", CheckStatus::Passed, "An OAuth app is set up, so you can connect with OAuth");
+    } else {
+        report.push(
+            "oauth_app",
+            CheckStatus::Warning,
+            "No OAuth app is set up for this instance. Connect with a personal access token, or ask an administrator to add an OAuth app",
+        );
+    }
+
+    report.ok = report.checks.iter().all(|c| c.status != CheckStatus::Failed);
+    Ok(report)
+}
+
+/// Result of testing an OAuth app's configuration against its platform
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthAppReport {
+    pub app_id: uuid::Uuid,
+    pub platform: String,
+    pub instance_url: String,
+    /// No check failed, so users should be able to connect with the app
+    pub ok: bool,
+    /// `client_secret`, `redirect_uri`, `authorize_endpoint` and `credentials`
+    pub checks: Vec<InstanceCheck>,
+}
+
+/// Test an OAuth app without a user: decrypt its secret, check its redirect
+/// URI, open its authorize endpoint, and exchange a made-up authorization
+/// code. The platform rejects that code either way, but says whether the
+/// client ID and secret were accepted before it did.
+pub async fn test_oauth_app(
+    db: &DatabaseConnection,
+    config: &Config,
+    app: &oauth_application::Model,
+) -> Result<OAuthAppReport, DbErr> {
+    let (platform_name, base, authorize_path, token_path) = match app.platform {
+        GitPlatform::GitHub => ("github", "https://github.com", "/login/oauth/authorize", "/login/oauth/access_token"),
+        GitPlatform::GitLab => ("gitlab", app.instance_url.trim_end_matches('/'), "/oauth/authorize", "/oauth/token"),
+        GitPlatform::Gitea => ("gitea", app.instance_url.trim_end_matches('/'), "/login/oauth/authorize", "/login/oauth/access_token"),
+    };
+    let settings = oauth_application_setting::Entity::find_by_id(app.id).one(db).await?;
+    let (scopes, redirect_uri) = settings.map(|s| (s.scopes, s.redirect_uri)).unwrap_or_default();
+    let scopes = scopes.unwrap_or_else(|| default_scopes(&app.platform).to_string());
+    let default_redirect = default_redirect_uri(config, &app.platform);
+    let custom_redirect = redirect_uri.is_some();
+    let redirect_uri = redirect_uri.unwrap_or_else(|| default_redirect.clone());
+
+    // Collects the checks with the same helpers as `validate`
+    let mut report = InstanceReport {
+        instance_url: base.to_string(),
+        platform: platform_name.to_string(),
+        detected_platform: None,
+        version: None,
+        ok: false,
+        checks: Vec::new(),
+    };
+
+    let client_secret = match encryption::decrypt(&app.client_secret, &config.encryption_key) {
+        Ok(secret) => {
+            report.push("client_secret", CheckStatus::Passed, "The client secret decrypts with ENCRYPTION_KEY");
+            Some(secret)
+        }
+        Err(e) => {
+            report.push(
+                "client_secret",
+                CheckStatus::Failed,
+                format!("The client secret can't be decrypted ({}); enter it again", e),
+            );
+            None
+        }
+    };
+
+    if custom_redirect {
+        report.push(
+            "redirect_uri",
+            CheckStatus::Warning,
+            format!(
+                "Using the custom redirect URI {}. It has to be registered with the app and lead to {}",
+                redirect_uri, default_redirect
+            ),
+        );
+    } else if validators::validate_url(&redirect_uri).is_err() {
+        report.push(
+            "redirect_uri",
+            CheckStatus::Failed,
+            format!("{} is not a valid URL; check BASE_URL", redirect_uri),
+        );
+    } else {
+        report.push(
+            "redirect_uri",
+            CheckStatus::Passed,
+            format!("{} has to be registered as the app's callback URL", redirect_uri),
+        );
+    }
+
+    let authorize_url = format!(
+        "{}{}?client_id={}&redirect_uri={}&response_type=code&scope={}",
+        base,
+        authorize_path,
+        urlencoding::encode(&app.client_id),
+        urlencoding::encode(&redirect_uri),
+        urlencoding::encode(&scopes)
+    );
+    // Signed out, Gitea and GitLab send everyone to their login page, so
+    // only GitHub can tell an unknown client ID here
+    match probe(shared_client(), &authorize_url).await {
+        Ok(StatusCode::NOT_FOUND) if app.platform == GitPlatform::GitHub => report.push(
+            "authorize_endpoint",
+            CheckStatus::Failed,
+            "GitHub does not know this client ID",
+        ),
+        Ok(status) if status.is_client_error() || status.is_server_error() => report.push(
+            "authorize_endpoint",
+            CheckStatus::Failed,
+            format!("{}{} answered with HTTP {}", base, authorize_path, status.as_u16()),
+        ),
+        Ok(_) => report.push("authorize_endpoint", CheckStatus::Passed, "The authorize endpoint answered"),
+        Err(e) => report.push(
+            "authorize_endpoint",
+            CheckStatus::Failed,
+            format!("Could not reach {}{}: {}", base, authorize_path, root_cause(&e)),
+        ),
+    }
+
+    match client_secret {
+        Some(client_secret) => {
+            let token_url = format!("{}{}", base, token_path);
+            check_credentials(&mut report, &token_url, &app.client_id, &client_secret, &redirect_uri).await
+        }
+        None => report.skip(&["credentials"]),
+    }
+
+    Ok(OAuthAppReport {
+        app_id: app.id,
+        platform: report.platform,
+        instance_url: report.instance_url,
+        ok: report.checks.iter().all(|c| c.status != CheckStatus::Failed),
+        checks: report.checks,
+    })
+}
+
+/// Exchange an invalid code. An invalid-grant error means the platform
+/// accepted the client ID and secret; an invalid-client error means it did not.
+async fn check_credentials(
+    report: &mut InstanceReport,
+    token_url: &str,
+    client_id: &str,
+    client_secret: &str,
+    redirect_uri: &str,
+) {
+    let params = [
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+        ("code", "hgitmap-credential-test"),
+        ("grant_type", "authorization_code"),
+        ("redirect_uri", redirect_uri),
+    ];
+    let response = shared_client()
+        .post(token_url)
+        .timeout(PROBE_TIMEOUT)
+        .header("accept", "application/json")
+        .form(&params)
+        .send_traced()
+        .await;
+
+    let (status, body) = match response {
+        Ok(response) => {
+            let status = response.status();
+            (status, response.json::<serde_json::Value>().await.unwrap_or_default())
+        }
+        Err(e) => {
+            report.push(
+                "credentials",
+                CheckStatus::Failed,
+                format!("Could not reach {}: {}", token_url, root_cause(&e)),
+            );
+            return;
+        }
+    };
+    let error = body.get("error").and_then(|e| e.as_str()).unwrap_or_default();
+    let description = body
+        .get("error_description")
+        .and_then(|d| d.as_str())
+        .unwrap_or(error);
+
+    match error {
+        // GitHub's name for invalid_grant
+        "invalid_grant" | "bad_verification_code" => {
+            report.push("credentials", CheckStatus::Passed, "The client ID and secret were accepted")
+        }
+        "invalid_client" | "unauthorized_client" | "incorrect_client_credentials" => report.push(
+            "credentials",
+            CheckStatus::Failed,
+            format!("The client ID or secret was rejected ({}); copy both from the app again", description),
+        ),
+        "" => report.push(
+            "credentials",
+            CheckStatus::Warning,
+            format!("{} answered with HTTP {} and no OAuth error", token_url, status.as_u16()),
+        ),
+        _ => report.push(
+            "credentials",
+            CheckStatus::Warning,
+            format!("The platform answered with an unexpected error: {}", description),
+        ),
+    }
+}
+
+async fn check_gitea(client: &Client, report: &mut InstanceReport) {
+    let base = report.instance_url.clone();
+    match get_json(client, &format!("{}/api/v1/version", base), None).await {
+        Some((status, body)) if status.is_success() => {
+            report.push("api", CheckStatus::Passed, "The Gitea API answered at /api/v1");
+
+            // Forgejo keeps the Gitea API and adds its own version endpoint
+            let forgejo = get_json(client, &format!("{}/api/forgejo/v1/version", base), None)
+                .await
+                .filter(|(status, _)| status.is_success());
+            let (detected, body) = match forgejo {
+                Some((_, body)) => ("forgejo", body),
+                None => ("gitea", body),
+            };
+            report.detected_platform = Some(detected.to_string());
+            report.version = body.get("version").and_then(|v| v.as_str()).map(str::to_string);
+
+            match &report.version {
+                Some(version) => {
+                    let name = if detected == "forgejo" { "Forgejo" } else { "Gitea" };
+                    report.push("version", CheckStatus::Passed, format!("{} {}", name, version));
+                }
+                None => report.push("version", CheckStatus::Warning, "The instance did not report its version"),
+            }
+        }
+        answer => {
+            if is_gitlab(client, &base).await {
+                report.detected_platform = Some("gitlab".to_string());
+                report.push("api", CheckStatus::Failed, "This is a GitLab instance; connect it as GitLab");
+            } else {
+                let status = answer.map_or_else(|| "not JSON".to_string(), |(status, _)| format!("HTTP {}", status.as_u16()));
+                report.push(
+                    "api",
+                    CheckStatus::Failed,
+                    format!(
+                        "No Gitea API at {}/api/v1 ({}). Use the address of the instance itself, not of a repository or user page",
+                        base, status
+                    ),
+                );
+            }
+            report.skip(&["version"]);
+        }
+    }
+}
+
+async fn check_gitlab(client: &Client, report: &mut InstanceReport, access_token: Option<&str>) {
+    let base = report.instance_url.clone();
+    let answer = get_json(client, &format!("{}/api/v4/version", base), access_token).await;
+    match answer {
+        Some((status, body)) if status.is_success() => {
+            report.detected_platform = Some("gitlab".to_string());
+            report.push("api", CheckStatus::Passed, "The GitLab API answered at /api/v4");
+            report.version = body.get("version").and_then(|v| v.as_str()).map(str::to_string);
+            match &report.version {
+                Some(version) => report.push("version", CheckStatus::Passed, format!("GitLab {}", version)),
+                None => report.push("version", CheckStatus::Warning, "The instance did not report its version"),
+            }
+        }
+        // GitLab only shows the version to signed-in users
+        Some((StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN, _)) => {
+            report.detected_platform = Some("gitlab".to_string());
+            report.push("api", CheckStatus::Passed, "The GitLab API answered at /api/v4");
+            if access_token.is_some() {
+                report.push(
+                    "version",
+                    CheckStatus::Failed,
+                    "The access token was rejected. Check that it has not expired and has the read_api scope",
+                );
+            } else {
+                report.push(
+                    "version",
+                    CheckStatus::Warning,
+                    "GitLab only shows its version to signed-in users; pass an access token to check it",
+                );
+            }
+        }
+        answer => {
+            let gitea = get_json(client, &format!("{}/api/v1/version", base), None)
+                .await
+                .is_some_and(|(status, _)| status.is_success());
+            if gitea {
+                report.detected_platform = Some("gitea".to_string());
+                report.push("api", CheckStatus::Failed, "This is a Gitea or Forgejo instance; connect it as Gitea");
+            } else {
+                let status = answer.map_or_else(|| "not JSON".to_string(), |(status, _)| format!("HTTP {}", status.as_u16()));
+                report.push(
+                    "api",
+                    CheckStatus::Failed,
+                    format!(
+                        "No GitLab API at {}/api/v4 ({}). Use the address of the instance itself, not of a group or project page",
+                        base, status
+                    ),
+                );
+            }
+            report.skip(&["version"]);
+        }
+    }
+}
+
+/// Whether a GitLab API answers at `base`, with or without a token
+async fn is_gitlab(client: &Client, base: &str) -> bool {
+    get_json(client, &format!("{}/api/v4/version", base), None)
+        .await
+        .is_some_and(|(status, _)| {
+            status.is_success() || status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
+        })
+}
+
+async fn probe(client: Client, url: &str) -> reqwest::Result<StatusCode> {
+    let response = client.get(url).timeout(PROBE_TIMEOUT).send_traced().await?;
+    Ok(response.status())
+}
+
+/// Status and body of a JSON answer. None when the request fails or the
+/// answer is not JSON, e.g. a web page served for an unknown path.
+async fn get_json(client: &Client, url: &str, token: Option<&str>) -> Option<(StatusCode, serde_json::Value)> {
+    let mut request = client
+        .get(url)
+        .timeout(PROBE_TIMEOUT)
+        .header("accept", "application/json");
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = request.send_traced().await.ok()?;
+    let status = response.status();
+    let body = response.json::<serde_json::Value>().await.ok()?;
+    Some((status, body))
+}
+
+/// The innermost error, which names what actually went wrong (DNS, refused
+/// connection, certificate) instead of reqwest's generic wrapper
+fn root_cause(error: &reqwest::Error) -> String {
+    let mut source: &dyn std::error::Error = error;
+    while let Some(inner) = source.source() {
+        source = inner;
+    }
+    source.to_string()
+}
@@ -0,0 +1,288 @@
+use chrono::{Datelike, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::instance_setting;
+use crate::utils::config::{Config, MAX_HEATMAP_WORKERS};
+use crate::utils::cors::AllowedOrigins;
+use crate::utils::ttl_cache::TtlCache;
+
+/// How long other server processes may keep serving a changed setting
+const CACHE_TTL_SECS: u64 = 30;
+
+pub const ALLOW_REGISTRATION: &str = "allow_registration";
+pub const FRONTEND_URL: &str = "frontend_url";
+pub const DEFAULT_SYNC_INTERVAL_MINUTES: &str = "default_sync_interval_minutes";
+pub const USERNAME_CHANGE_COOLDOWN_DAYS: &str = "username_change_cooldown_days";
+pub const HEATMAP_WORKERS: &str = "heatmap_workers";
+pub const MAX_STORAGE_MB: &str = "max_storage_mb";
+pub const DEFAULT_EARLIEST_SYNC_YEAR: &str = "default_earliest_sync_year";
+pub const SYNC_ACTIVITIES: &str = "sync_activities";
+pub const ALLOW_ALL_YEARS_SYNC: &str = "allow_all_years_sync";
+
+/// The first year an account can sync from
+pub const MIN_SYNC_YEAR: i32 = 2008;
+
+pub const KEYS: &[&str] = &[
+    ALLOW_REGISTRATION,
+    FRONTEND_URL,
+    DEFAULT_SYNC_INTERVAL_MINUTES,
+    USERNAME_CHANGE_COOLDOWN_DAYS,
+    HEATMAP_WORKERS,
+    MAX_STORAGE_MB,
+    DEFAULT_EARLIEST_SYNC_YEAR,
+    SYNC_ACTIVITIES,
+    ALLOW_ALL_YEARS_SYNC,
+];
+
+/// Settings admins can change without a restart. Keys without a stored
+/// value fall back to the environment.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct InstanceSettings {
+    pub allow_registration: bool,
+    /// Used in links and redirects to the frontend. Must be an origin CORS
+    /// allows, so FRONTEND_URL or one of CORS_ALLOWED_ORIGINS.
+    pub frontend_url: String,
+    /// Sync interval of users who have not chosen one
+    pub default_sync_interval_minutes: i32,
+    pub username_change_cooldown_days: i64,
+    /// Heatmap generation jobs run at once
+    pub heatmap_workers: u32,
+    /// Generated file storage per user in MB; 0 for unlimited
+    pub max_storage_mb: u64,
+    /// First year new accounts' all-years syncs go back to
+    pub default_earliest_sync_year: i32,
+    /// Syncs also store activities for the timeline. Off, only
+    /// contributions are synced, whatever accounts chose.
+    pub sync_activities: bool,
+    /// Users may sync every year since an account's earliest sync year;
+    /// otherwise one year at a time
+    pub allow_all_years_sync: bool,
+}
+
+impl InstanceSettings {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            allow_registration: config.allow_registration,
+            frontend_url: config.frontend_url.clone(),
+            default_sync_interval_minutes: 60,
+            username_change_cooldown_days: config.username_change_cooldown_days,
+            heatmap_workers: config.heatmap_workers,
+            max_storage_mb: config.max_storage_mb,
+            default_earliest_sync_year: 2020,
+            sync_activities: true,
+            allow_all_years_sync: true,
+        }
+    }
+
+    /// Validate a value and set it
+    fn apply(&mut self, key: &str, value: &Value) -> Result<(), String> {
+        match key {
+            ALLOW_REGISTRATION => {
+                self.allow_registration = value
+                    .as_bool()
+                    .ok_or_else(|| format!("{} must be a boolean", key))?;
+            }
+            FRONTEND_URL => {
+                let url = value
+                    .as_str()
+                    .map(|u| u.trim().trim_end_matches('/'))
+                    .filter(|u| url::Url::parse(u).is_ok_and(|u| matches!(u.scheme(), "http" | "https")))
+                    .ok_or_else(|| format!("{} must be an http(s) URL", key))?;
+                self.frontend_url = url.to_string();
+            }
+            DEFAULT_SYNC_INTERVAL_MINUTES => {
+                self.default_sync_interval_minutes = value
+                    .as_i64()
+                    .filter(|m| (15..=1440).contains(m))
+                    .ok_or_else(|| format!("{} must be between 15 and 1440", key))?
+                    as i32;
+            }
+            USERNAME_CHANGE_COOLDOWN_DAYS => {
+                self.username_change_cooldown_days = value
+                    .as_i64()
+                    .filter(|d| (0..=365).contains(d))
+                    .ok_or_else(|| format!("{} must be between 0 and 365", key))?;
+            }
+            HEATMAP_WORKERS => {
+                self.heatmap_workers = value
+                    .as_u64()
+                    .filter(|w| (1..=MAX_HEATMAP_WORKERS as u64).contains(w))
+                    .ok_or_else(|| format!("{} must be between 1 and {}", key, MAX_HEATMAP_WORKERS))?
+                    as u32;
+            }
+            MAX_STORAGE_MB => {
+                self.max_storage_mb = value
+                    .as_u64()
+                    .ok_or_else(|| format!("{} must be a whole number of MB, 0 for unlimited", key))?;
+            }
+            DEFAULT_EARLIEST_SYNC_YEAR => {
+                let current_year = Utc::now().year() as i64;
+                self.default_earliest_sync_year = value
+                    .as_i64()
+                    .filter(|y| (MIN_SYNC_YEAR as i64..=current_year).contains(y))
+                    .ok_or_else(|| format!("{} must be between {} and {}", key, MIN_SYNC_YEAR, current_year))?
+                    as i32;
+            }
+            SYNC_ACTIVITIES => {
+                self.sync_activities = value
+                    .as_bool()
+                    .ok_or_else(|| format!("{} must be a boolean", key))?;
+            }
+            ALLOW_ALL_YEARS_SYNC => {
+                self.allow_all_years_sync = value
+                    .as_bool()
+                    .ok_or_else(|| format!("{} must be a boolean", key))?;
+            }
+            _ => return Err(format!("Unknown setting '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// The stored form of a setting
+    fn value(&self, key: &str) -> Value {
+        match key {
+            ALLOW_REGISTRATION => Value::from(self.allow_registration),
+            FRONTEND_URL => Value::from(self.frontend_url.clone()),
+            DEFAULT_SYNC_INTERVAL_MINUTES => Value::from(self.default_sync_interval_minutes),
+            USERNAME_CHANGE_COOLDOWN_DAYS => Value::from(self.username_change_cooldown_days),
+            HEATMAP_WORKERS => Value::from(self.heatmap_workers),
+            MAX_STORAGE_MB => Value::from(self.max_storage_mb),
+            DEFAULT_EARLIEST_SYNC_YEAR => Value::from(self.default_earliest_sync_year),
+            SYNC_ACTIVITIES => Value::from(self.sync_activities),
+            ALLOW_ALL_YEARS_SYNC => Value::from(self.allow_all_years_sync),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// Instance settings stored in the database, cached briefly so handlers can
+/// read them on every request
+pub struct InstanceSettingsService {
+    db: DatabaseConnection,
+    defaults: InstanceSettings,
+    allowed_origins: AllowedOrigins,
+    cache: TtlCache<(), InstanceSettings>,
+}
+
+impl InstanceSettingsService {
+    pub fn new(db: DatabaseConnection, config: &Config) -> Self {
+        Self {
+            db,
+            defaults: InstanceSettings::from_config(config),
+            // The server refuses to start with invalid origins
+            allowed_origins: AllowedOrigins::from_config(config).unwrap_or_default(),
+            cache: TtlCache::new(Duration::from_secs(CACHE_TTL_SECS), 1),
+        }
+    }
+
+    /// The values from the environment
+    pub fn defaults(&self) -> &InstanceSettings {
+        &self.defaults
+    }
+
+    /// Validate a value and set it. The frontend must be an origin CORS
+    /// allows, or the browser would reject its API calls.
+    fn apply(&self, settings: &mut InstanceSettings, key: &str, value: &Value) -> Result<(), String> {
+        settings.apply(key, value)?;
+
+        if key == FRONTEND_URL {
+            let origin = url::Url::parse(&settings.frontend_url)
+                .map(|url| url.origin().ascii_serialization())
+                .unwrap_or_default();
+            if !self.allowed_origins.is_allowed(&origin) {
+                return Err(format!("{} must be FRONTEND_URL or one of CORS_ALLOWED_ORIGINS", key));
+            }
+        }
+        Ok(())
+    }
+
+    /// The settings in effect. Falls back to the environment when the
+    /// database cannot be read.
+    pub async fn current(&self) -> InstanceSettings {
+        if let Some(settings) = self.cache.get(&()) {
+            return settings;
+        }
+
+        match self.load().await {
+            Ok((settings, _)) => {
+                self.cache.insert((), settings.clone());
+                settings
+            }
+            Err(e) => {
+                log::error!("⚠️  Failed to load instance settings, using defaults: {}", e);
+                self.defaults.clone()
+            }
+        }
+    }
+
+    /// Read the stored overrides, returning the resulting settings and the
+    /// overridden keys. Invalid stored values are skipped.
+    pub async fn load(&self) -> Result<(InstanceSettings, Vec<String>), DbErr> {
+        let mut settings = self.defaults.clone();
+        let mut overridden = Vec::new();
+
+        for row in instance_setting::Entity::find().all(&self.db).await? {
+            match self.apply(&mut settings, &row.key, &row.value) {
+                Ok(()) => overridden.push(row.key),
+                Err(e) => log::warn!("⚠️  Ignoring stored instance setting: {}", e),
+            }
+        }
+
+        Ok((settings, overridden))
+    }
+
+    /// Store the given settings; `null` removes an override. Nothing is
+    /// stored unless every value is valid.
+    pub async fn update(&self, changes: &Map<String, Value>, admin_id: Uuid) -> Result<Result<(), String>, DbErr> {
+        let mut validated = self.defaults.clone();
+        for (key, value) in changes {
+            if !KEYS.contains(&key.as_str()) {
+                return Ok(Err(format!("Unknown setting '{}'", key)));
+            }
+            if !value.is_null() {
+                if let Err(e) = self.apply(&mut validated, key, value) {
+                    return Ok(Err(e));
+                }
+            }
+        }
+
+        let txn = self.db.begin().await?;
+        for (key, value) in changes {
+            if value.is_null() {
+                instance_setting::Entity::delete_by_id(key.clone()).exec(&txn).await?;
+                continue;
+            }
+
+            instance_setting::Entity::insert(instance_setting::ActiveModel {
+                key: Set(key.clone()),
+                value: Set(validated.value(key)),
+                updated_by: Set(Some(admin_id)),
+                updated_at: Set(Utc::now()),
+            })
+            .on_conflict(
+                OnConflict::column(instance_setting::Column::Key)
+                    .update_columns([
+                        instance_setting::Column::Value,
+                        instance_setting::Column::UpdatedBy,
+                        instance_setting::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+
+        // This process sees the change at once, others within the cache TTL
+        let (settings, _) = self.load().await?;
+        self.cache.insert((), settings);
+
+        Ok(Ok(()))
+    }
+}
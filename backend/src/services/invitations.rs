@@ -0,0 +1,52 @@
+use chrono::Utc;
+use rand::RngCore;
+use sea_orm::sea_query::{Condition, Expr, Func};
+use sea_orm::*;
+
+use crate::models::invitation;
+use crate::utils::api_token::hash_token;
+
+/// Prefix that makes invitation codes recognisable when pasted
+const INVITE_CODE_PREFIX: &str = "hgi_";
+
+/// Generate a new random invitation code. Only its hash is stored.
+pub fn generate_code() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", INVITE_CODE_PREFIX, hex::encode(bytes))
+}
+
+/// Use up one registration on an invitation. Returns false when the code is
+/// unknown, expired, used up, or issued to a different email address. The
+/// checks and the increment run as one UPDATE so concurrent sign-ups cannot
+/// exceed `max_uses`.
+pub async fn redeem(db: &DatabaseConnection, code: &str, email: Option<&str>) -> Result<bool, DbErr> {
+    let email_matches = match email.map(str::trim).filter(|e| !e.is_empty()) {
+        Some(email) => Condition::any()
+            .add(invitation::Column::Email.is_null())
+            .add(Expr::expr(Func::lower(Expr::col(invitation::Column::Email))).eq(email.to_lowercase())),
+        None => Condition::all().add(invitation::Column::Email.is_null()),
+    };
+
+    let result = invitation::Entity::update_many()
+        .col_expr(
+            invitation::Column::UseCount,
+            Expr::col(invitation::Column::UseCount).add(1),
+        )
+        .filter(invitation::Column::CodeHash.eq(hash_token(code.trim())))
+        .filter(
+            Condition::any()
+                .add(invitation::Column::MaxUses.is_null())
+                .add(Expr::col(invitation::Column::UseCount).lt(Expr::col(invitation::Column::MaxUses))),
+        )
+        .filter(
+            Condition::any()
+                .add(invitation::Column::ExpiresAt.is_null())
+                .add(invitation::Column::ExpiresAt.gt(Utc::now())),
+        )
+        .filter(email_matches)
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected > 0)
+}
@@ -0,0 +1,110 @@
+use sea_orm::*;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+use crate::models::user_job_limit;
+
+/// Pending jobs each processor looks at per pass, so that a user with a long
+/// queue does not hide everyone else's jobs
+pub const CANDIDATE_WINDOW: u64 = 100;
+
+/// Job priorities; pending jobs run highest first, so jobs users start by
+/// hand go ahead of scheduled ones
+pub const MANUAL_JOB_PRIORITY: i32 = 10;
+pub const SCHEDULED_JOB_PRIORITY: i32 = 0;
+
+/// Per-user concurrency overrides for the given users
+pub async fn overrides(
+    db: &DatabaseConnection,
+    user_ids: Vec<Uuid>,
+) -> Result<HashMap<Uuid, u32>, DbErr> {
+    if user_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let limits = user_job_limit::Entity::find()
+        .filter(user_job_limit::Column::UserId.is_in(user_ids))
+        .all(db)
+        .await?;
+
+    Ok(limits
+        .into_iter()
+        .map(|limit| (limit.user_id, limit.max_concurrent_jobs.max(1) as u32))
+        .collect())
+}
+
+/// Pick up to `batch_size` jobs from `jobs` (already in priority order),
+/// taking no more of one user's jobs than their limit. The rest wait for the
+/// next pass.
+pub async fn take_batch<T>(
+    db: &DatabaseConnection,
+    jobs: Vec<T>,
+    user_of: impl Fn(&T) -> Uuid,
+    default_limit: u32,
+    batch_size: usize,
+) -> Result<Vec<T>, DbErr> {
+    let mut user_ids: Vec<Uuid> = jobs.iter().map(&user_of).collect();
+    user_ids.sort();
+    user_ids.dedup();
+    let overrides = overrides(db, user_ids).await?;
+
+    let mut taken: HashMap<Uuid, u32> = HashMap::new();
+    let mut batch = Vec::new();
+
+    for job in jobs {
+        if batch.len() >= batch_size {
+            break;
+        }
+
+        let user_id = user_of(&job);
+        let limit = overrides.get(&user_id).copied().unwrap_or(default_limit);
+        let count = taken.entry(user_id).or_insert(0);
+        if limit > 0 && *count >= limit {
+            continue;
+        }
+
+        *count += 1;
+        batch.push(job);
+    }
+
+    Ok(batch)
+}
+
+/// Reorder jobs (already in priority order) so that users take turns within
+/// each priority: one job of every user, then a second of every user, and
+/// so on. Keeps one user's long queue from going ahead of everyone else.
+pub fn interleave_users<T>(
+    jobs: Vec<T>,
+    user_of: impl Fn(&T) -> Uuid,
+    priority_of: impl Fn(&T) -> i32,
+) -> Vec<T> {
+    let mut ordered = Vec::with_capacity(jobs.len());
+    let mut jobs = jobs.into_iter().peekable();
+
+    while let Some(first) = jobs.next() {
+        let priority = priority_of(&first);
+
+        // Each user's jobs of this priority, users in order of their first job
+        let mut queues: Vec<(Uuid, VecDeque<T>)> = Vec::new();
+        let mut push = |job: T| {
+            let user_id = user_of(&job);
+            match queues.iter_mut().find(|(user, _)| *user == user_id) {
+                Some((_, queue)) => queue.push_back(job),
+                None => queues.push((user_id, VecDeque::from([job]))),
+            }
+        };
+        push(first);
+        while let Some(job) = jobs.next_if(|job| priority_of(job) == priority) {
+            push(job);
+        }
+
+        while !queues.is_empty() {
+            for (_, queue) in queues.iter_mut() {
+                ordered.extend(queue.pop_front());
+            }
+            queues.retain(|(_, queue)| !queue.is_empty());
+        }
+    }
+
+    ordered
+}
@@ -1,6 +1,7 @@
 use sea_orm::*;
 use tokio::time::{interval, Duration};
 use chrono::Utc;
+use futures_util::{stream, StreamExt};
 use std::sync::Arc;
 
 use crate::models::{
@@ -8,18 +9,108 @@ use crate::models::{
     heatmap_theme,
     heatmap_generation_setting,
 };
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::services::health;
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::job_limits::{self, SCHEDULED_JOB_PRIORITY};
+use crate::services::shutdown::Shutdown;
 use crate::services::heatmap_generator::HeatmapGenerator;
+use crate::utils::config::Config;
+use crate::utils::error_reporting;
+use crate::utils::generation_schedule::GenerationSchedule;
+
+/// Jobs taken per pass for each worker
+const JOBS_PER_WORKER: usize = 5;
+
+/// Longest wait between two attempts of a failed job
+const MAX_RETRY_BACKOFF_SECS: u64 = 60 * 60;
+
+/// How failed generation jobs are retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries of new jobs before they are dead-lettered
+    pub max_retries: i32,
+    /// Wait before the first retry; it doubles with every further one
+    pub backoff_secs: u64,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_retries: config.generation_max_retries,
+            backoff_secs: config.generation_retry_backoff_secs,
+        }
+    }
+
+    /// Wait before retry number `attempt` (starting at 1)
+    fn delay(&self, attempt: i32) -> chrono::Duration {
+        let factor = 1u64 << (attempt - 1).clamp(0, 16);
+        let secs = self.backoff_secs.saturating_mul(factor).min(MAX_RETRY_BACKOFF_SECS);
+        chrono::Duration::seconds(secs as i64)
+    }
+}
+
+/// Queue a failed or dead-lettered job again with a fresh retry budget
+pub async fn requeue(
+    db: &DatabaseConnection,
+    job: heatmap_generation_job::Model,
+    retry: RetryPolicy,
+) -> Result<heatmap_generation_job::Model, DbErr> {
+    let mut active: heatmap_generation_job::ActiveModel = job.into();
+    active.status = Set(GenerationJobStatus::Pending);
+    active.scheduled_at = Set(Utc::now());
+    active.started_at = Set(None);
+    active.completed_at = Set(None);
+    active.error_message = Set(None);
+    active.retry_count = Set(0);
+    active.max_retries = Set(retry.max_retries);
+    active.update(db).await
+}
+
+/// A user's generation schedule. Stored settings that no longer parse, such
+/// as a timezone dropped from the database, fall back to the plain interval.
+pub fn generation_schedule(setting: &heatmap_generation_setting::Model) -> GenerationSchedule {
+    let window = setting
+        .generation_window_start_minutes
+        .zip(setting.generation_window_end_minutes);
+
+    GenerationSchedule::new(setting.timezone.as_deref(), window, setting.generation_cron.as_deref())
+        .unwrap_or_else(|e| {
+            log::warn!("Ignoring generation schedule of user {}: {}", setting.user_id, e);
+            GenerationSchedule::default()
+        })
+}
 
 pub struct JobProcessor {
     db: DatabaseConnection,
+    events: EventBus,
+    /// Source of the worker count, which admins can change at runtime
+    instance: Arc<InstanceSettingsService>,
     check_interval: Duration,
+    /// Default per-user concurrency, 0 for unlimited
+    job_concurrency: u32,
+    retry: RetryPolicy,
+    shutdown: Shutdown,
 }
 
 impl JobProcessor {
-    pub fn new(db: DatabaseConnection, check_interval_secs: u64) -> Self {
+    pub fn new(
+        db: DatabaseConnection,
+        events: EventBus,
+        instance: Arc<InstanceSettingsService>,
+        check_interval_secs: u64,
+        job_concurrency: u32,
+        retry: RetryPolicy,
+        shutdown: Shutdown,
+    ) -> Self {
         Self {
             db,
+            events,
+            instance,
             check_interval: Duration::from_secs(check_interval_secs),
+            job_concurrency,
+            retry,
+            shutdown,
         }
     }
 
@@ -28,9 +119,14 @@ impl JobProcessor {
         log::info!("Starting heatmap generation job processor");
 
         let mut interval = interval(self.check_interval);
+        let mut shutdown = self.shutdown.clone();
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.requested() => break,
+            }
+            health::HEATMAP_JOB_PROCESSOR.beat();
 
             if let Err(e) = self.process_pending_jobs().await {
                 log::error!("Error processing jobs: {}", e);
@@ -40,35 +136,64 @@ impl JobProcessor {
                 log::error!("Error scheduling automatic jobs: {}", e);
             }
         }
+
+        log::info!("Heatmap generation job processor stopped");
     }
 
-    /// Process all pending jobs
+    /// Process pending jobs with the configured number of workers
     async fn process_pending_jobs(&self) -> Result<(), DbErr> {
-        // Fetch pending jobs ordered by priority
+        let workers = self.instance.current().await.heatmap_workers.max(1) as usize;
+
+        // Fetch pending jobs ordered by priority; retries wait for their
+        // backoff to pass
         let pending_jobs = heatmap_generation_job::Entity::find()
             .filter(heatmap_generation_job::Column::Status.eq(GenerationJobStatus::Pending))
+            .filter(heatmap_generation_job::Column::ScheduledAt.lte(Utc::now()))
             .order_by_desc(heatmap_generation_job::Column::Priority)
             .order_by_asc(heatmap_generation_job::Column::ScheduledAt)
-            .limit(10) // Process up to 10 jobs at a time
+            .limit(job_limits::CANDIDATE_WINDOW)
             .all(&self.db)
             .await?;
 
+        // Users take turns within each priority, and no user gets more jobs
+        // than their concurrency allows
+        let pending_jobs = job_limits::interleave_users(pending_jobs, |job| job.user_id, |job| job.priority);
+        let pending_jobs = job_limits::take_batch(
+            &self.db,
+            pending_jobs,
+            |job| job.user_id,
+            self.job_concurrency,
+            workers * JOBS_PER_WORKER,
+        )
+        .await?;
+
         if pending_jobs.is_empty() {
             return Ok(());
         }
 
-        log::info!("Processing {} pending generation jobs", pending_jobs.len());
+        log::info!(
+            "Processing {} pending generation jobs with {} workers",
+            pending_jobs.len(),
+            workers
+        );
 
-        for job in pending_jobs {
-            if let Err(e) = self.process_job(job).await {
-                log::error!("Failed to process job: {}", e);
-            }
-        }
+        stream::iter(pending_jobs)
+            .for_each_concurrent(workers, |job| async move {
+                // Leave the rest of the batch for the next start
+                if self.shutdown.is_requested() {
+                    return;
+                }
+                if let Err(e) = self.process_job(job).await {
+                    log::error!("Failed to process job: {}", e);
+                }
+            })
+            .await;
 
         Ok(())
     }
 
     /// Process a single job
+    #[tracing::instrument(name = "heatmap_job", skip_all, fields(job.id = %job.id, user.id = %job.user_id, job.manual = job.is_manual))]
     async fn process_job(&self, job: heatmap_generation_job::Model) -> Result<(), anyhow::Error> {
         log::info!(
             "Processing job {} for user {} (manual: {})",
@@ -87,6 +212,12 @@ impl JobProcessor {
         // Process the job
         let result = self.execute_generation(&processing_job).await;
 
+        // An admin may have cancelled the job while it ran
+        if self.is_job_cancelled(job.id).await? {
+            log::warn!("🚫 Job {} was cancelled while running", job.id);
+            return Ok(());
+        }
+
         // Update job status
         let mut final_job: heatmap_generation_job::ActiveModel = processing_job.into();
 
@@ -107,9 +238,11 @@ impl JobProcessor {
                 let max_retries = job.max_retries;
 
                 if retry_count < max_retries {
-                    // Retry
+                    // Retry once the backoff has passed
+                    let retry_at = Utc::now() + self.retry.delay(retry_count + 1);
                     final_job.status = Set(GenerationJobStatus::Pending);
                     final_job.retry_count = Set(retry_count + 1);
+                    final_job.scheduled_at = Set(retry_at);
                     final_job.error_message = Set(Some(format!(
                         "Attempt {}/{} failed: {}",
                         retry_count + 1,
@@ -118,24 +251,54 @@ impl JobProcessor {
                     )));
                     final_job.started_at = Set(None);
 
-                    log::info!("Job {} will be retried (attempt {}/{})", job.id, retry_count + 1, max_retries);
+                    log::info!(
+                        "Job {} will be retried at {} (attempt {}/{})",
+                        job.id,
+                        retry_at,
+                        retry_count + 1,
+                        max_retries
+                    );
                 } else {
-                    // Max retries reached
-                    final_job.status = Set(GenerationJobStatus::Failed);
+                    // Max retries reached; kept until someone requeues it
+                    final_job.status = Set(GenerationJobStatus::DeadLetter);
                     final_job.completed_at = Set(Some(Utc::now()));
                     final_job.error_message = Set(Some(error_msg));
 
-                    log::error!("Job {} failed after {} attempts", job.id, max_retries);
+                    log::error!("Job {} dead-lettered after {} attempts", job.id, max_retries + 1);
+                    error_reporting::report_job_failure("heatmap_generation", Some(job.id), job.user_id, None, &e);
                 }
             }
         }
 
-        final_job.update(&self.db).await?;
+        let final_job = final_job.update(&self.db).await?;
+
+        self.events.publish(
+            final_job.user_id,
+            DashboardEvent::GenerationFinished {
+                job_id: final_job.id,
+                theme_id: final_job.theme_id,
+                status: final_job.status,
+                error: final_job.error_message,
+            },
+        );
 
         Ok(())
     }
 
+    /// Check if a job has been cancelled
+    async fn is_job_cancelled(&self, job_id: uuid::Uuid) -> Result<bool, DbErr> {
+        let job = heatmap_generation_job::Entity::find_by_id(job_id)
+            .one(&self.db)
+            .await?;
+
+        Ok(job.is_some_and(|job| {
+            job.status == GenerationJobStatus::Failed
+                && job.error_message.is_some_and(|m| m.contains("Cancelled"))
+        }))
+    }
+
     /// Execute the actual generation
+    #[tracing::instrument(skip_all)]
     async fn execute_generation(
         &self,
         job: &heatmap_generation_job::Model,
@@ -176,10 +339,22 @@ impl JobProcessor {
         let now = Utc::now();
 
         for setting in settings {
+            let schedule = generation_schedule(&setting);
+
             // Check if it's time to generate
             let should_generate = match setting.next_scheduled_generation_at {
                 Some(next_time) => now >= next_time,
-                None => true, // First time, generate now
+                // First time: generate now, or once the window opens
+                None => {
+                    let first_time = schedule.first_run(now);
+                    if first_time > now {
+                        let mut active_setting: heatmap_generation_setting::ActiveModel = setting.into();
+                        active_setting.next_scheduled_generation_at = Set(Some(first_time));
+                        active_setting.update(&self.db).await?;
+                        continue;
+                    }
+                    true
+                }
             };
 
             if !should_generate {
@@ -209,9 +384,9 @@ impl JobProcessor {
                 completed_at: Set(None),
                 error_message: Set(None),
                 retry_count: Set(0),
-                max_retries: Set(3),
+                max_retries: Set(self.retry.max_retries),
                 is_manual: Set(false),
-                priority: Set(0), // Normal priority for automatic jobs
+                priority: Set(SCHEDULED_JOB_PRIORITY),
                 created_at: Set(now),
             };
 
@@ -220,7 +395,7 @@ impl JobProcessor {
                 .await?;
 
             // Update next scheduled time
-            let next_time = now + chrono::Duration::minutes(setting.update_interval_minutes as i64);
+            let next_time = schedule.next_run(now, chrono::Duration::minutes(setting.update_interval_minutes as i64));
             let user_id = setting.user_id;
 
             let mut active_setting: heatmap_generation_setting::ActiveModel = setting.into();
@@ -258,8 +433,15 @@ impl JobProcessor {
 }
 
 /// Start the job processor in the background
-pub fn start_job_processor(db: DatabaseConnection) -> tokio::task::JoinHandle<()> {
-    let processor = Arc::new(JobProcessor::new(db, 30)); // Check every 30 seconds
+pub fn start_job_processor(
+    db: DatabaseConnection,
+    events: EventBus,
+    instance: Arc<InstanceSettingsService>,
+    job_concurrency: u32,
+    retry: RetryPolicy,
+    shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    let processor = Arc::new(JobProcessor::new(db, events, instance, 30, job_concurrency, retry, shutdown)); // Check every 30 seconds
 
     tokio::spawn(async move {
         processor.start().await;
@@ -0,0 +1,175 @@
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{git_platform_account, mqtt_publisher, notification_channel, oauth_application};
+use crate::utils::encryption;
+
+/// Stored secrets per encryption key
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KeyUsage {
+    pub key_id: String,
+    /// The newest key, which new secrets are encrypted with
+    pub current: bool,
+    /// Platform tokens, OAuth app secrets, MQTT passwords and notification
+    /// channel secrets encrypted with this key
+    pub secrets: u64,
+}
+
+/// What a rotation run re-encrypted (or would re-encrypt)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RotationReport {
+    /// True when nothing was written
+    pub dry_run: bool,
+    pub current_key_id: String,
+    /// Every configured key, newest first, as counted before the run
+    pub keys: Vec<KeyUsage>,
+    /// Secrets moved to the newest key
+    pub re_encrypted: u64,
+    /// Secrets no configured key opens; they are left alone and have to be
+    /// entered again
+    pub undecryptable: u64,
+    /// Older keys nothing is encrypted with after the run, which can be
+    /// removed from ENCRYPTION_KEY
+    pub retirable_key_ids: Vec<String>,
+}
+
+/// Where a secret that needs re-encrypting is stored
+enum StoredIn {
+    AccessToken(Uuid),
+    RefreshToken(Uuid),
+    ClientSecret(Uuid),
+    MqttPassword(Uuid),
+    ChannelSecret(Uuid),
+}
+
+/// Re-encrypt every platform token, OAuth app client secret, MQTT broker
+/// password and notification channel secret that is not yet under the newest
+/// key in `encryption_key`. A dry run only counts them. Secrets are
+/// re-encrypted one row at a time, so a run can be interrupted and repeated.
+pub async fn run(
+    db: &DatabaseConnection,
+    encryption_key: &str,
+    dry_run: bool,
+) -> Result<RotationReport, DbErr> {
+    let key_ids = encryption::key_ids(encryption_key).map_err(|e| DbErr::Custom(e.to_string()))?;
+    let current_key_id = key_ids[0].clone();
+    let mut counts: BTreeMap<String, u64> = key_ids.iter().map(|id| (id.clone(), 0)).collect();
+    let mut undecryptable = 0;
+    let mut pending = Vec::new();
+
+    let mut inspect = |ciphertext: &str, location: StoredIn| {
+        match encryption::decrypt_with_key_id(ciphertext, encryption_key) {
+            Ok((plaintext, key_id)) => {
+                *counts.entry(key_id.clone()).or_default() += 1;
+                // Unprefixed ciphertexts get a key ID even under the newest key
+                if !ciphertext.starts_with(&format!("{}:", current_key_id)) {
+                    pending.push((location, ciphertext.to_string(), plaintext));
+                }
+            }
+            Err(_) => undecryptable += 1,
+        }
+    };
+
+    for account in git_platform_account::Entity::find().all(db).await? {
+        if let Some(token) = account.access_token.filter(|t| !t.is_empty()) {
+            inspect(&token, StoredIn::AccessToken(account.id));
+        }
+        if let Some(token) = account.refresh_token.filter(|t| !t.is_empty()) {
+            inspect(&token, StoredIn::RefreshToken(account.id));
+        }
+    }
+    for app in oauth_application::Entity::find().all(db).await? {
+        inspect(&app.client_secret, StoredIn::ClientSecret(app.id));
+    }
+    for publisher in mqtt_publisher::Entity::find().all(db).await? {
+        if let Some(password) = publisher.password {
+            inspect(&password, StoredIn::MqttPassword(publisher.user_id));
+        }
+    }
+    // Email and MQTT channels store no secret
+    for channel in notification_channel::Entity::find().all(db).await? {
+        if !channel.secret.is_empty() {
+            inspect(&channel.secret, StoredIn::ChannelSecret(channel.id));
+        }
+    }
+
+    let keys = key_ids
+        .iter()
+        .map(|id| KeyUsage {
+            key_id: id.clone(),
+            current: *id == current_key_id,
+            secrets: counts[id],
+        })
+        .collect();
+    let re_encrypted = pending.len() as u64;
+    let retirable_key_ids = if undecryptable == 0 {
+        key_ids[1..].to_vec()
+    } else {
+        // Unreadable secrets may belong to an older key that was typed wrong
+        Vec::new()
+    };
+
+    if !dry_run {
+        // Only rows still holding the ciphertext that was read are updated,
+        // so a token replaced meanwhile is not overwritten with the old one
+        for (location, old, plaintext) in pending {
+            let ciphertext = encryption::encrypt(&plaintext, encryption_key)
+                .map_err(|e| DbErr::Custom(e.to_string()))?;
+            match location {
+                StoredIn::AccessToken(id) => {
+                    git_platform_account::Entity::update_many()
+                        .col_expr(git_platform_account::Column::AccessToken, Expr::value(ciphertext))
+                        .filter(git_platform_account::Column::Id.eq(id))
+                        .filter(git_platform_account::Column::AccessToken.eq(old))
+                        .exec(db)
+                        .await?;
+                }
+                StoredIn::RefreshToken(id) => {
+                    git_platform_account::Entity::update_many()
+                        .col_expr(git_platform_account::Column::RefreshToken, Expr::value(ciphertext))
+                        .filter(git_platform_account::Column::Id.eq(id))
+                        .filter(git_platform_account::Column::RefreshToken.eq(old))
+                        .exec(db)
+                        .await?;
+                }
+                StoredIn::ClientSecret(id) => {
+                    oauth_application::Entity::update_many()
+                        .col_expr(oauth_application::Column::ClientSecret, Expr::value(ciphertext))
+                        .filter(oauth_application::Column::Id.eq(id))
+                        .filter(oauth_application::Column::ClientSecret.eq(old))
+                        .exec(db)
+                        .await?;
+                }
+                StoredIn::MqttPassword(user_id) => {
+                    mqtt_publisher::Entity::update_many()
+                        .col_expr(mqtt_publisher::Column::Password, Expr::value(ciphertext))
+                        .filter(mqtt_publisher::Column::UserId.eq(user_id))
+                        .filter(mqtt_publisher::Column::Password.eq(old))
+                        .exec(db)
+                        .await?;
+                }
+                StoredIn::ChannelSecret(id) => {
+                    notification_channel::Entity::update_many()
+                        .col_expr(notification_channel::Column::Secret, Expr::value(ciphertext))
+                        .filter(notification_channel::Column::Id.eq(id))
+                        .filter(notification_channel::Column::Secret.eq(old))
+                        .exec(db)
+                        .await?;
+                }
+            }
+        }
+    }
+
+    Ok(RotationReport {
+        dry_run,
+        current_key_id,
+        keys,
+        re_encrypted,
+        undecryptable,
+        retirable_key_ids,
+    })
+}
@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Context, Result};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::utils::config::Config;
+
+/// Sends plain-text email over SMTP, optionally with a file attached.
+/// Disabled unless SMTP_URL is set.
+pub struct Mailer {
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn from_config(config: &Config) -> Self {
+        let from = config
+            .smtp_from
+            .parse()
+            .expect("SMTP_FROM must be an email address, optionally with a name");
+
+        let transport = config.smtp_url.as_deref().map(|url| {
+            AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+                .expect("SMTP_URL must be an smtp:// or smtps:// URL")
+                .build()
+        });
+
+        Self { transport, from }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> Result<()> {
+        let message = self
+            .builder(to, subject)?
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)?;
+        self.deliver(message, subject, to).await
+    }
+
+    /// Send `body` with one attachment, e.g. an image of the heatmap
+    pub async fn send_with_attachment(
+        &self,
+        to: &str,
+        subject: &str,
+        body: String,
+        filename: &str,
+        content_type: &str,
+        content: Vec<u8>,
+    ) -> Result<()> {
+        let content_type = ContentType::parse(content_type).context("Invalid attachment type")?;
+        let message = self.builder(to, subject)?.multipart(
+            MultiPart::mixed()
+                .singlepart(SinglePart::plain(body))
+                .singlepart(Attachment::new(filename.to_string()).body(content, content_type)),
+        )?;
+        self.deliver(message, subject, to).await
+    }
+
+    fn builder(&self, to: &str, subject: &str) -> Result<lettre::message::MessageBuilder> {
+        Ok(Message::builder()
+            .from(self.from.clone())
+            .to(to.parse().context("Invalid recipient address")?)
+            .subject(subject))
+    }
+
+    async fn deliver(&self, message: Message, subject: &str, to: &str) -> Result<()> {
+        let transport = self
+            .transport
+            .as_ref()
+            .ok_or_else(|| anyhow!("Email is not configured"))?;
+
+        transport.send(message).await?;
+        log::info!("📧 [Mailer] Sent '{}' to {}", subject, to);
+        Ok(())
+    }
+}
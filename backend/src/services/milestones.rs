@@ -0,0 +1,293 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::activity::{self, ActivityType};
+use crate::models::{contribution, git_platform_account, milestone, user, user_setting};
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::utils::http_client::{shared_client, SendTraced};
+use crate::utils::{milestones, repo_exclusion};
+
+/// Milestones older than this are recorded without notifying, e.g. the
+/// history found by a first sync
+const NOTIFY_WITHIN_DAYS: i64 = 3;
+
+/// Give up on a webhook receiver after this long
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// Where a milestone activity goes: the account and privacy of the
+/// contributions that reached it
+struct Source {
+    account_id: Uuid,
+    is_private: bool,
+    /// The organization as its repositories spell it
+    organization: Option<String>,
+}
+
+/// Record the milestones the user reached since the last check, each with a
+/// `milestone` activity, and announce the recent ones on the dashboard and
+/// the user's webhook. Run after a sync stored new contributions.
+pub async fn detect_and_notify(db: &DatabaseConnection, events: &EventBus, user_id: Uuid) -> Result<()> {
+    let recorded = record_new(db, user_id).await?;
+    if recorded.is_empty() {
+        return Ok(());
+    }
+    log::info!("🏆 [Milestones] {} reached {} milestone(s)", user_id, recorded.len());
+
+    let since = Utc::now().date_naive() - Duration::days(NOTIFY_WITHIN_DAYS);
+    let recent: Vec<&milestone::Model> = recorded.iter().filter(|m| m.achieved_on >= since).collect();
+    if recent.is_empty() {
+        return Ok(());
+    }
+
+    for milestone in &recent {
+        events.publish(
+            user_id,
+            DashboardEvent::MilestoneReached {
+                milestone_id: milestone.id,
+                kind: milestone.kind.clone(),
+                title: milestone.title.clone(),
+                achieved_on: milestone.achieved_on,
+            },
+        );
+    }
+
+    let webhook_url = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .and_then(|s| s.milestone_webhook_url);
+    if let Some(url) = webhook_url {
+        let username = user::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .map(|u| u.username)
+            .unwrap_or_default();
+        for milestone in recent {
+            if let Err(e) = post_webhook(&url, &username, milestone).await {
+                log::warn!("⚠️  [Milestones] Webhook for {} failed: {}", username, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the milestones in the user's contribution history and store the
+/// ones not recorded yet. Returns them, oldest first.
+pub async fn record_new(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<milestone::Model>> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+    let show_private_contributions = settings.as_ref().map(|s| s.show_private_contributions).unwrap_or(true);
+    let excluded_repositories: Vec<String> = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.to_vec())
+        .unwrap_or_default();
+    let away = settings.as_ref().map(|s| s.away_periods.to_vec()).unwrap_or_default();
+
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .all(db)
+        .await?;
+    if accounts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let personal: HashSet<String> = accounts.iter().map(|a| a.platform_username.to_lowercase()).collect();
+
+    // Only what the public profile counts, so milestones never reveal more
+    let mut query = contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.is_in(accounts.iter().map(|a| a.id)))
+        .filter(repo_exclusion::not_excluded(
+            contribution::Column::RepositoryName,
+            &excluded_repositories,
+        ));
+    if !show_private_contributions {
+        query = query.filter(contribution::Column::IsPrivateRepo.eq(false));
+    }
+    let contributions = query.order_by_asc(contribution::Column::ContributionDate).all(db).await?;
+
+    let mut days: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    let mut day_accounts: HashMap<NaiveDate, HashMap<Uuid, i64>> = HashMap::new();
+    for c in &contributions {
+        *days.entry(c.contribution_date).or_default() += c.count as i64;
+        *day_accounts
+            .entry(c.contribution_date)
+            .or_default()
+            .entry(c.git_platform_account_id)
+            .or_default() += c.count as i64;
+    }
+
+    let organizations = milestones::organizations(
+        contributions
+            .iter()
+            .filter_map(|c| Some((c.contribution_date, c.repository_name.as_deref()?))),
+        &personal,
+    );
+
+    let existing: HashSet<(String, String)> = milestone::Entity::find()
+        .filter(milestone::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.kind, m.milestone_key))
+        .collect();
+
+    let mut reached = milestones::contribution_totals(&days);
+    reached.extend(milestones::streaks(&days, &away));
+    reached.extend(organizations);
+    reached.retain(|r| !existing.contains(&(r.kind.to_string(), r.key.clone())));
+    reached.sort_by_key(|r| r.date);
+
+    let mut recorded = Vec::new();
+    for r in reached {
+        let source = if r.kind == milestones::ORGANIZATION {
+            organization_source(&contributions, &r)
+        } else {
+            busiest_account(&day_accounts, r.date)
+        };
+        let Some(source) = source else { continue };
+
+        if let Some(model) = record(db, user_id, &r, source).await? {
+            recorded.push(model);
+        }
+    }
+
+    Ok(recorded)
+}
+
+/// The account with the most contributions on the day; totals and streaks
+/// span every account
+fn busiest_account(day_accounts: &HashMap<NaiveDate, HashMap<Uuid, i64>>, date: NaiveDate) -> Option<Source> {
+    let (&account_id, _) = day_accounts
+        .get(&date)?
+        .iter()
+        .max_by_key(|(id, count)| (**count, std::cmp::Reverse(**id)))?;
+    Some(Source {
+        account_id,
+        is_private: false,
+        organization: None,
+    })
+}
+
+/// The account of the first contribution to the organization; private when
+/// every contribution to it that day was
+fn organization_source(contributions: &[contribution::Model], reached: &milestones::Reached) -> Option<Source> {
+    let prefix = format!("{}/", reached.key);
+    let first_day: Vec<&contribution::Model> = contributions
+        .iter()
+        .filter(|c| c.contribution_date == reached.date)
+        .filter(|c| {
+            c.repository_name
+                .as_deref()
+                .is_some_and(|name| name.to_lowercase().starts_with(&prefix))
+        })
+        .collect();
+
+    let first = first_day.iter().find(|c| !c.is_private_repo).or(first_day.first())?;
+    Some(Source {
+        account_id: first.git_platform_account_id,
+        is_private: first.is_private_repo,
+        organization: first
+            .repository_name
+            .as_deref()
+            .and_then(|name| name.split_once('/'))
+            .map(|(owner, _)| owner.to_string()),
+    })
+}
+
+/// Store a milestone and its activity. None when a concurrent check
+/// recorded it first.
+async fn record(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    reached: &milestones::Reached,
+    source: Source,
+) -> Result<Option<milestone::Model>> {
+    let now = Utc::now();
+    let model = milestone::Model {
+        id: Uuid::new_v4(),
+        user_id,
+        kind: reached.kind.to_string(),
+        milestone_key: reached.key.clone(),
+        title: reached.title.clone(),
+        achieved_on: reached.date,
+        created_at: now,
+    };
+
+    let txn = db.begin().await?;
+    let inserted = milestone::Entity::insert(milestone::ActiveModel::from(model.clone()))
+        .on_conflict(
+            OnConflict::columns([
+                milestone::Column::UserId,
+                milestone::Column::Kind,
+                milestone::Column::MilestoneKey,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+
+    let identity = format!("Milestone|{}|{}", reached.kind, reached.key);
+    activity::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        git_platform_account_id: Set(source.account_id),
+        activity_type: Set(ActivityType::Milestone),
+        activity_date: Set(reached.date),
+        metadata: Set(json!({
+            "kind": reached.kind,
+            "key": reached.key,
+            "title": reached.title,
+        })),
+        repository_name: Set(None),
+        repository_url: Set(None),
+        is_private_repo: Set(source.is_private),
+        count: Set(1),
+        primary_language: Set(None),
+        organization_name: Set(source.organization),
+        organization_avatar_url: Set(None),
+        natural_key: Set(Some(hex::encode(Sha256::digest(identity.as_bytes())))),
+        imported: Set(false),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+
+    Ok(Some(model))
+}
+
+async fn post_webhook(url: &str, username: &str, milestone: &milestone::Model) -> Result<()> {
+    let response = shared_client()
+        .post(url)
+        .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .json(&json!({
+            "event": "milestone",
+            "username": username,
+            "milestone": {
+                "kind": milestone.kind,
+                "key": milestone.milestone_key,
+                "title": milestone.title,
+                "achieved_on": milestone.achieved_on,
+            },
+        }))
+        .send_traced()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("receiver answered {}", response.status());
+    }
+    Ok(())
+}
@@ -1,7 +1,51 @@
 pub mod git_platforms;
+pub mod account_deletion;
+pub mod achievements;
+pub mod api_usage;
+pub mod backup;
 pub mod activity_aggregation;
+pub mod activity_import;
+pub mod activity_store;
+pub mod captcha;
+pub mod commit_details;
+pub mod cleanup;
+pub mod demo_seed;
+pub mod email_digest;
+pub mod event_bus;
+pub mod health;
+pub mod heatmap_analytics;
 pub mod heatmap_generator;
+pub mod heatmap_renderers;
+pub mod identity;
+pub mod instance_check;
+pub mod instance_settings;
+pub mod invitations;
+pub mod job_limits;
+pub mod key_rotation;
+pub mod oidc;
 pub mod job_processor;
+pub mod mailer;
+pub mod milestones;
+pub mod mqtt_publisher;
+pub mod notification_channels;
+pub mod platform_disconnect;
+pub mod platform_merge;
 pub mod platform_sync;
+pub mod platform_visibility;
+pub mod preview_cache;
+pub mod profile_visibility;
+pub mod quotas;
+pub mod rate_limiter;
+pub mod render_lanes;
+pub mod repository_languages;
+pub mod response_cache;
+pub mod retention;
+pub mod sessions;
+pub mod shutdown;
 pub mod sync_scheduler;
 pub mod sync_job_processor;
+pub mod teams;
+pub mod username_redirects;
+pub mod text_heatmap;
+pub mod today;
+pub mod weekly_summary;
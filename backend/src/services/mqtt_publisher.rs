@@ -0,0 +1,293 @@
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::models::platform_sync_job::SyncJobStatus;
+use crate::models::{mqtt_publisher, user};
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::services::today::{self, TodayResponse};
+use crate::utils::encryption;
+use crate::utils::mqtt::{self, Broker};
+
+/// Whether this build can publish; without the `mqtt` feature brokers can
+/// not be set up
+pub const ENABLED: bool = cfg!(feature = "mqtt");
+
+/// How long a publish may take, connecting included, before it is given up
+#[cfg(feature = "mqtt")]
+const PUBLISH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Topic prefix Home Assistant discovers MQTT devices under
+const HOME_ASSISTANT_PREFIX: &str = "homeassistant";
+
+/// The daily counts a display draws its heatmap from
+#[derive(Serialize)]
+struct HeatmapDays {
+    /// A Sunday; counts[0] is this day and each next one the day after
+    start: NaiveDate,
+    counts: Vec<i64>,
+    max: i64,
+}
+
+/// Retained messages for the user's broker: today's count and streak as
+/// JSON and as bare numbers for microcontrollers, the last year of daily
+/// counts, and optionally Home Assistant sensor configs
+fn messages(
+    publisher: &mqtt_publisher::Model,
+    username: &str,
+    today: &TodayResponse,
+    days: &BTreeMap<NaiveDate, i64>,
+) -> Vec<(String, Vec<u8>)> {
+    let topic = &publisher.topic;
+    let (start, counts) = mqtt::heatmap_days(days, today.date);
+    let heatmap = HeatmapDays {
+        start,
+        max: counts.iter().copied().max().unwrap_or(0),
+        counts,
+    };
+
+    let mut messages = vec![
+        (
+            format!("{}/today", topic),
+            serde_json::to_vec(today).unwrap_or_default(),
+        ),
+        (
+            format!("{}/contributions", topic),
+            today.contributions.to_string().into_bytes(),
+        ),
+        (
+            format!("{}/streak", topic),
+            today.current_streak.to_string().into_bytes(),
+        ),
+        (
+            format!("{}/days", topic),
+            serde_json::to_vec(&heatmap).unwrap_or_default(),
+        ),
+    ];
+
+    if publisher.home_assistant_discovery {
+        let node_id = format!("hgitmap_{}", publisher.user_id.simple());
+        let device = json!({
+            "identifiers": [node_id],
+            "name": format!("hgitmap {}", username),
+            "manufacturer": "hgitmap",
+        });
+        let sensors = [
+            ("contributions_today", "Contributions today", "{{ value_json.contributions }}", Some("contributions")),
+            ("current_streak", "Current streak", "{{ value_json.current_streak }}", Some("d")),
+            ("streak_status", "Streak status", "{{ value_json.streak_status }}", None),
+        ];
+        for (object_id, name, template, unit) in sensors {
+            let mut config = json!({
+                "name": name,
+                "unique_id": format!("{}_{}", node_id, object_id),
+                "state_topic": format!("{}/today", topic),
+                "value_template": template,
+                "device": device,
+            });
+            if let Some(unit) = unit {
+                config["unit_of_measurement"] = json!(unit);
+                config["state_class"] = json!("measurement");
+            }
+            messages.push((
+                format!("{}/sensor/{}/{}/config", HOME_ASSISTANT_PREFIX, node_id, object_id),
+                config.to_string().into_bytes(),
+            ));
+        }
+    }
+
+    messages
+}
+
+/// Connect, publish every message with QoS 1, retained if `retain`, and
+/// wait for the broker to acknowledge them
+#[cfg(feature = "mqtt")]
+async fn send(
+    broker: &Broker,
+    username: Option<&str>,
+    password: Option<String>,
+    messages: Vec<(String, Vec<u8>)>,
+    retain: bool,
+) -> Result<(), String> {
+    use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS, Transport};
+    use std::time::Duration;
+
+    // Unique per connection, so overlapping publishes do not take over each
+    // other's session; 23 characters at most, as MQTT 3.1.1 guarantees
+    let client_id = format!("hgitmap-{}", &Uuid::new_v4().simple().to_string()[..15]);
+    let mut options = MqttOptions::new(client_id, broker.host.clone(), broker.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = username {
+        options.set_credentials(username, password.unwrap_or_default());
+    }
+    if broker.tls {
+        options.set_transport(Transport::tls_with_default_config());
+    }
+
+    // Room for every message, so publishing does not wait on the event loop
+    let (client, mut eventloop) = AsyncClient::new(options, messages.len() + 1);
+    let mut pending = messages.len();
+    for (topic, payload) in messages {
+        client
+            .publish(topic, QoS::AtLeastOnce, retain, payload)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let delivered = async {
+        while pending > 0 {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::PubAck(_))) => pending -= 1,
+                Ok(_) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Ok(())
+    };
+    tokio::time::timeout(PUBLISH_TIMEOUT, delivered)
+        .await
+        .map_err(|_| "Timed out waiting for the broker".to_string())??;
+
+    // Everything arrived, so a failed goodbye does not matter
+    let _ = client.disconnect().await;
+    let _ = tokio::time::timeout(Duration::from_secs(1), eventloop.poll()).await;
+    Ok(())
+}
+
+#[cfg(not(feature = "mqtt"))]
+async fn send(
+    _broker: &Broker,
+    _username: Option<&str>,
+    _password: Option<String>,
+    _messages: Vec<(String, Vec<u8>)>,
+    _retain: bool,
+) -> Result<(), String> {
+    Err("MQTT support is not enabled in this build".to_string())
+}
+
+/// The publisher's broker password, decrypted
+fn password(publisher: &mqtt_publisher::Model, encryption_key: &str) -> Result<Option<String>, String> {
+    publisher
+        .password
+        .as_deref()
+        .map(|password| encryption::decrypt(password, encryption_key))
+        .transpose()
+        .map_err(|_| "The stored broker password can not be decrypted; enter it again".to_string())
+}
+
+/// Publish the user's current numbers to their broker
+async fn publish(
+    db: &DatabaseConnection,
+    encryption_key: &str,
+    publisher: &mqtt_publisher::Model,
+) -> Result<(), String> {
+    let broker = mqtt::parse_broker_url(&publisher.broker_url)?;
+    let password = password(publisher, encryption_key)?;
+
+    let username = user::Entity::find_by_id(publisher.user_id)
+        .one(db)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|u| u.username)
+        .unwrap_or_default();
+    let (today, days) = today::with_days(db, publisher.user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    send(
+        &broker,
+        publisher.username.as_deref(),
+        password,
+        messages(publisher, &username, &today, &days),
+        true,
+    )
+    .await
+}
+
+/// Publish a notification to `{topic}/events/{event}`, not retained, so
+/// subscribers see each one once
+pub async fn publish_event(
+    encryption_key: &str,
+    publisher: &mqtt_publisher::Model,
+    event: &str,
+    payload: Vec<u8>,
+) -> Result<(), String> {
+    let broker = mqtt::parse_broker_url(&publisher.broker_url)?;
+    let password = password(publisher, encryption_key)?;
+    send(
+        &broker,
+        publisher.username.as_deref(),
+        password,
+        vec![(format!("{}/events/{}", publisher.topic, event), payload)],
+        false,
+    )
+    .await
+}
+
+/// Publish to the user's broker, if they set one up, and record how it went.
+/// Returns the updated publisher, with `last_error` set when it failed.
+pub async fn publish_for_user(
+    db: &DatabaseConnection,
+    encryption_key: &str,
+    user_id: Uuid,
+) -> Result<Option<mqtt_publisher::Model>, DbErr> {
+    let Some(publisher) = mqtt_publisher::Entity::find_by_id(user_id).one(db).await? else {
+        return Ok(None);
+    };
+
+    let result = publish(db, encryption_key, &publisher).await;
+    let mut active: mqtt_publisher::ActiveModel = publisher.into();
+    match result {
+        Ok(()) => {
+            active.last_published_at = Set(Some(Utc::now()));
+            active.last_error = Set(None);
+        }
+        Err(e) => {
+            log::warn!("⚠️  [MQTT] Failed to publish for user {}: {}", user_id, e);
+            active.last_error = Set(Some(e));
+        }
+    }
+    active.update(db).await.map(Some)
+}
+
+/// Publish a user's numbers whenever one of their syncs completes. Each
+/// publish runs on its own, so a slow broker holds up no one else.
+pub fn publish_on_events(db: DatabaseConnection, encryption_key: String, events: &EventBus) -> JoinHandle<()> {
+    let mut receiver = events.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if !matches!(
+                        event.event,
+                        DashboardEvent::SyncFinished {
+                            status: SyncJobStatus::Completed,
+                            ..
+                        }
+                    ) {
+                        continue;
+                    }
+                    let db = db.clone();
+                    let encryption_key = encryption_key.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = publish_for_user(&db, &encryption_key, event.user_id).await {
+                            log::error!("Failed to publish MQTT update for user {}: {}", event.user_id, e);
+                        }
+                    });
+                }
+                // The next sync of the users whose events were missed
+                // publishes again
+                Err(RecvError::Lagged(missed)) => {
+                    log::warn!("⚠️  [MQTT] Missed {} events", missed);
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    })
+}
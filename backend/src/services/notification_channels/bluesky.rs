@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::{error_body, ChannelEvent, NotificationChannel, Payload, REQUEST_TIMEOUT_SECS};
+use crate::utils::http_client::{shared_client, SendTraced};
+use crate::utils::social_post::link_ranges;
+
+/// The PDS most accounts live on
+pub const DEFAULT_SERVICE_URL: &str = "https://bsky.social";
+
+/// Largest image blob Bluesky accepts in a post
+const MAX_IMAGE_BYTES: usize = 1_000_000;
+
+/// Posts to Bluesky with the account's handle and an app password
+/// (Settings → Privacy and security → App passwords)
+pub struct Bluesky {
+    service_url: String,
+    identifier: String,
+    app_password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    access_jwt: String,
+    did: String,
+    handle: String,
+}
+
+impl Bluesky {
+    pub fn new(service_url: &str, identifier: &str, app_password: String) -> Self {
+        Self {
+            service_url: service_url.trim_end_matches('/').to_string(),
+            identifier: identifier.to_string(),
+            app_password,
+        }
+    }
+
+    fn xrpc(&self, method: &str) -> String {
+        format!("{}/xrpc/{}", self.service_url, method)
+    }
+
+    async fn create_session(&self) -> Result<Session> {
+        let response = shared_client()
+            .post(self.xrpc("com.atproto.server.createSession"))
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .json(&json!({
+                "identifier": self.identifier,
+                "password": self.app_password,
+            }))
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Bluesky rejected the app password: {}", error_body(response).await);
+        }
+        response.json().await.context("Unexpected session response")
+    }
+
+    /// Upload an image, returning the blob reference a post embeds
+    async fn upload_blob(&self, session: &Session, png: Vec<u8>) -> Result<Value> {
+        let response = shared_client()
+            .post(self.xrpc("com.atproto.repo.uploadBlob"))
+            .bearer_auth(&session.access_jwt)
+            .header(reqwest::header::CONTENT_TYPE, "image/png")
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .body(png)
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Image upload failed: {}", error_body(response).await);
+        }
+        let body: Value = response.json().await.context("Unexpected blob response")?;
+        body.get("blob").cloned().context("Unexpected blob response")
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Bluesky {
+    async fn verify(&self) -> Result<String> {
+        Ok(format!("@{}", self.create_session().await?.handle))
+    }
+
+    async fn send(&self, _event: ChannelEvent, payload: &Payload) -> Result<()> {
+        let session = self.create_session().await?;
+
+        // Links in the text are only clickable with a facet pointing at them
+        let facets: Vec<Value> = link_ranges(&payload.text)
+            .into_iter()
+            .map(|range| {
+                json!({
+                    "index": { "byteStart": range.start, "byteEnd": range.end },
+                    "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": &payload.text[range] }],
+                })
+            })
+            .collect();
+
+        let mut record = json!({
+            "$type": "app.bsky.feed.post",
+            "text": payload.text,
+            "facets": facets,
+            "createdAt": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        });
+        match &payload.image {
+            Some(image) if image.png.len() <= MAX_IMAGE_BYTES => {
+                let blob = self.upload_blob(&session, image.png.clone()).await?;
+                record["embed"] = json!({
+                    "$type": "app.bsky.embed.images",
+                    "images": [{ "alt": image.description, "image": blob }],
+                });
+            }
+            Some(image) => {
+                log::warn!(
+                    "⚠️  [Bluesky] Posting without the {} byte image, over the {} byte limit",
+                    image.png.len(),
+                    MAX_IMAGE_BYTES
+                );
+            }
+            None => {}
+        }
+
+        let response = shared_client()
+            .post(self.xrpc("com.atproto.repo.createRecord"))
+            .bearer_auth(&session.access_jwt)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .json(&json!({
+                "repo": session.did,
+                "collection": "app.bsky.feed.post",
+                "record": record,
+            }))
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Posting failed: {}", error_body(response).await);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+use super::{error_body, ChannelEvent, NotificationChannel, Payload, REQUEST_TIMEOUT_SECS};
+use crate::utils::http_client::{shared_client, SendTraced};
+
+/// Posts to a Discord channel through a webhook (Channel settings →
+/// Integrations → Webhooks), with the image attached
+pub struct Discord {
+    webhook_url: String,
+}
+
+#[derive(Deserialize)]
+struct WebhookInfo {
+    name: String,
+}
+
+impl Discord {
+    pub fn new(webhook_url: String) -> Self {
+        Self { webhook_url }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Discord {
+    /// Fetching a webhook by its URL returns it without posting anything
+    async fn verify(&self) -> Result<String> {
+        let response = shared_client()
+            .get(&self.webhook_url)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Discord rejected the webhook: {}",
+                error_body(response).await
+            );
+        }
+        let info: WebhookInfo = response
+            .json()
+            .await
+            .context("Unexpected webhook response")?;
+        Ok(info.name)
+    }
+
+    async fn send(&self, _event: ChannelEvent, payload: &Payload) -> Result<()> {
+        let mut message = json!({
+            "content": format!("**{}**\n{}", payload.title, payload.text),
+            // Links in summaries are not expanded into previews
+            "flags": 4,
+        });
+
+        let request = shared_client()
+            .post(&self.webhook_url)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+        let request = match &payload.image {
+            Some(image) => {
+                message["attachments"] = json!([{ "id": 0, "description": image.description }]);
+                request.multipart(
+                    Form::new().text("payload_json", message.to_string()).part(
+                        "files[0]",
+                        Part::bytes(image.png.clone())
+                            .file_name("heatmap.png")
+                            .mime_str("image/png")?,
+                    ),
+                )
+            }
+            None => request.json(&message),
+        };
+
+        let response = request.send_traced().await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Discord rejected the message: {}",
+                error_body(response).await
+            );
+        }
+        Ok(())
+    }
+}
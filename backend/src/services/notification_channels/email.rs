@@ -0,0 +1,46 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::{ChannelEvent, NotificationChannel, Payload};
+use crate::services::mailer::Mailer;
+
+/// Mails the user at their account's address, with the image attached
+pub struct Email {
+    mailer: Arc<Mailer>,
+    address: String,
+}
+
+impl Email {
+    pub fn new(mailer: Arc<Mailer>, address: String) -> Self {
+        Self { mailer, address }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Email {
+    async fn verify(&self) -> Result<String> {
+        if !self.mailer.is_enabled() {
+            anyhow::bail!("This instance does not send email");
+        }
+        Ok(self.address.clone())
+    }
+
+    async fn send(&self, _event: ChannelEvent, payload: &Payload) -> Result<()> {
+        match &payload.image {
+            Some(image) => {
+                self.mailer
+                    .send_with_attachment(
+                        &self.address,
+                        &payload.title,
+                        payload.text.clone(),
+                        "heatmap.png",
+                        "image/png",
+                        image.png.clone(),
+                    )
+                    .await
+            }
+            None => self.mailer.send(&self.address, &payload.title, payload.text.clone()).await,
+        }
+    }
+}
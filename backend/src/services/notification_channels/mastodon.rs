@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{error_body, ChannelEvent, NotificationChannel, Payload, REQUEST_TIMEOUT_SECS};
+use crate::utils::http_client::{shared_client, SendTraced};
+
+/// Times an upload still being processed is checked on, a second apart
+const MEDIA_POLLS: usize = 10;
+
+/// Posts statuses with an access token that has the `write:statuses` and
+/// `write:media` scopes (Preferences → Development → New application)
+pub struct Mastodon {
+    instance_url: String,
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    acct: String,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    id: String,
+    /// Null while the instance still processes the upload
+    url: Option<String>,
+}
+
+impl Mastodon {
+    pub fn new(instance_url: &str, access_token: String) -> Self {
+        Self {
+            instance_url: instance_url.trim_end_matches('/').to_string(),
+            access_token,
+        }
+    }
+
+    /// Upload an image and wait until it can be attached
+    async fn upload(&self, png: Vec<u8>, description: &str) -> Result<String> {
+        let form = Form::new()
+            .part("file", Part::bytes(png).file_name("heatmap.png").mime_str("image/png")?)
+            .text("description", description.to_string());
+        let response = shared_client()
+            .post(format!("{}/api/v2/media", self.instance_url))
+            .bearer_auth(&self.access_token)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .multipart(form)
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Image upload failed: {}", error_body(response).await);
+        }
+        let mut attachment: Attachment = response.json().await.context("Unexpected media response")?;
+
+        // Large images are processed after the upload returns
+        for _ in 0..MEDIA_POLLS {
+            if attachment.url.is_some() {
+                return Ok(attachment.id);
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            attachment = shared_client()
+                .get(format!("{}/api/v1/media/{}", self.instance_url, attachment.id))
+                .bearer_auth(&self.access_token)
+                .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+                .send_traced()
+                .await?
+                .json()
+                .await
+                .context("Unexpected media response")?;
+        }
+        anyhow::bail!("The image was still being processed")
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Mastodon {
+    async fn verify(&self) -> Result<String> {
+        let response = shared_client()
+            .get(format!("{}/api/v1/accounts/verify_credentials", self.instance_url))
+            .bearer_auth(&self.access_token)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Mastodon rejected the access token: {}", error_body(response).await);
+        }
+        let account: Account = response.json().await.context("Unexpected account response")?;
+        let host = reqwest::Url::parse(&self.instance_url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_string();
+        Ok(format!("@{}@{}", account.acct, host))
+    }
+
+    async fn send(&self, _event: ChannelEvent, payload: &Payload) -> Result<()> {
+        let mut media_ids = Vec::new();
+        if let Some(image) = &payload.image {
+            media_ids.push(self.upload(image.png.clone(), &image.description).await?);
+        }
+
+        let response = shared_client()
+            .post(format!("{}/api/v1/statuses", self.instance_url))
+            .bearer_auth(&self.access_token)
+            .header("Idempotency-Key", &payload.idempotency_key)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .json(&serde_json::json!({
+                "status": payload.text,
+                "media_ids": media_ids,
+                "visibility": "public",
+            }))
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Posting failed: {}", error_body(response).await);
+        }
+        Ok(())
+    }
+}
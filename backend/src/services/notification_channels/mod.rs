@@ -0,0 +1,309 @@
+pub mod bluesky;
+pub mod discord;
+pub mod email;
+pub mod mastodon;
+pub mod mqtt;
+pub mod slack;
+pub mod webhook;
+
+pub use bluesky::Bluesky;
+pub use discord::Discord;
+pub use email::Email;
+pub use mastodon::Mastodon;
+pub use mqtt::Mqtt;
+pub use slack::Slack;
+pub use webhook::Webhook;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::*;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::models::platform_sync_job::SyncJobStatus;
+use crate::models::{git_platform_account, notification_channel, user};
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::services::mailer::Mailer;
+use crate::utils::encryption;
+
+/// Values of `notification_channels.kind`
+pub const MASTODON: &str = "mastodon";
+pub const BLUESKY: &str = "bluesky";
+pub const EMAIL: &str = "email";
+pub const WEBHOOK: &str = "webhook";
+pub const SLACK: &str = "slack";
+pub const DISCORD: &str = "discord";
+pub const MQTT: &str = "mqtt";
+
+pub const KINDS: &[&str] = &[MASTODON, BLUESKY, EMAIL, WEBHOOK, SLACK, DISCORD, MQTT];
+
+/// Values of `notification_channels.events`
+pub const WEEKLY_SUMMARY: &str = "weekly_summary";
+pub const MILESTONE: &str = "milestone";
+pub const SYNC_FAILED: &str = "sync_failed";
+
+pub const EVENTS: &[&str] = &[WEEKLY_SUMMARY, MILESTONE, SYNC_FAILED];
+
+/// Give up on a channel's API after this long
+pub(crate) const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// What a channel is notified about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelEvent {
+    /// The week's contributions with the heatmap, from `services::weekly_summary`
+    WeeklySummary,
+    /// A milestone was reached
+    Milestone,
+    /// A sync of one of the user's accounts failed
+    SyncFailed,
+    /// Sent on request to check a channel; never routed
+    Test,
+}
+
+impl ChannelEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChannelEvent::WeeklySummary => WEEKLY_SUMMARY,
+            ChannelEvent::Milestone => MILESTONE,
+            ChannelEvent::SyncFailed => SYNC_FAILED,
+            ChannelEvent::Test => "test",
+        }
+    }
+}
+
+/// What a channel is sent. Each channel uses what suits it: social posts
+/// are the text and image, emails add the title as subject, webhooks and
+/// MQTT carry everything.
+#[derive(Debug, Clone)]
+pub struct Payload {
+    /// One line: the email subject, or the heading of a chat message
+    pub title: String,
+    pub text: String,
+    pub image: Option<PostImage>,
+    /// Details for programs, e.g. the milestone's kind and date
+    pub data: serde_json::Value,
+    /// The same when a failed send is retried, for channels that can drop
+    /// duplicates
+    pub idempotency_key: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PostImage {
+    pub png: Vec<u8>,
+    /// Alt text
+    pub description: String,
+}
+
+/// A place hgitmap notifies a user at. A new kind implements this, gets a
+/// constant in `KINDS` and an arm in `build` or `from_model`.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Check the channel can be sent to and return what it is known by,
+    /// like the handle it posts as
+    async fn verify(&self) -> Result<String>;
+
+    /// Deliver `payload` about `event`
+    async fn send(&self, event: ChannelEvent, payload: &Payload) -> Result<()>;
+}
+
+/// What channels need beyond their own row
+#[derive(Clone)]
+pub struct ChannelContext {
+    pub db: DatabaseConnection,
+    pub encryption_key: String,
+    pub mailer: Arc<Mailer>,
+}
+
+/// The client for a stored channel, with its secret decrypted
+pub async fn from_model(
+    ctx: &ChannelContext,
+    channel: &notification_channel::Model,
+) -> Result<Box<dyn NotificationChannel>> {
+    match channel.kind.as_str() {
+        EMAIL => {
+            let address = user::Entity::find_by_id(channel.user_id)
+                .one(&ctx.db)
+                .await?
+                .and_then(|u| u.email)
+                .context("Your account has no email address")?;
+            Ok(Box::new(Email::new(ctx.mailer.clone(), address)))
+        }
+        MQTT => Ok(Box::new(Mqtt::new(ctx.db.clone(), ctx.encryption_key.clone(), channel.user_id))),
+        kind => {
+            let secret = encryption::decrypt(&channel.secret, &ctx.encryption_key)
+                .context("The stored secret can not be decrypted; connect the channel again")?;
+            build(kind, &channel.instance_url, &channel.account, secret)
+        }
+    }
+}
+
+/// The client for a channel of `kind` that is configured by its row alone.
+/// `account` is only used by channels that sign in with it.
+pub fn build(kind: &str, instance_url: &str, account: &str, secret: String) -> Result<Box<dyn NotificationChannel>> {
+    match kind {
+        MASTODON => Ok(Box::new(Mastodon::new(instance_url, secret))),
+        BLUESKY => Ok(Box::new(Bluesky::new(instance_url, account, secret))),
+        WEBHOOK => Ok(Box::new(Webhook::new(instance_url, secret))),
+        SLACK => Ok(Box::new(Slack::new(secret))),
+        DISCORD => Ok(Box::new(Discord::new(secret))),
+        _ => anyhow::bail!("Unknown channel kind: {}", kind),
+    }
+}
+
+/// Send to one channel and record how it went on it
+pub async fn send_to(
+    ctx: &ChannelContext,
+    channel: notification_channel::Model,
+    event: ChannelEvent,
+    payload: &Payload,
+) -> Result<notification_channel::Model, DbErr> {
+    let result = match from_model(ctx, &channel).await {
+        Ok(client) => client.send(event, payload).await,
+        Err(e) => Err(e),
+    };
+
+    let mut active: notification_channel::ActiveModel = channel.clone().into();
+    match result {
+        Ok(()) => active.last_error = Set(None),
+        Err(e) => {
+            log::warn!(
+                "⚠️  [Channels] Failed to send {} to {} channel {}: {:#}",
+                event.as_str(),
+                channel.kind,
+                channel.id,
+                e
+            );
+            active.last_error = Set(Some(format!("{:#}", e)));
+        }
+    }
+    active.update(&ctx.db).await
+}
+
+/// Send `payload` to each of the user's channels that `event` is routed to.
+/// Returns how many succeeded.
+pub async fn dispatch(ctx: &ChannelContext, user_id: Uuid, event: ChannelEvent, payload: &Payload) -> Result<usize, DbErr> {
+    let channels = notification_channel::Entity::find()
+        .filter(notification_channel::Column::UserId.eq(user_id))
+        .all(&ctx.db)
+        .await?;
+
+    let mut sent = 0;
+    for channel in channels {
+        if !channel.events.iter().any(|e| e == event.as_str()) {
+            continue;
+        }
+        if send_to(ctx, channel, event, payload).await?.last_error.is_none() {
+            sent += 1;
+        }
+    }
+    Ok(sent)
+}
+
+/// The notification for a dashboard event, if channels can be told about it
+async fn payload_for(db: &DatabaseConnection, event: &DashboardEvent) -> Result<Option<(ChannelEvent, Payload)>, DbErr> {
+    match event {
+        DashboardEvent::MilestoneReached {
+            milestone_id,
+            kind,
+            title,
+            achieved_on,
+        } => Ok(Some((
+            ChannelEvent::Milestone,
+            Payload {
+                title: format!("Milestone reached: {}", title),
+                text: format!("🏆 {} ({})", title, achieved_on.format("%B %-d, %Y")),
+                image: None,
+                data: json!({ "kind": kind, "title": title, "achieved_on": achieved_on }),
+                idempotency_key: format!("hgitmap-milestone-{}", milestone_id),
+            },
+        ))),
+        DashboardEvent::SyncFinished {
+            job_id,
+            platform_account_id,
+            status: SyncJobStatus::Failed,
+            error,
+            ..
+        } => {
+            let account = git_platform_account::Entity::find_by_id(*platform_account_id)
+                .one(db)
+                .await?;
+            let name = account
+                .as_ref()
+                .map(|a| format!("{:?} account {}", a.platform_type, a.platform_username))
+                .unwrap_or_else(|| "account".to_string());
+            let error = error.clone().unwrap_or_else(|| "Unknown error".to_string());
+            Ok(Some((
+                ChannelEvent::SyncFailed,
+                Payload {
+                    title: format!("Syncing your {} failed", name),
+                    text: format!("⚠️ Syncing your {} failed: {}", name, error),
+                    image: None,
+                    data: json!({
+                        "platform_account_id": platform_account_id,
+                        "platform": account.as_ref().map(|a| a.platform_type.to_value()),
+                        "platform_username": account.as_ref().map(|a| a.platform_username.clone()),
+                        "error": error,
+                        "failed_at": Utc::now(),
+                    }),
+                    idempotency_key: format!("hgitmap-sync-failed-{}", job_id),
+                },
+            )))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Route dashboard events to the channels users picked for them. Each
+/// dispatch runs on its own, so a slow channel holds up no one else.
+pub fn route_events(ctx: ChannelContext, events: &EventBus) -> JoinHandle<()> {
+    let mut receiver = events.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if !matches!(
+                        event.event,
+                        DashboardEvent::MilestoneReached { .. }
+                            | DashboardEvent::SyncFinished {
+                                status: SyncJobStatus::Failed,
+                                ..
+                            }
+                    ) {
+                        continue;
+                    }
+                    let ctx = ctx.clone();
+                    tokio::spawn(async move {
+                        let result = async {
+                            let Some((kind, payload)) = payload_for(&ctx.db, &event.event).await? else {
+                                return Ok(0);
+                            };
+                            dispatch(&ctx, event.user_id, kind, &payload).await
+                        }
+                        .await;
+                        if let Err(e) = result {
+                            log::error!("Failed to notify channels of user {}: {}", event.user_id, e);
+                        }
+                    });
+                }
+                // Missed events are not sent; channels are notifications,
+                // not a record
+                Err(RecvError::Lagged(missed)) => {
+                    log::warn!("⚠️  [Channels] Missed {} events", missed);
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    })
+}
+
+/// The start of an error response body, for error messages
+pub(crate) async fn error_body(response: reqwest::Response) -> String {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    format!("{} {}", status, body.chars().take(200).collect::<String>())
+}
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::*;
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{ChannelEvent, NotificationChannel, Payload};
+use crate::models::mqtt_publisher;
+use crate::services::mqtt_publisher::{publish_event, ENABLED};
+
+/// Publishes events to the broker set up under MQTT publishing, next to the
+/// numbers published there, under `{topic}/events/{event}`
+pub struct Mqtt {
+    db: DatabaseConnection,
+    encryption_key: String,
+    user_id: Uuid,
+}
+
+impl Mqtt {
+    pub fn new(db: DatabaseConnection, encryption_key: String, user_id: Uuid) -> Self {
+        Self {
+            db,
+            encryption_key,
+            user_id,
+        }
+    }
+
+    async fn publisher(&self) -> Result<mqtt_publisher::Model> {
+        if !ENABLED {
+            anyhow::bail!("MQTT support is not enabled in this build");
+        }
+        mqtt_publisher::Entity::find_by_id(self.user_id)
+            .one(&self.db)
+            .await?
+            .context("Set up MQTT publishing first")
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Mqtt {
+    async fn verify(&self) -> Result<String> {
+        Ok(format!("{}/events", self.publisher().await?.topic))
+    }
+
+    async fn send(&self, event: ChannelEvent, payload: &Payload) -> Result<()> {
+        let publisher = self.publisher().await?;
+        let body = serde_json::to_vec(&json!({
+            "title": payload.title,
+            "text": payload.text,
+            "data": payload.data,
+            "sent_at": Utc::now(),
+        }))?;
+        publish_event(&self.encryption_key, &publisher, event.as_str(), body)
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::time::Duration;
+
+use super::{error_body, ChannelEvent, NotificationChannel, Payload, REQUEST_TIMEOUT_SECS};
+use crate::utils::http_client::{shared_client, SendTraced};
+
+/// Posts to a Slack channel through an incoming webhook (Apps → Incoming
+/// WebHooks). Slack only shows images it can fetch by URL, so none are sent.
+pub struct Slack {
+    webhook_url: String,
+}
+
+impl Slack {
+    pub fn new(webhook_url: String) -> Self {
+        Self { webhook_url }
+    }
+
+    async fn post(&self, text: String) -> Result<()> {
+        let response = shared_client()
+            .post(&self.webhook_url)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .json(&json!({ "text": text }))
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Slack rejected the message: {}", error_body(response).await);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Slack {
+    /// Incoming webhooks can only be checked by posting to them
+    async fn verify(&self) -> Result<String> {
+        self.post("hgitmap is connected to this channel.".to_string()).await?;
+        Ok("Slack".to_string())
+    }
+
+    async fn send(&self, _event: ChannelEvent, payload: &Payload) -> Result<()> {
+        self.post(format!("*{}*\n{}", payload.title, payload.text)).await
+    }
+}
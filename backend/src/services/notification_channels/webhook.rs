@@ -0,0 +1,63 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::time::Duration;
+
+use super::{error_body, ChannelEvent, NotificationChannel, Payload, REQUEST_TIMEOUT_SECS};
+use crate::utils::http_client::{shared_client, SendTraced};
+use crate::utils::notification_channels::webhook_signature;
+
+/// POSTs each event as JSON to a URL, signed with the channel's secret in
+/// the `X-Hgitmap-Signature` header so the receiver can tell it is genuine
+pub struct Webhook {
+    url: String,
+    signing_secret: String,
+}
+
+impl Webhook {
+    pub fn new(url: &str, signing_secret: String) -> Self {
+        Self {
+            url: url.to_string(),
+            signing_secret,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for Webhook {
+    /// Nothing is sent: the receiver can only check signatures once it has
+    /// the secret, which is handed out after the channel is created
+    async fn verify(&self) -> Result<String> {
+        Ok(reqwest::Url::parse(&self.url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    async fn send(&self, event: ChannelEvent, payload: &Payload) -> Result<()> {
+        // Signed as sent, so the receiver checks the exact bytes
+        let body = serde_json::to_vec(&json!({
+            "event": event.as_str(),
+            "title": payload.title,
+            "text": payload.text,
+            "data": payload.data,
+            "sent_at": Utc::now(),
+        }))?;
+
+        let response = shared_client()
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Hgitmap-Event", event.as_str())
+            .header("X-Hgitmap-Signature", webhook_signature(&self.signing_secret, &body))
+            .header("Idempotency-Key", &payload.idempotency_key)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .body(body)
+            .send_traced()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("The webhook answered {}", error_body(response).await);
+        }
+        Ok(())
+    }
+}
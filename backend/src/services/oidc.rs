@@ -0,0 +1,227 @@
+use anyhow::{anyhow, Context, Result};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::utils::config::Config;
+use crate::utils::http_client::{shared_client, SendTraced};
+use crate::utils::oidc_claims::OidcUser;
+
+/// Endpoints from the provider's discovery document
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    userinfo_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+    access_token: Option<String>,
+}
+
+struct Settings {
+    issuer_url: String,
+    client_id: String,
+    client_secret: String,
+    scopes: String,
+    groups_claim: String,
+    admin_group: Option<String>,
+}
+
+/// Single sign-on against a generic OpenID Connect provider such as
+/// Keycloak or Authentik. Disabled unless OIDC_ISSUER_URL is set.
+pub struct OidcClient {
+    settings: Option<Settings>,
+    display_name: String,
+    http: reqwest::Client,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcClient {
+    pub fn new(config: &Config) -> Self {
+        let settings = config.oidc_issuer_url.as_ref().map(|issuer_url| Settings {
+            issuer_url: issuer_url.trim_end_matches('/').to_string(),
+            client_id: config.oidc_client_id.clone(),
+            client_secret: config.oidc_client_secret.clone(),
+            scopes: config.oidc_scopes.clone(),
+            groups_claim: config.oidc_groups_claim.clone(),
+            admin_group: config.oidc_admin_group.clone(),
+        });
+
+        Self {
+            settings,
+            display_name: config.oidc_display_name.clone(),
+            http: shared_client(),
+            metadata: RwLock::new(None),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.is_some()
+    }
+
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    fn settings(&self) -> Result<&Settings> {
+        self.settings.as_ref().ok_or_else(|| anyhow!("OIDC is not configured"))
+    }
+
+    /// Discovery document, fetched once and cached
+    async fn metadata(&self) -> Result<ProviderMetadata> {
+        if let Some(metadata) = self.metadata.read().await.as_ref() {
+            return Ok(metadata.clone());
+        }
+
+        let settings = self.settings()?;
+        let url = format!("{}/.well-known/openid-configuration", settings.issuer_url);
+        log::info!("🔍 [OIDC] Fetching discovery document from {}", url);
+
+        let metadata: ProviderMetadata = self
+            .http
+            .get(&url)
+            .send_traced()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid OIDC discovery document")?;
+
+        *self.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Signing keys, refetched when a token uses a key we have not seen,
+    /// which is how providers roll keys over
+    async fn decoding_key(&self, metadata: &ProviderMetadata, kid: Option<&str>) -> Result<DecodingKey> {
+        for refresh in [false, true] {
+            if refresh || self.jwks.read().await.is_none() {
+                log::info!("🔑 [OIDC] Fetching signing keys from {}", metadata.jwks_uri);
+                let jwks: JwkSet = self
+                    .http
+                    .get(&metadata.jwks_uri)
+                    .send_traced()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+                    .context("Invalid OIDC key set")?;
+                *self.jwks.write().await = Some(jwks);
+            }
+
+            let jwks = self.jwks.read().await;
+            let Some(jwks) = jwks.as_ref() else { continue };
+            let jwk = match kid {
+                Some(kid) => jwks.find(kid),
+                None if jwks.keys.len() == 1 => jwks.keys.first(),
+                None => None,
+            };
+            if let Some(jwk) = jwk {
+                return Ok(DecodingKey::from_jwk(jwk)?);
+            }
+        }
+
+        Err(anyhow!("No OIDC signing key matches the ID token"))
+    }
+
+    /// URL of the provider's login page for this sign-in attempt
+    pub async fn authorization_url(&self, redirect_uri: &str, state: &str, nonce: &str) -> Result<String> {
+        let settings = self.settings()?;
+        let metadata = self.metadata().await?;
+
+        Ok(format!(
+            "{}?response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&nonce={}",
+            metadata.authorization_endpoint,
+            urlencoding::encode(&settings.client_id),
+            urlencoding::encode(redirect_uri),
+            urlencoding::encode(&settings.scopes),
+            urlencoding::encode(state),
+            urlencoding::encode(nonce)
+        ))
+    }
+
+    /// Exchange the authorization code, verify the ID token and read the
+    /// user's claims, including userinfo claims missing from the ID token
+    pub async fn authenticate(&self, code: &str, redirect_uri: &str, nonce: &str) -> Result<OidcUser> {
+        let settings = self.settings()?;
+        let metadata = self.metadata().await?;
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("client_id", settings.client_id.as_str()),
+                ("client_secret", settings.client_secret.as_str()),
+            ])
+            .send_traced()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("OIDC token request failed: {} - {}", status, error_text));
+        }
+
+        let tokens: TokenResponse = response.json().await.context("Invalid OIDC token response")?;
+
+        let header = decode_header(&tokens.id_token)?;
+        let key = self.decoding_key(&metadata, header.kid.as_deref()).await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&settings.client_id]);
+        validation.set_issuer(&[&metadata.issuer]);
+
+        let mut claims = decode::<Value>(&tokens.id_token, &key, &validation)?.claims;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            return Err(anyhow!("OIDC ID token nonce does not match"));
+        }
+
+        if let (Some(userinfo_endpoint), Some(access_token)) = (&metadata.userinfo_endpoint, &tokens.access_token) {
+            match self.userinfo(userinfo_endpoint, access_token).await {
+                Ok(Value::Object(userinfo)) if userinfo.get("sub") == claims.get("sub") => {
+                    if let Value::Object(claims) = &mut claims {
+                        for (name, value) in userinfo {
+                            claims.entry(name).or_insert(value);
+                        }
+                    }
+                }
+                Ok(_) => log::warn!("⚠️  [OIDC] Ignoring userinfo for a different subject"),
+                Err(e) => log::warn!("⚠️  [OIDC] Failed to fetch userinfo: {}", e),
+            }
+        }
+
+        OidcUser::from_claims(&claims, &settings.groups_claim)
+            .ok_or_else(|| anyhow!("OIDC ID token has no subject"))
+    }
+
+    async fn userinfo(&self, endpoint: &str, access_token: &str) -> Result<Value> {
+        Ok(self
+            .http
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .send_traced()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Whether group membership makes the user an admin, or None when no
+    /// admin group is configured
+    pub fn is_admin(&self, user: &OidcUser) -> Option<bool> {
+        let group = self.settings.as_ref()?.admin_group.as_ref()?;
+        Some(user.in_group(group))
+    }
+}
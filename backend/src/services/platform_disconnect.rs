@@ -0,0 +1,144 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::models::{git_platform_account, platform_account_disconnection};
+use crate::services::account_deletion;
+
+/// Disconnect a platform account. Its token is revoked and cleared, and the
+/// account is deactivated so its data drops out of heatmaps and profiles.
+/// The data is kept for `retention_days`, during which the account can be
+/// restored; with 0 days, or when the account is already disconnected, it is
+/// deleted right away. Returns when the account will be purged, or None if
+/// it was deleted.
+pub async fn disconnect(
+    db: &DatabaseConnection,
+    encryption_key: &str,
+    retention_days: i64,
+    account: git_platform_account::Model,
+) -> Result<Option<DateTime<Utc>>, DbErr> {
+    // Revoke the OAuth token on the platform while it is still stored
+    account_deletion::revoke_platform_token(db, encryption_key, &account).await;
+
+    let already_disconnected = !account.is_active
+        && platform_account_disconnection::Entity::find_by_id(account.id)
+            .one(db)
+            .await?
+            .is_some();
+    if retention_days <= 0 || already_disconnected {
+        // CASCADE deletes all contributions and activities
+        git_platform_account::Entity::delete_by_id(account.id).exec(db).await?;
+        log::info!("🗑️  Platform account {} and its data deleted", account.id);
+        return Ok(None);
+    }
+
+    let now = Utc::now();
+    let purge_after = now + Duration::days(retention_days);
+    let account_id = account.id;
+
+    let txn = db.begin().await?;
+    let mut active: git_platform_account::ActiveModel = account.into();
+    active.is_active = Set(false);
+    active.access_token = Set(None);
+    active.refresh_token = Set(None);
+    active.updated_at = Set(now);
+    active.update(&txn).await?;
+
+    platform_account_disconnection::Entity::insert(platform_account_disconnection::ActiveModel {
+        platform_account_id: Set(account_id),
+        disconnected_at: Set(now),
+        purge_after: Set(purge_after),
+    })
+    .on_conflict(
+        OnConflict::column(platform_account_disconnection::Column::PlatformAccountId)
+            .update_columns([
+                platform_account_disconnection::Column::DisconnectedAt,
+                platform_account_disconnection::Column::PurgeAfter,
+            ])
+            .to_owned(),
+    )
+    .exec(&txn)
+    .await?;
+    txn.commit().await?;
+
+    log::info!("🔌 Platform account {} disconnected, kept until {}", account_id, purge_after);
+    Ok(Some(purge_after))
+}
+
+/// A user's disconnected accounts that can still be restored, with when
+/// each was disconnected and will be purged
+pub async fn list(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> Result<Vec<(git_platform_account::Model, platform_account_disconnection::Model)>, DbErr> {
+    let rows = platform_account_disconnection::Entity::find()
+        .find_also_related(git_platform_account::Entity)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(false))
+        .order_by_desc(platform_account_disconnection::Column::DisconnectedAt)
+        .all(db)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(disconnection, account)| account.map(|account| (account, disconnection)))
+        .collect())
+}
+
+/// Reactivate a disconnected account with its contributions and activities.
+/// The token was revoked, so syncing resumes once the account is connected
+/// again. Returns None when the account is not disconnected.
+pub async fn restore(
+    db: &DatabaseConnection,
+    account: git_platform_account::Model,
+) -> Result<Option<git_platform_account::Model>, DbErr> {
+    if account.is_active {
+        return Ok(None);
+    }
+    let Some(disconnection) = platform_account_disconnection::Entity::find_by_id(account.id)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let txn = db.begin().await?;
+    let mut active: git_platform_account::ActiveModel = account.into();
+    active.is_active = Set(true);
+    active.updated_at = Set(Utc::now());
+    let account = active.update(&txn).await?;
+    disconnection.delete(&txn).await?;
+    txn.commit().await?;
+
+    log::info!("♻️  Platform account {} restored", account.id);
+    Ok(Some(account))
+}
+
+/// Delete disconnected accounts whose retention has ended. Records of
+/// accounts that were connected again are dropped without touching the
+/// account. A dry run only counts the accounts.
+pub async fn purge_expired(db: &DatabaseConnection, dry_run: bool) -> Result<u64, DbErr> {
+    let mut purged = 0;
+
+    for (disconnection, account) in platform_account_disconnection::Entity::find()
+        .find_also_related(git_platform_account::Entity)
+        .all(db)
+        .await?
+    {
+        let Some(account) = account else { continue };
+        if account.is_active {
+            // Reconnecting reactivates the account without going through restore
+            if !dry_run {
+                disconnection.delete(db).await?;
+            }
+        } else if disconnection.purge_after <= Utc::now() {
+            purged += 1;
+            if !dry_run {
+                git_platform_account::Entity::delete_by_id(account.id).exec(db).await?;
+            }
+        }
+    }
+
+    Ok(purged)
+}
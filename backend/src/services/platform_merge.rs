@@ -0,0 +1,187 @@
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{activity, contribution, generated_heatmap, git_platform_account};
+
+/// Most ids bound in one `IN (...)` list
+const ID_CHUNK_SIZE: usize = 500;
+
+/// What merging one platform account into another did
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct MergeReport {
+    /// Contribution days moved over as they were
+    pub contributions_moved: u64,
+    /// Days both accounts had for the same repository; the larger count was
+    /// kept
+    pub contributions_combined: u64,
+    pub activities_moved: u64,
+    /// Activities both accounts had; the one with the larger count was kept
+    pub activities_combined: u64,
+}
+
+/// Identity of an activity apart from its account, as in the feed's entry
+/// ids: commit summaries by month, everything else by day and item
+fn activity_identity(a: &activity::Model) -> (String, String, Option<String>, Option<String>, String) {
+    let period = match a.activity_type {
+        activity::ActivityType::Commit => a.activity_date.format("%Y-%m").to_string(),
+        _ => a.activity_date.format("%Y-%m-%d").to_string(),
+    };
+    let detail = a
+        .metadata
+        .get("url")
+        .or_else(|| a.metadata.get("number"))
+        .map(|v| v.to_string())
+        .unwrap_or_default();
+
+    (
+        format!("{:?}", a.activity_type),
+        period,
+        a.repository_name.clone(),
+        a.organization_name.clone(),
+        detail,
+    )
+}
+
+/// Move `source`'s contributions and activities to `target` and delete
+/// `source`. Where both accounts have the same contribution day or activity,
+/// which happens when both synced the same history, only the larger count is
+/// kept, so nothing is counted twice.
+pub async fn merge(
+    db: &DatabaseConnection,
+    source: &git_platform_account::Model,
+    target: &git_platform_account::Model,
+) -> Result<MergeReport, DbErr> {
+    let txn = db.begin().await?;
+    let mut report = MergeReport::default();
+    let now = chrono::Utc::now();
+
+    // Contributions are unique per account, day and repository
+    let existing: HashMap<_, _> = contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.eq(target.id))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|c| ((c.contribution_date, c.repository_name.clone()), c))
+        .collect();
+
+    let mut moved = Vec::new();
+    let mut combined = Vec::new();
+    for c in contribution::Entity::find()
+        .filter(contribution::Column::GitPlatformAccountId.eq(source.id))
+        .all(&txn)
+        .await?
+    {
+        match existing.get(&(c.contribution_date, c.repository_name.clone())) {
+            Some(kept) => {
+                if c.count > kept.count {
+                    contribution::Entity::update_many()
+                        .col_expr(contribution::Column::Count, Expr::value(c.count))
+                        .col_expr(contribution::Column::UpdatedAt, Expr::value(now))
+                        .filter(contribution::Column::Id.eq(kept.id))
+                        .exec(&txn)
+                        .await?;
+                }
+                combined.push(c.id);
+            }
+            None => moved.push(c.id),
+        }
+    }
+
+    for ids in moved.chunks(ID_CHUNK_SIZE) {
+        report.contributions_moved += contribution::Entity::update_many()
+            .col_expr(contribution::Column::GitPlatformAccountId, Expr::value(target.id))
+            .col_expr(contribution::Column::UpdatedAt, Expr::value(now))
+            .filter(contribution::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+    }
+    for ids in combined.chunks(ID_CHUNK_SIZE) {
+        report.contributions_combined += contribution::Entity::delete_many()
+            .filter(contribution::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+    }
+
+    // Duplicates are found by natural key, and by identity for activities
+    // stored before natural keys, which have none. A moved activity must not
+    // take a natural key the target already has.
+    let targets = activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.eq(target.id))
+        .all(&txn)
+        .await?;
+    let by_key: HashMap<_, _> = targets
+        .iter()
+        .filter_map(|a| Some((a.natural_key.clone()?, a.clone())))
+        .collect();
+    let existing: HashMap<_, _> = targets
+        .into_iter()
+        .map(|a| (activity_identity(&a), a))
+        .collect();
+
+    let mut moved = Vec::new();
+    let mut dropped: Vec<Uuid> = Vec::new();
+    for a in activity::Entity::find()
+        .filter(activity::Column::GitPlatformAccountId.eq(source.id))
+        .all(&txn)
+        .await?
+    {
+        let kept = a
+            .natural_key
+            .as_ref()
+            .and_then(|key| by_key.get(key))
+            .or_else(|| existing.get(&activity_identity(&a)));
+        match kept {
+            // The source's copy has the fuller count; it replaces the target's
+            Some(kept) if a.count > kept.count => {
+                dropped.push(kept.id);
+                moved.push(a.id);
+                report.activities_combined += 1;
+            }
+            Some(_) => {
+                dropped.push(a.id);
+                report.activities_combined += 1;
+            }
+            None => {
+                moved.push(a.id);
+                report.activities_moved += 1;
+            }
+        }
+    }
+
+    for ids in dropped.chunks(ID_CHUNK_SIZE) {
+        activity::Entity::delete_many()
+            .filter(activity::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?;
+    }
+    for ids in moved.chunks(ID_CHUNK_SIZE) {
+        activity::Entity::update_many()
+            .col_expr(activity::Column::GitPlatformAccountId, Expr::value(target.id))
+            .col_expr(activity::Column::UpdatedAt, Expr::value(now))
+            .filter(activity::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?;
+    }
+
+    // Nothing is left on the source; its sync jobs, label and visibility go
+    // with it
+    git_platform_account::Entity::delete_by_id(source.id)
+        .exec(&txn)
+        .await?;
+
+    // Heatmaps drawn before still split the history
+    generated_heatmap::Entity::update_many()
+        .col_expr(generated_heatmap::Column::IsValid, Expr::value(false))
+        .filter(generated_heatmap::Column::UserId.eq(target.user_id))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+    Ok(report)
+}
@@ -6,21 +6,36 @@ use std::collections::HashMap;
 use serde_json::json;
 
 use crate::models::{contribution, git_platform_account, heatmap_theme, activity};
+use crate::services::{activity_store, commit_details};
+use crate::services::api_usage;
 use crate::services::heatmap_generator::HeatmapGenerator;
-use crate::services::git_platforms::{github::GitHubClient, gitea::GiteaClient, GitPlatform, PlatformConfig, Contribution, Activity, ActivityType};
-use crate::utils::{config::Config, encryption};
+use crate::services::git_platforms::{github::GitHubClient, gitea::GiteaClient, gitlab::GitLabClient, GitPlatform, PlatformConfig, Contribution, Activity, ActivityType};
+use crate::utils::{config::Config, db, encryption};
+
+/// Month-keyed commit aggregation: (repositories, total count, has private, latest date)
+type MonthlyCommits = HashMap<(i32, u32), (Vec<serde_json::Value>, i32, bool, chrono::NaiveDate)>;
 
 pub struct PlatformSyncService {
     db: DatabaseConnection,
     config: Config,
+    /// Store activities along with contributions
+    sync_activities: bool,
 }
 
 impl PlatformSyncService {
     pub fn new(db: DatabaseConnection, config: Config) -> Self {
-        Self { db, config }
+        Self { db, config, sync_activities: true }
+    }
+
+    /// Sync only contributions when `enabled` is false, as the instance's
+    /// `sync_activities` setting asks
+    pub fn with_activities(mut self, enabled: bool) -> Self {
+        self.sync_activities = enabled;
+        self
     }
 
     /// Sync all active platform accounts for a user (current year only)
+    #[tracing::instrument(skip_all, fields(user.id = %user_id))]
     pub async fn sync_user_data(&self, user_id: Uuid) -> Result<SyncResult> {
         log::info!("Starting sync for user: {}", user_id);
 
@@ -55,7 +70,7 @@ impl PlatformSyncService {
         for account in &accounts {
             // First, sync profile data if enabled
             if account.sync_profile {
-                if let Err(e) = self.sync_profile_data(account).await {
+                if let Err(e) = api_usage::track(&self.db, account.id, self.sync_profile_data(account)).await {
                     let error_msg = format!(
                         "Failed to sync profile for {}: {}",
                         account.platform_username, e
@@ -69,7 +84,8 @@ impl PlatformSyncService {
 
             // Then sync contribution data if enabled
             if account.sync_contributions {
-                match self.sync_platform_account(account, start_date, end_date).await {
+                let synced = self.sync_platform_account(account, start_date, end_date);
+                match api_usage::track(&self.db, account.id, synced).await {
                 Ok(stats) => {
                     result.platforms_synced += 1;
                     result.contributions_added += stats.added;
@@ -127,6 +143,7 @@ impl PlatformSyncService {
     }
 
     /// Sync a single platform account (unified: contributions + activities)
+    #[tracing::instrument(skip_all, fields(account.id = %account.id, platform = ?account.platform_type))]
     async fn sync_platform_account(
         &self,
         account: &git_platform_account::Model,
@@ -139,52 +156,54 @@ impl PlatformSyncService {
         };
 
         // ========================================
-        // STEP 1: Delete existing contributions in the date range
+        // STEP 1: Fetch fresh contributions from platform
         // ========================================
-        log::info!("🗑️  Deleting existing contributions from {} to {}", start_date, end_date);
-
-        let deleted = contribution::Entity::delete_many()
-            .filter(contribution::Column::GitPlatformAccountId.eq(account.id))
-            .filter(contribution::Column::ContributionDate.gte(start_date))
-            .filter(contribution::Column::ContributionDate.lte(end_date))
-            .exec(&self.db)
-            .await?;
-
-        log::info!("🗑️  Deleted {} existing contributions in date range", deleted.rows_affected);
-
-        // ========================================
-        // STEP 2: Fetch fresh contributions from platform
-        // ========================================
-        let contributions = self.fetch_contributions_from_platform(account, start_date, end_date).await?;
+        let mut contributions = self.fetch_contributions_from_platform(account, start_date, end_date).await?;
 
         log::info!("📊 Fetched {} fresh contribution records", contributions.len());
 
+        // Commits the user co-authored, found by earlier detailed syncs
+        commit_details::add_co_authored(&self.db, account, &mut contributions, start_date, end_date).await?;
+
         // ========================================
-        // STEP 3: Insert all fresh contributions
+        // STEP 2: Replace the contributions in the date range
+        // One transaction, so a failed sync keeps the old data
         // ========================================
-        for contrib in &contributions {
-            let new_contrib = contribution::ActiveModel {
+        let now = Utc::now();
+        let new_contribs: Vec<_> = contributions
+            .iter()
+            .map(|contrib| contribution::ActiveModel {
                 id: Set(Uuid::new_v4()),
                 git_platform_account_id: Set(account.id),
                 contribution_date: Set(contrib.date),
                 count: Set(contrib.count),
                 repository_name: Set(contrib.repository_name.clone()),
                 is_private_repo: Set(contrib.is_private),
-                created_at: Set(Utc::now()),
-                updated_at: Set(Utc::now()),
-            };
-            contribution::Entity::insert(new_contrib).exec(&self.db).await?;
-            stats.added += 1;
-        }
+                created_at: Set(now),
+                updated_at: Set(now),
+            })
+            .collect();
 
+        let txn = self.db.begin().await?;
+        let deleted = contribution::Entity::delete_many()
+            .filter(contribution::Column::GitPlatformAccountId.eq(account.id))
+            .filter(contribution::Column::ContributionDate.gte(start_date))
+            .filter(contribution::Column::ContributionDate.lte(end_date))
+            .exec(&txn)
+            .await?;
+        stats.added = db::insert_chunked(&txn, new_contribs).await? as i32;
+        txn.commit().await?;
+
+        log::info!("🗑️  Replaced {} existing contributions from {} to {}", deleted.rows_affected, start_date, end_date);
         log::info!("✅ Inserted {} fresh contributions", stats.added);
 
         // ========================================
         // PART 2: Sync activities using the same contribution data
-        // Activities always sync together with contributions
+        // Activities sync together with contributions, unless the
+        // instance turned them off
         // ========================================
-        if account.sync_contributions {
-            log::info!("📅 Syncing activities for timeline (always syncs with contributions)...");
+        if account.sync_contributions && self.sync_activities {
+            log::info!("📅 Syncing activities for timeline (syncs with contributions)...");
 
             let from = chrono::NaiveDateTime::new(start_date, chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap()).and_utc();
             let to = chrono::NaiveDateTime::new(end_date, chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap()).and_utc();
@@ -192,6 +211,8 @@ impl PlatformSyncService {
             if let Err(e) = self.sync_activities_from_contributions(account, &contributions, from, to).await {
                 log::error!("Failed to sync activities: {}", e);
             }
+        } else if !self.sync_activities {
+            log::debug!("Activity sync is turned off on this instance, skipping activities for {}", account.platform_username);
         } else {
             log::debug!("Contribution sync disabled for {}, skipping activities (always synced together)", account.platform_username);
         }
@@ -287,8 +308,17 @@ impl PlatformSyncService {
                     .context("Failed to fetch Gitea contributions")?
             }
             git_platform_account::GitPlatform::GitLab => {
-                log::warn!("GitLab sync not yet implemented");
-                return Ok(Vec::new());
+                let client = GitLabClient::new();
+                let config = match account.platform_url.as_ref() {
+                    Some(url) => PlatformConfig::gitlab_custom(url),
+                    None => PlatformConfig::gitlab(),
+                };
+
+                log::info!("Fetching GitLab contributions for {} from {}", account.platform_username, config.instance_url);
+
+                client.fetch_contributions(&config, &account.platform_username, &decrypted_token, from, to)
+                    .await
+                    .context("Failed to fetch GitLab contributions")?
             }
         };
 
@@ -374,23 +404,13 @@ impl PlatformSyncService {
         let from_date = from.naive_utc().date();
         let to_date = to.naive_utc().date();
 
-        // Delete existing activities in the date range
-        let deleted = activity::Entity::delete_many()
-            .filter(activity::Column::GitPlatformAccountId.eq(account.id))
-            .filter(activity::Column::ActivityDate.gte(from_date))
-            .filter(activity::Column::ActivityDate.lte(to_date))
-            .exec(&self.db)
-            .await?;
-
-        log::info!("🗑️  Deleted {} existing activities in date range", deleted.rows_affected);
-
         // Aggregate commits by month from contribution data (no additional API calls!)
-        let mut commits_by_month: HashMap<(i32, u32), (Vec<serde_json::Value>, i32, bool, chrono::NaiveDate)> = HashMap::new();
+        let mut commits_by_month = MonthlyCommits::new();
 
         for contribution in contributions {
             if contribution.count > 0 {
                 // Skip contributions without repository names
-                let Some(ref repo_name) = contribution.repository_name else {
+                let Some(repo_name) = contribution.repository() else {
                     log::debug!("Skipping contribution on {} - no repository name available", contribution.date);
                     continue;
                 };
@@ -436,9 +456,11 @@ impl PlatformSyncService {
 
         log::info!("📊 Aggregated contributions into {} month activities", commits_by_month.len());
 
-        // Store commit activities
+        // Build commit activities
+        let now = Utc::now();
+        let mut activities = Vec::new();
         for ((year, month), (repos, total_count, has_private, latest_date)) in commits_by_month {
-            let activity_model = activity::ActiveModel {
+            activities.push(activity::ActiveModel {
                 id: Set(Uuid::new_v4()),
                 git_platform_account_id: Set(account.id),
                 activity_type: Set(activity::ActivityType::Commit),
@@ -456,11 +478,11 @@ impl PlatformSyncService {
                 primary_language: Set(None),
                 organization_name: Set(None),
                 organization_avatar_url: Set(None),
-                created_at: Set(Utc::now()),
-                updated_at: Set(Utc::now()),
-            };
-
-            activity::Entity::insert(activity_model).exec(&self.db).await?;
+                natural_key: NotSet,
+                imported: Set(false),
+                created_at: Set(now),
+                updated_at: Set(now),
+            });
         }
 
         // Fetch additional activity types (repos created, PRs, issues, orgs) - only for GitHub for now
@@ -479,9 +501,7 @@ impl PlatformSyncService {
                 &config, &account.platform_username, &token, from, to
             ).await {
                 log::info!("Found {} repository creation activities", repo_activities.len());
-                for activity in repo_activities {
-                    self.store_activity(account.id, activity).await?;
-                }
+                activities.extend(repo_activities.into_iter().map(|a| new_activity(account.id, a)));
             }
 
             // Fetch PR and issue activities
@@ -490,49 +510,21 @@ impl PlatformSyncService {
                 &config, &account.platform_username, &token, from, to
             ).await {
                 log::info!("Found {} PR/issue activities", pr_issue_activities.len());
-                for activity in pr_issue_activities {
-                    self.store_activity(account.id, activity).await?;
-                }
+                activities.extend(pr_issue_activities.into_iter().map(|a| new_activity(account.id, a)));
             }
         }
 
-        log::info!("✅ Activities sync completed");
-
-        Ok(())
-    }
+        // Replace the activities in the date range in one transaction
+        let scope = Condition::all()
+            .add(activity::Column::ActivityDate.gte(from_date))
+            .add(activity::Column::ActivityDate.lte(to_date));
+        let txn = self.db.begin().await?;
+        let stats = activity_store::replace(&txn, account.id, scope, activities).await?;
+        txn.commit().await?;
 
-    /// Store a single activity
-    async fn store_activity(&self, account_id: Uuid, activity: Activity) -> Result<()> {
-        let db_activity_type = match activity.activity_type {
-            ActivityType::Commit => activity::ActivityType::Commit,
-            ActivityType::RepositoryCreated => activity::ActivityType::RepositoryCreated,
-            ActivityType::PullRequest => activity::ActivityType::PullRequest,
-            ActivityType::Issue => activity::ActivityType::Issue,
-            ActivityType::Review => activity::ActivityType::Review,
-            ActivityType::OrganizationJoined => activity::ActivityType::OrganizationJoined,
-            ActivityType::Fork => activity::ActivityType::Fork,
-            ActivityType::Release => activity::ActivityType::Release,
-            ActivityType::Star => activity::ActivityType::Star,
-        };
+        log::info!("🗑️  Stored {} activities in date range, removed {} stale ones", stats.stored, stats.removed);
 
-        let activity_model = activity::ActiveModel {
-            id: Set(Uuid::new_v4()),
-            git_platform_account_id: Set(account_id),
-            activity_type: Set(db_activity_type),
-            activity_date: Set(activity.date),
-            metadata: Set(activity.metadata),
-            repository_name: Set(activity.repository_name),
-            repository_url: Set(activity.repository_url),
-            is_private_repo: Set(activity.is_private),
-            count: Set(activity.count),
-            primary_language: Set(activity.primary_language),
-            organization_name: Set(activity.organization_name),
-            organization_avatar_url: Set(activity.organization_avatar_url),
-            created_at: Set(Utc::now()),
-            updated_at: Set(Utc::now()),
-        };
-
-        activity::Entity::insert(activity_model).exec(&self.db).await?;
+        log::info!("✅ Activities sync completed");
 
         Ok(())
     }
@@ -591,3 +583,37 @@ struct SyncStats {
     added: i32,
     updated: i32,
 }
+
+/// Row for an activity fetched from a platform
+fn new_activity(account_id: Uuid, activity: Activity) -> activity::ActiveModel {
+    let db_activity_type = match activity.activity_type {
+        ActivityType::Commit => activity::ActivityType::Commit,
+        ActivityType::RepositoryCreated => activity::ActivityType::RepositoryCreated,
+        ActivityType::PullRequest => activity::ActivityType::PullRequest,
+        ActivityType::Issue => activity::ActivityType::Issue,
+        ActivityType::Review => activity::ActivityType::Review,
+        ActivityType::OrganizationJoined => activity::ActivityType::OrganizationJoined,
+        ActivityType::Fork => activity::ActivityType::Fork,
+        ActivityType::Release => activity::ActivityType::Release,
+        ActivityType::Star => activity::ActivityType::Star,
+    };
+
+    activity::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        git_platform_account_id: Set(account_id),
+        activity_type: Set(db_activity_type),
+        activity_date: Set(activity.date),
+        metadata: Set(activity.metadata),
+        repository_name: Set(activity.repository_name),
+        repository_url: Set(activity.repository_url),
+        is_private_repo: Set(activity.is_private),
+        count: Set(activity.count),
+        primary_language: Set(activity.primary_language),
+        organization_name: Set(activity.organization_name),
+        organization_avatar_url: Set(activity.organization_avatar_url),
+        natural_key: NotSet,
+        imported: Set(false),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+    }
+}
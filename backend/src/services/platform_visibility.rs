@@ -0,0 +1,105 @@
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{git_platform_account, platform_account_visibility};
+
+/// What public profile endpoints show of one platform account
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+pub struct AccountVisibility {
+    /// Listed on the public profile, along with its activities
+    pub is_public: bool,
+    /// Public listings include when the account last synced
+    pub show_last_synced_at: bool,
+}
+
+/// Accounts nobody configured are listed, without their sync time
+impl Default for AccountVisibility {
+    fn default() -> Self {
+        Self {
+            is_public: true,
+            show_last_synced_at: false,
+        }
+    }
+}
+
+impl From<&platform_account_visibility::Model> for AccountVisibility {
+    fn from(row: &platform_account_visibility::Model) -> Self {
+        Self {
+            is_public: row.is_public,
+            show_last_synced_at: row.show_last_synced_at,
+        }
+    }
+}
+
+/// Load one account's visibility
+pub async fn find(db: &DatabaseConnection, account_id: Uuid) -> Result<AccountVisibility, DbErr> {
+    Ok(platform_account_visibility::Entity::find_by_id(account_id)
+        .one(db)
+        .await?
+        .map(|row| AccountVisibility::from(&row))
+        .unwrap_or_default())
+}
+
+/// Visibility of each of `account_ids`
+pub async fn for_accounts(
+    db: &DatabaseConnection,
+    account_ids: &[Uuid],
+) -> Result<HashMap<Uuid, AccountVisibility>, DbErr> {
+    let rows = platform_account_visibility::Entity::find()
+        .filter(platform_account_visibility::Column::AccountId.is_in(account_ids.iter().copied()))
+        .all(db)
+        .await?;
+
+    let mut visibility: HashMap<Uuid, AccountVisibility> = account_ids
+        .iter()
+        .map(|id| (*id, AccountVisibility::default()))
+        .collect();
+    for row in &rows {
+        visibility.insert(row.account_id, row.into());
+    }
+    Ok(visibility)
+}
+
+/// The user's accounts hidden from their public profile
+pub async fn hidden_accounts(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<Uuid>, DbErr> {
+    platform_account_visibility::Entity::find()
+        .select_only()
+        .column(platform_account_visibility::Column::AccountId)
+        .inner_join(git_platform_account::Entity)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(platform_account_visibility::Column::IsPublic.eq(false))
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+/// Save an account's visibility
+pub async fn save(
+    db: &DatabaseConnection,
+    account_id: Uuid,
+    visibility: AccountVisibility,
+) -> Result<(), DbErr> {
+    let row = platform_account_visibility::ActiveModel {
+        account_id: Set(account_id),
+        is_public: Set(visibility.is_public),
+        show_last_synced_at: Set(visibility.show_last_synced_at),
+        updated_at: Set(chrono::Utc::now()),
+    };
+
+    platform_account_visibility::Entity::insert(row)
+        .on_conflict(
+            sea_query::OnConflict::column(platform_account_visibility::Column::AccountId)
+                .update_columns([
+                    platform_account_visibility::Column::IsPublic,
+                    platform_account_visibility::Column::ShowLastSyncedAt,
+                    platform_account_visibility::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+    Ok(())
+}
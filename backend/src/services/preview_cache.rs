@@ -0,0 +1,52 @@
+use sha2::{Digest, Sha256};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::heatmap_theme::HeatmapFormat;
+use crate::services::heatmap_generator::HeatmapData;
+use crate::utils::config::Config;
+use crate::utils::ttl_cache::TtlCache;
+
+/// Previews kept at once; the least recently used makes room
+const MAX_ENTRIES: usize = 200;
+
+/// Rendered theme previews, so the editor sending the same theme again (an
+/// undo, or switching back to a format) gets it without rendering
+pub struct PreviewCache {
+    entries: TtlCache<String, Arc<Vec<u8>>>,
+}
+
+impl PreviewCache {
+    pub fn from_config(config: &Config) -> Self {
+        if config.preview_cache_ttl_secs == 0 {
+            log::info!("Preview cache disabled");
+        }
+        Self {
+            entries: TtlCache::new(Duration::from_secs(config.preview_cache_ttl_secs), MAX_ENTRIES),
+        }
+    }
+
+    /// The key of a preview: the theme as the editor sent it, the data it is
+    /// drawn from, the format, and the name drawn on it
+    pub fn key(theme_json: &[u8], data: &HeatmapData, format: &HeatmapFormat, username: &str) -> String {
+        let mut data_hasher = DefaultHasher::new();
+        data.hash(&mut data_hasher);
+
+        let mut hasher = Sha256::new();
+        hasher.update(theme_json);
+        hasher.update(data_hasher.finish().to_be_bytes());
+        hasher.update(format!("{:?}\n{}", format, username));
+        hex::encode(hasher.finalize())
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<Vec<u8>>> {
+        self.entries.get(&key.to_string())
+    }
+
+    pub fn insert(&self, key: String, content: Vec<u8>) -> Arc<Vec<u8>> {
+        let content = Arc::new(content);
+        self.entries.insert(key, content.clone());
+        content
+    }
+}
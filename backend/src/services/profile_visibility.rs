@@ -0,0 +1,82 @@
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::{HttpRequest, HttpResponse};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::models::user_setting;
+use crate::services::sessions;
+use crate::utils::auth::decode_jwt;
+use crate::utils::config::Config;
+
+/// Values of `user_settings.profile_visibility`
+pub const PUBLIC: &str = "public";
+/// Served to anyone with the link but kept out of listings and search engines
+pub const UNLISTED: &str = "unlisted";
+/// Only served to the owner's own session
+pub const PRIVATE: &str = "private";
+
+pub const VALUES: &[&str] = &[PUBLIC, UNLISTED, PRIVATE];
+
+/// The user's visibility, public when they have no settings yet
+pub fn of(settings: Option<&user_setting::Model>) -> &str {
+    settings.map_or(PUBLIC, |s| s.profile_visibility.as_str())
+}
+
+/// Load a user's visibility
+pub async fn find(db: &DatabaseConnection, user_id: Uuid) -> Result<String, DbErr> {
+    Ok(user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .map_or_else(|| PUBLIC.to_string(), |s| s.profile_visibility))
+}
+
+/// The user whose JWT session sent the request, if any. Public endpoints
+/// have no auth middleware, so the header is checked here.
+pub async fn viewer(db: &DatabaseConnection, config: &Config, req: &HttpRequest) -> Result<Option<Uuid>, DbErr> {
+    let Some(token) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+    else {
+        return Ok(None);
+    };
+
+    let Ok(claims) = decode_jwt(token, &config.jwt_secret) else {
+        return Ok(None);
+    };
+
+    if let Some(sid) = &claims.sid {
+        let session_id = Uuid::parse_str(sid).unwrap_or_default();
+        if !sessions::is_active(db, session_id).await? {
+            return Ok(None);
+        }
+    }
+
+    Ok(Uuid::parse_str(&claims.sub).ok())
+}
+
+/// Whether the request may read `owner_id`'s profile
+pub async fn can_view(
+    db: &DatabaseConnection,
+    config: &Config,
+    req: &HttpRequest,
+    owner_id: Uuid,
+    visibility: &str,
+) -> Result<bool, DbErr> {
+    if visibility != PRIVATE {
+        return Ok(true);
+    }
+    Ok(viewer(db, config, req).await? == Some(owner_id))
+}
+
+/// Ask search engines not to index an unlisted profile
+pub fn mark_unlisted(response: &mut HttpResponse, visibility: &str) {
+    if visibility == UNLISTED {
+        response.headers_mut().insert(
+            HeaderName::from_static("x-robots-tag"),
+            HeaderValue::from_static("noindex"),
+        );
+    }
+}
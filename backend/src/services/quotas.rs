@@ -0,0 +1,222 @@
+use actix_web::http::header;
+use actix_web::HttpResponse;
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{generated_heatmap, git_platform_account, heatmap_theme};
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::rate_limiter::{Budget, RateLimiter};
+use crate::utils::config::Config;
+
+/// A per-user limit the request would exceed
+#[derive(Debug)]
+pub struct QuotaExceeded {
+    pub message: String,
+    /// Set for rate quotas, which free up over time
+    pub retry_after_secs: Option<u64>,
+}
+
+impl QuotaExceeded {
+    fn limit(message: String) -> Self {
+        Self {
+            message,
+            retry_after_secs: None,
+        }
+    }
+
+    /// 429 with Retry-After for rate quotas, 403 for the others
+    pub fn response(&self) -> HttpResponse {
+        match self.retry_after_secs {
+            Some(retry_after) => HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after))
+                .json(serde_json::json!({
+                    "error": self.message,
+                    "retry_after": retry_after,
+                })),
+            None => HttpResponse::Forbidden().json(serde_json::json!({ "error": self.message })),
+        }
+    }
+}
+
+/// Whether the user may connect (or restore) another platform account
+pub async fn check_platform_accounts(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+) -> Result<Option<QuotaExceeded>, DbErr> {
+    if config.max_platform_accounts == 0 {
+        return Ok(None);
+    }
+
+    // Disconnected accounts waiting to be purged don't count
+    let count = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .count(db)
+        .await?;
+
+    Ok((count >= config.max_platform_accounts).then(|| {
+        QuotaExceeded::limit(format!(
+            "You can connect at most {} platform accounts",
+            config.max_platform_accounts
+        ))
+    }))
+}
+
+/// Whether the user may create another theme
+pub async fn check_themes(
+    db: &DatabaseConnection,
+    config: &Config,
+    user_id: Uuid,
+) -> Result<Option<QuotaExceeded>, DbErr> {
+    if config.max_themes == 0 {
+        return Ok(None);
+    }
+
+    let count = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user_id))
+        .count(db)
+        .await?;
+
+    Ok((count >= config.max_themes).then(|| {
+        QuotaExceeded::limit(format!("You can create at most {} themes", config.max_themes))
+    }))
+}
+
+/// Bytes used by the user's generated heatmap files
+pub async fn storage_used(db: &DatabaseConnection, user_id: Uuid) -> Result<u64, DbErr> {
+    Ok(storage_by_format(db, user_id).await?.values().map(|usage| usage.bytes).sum())
+}
+
+/// Generated heatmap files and their bytes per format
+async fn storage_by_format(db: &DatabaseConnection, user_id: Uuid) -> Result<BTreeMap<String, FormatUsage>, DbErr> {
+    let files: Vec<(heatmap_theme::HeatmapFormat, Option<i64>)> = generated_heatmap::Entity::find()
+        .select_only()
+        .column(generated_heatmap::Column::Format)
+        .column(generated_heatmap::Column::FileSizeBytes)
+        .filter(generated_heatmap::Column::UserId.eq(user_id))
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut by_format = BTreeMap::<String, FormatUsage>::new();
+    for (format, size) in files {
+        let usage = by_format.entry(format.to_value()).or_default();
+        usage.files += 1;
+        usage.bytes += size.unwrap_or(0).max(0) as u64;
+    }
+    Ok(by_format)
+}
+
+/// Whether the user may generate more heatmap files. The limit is the
+/// `max_storage_mb` instance setting.
+pub async fn check_storage(
+    db: &DatabaseConnection,
+    instance: &InstanceSettingsService,
+    user_id: Uuid,
+) -> Result<Option<QuotaExceeded>, DbErr> {
+    let max_storage_mb = instance.current().await.max_storage_mb;
+    if max_storage_mb == 0 {
+        return Ok(None);
+    }
+
+    let used = storage_used(db, user_id).await?;
+    Ok((used >= max_storage_mb * 1024 * 1024).then(|| {
+        QuotaExceeded::limit(format!(
+            "Your generated heatmaps use the full {} MB of storage; delete themes or formats to free space",
+            max_storage_mb
+        ))
+    }))
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct FormatUsage {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StorageUsage {
+    /// Bytes of all generated heatmap files
+    pub used_bytes: u64,
+    /// None when storage is unlimited
+    pub limit_bytes: Option<u64>,
+    /// Keyed by format: svg, png, jpeg or webp
+    pub by_format: BTreeMap<String, FormatUsage>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CountUsage {
+    pub used: u64,
+    /// None when unlimited
+    pub limit: Option<u64>,
+}
+
+/// A user's usage of everything that has a per-user limit
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Usage {
+    pub storage: StorageUsage,
+    pub themes: CountUsage,
+    pub platform_accounts: CountUsage,
+}
+
+pub async fn usage(
+    db: &DatabaseConnection,
+    config: &Config,
+    instance: &InstanceSettingsService,
+    user_id: Uuid,
+) -> Result<Usage, DbErr> {
+    let by_format = storage_by_format(db, user_id).await?;
+    let max_storage_mb = instance.current().await.max_storage_mb;
+    let themes = heatmap_theme::Entity::find()
+        .filter(heatmap_theme::Column::UserId.eq(user_id))
+        .count(db)
+        .await?;
+    let platform_accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .count(db)
+        .await?;
+
+    Ok(Usage {
+        storage: StorageUsage {
+            used_bytes: by_format.values().map(|usage| usage.bytes).sum(),
+            limit_bytes: (max_storage_mb > 0).then_some(max_storage_mb * 1024 * 1024),
+            by_format,
+        },
+        themes: CountUsage {
+            used: themes,
+            limit: (config.max_themes > 0).then_some(config.max_themes),
+        },
+        platform_accounts: CountUsage {
+            used: platform_accounts,
+            limit: (config.max_platform_accounts > 0).then_some(config.max_platform_accounts),
+        },
+    })
+}
+
+/// Take one manual sync from the user's hourly allowance. A broken limiter
+/// backend lets the sync through.
+pub async fn check_manual_sync(limiter: &RateLimiter, config: &Config, user_id: Uuid) -> Option<QuotaExceeded> {
+    if config.max_manual_syncs_per_hour == 0 {
+        return None;
+    }
+
+    match limiter.check(Budget::ManualSync, &user_id.to_string()).await {
+        Ok(decision) if !decision.allowed => Some(QuotaExceeded {
+            message: format!(
+                "You can start at most {} manual syncs per hour",
+                config.max_manual_syncs_per_hour
+            ),
+            retry_after_secs: Some(decision.retry_after_secs),
+        }),
+        Ok(_) => None,
+        Err(e) => {
+            log::error!("❌ Failed to check manual sync quota: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::config::Config;
+use crate::utils::token_bucket::{BucketLimit, Decision, TokenBucket};
+
+/// Number of in-memory buckets above which idle ones are pruned
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Route groups with their own request budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Budget {
+    /// Registration and login
+    Auth,
+    /// Public profile endpoints under /users
+    Public,
+    /// Embedded heatmap images
+    Embed,
+    /// Manually triggered syncs, a per-user quota rather than a route budget
+    ManualSync,
+}
+
+impl Budget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Budget::Auth => "auth",
+            Budget::Public => "public",
+            Budget::Embed => "embed",
+            Budget::ManualSync => "manual_sync",
+        }
+    }
+}
+
+/// Storage for token buckets, shared by all workers of this process or, with
+/// Redis, by all instances
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn take(&self, key: &str, limit: &BucketLimit) -> Result<Decision, String>;
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+#[derive(Default)]
+pub struct InMemoryStore {
+    buckets: Mutex<HashMap<String, (TokenBucket, BucketLimit)>>,
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryStore {
+    async fn take(&self, key: &str, limit: &BucketLimit) -> Result<Decision, String> {
+        let now = now_secs();
+        let mut buckets = self.buckets.lock().map_err(|e| e.to_string())?;
+
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, (bucket, limit)| !bucket.is_idle(limit, now));
+        }
+
+        let (bucket, _) = buckets
+            .entry(key.to_string())
+            .or_insert_with(|| (TokenBucket::full(limit, now), *limit));
+        Ok(bucket.take(limit, now))
+    }
+}
+
+#[cfg(feature = "redis")]
+pub struct RedisStore {
+    connection: redis::aio::ConnectionManager,
+    script: redis::Script,
+}
+
+#[cfg(feature = "redis")]
+impl RedisStore {
+    pub async fn connect(url: &str) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(url)?;
+        let connection = redis::aio::ConnectionManager::new(client).await?;
+
+        // Same refill-then-take logic as TokenBucket, done atomically in Redis
+        let script = redis::Script::new(
+            r#"
+            local capacity = tonumber(ARGV[1])
+            local rate = tonumber(ARGV[2])
+            local now = tonumber(ARGV[3])
+            local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+            local tokens = tonumber(state[1]) or capacity
+            local ts = tonumber(state[2]) or now
+            tokens = math.min(capacity, tokens + math.max(0, now - ts) * rate)
+            local allowed = 0
+            if tokens >= 1 then
+                tokens = tokens - 1
+                allowed = 1
+            end
+            redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', tostring(now))
+            redis.call('EXPIRE', KEYS[1], math.ceil(capacity / rate) + 1)
+            return {allowed, tostring(tokens)}
+            "#,
+        );
+
+        Ok(Self { connection, script })
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn take(&self, key: &str, limit: &BucketLimit) -> Result<Decision, String> {
+        let mut connection = self.connection.clone();
+        let (allowed, tokens): (i32, String) = self
+            .script
+            .key(format!("hgitmap:ratelimit:{}", key))
+            .arg(limit.capacity)
+            .arg(limit.refill_per_sec)
+            .arg(now_secs())
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let tokens: f64 = tokens.parse().map_err(|_| "Invalid token count from Redis".to_string())?;
+        Ok(Decision::new(allowed == 1, tokens, limit))
+    }
+}
+
+/// Per-route-group rate limiting, keyed by user or client address
+pub struct RateLimiter {
+    /// None when neither rate limiting nor the manual sync quota is enabled
+    store: Option<Arc<dyn RateLimitStore>>,
+    limits: HashMap<Budget, BucketLimit>,
+    /// Whether the per-route budgets are enforced
+    enabled: bool,
+}
+
+impl RateLimiter {
+    /// Build the limiter from config, using Redis when `REDIS_URL` is set and
+    /// the `redis` feature is enabled
+    pub async fn from_config(config: &Config) -> Self {
+        let limits = HashMap::from([
+            (Budget::Auth, BucketLimit::per_minute(config.rate_limit_auth_per_minute)),
+            (Budget::Public, BucketLimit::per_minute(config.rate_limit_public_per_minute)),
+            (Budget::Embed, BucketLimit::per_minute(config.rate_limit_embed_per_minute)),
+            (Budget::ManualSync, BucketLimit::per_hour(config.max_manual_syncs_per_hour)),
+        ]);
+
+        if !config.rate_limit_enabled {
+            log::info!("Rate limiting disabled");
+        }
+        let store = if config.rate_limit_enabled || config.max_manual_syncs_per_hour > 0 {
+            Some(Self::store(config.redis_url.as_deref()).await)
+        } else {
+            None
+        };
+
+        Self {
+            store,
+            limits,
+            enabled: config.rate_limit_enabled,
+        }
+    }
+
+    #[cfg(feature = "redis")]
+    async fn store(redis_url: Option<&str>) -> Arc<dyn RateLimitStore> {
+        if let Some(url) = redis_url {
+            match RedisStore::connect(url).await {
+                Ok(store) => {
+                    log::info!("Rate limiting with Redis backend");
+                    return Arc::new(store);
+                }
+                Err(e) => {
+                    log::error!("Failed to connect to Redis, falling back to in-memory rate limiting: {}", e);
+                }
+            }
+        }
+        Arc::new(InMemoryStore::default())
+    }
+
+    #[cfg(not(feature = "redis"))]
+    async fn store(redis_url: Option<&str>) -> Arc<dyn RateLimitStore> {
+        if redis_url.is_some() {
+            log::warn!("REDIS_URL is set but the redis feature is not enabled; using in-memory rate limiting");
+        }
+        Arc::new(InMemoryStore::default())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Take one request from `identity`'s budget for the route group.
+    /// Always allowed when rate limiting is disabled.
+    pub async fn check(&self, budget: Budget, identity: &str) -> Result<Decision, String> {
+        let limit = self.limits[&budget];
+        match &self.store {
+            Some(store) => {
+                store
+                    .take(&format!("{}:{}", budget.name(), identity), &limit)
+                    .await
+            }
+            None => Ok(Decision::new(true, limit.capacity as f64, &limit)),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use std::sync::OnceLock;
+use tokio::sync::Semaphore;
+
+/// Interactive renders run at once. Bulk renders get the other cores, so
+/// these always have room.
+const INTERACTIVE_RENDERS: usize = 2;
+
+/// Which renders a render waits behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+    /// Someone is waiting for the image: theme previews and images rendered
+    /// on request
+    Interactive,
+    /// Generation jobs, digests and summaries
+    Bulk,
+}
+
+struct Lanes {
+    interactive: Semaphore,
+    bulk: Semaphore,
+}
+
+static LANES: OnceLock<Lanes> = OnceLock::new();
+
+fn lanes() -> &'static Lanes {
+    LANES.get_or_init(|| {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let bulk = cores.saturating_sub(INTERACTIVE_RENDERS).max(1);
+        log::info!(
+            "🎨 Rendering {} interactive and {} bulk heatmaps at once",
+            INTERACTIVE_RENDERS,
+            bulk
+        );
+        Lanes {
+            interactive: Semaphore::new(INTERACTIVE_RENDERS),
+            bulk: Semaphore::new(bulk),
+        }
+    })
+}
+
+/// Run `render` on a blocking thread once `lane` has room. Rendering is CPU
+/// bound, so it stays off the async workers; each lane has its own slots,
+/// so a preview never queues behind a regeneration of every heatmap.
+pub async fn run<T, F>(lane: Lane, render: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let lanes = lanes();
+    let semaphore = match lane {
+        Lane::Interactive => &lanes.interactive,
+        Lane::Bulk => &lanes.bulk,
+    };
+    let _permit = semaphore.acquire().await?;
+
+    tokio::task::spawn_blocking(render).await?
+}
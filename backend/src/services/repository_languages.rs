@@ -0,0 +1,133 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sea_orm::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::{git_platform_account, repository_language, user_setting};
+use crate::services::git_platforms::{GitPlatform, PlatformConfig};
+use crate::services::platform_visibility;
+use crate::utils::languages::{self, LanguageShare};
+use crate::utils::{db, repo_exclusion};
+
+/// Languages of a repository are fetched again after this long
+const REFRESH_AFTER_DAYS: i64 = 7;
+
+/// Most repositories fetched per sync, busiest first; the rest wait for the
+/// next sync
+const MAX_REPOSITORIES_PER_SYNC: usize = 50;
+
+/// A repository the account contributed to in the synced range
+pub struct Contributed {
+    pub contributions: i64,
+    pub is_private: bool,
+}
+
+/// Fetch the languages of the repositories in `repositories` that have none
+/// stored or stale ones. A repository that fails keeps what it had.
+pub async fn refresh(
+    db: &DatabaseConnection,
+    platform: &dyn GitPlatform,
+    config: &PlatformConfig,
+    token: &str,
+    account_id: Uuid,
+    repositories: &HashMap<String, Contributed>,
+) -> Result<usize> {
+    let stale_before = Utc::now() - Duration::days(REFRESH_AFTER_DAYS);
+    let fresh: HashSet<String> = repository_language::Entity::find()
+        .filter(repository_language::Column::AccountId.eq(account_id))
+        .filter(repository_language::Column::FetchedAt.gte(stale_before))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| row.repository_name)
+        .collect();
+
+    let mut due: Vec<(&String, &Contributed)> = repositories
+        .iter()
+        .filter(|(name, _)| !fresh.contains(*name))
+        .collect();
+    due.sort_by(|(a_name, a), (b_name, b)| b.contributions.cmp(&a.contributions).then_with(|| a_name.cmp(b_name)));
+
+    let mut refreshed = 0;
+    for (name, contributed) in due.into_iter().take(MAX_REPOSITORIES_PER_SYNC) {
+        let languages = match platform.fetch_repository_languages(config, token, name).await {
+            Ok(languages) => languages,
+            Err(e) => {
+                log::warn!("⚠️  Failed to fetch languages of {}: {}", name, e);
+                continue;
+            }
+        };
+        store(db, account_id, name, contributed.is_private, languages).await?;
+        refreshed += 1;
+    }
+
+    Ok(refreshed)
+}
+
+/// Replace the stored languages of one repository
+async fn store(
+    db: &DatabaseConnection,
+    account_id: Uuid,
+    repository: &str,
+    is_private: bool,
+    languages: BTreeMap<String, i64>,
+) -> Result<()> {
+    let now = Utc::now();
+    let rows: Vec<repository_language::ActiveModel> = languages
+        .into_iter()
+        .map(|(language, bytes)| repository_language::ActiveModel {
+            account_id: Set(account_id),
+            repository_name: Set(repository.to_string()),
+            language: Set(language),
+            bytes: Set(bytes),
+            is_private_repo: Set(is_private),
+            fetched_at: Set(now),
+        })
+        .collect();
+
+    let txn = db.begin().await?;
+    repository_language::Entity::delete_many()
+        .filter(repository_language::Column::AccountId.eq(account_id))
+        .filter(repository_language::Column::RepositoryName.eq(repository))
+        .exec(&txn)
+        .await?;
+    db::insert_chunked(&txn, rows).await?;
+    txn.commit().await?;
+
+    Ok(())
+}
+
+/// The languages of the repositories the user contributed to, over the
+/// accounts and repositories their public profile shows, largest first
+pub async fn for_user(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<LanguageShare>, DbErr> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+    let show_private_contributions = settings.as_ref().map(|s| s.show_private_contributions).unwrap_or(true);
+    let excluded_repositories: Vec<String> = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.to_vec())
+        .unwrap_or_default();
+
+    let hidden = platform_visibility::hidden_accounts(db, user_id).await?;
+    let mut query = repository_language::Entity::find()
+        .inner_join(git_platform_account::Entity)
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .filter(git_platform_account::Column::Id.is_not_in(hidden))
+        .filter(repo_exclusion::not_excluded(
+            repository_language::Column::RepositoryName,
+            &excluded_repositories,
+        ));
+    if !show_private_contributions {
+        query = query.filter(repository_language::Column::IsPrivateRepo.eq(false));
+    }
+    let rows = query.all(db).await?;
+
+    Ok(languages::totals(
+        rows.iter()
+            .map(|row| (row.repository_name.as_str(), row.language.as_str(), row.bytes)),
+    ))
+}
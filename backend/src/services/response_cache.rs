@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::services::event_bus::EventBus;
+use crate::utils::config::Config;
+use crate::utils::ttl_cache::TtlCache;
+
+/// Upper bound on entries in the in-memory store
+pub const MEMORY_CACHE_ENTRIES: usize = 1000;
+
+/// Storage for cached responses, private to this process or, with Redis,
+/// shared by all instances. Entries expire after the store's TTL.
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    async fn set(&self, key: &str, value: Vec<u8>) -> Result<(), String>;
+    /// Drop every entry whose key starts with `prefix`
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), String>;
+}
+
+pub struct InMemoryCacheStore {
+    entries: TtlCache<String, Arc<Vec<u8>>>,
+}
+
+impl InMemoryCacheStore {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            entries: TtlCache::new(ttl, max_entries),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheStore for InMemoryCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.entries.get(&key.to_string()).map(|value| value.to_vec()))
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        self.entries.insert(key.to_string(), Arc::new(value));
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), String> {
+        self.entries.remove_where(|key| key.starts_with(prefix));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "redis")]
+pub struct RedisCacheStore {
+    connection: redis::aio::ConnectionManager,
+    ttl: Duration,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCacheStore {
+    pub async fn connect(url: &str, ttl: Duration) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(url)?;
+        let connection = redis::aio::ConnectionManager::new(client).await?;
+        Ok(Self { connection, ttl })
+    }
+
+    fn key(key: &str) -> String {
+        format!("hgitmap:cache:{}", key)
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl CacheStore for RedisCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let mut connection = self.connection.clone();
+        redis::cmd("GET")
+            .arg(Self::key(key))
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>) -> Result<(), String> {
+        let mut connection = self.connection.clone();
+        redis::cmd("SET")
+            .arg(Self::key(key))
+            .arg(value)
+            .arg("EX")
+            .arg(self.ttl.as_secs().max(1))
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), String> {
+        let mut connection = self.connection.clone();
+        let pattern = format!("{}*", Self::key(prefix));
+        let mut cursor: u64 = 0;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut connection)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            if !keys.is_empty() {
+                redis::cmd("DEL")
+                    .arg(keys)
+                    .query_async::<()>(&mut connection)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
+        }
+    }
+}
+
+/// Cache for hot public responses (profile contributions and stats, embed
+/// lookups), keyed per user so a user's entries can be dropped when their
+/// data changes. Misses and store errors just mean recomputing.
+#[derive(Clone)]
+pub struct ResponseCache {
+    /// None when `PUBLIC_CACHE_TTL_SECS` is 0
+    store: Option<Arc<dyn CacheStore>>,
+}
+
+impl ResponseCache {
+    /// Build the cache from config, using Redis when `REDIS_URL` is set and
+    /// the `redis` feature is enabled
+    pub async fn from_config(config: &Config) -> Self {
+        if config.public_cache_ttl_secs == 0 {
+            log::info!("Public response cache disabled");
+            return Self { store: None };
+        }
+
+        let ttl = Duration::from_secs(config.public_cache_ttl_secs);
+        Self {
+            store: Some(Self::store(config.redis_url.as_deref(), ttl).await),
+        }
+    }
+
+    #[cfg(feature = "redis")]
+    async fn store(redis_url: Option<&str>, ttl: Duration) -> Arc<dyn CacheStore> {
+        if let Some(url) = redis_url {
+            match RedisCacheStore::connect(url, ttl).await {
+                Ok(store) => {
+                    log::info!("Caching public responses in Redis");
+                    return Arc::new(store);
+                }
+                Err(e) => {
+                    log::error!("Failed to connect to Redis, falling back to the in-memory cache: {}", e);
+                }
+            }
+        }
+        Arc::new(InMemoryCacheStore::new(ttl, MEMORY_CACHE_ENTRIES))
+    }
+
+    #[cfg(not(feature = "redis"))]
+    async fn store(redis_url: Option<&str>, ttl: Duration) -> Arc<dyn CacheStore> {
+        if redis_url.is_some() {
+            log::warn!("REDIS_URL is set but the redis feature is not enabled; using the in-memory cache");
+        }
+        Arc::new(InMemoryCacheStore::new(ttl, MEMORY_CACHE_ENTRIES))
+    }
+
+    fn user_prefix(user_id: Uuid) -> String {
+        format!("{}|", user_id)
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, user_id: Uuid, key: &str) -> Option<T> {
+        let store = self.store.as_ref()?;
+        let key = format!("{}{}", Self::user_prefix(user_id), key);
+
+        match store.get(&key).await {
+            Ok(value) => value.and_then(|value| serde_json::from_slice(&value).ok()),
+            Err(e) => {
+                log::warn!("Response cache read failed: {}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn insert<T: Serialize>(&self, user_id: Uuid, key: &str, value: &T) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Ok(value) = serde_json::to_vec(value) else {
+            return;
+        };
+        let key = format!("{}{}", Self::user_prefix(user_id), key);
+
+        if let Err(e) = store.set(&key, value).await {
+            log::warn!("Response cache write failed: {}", e);
+        }
+    }
+
+    /// Drop everything cached for a user, after their data changed
+    pub async fn invalidate_user(&self, user_id: Uuid) {
+        self.invalidate_prefix(&Self::user_prefix(user_id)).await;
+    }
+
+    async fn invalidate_prefix(&self, prefix: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.delete_prefix(prefix).await {
+            log::warn!("Response cache invalidation failed: {}", e);
+        }
+    }
+
+    /// Invalidate a user's entries when one of their syncs or heatmap
+    /// generations completes
+    pub fn invalidate_on_events(&self, events: &EventBus) -> JoinHandle<()> {
+        let cache = self.clone();
+        let mut receiver = events.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if event.event.updates_profile() {
+                            cache.invalidate_user(event.user_id).await;
+                        }
+                    }
+                    // Missed some events, so any user may be stale
+                    Err(RecvError::Lagged(_)) => cache.invalidate_prefix("").await,
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::time::{sleep, Duration as TokioDuration};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{activity, git_platform_account, user, user_setting};
+use crate::services::commit_details;
+use crate::services::shutdown::Shutdown;
+use crate::utils::config::Config;
+
+/// How often the pruning job runs
+const PRUNE_INTERVAL_HOURS: u64 = 24;
+
+/// Activities that are (or would be) deleted for one user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserRetention {
+    pub user_id: String,
+    pub username: String,
+    /// Days of activities kept; null keeps them forever
+    pub retention_days: Option<i64>,
+    /// Activities dated before this day are pruned
+    pub cutoff_date: Option<NaiveDate>,
+    pub activities: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RetentionReport {
+    /// True when nothing was deleted
+    pub dry_run: bool,
+    pub total_activities: u64,
+    /// Users with at least one expired activity
+    pub users: Vec<UserRetention>,
+}
+
+/// Days of activities kept for a user: the shorter of the instance policy
+/// and the user's own setting. None keeps them forever.
+pub fn effective_days(config: &Config, user_days: Option<i32>) -> Option<i64> {
+    match (config.activity_retention_days, user_days.map(i64::from)) {
+        (Some(instance), Some(user)) => Some(instance.min(user)),
+        (instance, user) => instance.or(user),
+    }
+}
+
+fn expired(account_ids: Vec<Uuid>, cutoff: NaiveDate) -> Condition {
+    Condition::all()
+        .add(activity::Column::GitPlatformAccountId.is_in(account_ids))
+        .add(activity::Column::ActivityDate.lt(cutoff))
+}
+
+async fn apply_user(
+    db: &DatabaseConnection,
+    user: &user::Model,
+    retention_days: Option<i64>,
+    dry_run: bool,
+) -> Result<UserRetention, DbErr> {
+    let cutoff = retention_days.map(|days| (Utc::now() - Duration::days(days)).date_naive());
+    let mut activities = 0;
+
+    if let Some(cutoff) = cutoff {
+        let account_ids: Vec<Uuid> = git_platform_account::Entity::find()
+            .filter(git_platform_account::Column::UserId.eq(user.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+
+        if !account_ids.is_empty() {
+            activities = if dry_run {
+                activity::Entity::find()
+                    .filter(expired(account_ids, cutoff))
+                    .count(db)
+                    .await?
+            } else {
+                activity::Entity::delete_many()
+                    .filter(expired(account_ids, cutoff))
+                    .exec(db)
+                    .await?
+                    .rows_affected
+            };
+        }
+    }
+
+    Ok(UserRetention {
+        user_id: user.id.to_string(),
+        username: user.username.clone(),
+        retention_days,
+        cutoff_date: cutoff,
+        activities,
+    })
+}
+
+/// The retention that applies to one user and what it would delete now
+pub async fn preview_user(db: &DatabaseConnection, config: &Config, user: &user::Model) -> Result<UserRetention, DbErr> {
+    let user_days = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user.id))
+        .one(db)
+        .await?
+        .and_then(|s| s.activity_retention_days);
+
+    apply_user(db, user, effective_days(config, user_days), true).await
+}
+
+/// Delete activities older than each user's retention, or with `dry_run`
+/// only count them. Contributions are never pruned.
+pub async fn prune(db: &DatabaseConnection, config: &Config, dry_run: bool) -> Result<RetentionReport, DbErr> {
+    let user_days: HashMap<Uuid, i32> = user_setting::Entity::find()
+        .filter(user_setting::Column::ActivityRetentionDays.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|s| s.activity_retention_days.map(|days| (s.user_id, days)))
+        .collect();
+
+    // Without an instance policy only users with their own setting are pruned
+    let mut users = user::Entity::find();
+    if config.activity_retention_days.is_none() {
+        users = users.filter(user::Column::Id.is_in(user_days.keys().copied()));
+    }
+
+    let mut report = RetentionReport {
+        dry_run,
+        total_activities: 0,
+        users: Vec::new(),
+    };
+
+    for user in users.all(db).await? {
+        let retention_days = effective_days(config, user_days.get(&user.id).copied());
+        let result = apply_user(db, &user, retention_days, dry_run).await?;
+        if result.activities > 0 {
+            report.total_activities += result.activities;
+            report.users.push(result);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Prune expired activities once a day
+pub fn start_retention_job(
+    db: DatabaseConnection,
+    config: Config,
+    mut shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        log::info!("Starting activity retention job (every {} hours)", PRUNE_INTERVAL_HOURS);
+
+        loop {
+            match prune(&db, &config, false).await {
+                Ok(report) if report.total_activities > 0 => log::info!(
+                    "🧹 [Retention] Pruned {} activities for {} users",
+                    report.total_activities,
+                    report.users.len()
+                ),
+                Ok(_) => log::debug!("🧹 [Retention] Nothing to prune"),
+                Err(e) => log::error!("Error in activity retention job: {}", e),
+            }
+
+            // Accounts with detailed sync set their own commit retention
+            match commit_details::prune(&db).await {
+                Ok(0) => {}
+                Ok(pruned) => log::info!("🧹 [Retention] Pruned {} commit details", pruned),
+                Err(e) => log::error!("Error pruning commit details: {}", e),
+            }
+
+            tokio::select! {
+                _ = sleep(TokioDuration::from_secs(PRUNE_INTERVAL_HOURS * 3600)) => {}
+                _ = shutdown.requested() => break,
+            }
+        }
+    })
+}
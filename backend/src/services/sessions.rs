@@ -0,0 +1,151 @@
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::models::session;
+use crate::utils::api_token::hash_token;
+use crate::utils::auth::generate_refresh_token;
+
+/// Longest user agent stored with a session
+const MAX_USER_AGENT_LENGTH: usize = 255;
+
+/// Result of presenting a refresh token
+pub enum Refresh {
+    /// The session was extended and holds a new refresh token
+    Rotated {
+        session: session::Model,
+        refresh_token: String,
+    },
+    /// An already rotated token was presented, so it was probably stolen;
+    /// the session has been revoked
+    Reused,
+    /// Unknown or expired token
+    Invalid,
+}
+
+/// Start a session, returning it with its first refresh token
+pub async fn create_session(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    ttl_days: i64,
+    user_agent: Option<&str>,
+    ip_address: Option<&str>,
+) -> Result<(session::Model, String), DbErr> {
+    let refresh_token = generate_refresh_token();
+    let now = Utc::now();
+
+    let session = session::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        refresh_token_hash: Set(hash_token(&refresh_token)),
+        previous_token_hash: Set(None),
+        user_agent: Set(user_agent.map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect())),
+        ip_address: Set(ip_address.map(str::to_string)),
+        created_at: Set(now),
+        last_used_at: Set(now),
+        expires_at: Set(now + Duration::days(ttl_days)),
+    }
+    .insert(db)
+    .await?;
+
+    log::info!("🔐 [Sessions] Started session {} for user {}", session.id, user_id);
+
+    Ok((session, refresh_token))
+}
+
+/// Swap a refresh token for a new one, extending the session
+pub async fn refresh_session(db: &DatabaseConnection, refresh_token: &str, ttl_days: i64) -> Result<Refresh, DbErr> {
+    let token_hash = hash_token(refresh_token);
+
+    let Some(current) = session::Entity::find()
+        .filter(session::Column::RefreshTokenHash.eq(&token_hash))
+        .one(db)
+        .await?
+    else {
+        let reused = session::Entity::find()
+            .filter(session::Column::PreviousTokenHash.eq(&token_hash))
+            .one(db)
+            .await?;
+        return Ok(match reused {
+            Some(session) => revoke_reused(db, session.id).await?,
+            None => Refresh::Invalid,
+        });
+    };
+
+    let now = Utc::now();
+    if current.expires_at <= now {
+        session::Entity::delete_by_id(current.id).exec(db).await?;
+        return Ok(Refresh::Invalid);
+    }
+
+    let refresh_token = generate_refresh_token();
+    let session = session::Model {
+        previous_token_hash: Some(token_hash.clone()),
+        refresh_token_hash: hash_token(&refresh_token),
+        last_used_at: now,
+        expires_at: now + Duration::days(ttl_days),
+        ..current
+    };
+
+    // Only rotates while the session still holds the presented token, so of
+    // two requests racing with it one gets a new token and the other counts
+    // as reuse
+    let rotated = session::Entity::update_many()
+        .col_expr(session::Column::PreviousTokenHash, Expr::value(session.previous_token_hash.clone()))
+        .col_expr(session::Column::RefreshTokenHash, Expr::value(session.refresh_token_hash.clone()))
+        .col_expr(session::Column::LastUsedAt, Expr::value(session.last_used_at))
+        .col_expr(session::Column::ExpiresAt, Expr::value(session.expires_at))
+        .filter(session::Column::Id.eq(session.id))
+        .filter(session::Column::RefreshTokenHash.eq(&token_hash))
+        .exec(db)
+        .await?;
+    if rotated.rows_affected == 0 {
+        return revoke_reused(db, session.id).await;
+    }
+
+    Ok(Refresh::Rotated { session, refresh_token })
+}
+
+async fn revoke_reused(db: &DatabaseConnection, session_id: Uuid) -> Result<Refresh, DbErr> {
+    log::warn!("⚠️  [Sessions] Rotated refresh token reused, revoking session {}", session_id);
+    session::Entity::delete_by_id(session_id).exec(db).await?;
+    Ok(Refresh::Reused)
+}
+
+/// End the session holding this refresh token. Returns whether one existed.
+pub async fn revoke_by_refresh_token(db: &DatabaseConnection, refresh_token: &str) -> Result<bool, DbErr> {
+    let result = session::Entity::delete_many()
+        .filter(session::Column::RefreshTokenHash.eq(hash_token(refresh_token)))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// End all of a user's sessions except `keep`, e.g. after a password change.
+/// Returns how many were revoked.
+pub async fn revoke_user_sessions(db: &DatabaseConnection, user_id: Uuid, keep: Option<Uuid>) -> Result<u64, DbErr> {
+    let mut query = session::Entity::delete_many().filter(session::Column::UserId.eq(user_id));
+    if let Some(keep) = keep {
+        query = query.filter(session::Column::Id.ne(keep));
+    }
+    Ok(query.exec(db).await?.rows_affected)
+}
+
+/// Whether the user signed in to this session within the last `minutes`.
+/// Refreshing a session does not count as signing in.
+pub async fn signed_in_within(db: &DatabaseConnection, session_id: Uuid, user_id: Uuid, minutes: i64) -> Result<bool, DbErr> {
+    let since = Utc::now() - Duration::minutes(minutes);
+    Ok(session::Entity::find_by_id(session_id)
+        .one(db)
+        .await?
+        .is_some_and(|s| s.user_id == user_id && s.created_at >= since))
+}
+
+/// Whether an access token's session is still valid
+pub async fn is_active(db: &DatabaseConnection, session_id: Uuid) -> Result<bool, DbErr> {
+    Ok(session::Entity::find_by_id(session_id)
+        .one(db)
+        .await?
+        .is_some_and(|s| s.expires_at > Utc::now()))
+}
@@ -0,0 +1,154 @@
+use sea_orm::*;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use crate::models::{
+    heatmap_generation_job::{self, GenerationJobStatus},
+    platform_sync_job::{self, SyncJobStatus},
+};
+
+/// Error message of jobs put back in the queue by a shutdown
+const INTERRUPTED_MESSAGE: &str = "Interrupted by server shutdown; will resume";
+
+/// Tells background workers the server is stopping. They finish (or
+/// checkpoint) the job at hand and exit instead of starting another one.
+#[derive(Clone)]
+pub struct Shutdown {
+    receiver: watch::Receiver<bool>,
+}
+
+/// Sending half of [`Shutdown`], held by main
+#[derive(Clone)]
+pub struct ShutdownTrigger {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+pub fn channel() -> (ShutdownTrigger, Shutdown) {
+    let (sender, receiver) = watch::channel(false);
+    (
+        ShutdownTrigger {
+            sender: Arc::new(sender),
+        },
+        Shutdown { receiver },
+    )
+}
+
+impl ShutdownTrigger {
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+}
+
+impl Shutdown {
+    pub fn is_requested(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Resolves once shutdown has been requested
+    pub async fn requested(&mut self) {
+        let _ = self.receiver.wait_for(|requested| *requested).await;
+    }
+}
+
+/// A job stopped early because of shutdown; it goes back in the queue
+/// without counting as a failed attempt
+#[derive(Debug, thiserror::Error)]
+#[error("{}", INTERRUPTED_MESSAGE)]
+pub struct Interrupted;
+
+impl Interrupted {
+    pub fn message() -> String {
+        INTERRUPTED_MESSAGE.to_string()
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                log::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Stop the background workers, giving their current jobs up to `timeout`.
+/// Jobs still running after that are stopped and requeued.
+pub async fn drain(
+    db: &DatabaseConnection,
+    trigger: &ShutdownTrigger,
+    mut workers: Vec<JoinHandle<()>>,
+    timeout: Duration,
+) {
+    trigger.trigger();
+    log::info!("⏳ Waiting up to {}s for background jobs to finish", timeout.as_secs());
+
+    let finished = futures_util::future::join_all(workers.iter_mut());
+    if tokio::time::timeout(timeout, finished).await.is_ok() {
+        log::info!("✅ Background jobs stopped");
+        return;
+    }
+
+    for worker in &workers {
+        worker.abort();
+    }
+    match requeue_interrupted(db).await {
+        Ok(count) => log::warn!(
+            "⏱️  Background jobs did not finish in time; requeued {} interrupted jobs",
+            count
+        ),
+        Err(e) => log::error!("Failed to requeue interrupted jobs: {}", e),
+    }
+}
+
+/// Put jobs left in processing back in the queue
+async fn requeue_interrupted(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let generation_jobs = heatmap_generation_job::Entity::find()
+        .filter(heatmap_generation_job::Column::Status.eq(GenerationJobStatus::Processing))
+        .all(db)
+        .await?;
+    let sync_jobs = platform_sync_job::Entity::find()
+        .filter(platform_sync_job::Column::Status.eq(SyncJobStatus::Processing))
+        .all(db)
+        .await?;
+    let count = generation_jobs.len() + sync_jobs.len();
+
+    for job in generation_jobs {
+        let mut active_job: heatmap_generation_job::ActiveModel = job.into();
+        active_job.status = Set(GenerationJobStatus::Pending);
+        active_job.started_at = Set(None);
+        active_job.error_message = Set(Some(Interrupted::message()));
+        active_job.update(db).await?;
+    }
+    for job in sync_jobs {
+        let mut active_job: platform_sync_job::ActiveModel = job.into();
+        active_job.status = Set(SyncJobStatus::Pending);
+        active_job.started_at = Set(None);
+        active_job.error_message = Set(Some(Interrupted::message()));
+        active_job.update(db).await?;
+    }
+
+    Ok(count)
+}
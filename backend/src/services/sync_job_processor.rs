@@ -1,4 +1,5 @@
 use chrono::{Datelike, Utc};
+use futures_util::stream::{FuturesUnordered, StreamExt};
 use sea_orm::*;
 use std::sync::Arc;
 use tokio::time::{interval, Duration};
@@ -9,23 +10,50 @@ use crate::models::{
     platform_sync_job::{self, SyncJobStatus},
 };
 use crate::services::activity_aggregation::ActivityAggregationService;
+use crate::services::api_usage;
+use crate::services::commit_details;
 use crate::services::git_platforms::{
     GitHubClient, GitLabClient, GitPlatform, GiteaClient, PlatformConfig,
 };
+use crate::services::event_bus::{DashboardEvent, EventBus};
+use crate::services::health;
+use crate::services::job_limits;
+use crate::services::achievements;
+use crate::services::milestones;
+use crate::services::shutdown::{Interrupted, Shutdown};
+use crate::utils::db;
 use crate::utils::encryption::decrypt;
+use crate::utils::error_reporting;
+
+/// Pause between starting the years of a multi-year sync
+const YEAR_STAGGER: Duration = Duration::from_secs(2);
 
 pub struct SyncJobProcessor {
     db: DatabaseConnection,
     encryption_key: String,
+    events: EventBus,
     check_interval: Duration,
+    /// Default per-user concurrency, 0 for unlimited
+    job_concurrency: u32,
+    shutdown: Shutdown,
 }
 
 impl SyncJobProcessor {
-    pub fn new(db: DatabaseConnection, encryption_key: String, check_interval_secs: u64) -> Self {
+    pub fn new(
+        db: DatabaseConnection,
+        encryption_key: String,
+        events: EventBus,
+        check_interval_secs: u64,
+        job_concurrency: u32,
+        shutdown: Shutdown,
+    ) -> Self {
         Self {
             db,
             encryption_key,
+            events,
             check_interval: Duration::from_secs(check_interval_secs),
+            job_concurrency,
+            shutdown,
         }
     }
 
@@ -39,14 +67,21 @@ impl SyncJobProcessor {
         }
 
         let mut interval_timer = interval(self.check_interval);
+        let mut shutdown = self.shutdown.clone();
 
         loop {
-            interval_timer.tick().await;
+            tokio::select! {
+                _ = interval_timer.tick() => {}
+                _ = shutdown.requested() => break,
+            }
+            health::SYNC_JOB_PROCESSOR.beat();
 
             if let Err(e) = self.process_pending_jobs().await {
                 log::error!("Error processing sync jobs: {}", e);
             }
         }
+
+        log::info!("Platform sync job processor stopped");
     }
 
     /// Reset jobs that were processing when the server shut down
@@ -90,7 +125,8 @@ impl SyncJobProcessor {
             let mut active_job: platform_sync_job::ActiveModel = job_model.into();
             active_job.contributions_synced = Set(Some(contributions));
             active_job.activities_synced = Set(Some(activities));
-            active_job.update(&self.db).await?;
+            let job_model = active_job.update(&self.db).await?;
+            self.publish_progress(&job_model);
         }
 
         Ok(())
@@ -113,12 +149,26 @@ impl SyncJobProcessor {
             active_job.contributions_synced = Set(Some(contributions));
             active_job.activities_synced = Set(Some(activities));
             active_job.years_completed = Set(Some(years_completed));
-            active_job.update(&self.db).await?;
+            let job_model = active_job.update(&self.db).await?;
+            self.publish_progress(&job_model);
         }
 
         Ok(())
     }
 
+    fn publish_progress(&self, job: &platform_sync_job::Model) {
+        self.events.publish(
+            job.user_id,
+            DashboardEvent::SyncProgress {
+                job_id: job.id,
+                platform_account_id: job.platform_account_id,
+                contributions_synced: job.contributions_synced.unwrap_or(0),
+                activities_synced: job.activities_synced.unwrap_or(0),
+                years_completed: job.years_completed,
+            },
+        );
+    }
+
     /// Check if a job has been cancelled
     async fn is_job_cancelled(&self, job_id: Uuid) -> Result<bool, DbErr> {
         let job = platform_sync_job::Entity::find_by_id(job_id)
@@ -144,10 +194,14 @@ impl SyncJobProcessor {
             .filter(platform_sync_job::Column::Status.eq(SyncJobStatus::Pending))
             .order_by_desc(platform_sync_job::Column::Priority)
             .order_by_asc(platform_sync_job::Column::ScheduledAt)
-            .limit(5) // Process up to 5 jobs at a time
+            .limit(job_limits::CANDIDATE_WINDOW)
             .all(&self.db)
             .await?;
 
+        // Process up to 5 jobs at a time, within each user's concurrency
+        let pending_jobs =
+            job_limits::take_batch(&self.db, pending_jobs, |job| job.user_id, self.job_concurrency, 5).await?;
+
         if pending_jobs.is_empty() {
             return Ok(());
         }
@@ -155,6 +209,10 @@ impl SyncJobProcessor {
         log::info!("Processing {} pending sync jobs", pending_jobs.len());
 
         for job in pending_jobs {
+            // Leave the rest of the batch for the next start
+            if self.shutdown.is_requested() {
+                break;
+            }
             if let Err(e) = self.process_job(job).await {
                 log::error!("Failed to process sync job: {}", e);
             }
@@ -164,6 +222,7 @@ impl SyncJobProcessor {
     }
 
     /// Process a single job
+    #[tracing::instrument(name = "sync_job", skip_all, fields(job.id = %job.id, account.id = %job.platform_account_id))]
     async fn process_job(&self, job: platform_sync_job::Model) -> Result<(), anyhow::Error> {
         log::info!(
             "Processing sync job {} for platform account {} (manual: {})",
@@ -180,13 +239,27 @@ impl SyncJobProcessor {
 
         let processing_job = active_job.update(&self.db).await?;
 
-        // Process the job
-        let result = self.execute_sync(&processing_job).await;
+        // Process the job, counting the platform API calls it makes
+        let result = api_usage::track(&self.db, job.platform_account_id, self.execute_sync(&processing_job)).await;
+
+        // A cancelled job keeps its cancelled state instead of being retried
+        if self.is_job_cancelled(job.id).await? {
+            log::warn!("🚫 [SyncJob] Job {} was cancelled while running", job.id);
+            return Ok(());
+        }
 
         // Update job status
         let mut final_job: platform_sync_job::ActiveModel = processing_job.into();
 
         match result {
+            // Stopped between years for shutdown; resume without using up a retry
+            Err(e) if e.is::<Interrupted>() => {
+                final_job.status = Set(SyncJobStatus::Pending);
+                final_job.started_at = Set(None);
+                final_job.error_message = Set(Some(Interrupted::message()));
+
+                log::info!("⏸️  [SyncJob] Job {} interrupted by shutdown, requeued", job.id);
+            }
             Ok((contributions_count, activities_count)) => {
                 final_job.status = Set(SyncJobStatus::Completed);
                 final_job.completed_at = Set(Some(Utc::now()));
@@ -234,16 +307,56 @@ impl SyncJobProcessor {
                     final_job.error_message = Set(Some(error_msg));
 
                     log::error!("Sync job {} failed after {} attempts", job.id, max_retries);
+                    error_reporting::report_job_failure(
+                        "platform_sync",
+                        Some(job.id),
+                        job.user_id,
+                        Some(job.platform_account_id),
+                        &e,
+                    );
                 }
             }
         }
 
-        final_job.update(&self.db).await?;
+        let final_job = final_job.update(&self.db).await?;
+
+        // Milestones reached with the newly synced contributions
+        if final_job.status == SyncJobStatus::Completed {
+            if let Err(e) = milestones::detect_and_notify(&self.db, &self.events, final_job.user_id).await {
+                log::error!("Failed to check milestones for user {}: {}", final_job.user_id, e);
+            }
+            if let Err(e) = achievements::evaluate(&self.db, final_job.user_id).await {
+                log::error!("Failed to evaluate achievements for user {}: {}", final_job.user_id, e);
+            }
+        }
+
+        let activities_synced = final_job.activities_synced.unwrap_or(0);
+        if final_job.status == SyncJobStatus::Completed && activities_synced > 0 {
+            self.events.publish(
+                final_job.user_id,
+                DashboardEvent::NewActivities {
+                    platform_account_id: final_job.platform_account_id,
+                    count: activities_synced,
+                },
+            );
+        }
+        self.events.publish(
+            final_job.user_id,
+            DashboardEvent::SyncFinished {
+                job_id: final_job.id,
+                platform_account_id: final_job.platform_account_id,
+                status: final_job.status,
+                contributions_synced: final_job.contributions_synced.unwrap_or(0),
+                activities_synced,
+                error: final_job.error_message,
+            },
+        );
 
         Ok(())
     }
 
     /// Execute the actual sync
+    #[tracing::instrument(skip_all)]
     async fn execute_sync(
         &self,
         job: &platform_sync_job::Model,
@@ -263,7 +376,7 @@ impl SyncJobProcessor {
         let current_year = Utc::now().year();
 
         let (start_year, end_year) = if job.sync_all_years {
-            (2020, current_year)
+            (account.earliest_sync_year.min(current_year), current_year)
         } else if let Some(year) = job.specific_year {
             (year, year)
         } else {
@@ -271,7 +384,7 @@ impl SyncJobProcessor {
         };
 
         // Calculate total years and update job
-        let total_years = (end_year - start_year + 1) as i32;
+        let total_years = end_year - start_year + 1 ;
         let mut job_update: platform_sync_job::ActiveModel = job.clone().into();
         job_update.total_years = Set(Some(total_years));
         job_update.years_completed = Set(Some(0));
@@ -300,6 +413,10 @@ impl SyncJobProcessor {
             return Err(anyhow::anyhow!("Sync cancelled by user"));
         }
 
+        if self.shutdown.is_requested() {
+            return Err(Interrupted.into());
+        }
+
         // Sync activities if requested
         if job.sync_activities {
             total_activities = self
@@ -321,6 +438,7 @@ impl SyncJobProcessor {
         Ok((total_contributions, total_activities))
     }
 
+    #[tracing::instrument(skip_all, fields(platform = ?account.platform_type, start_year = start_year, end_year = end_year))]
     async fn sync_contributions(
         &self,
         job_id: Uuid,
@@ -354,6 +472,18 @@ impl SyncJobProcessor {
             git_platform_account::GitPlatform::GitLab => Box::new(GitLabClient::new()),
         };
 
+        // Leave the stored contributions alone rather than failing the job
+        let capabilities = client
+            .capabilities(&platform_config, &account.platform_username, access_token)
+            .await;
+        if !capabilities.contributions {
+            log::info!(
+                "⏭️  [SyncJob] {} does not provide contributions, skipping",
+                platform_config.instance_url
+            );
+            return Ok(0);
+        }
+
         let mut all_contributions = Vec::new();
         let mut running_total = 0;
 
@@ -363,39 +493,77 @@ impl SyncJobProcessor {
             end_year
         );
 
-        for year in start_year..=end_year {
-            // Check if job has been cancelled
-            if self.is_job_cancelled(job_id).await? {
-                log::warn!("🚫 [SyncJob] Job {} cancelled, stopping sync", job_id);
-                return Err(anyhow::anyhow!("Sync cancelled by user"));
-            }
+        // Fetch a few years at a time; the first ones start staggered so a
+        // sync does not open with a burst of requests
+        let concurrency = platform_config.year_concurrency();
+        let mut years = start_year..=end_year;
+        let mut in_flight = FuturesUnordered::new();
+        let mut launched = 0;
+        let mut years_completed = 0;
 
-            let from_date = chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
-            let from = from_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        loop {
+            while in_flight.len() < concurrency {
+                let Some(year) = years.next() else {
+                    break;
+                };
+
+                // Nothing is written until every year is fetched, so stopping
+                // here loses no data
+                if self.shutdown.is_requested() {
+                    return Err(Interrupted.into());
+                }
 
-            let to_date = if year == current_year {
-                Utc::now().date_naive()
-            } else {
-                chrono::NaiveDate::from_ymd_opt(year, 12, 31).unwrap()
-            };
-            let to = to_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
+                // Check if job has been cancelled
+                if self.is_job_cancelled(job_id).await? {
+                    log::warn!("🚫 [SyncJob] Job {} cancelled, stopping sync", job_id);
+                    return Err(anyhow::anyhow!("Sync cancelled by user"));
+                }
 
-            log::info!(
-                "🔄 [SyncJob] Fetching year {}: {} to {}",
-                year,
-                from.format("%Y-%m-%d"),
-                to.format("%Y-%m-%d")
-            );
+                let delay = if launched < concurrency {
+                    YEAR_STAGGER * launched as u32
+                } else {
+                    YEAR_STAGGER
+                };
+                launched += 1;
 
-            let contributions = client
-                .fetch_contributions(
-                    &platform_config,
-                    &account.platform_username,
-                    access_token,
-                    from,
-                    to,
-                )
-                .await?;
+                let (client, platform_config) = (&client, &platform_config);
+                in_flight.push(async move {
+                    tokio::time::sleep(delay).await;
+
+                    let from_date = chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+                    let from = from_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+                    let to_date = if year == current_year {
+                        Utc::now().date_naive()
+                    } else {
+                        chrono::NaiveDate::from_ymd_opt(year, 12, 31).unwrap()
+                    };
+                    let to = to_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
+
+                    log::info!(
+                        "🔄 [SyncJob] Fetching year {}: {} to {}",
+                        year,
+                        from.format("%Y-%m-%d"),
+                        to.format("%Y-%m-%d")
+                    );
+
+                    let contributions = client
+                        .fetch_contributions(
+                            platform_config,
+                            &account.platform_username,
+                            access_token,
+                            from,
+                            to,
+                        )
+                        .await;
+                    (year, contributions)
+                });
+            }
+
+            let Some((year, contributions)) = in_flight.next().await else {
+                break;
+            };
+            let contributions = contributions?;
 
             log::info!(
                 "✅ [SyncJob] Fetched {} contribution days for year {}",
@@ -407,14 +575,9 @@ impl SyncJobProcessor {
             all_contributions.extend(contributions);
 
             // Update job progress after each year (increment years_completed)
-            let years_completed = (year - start_year + 1) as i32;
+            years_completed += 1;
             self.update_job_progress_with_years(job_id, running_total, 0, years_completed)
                 .await?;
-
-            // Add delay between years to be extra nice to the API
-            if year < end_year {
-                tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
-            }
         }
 
         // Delete existing contributions in the synced date range
@@ -431,40 +594,42 @@ impl SyncJobProcessor {
             delete_to
         );
 
-        contribution::Entity::delete_many()
-            .filter(contribution::Column::GitPlatformAccountId.eq(account.id))
-            .filter(contribution::Column::ContributionDate.gte(delete_from))
-            .filter(contribution::Column::ContributionDate.lte(delete_to))
-            .exec(&self.db)
-            .await?;
-
-        // Insert new contributions
-        let mut total_inserted = 0;
+        // Commits the user co-authored, found by earlier detailed syncs
+        commit_details::add_co_authored(&self.db, account, &mut all_contributions, delete_from, delete_to).await?;
 
-        for contribution_data in all_contributions {
-            let contribution = contribution::ActiveModel {
+        // Replace the range in one transaction so the heatmap never reads
+        // it half written
+        let now = Utc::now();
+        let contributions: Vec<_> = all_contributions
+            .into_iter()
+            .map(|contribution_data| contribution::ActiveModel {
                 id: Set(Uuid::new_v4()),
                 git_platform_account_id: Set(account.id),
                 contribution_date: Set(contribution_data.date),
                 count: Set(contribution_data.count),
                 repository_name: Set(contribution_data.repository_name),
                 is_private_repo: Set(contribution_data.is_private),
-                created_at: Set(Utc::now()),
-                updated_at: Set(Utc::now()),
-            };
+                created_at: Set(now),
+                updated_at: Set(now),
+            })
+            .collect();
 
-            contribution::Entity::insert(contribution)
-                .exec(&self.db)
-                .await?;
-
-            total_inserted += 1;
-        }
+        let txn = self.db.begin().await?;
+        contribution::Entity::delete_many()
+            .filter(contribution::Column::GitPlatformAccountId.eq(account.id))
+            .filter(contribution::Column::ContributionDate.gte(delete_from))
+            .filter(contribution::Column::ContributionDate.lte(delete_to))
+            .exec(&txn)
+            .await?;
+        let total_inserted = db::insert_chunked(&txn, contributions).await? as i32;
+        txn.commit().await?;
 
         log::info!("✅ [SyncJob] Inserted {} contributions", total_inserted);
 
         Ok(total_inserted)
     }
 
+    #[tracing::instrument(skip_all, fields(start_year = start_year, end_year = end_year))]
     async fn sync_activities(
         &self,
         job_id: Uuid,
@@ -644,8 +809,18 @@ impl SyncJobProcessor {
 pub fn start_sync_job_processor(
     db: DatabaseConnection,
     encryption_key: String,
+    events: EventBus,
+    job_concurrency: u32,
+    shutdown: Shutdown,
 ) -> tokio::task::JoinHandle<()> {
-    let processor = Arc::new(SyncJobProcessor::new(db, encryption_key, 2)); // Check every 2 seconds
+    let processor = Arc::new(SyncJobProcessor::new(
+        db,
+        encryption_key,
+        events,
+        2, // Check every 2 seconds
+        job_concurrency,
+        shutdown,
+    ));
 
     tokio::spawn(async move {
         processor.start().await;
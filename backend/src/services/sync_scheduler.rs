@@ -1,42 +1,80 @@
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sea_orm::*;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration as TokioDuration};
+use uuid::Uuid;
 
 use crate::models::heatmap_generation_setting;
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::{job_processor, mqtt_publisher};
 use crate::services::platform_sync::PlatformSyncService;
+use crate::services::response_cache::ResponseCache;
+use crate::services::shutdown::Shutdown;
 use crate::utils::config::Config;
+use crate::utils::error_reporting;
+
+/// Share of the sync interval a user's syncs are pushed back by at most
+const JITTER_PERCENT: i64 = 10;
 
 pub struct SyncScheduler {
     db: DatabaseConnection,
     config: Config,
+    /// Whether activities are synced
+    instance: Arc<InstanceSettingsService>,
+    /// Cleared for each user after their sync
+    cache: ResponseCache,
     check_interval_seconds: u64,
 }
 
 impl SyncScheduler {
-    pub fn new(db: DatabaseConnection, config: Config) -> Self {
+    pub fn new(
+        db: DatabaseConnection,
+        config: Config,
+        instance: Arc<InstanceSettingsService>,
+        cache: ResponseCache,
+    ) -> Self {
         Self {
             db,
             config,
+            instance,
+            cache,
             check_interval_seconds: 60, // Check every minute
         }
     }
 
-    /// Start the background scheduler
-    pub async fn start(self: Arc<Self>) {
+    /// Start the background scheduler. On shutdown it stops scheduling and
+    /// waits for the syncs already running.
+    pub async fn start(self: Arc<Self>, mut shutdown: Shutdown) {
         log::info!("Starting sync scheduler (checking every {} seconds)", self.check_interval_seconds);
 
+        let mut running = Vec::new();
+
         loop {
-            if let Err(e) = self.check_and_sync_users().await {
+            running.retain(|(_, sync): &(Uuid, JoinHandle<()>)| !sync.is_finished());
+
+            if let Err(e) = self.check_and_sync_users(&mut running, &shutdown).await {
                 log::error!("Error in sync scheduler: {}", e);
             }
 
-            sleep(TokioDuration::from_secs(self.check_interval_seconds)).await;
+            tokio::select! {
+                _ = sleep(TokioDuration::from_secs(self.check_interval_seconds)) => {}
+                _ = shutdown.requested() => break,
+            }
         }
+
+        log::info!("Sync scheduler waiting for {} running syncs", running.len());
+        futures_util::future::join_all(running.into_iter().map(|(_, sync)| sync)).await;
+        log::info!("Sync scheduler stopped");
     }
 
-    /// Check all users and sync those that are due
-    async fn check_and_sync_users(&self) -> Result<(), DbErr> {
+    /// Check all users and sync those that are due, most overdue first and
+    /// no more than `SYNC_STARTS_PER_MINUTE` per check
+    async fn check_and_sync_users(
+        &self,
+        running: &mut Vec<(Uuid, JoinHandle<()>)>,
+        shutdown: &Shutdown,
+    ) -> Result<(), DbErr> {
         let now = Utc::now();
 
         // Find all users with auto_generation_enabled
@@ -45,55 +83,117 @@ impl SyncScheduler {
             .all(&self.db)
             .await?;
 
-        for setting in settings {
-            // Check if it's time to sync this user
-            let should_sync = match setting.last_scheduled_generation_at {
-                Some(last_sync) => {
-                    let next_sync = last_sync + Duration::minutes(setting.update_interval_minutes as i64);
-                    now >= next_sync
-                }
-                None => true, // Never synced before, sync now
+        // Users never synced before are due now
+        let mut due: Vec<(Option<DateTime<Utc>>, Uuid)> = settings
+            .iter()
+            .map(|setting| (due_at(setting, now), setting.user_id))
+            .filter(|(due_at, user_id)| {
+                due_at.is_none_or(|due_at| now >= due_at)
+                    && !running.iter().any(|(running_id, _)| running_id == user_id)
+            })
+            .collect();
+        due.sort();
+
+        let budget = self.config.sync_starts_per_minute as usize;
+        if budget > 0 && due.len() > budget {
+            log::info!("{} users due for a sync, starting {} this minute", due.len(), budget);
+            due.truncate(budget);
+        }
+
+        for (index, (_, user_id)) in due.into_iter().enumerate() {
+            log::info!("Scheduling sync for user: {}", user_id);
+
+            // Spread the starts over the minute
+            let delay = if budget > 0 {
+                TokioDuration::from_secs(self.check_interval_seconds) * index as u32 / budget as u32
+            } else {
+                TokioDuration::ZERO
             };
 
-            if should_sync {
-                let user_id = setting.user_id;
-                log::info!("Scheduling sync for user: {}", user_id);
-
-                // Spawn a new task for this sync to avoid blocking
-                let db_clone = self.db.clone();
-                let config_clone = self.config.clone();
-                tokio::spawn(async move {
-                    let sync_service = PlatformSyncService::new(db_clone.clone(), config_clone);
-
-                    match sync_service.sync_user_data(user_id).await {
-                        Ok(result) => {
-                            log::info!(
-                                "Sync completed for user {}: {} platforms, {} added, {} updated",
-                                user_id,
-                                result.platforms_synced,
-                                result.contributions_added,
-                                result.contributions_updated
-                            );
-
-                            // Update last_scheduled_generation_at and next_scheduled_generation_at
-                            if let Err(e) = update_sync_timestamps(&db_clone, user_id).await {
-                                log::error!("Failed to update sync timestamps: {}", e);
-                            }
+            // Spawn a new task for this sync to avoid blocking
+            let db_clone = self.db.clone();
+            let config_clone = self.config.clone();
+            let instance = self.instance.clone();
+            let cache = self.cache.clone();
+            let mut shutdown = shutdown.clone();
+            running.push((user_id, tokio::spawn(async move {
+                tokio::select! {
+                    _ = sleep(delay) => {}
+                    _ = shutdown.requested() => return,
+                }
+
+                let encryption_key = config_clone.encryption_key.clone();
+                let sync_service = PlatformSyncService::new(db_clone.clone(), config_clone)
+                    .with_activities(instance.current().await.sync_activities);
+
+                match sync_service.sync_user_data(user_id).await {
+                    Ok(result) => {
+                        log::info!(
+                            "Sync completed for user {}: {} platforms, {} added, {} updated",
+                            user_id,
+                            result.platforms_synced,
+                            result.contributions_added,
+                            result.contributions_updated
+                        );
+                        cache.invalidate_user(user_id).await;
+                        if let Err(e) = mqtt_publisher::publish_for_user(&db_clone, &encryption_key, user_id).await {
+                            log::error!("Failed to publish MQTT update for user {}: {}", user_id, e);
                         }
-                        Err(e) => {
-                            log::error!("Sync failed for user {}: {}", user_id, e);
+
+                        // Update last_scheduled_generation_at and next_scheduled_generation_at
+                        if let Err(e) = update_sync_timestamps(&db_clone, user_id).await {
+                            log::error!("Failed to update sync timestamps: {}", e);
                         }
                     }
-                });
-            }
+                    Err(e) => {
+                        log::error!("Sync failed for user {}: {}", user_id, e);
+                        error_reporting::report_job_failure("scheduled_sync", None, user_id, None, &e);
+                    }
+                }
+            })));
         }
 
         Ok(())
     }
 }
 
+/// When a user's next scheduled sync is due; None if they were never synced
+/// and have no window or cron expression to wait for
+fn due_at(setting: &heatmap_generation_setting::Model, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    match setting.last_scheduled_generation_at {
+        Some(last_sync) => Some(next_sync_at(setting, last_sync)),
+        None => {
+            let schedule = job_processor::generation_schedule(setting);
+            (!schedule.is_interval_only()).then(|| schedule.first_run(now))
+        }
+    }
+}
+
+/// When the sync after one started at `last_sync` is due. Windows and cron
+/// expressions already pick the time, so only plain intervals get jitter;
+/// `SYNC_STARTS_PER_MINUTE` still spreads users whose window opens together.
+fn next_sync_at(setting: &heatmap_generation_setting::Model, last_sync: DateTime<Utc>) -> DateTime<Utc> {
+    let schedule = job_processor::generation_schedule(setting);
+    if schedule.is_interval_only() {
+        last_sync + interval_with_jitter(setting.user_id, setting.update_interval_minutes)
+    } else {
+        schedule.next_run(last_sync, Duration::minutes(setting.update_interval_minutes.max(1) as i64))
+    }
+}
+
+/// The sync interval plus a fixed delay of up to [`JITTER_PERCENT`] of it,
+/// derived from the user ID, so users with the same interval do not all
+/// sync in the same minute. A scheduled sync covers all of a user's accounts.
+fn interval_with_jitter(user_id: Uuid, interval_minutes: i32) -> Duration {
+    let interval = Duration::minutes(interval_minutes.max(1) as i64);
+    let window = (interval.num_seconds() * JITTER_PERCENT / 100).max(1);
+    let jitter = (user_id.as_u128() % window as u128) as i64;
+
+    interval + Duration::seconds(jitter)
+}
+
 /// Update the sync timestamps in the database
-async fn update_sync_timestamps(db: &DatabaseConnection, user_id: uuid::Uuid) -> Result<(), DbErr> {
+async fn update_sync_timestamps(db: &DatabaseConnection, user_id: Uuid) -> Result<(), DbErr> {
     let setting = heatmap_generation_setting::Entity::find()
         .filter(heatmap_generation_setting::Column::UserId.eq(user_id))
         .one(db)
@@ -101,7 +201,7 @@ async fn update_sync_timestamps(db: &DatabaseConnection, user_id: uuid::Uuid) ->
 
     if let Some(setting) = setting {
         let now = Utc::now();
-        let next_sync = now + Duration::minutes(setting.update_interval_minutes as i64);
+        let next_sync = next_sync_at(&setting, now);
 
         let mut active_setting: heatmap_generation_setting::ActiveModel = setting.into();
         active_setting.last_scheduled_generation_at = Set(Some(now));
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{heatmap_generation_setting, team, team_member, user};
+use crate::services::heatmap_generator::{HeatmapData, HeatmapGenerator};
+use crate::services::profile_visibility;
+
+/// Most members a team can have
+pub const MAX_MEMBERS: u64 = 100;
+
+/// One member's share of a team heatmap
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MemberStats {
+    pub user_id: Uuid,
+    pub username: String,
+    /// Contributions in the team's date range
+    pub total_count: i32,
+    /// Days with at least one contribution
+    pub active_days: i32,
+    /// The member's busiest day, null without contributions
+    pub busiest_day: Option<NaiveDate>,
+    pub busiest_day_count: i32,
+    /// Percentage of the team's contributions, rounded to one decimal
+    pub share: f64,
+}
+
+/// The merged contribution days of a team's members and what each added
+pub struct TeamHeatmap {
+    pub data: HeatmapData,
+    pub members: Vec<MemberStats>,
+}
+
+/// A team's members, earliest added first
+pub async fn members(db: &DatabaseConnection, team_id: Uuid) -> Result<Vec<user::Model>, DbErr> {
+    let memberships = team_member::Entity::find()
+        .filter(team_member::Column::TeamId.eq(team_id))
+        .order_by_asc(team_member::Column::AddedAt)
+        .find_also_related(user::Entity)
+        .all(db)
+        .await?;
+
+    Ok(memberships.into_iter().filter_map(|(_, user)| user).collect())
+}
+
+pub async fn is_member(db: &DatabaseConnection, team_id: Uuid, user_id: Uuid) -> Result<bool, DbErr> {
+    Ok(team_member::Entity::find_by_id((team_id, user_id))
+        .one(db)
+        .await?
+        .is_some())
+}
+
+/// Merge the members' contribution days over the owner's date range. Each
+/// member's own hidden repositories and private contribution setting apply.
+/// Members who made their profile private are left out.
+pub async fn heatmap(db: &DatabaseConnection, team: &team::Model) -> Result<TeamHeatmap> {
+    let range = generation_settings(db, team.owner_id).await?.date_range_days;
+    let end_date = Utc::now().date_naive();
+    let generator = HeatmapGenerator::new(db.clone());
+
+    let mut parts = Vec::new();
+    for member in members(db, team.id).await? {
+        if profile_visibility::find(db, member.id).await? == profile_visibility::PRIVATE {
+            continue;
+        }
+
+        let mut settings = generation_settings(db, member.id).await?;
+        settings.date_range_days = range;
+        let data = generator
+            .fetch_contribution_data_until(member.id, &settings, end_date)
+            .await?;
+        parts.push((member, data));
+    }
+
+    let data = merge(parts.iter().map(|(_, data)| data), range, end_date);
+    let members = parts
+        .iter()
+        .map(|(member, part)| member_stats(member, part, data.total_count))
+        .collect();
+
+    Ok(TeamHeatmap { data, members })
+}
+
+/// Sum the members' days. Every part covers the same window, so their weeks
+/// line up cell for cell.
+fn merge<'a>(
+    parts: impl Iterator<Item = &'a HeatmapData>,
+    date_range_days: i32,
+    end_date: NaiveDate,
+) -> HeatmapData {
+    let mut merged: Option<HeatmapData> = None;
+
+    for part in parts {
+        let Some(merged) = merged.as_mut() else {
+            merged = Some(HeatmapData {
+                weeks: part.weeks.clone(),
+                max_count: 0,
+                total_count: 0,
+                date_range_start: part.date_range_start,
+                date_range_end: part.date_range_end,
+                // Members' away periods are their own to show
+                hatch_away_days: false,
+                achievements: Vec::new(),
+                code_frequency: Vec::new(),
+            });
+            continue;
+        };
+
+        for (week, part_week) in merged.weeks.iter_mut().zip(&part.weeks) {
+            for (day, part_day) in week.iter_mut().zip(part_week) {
+                if day.count >= 0 && part_day.count > 0 {
+                    day.count += part_day.count;
+                }
+                // The team is only away while everyone is
+                day.away &= part_day.away;
+            }
+        }
+    }
+
+    let mut merged = merged.unwrap_or_else(|| empty(date_range_days, end_date));
+    let days = merged.weeks.iter().flatten().filter(|d| d.count > 0);
+    merged.max_count = days.clone().map(|d| d.count).max().unwrap_or(0);
+    merged.total_count = days.map(|d| d.count).sum();
+    merged
+}
+
+/// A window without contributions, for teams without visible members
+fn empty(date_range_days: i32, end_date: NaiveDate) -> HeatmapData {
+    use crate::services::heatmap_generator::DayContribution;
+    use chrono::{Datelike, Duration};
+
+    let window_start = end_date - Duration::days(date_range_days as i64 - 1);
+    let start_date = window_start - Duration::days(window_start.weekday().num_days_from_sunday() as i64);
+
+    let mut weeks = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        let week = (0..7)
+            .map(|i| {
+                let day = date + Duration::days(i);
+                DayContribution {
+                    date: if day <= end_date { day } else { NaiveDate::from_ymd_opt(1970, 1, 1).unwrap() },
+                    count: if day <= end_date { 0 } else { -1 },
+                    away: false,
+                }
+            })
+            .collect();
+        weeks.push(week);
+        date += Duration::days(7);
+    }
+
+    HeatmapData {
+        weeks,
+        max_count: 0,
+        total_count: 0,
+        date_range_start: start_date,
+        date_range_end: end_date,
+        hatch_away_days: false,
+        achievements: Vec::new(),
+        code_frequency: Vec::new(),
+    }
+}
+
+fn member_stats(member: &user::Model, data: &HeatmapData, team_total: i32) -> MemberStats {
+    let days: Vec<_> = data.weeks.iter().flatten().filter(|d| d.count > 0).collect();
+    let busiest = days.iter().max_by_key(|d| (d.count, std::cmp::Reverse(d.date)));
+    let share = if team_total > 0 {
+        (data.total_count as f64 * 1000.0 / team_total as f64).round() / 10.0
+    } else {
+        0.0
+    };
+
+    MemberStats {
+        user_id: member.id,
+        username: member.username.clone(),
+        total_count: data.total_count,
+        active_days: days.len() as i32,
+        busiest_day: busiest.map(|d| d.date),
+        busiest_day_count: busiest.map_or(0, |d| d.count),
+        share,
+    }
+}
+
+/// The user's generation settings, or the defaults if they never saved any
+async fn generation_settings(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> Result<heatmap_generation_setting::Model, DbErr> {
+    let settings = heatmap_generation_setting::Entity::find()
+        .filter(heatmap_generation_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+
+    Ok(settings.unwrap_or_else(|| heatmap_generation_setting::Model {
+        id: Uuid::new_v4(),
+        user_id,
+        update_interval_minutes: 60,
+        auto_generation_enabled: true,
+        date_range_days: 365,
+        include_private_contributions: true,
+        storage_path: None,
+        timezone: None,
+        generation_window_start_minutes: None,
+        generation_window_end_minutes: None,
+        generation_cron: None,
+        last_scheduled_generation_at: None,
+        next_scheduled_generation_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }))
+}
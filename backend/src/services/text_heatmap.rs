@@ -0,0 +1,217 @@
+use chrono::Datelike;
+
+use crate::services::heatmap_generator::{ColorPalette, DayContribution, HeatmapData};
+
+/// Cells of the plain rendering by level, two columns wide like the colored
+/// ones
+const LEVEL_CELLS: [&str; 5] = ["· ", "░░", "▒▒", "▓▓", "██"];
+
+/// Labels shown in front of the Monday, Wednesday and Friday rows
+const DAY_LABELS: [&str; 7] = ["    ", "Mon ", "    ", "Wed ", "    ", "Fri ", "    "];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Symbols of the emoji grid when none are given: white for days without
+/// contributions, green for the rest
+pub const DEFAULT_SYMBOLS: [&str; 2] = ["⬜", "🟩"];
+
+/// Most symbols a grid takes, one per level
+pub const MAX_SYMBOLS: usize = 5;
+
+/// Longest symbol in bytes, enough for emoji joined from several code points
+const MAX_SYMBOL_LEN: usize = 32;
+
+/// Whether a User-Agent belongs to a command-line HTTP client, which gets
+/// colors unless it asks otherwise
+pub fn is_terminal_client(user_agent: Option<&str>) -> bool {
+    let Some(user_agent) = user_agent.map(str::to_lowercase) else {
+        return false;
+    };
+    ["curl/", "wget/", "httpie/", "xh/"]
+        .iter()
+        .any(|client| user_agent.starts_with(client))
+}
+
+/// Render the contribution grid for a terminal, with totals and streaks.
+/// With `colors` (one per level, see [`ColorPalette::cell_colors`]) cells are
+/// drawn with 24-bit ANSI background colors, otherwise with block characters.
+pub fn render(username: &str, data: &HeatmapData, colors: Option<&[String]>) -> String {
+    let cell = |level: usize| match colors {
+        Some(colors) => {
+            let (r, g, b) = parse_hex(&colors[level.min(colors.len() - 1)]);
+            format!("\x1b[48;2;{};{};{}m  \x1b[0m", r, g, b)
+        }
+        None => LEVEL_CELLS[level].to_string(),
+    };
+
+    let mut out = format!(
+        "{}: {} from {} to {}\n\n",
+        username,
+        plural(data.total_count, "contribution"),
+        data.date_range_start,
+        data.date_range_end
+    );
+
+    out.push_str(&month_labels(&data.weeks));
+    for (row, label) in DAY_LABELS.iter().enumerate() {
+        out.push_str(label);
+        for week in &data.weeks {
+            match week.get(row) {
+                Some(day) if day.count >= 0 => {
+                    out.push_str(&cell(ColorPalette::level_for_count(day.count, data.max_count)))
+                }
+                _ => out.push_str("  "),
+            }
+        }
+        out.push('\n');
+    }
+
+    out.push_str("\n    Less ");
+    for level in 0..LEVEL_CELLS.len() {
+        out.push_str(&cell(level));
+    }
+    out.push_str(" More\n");
+
+    let (current, longest) = streaks(data);
+    out.push_str(&format!(
+        "\nCurrent streak: {}    Longest streak: {}\n",
+        plural(current, "day"),
+        plural(longest, "day")
+    ));
+
+    out
+}
+
+/// Month names above the first week of each month, skipped where they would
+/// run into the previous one
+fn month_labels(weeks: &[Vec<DayContribution>]) -> String {
+    let mut line = " ".repeat(DAY_LABELS[0].len() + weeks.len() * 2);
+    let mut previous_month = None;
+    let mut free_from = 0;
+
+    for (index, week) in weeks.iter().enumerate() {
+        let Some(first_day) = week.iter().find(|day| day.count >= 0) else {
+            continue;
+        };
+        let month = first_day.date.month0() as usize;
+        if previous_month != Some(month) {
+            let column = DAY_LABELS[0].len() + index * 2;
+            if column >= free_from && column + 3 <= line.len() {
+                line.replace_range(column..column + 3, MONTHS[month]);
+                free_from = column + 4;
+            }
+            previous_month = Some(month);
+        }
+    }
+
+    line.truncate(line.trim_end().len());
+    line.push('\n');
+    line
+}
+
+/// Current and longest runs of days with contributions within the shown
+/// range. Away days without contributions neither break nor extend a run.
+/// The current streak may end yesterday, since today is not over.
+pub fn streaks(data: &HeatmapData) -> (i32, i32) {
+    let days: Vec<&DayContribution> = data
+        .weeks
+        .iter()
+        .flatten()
+        .filter(|day| day.count >= 0)
+        .collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    for day in &days {
+        if day.count > 0 {
+            run += 1;
+        } else if !day.away {
+            run = 0;
+        }
+        longest = longest.max(run);
+    }
+
+    let current = days
+        .iter()
+        .rev()
+        .skip_while(|day| day.date == data.date_range_end && day.count == 0)
+        .take_while(|day| day.count > 0 || day.away)
+        .filter(|day| day.count > 0)
+        .count() as i32;
+
+    (current, longest)
+}
+
+pub fn plural(count: i32, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// RGB of a `#rrggbb` or `#rgb` color; grey for anything else
+fn parse_hex(color: &str) -> (u8, u8, u8) {
+    let hex = color.trim_start_matches('#');
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    let rgb = match hex.len() {
+        _ if !hex.is_ascii() => (None, None, None),
+        6 => (channel(&hex[0..2]), channel(&hex[2..4]), channel(&hex[4..6])),
+        3 => (
+            channel(&hex[0..1]).map(|v| v * 17),
+            channel(&hex[1..2]).map(|v| v * 17),
+            channel(&hex[2..3]).map(|v| v * 17),
+        ),
+        _ => (None, None, None),
+    };
+
+    match rgb {
+        (Some(r), Some(g), Some(b)) => (r, g, b),
+        _ => (128, 128, 128),
+    }
+}
+
+/// A grid of one symbol per day, for pasting where neither images nor colors
+/// work, such as chat statuses and social posts
+pub struct SymbolGrid {
+    /// Sunday first, oldest week on the left
+    pub rows: Vec<String>,
+    pub total_count: i32,
+}
+
+/// Parse comma-separated symbols, one per level starting with days without
+/// contributions. Levels past the last symbol use the last one.
+pub fn parse_symbols(symbols: &str) -> Result<Vec<String>, String> {
+    let symbols: Vec<String> = symbols.split(',').map(|s| s.trim().to_string()).collect();
+    if !(2..=MAX_SYMBOLS).contains(&symbols.len()) {
+        return Err(format!("symbols takes 2 to {} comma-separated symbols", MAX_SYMBOLS));
+    }
+    if symbols.iter().any(|s| s.is_empty() || s.len() > MAX_SYMBOL_LEN) {
+        return Err(format!("each symbol must be 1 to {} bytes long", MAX_SYMBOL_LEN));
+    }
+    Ok(symbols)
+}
+
+/// The last `weeks` weeks of the heatmap as symbols. Levels are those of the
+/// full range, so a quiet month stays quiet when shown alone.
+pub fn symbol_grid(data: &HeatmapData, weeks: usize, symbols: &[String]) -> SymbolGrid {
+    let shown = &data.weeks[data.weeks.len().saturating_sub(weeks)..];
+    let mut rows = vec![String::new(); 7];
+    let mut total_count = 0;
+
+    for week in shown {
+        for (row, day) in week.iter().enumerate() {
+            // Padding after the last day
+            if day.count < 0 {
+                continue;
+            }
+            let level = ColorPalette::level_for_count(day.count, data.max_count);
+            rows[row].push_str(&symbols[level.min(symbols.len() - 1)]);
+            total_count += day.count;
+        }
+    }
+
+    SymbolGrid { rows, total_count }
+}
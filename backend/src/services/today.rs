@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{contribution, git_platform_account, user_setting};
+use crate::services::achievements::user_timezone;
+use crate::utils::repo_exclusion;
+use crate::utils::today::{self, StreakStatus};
+
+/// A user's contributions so far today, for status bars and overlays
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TodayResponse {
+    /// Today in the user's timezone
+    pub date: NaiveDate,
+    pub timezone: String,
+    pub contributions: i64,
+    pub current_streak: i64,
+    pub streak_status: StreakStatus,
+    /// The user's next midnight, when the count starts over
+    pub resets_at: DateTime<Utc>,
+    /// When an account of the user last synced; today's count is as of then
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+/// Today's count and streak of the user, counting what their public stats
+/// count. Today is the user's local day, in the timezone heatmaps are
+/// scheduled in.
+pub async fn for_user(db: &DatabaseConnection, user_id: Uuid) -> Result<TodayResponse, DbErr> {
+    with_days(db, user_id).await.map(|(today, _)| today)
+}
+
+/// Like `for_user`, also returning the daily counts the streak was found in
+pub async fn with_days(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+) -> Result<(TodayResponse, BTreeMap<NaiveDate, i64>), DbErr> {
+    let settings = user_setting::Entity::find()
+        .filter(user_setting::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+    let show_private_contributions = settings.as_ref().map(|s| s.show_private_contributions).unwrap_or(true);
+    let excluded_repositories: Vec<String> = settings
+        .as_ref()
+        .map(|s| s.excluded_repositories.to_vec())
+        .unwrap_or_default();
+    let away = settings.as_ref().map(|s| s.away_periods.to_vec()).unwrap_or_default();
+
+    let timezone = user_timezone(db, user_id).await?;
+    let date = Utc::now().with_timezone(&timezone).date_naive();
+    // A midnight skipped by a DST change falls back to an hour from now
+    let resets_at = timezone
+        .from_local_datetime(&(date + Duration::days(1)).and_time(Default::default()))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc::now() + Duration::hours(1));
+
+    let accounts = git_platform_account::Entity::find()
+        .filter(git_platform_account::Column::UserId.eq(user_id))
+        .filter(git_platform_account::Column::IsActive.eq(true))
+        .all(db)
+        .await?;
+    let last_synced_at = accounts.iter().filter_map(|a| a.last_synced_at).max();
+
+    let mut query = contribution::Entity::find()
+        .select_only()
+        .columns([contribution::Column::ContributionDate, contribution::Column::Count])
+        .filter(contribution::Column::GitPlatformAccountId.is_in(accounts.iter().map(|a| a.id)))
+        .filter(repo_exclusion::not_excluded(
+            contribution::Column::RepositoryName,
+            &excluded_repositories,
+        ));
+    if !show_private_contributions {
+        query = query.filter(contribution::Column::IsPrivateRepo.eq(false));
+    }
+    let rows: Vec<(NaiveDate, i32)> = query.into_tuple().all(db).await?;
+
+    let mut days: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    for (day, count) in rows {
+        *days.entry(day).or_default() += count as i64;
+    }
+    let summary = today::summarize(&days, &away, date);
+
+    let today = TodayResponse {
+        date,
+        timezone: timezone.name().to_string(),
+        contributions: summary.contributions,
+        current_streak: summary.current_streak,
+        streak_status: summary.streak_status,
+        resets_at,
+        last_synced_at,
+    };
+    Ok((today, days))
+}
@@ -0,0 +1,92 @@
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, Func};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::models::{user, username_redirect};
+
+/// The current username of the user who used to be called `old_username`,
+/// while the redirect has not expired
+pub async fn resolve(db: &DatabaseConnection, old_username: &str) -> Result<Option<String>, DbErr> {
+    let Some(redirect) = username_redirect::Entity::find_by_id(old_username.to_lowercase())
+        .filter(username_redirect::Column::ExpiresAt.gt(Utc::now()))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(user::Entity::find_by_id(redirect.user_id)
+        .one(db)
+        .await?
+        .map(|user| user.username))
+}
+
+/// A redirect from a public URL containing an old username to the same URL
+/// with the current one, keeping the query string. It is temporary, since
+/// the old name can be taken by someone else once the redirect expires.
+pub async fn redirect(db: &DatabaseConnection, req: &HttpRequest, old_username: &str) -> Result<Option<HttpResponse>, DbErr> {
+    let Some(username) = resolve(db, old_username).await? else {
+        return Ok(None);
+    };
+
+    let old_segment = urlencoding::encode(old_username).into_owned();
+    let new_segment = urlencoding::encode(&username).into_owned();
+    let path = req
+        .path()
+        .split('/')
+        .map(|segment| {
+            if segment == old_segment || segment == old_username {
+                new_segment.as_str()
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    let location = match req.query_string() {
+        "" => path,
+        query => format!("{}?{}", path, query),
+    };
+
+    Ok(Some(
+        HttpResponse::TemporaryRedirect()
+            .insert_header((header::LOCATION, location))
+            .finish(),
+    ))
+}
+
+/// The user behind a public URL by case-insensitive username. Otherwise the
+/// response to send: a redirect while the name belongs to a renamed user, or
+/// a 404.
+pub async fn find_user(
+    db: &DatabaseConnection,
+    req: &HttpRequest,
+    username: &str,
+) -> Result<Result<user::Model, HttpResponse>, DbErr> {
+    let user = user::Entity::find()
+        .filter(Expr::expr(Func::lower(Expr::col(user::Column::Username))).eq(username.to_lowercase()))
+        .one(db)
+        .await?;
+    if let Some(user) = user {
+        return Ok(Ok(user));
+    }
+
+    // A renamed user's old URLs keep working for a while
+    Ok(Err(match redirect(db, req, username).await? {
+        Some(response) => response,
+        None => HttpResponse::NotFound().json(serde_json::json!({ "error": "User not found" })),
+    }))
+}
+
+/// Whether `username` still redirects to a user other than `except_user`
+pub async fn is_reserved(db: &DatabaseConnection, username: &str, except_user: Option<Uuid>) -> Result<bool, DbErr> {
+    let mut query = username_redirect::Entity::find_by_id(username.to_lowercase())
+        .filter(username_redirect::Column::ExpiresAt.gt(Utc::now()));
+    if let Some(user_id) = except_user {
+        query = query.filter(username_redirect::Column::UserId.ne(user_id));
+    }
+    Ok(query.one(db).await?.is_some())
+}
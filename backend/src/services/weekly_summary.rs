@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sea_orm::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration as TokioDuration};
+use uuid::Uuid;
+
+use crate::models::heatmap_theme::HeatmapFormat;
+use crate::models::{notification_channel, user};
+use crate::services::email_digest::{default_theme, generation_settings};
+use crate::services::heatmap_generator::HeatmapGenerator;
+use crate::services::instance_settings::InstanceSettingsService;
+use crate::services::mailer::Mailer;
+use crate::services::notification_channels::{self, ChannelContext, ChannelEvent, Payload, PostImage, WEEKLY_SUMMARY};
+use crate::services::profile_visibility;
+use crate::services::render_lanes::Lane;
+use crate::services::shutdown::Shutdown;
+use crate::services::text_heatmap::{plural, streaks};
+use crate::utils::config::Config;
+use crate::utils::social_post::{summary_text, WeekSummary};
+
+/// How often the job looks for summaries that are due
+const CHECK_INTERVAL_MINUTES: u64 = 60;
+
+/// When the channel's next weekly summary goes out
+pub fn next_post_at(channel: &notification_channel::Model) -> DateTime<Utc> {
+    channel.last_sent_at + Duration::days(7)
+}
+
+/// Start the background job that posts weekly summaries to the users'
+/// notification channels that take them, as they fall due
+pub fn start_weekly_summary_job(
+    db: DatabaseConnection,
+    config: Config,
+    instance_settings: Arc<InstanceSettingsService>,
+    mut shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        log::info!("Starting weekly summary job (every {} minutes)", CHECK_INTERVAL_MINUTES);
+        let ctx = ChannelContext {
+            db,
+            encryption_key: config.encryption_key.clone(),
+            mailer: Arc::new(Mailer::from_config(&config)),
+        };
+
+        loop {
+            match post_due(&ctx, &instance_settings).await {
+                Ok(0) => {}
+                Ok(posted) => log::info!("📣 [Weekly summary] Sent {}", plural(posted, "post")),
+                Err(e) => log::error!("Error in weekly summary job: {}", e),
+            }
+
+            tokio::select! {
+                _ = sleep(TokioDuration::from_secs(CHECK_INTERVAL_MINUTES * 60)) => {}
+                _ = shutdown.requested() => break,
+            }
+        }
+    })
+}
+
+/// Post every summary that is due. A summary that fails is retried on the
+/// next run, and the error is kept for the user to see.
+async fn post_due(ctx: &ChannelContext, instance_settings: &InstanceSettingsService) -> Result<i32> {
+    let db = &ctx.db;
+    let now = Utc::now();
+    let due = notification_channel::Entity::find()
+        .filter(notification_channel::Column::LastSentAt.lte(now - Duration::days(7)))
+        .find_also_related(user::Entity)
+        .all(db)
+        .await?;
+
+    let frontend_url = instance_settings.current().await.frontend_url;
+    // Channels of one user share the post; each covers its own week, so the
+    // post is keyed by the week too
+    let mut posts: HashMap<(Uuid, NaiveDate), Payload> = HashMap::new();
+    let mut posted = 0;
+
+    for (channel, user) in due {
+        let Some(user) = user else { continue };
+        if !channel.events.iter().any(|e| e == WEEKLY_SUMMARY) {
+            continue;
+        }
+        let from = channel.last_sent_at.date_naive();
+
+        let result = async {
+            let mut post = match posts.get(&(user.id, from)) {
+                Some(post) => post.clone(),
+                None => {
+                    let post = build_post(db, &user, from, &frontend_url).await?;
+                    posts.insert((user.id, from), post.clone());
+                    post
+                }
+            };
+            // Retries of the same week send the same key
+            post.idempotency_key = format!("hgitmap-weekly-{}-{}", channel.id, channel.last_sent_at.timestamp());
+            let client = notification_channels::from_model(ctx, &channel).await?;
+            client.send(ChannelEvent::WeeklySummary, &post).await
+        }
+        .await;
+
+        let mut active: notification_channel::ActiveModel = channel.clone().into();
+        match result {
+            Ok(()) => {
+                active.last_sent_at = Set(now);
+                active.last_error = Set(None);
+                posted += 1;
+            }
+            Err(e) => {
+                log::warn!(
+                    "⚠️  [Weekly summary] Failed to post to {} for {}: {:#}",
+                    channel.kind,
+                    user.username,
+                    e
+                );
+                active.last_error = Set(Some(format!("{:#}", e)));
+            }
+        }
+        active.update(db).await?;
+    }
+
+    Ok(posted)
+}
+
+/// The summary of the whole days from `from` up to yesterday, with the
+/// current heatmap in the user's default theme attached
+async fn build_post(db: &DatabaseConnection, user: &user::Model, from: NaiveDate, frontend_url: &str) -> Result<Payload> {
+    let to = Utc::now().date_naive() - Duration::days(1);
+
+    let generator = HeatmapGenerator::new(db.clone());
+    let settings = generation_settings(db, user.id).await?;
+    let data = generator.fetch_contribution_data(user.id, &settings).await?;
+
+    let in_week: Vec<_> = data
+        .weeks
+        .iter()
+        .flatten()
+        .filter(|day| day.count >= 0 && day.date >= from && day.date <= to)
+        .collect();
+    let busiest = in_week
+        .iter()
+        .filter(|day| day.count > 0)
+        .max_by_key(|day| day.count)
+        .map(|day| (day.date, day.count));
+
+    // Unlisted and private profiles are not linked from a public post
+    let visibility = profile_visibility::find(db, user.id).await?;
+    let profile_url = (visibility == profile_visibility::PUBLIC)
+        .then(|| format!("{}/{}", frontend_url.trim_end_matches('/'), user.username));
+
+    let summary = WeekSummary {
+        contributions: in_week.iter().map(|day| day.count).sum(),
+        active_days: in_week.iter().filter(|day| day.count > 0).count() as i32,
+        busiest,
+        current_streak: streaks(&data).0,
+        profile_url,
+    };
+
+    // Without a theme there is nothing to draw the heatmap with
+    let image = match default_theme(db, user.id).await? {
+        Some(theme) => Some(PostImage {
+            png: generator
+                .render(Lane::Bulk, &theme, &data, &HeatmapFormat::Png, Some(&user.username))
+                .await
+                .context("Failed to render heatmap")?,
+            description: format!("Contribution heatmap of {} for the past year", user.username),
+        }),
+        None => None,
+    };
+
+    Ok(Payload {
+        title: format!("Your coding week, {} to {}", from.format("%B %-d"), to.format("%B %-d")),
+        text: summary_text(&summary),
+        image,
+        data: json!({
+            "from": from,
+            "to": to,
+            "contributions": summary.contributions,
+            "active_days": summary.active_days,
+            "busiest_day": summary.busiest.map(|(date, count)| json!({ "date": date, "count": count })),
+            "current_streak": summary.current_streak,
+            "profile_url": summary.profile_url,
+        }),
+        // Set per channel
+        idempotency_key: String::new(),
+    })
+}
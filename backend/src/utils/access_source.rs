@@ -0,0 +1,65 @@
+/// Kinds of client counted in heatmap analytics
+pub const BROWSER: &str = "browser";
+/// GitHub's image proxy, which fetches images shown in READMEs
+pub const GITHUB: &str = "github";
+pub const BOT: &str = "bot";
+pub const OTHER: &str = "other";
+
+/// Longest referrer host kept; longer ones are cut
+const MAX_HOST_LEN: usize = 255;
+
+/// The kind of client behind a User-Agent header
+pub fn client_category(user_agent: Option<&str>) -> &'static str {
+    let Some(user_agent) = user_agent.map(str::to_lowercase) else {
+        return OTHER;
+    };
+
+    if user_agent.starts_with("github-camo") {
+        GITHUB
+    } else if ["bot", "crawler", "spider", "slurp", "preview"]
+        .iter()
+        .any(|word| user_agent.contains(word))
+    {
+        BOT
+    } else if user_agent.starts_with("mozilla/") {
+        BROWSER
+    } else {
+        OTHER
+    }
+}
+
+/// A referrer host as stored, empty when there is none
+pub fn stored_host(host: Option<String>) -> String {
+    let host = host.unwrap_or_default();
+    match host.char_indices().nth(MAX_HOST_LEN) {
+        Some((idx, _)) => host[..idx].to_string(),
+        None => host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categorizes_clients() {
+        assert_eq!(client_category(Some("github-camo (876de43e)")), GITHUB);
+        assert_eq!(
+            client_category(Some("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)")),
+            BOT
+        );
+        assert_eq!(
+            client_category(Some("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0")),
+            BROWSER
+        );
+        assert_eq!(client_category(Some("curl/8.5.0")), OTHER);
+        assert_eq!(client_category(None), OTHER);
+    }
+
+    #[test]
+    fn limits_stored_hosts() {
+        assert_eq!(stored_host(None), "");
+        assert_eq!(stored_host(Some("example.com".to_string())), "example.com");
+        assert_eq!(stored_host(Some("a".repeat(300))).len(), MAX_HOST_LEN);
+    }
+}
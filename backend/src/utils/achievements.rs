@@ -0,0 +1,198 @@
+use chrono::{DateTime, NaiveDate, Timelike};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use crate::models::user_setting::AwayPeriod;
+use crate::utils::away_periods;
+
+/// An achievement and how it is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Achievement {
+    pub key: &'static str,
+    pub emoji: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+pub const NIGHT_OWL: Achievement = Achievement {
+    key: "night_owl",
+    emoji: "🦉",
+    title: "Night owl",
+    description: "10 activities between midnight and 5am",
+};
+
+pub const EARLY_BIRD: Achievement = Achievement {
+    key: "early_bird",
+    emoji: "🐦",
+    title: "Early bird",
+    description: "10 activities between 5am and 8am",
+};
+
+pub const POLYGLOT: Achievement = Achievement {
+    key: "polyglot",
+    emoji: "🗣️",
+    title: "Polyglot",
+    description: "Repositories in 5 different languages",
+};
+
+pub const MARATHONER: Achievement = Achievement {
+    key: "marathoner",
+    emoji: "🏃",
+    title: "Marathoner",
+    description: "Contributed 30 days in a row",
+};
+
+/// Every achievement, in display order
+pub const ALL: &[Achievement] = &[NIGHT_OWL, EARLY_BIRD, POLYGLOT, MARATHONER];
+
+/// Activities needed at night or in the early morning
+const TIMED_ACTIVITIES: usize = 10;
+const NIGHT_HOURS: Range<u32> = 0..5;
+const MORNING_HOURS: Range<u32> = 5..8;
+const LANGUAGES: usize = 5;
+const MARATHON_DAYS: i64 = 30;
+
+/// What the achievements are evaluated from
+pub struct History<'a> {
+    /// When timestamped activities happened, in the user's timezone
+    pub activity_times: Vec<DateTime<Tz>>,
+    /// The primary language of repositories by the day they were active
+    pub languages: Vec<(NaiveDate, &'a str)>,
+    /// Contributions per day
+    pub days: &'a BTreeMap<NaiveDate, i64>,
+    /// Away days bridge a streak, as in the stats
+    pub away: &'a [AwayPeriod],
+}
+
+/// The achievements the history earns, each with the day it was first earned
+pub fn evaluate(history: &History) -> Vec<(Achievement, NaiveDate)> {
+    let mut earned = Vec::new();
+    if let Some(date) = nth_in_hours(&history.activity_times, NIGHT_HOURS) {
+        earned.push((NIGHT_OWL, date));
+    }
+    if let Some(date) = nth_in_hours(&history.activity_times, MORNING_HOURS) {
+        earned.push((EARLY_BIRD, date));
+    }
+    if let Some(date) = nth_language(&history.languages) {
+        earned.push((POLYGLOT, date));
+    }
+    if let Some(date) = first_streak(history.days, history.away) {
+        earned.push((MARATHONER, date));
+    }
+    earned
+}
+
+/// A timestamp stored in activity metadata, in `timezone`. None for
+/// anything but RFC 3339.
+pub fn parse_time(value: &str, timezone: Tz) -> Option<DateTime<Tz>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&timezone))
+}
+
+/// The local day of the TIMED_ACTIVITIES-th activity within `hours`
+fn nth_in_hours(times: &[DateTime<Tz>], hours: Range<u32>) -> Option<NaiveDate> {
+    let mut matching: Vec<&DateTime<Tz>> = times.iter().filter(|t| hours.contains(&t.hour())).collect();
+    matching.sort();
+    matching.get(TIMED_ACTIVITIES - 1).map(|t| t.date_naive())
+}
+
+/// The day the LANGUAGES-th distinct language showed up
+fn nth_language(languages: &[(NaiveDate, &str)]) -> Option<NaiveDate> {
+    let mut sorted = languages.to_vec();
+    sorted.sort();
+
+    let mut seen = HashSet::new();
+    for (date, language) in sorted {
+        if seen.insert(language.to_lowercase()) && seen.len() == LANGUAGES {
+            return Some(date);
+        }
+    }
+    None
+}
+
+/// The day a streak first reached MARATHON_DAYS
+fn first_streak(days: &BTreeMap<NaiveDate, i64>, away: &[AwayPeriod]) -> Option<NaiveDate> {
+    let mut previous: Option<NaiveDate> = None;
+    let mut length = 0;
+
+    for (&date, _) in days.iter().filter(|(_, &count)| count > 0) {
+        length = match previous {
+            Some(previous) if away_periods::bridges(away, previous, date) => length + 1,
+            _ => 1,
+        };
+        previous = Some(date);
+        if length >= MARATHON_DAYS {
+            return Some(date);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn keys(earned: &[(Achievement, NaiveDate)]) -> Vec<&'static str> {
+        earned.iter().map(|(a, _)| a.key).collect()
+    }
+
+    #[test]
+    fn hours_are_counted_in_the_users_timezone() {
+        let days = BTreeMap::new();
+        // 23:30 UTC is 01:30 in Berlin's summer
+        let times: Vec<DateTime<Tz>> = (1..=10)
+            .map(|d| parse_time(&format!("2026-06-{:02}T23:30:00Z", d), chrono_tz::Europe::Berlin).unwrap())
+            .collect();
+        let history = History { activity_times: times, languages: Vec::new(), days: &days, away: &[] };
+        let earned = evaluate(&history);
+        assert_eq!(keys(&earned), ["night_owl"]);
+        assert_eq!(earned[0].1, date("2026-06-11"));
+
+        let utc: Vec<DateTime<Tz>> = (1..=10)
+            .map(|d| parse_time(&format!("2026-06-{:02}T23:30:00Z", d), Tz::UTC).unwrap())
+            .collect();
+        let history = History { activity_times: utc, languages: Vec::new(), days: &days, away: &[] };
+        assert!(evaluate(&history).is_empty());
+        assert!(parse_time("2026-06-01", Tz::UTC).is_none());
+    }
+
+    #[test]
+    fn polyglot_needs_five_distinct_languages() {
+        let days = BTreeMap::new();
+        let languages = vec![
+            (date("2026-01-05"), "Rust"),
+            (date("2026-01-01"), "rust"),
+            (date("2026-01-02"), "Go"),
+            (date("2026-01-03"), "Python"),
+            (date("2026-01-04"), "C"),
+        ];
+        let history = History { activity_times: Vec::new(), languages: languages.clone(), days: &days, away: &[] };
+        assert!(evaluate(&history).is_empty());
+
+        let mut languages = languages;
+        languages.push((date("2026-01-09"), "Zig"));
+        let history = History { activity_times: Vec::new(), languages, days: &days, away: &[] };
+        assert_eq!(evaluate(&history), [(POLYGLOT, date("2026-01-09"))]);
+    }
+
+    #[test]
+    fn marathoner_bridges_away_days() {
+        let start = date("2026-03-01");
+        let days: BTreeMap<NaiveDate, i64> = (0..31)
+            .filter(|&d| d != 10)
+            .map(|d| (start + chrono::Duration::days(d), 1))
+            .collect();
+        let history = History { activity_times: Vec::new(), languages: Vec::new(), days: &days, away: &[] };
+        assert!(evaluate(&history).is_empty());
+
+        let away = [AwayPeriod { start: date("2026-03-11"), end: date("2026-03-11") }];
+        let history = History { activity_times: Vec::new(), languages: Vec::new(), days: &days, away: &away };
+        assert_eq!(evaluate(&history), [(MARATHONER, date("2026-03-31"))]);
+    }
+}
@@ -0,0 +1,57 @@
+/// Longest annotation text, in characters
+pub const MAX_TEXT_LENGTH: usize = 200;
+
+/// Longest emoji, in characters. Leaves room for joined sequences like 👩‍💻.
+pub const MAX_EMOJI_LENGTH: usize = 16;
+
+/// Most annotations a user can keep
+pub const MAX_ANNOTATIONS: u64 = 1000;
+
+/// Validate annotation text, returning it trimmed
+pub fn normalize_text(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("text is required".to_string());
+    }
+    if text.chars().count() > MAX_TEXT_LENGTH {
+        return Err(format!("text must be at most {} characters", MAX_TEXT_LENGTH));
+    }
+    Ok(text.to_string())
+}
+
+/// Validate an optional emoji, returning it trimmed. Blank means none.
+pub fn normalize_emoji(emoji: Option<&str>) -> Result<Option<String>, String> {
+    let Some(emoji) = emoji.map(str::trim).filter(|e| !e.is_empty()) else {
+        return Ok(None);
+    };
+    if emoji.chars().count() > MAX_EMOJI_LENGTH
+        || emoji.chars().any(char::is_whitespace)
+        || emoji.is_ascii()
+    {
+        return Err("emoji must be a single emoji".to_string());
+    }
+    Ok(Some(emoji.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_and_limits_text() {
+        assert_eq!(normalize_text("  shipped v2.0 ").unwrap(), "shipped v2.0");
+        assert!(normalize_text("   ").is_err());
+        assert!(normalize_text(&"é".repeat(MAX_TEXT_LENGTH)).is_ok());
+        assert!(normalize_text(&"é".repeat(MAX_TEXT_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn accepts_emoji_only() {
+        assert_eq!(normalize_emoji(None).unwrap(), None);
+        assert_eq!(normalize_emoji(Some(" ")).unwrap(), None);
+        assert_eq!(normalize_emoji(Some(" 🚀 ")).unwrap().as_deref(), Some("🚀"));
+        assert_eq!(normalize_emoji(Some("👩‍💻")).unwrap().as_deref(), Some("👩‍💻"));
+        assert!(normalize_emoji(Some("ship")).is_err());
+        assert!(normalize_emoji(Some("🚀 🎉")).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Prefix that distinguishes API tokens from JWT session tokens
+pub const TOKEN_PREFIX: &str = "hgm_";
+
+/// Scope that implies every other scope
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// All scopes a token can be granted
+pub const SCOPES: &[&str] = &[
+    "read:platforms",
+    "write:platforms",
+    "read:contributions",
+    "read:activities",
+    "read:following",
+    "write:following",
+    "read:annotations",
+    "write:annotations",
+    "read:settings",
+    "write:settings",
+    "read:sync",
+    "write:sync",
+    "read:themes",
+    "write:themes",
+    "read:heatmaps",
+    "write:heatmaps",
+    "read:teams",
+    "write:teams",
+    "read:graphql",
+    ADMIN_SCOPE,
+];
+
+/// Generate a new random API token. Only its hash is stored.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(bytes))
+}
+
+/// SHA-256 of the token as stored in the database
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
+
+/// Validate and normalize requested scopes, rejecting unknown ones
+pub fn normalize_scopes(scopes: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for scope in scopes {
+        let scope = scope.trim().to_lowercase();
+        if !SCOPES.contains(&scope.as_str()) {
+            return Err(format!("Unknown scope: {}", scope));
+        }
+        if !normalized.contains(&scope) {
+            normalized.push(scope);
+        }
+    }
+
+    if normalized.is_empty() {
+        return Err("At least one scope is required".to_string());
+    }
+
+    normalized.sort();
+    Ok(normalized)
+}
+
+/// Scope needed to call a route group: read for safe methods, write otherwise
+pub fn required_scope(resource: &str, method: &actix_web::http::Method) -> String {
+    let access = if method.is_safe() { "read" } else { "write" };
+    format!("{}:{}", access, resource)
+}
+
+pub fn grants(granted: &[String], required: &str) -> bool {
+    granted
+        .iter()
+        .any(|scope| scope == required || scope == ADMIN_SCOPE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::Method;
+
+    #[test]
+    fn test_generate_and_hash_token() {
+        let token = generate_token();
+        assert!(is_api_token(&token));
+        assert_eq!(token.len(), TOKEN_PREFIX.len() + 64);
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), hash_token(&generate_token()));
+    }
+
+    #[test]
+    fn test_normalize_scopes() {
+        let scopes = vec!["Write:Themes".to_string(), "read:contributions".to_string(), "write:themes".to_string()];
+        assert_eq!(
+            normalize_scopes(&scopes).unwrap(),
+            vec!["read:contributions".to_string(), "write:themes".to_string()]
+        );
+        assert!(normalize_scopes(&["write:everything".to_string()]).is_err());
+        assert!(normalize_scopes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_scope_checks() {
+        assert_eq!(required_scope("themes", &Method::GET), "read:themes");
+        assert_eq!(required_scope("themes", &Method::DELETE), "write:themes");
+
+        let read_only = vec!["read:contributions".to_string()];
+        assert!(grants(&read_only, "read:contributions"));
+        assert!(!grants(&read_only, "write:themes"));
+        assert!(grants(&[ADMIN_SCOPE.to_string()], "write:themes"));
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Duration, DurationRound, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode, Url};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    static RECORDER: UsageRecorder;
+}
+
+/// Kind of platform API a request went to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Api {
+    Rest,
+    GraphQl,
+}
+
+impl Api {
+    /// GitHub and GitLab serve GraphQL at `.../graphql`; everything else is REST
+    pub fn of(url: &Url) -> Self {
+        if url.path().trim_end_matches('/').ends_with("/graphql") {
+            Api::GraphQl
+        } else {
+            Api::Rest
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Api::Rest => "rest",
+            Api::GraphQl => "graphql",
+        }
+    }
+}
+
+/// Rate limit a platform reported with a response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimit {
+    pub remaining: Option<i32>,
+    pub limit: Option<i32>,
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    /// Read GitHub's `X-RateLimit-*` or GitLab's `RateLimit-*` headers; None
+    /// when the platform sent neither, as Gitea does
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            [format!("x-ratelimit-{}", name), format!("ratelimit-{}", name)]
+                .iter()
+                .find_map(|key| headers.get(key.as_str()))
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<i64>().ok())
+        };
+
+        let rate_limit = Self {
+            remaining: header("remaining").and_then(|v| i32::try_from(v).ok()),
+            limit: header("limit").and_then(|v| i32::try_from(v).ok()),
+            reset_at: header("reset").and_then(|v| Utc.timestamp_opt(v, 0).single()),
+        };
+        (rate_limit != Self::default()).then_some(rate_limit)
+    }
+}
+
+/// Calls to one kind of API in one hour
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tally {
+    pub requests: i32,
+    /// Requests that got no response or a 5xx
+    pub failed: i32,
+    /// Responses refusing the request for its rate limit
+    pub rate_limited: i32,
+    /// As of the last response that reported one
+    pub rate_limit: Option<RateLimit>,
+    pub last_called_at: Option<DateTime<Utc>>,
+}
+
+/// Calls by kind of API and the hour they were made in
+pub type Tallies = HashMap<(Api, DateTime<Utc>), Tally>;
+
+/// Counts the platform API calls made while a future runs, so they can be
+/// stored for the account the future works for
+#[derive(Debug, Clone, Default)]
+pub struct UsageRecorder(Arc<Mutex<Tallies>>);
+
+impl UsageRecorder {
+    /// Run `future` with the calls it sends through `send_traced` counted here
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        RECORDER.scope(self.clone(), future).await
+    }
+
+    /// The calls counted so far, leaving the recorder empty
+    pub fn take(&self) -> Tallies {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn add(&self, api: Api, at: DateTime<Utc>, status: Option<StatusCode>, rate_limit: Option<RateLimit>) {
+        let hour = at.duration_trunc(Duration::hours(1)).unwrap_or(at);
+        let mut tallies = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let tally = tallies.entry((api, hour)).or_default();
+
+        tally.requests += 1;
+        tally.last_called_at = Some(at);
+        match status {
+            None => tally.failed += 1,
+            Some(status) if status.is_server_error() => tally.failed += 1,
+            Some(status) if is_rate_limited(status, rate_limit.as_ref()) => tally.rate_limited += 1,
+            Some(_) => {}
+        }
+        if rate_limit.is_some() {
+            tally.rate_limit = rate_limit;
+        }
+    }
+}
+
+/// A 429, or a 403 sent with no requests remaining as GitHub does
+fn is_rate_limited(status: StatusCode, rate_limit: Option<&RateLimit>) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && rate_limit.is_some_and(|limit| limit.remaining == Some(0)))
+}
+
+/// Count a request in the recorder of the current task, if there is one.
+/// `response` is None when the request failed without a response.
+pub fn record(url: &Url, response: Option<&Response>) {
+    let _ = RECORDER.try_with(|recorder| {
+        recorder.add(
+            Api::of(url),
+            Utc::now(),
+            response.map(|r| r.status()),
+            response.and_then(|r| RateLimit::from_headers(r.headers())),
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn tells_graphql_from_rest() {
+        let graphql = Url::parse("https://api.github.com/graphql").unwrap();
+        let rest = Url::parse("https://gitlab.com/api/v4/users/1/events").unwrap();
+        assert_eq!(Api::of(&graphql), Api::GraphQl);
+        assert_eq!(Api::of(&rest), Api::Rest);
+    }
+
+    #[test]
+    fn reads_rate_limit_headers() {
+        let mut github = HeaderMap::new();
+        github.insert("x-ratelimit-remaining", HeaderValue::from_static("4990"));
+        github.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        github.insert("x-ratelimit-reset", HeaderValue::from_static("1735689600"));
+        assert_eq!(
+            RateLimit::from_headers(&github),
+            Some(RateLimit {
+                remaining: Some(4990),
+                limit: Some(5000),
+                reset_at: Utc.timestamp_opt(1735689600, 0).single(),
+            })
+        );
+
+        let mut gitlab = HeaderMap::new();
+        gitlab.insert("ratelimit-remaining", HeaderValue::from_static("0"));
+        assert_eq!(RateLimit::from_headers(&gitlab).unwrap().remaining, Some(0));
+
+        assert_eq!(RateLimit::from_headers(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn tallies_calls_by_api_and_hour() {
+        let recorder = UsageRecorder::default();
+        let at = Utc.with_ymd_and_hms(2025, 1, 10, 14, 35, 0).unwrap();
+        let exhausted = RateLimit {
+            remaining: Some(0),
+            ..Default::default()
+        };
+
+        recorder.add(Api::Rest, at, Some(StatusCode::OK), None);
+        recorder.add(Api::Rest, at, None, None);
+        recorder.add(Api::Rest, at, Some(StatusCode::FORBIDDEN), Some(exhausted.clone()));
+        recorder.add(Api::Rest, at, Some(StatusCode::FORBIDDEN), None);
+        recorder.add(Api::GraphQl, at + Duration::hours(1), Some(StatusCode::OK), None);
+
+        let tallies = recorder.take();
+        let hour = Utc.with_ymd_and_hms(2025, 1, 10, 14, 0, 0).unwrap();
+        let rest = &tallies[&(Api::Rest, hour)];
+        assert_eq!((rest.requests, rest.failed, rest.rate_limited), (4, 1, 1));
+        assert_eq!(rest.rate_limit, Some(exhausted));
+        assert_eq!(tallies[&(Api::GraphQl, hour + Duration::hours(1))].requests, 1);
+        assert!(recorder.take().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use actix_web::http::header::HeaderMap;
+use chrono::NaiveDate;
+
+/// Version served under /api/v1
+pub const CURRENT_VERSION: u32 = 1;
+pub const SUPPORTED_VERSIONS: &[u32] = &[1];
+
+/// Request/response header naming the API version
+pub const API_VERSION_HEADER: &str = "API-Version";
+/// Vendor media type prefix, e.g. `application/vnd.hgitmap.v1+json`
+const VENDOR_MEDIA_TYPE_PREFIX: &str = "application/vnd.hgitmap.v";
+
+/// Date from which the unversioned root routes are deprecated
+pub fn legacy_deprecated_since() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, 17).expect("valid date")
+}
+
+/// Version explicitly requested by the client, either via the `API-Version`
+/// header or a vendor media type in `Accept`. `Ok(None)` means no preference.
+pub fn requested_version(headers: &HeaderMap) -> Result<Option<u32>, String> {
+    if let Some(value) = headers.get(API_VERSION_HEADER) {
+        let value = value.to_str().map_err(|_| "Invalid API-Version header".to_string())?;
+        let version = value
+            .trim()
+            .trim_start_matches(['v', 'V'])
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid API-Version header: {}", value))?;
+        return Ok(Some(version));
+    }
+
+    let accept = headers
+        .get(actix_web::http::header::ACCEPT)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+    for media_type in accept.split(',') {
+        let media_type = media_type.split(';').next().unwrap_or_default().trim();
+        if let Some(rest) = media_type.strip_prefix(VENDOR_MEDIA_TYPE_PREFIX) {
+            let version = rest.split('+').next().unwrap_or_default();
+            return version
+                .parse::<u32>()
+                .map(Some)
+                .map_err(|_| format!("Invalid media type version: {}", media_type));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Resolve the version to serve, defaulting to the current one
+pub fn negotiate(headers: &HeaderMap) -> Result<u32, String> {
+    match requested_version(headers)? {
+        None => Ok(CURRENT_VERSION),
+        Some(v) if SUPPORTED_VERSIONS.contains(&v) => Ok(v),
+        Some(v) => Err(format!(
+            "Unsupported API version: {}. Supported versions: {}",
+            v,
+            SUPPORTED_VERSIONS
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Format a date as an HTTP date (IMF-fixdate), as used by the Sunset header
+pub fn http_date(date: NaiveDate) -> String {
+    date.and_hms_opt(0, 0, 0)
+        .expect("valid time")
+        .and_utc()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// Format a date as a structured-field date, as used by the Deprecation header
+pub fn structured_date(date: NaiveDate) -> String {
+    format!(
+        "@{}",
+        date.and_hms_opt(0, 0, 0).expect("valid time").and_utc().timestamp()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue, ACCEPT};
+
+    fn headers(name: HeaderName, value: &str) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        map.insert(name, HeaderValue::from_str(value).unwrap());
+        map
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&HeaderMap::new()), Ok(1));
+        assert_eq!(
+            negotiate(&headers(HeaderName::from_static("api-version"), "1")),
+            Ok(1)
+        );
+        assert_eq!(
+            negotiate(&headers(ACCEPT, "application/vnd.hgitmap.v1+json, */*;q=0.8")),
+            Ok(1)
+        );
+        assert!(negotiate(&headers(HeaderName::from_static("api-version"), "2")).is_err());
+        assert!(negotiate(&headers(HeaderName::from_static("api-version"), "latest")).is_err());
+    }
+
+    #[test]
+    fn test_header_dates() {
+        let date = NaiveDate::from_ymd_opt(2027, 6, 30).unwrap();
+        assert_eq!(http_date(date), "Wed, 30 Jun 2027 00:00:00 GMT");
+        assert_eq!(structured_date(date), "@1814313600");
+    }
+}
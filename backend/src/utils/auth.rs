@@ -1,30 +1,48 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Prefix of refresh tokens, which are opaque and stored hashed
+pub const REFRESH_TOKEN_PREFIX: &str = "hgr_";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // user_id
     pub exp: i64,    // expiration timestamp
     pub iat: i64,    // issued at timestamp
+    /// Scopes granted to an API token; None for full user sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    /// Login session the access token belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 impl Claims {
-    pub fn new(user_id: Uuid, expiration_hours: i64) -> Self {
+    pub fn new(user_id: Uuid, session_id: Uuid, expiration_minutes: i64) -> Self {
         let now = Utc::now();
-        let exp = (now + Duration::hours(expiration_hours)).timestamp();
+        let exp = (now + Duration::minutes(expiration_minutes)).timestamp();
 
         Self {
             sub: user_id.to_string(),
             exp,
             iat: now.timestamp(),
+            scopes: None,
+            sid: Some(session_id.to_string()),
         }
     }
 }
 
-pub fn create_jwt(user_id: Uuid, secret: &str, expiration_hours: i64) -> Result<String, jsonwebtoken::errors::Error> {
-    let claims = Claims::new(user_id, expiration_hours);
+/// Create a short-lived access token for a login session
+pub fn create_jwt(
+    user_id: Uuid,
+    session_id: Uuid,
+    secret: &str,
+    expiration_minutes: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = Claims::new(user_id, session_id, expiration_minutes);
     encode(
         &Header::default(),
         &claims,
@@ -42,6 +60,13 @@ pub fn decode_jwt(token: &str, secret: &str) -> Result<Claims, jsonwebtoken::err
     Ok(token_data.claims)
 }
 
+/// Generate a new random refresh token. Only its hash is stored.
+pub fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", REFRESH_TOKEN_PREFIX, hex::encode(bytes))
+}
+
 pub fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
     bcrypt::hash(password, bcrypt::DEFAULT_COST)
 }
@@ -0,0 +1,108 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::models::user_setting::AwayPeriod;
+
+/// Most periods a user can mark
+pub const MAX_PERIODS: usize = 50;
+
+/// Validate away periods, returning them sorted with overlapping and
+/// back-to-back periods merged
+pub fn normalize(periods: &[AwayPeriod]) -> Result<Vec<AwayPeriod>, String> {
+    if let Some(period) = periods.iter().find(|p| p.end < p.start) {
+        return Err(format!("Away period {} to {} ends before it starts", period.start, period.end));
+    }
+
+    let mut sorted = periods.to_vec();
+    sorted.sort();
+
+    let mut normalized: Vec<AwayPeriod> = Vec::new();
+    for period in sorted {
+        match normalized.last_mut() {
+            Some(last) if period.start <= last.end + Duration::days(1) => {
+                last.end = last.end.max(period.end);
+            }
+            _ => normalized.push(period),
+        }
+    }
+
+    if normalized.len() > MAX_PERIODS {
+        return Err(format!("At most {} away periods can be marked", MAX_PERIODS));
+    }
+
+    Ok(normalized)
+}
+
+/// Whether `date` is in one of the periods
+pub fn contains(periods: &[AwayPeriod], date: NaiveDate) -> bool {
+    periods.iter().any(|p| p.start <= date && date <= p.end)
+}
+
+/// Whether every day strictly between `from` and `to` is away, so a streak
+/// running on `to` continues from `from`. True for consecutive days.
+pub fn bridges(periods: &[AwayPeriod], from: NaiveDate, to: NaiveDate) -> bool {
+    let mut date = from + Duration::days(1);
+    while date < to {
+        // Jump to the end of the period holding the day, if any
+        match periods.iter().find(|p| p.start <= date && date <= p.end) {
+            Some(period) => date = period.end + Duration::days(1),
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(start: &str, end: &str) -> AwayPeriod {
+        AwayPeriod {
+            start: start.parse().unwrap(),
+            end: end.parse().unwrap(),
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn merges_overlapping_periods() {
+        let periods = vec![
+            period("2026-07-10", "2026-07-20"),
+            period("2026-07-01", "2026-07-12"),
+            period("2026-07-21", "2026-07-22"),
+            period("2026-12-24", "2026-12-26"),
+        ];
+        assert_eq!(
+            normalize(&periods).unwrap(),
+            vec![period("2026-07-01", "2026-07-22"), period("2026-12-24", "2026-12-26")]
+        );
+    }
+
+    #[test]
+    fn rejects_reversed_and_too_many_periods() {
+        assert!(normalize(&[period("2026-07-10", "2026-07-01")]).is_err());
+
+        let start = date("2020-01-01");
+        let many: Vec<AwayPeriod> = (0..=MAX_PERIODS as i64)
+            .map(|i| AwayPeriod {
+                start: start + Duration::days(i * 3),
+                end: start + Duration::days(i * 3),
+            })
+            .collect();
+        assert!(normalize(&many).is_err());
+    }
+
+    #[test]
+    fn bridges_only_fully_away_gaps() {
+        let periods = vec![period("2026-07-01", "2026-07-14"), period("2026-07-15", "2026-07-16")];
+
+        assert!(bridges(&periods, date("2026-06-30"), date("2026-07-17")));
+        assert!(bridges(&[], date("2026-06-30"), date("2026-07-01")));
+        assert!(!bridges(&periods, date("2026-06-29"), date("2026-07-17")));
+        assert!(!bridges(&periods, date("2026-06-30"), date("2026-07-18")));
+        assert!(contains(&periods, date("2026-07-16")));
+        assert!(!contains(&periods, date("2026-07-17")));
+    }
+}
@@ -0,0 +1,82 @@
+use actix_web::HttpRequest;
+use std::net::IpAddr;
+
+/// The address a request came from. X-Forwarded-For is only believed when
+/// the connection comes from one of `trusted_proxies`, since any client can
+/// send it. Each proxy appends the address it was connected from, so the
+/// client is the rightmost entry that is not itself a trusted proxy.
+pub fn client_ip(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let mut ip = req.peer_addr()?.ip();
+    if !trusted_proxies.contains(&ip) {
+        return Some(ip);
+    }
+
+    let forwarded: Vec<&str> = req
+        .headers()
+        .get_all("x-forwarded-for")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    for entry in forwarded.iter().rev() {
+        let Ok(hop) = entry.parse::<IpAddr>() else {
+            break;
+        };
+        ip = hop;
+        if !trusted_proxies.contains(&hop) {
+            break;
+        }
+    }
+    Some(ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn request(peer: &str, forwarded_for: Option<&str>) -> HttpRequest {
+        let mut req = TestRequest::default().peer_addr(format!("{}:40000", peer).parse().unwrap());
+        if let Some(value) = forwarded_for {
+            req = req.insert_header(("X-Forwarded-For", value));
+        }
+        req.to_http_request()
+    }
+
+    fn ips(list: &[&str]) -> Vec<IpAddr> {
+        list.iter().map(|ip| ip.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn ignores_forwarded_for_from_untrusted_peers() {
+        let req = request("203.0.113.7", Some("198.51.100.1"));
+        assert_eq!(client_ip(&req, &[]), Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(client_ip(&req, &ips(&["127.0.0.1"])), Some("203.0.113.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn takes_the_address_the_trusted_proxy_saw() {
+        // The client sent its own header; nginx appended the real address
+        let req = request("127.0.0.1", Some("198.51.100.1, 203.0.113.7"));
+        assert_eq!(client_ip(&req, &ips(&["127.0.0.1"])), Some("203.0.113.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn skips_chained_trusted_proxies() {
+        let req = request("127.0.0.1", Some("203.0.113.7, 10.0.0.2"));
+        assert_eq!(
+            client_ip(&req, &ips(&["127.0.0.1", "10.0.0.2"])),
+            Some("203.0.113.7".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn stops_at_entries_that_are_not_addresses() {
+        let req = request("127.0.0.1", Some("unknown"));
+        assert_eq!(client_ip(&req, &ips(&["127.0.0.1"])), Some("127.0.0.1".parse().unwrap()));
+
+        let req = request("127.0.0.1", None);
+        assert_eq!(client_ip(&req, &ips(&["127.0.0.1"])), Some("127.0.0.1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,35 @@
+/// Lowercase emails of the `Co-authored-by: Name <email>` trailers in a
+/// commit message, in order, without duplicates
+pub fn emails(message: &str) -> Vec<String> {
+    let mut emails: Vec<String> = Vec::new();
+    for line in message.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else { continue };
+        if !key.trim().eq_ignore_ascii_case("co-authored-by") {
+            continue;
+        }
+        let Some((_, rest)) = value.split_once('<') else { continue };
+        let Some((email, _)) = rest.split_once('>') else { continue };
+        let email = email.trim().to_lowercase();
+        if email.contains('@') && !emails.contains(&email) {
+            emails.push(email);
+        }
+    }
+    emails
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailers_are_read_case_insensitively() {
+        let message = "Fix the parser\n\nCo-authored-by: Alice <Alice@Example.com>\nco-authored-by: Bob <bob@example.com>\nCo-Authored-By: Alice again <alice@example.com>";
+        assert_eq!(emails(message), ["alice@example.com", "bob@example.com"]);
+    }
+
+    #[test]
+    fn malformed_trailers_are_skipped() {
+        let message = "Co-authored-by: Alice\nCo-authored-by: <not an email>\nSigned-off-by: Bob <bob@example.com>";
+        assert!(emails(message).is_empty());
+    }
+}
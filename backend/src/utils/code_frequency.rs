@@ -0,0 +1,85 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Lines changed in one week, as GitHub's code frequency graph shows them
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, ToSchema)]
+pub struct WeekLines {
+    /// The Sunday the week starts on, as the heatmap's columns do
+    pub week_start: NaiveDate,
+    pub additions: i64,
+    pub deletions: i64,
+    pub commits: i64,
+}
+
+/// The Sunday on or before `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_sunday() as i64)
+}
+
+/// Sum `(day, additions, deletions)` commits per week, with a week for every
+/// week from the one of `from` to the one of `to`, empty ones included.
+/// Commits outside the range are left out; unknown line counts count as 0.
+pub fn weekly(
+    commits: impl IntoIterator<Item = (NaiveDate, Option<i32>, Option<i32>)>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<WeekLines> {
+    let first = week_start(from);
+    let mut weeks: Vec<WeekLines> = std::iter::successors(Some(first), |week| Some(*week + Duration::days(7)))
+        .take_while(|week| *week <= to)
+        .map(|week_start| WeekLines { week_start, additions: 0, deletions: 0, commits: 0 })
+        .collect();
+
+    for (day, additions, deletions) in commits {
+        if day < from || day > to {
+            continue;
+        }
+        let week = &mut weeks[((day - first).num_days() / 7) as usize];
+        week.additions += additions.unwrap_or(0) as i64;
+        week.deletions += deletions.unwrap_or(0) as i64;
+        week.commits += 1;
+    }
+    weeks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn weeks_start_on_sunday_and_include_empty_ones() {
+        // 2026-03-04 is a Wednesday
+        let weeks = weekly(
+            [
+                (date("2026-03-04"), Some(10), Some(2)),
+                (date("2026-03-07"), None, Some(1)),
+                (date("2026-03-20"), Some(5), Some(0)),
+            ],
+            date("2026-03-04"),
+            date("2026-03-21"),
+        );
+
+        assert_eq!(weeks.len(), 3);
+        assert_eq!(weeks[0].week_start, date("2026-03-01"));
+        assert_eq!((weeks[0].additions, weeks[0].deletions, weeks[0].commits), (10, 3, 2));
+        assert_eq!(weeks[1].commits, 0);
+        assert_eq!((weeks[2].additions, weeks[2].commits), (5, 1));
+    }
+
+    #[test]
+    fn commits_outside_the_range_are_left_out() {
+        let weeks = weekly(
+            [(date("2026-02-28"), Some(1), Some(1)), (date("2026-03-02"), Some(4), Some(4))],
+            date("2026-03-01"),
+            date("2026-03-01"),
+        );
+
+        assert_eq!(weeks.len(), 1);
+        assert_eq!(weeks[0].commits, 0);
+    }
+}
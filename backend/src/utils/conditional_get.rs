@@ -0,0 +1,137 @@
+use actix_web::http::header::{self, HeaderMap, HttpDate};
+use actix_web::web::Bytes;
+use actix_web::HttpResponse;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+
+/// ETag and Last-Modified of a response, used to answer conditional GETs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: String,
+    pub last_modified: DateTime<Utc>,
+}
+
+impl Validators {
+    /// Build a strong ETag from everything the response body depends on
+    pub fn new(parts: &[&str], last_modified: DateTime<Utc>) -> Self {
+        let digest = Sha256::digest(parts.join("\u{1f}").as_bytes());
+        Self {
+            etag: format!("\"{}\"", &hex::encode(digest)[..32]),
+            last_modified,
+        }
+    }
+
+    /// Whether the client's copy is still current. If-None-Match wins over
+    /// If-Modified-Since when both are sent.
+    pub fn is_fresh(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+            let Ok(value) = if_none_match.to_str() else {
+                return false;
+            };
+            return value.split(',').map(str::trim).any(|tag| {
+                tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.etag
+            });
+        }
+
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<HttpDate>().ok())
+            .map(|since| {
+                let since = DateTime::<Utc>::from(SystemTime::from(since));
+                self.last_modified.timestamp() <= since.timestamp()
+            })
+            .unwrap_or(false)
+    }
+
+    fn headers(&self) -> [(header::HeaderName, String); 3] {
+        [
+            (header::ETAG, self.etag.clone()),
+            (
+                header::LAST_MODIFIED,
+                HttpDate::from(SystemTime::from(self.last_modified)).to_string(),
+            ),
+            // Cacheable, but revalidate every time; revalidation is cheap
+            (header::CACHE_CONTROL, "public, no-cache".to_string()),
+        ]
+    }
+
+    /// 304 when the client's copy is current, otherwise 200 with the JSON body
+    pub fn respond(&self, headers: &HeaderMap, body: Bytes) -> HttpResponse {
+        let fresh = self.is_fresh(headers);
+        let mut response = if fresh {
+            HttpResponse::NotModified()
+        } else {
+            HttpResponse::Ok()
+        };
+        for header in self.headers() {
+            response.insert_header(header);
+        }
+
+        if fresh {
+            response.finish()
+        } else {
+            response.content_type("application/json").body(body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::HeaderValue;
+    use chrono::TimeZone;
+
+    fn validators() -> Validators {
+        Validators::new(
+            &["user", "2024"],
+            Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+        )
+    }
+
+    fn headers(name: header::HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn etag_depends_on_every_part() {
+        let a = Validators::new(&["user", "2024"], Utc::now());
+        let b = Validators::new(&["user", "2025"], Utc::now());
+        let c = Validators::new(&["user2", "024"], Utc::now());
+        assert_ne!(a.etag, b.etag);
+        assert_ne!(a.etag, c.etag);
+        assert!(a.etag.starts_with('"') && a.etag.ends_with('"'));
+    }
+
+    #[test]
+    fn matches_if_none_match() {
+        let v = validators();
+        assert!(v.is_fresh(&headers(header::IF_NONE_MATCH, &v.etag)));
+        assert!(v.is_fresh(&headers(header::IF_NONE_MATCH, &format!("\"x\", W/{}", v.etag))));
+        assert!(v.is_fresh(&headers(header::IF_NONE_MATCH, "*")));
+        assert!(!v.is_fresh(&headers(header::IF_NONE_MATCH, "\"other\"")));
+    }
+
+    #[test]
+    fn matches_if_modified_since() {
+        let v = validators();
+        assert!(v.is_fresh(&headers(header::IF_MODIFIED_SINCE, "Wed, 01 May 2024 12:00:00 GMT")));
+        assert!(!v.is_fresh(&headers(header::IF_MODIFIED_SINCE, "Wed, 01 May 2024 11:59:59 GMT")));
+        assert!(!v.is_fresh(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence() {
+        let v = validators();
+        let mut h = headers(header::IF_NONE_MATCH, "\"other\"");
+        h.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Wed, 01 May 2030 12:00:00 GMT"),
+        );
+        assert!(!v.is_fresh(&h));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::utils::config::Config;
+
+/// One entry of CORS_ALLOWED_ORIGINS: an exact origin such as
+/// `https://hgitmap.example.com`, or a wildcard such as `https://*.example.com`
+/// matching any subdomain (but not `example.com` itself)
+#[derive(Debug, Clone, PartialEq)]
+enum OriginPattern {
+    Exact(String),
+    Subdomains {
+        scheme: String,
+        /// Host suffix including the leading dot and any port, e.g. ".example.com:8443"
+        suffix: String,
+    },
+}
+
+impl OriginPattern {
+    fn parse(entry: &str) -> Result<Self, String> {
+        let entry = entry.trim().trim_end_matches('/').to_lowercase();
+        let (scheme, host) = entry
+            .split_once("://")
+            .ok_or_else(|| format!("CORS origin '{}' must include a scheme", entry))?;
+
+        if scheme != "http" && scheme != "https" {
+            return Err(format!("CORS origin '{}' must use http or https", entry));
+        }
+        if host.is_empty() || host.contains('/') {
+            return Err(format!("CORS origin '{}' must not contain a path", entry));
+        }
+
+        match host.strip_prefix("*.") {
+            Some(suffix) if !suffix.is_empty() && !suffix.contains('*') => Ok(Self::Subdomains {
+                scheme: scheme.to_string(),
+                suffix: format!(".{}", suffix),
+            }),
+            Some(_) => Err(format!("CORS origin '{}' has an invalid wildcard", entry)),
+            None if host.contains('*') => Err(format!(
+                "CORS origin '{}' may only use a wildcard as the leftmost label",
+                entry
+            )),
+            None => Ok(Self::Exact(entry)),
+        }
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Exact(allowed) => allowed == origin,
+            Self::Subdomains { scheme, suffix } => origin
+                .strip_prefix(scheme.as_str())
+                .and_then(|rest| rest.strip_prefix("://"))
+                .and_then(|host| host.strip_suffix(suffix.as_str()))
+                .is_some_and(|subdomain| {
+                    subdomain.split('.').all(|label| {
+                        !label.is_empty()
+                            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    })
+                }),
+        }
+    }
+}
+
+/// Origins allowed to call the authenticated API from a browser
+#[derive(Debug, Clone, Default)]
+pub struct AllowedOrigins {
+    patterns: Vec<OriginPattern>,
+}
+
+impl AllowedOrigins {
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self, String> {
+        let patterns = entries
+            .iter()
+            .map(|e| e.as_ref())
+            .filter(|e| !e.trim().is_empty())
+            .map(OriginPattern::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// `CORS_ALLOWED_ORIGINS` and `FRONTEND_URL`
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut entries = config.cors_allowed_origins.clone();
+        entries.push(config.frontend_url.clone());
+        Self::parse(&entries)
+    }
+
+    pub fn is_allowed(&self, origin: &str) -> bool {
+        let origin = origin.to_lowercase();
+        self.patterns.iter().any(|p| p.matches(&origin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exact_origins() {
+        let origins =
+            AllowedOrigins::parse(&["http://localhost:5173", "https://hgitmap.example.com/"])
+                .unwrap();
+        assert!(origins.is_allowed("http://localhost:5173"));
+        assert!(origins.is_allowed("https://HGITMAP.example.com"));
+        assert!(!origins.is_allowed("http://localhost:3000"));
+        assert!(!origins.is_allowed("http://hgitmap.example.com"));
+    }
+
+    #[test]
+    fn wildcard_matches_subdomains_only() {
+        let origins = AllowedOrigins::parse(&["https://*.example.com"]).unwrap();
+        assert!(origins.is_allowed("https://app.example.com"));
+        assert!(origins.is_allowed("https://a.b.example.com"));
+        assert!(!origins.is_allowed("https://example.com"));
+        assert!(!origins.is_allowed("http://app.example.com"));
+        assert!(!origins.is_allowed("https://evil-example.com"));
+        assert!(!origins.is_allowed("https://app.example.com.evil.com"));
+        assert!(!origins.is_allowed("https://app.example.com:8443"));
+    }
+
+    #[test]
+    fn wildcard_keeps_port() {
+        let origins = AllowedOrigins::parse(&["http://*.local.test:8080"]).unwrap();
+        assert!(origins.is_allowed("http://web.local.test:8080"));
+        assert!(!origins.is_allowed("http://web.local.test"));
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        assert!(AllowedOrigins::parse(&["example.com"]).is_err());
+        assert!(AllowedOrigins::parse(&["ftp://example.com"]).is_err());
+        assert!(AllowedOrigins::parse(&["https://example.com/app"]).is_err());
+        assert!(AllowedOrigins::parse(&["https://app.*.example.com"]).is_err());
+        assert!(AllowedOrigins::parse(&["https://*."]).is_err());
+        assert!(AllowedOrigins::parse(&["", " "]).is_ok());
+    }
+}
@@ -1,5 +1,614 @@
-use sea_orm::{Database, DatabaseConnection, DbErr};
+use sea_orm::{
+    metric, ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    IntoActiveModel, Statement, TransactionTrait,
+};
+use std::time::Duration;
+
+/// Rows per statement in [`insert_chunked`]. Keeps the bind parameters of the
+/// widest table well below what SQLite and PostgreSQL accept in one query.
+pub const INSERT_CHUNK_SIZE: usize = 500;
+
+/// Characters of SQL kept in a slow query log line
+const MAX_SLOW_QUERY_LEN: usize = 1000;
+
+/// Schema changes applied by `hgitmap migrate`, in order. The first entry is
+/// schema.sql as it stood before migrations existed, and 0001a catches it up
+/// with the changes made to schema.sql until then. Later changes are added to
+/// schema.sql (and the SQLite and MySQL schemas) and as a new entry here and
+/// in the lists below, written so that they are a no-op on a database created
+/// from the current schema file
+/// (`ADD COLUMN IF NOT EXISTS`, `CREATE TABLE IF NOT EXISTS`, ...).
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_initial_schema", include_str!("../../db_schema/migrations/0001_initial_schema.sql")),
+    (
+        "0001a_pre_migration_changes",
+        include_str!("../../db_schema/migrations/0001a_pre_migration_changes.sql"),
+    ),
+    ("0002_json_lists", include_str!("../../db_schema/migrations/0002_json_lists.sql")),
+    (
+        "0003_activity_type_index",
+        include_str!("../../db_schema/migrations/0003_activity_type_index.sql"),
+    ),
+    (
+        "0004_heatmap_access_daily",
+        include_str!("../../db_schema/migrations/0004_heatmap_access_daily.sql"),
+    ),
+    (
+        "0005_heatmap_share_links",
+        include_str!("../../db_schema/migrations/0005_heatmap_share_links.sql"),
+    ),
+    (
+        "0006_email_digests",
+        include_str!("../../db_schema/migrations/0006_email_digests.sql"),
+    ),
+    (
+        "0007_platform_account_visibility",
+        include_str!("../../db_schema/migrations/0007_platform_account_visibility.sql"),
+    ),
+    (
+        "0008_platform_account_labels",
+        include_str!("../../db_schema/migrations/0008_platform_account_labels.sql"),
+    ),
+    (
+        "0009_organization_memberships",
+        include_str!("../../db_schema/migrations/0009_organization_memberships.sql"),
+    ),
+    (
+        "0010_oauth_application_settings",
+        include_str!("../../db_schema/migrations/0010_oauth_application_settings.sql"),
+    ),
+    (
+        "0011_platform_account_disconnections",
+        include_str!("../../db_schema/migrations/0011_platform_account_disconnections.sql"),
+    ),
+    (
+        "0012_activity_natural_keys",
+        include_str!("../../db_schema/migrations/0012_activity_natural_keys.sql"),
+    ),
+    (
+        "0013_generation_job_dead_letter",
+        include_str!("../../db_schema/migrations/0013_generation_job_dead_letter.sql"),
+    ),
+    (
+        "0014_generation_schedule",
+        include_str!("../../db_schema/migrations/0014_generation_schedule.sql"),
+    ),
+    (
+        "0015_platform_api_usage",
+        include_str!("../../db_schema/migrations/0015_platform_api_usage.sql"),
+    ),
+    (
+        "0016_heatmap_layout",
+        include_str!("../../db_schema/migrations/0016_heatmap_layout.sql"),
+    ),
+    (
+        "0017_theme_custom_template",
+        include_str!("../../db_schema/migrations/0017_theme_custom_template.sql"),
+    ),
+    (
+        "0018_theme_mini_days",
+        include_str!("../../db_schema/migrations/0018_theme_mini_days.sql"),
+    ),
+    (
+        "0019_teams",
+        include_str!("../../db_schema/migrations/0019_teams.sql"),
+    ),
+    (
+        "0020_follows",
+        include_str!("../../db_schema/migrations/0020_follows.sql"),
+    ),
+    (
+        "0021_user_directory",
+        include_str!("../../db_schema/migrations/0021_user_directory.sql"),
+    ),
+    (
+        "0022_activity_imports",
+        include_str!("../../db_schema/migrations/0022_activity_imports.sql"),
+    ),
+    (
+        "0023_away_periods",
+        include_str!("../../db_schema/migrations/0023_away_periods.sql"),
+    ),
+    (
+        "0024_annotations",
+        include_str!("../../db_schema/migrations/0024_annotations.sql"),
+    ),
+    (
+        "0025_milestones",
+        include_str!("../../db_schema/migrations/0025_milestones.sql"),
+    ),
+    (
+        "0026_achievements",
+        include_str!("../../db_schema/migrations/0026_achievements.sql"),
+    ),
+    (
+        "0027_repository_languages",
+        include_str!("../../db_schema/migrations/0027_repository_languages.sql"),
+    ),
+    (
+        "0028_commit_details",
+        include_str!("../../db_schema/migrations/0028_commit_details.sql"),
+    ),
+    (
+        "0029_code_frequency",
+        include_str!("../../db_schema/migrations/0029_code_frequency.sql"),
+    ),
+    (
+        "0030_commit_emails",
+        include_str!("../../db_schema/migrations/0030_commit_emails.sql"),
+    ),
+    (
+        "0031_commit_email_verification",
+        include_str!("../../db_schema/migrations/0031_commit_email_verification.sql"),
+    ),
+    (
+        "0032_mqtt_publishers",
+        include_str!("../../db_schema/migrations/0032_mqtt_publishers.sql"),
+    ),
+    (
+        "0033_notification_channels",
+        include_str!("../../db_schema/migrations/0033_notification_channels.sql"),
+    ),
+    (
+        "0034_notification_channel_events",
+        include_str!("../../db_schema/migrations/0034_notification_channel_events.sql"),
+    ),
+    (
+        "0035_oauth_application_listing",
+        include_str!("../../db_schema/migrations/0035_oauth_application_listing.sql"),
+    ),
+    (
+        "0036_platform_account_earliest_sync_year",
+        include_str!("../../db_schema/migrations/0036_platform_account_earliest_sync_year.sql"),
+    ),
+];
+
+/// SQLite support started from the current schema, so its list restarts
+/// with schema.sqlite.sql. Later changes go into every list.
+#[cfg(feature = "sqlite")]
+const SQLITE_MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_initial_schema", include_str!("../../db_schema/schema.sqlite.sql")),
+    (
+        "0001a_pre_migration_changes",
+        include_str!("../../db_schema/migrations/0001a_pre_migration_changes.sqlite.sql"),
+    ),
+    (
+        "0003_activity_type_index",
+        include_str!("../../db_schema/migrations/0003_activity_type_index.sql"),
+    ),
+    (
+        "0004_heatmap_access_daily",
+        include_str!("../../db_schema/migrations/0004_heatmap_access_daily.sqlite.sql"),
+    ),
+    (
+        "0005_heatmap_share_links",
+        include_str!("../../db_schema/migrations/0005_heatmap_share_links.sqlite.sql"),
+    ),
+    (
+        "0006_email_digests",
+        include_str!("../../db_schema/migrations/0006_email_digests.sqlite.sql"),
+    ),
+    (
+        "0007_platform_account_visibility",
+        include_str!("../../db_schema/migrations/0007_platform_account_visibility.sqlite.sql"),
+    ),
+    (
+        "0008_platform_account_labels",
+        include_str!("../../db_schema/migrations/0008_platform_account_labels.sqlite.sql"),
+    ),
+    (
+        "0009_organization_memberships",
+        include_str!("../../db_schema/migrations/0009_organization_memberships.sqlite.sql"),
+    ),
+    (
+        "0010_oauth_application_settings",
+        include_str!("../../db_schema/migrations/0010_oauth_application_settings.sqlite.sql"),
+    ),
+    (
+        "0011_platform_account_disconnections",
+        include_str!("../../db_schema/migrations/0011_platform_account_disconnections.sqlite.sql"),
+    ),
+    (
+        "0012_activity_natural_keys",
+        include_str!("../../db_schema/migrations/0012_activity_natural_keys.sqlite.sql"),
+    ),
+    (
+        "0014_generation_schedule",
+        include_str!("../../db_schema/migrations/0014_generation_schedule.sqlite.sql"),
+    ),
+    (
+        "0015_platform_api_usage",
+        include_str!("../../db_schema/migrations/0015_platform_api_usage.sqlite.sql"),
+    ),
+    (
+        "0016_heatmap_layout",
+        include_str!("../../db_schema/migrations/0016_heatmap_layout.sqlite.sql"),
+    ),
+    (
+        "0017_theme_custom_template",
+        include_str!("../../db_schema/migrations/0017_theme_custom_template.sqlite.sql"),
+    ),
+    (
+        "0018_theme_mini_days",
+        include_str!("../../db_schema/migrations/0018_theme_mini_days.sqlite.sql"),
+    ),
+    (
+        "0019_teams",
+        include_str!("../../db_schema/migrations/0019_teams.sqlite.sql"),
+    ),
+    (
+        "0020_follows",
+        include_str!("../../db_schema/migrations/0020_follows.sqlite.sql"),
+    ),
+    (
+        "0021_user_directory",
+        include_str!("../../db_schema/migrations/0021_user_directory.sqlite.sql"),
+    ),
+    (
+        "0022_activity_imports",
+        include_str!("../../db_schema/migrations/0022_activity_imports.sqlite.sql"),
+    ),
+    (
+        "0023_away_periods",
+        include_str!("../../db_schema/migrations/0023_away_periods.sqlite.sql"),
+    ),
+    (
+        "0024_annotations",
+        include_str!("../../db_schema/migrations/0024_annotations.sqlite.sql"),
+    ),
+    (
+        "0025_milestones",
+        include_str!("../../db_schema/migrations/0025_milestones.sqlite.sql"),
+    ),
+    (
+        "0026_achievements",
+        include_str!("../../db_schema/migrations/0026_achievements.sqlite.sql"),
+    ),
+    (
+        "0027_repository_languages",
+        include_str!("../../db_schema/migrations/0027_repository_languages.sqlite.sql"),
+    ),
+    (
+        "0028_commit_details",
+        include_str!("../../db_schema/migrations/0028_commit_details.sqlite.sql"),
+    ),
+    (
+        "0029_code_frequency",
+        include_str!("../../db_schema/migrations/0029_code_frequency.sqlite.sql"),
+    ),
+    (
+        "0030_commit_emails",
+        include_str!("../../db_schema/migrations/0030_commit_emails.sqlite.sql"),
+    ),
+    (
+        "0031_commit_email_verification",
+        include_str!("../../db_schema/migrations/0031_commit_email_verification.sqlite.sql"),
+    ),
+    (
+        "0032_mqtt_publishers",
+        include_str!("../../db_schema/migrations/0032_mqtt_publishers.sqlite.sql"),
+    ),
+    (
+        "0033_notification_channels",
+        include_str!("../../db_schema/migrations/0033_notification_channels.sqlite.sql"),
+    ),
+    (
+        "0034_notification_channel_events",
+        include_str!("../../db_schema/migrations/0034_notification_channel_events.sqlite.sql"),
+    ),
+    (
+        "0035_oauth_application_listing",
+        include_str!("../../db_schema/migrations/0035_oauth_application_listing.sqlite.sql"),
+    ),
+    (
+        "0036_platform_account_earliest_sync_year",
+        include_str!("../../db_schema/migrations/0036_platform_account_earliest_sync_year.sqlite.sql"),
+    ),
+];
+
+/// Same for MySQL and MariaDB. Their DDL is not transactional, so a failed
+/// migration may leave part of it applied.
+#[cfg(feature = "mysql")]
+const MYSQL_MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_initial_schema", include_str!("../../db_schema/schema.mysql.sql")),
+    (
+        "0001a_pre_migration_changes",
+        include_str!("../../db_schema/migrations/0001a_pre_migration_changes.mysql.sql"),
+    ),
+    (
+        "0003_activity_type_index",
+        include_str!("../../db_schema/migrations/0003_activity_type_index.mysql.sql"),
+    ),
+    (
+        "0004_heatmap_access_daily",
+        include_str!("../../db_schema/migrations/0004_heatmap_access_daily.mysql.sql"),
+    ),
+    (
+        "0005_heatmap_share_links",
+        include_str!("../../db_schema/migrations/0005_heatmap_share_links.mysql.sql"),
+    ),
+    (
+        "0006_email_digests",
+        include_str!("../../db_schema/migrations/0006_email_digests.mysql.sql"),
+    ),
+    (
+        "0007_platform_account_visibility",
+        include_str!("../../db_schema/migrations/0007_platform_account_visibility.mysql.sql"),
+    ),
+    (
+        "0008_platform_account_labels",
+        include_str!("../../db_schema/migrations/0008_platform_account_labels.mysql.sql"),
+    ),
+    (
+        "0009_organization_memberships",
+        include_str!("../../db_schema/migrations/0009_organization_memberships.mysql.sql"),
+    ),
+    (
+        "0010_oauth_application_settings",
+        include_str!("../../db_schema/migrations/0010_oauth_application_settings.mysql.sql"),
+    ),
+    (
+        "0011_platform_account_disconnections",
+        include_str!("../../db_schema/migrations/0011_platform_account_disconnections.mysql.sql"),
+    ),
+    (
+        "0012_activity_natural_keys",
+        include_str!("../../db_schema/migrations/0012_activity_natural_keys.mysql.sql"),
+    ),
+    (
+        "0014_generation_schedule",
+        include_str!("../../db_schema/migrations/0014_generation_schedule.mysql.sql"),
+    ),
+    (
+        "0015_platform_api_usage",
+        include_str!("../../db_schema/migrations/0015_platform_api_usage.mysql.sql"),
+    ),
+    (
+        "0016_heatmap_layout",
+        include_str!("../../db_schema/migrations/0016_heatmap_layout.mysql.sql"),
+    ),
+    (
+        "0017_theme_custom_template",
+        include_str!("../../db_schema/migrations/0017_theme_custom_template.mysql.sql"),
+    ),
+    (
+        "0018_theme_mini_days",
+        include_str!("../../db_schema/migrations/0018_theme_mini_days.mysql.sql"),
+    ),
+    (
+        "0019_teams",
+        include_str!("../../db_schema/migrations/0019_teams.mysql.sql"),
+    ),
+    (
+        "0020_follows",
+        include_str!("../../db_schema/migrations/0020_follows.mysql.sql"),
+    ),
+    (
+        "0021_user_directory",
+        include_str!("../../db_schema/migrations/0021_user_directory.mysql.sql"),
+    ),
+    (
+        "0022_activity_imports",
+        include_str!("../../db_schema/migrations/0022_activity_imports.mysql.sql"),
+    ),
+    (
+        "0023_away_periods",
+        include_str!("../../db_schema/migrations/0023_away_periods.mysql.sql"),
+    ),
+    (
+        "0024_annotations",
+        include_str!("../../db_schema/migrations/0024_annotations.mysql.sql"),
+    ),
+    (
+        "0025_milestones",
+        include_str!("../../db_schema/migrations/0025_milestones.mysql.sql"),
+    ),
+    (
+        "0026_achievements",
+        include_str!("../../db_schema/migrations/0026_achievements.mysql.sql"),
+    ),
+    (
+        "0027_repository_languages",
+        include_str!("../../db_schema/migrations/0027_repository_languages.mysql.sql"),
+    ),
+    (
+        "0028_commit_details",
+        include_str!("../../db_schema/migrations/0028_commit_details.mysql.sql"),
+    ),
+    (
+        "0029_code_frequency",
+        include_str!("../../db_schema/migrations/0029_code_frequency.mysql.sql"),
+    ),
+    (
+        "0030_commit_emails",
+        include_str!("../../db_schema/migrations/0030_commit_emails.mysql.sql"),
+    ),
+    (
+        "0031_commit_email_verification",
+        include_str!("../../db_schema/migrations/0031_commit_email_verification.mysql.sql"),
+    ),
+    (
+        "0032_mqtt_publishers",
+        include_str!("../../db_schema/migrations/0032_mqtt_publishers.mysql.sql"),
+    ),
+    (
+        "0033_notification_channels",
+        include_str!("../../db_schema/migrations/0033_notification_channels.mysql.sql"),
+    ),
+    (
+        "0034_notification_channel_events",
+        include_str!("../../db_schema/migrations/0034_notification_channel_events.mysql.sql"),
+    ),
+    (
+        "0035_oauth_application_listing",
+        include_str!("../../db_schema/migrations/0035_oauth_application_listing.mysql.sql"),
+    ),
+    (
+        "0036_platform_account_earliest_sync_year",
+        include_str!("../../db_schema/migrations/0036_platform_account_earliest_sync_year.mysql.sql"),
+    ),
+];
 
 pub async fn establish_connection(database_url: &str) -> Result<DatabaseConnection, DbErr> {
-    Database::connect(database_url).await
+    if !is_sqlite_url(database_url) {
+        return Database::connect(database_url).await;
+    }
+
+    // Create the database file on first start
+    let url = if database_url.contains("mode=") {
+        database_url.to_string()
+    } else if database_url.contains('?') {
+        format!("{}&mode=rwc", database_url)
+    } else {
+        format!("{}?mode=rwc", database_url)
+    };
+    let db = Database::connect(url).await?;
+    // Let the background jobs write while the API serves reads; the journal
+    // mode is stored in the file, so setting it on one connection is enough
+    db.execute_unprepared("PRAGMA journal_mode=WAL").await?;
+    Ok(db)
+}
+
+pub fn is_sqlite_url(database_url: &str) -> bool {
+    database_url.starts_with("sqlite:")
+}
+
+/// Insert rows with one multi-row INSERT per [`INSERT_CHUNK_SIZE`] rows
+/// instead of a round trip each. Run it in a transaction so a failed chunk
+/// does not leave the earlier ones behind.
+pub async fn insert_chunked<A, C>(db: &C, models: Vec<A>) -> Result<u64, DbErr>
+where
+    A: ActiveModelTrait + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    let mut inserted = 0;
+    let mut models = models.into_iter().peekable();
+
+    while models.peek().is_some() {
+        let chunk: Vec<A> = models.by_ref().take(INSERT_CHUNK_SIZE).collect();
+        inserted += chunk.len() as u64;
+        A::Entity::insert_many(chunk).exec_without_returning(db).await?;
+    }
+    Ok(inserted)
+}
+
+/// Log a query that ran for at least `threshold`. Used as the connection's
+/// metric callback when `SLOW_QUERY_MS` is set. Bound values are left out,
+/// since they can hold tokens.
+pub fn log_slow_query(info: &metric::Info<'_>, threshold: Duration) {
+    if info.elapsed < threshold {
+        return;
+    }
+
+    let sql = info.statement.sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sql = match sql.char_indices().nth(MAX_SLOW_QUERY_LEN) {
+        Some((idx, _)) => format!("{}...", &sql[..idx]),
+        None => sql,
+    };
+    log::warn!(
+        "Slow query ({} ms{}): {}",
+        info.elapsed.as_millis(),
+        if info.failed { ", failed" } else { "" },
+        sql
+    );
+}
+
+fn migrations(backend: DbBackend) -> &'static [(&'static str, &'static str)] {
+    match backend {
+        #[cfg(feature = "sqlite")]
+        DbBackend::Sqlite => SQLITE_MIGRATIONS,
+        #[cfg(feature = "mysql")]
+        DbBackend::MySql => MYSQL_MIGRATIONS,
+        _ => MIGRATIONS,
+    }
+}
+
+/// Apply the migrations the database has not seen yet, returning their names.
+/// A database set up by hand from schema.sql, whatever its age, is adopted as
+/// having the first migration; the rest bring it up to date.
+pub async fn run_migrations(db: &DatabaseConnection) -> Result<Vec<&'static str>, DbErr> {
+    let backend = db.get_database_backend();
+    let migrations = migrations(backend);
+
+    let applied_at_type = match backend {
+        DbBackend::Postgres => "TIMESTAMP WITH TIME ZONE",
+        _ => "TIMESTAMP",
+    };
+    db.execute_unprepared(&format!(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version VARCHAR(255) PRIMARY KEY,
+            applied_at {} DEFAULT CURRENT_TIMESTAMP
+        )",
+        applied_at_type
+    ))
+    .await?;
+
+    let mut applied: Vec<String> = db
+        .query_all(Statement::from_string(backend, "SELECT version FROM schema_migrations"))
+        .await?
+        .iter()
+        .map(|row| row.try_get("", "version"))
+        .collect::<Result<_, _>>()?;
+
+    if applied.is_empty() && table_exists(db, "users").await? {
+        let (baseline, _) = migrations[0];
+        record(db, baseline).await?;
+        applied.push(baseline.to_string());
+        log::info!("📦 Existing schema adopted as {}", baseline);
+    }
+
+    let mut newly_applied = Vec::new();
+    for (version, sql) in migrations {
+        if applied.iter().any(|v| v == version) {
+            continue;
+        }
+
+        let txn = db.begin().await?;
+        txn.execute_unprepared(sql).await?;
+        record(&txn, version).await?;
+        txn.commit().await?;
+
+        log::info!("📦 Applied migration {}", version);
+        newly_applied.push(*version);
+    }
+
+    Ok(newly_applied)
+}
+
+async fn table_exists(db: &DatabaseConnection, table: &str) -> Result<bool, DbErr> {
+    let statement = match db.get_database_backend() {
+        DbBackend::Sqlite => Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?) AS present",
+            [table.into()],
+        ),
+        DbBackend::MySql => Statement::from_sql_and_values(
+            DbBackend::MySql,
+            "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = ?) AS present",
+            [table.into()],
+        ),
+        backend => Statement::from_sql_and_values(
+            backend,
+            "SELECT to_regclass($1) IS NOT NULL AS present",
+            [format!("public.{}", table).into()],
+        ),
+    };
+    let row = db.query_one(statement).await?;
+
+    match row {
+        Some(row) => row.try_get("", "present"),
+        None => Ok(false),
+    }
+}
+
+async fn record<C: ConnectionTrait>(db: &C, version: &str) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+    let sql = match backend {
+        DbBackend::Sqlite | DbBackend::MySql => "INSERT INTO schema_migrations (version) VALUES (?)",
+        _ => "INSERT INTO schema_migrations (version) VALUES ($1)",
+    };
+    db.execute(Statement::from_sql_and_values(backend, sql, [version.into()]))
+        .await?;
+    Ok(())
 }
@@ -0,0 +1,90 @@
+/// Most domains a user can allow
+pub const MAX_DOMAINS: usize = 50;
+
+/// Validate referrer domains such as `example.com` or `https://blog.example.com/`,
+/// returning bare lowercase hostnames without duplicates
+pub fn normalize(domains: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for domain in domains {
+        let trimmed = domain.trim().to_lowercase();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let with_scheme = if trimmed.contains("://") {
+            trimmed.clone()
+        } else {
+            format!("https://{}", trimmed)
+        };
+        let host = url::Url::parse(&with_scheme)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.trim_start_matches("*.").to_string()))
+            .filter(|h| h.contains('.') || h == "localhost")
+            .ok_or_else(|| format!("'{}' is not a valid domain", domain.trim()))?;
+
+        if !normalized.contains(&host) {
+            normalized.push(host);
+        }
+    }
+
+    if normalized.len() > MAX_DOMAINS {
+        return Err(format!("At most {} domains can be allowed", MAX_DOMAINS));
+    }
+
+    Ok(normalized)
+}
+
+/// Host of a Referer header value
+pub fn referrer_host(referrer: &str) -> Option<String> {
+    url::Url::parse(referrer)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_lowercase))
+}
+
+/// Whether `host` is one of the domains or a subdomain of one
+pub fn is_allowed(domains: &[String], host: &str) -> bool {
+    domains.iter().any(|domain| {
+        host == domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_domains() {
+        let domains = vec![
+            "Example.com".to_string(),
+            "https://blog.example.com/posts".to_string(),
+            "*.example.org".to_string(),
+            "example.com".to_string(),
+            " ".to_string(),
+        ];
+        assert_eq!(
+            normalize(&domains).unwrap(),
+            vec!["example.com", "blog.example.com", "example.org"]
+        );
+        assert!(normalize(&["not a domain".to_string()]).is_err());
+        assert!(normalize(&["intranet".to_string()]).is_err());
+    }
+
+    #[test]
+    fn matches_subdomains_only() {
+        let domains = vec!["example.com".to_string()];
+        assert!(is_allowed(&domains, "example.com"));
+        assert!(is_allowed(&domains, "www.example.com"));
+        assert!(!is_allowed(&domains, "badexample.com"));
+        assert!(!is_allowed(&domains, "example.com.evil.net"));
+    }
+
+    #[test]
+    fn parses_referrer_host() {
+        assert_eq!(referrer_host("https://Blog.Example.com/post?id=1").as_deref(), Some("blog.example.com"));
+        assert_eq!(referrer_host("not a url"), None);
+    }
+}
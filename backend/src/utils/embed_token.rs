@@ -0,0 +1,73 @@
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Audience of embed tokens. Session validation rejects tokens with an
+/// audience, so an embed token can never be used to sign in.
+const AUDIENCE: &str = "embed";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EmbedClaims {
+    sub: String,
+    aud: String,
+    exp: i64,
+    iat: i64,
+}
+
+/// Sign a token that lets anyone holding it view `user_id`'s heatmaps
+/// until `expires_at`, even when the profile is private
+pub fn create(user_id: Uuid, secret: &str, expires_in: Duration) -> Result<(String, DateTime<Utc>), jsonwebtoken::errors::Error> {
+    let now = Utc::now();
+    let expires_at = now + expires_in;
+    let claims = EmbedClaims {
+        sub: user_id.to_string(),
+        aud: AUDIENCE.to_string(),
+        exp: expires_at.timestamp(),
+        iat: now.timestamp(),
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )?;
+    Ok((token, expires_at))
+}
+
+/// Whether `token` is a valid, unexpired embed token for `user_id`
+pub fn verify(token: &str, secret: &str, user_id: Uuid) -> bool {
+    let mut validation = Validation::default();
+    validation.set_audience(&[AUDIENCE]);
+    decode::<EmbedClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .is_ok_and(|data| data.claims.sub == user_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::auth::decode_jwt;
+
+    const SECRET: &str = "test-secret";
+
+    #[test]
+    fn verifies_for_owner_only() {
+        let owner = Uuid::new_v4();
+        let (token, _) = create(owner, SECRET, Duration::days(1)).unwrap();
+        assert!(verify(&token, SECRET, owner));
+        assert!(!verify(&token, SECRET, Uuid::new_v4()));
+        assert!(!verify(&token, "other-secret", owner));
+    }
+
+    #[test]
+    fn rejects_expired_tokens() {
+        let owner = Uuid::new_v4();
+        let (token, _) = create(owner, SECRET, Duration::days(-1)).unwrap();
+        assert!(!verify(&token, SECRET, owner));
+    }
+
+    #[test]
+    fn cannot_be_used_as_session() {
+        let (token, _) = create(Uuid::new_v4(), SECRET, Duration::days(1)).unwrap();
+        assert!(decode_jwt(&token, SECRET).is_err());
+    }
+}
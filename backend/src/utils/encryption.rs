@@ -4,25 +4,63 @@ use aes_gcm::{
 };
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose, Engine as _};
+use sha2::{Digest, Sha256};
 
-/// Encrypt a plaintext string using AES-256-GCM
-/// Returns base64-encoded ciphertext with nonce prepended (nonce is first 12 bytes)
-pub fn encrypt(plaintext: &str, key_base64: &str) -> Result<String> {
-    // Decode the base64-encoded key
-    let key_bytes = general_purpose::STANDARD
-        .decode(key_base64)
-        .map_err(|e| anyhow!("Invalid base64 encryption key: {}", e))?;
-
-    if key_bytes.len() != 32 {
-        return Err(anyhow!(
-            "Encryption key must be 32 bytes (256 bits), got {} bytes",
-            key_bytes.len()
-        ));
+/// A key from ENCRYPTION_KEY
+struct Key {
+    /// First 8 hex characters of the SHA-256 of the key bytes
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+/// Parse ENCRYPTION_KEY: one or more comma-separated base64 keys, newest
+/// first. The newest key encrypts; the others only decrypt, so a key can be
+/// retired by removing it once nothing is encrypted with it anymore.
+fn parse_keys(keys_base64: &str) -> Result<Vec<Key>> {
+    let keys = keys_base64
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key_base64| {
+            // Decode the base64-encoded key
+            let key_bytes = general_purpose::STANDARD
+                .decode(key_base64)
+                .map_err(|e| anyhow!("Invalid base64 encryption key: {}", e))?;
+
+            if key_bytes.len() != 32 {
+                return Err(anyhow!(
+                    "Encryption key must be 32 bytes (256 bits), got {} bytes",
+                    key_bytes.len()
+                ));
+            }
+
+            let cipher = Aes256Gcm::new_from_slice(&key_bytes)
+                .map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
+
+            Ok(Key {
+                id: hex::encode(&Sha256::digest(&key_bytes)[..4]),
+                cipher,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if keys.is_empty() {
+        return Err(anyhow!("No encryption key configured"));
     }
+    Ok(keys)
+}
+
+/// IDs of the configured keys, newest (the one used to encrypt) first
+pub fn key_ids(keys_base64: &str) -> Result<Vec<String>> {
+    Ok(parse_keys(keys_base64)?.into_iter().map(|key| key.id).collect())
+}
 
-    // Create cipher instance
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes)
-        .map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
+/// Encrypt a plaintext string using AES-256-GCM with the newest key
+/// Returns `<key id>:<base64>`, where the base64 part is the ciphertext with
+/// the nonce prepended (nonce is first 12 bytes)
+pub fn encrypt(plaintext: &str, keys_base64: &str) -> Result<String> {
+    let keys = parse_keys(keys_base64)?;
+    let key = &keys[0];
 
     // Generate a random nonce (96 bits / 12 bytes for GCM)
     let mut nonce_bytes = [0u8; 12];
@@ -30,7 +68,8 @@ pub fn encrypt(plaintext: &str, key_base64: &str) -> Result<String> {
     let nonce = Nonce::from_slice(&nonce_bytes);
 
     // Encrypt the plaintext
-    let ciphertext = cipher
+    let ciphertext = key
+        .cipher
         .encrypt(nonce, plaintext.as_bytes())
         .map_err(|e| anyhow!("Encryption failed: {}", e))?;
 
@@ -38,24 +77,47 @@ pub fn encrypt(plaintext: &str, key_base64: &str) -> Result<String> {
     let mut combined = nonce.to_vec();
     combined.extend_from_slice(&ciphertext);
 
-    Ok(general_purpose::STANDARD.encode(&combined))
+    Ok(format!("{}:{}", key.id, general_purpose::STANDARD.encode(&combined)))
 }
 
-/// Decrypt a base64-encoded ciphertext using AES-256-GCM
-/// Expects nonce to be prepended to ciphertext (first 12 bytes)
-pub fn decrypt(ciphertext_base64: &str, key_base64: &str) -> Result<String> {
-    // Decode the base64-encoded key
-    let key_bytes = general_purpose::STANDARD
-        .decode(key_base64)
-        .map_err(|e| anyhow!("Invalid base64 encryption key: {}", e))?;
-
-    if key_bytes.len() != 32 {
-        return Err(anyhow!(
-            "Encryption key must be 32 bytes (256 bits), got {} bytes",
-            key_bytes.len()
-        ));
+/// Decrypt a ciphertext produced by `encrypt`
+pub fn decrypt(ciphertext: &str, keys_base64: &str) -> Result<String> {
+    decrypt_with_key_id(ciphertext, keys_base64).map(|(plaintext, _)| plaintext)
+}
+
+/// Decrypt a ciphertext and return the ID of the key that opened it.
+/// Ciphertexts from before key IDs were added have no prefix; every key is
+/// tried on those, newest first.
+pub fn decrypt_with_key_id(ciphertext: &str, keys_base64: &str) -> Result<(String, String)> {
+    let keys = parse_keys(keys_base64)?;
+
+    // Base64 has no ':', so a prefix can't be confused with ciphertext
+    match ciphertext.split_once(':') {
+        Some((key_id, encoded)) => {
+            let key = keys.iter().find(|key| key.id == key_id).ok_or_else(|| {
+                anyhow!(
+                    "Encrypted with key {}, which is not in ENCRYPTION_KEY; was it retired too early?",
+                    key_id
+                )
+            })?;
+            Ok((open(&key.cipher, encoded)?, key.id.clone()))
+        }
+        None => {
+            let mut last_error = None;
+            for key in &keys {
+                match open(&key.cipher, ciphertext) {
+                    Ok(plaintext) => return Ok((plaintext, key.id.clone())),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            Err(last_error.unwrap_or_else(|| anyhow!("Decryption failed")))
+        }
     }
+}
 
+/// Decrypt a base64-encoded ciphertext using AES-256-GCM
+/// Expects nonce to be prepended to ciphertext (first 12 bytes)
+fn open(cipher: &Aes256Gcm, ciphertext_base64: &str) -> Result<String> {
     // Decode the base64-encoded ciphertext
     let combined = general_purpose::STANDARD
         .decode(ciphertext_base64)
@@ -69,10 +131,6 @@ pub fn decrypt(ciphertext_base64: &str, key_base64: &str) -> Result<String> {
     let (nonce_bytes, ciphertext) = combined.split_at(12);
     let nonce = Nonce::from_slice(nonce_bytes);
 
-    // Create cipher instance
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes)
-        .map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
-
     // Decrypt the ciphertext
     let plaintext = cipher
         .decrypt(nonce, ciphertext)
@@ -88,7 +146,7 @@ mod tests {
     #[test]
     fn test_encrypt_decrypt() {
         // Generate a random 32-byte key and encode it as base64
-        let key = general_purpose::STANDARD.encode(&[0u8; 32]);
+        let key = general_purpose::STANDARD.encode([0u8; 32]);
         let plaintext = "my-secret-access-token";
 
         let encrypted = encrypt(plaintext, &key).unwrap();
@@ -99,8 +157,8 @@ mod tests {
 
     #[test]
     fn test_decrypt_with_wrong_key() {
-        let key1 = general_purpose::STANDARD.encode(&[0u8; 32]);
-        let key2 = general_purpose::STANDARD.encode(&[1u8; 32]);
+        let key1 = general_purpose::STANDARD.encode([0u8; 32]);
+        let key2 = general_purpose::STANDARD.encode([1u8; 32]);
         let plaintext = "my-secret-access-token";
 
         let encrypted = encrypt(plaintext, &key1).unwrap();
@@ -111,10 +169,41 @@ mod tests {
 
     #[test]
     fn test_invalid_key_length() {
-        let short_key = general_purpose::STANDARD.encode(&[0u8; 16]); // Only 16 bytes
+        let short_key = general_purpose::STANDARD.encode([0u8; 16]); // Only 16 bytes
         let plaintext = "my-secret-access-token";
 
         let result = encrypt(plaintext, &short_key);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_rotated_keys() {
+        let old = general_purpose::STANDARD.encode([0u8; 32]);
+        let new = general_purpose::STANDARD.encode([1u8; 32]);
+        let both = format!("{}, {}", new, old);
+        let ids = key_ids(&both).unwrap();
+
+        // Secrets from the old key still open, and new ones use the new key
+        let encrypted = encrypt("token", &old).unwrap();
+        assert_eq!(decrypt_with_key_id(&encrypted, &both).unwrap(), ("token".to_string(), ids[1].clone()));
+        let encrypted = encrypt("token", &both).unwrap();
+        assert!(encrypted.starts_with(&format!("{}:", ids[0])));
+
+        // Once retired, the old key's ID is named in the error
+        let encrypted = encrypt("token", &old).unwrap();
+        let error = decrypt(&encrypted, &new).unwrap_err().to_string();
+        assert!(error.contains(&ids[1]));
+    }
+
+    #[test]
+    fn test_decrypt_unprefixed_ciphertext() {
+        let old = general_purpose::STANDARD.encode([0u8; 32]);
+        let new = general_purpose::STANDARD.encode([1u8; 32]);
+        let encrypted = encrypt("token", &old).unwrap();
+        let (_, legacy) = encrypted.split_once(':').unwrap();
+
+        let (plaintext, key_id) = decrypt_with_key_id(legacy, &format!("{},{}", new, old)).unwrap();
+        assert_eq!(plaintext, "token");
+        assert_eq!(key_id, key_ids(&old).unwrap()[0]);
+    }
 }
@@ -0,0 +1,62 @@
+use sentry::{ClientInitGuard, ClientOptions, User};
+use uuid::Uuid;
+
+use crate::utils::config::Config;
+
+/// Start the error reporting client when `SENTRY_DSN` is set. The returned
+/// guard flushes pending events when dropped, so keep it alive until exit.
+/// Captures panics on any thread, including the background job processors.
+pub fn init(config: &Config) -> Option<ClientInitGuard> {
+    let dsn = config.error_reporting_dsn.as_deref()?;
+
+    let guard = sentry::init((
+        dsn,
+        ClientOptions {
+            release: sentry::release_name!(),
+            ..Default::default()
+        },
+    ));
+
+    if !guard.is_enabled() {
+        log::error!("❌ Invalid SENTRY_DSN, error reporting disabled");
+        return None;
+    }
+
+    Some(guard)
+}
+
+/// Attach the authenticated user to events reported for the current request
+pub fn set_user(user_id: &str) {
+    sentry::configure_scope(|scope| {
+        scope.set_user(Some(User {
+            id: Some(user_id.to_string()),
+            ..Default::default()
+        }));
+    });
+}
+
+/// Report a background job or scheduled sync that failed for good
+pub fn report_job_failure(
+    kind: &str,
+    job_id: Option<Uuid>,
+    user_id: Uuid,
+    platform_account_id: Option<Uuid>,
+    error: &anyhow::Error,
+) {
+    sentry::with_scope(
+        |scope| {
+            scope.set_user(Some(User {
+                id: Some(user_id.to_string()),
+                ..Default::default()
+            }));
+            scope.set_tag("job.kind", kind);
+            if let Some(job_id) = job_id {
+                scope.set_tag("job.id", job_id);
+            }
+            if let Some(account_id) = platform_account_id {
+                scope.set_tag("platform_account.id", account_id);
+            }
+        },
+        || sentry::integrations::anyhow::capture_anyhow(error),
+    );
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Utc};
+use flate2::read::MultiGzDecoder;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// Most a file may hold once decompressed; an hour of GH Archive is a few
+/// hundred MB
+pub const MAX_DECOMPRESSED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// A GitHub event, trimmed to what an import maps to activities
+#[derive(Debug, Deserialize)]
+pub struct Event {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub created_at: DateTime<Utc>,
+    pub repo: Repo,
+    /// GH Archive only has public events; a saved response of the
+    /// authenticated events endpoint may have private ones
+    #[serde(default = "public")]
+    pub public: bool,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Repo {
+    pub name: String,
+}
+
+fn public() -> bool {
+    true
+}
+
+/// The events of one user in a file
+#[derive(Debug, Default)]
+pub struct EventFile {
+    /// Every event in the file, whoever it belongs to
+    pub read: usize,
+    pub events: Vec<Event>,
+}
+
+/// Fails reads past [`MAX_DECOMPRESSED_BYTES`], so a small gzip bomb cannot
+/// keep a worker busy
+struct Limited<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for Limited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            // Anything left means the file is too large
+            let mut probe = [0u8; 1];
+            return match self.inner.read(&mut probe)? {
+                0 => Ok(0),
+                _ => Err(io::Error::other(format!(
+                    "File must be at most {} GB uncompressed",
+                    MAX_DECOMPRESSED_BYTES / 1024 / 1024 / 1024
+                ))),
+            };
+        }
+        let max = buf.len().min(self.remaining as usize);
+        let n = self.inner.read(&mut buf[..max])?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Read the events of `login` (case-insensitive) from a GH Archive file
+/// (newline-delimited JSON) or a saved Events API response (a JSON array),
+/// either of them optionally gzipped. Events in another shape, like GH
+/// Archive's format before 2015, are counted but skipped.
+pub fn read(data: &[u8], login: &str) -> Result<EventFile, String> {
+    let decompressed: Box<dyn Read + '_> = if data.starts_with(&[0x1f, 0x8b]) {
+        // GH Archive slices are often several hourly files concatenated
+        Box::new(MultiGzDecoder::new(data))
+    } else {
+        Box::new(data)
+    };
+    let mut reader = BufReader::new(Limited {
+        inner: decompressed,
+        remaining: MAX_DECOMPRESSED_BYTES,
+    });
+
+    // serde_json's messages end with the line and column
+    let error = |e: serde_json::Error| {
+        if e.is_io() {
+            e.to_string()
+        } else {
+            format!("Invalid JSON: {}", e)
+        }
+    };
+
+    let mut file = EventFile::default();
+    if first_byte(&mut reader).map_err(|e| e.to_string())? == Some(b'[') {
+        let values: Vec<Value> = serde_json::from_reader(reader).map_err(error)?;
+        for value in values {
+            file.add(value, login);
+        }
+    } else {
+        for value in serde_json::Deserializer::from_reader(reader).into_iter::<Value>() {
+            file.add(value.map_err(error)?, login);
+        }
+    }
+
+    Ok(file)
+}
+
+/// The first byte that is not whitespace, without consuming it
+fn first_byte(reader: &mut impl BufRead) -> io::Result<Option<u8>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(i) => {
+                let byte = buf[i];
+                reader.consume(i);
+                return Ok(Some(byte));
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+impl EventFile {
+    fn add(&mut self, value: Value, login: &str) {
+        self.read += 1;
+        let actor = value.pointer("/actor/login").and_then(Value::as_str);
+        if !actor.is_some_and(|actor| actor.eq_ignore_ascii_case(login)) {
+            return;
+        }
+        if let Ok(event) = serde_json::from_value(value) {
+            self.events.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    const PUSH: &str = r#"{"type":"PushEvent","created_at":"2015-03-01T12:00:00Z","actor":{"login":"Octocat"},"repo":{"name":"octocat/hello"},"payload":{"size":2}}"#;
+    const OTHER: &str = r#"{"type":"WatchEvent","created_at":"2015-03-01T12:00:00Z","actor":{"login":"someone"},"repo":{"name":"octocat/hello"}}"#;
+
+    #[test]
+    fn reads_the_users_events() {
+        let file = read(format!("{}\n{}\n", PUSH, OTHER).as_bytes(), "octocat").unwrap();
+        assert_eq!(file.read, 2);
+        assert_eq!(file.events.len(), 1);
+        assert_eq!(file.events[0].event_type, "PushEvent");
+        assert_eq!(file.events[0].repo.name, "octocat/hello");
+        assert!(file.events[0].public);
+
+        let file = read(format!("\n [{}, {}]", PUSH, OTHER).as_bytes(), "octocat").unwrap();
+        assert_eq!((file.read, file.events.len()), (2, 1));
+    }
+
+    #[test]
+    fn reads_concatenated_gzip() {
+        let mut data = Vec::new();
+        for line in [PUSH, PUSH] {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            writeln!(encoder, "{}", line).unwrap();
+            data.extend(encoder.finish().unwrap());
+        }
+
+        let file = read(&data, "octocat").unwrap();
+        assert_eq!((file.read, file.events.len()), (2, 2));
+    }
+
+    #[test]
+    fn skips_events_of_another_shape() {
+        let old = r#"{"type":"PushEvent","created_at":"2012-03-01T12:00:00-08:00","actor":{"login":"octocat"},"repository":{"name":"hello"}}"#;
+        let file = read(old.as_bytes(), "octocat").unwrap();
+        assert_eq!((file.read, file.events.len()), (1, 0));
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        let error = read(format!("{}\n{{\"type\":", PUSH).as_bytes(), "octocat").unwrap_err();
+        assert!(error.contains("line 2"), "{}", error);
+        assert!(read(b"", "octocat").unwrap().events.is_empty());
+    }
+}
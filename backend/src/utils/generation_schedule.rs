@@ -0,0 +1,229 @@
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+
+/// Shortest time between two scheduled runs, the same as the shortest
+/// update interval
+pub const MIN_GAP_MINUTES: i64 = 15;
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// When a user's scheduled syncs and generations may start. By default a run
+/// starts one update interval after the last; a daily window holds runs back
+/// until the window opens, and a cron expression replaces both. Windows and
+/// cron expressions are in the user's timezone.
+#[derive(Debug, Clone)]
+pub struct GenerationSchedule {
+    timezone: Tz,
+    /// Minutes after midnight the window opens and closes; it closes the
+    /// next day when the end is before the start
+    window: Option<(i32, i32)>,
+    cron: Option<Cron>,
+}
+
+impl Default for GenerationSchedule {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            window: None,
+            cron: None,
+        }
+    }
+}
+
+impl GenerationSchedule {
+    /// Check the stored schedule settings. The timezone defaults to UTC.
+    pub fn new(timezone: Option<&str>, window: Option<(i32, i32)>, cron: Option<&str>) -> Result<Self, String> {
+        let timezone = match timezone {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| format!("'{}' is not a known timezone", name))?,
+            None => Tz::UTC,
+        };
+
+        if let Some((start, end)) = window {
+            if !(0..MINUTES_PER_DAY).contains(&start) || !(0..MINUTES_PER_DAY).contains(&end) {
+                return Err("Generation window times must be between 00:00 and 23:59".to_string());
+            }
+            if start == end {
+                return Err("Generation window must not start and end at the same time".to_string());
+            }
+        }
+
+        let cron = match cron {
+            Some(expression) => {
+                let cron = Cron::new(expression)
+                    .parse()
+                    .map_err(|e| format!("Invalid cron expression '{}': {}", expression, e))?;
+                if cron.find_next_occurrence(&Utc::now(), false).is_err() {
+                    return Err(format!("Cron expression '{}' never matches", expression));
+                }
+                Some(cron)
+            }
+            None => None,
+        };
+
+        if window.is_some() && cron.is_some() {
+            return Err("Set either a generation window or a cron expression, not both".to_string());
+        }
+
+        Ok(Self { timezone, window, cron })
+    }
+
+    /// Whether runs only follow the update interval, with no window or cron
+    /// expression
+    pub fn is_interval_only(&self) -> bool {
+        self.window.is_none() && self.cron.is_none()
+    }
+
+    /// When the first run may start
+    pub fn first_run(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match &self.cron {
+            Some(cron) => self.next_match(cron, now).unwrap_or(now),
+            None => self.window_open_at(now),
+        }
+    }
+
+    /// When the run after one started at `last` may start. Cron runs are at
+    /// least [`MIN_GAP_MINUTES`] apart.
+    pub fn next_run(&self, last: DateTime<Utc>, interval: Duration) -> DateTime<Utc> {
+        match &self.cron {
+            Some(cron) => self
+                .next_match(cron, last + Duration::minutes(MIN_GAP_MINUTES))
+                .unwrap_or(last + interval),
+            None => self.window_open_at(last + interval),
+        }
+    }
+
+    fn next_match(&self, cron: &Cron, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        cron.find_next_occurrence(&from.with_timezone(&self.timezone), true)
+            .ok()
+            .map(|next| next.with_timezone(&Utc))
+    }
+
+    /// `time` if the window is open then, otherwise when it next opens
+    fn window_open_at(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let Some((start, end)) = self.window else {
+            return time;
+        };
+
+        let local = time.with_timezone(&self.timezone).naive_local();
+        let minute = minute_of_day(&local);
+        let open = if start < end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        };
+        if open {
+            return time;
+        }
+
+        let mut opens = local.date().and_time(Default::default()) + Duration::minutes(start as i64);
+        if opens <= local {
+            opens += Duration::days(1);
+        }
+        self.to_utc(opens)
+    }
+
+    /// A local time in UTC; times skipped by a clock change move on by an hour
+    fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| self.timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or_else(|| local.and_utc())
+    }
+}
+
+fn minute_of_day(time: &NaiveDateTime) -> i32 {
+    use chrono::Timelike;
+    (time.hour() * 60 + time.minute()) as i32
+}
+
+/// Parse an `HH:MM` time of day into minutes after midnight
+pub fn parse_time_of_day(value: &str) -> Option<i32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    ((0..24).contains(&hours) && (0..60).contains(&minutes)).then_some(hours * 60 + minutes)
+}
+
+/// Format minutes after midnight as `HH:MM`
+pub fn format_time_of_day(minutes: i32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_times_of_day() {
+        assert_eq!(parse_time_of_day("02:30"), Some(150));
+        assert_eq!(parse_time_of_day("0:00"), Some(0));
+        assert_eq!(parse_time_of_day("24:00"), None);
+        assert_eq!(parse_time_of_day("12:60"), None);
+        assert_eq!(parse_time_of_day("noon"), None);
+        assert_eq!(format_time_of_day(150), "02:30");
+    }
+
+    #[test]
+    fn interval_only_schedule_adds_the_interval() {
+        let schedule = GenerationSchedule::default();
+        let last = utc("2025-03-01T10:00:00Z");
+        assert!(schedule.is_interval_only());
+        assert_eq!(schedule.first_run(last), last);
+        assert_eq!(schedule.next_run(last, Duration::hours(1)), utc("2025-03-01T11:00:00Z"));
+    }
+
+    #[test]
+    fn window_holds_runs_until_it_opens() {
+        let schedule = GenerationSchedule::new(Some("Europe/Berlin"), Some((120, 300)), None).unwrap();
+
+        // 03:30 in Berlin is inside 02:00-05:00
+        let inside = utc("2025-01-10T02:30:00Z");
+        assert_eq!(schedule.next_run(inside - Duration::hours(1), Duration::hours(1)), inside);
+        // 06:00 in Berlin waits for 02:00 the next day
+        assert_eq!(schedule.first_run(utc("2025-01-10T05:00:00Z")), utc("2025-01-11T01:00:00Z"));
+        // 01:00 in Berlin waits for 02:00 the same day
+        assert_eq!(schedule.first_run(utc("2025-01-10T00:00:00Z")), utc("2025-01-10T01:00:00Z"));
+    }
+
+    #[test]
+    fn window_can_span_midnight() {
+        let schedule = GenerationSchedule::new(None, Some((22 * 60, 2 * 60)), None).unwrap();
+        let late = utc("2025-01-10T23:30:00Z");
+        let early = utc("2025-01-11T01:30:00Z");
+        assert_eq!(schedule.first_run(late), late);
+        assert_eq!(schedule.first_run(early), early);
+        assert_eq!(schedule.first_run(utc("2025-01-11T12:00:00Z")), utc("2025-01-11T22:00:00Z"));
+    }
+
+    #[test]
+    fn cron_replaces_the_interval() {
+        let schedule = GenerationSchedule::new(Some("America/New_York"), None, Some("5 0 * * *")).unwrap();
+        // 00:05 in New York is 05:05 UTC in winter
+        let last = utc("2025-01-10T05:05:00Z");
+        assert_eq!(schedule.next_run(last, Duration::hours(1)), utc("2025-01-11T05:05:00Z"));
+        assert_eq!(schedule.first_run(utc("2025-01-10T12:00:00Z")), utc("2025-01-11T05:05:00Z"));
+
+        // Runs stay at least MIN_GAP_MINUTES apart
+        let every_minute = GenerationSchedule::new(None, None, Some("* * * * *")).unwrap();
+        let last = utc("2025-01-10T05:00:00Z");
+        assert_eq!(every_minute.next_run(last, Duration::hours(1)), last + Duration::minutes(MIN_GAP_MINUTES));
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        assert!(GenerationSchedule::new(Some("Mars/Olympus"), None, None).is_err());
+        assert!(GenerationSchedule::new(None, Some((60, 60)), None).is_err());
+        assert!(GenerationSchedule::new(None, Some((0, MINUTES_PER_DAY)), None).is_err());
+        assert!(GenerationSchedule::new(None, None, Some("not cron")).is_err());
+        assert!(GenerationSchedule::new(None, Some((0, 60)), Some("0 0 * * *")).is_err());
+    }
+}
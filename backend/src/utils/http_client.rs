@@ -1,7 +1,23 @@
-use reqwest::{Client, ClientBuilder};
+use async_trait::async_trait;
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response};
+use std::sync::OnceLock;
 use std::time::Duration;
+use tracing::Instrument;
 
-/// Create a configured HTTP client for making requests to git platform APIs
+use crate::utils::api_usage;
+
+static SHARED_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// The HTTP client for git platform APIs, OAuth providers and CAPTCHA checks.
+/// Clones share one connection pool, so TLS sessions, keep-alive connections
+/// and DNS lookups are reused across requests. Proxies come from
+/// `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`, certificates from the system store.
+pub fn shared_client() -> Client {
+    SHARED_CLIENT.get_or_init(create_http_client).clone()
+}
+
+/// Create a configured HTTP client for making requests to git platform APIs.
+/// Use [`shared_client`] instead unless the client needs different settings.
 pub fn create_http_client() -> Client {
     ClientBuilder::new()
         .timeout(Duration::from_secs(30))
@@ -10,6 +26,48 @@ pub fn create_http_client() -> Client {
         .expect("Failed to create HTTP client")
 }
 
+/// Send a request inside a client span carrying method, host, path and status.
+/// Inside an [`api_usage::UsageRecorder`] scope the request is also counted.
+#[async_trait]
+pub trait SendTraced {
+    async fn send_traced(self) -> reqwest::Result<Response>;
+}
+
+#[async_trait]
+impl SendTraced for RequestBuilder {
+    async fn send_traced(self) -> reqwest::Result<Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+
+        let url = request.url().clone();
+        let span = tracing::info_span!(
+            "http.client",
+            otel.name = %format!("{} {}", request.method(), url.host_str().unwrap_or_default()),
+            otel.kind = "client",
+            http.request.method = %request.method(),
+            server.address = url.host_str().unwrap_or_default(),
+            url.path = url.path(),
+            http.response.status_code = tracing::field::Empty,
+            otel.status_code = tracing::field::Empty,
+        );
+
+        let result = client.execute(request).instrument(span.clone()).await;
+        api_usage::record(&url, result.as_ref().ok());
+        match &result {
+            Ok(response) => {
+                span.record("http.response.status_code", response.status().as_u16());
+                if response.status().is_server_error() {
+                    span.record("otel.status_code", "ERROR");
+                }
+            }
+            Err(_) => {
+                span.record("otel.status_code", "ERROR");
+            }
+        }
+        result
+    }
+}
+
 /// Create an HTTP client that accepts self-signed certificates (development only)
 #[cfg(debug_assertions)]
 #[allow(dead_code)]
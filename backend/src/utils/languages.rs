@@ -0,0 +1,74 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// How much of a user's code is in one language
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct LanguageShare {
+    pub language: String,
+    pub bytes: i64,
+    /// Share of all bytes, rounded to one decimal
+    pub percentage: f64,
+    /// Repositories with code in the language
+    pub repositories: usize,
+}
+
+/// Sum the bytes per language over `(repository, language, bytes)` rows,
+/// largest first. A repository seen more than once, say through two
+/// accounts, counts once with its largest size.
+pub fn totals<'a>(rows: impl IntoIterator<Item = (&'a str, &'a str, i64)>) -> Vec<LanguageShare> {
+    let mut per_repository: HashMap<(String, &str), i64> = HashMap::new();
+    for (repository, language, bytes) in rows.into_iter().filter(|(_, _, bytes)| *bytes > 0) {
+        let entry = per_repository.entry((repository.to_lowercase(), language)).or_default();
+        *entry = (*entry).max(bytes);
+    }
+
+    let mut bytes: HashMap<&str, i64> = HashMap::new();
+    let mut repositories: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for ((repository, language), size) in &per_repository {
+        *bytes.entry(language).or_default() += size;
+        repositories.entry(language).or_default().insert(repository);
+    }
+
+    let total: i64 = bytes.values().sum();
+    let mut shares: Vec<LanguageShare> = bytes
+        .into_iter()
+        .map(|(language, bytes)| LanguageShare {
+            language: language.to_string(),
+            bytes,
+            percentage: (bytes as f64 * 1000.0 / total as f64).round() / 10.0,
+            repositories: repositories[language].len(),
+        })
+        .collect();
+    shares.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_count_each_repository_once() {
+        let shares = totals([
+            ("alice/api", "Rust", 600),
+            ("Alice/API", "Rust", 500),
+            ("alice/api", "Shell", 100),
+            ("acme/web", "TypeScript", 300),
+            ("acme/web", "Rust", 0),
+        ]);
+
+        assert_eq!(shares.len(), 3);
+        assert_eq!(shares[0].language, "Rust");
+        assert_eq!(shares[0].bytes, 600);
+        assert_eq!(shares[0].percentage, 60.0);
+        assert_eq!(shares[0].repositories, 1);
+        assert_eq!(shares[1].language, "TypeScript");
+        assert_eq!(shares[2].percentage, 10.0);
+    }
+
+    #[test]
+    fn totals_of_nothing_are_empty() {
+        assert!(totals([]).is_empty());
+    }
+}
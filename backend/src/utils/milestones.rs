@@ -0,0 +1,195 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::models::user_setting::AwayPeriod;
+use crate::utils::away_periods;
+
+pub const CONTRIBUTIONS: &str = "contributions";
+pub const STREAK: &str = "streak";
+pub const ORGANIZATION: &str = "organization";
+
+/// Contribution totals worth celebrating
+pub const CONTRIBUTION_THRESHOLDS: &[i64] = &[100, 500, 1_000, 5_000, 10_000, 25_000, 50_000, 100_000];
+
+/// Streak lengths worth celebrating, in days
+pub const STREAK_THRESHOLDS: &[i64] = &[7, 30, 100, 365, 1_000];
+
+/// A milestone found in the contribution history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reached {
+    pub kind: &'static str,
+    /// Unique per kind: the threshold, or the organization in lowercase
+    pub key: String,
+    pub title: String,
+    pub date: NaiveDate,
+}
+
+/// The day the running total of `days` (daily counts) crossed each threshold
+pub fn contribution_totals(days: &BTreeMap<NaiveDate, i64>) -> Vec<Reached> {
+    let mut reached = Vec::new();
+    let mut thresholds = CONTRIBUTION_THRESHOLDS.iter().peekable();
+    let mut total = 0;
+
+    for (&date, &count) in days {
+        total += count;
+        while let Some(&&threshold) = thresholds.peek() {
+            if total < threshold {
+                break;
+            }
+            reached.push(Reached {
+                kind: CONTRIBUTIONS,
+                key: threshold.to_string(),
+                title: format!("{}th contribution", with_separators(threshold)),
+                date,
+            });
+            thresholds.next();
+        }
+    }
+
+    reached
+}
+
+/// The day a streak first reached each length. Away days bridge a streak
+/// without adding to it, as in the stats.
+pub fn streaks(days: &BTreeMap<NaiveDate, i64>, away: &[AwayPeriod]) -> Vec<Reached> {
+    let mut reached = Vec::new();
+    let mut thresholds = STREAK_THRESHOLDS.iter().peekable();
+    let mut previous: Option<NaiveDate> = None;
+    let mut length = 0;
+
+    for (&date, _) in days.iter().filter(|(_, &count)| count > 0) {
+        length = match previous {
+            Some(previous) if away_periods::bridges(away, previous, date) => length + 1,
+            _ => 1,
+        };
+        previous = Some(date);
+
+        while let Some(&&threshold) = thresholds.peek() {
+            if length < threshold {
+                break;
+            }
+            reached.push(Reached {
+                kind: STREAK,
+                key: threshold.to_string(),
+                title: format!("{}-day streak", with_separators(threshold)),
+                date,
+            });
+            thresholds.next();
+        }
+    }
+
+    reached
+}
+
+/// The first contribution to each organization, i.e. each repository owner
+/// other than the user's own accounts (`personal`, lowercase), by date
+pub fn organizations<'a>(
+    contributions: impl IntoIterator<Item = (NaiveDate, &'a str)>,
+    personal: &HashSet<String>,
+) -> Vec<Reached> {
+    let mut first: HashMap<String, (NaiveDate, &str)> = HashMap::new();
+    for (date, repository) in contributions {
+        let Some((owner, _)) = repository.split_once('/') else {
+            continue;
+        };
+        let key = owner.to_lowercase();
+        if owner.is_empty() || personal.contains(&key) {
+            continue;
+        }
+        let entry = first.entry(key).or_insert((date, owner));
+        if date < entry.0 {
+            *entry = (date, owner);
+        }
+    }
+
+    let mut reached: Vec<Reached> = first
+        .into_iter()
+        .map(|(key, (date, owner))| Reached {
+            kind: ORGANIZATION,
+            title: format!("First contribution to {}", owner),
+            key,
+            date,
+        })
+        .collect();
+    reached.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.key.cmp(&b.key)));
+    reached
+}
+
+/// 1000 as "1,000"
+fn with_separators(n: i64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn totals_cross_thresholds_once() {
+        let days = BTreeMap::from([
+            (date("2026-01-01"), 99),
+            (date("2026-01-02"), 450),
+            (date("2026-01-03"), 500),
+        ]);
+        let reached = contribution_totals(&days);
+
+        assert_eq!(reached.len(), 3);
+        assert_eq!(reached[0].date, date("2026-01-02"));
+        assert_eq!(reached[1].key, "500");
+        assert_eq!(reached[1].date, date("2026-01-02"));
+        assert_eq!(reached[2].title, "1,000th contribution");
+        assert_eq!(reached[2].date, date("2026-01-03"));
+    }
+
+    #[test]
+    fn streaks_restart_and_bridge_away_days() {
+        let mut days = BTreeMap::new();
+        for d in 1..=5 {
+            days.insert(date(&format!("2026-03-{:02}", d)), 1);
+        }
+        // Gap on the 6th ends the first run
+        for d in 7..=9 {
+            days.insert(date(&format!("2026-03-{:02}", d)), 1);
+        }
+        assert!(streaks(&days, &[]).is_empty());
+
+        let away = [AwayPeriod { start: date("2026-03-06"), end: date("2026-03-06") }];
+        let reached = streaks(&days, &away);
+        assert_eq!(reached.len(), 1);
+        assert_eq!(reached[0].title, "7-day streak");
+        assert_eq!(reached[0].date, date("2026-03-08"));
+    }
+
+    #[test]
+    fn organizations_skip_personal_repositories() {
+        let personal = HashSet::from(["alice".to_string()]);
+        let reached = organizations(
+            [
+                (date("2026-02-01"), "Acme/api"),
+                (date("2026-01-15"), "acme/web"),
+                (date("2026-01-01"), "alice/dotfiles"),
+                (date("2026-01-20"), "rust-lang/rust"),
+                (date("2026-01-10"), "no-owner"),
+            ],
+            &personal,
+        );
+
+        assert_eq!(reached.len(), 2);
+        assert_eq!(reached[0].key, "acme");
+        assert_eq!(reached[0].title, "First contribution to acme");
+        assert_eq!(reached[0].date, date("2026-01-15"));
+        assert_eq!(reached[1].key, "rust-lang");
+    }
+}
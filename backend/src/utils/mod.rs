@@ -1,6 +1,38 @@
 pub mod db;
 pub mod config;
 pub mod auth;
+pub mod api_token;
+pub mod api_version;
 pub mod encryption;
 pub mod validators;
 pub mod http_client;
+pub mod pagination;
+pub mod token_bucket;
+pub mod telemetry;
+pub mod error_reporting;
+pub mod conditional_get;
+pub mod ttl_cache;
+pub mod cors;
+pub mod oidc_claims;
+pub mod repo_exclusion;
+pub mod embed_domains;
+pub mod embed_token;
+pub mod access_source;
+pub mod share_token;
+pub mod generation_schedule;
+pub mod api_usage;
+pub mod svg_template;
+pub mod event_file;
+pub mod away_periods;
+pub mod annotations;
+pub mod milestones;
+pub mod achievements;
+pub mod languages;
+pub mod co_authors;
+pub mod code_frequency;
+pub mod today;
+pub mod mqtt;
+pub mod social_post;
+pub mod notification_channels;
+pub mod regeneration;
+pub mod client_ip;
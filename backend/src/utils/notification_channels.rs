@@ -0,0 +1,70 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Parse the events a user routes to a channel: trimmed, lowercase, each
+/// one of `known` and listed once, in the order given
+pub fn normalize_events(events: &[String], known: &[&str]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for event in events {
+        let event = event.trim().to_lowercase();
+        if !known.contains(&event.as_str()) {
+            return Err(format!("Unknown event {:?}; events are: {}", event, known.join(", ")));
+        }
+        if !normalized.contains(&event) {
+            normalized.push(event);
+        }
+    }
+    Ok(normalized)
+}
+
+/// The `X-Hgitmap-Signature` of a webhook body: `sha256=` and the hex
+/// HMAC-SHA256 of the body, keyed with the channel's signing secret
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Whether `url` is a Slack incoming webhook
+pub fn is_slack_webhook_url(url: &str) -> bool {
+    url.starts_with("https://hooks.slack.com/services/")
+}
+
+/// Whether `url` is a Discord channel webhook
+pub fn is_discord_webhook_url(url: &str) -> bool {
+    ["https://discord.com/api/webhooks/", "https://discordapp.com/api/webhooks/"]
+        .iter()
+        .any(|prefix| url.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_normalized_and_checked() {
+        let known = ["weekly_summary", "milestone", "sync_failed"];
+        let events = vec![" Milestone".to_string(), "sync_failed".to_string(), "milestone".to_string()];
+        assert_eq!(normalize_events(&events, &known).unwrap(), vec!["milestone", "sync_failed"]);
+        assert!(normalize_events(&[], &known).unwrap().is_empty());
+        assert!(normalize_events(&["deploy".to_string()], &known).is_err());
+    }
+
+    #[test]
+    fn webhook_signature_is_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            webhook_signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn chat_webhook_urls_are_recognized() {
+        assert!(is_slack_webhook_url("https://hooks.slack.com/services/T0/B0/x"));
+        assert!(!is_slack_webhook_url("https://hooks.slack.com.evil.example/services/x"));
+        assert!(is_discord_webhook_url("https://discord.com/api/webhooks/1/abc"));
+        assert!(is_discord_webhook_url("https://discordapp.com/api/webhooks/1/abc"));
+        assert!(!is_discord_webhook_url("http://discord.com/api/webhooks/1/abc"));
+    }
+}
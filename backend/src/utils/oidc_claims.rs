@@ -0,0 +1,117 @@
+use serde_json::Value;
+
+/// The signed-in user, read from ID token and userinfo claims
+#[derive(Debug, Clone, PartialEq)]
+pub struct OidcUser {
+    pub subject: String,
+    /// Preferred hgitmap username
+    pub username: String,
+    pub email: Option<String>,
+    pub groups: Vec<String>,
+}
+
+impl OidcUser {
+    /// Map provider claims to a user. `groups_claim` may hold an array of
+    /// group names or a single name. Returns None without a `sub` claim.
+    pub fn from_claims(claims: &Value, groups_claim: &str) -> Option<Self> {
+        let string = |name: &str| {
+            claims
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+
+        let subject = string("sub")?;
+        let email = string("email");
+        let username = string("preferred_username")
+            .or_else(|| string("nickname"))
+            .or_else(|| email.as_ref().and_then(|e| e.split('@').next()).map(str::to_string))
+            .map(|name| sanitize_username(&name))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "user".to_string());
+
+        let groups = match claims.get(groups_claim) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Some(Self {
+            subject,
+            username,
+            email,
+            groups,
+        })
+    }
+
+    /// Whether the user is in `group`. Keycloak reports groups as paths
+    /// ("/admins"), so a leading slash is ignored.
+    pub fn in_group(&self, group: &str) -> bool {
+        let group = group.trim_start_matches('/');
+        self.groups.iter().any(|g| g.trim_start_matches('/') == group)
+    }
+}
+
+/// Keep only characters allowed in usernames
+fn sanitize_username(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(39)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_standard_claims() {
+        let user = OidcUser::from_claims(
+            &json!({
+                "sub": "f3c1",
+                "preferred_username": "jane.doe",
+                "email": "jane@example.com",
+                "groups": ["/hgitmap-admins", "staff"]
+            }),
+            "groups",
+        )
+        .unwrap();
+        assert_eq!(user.subject, "f3c1");
+        assert_eq!(user.username, "janedoe");
+        assert_eq!(user.email.as_deref(), Some("jane@example.com"));
+        assert!(user.in_group("hgitmap-admins"));
+        assert!(user.in_group("/staff"));
+        assert!(!user.in_group("admins"));
+    }
+
+    #[test]
+    fn falls_back_to_email_for_username() {
+        let user =
+            OidcUser::from_claims(&json!({"sub": "1", "email": "ops@example.com"}), "groups")
+                .unwrap();
+        assert_eq!(user.username, "ops");
+        assert!(user.groups.is_empty());
+
+        let user = OidcUser::from_claims(&json!({"sub": "2"}), "groups").unwrap();
+        assert_eq!(user.username, "user");
+    }
+
+    #[test]
+    fn reads_custom_single_group_claim() {
+        let user =
+            OidcUser::from_claims(&json!({"sub": "1", "role": "admin"}), "role").unwrap();
+        assert_eq!(user.groups, vec!["admin".to_string()]);
+    }
+
+    #[test]
+    fn requires_subject() {
+        assert!(OidcUser::from_claims(&json!({"email": "a@example.com"}), "groups").is_none());
+    }
+}
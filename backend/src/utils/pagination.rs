@@ -0,0 +1,240 @@
+use actix_web::HttpResponse;
+use base64::Engine;
+use sea_orm::{ConnectionTrait, DbErr, EntityTrait, Order, PaginatorTrait, QueryOrder, QuerySelect, Select};
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+pub const DEFAULT_LIMIT: u64 = 50;
+pub const MAX_LIMIT: u64 = 200;
+
+/// Response header carrying the number of items matching the query
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+/// Response header carrying the cursor of the next page, if any
+pub const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+
+/// Pagination, sorting and field selection shared by list endpoints
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageParams {
+    /// Page size (default 50, max 200)
+    pub limit: Option<u64>,
+    /// Opaque cursor taken from the `X-Next-Cursor` header of a previous page
+    pub cursor: Option<String>,
+    /// Sort field, prefixed with `-` for descending order, e.g. `-created_at`
+    pub sort: Option<String>,
+    /// Comma-separated list of fields to include in each item
+    pub fields: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortOrder {
+    pub field: String,
+    pub ascending: bool,
+}
+
+/// Parse `?sort=` as `field` or `-field`. A bare `asc` or `desc` flips the
+/// direction of the default field.
+pub fn parse_sort(sort: Option<&str>, allowed: &[&str], default: &str) -> Result<SortOrder, String> {
+    let parse = |value: &str| -> SortOrder {
+        match value.strip_prefix('-') {
+            Some(field) => SortOrder { field: field.to_string(), ascending: false },
+            None => SortOrder { field: value.to_string(), ascending: true },
+        }
+    };
+
+    let default = parse(default);
+    let value = match sort.map(|s| s.trim().to_lowercase()) {
+        None => return Ok(default),
+        Some(s) if s.is_empty() => return Ok(default),
+        Some(s) => s,
+    };
+
+    match value.as_str() {
+        "asc" => return Ok(SortOrder { ascending: true, ..default }),
+        "desc" => return Ok(SortOrder { ascending: false, ..default }),
+        _ => {}
+    }
+
+    let order = parse(&value);
+    if !allowed.contains(&order.field.as_str()) {
+        return Err(format!(
+            "Invalid sort: {}. Allowed fields: {}",
+            order.field,
+            allowed.join(", ")
+        ));
+    }
+    Ok(order)
+}
+
+pub fn clamp_limit(limit: Option<u64>) -> u64 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+pub fn encode_offset_cursor(offset: u64) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("offset:{}", offset))
+}
+
+pub fn decode_offset_cursor(cursor: &str) -> Option<u64> {
+    let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()?;
+    String::from_utf8(decoded)
+        .ok()?
+        .strip_prefix("offset:")?
+        .parse()
+        .ok()
+}
+
+/// One page of a list endpoint
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total_count: u64,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total_count: self.total_count,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+impl<T: Serialize> Page<T> {
+    /// JSON array of the items, with totals and the next cursor in headers
+    pub fn into_response(self, fields: Option<&str>) -> HttpResponse {
+        let mut items = serde_json::to_value(&self.items).unwrap_or_default();
+        if let Some(fields) = fields {
+            select_fields(&mut items, fields);
+        }
+
+        let mut response = HttpResponse::Ok();
+        response.insert_header((TOTAL_COUNT_HEADER, self.total_count.to_string()));
+        if let Some(cursor) = &self.next_cursor {
+            response.insert_header((NEXT_CURSOR_HEADER, cursor.as_str()));
+        }
+        response.json(items)
+    }
+}
+
+/// Fetch one page of `select`, ordered by the column that `sort` maps to and
+/// then by `tie_breaker` so pages stay stable. Client errors are returned as
+/// `Ok(Err(message))`.
+pub async fn fetch_page<E, C>(
+    db: &C,
+    select: Select<E>,
+    params: &PageParams,
+    sort_columns: &[(&str, E::Column)],
+    default_sort: &str,
+    tie_breaker: E::Column,
+) -> Result<Result<Page<E::Model>, String>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    C: ConnectionTrait,
+{
+    let allowed: Vec<&str> = sort_columns.iter().map(|(name, _)| *name).collect();
+    let sort = match parse_sort(params.sort.as_deref(), &allowed, default_sort) {
+        Ok(s) => s,
+        Err(e) => return Ok(Err(e)),
+    };
+    let Some(column) = sort_columns
+        .iter()
+        .find(|(name, _)| *name == sort.field)
+        .map(|(_, column)| *column)
+    else {
+        return Ok(Err(format!("Invalid sort: {}", sort.field)));
+    };
+
+    let offset = match params.cursor.as_deref() {
+        Some(cursor) => match decode_offset_cursor(cursor) {
+            Some(offset) => offset,
+            None => return Ok(Err("Invalid cursor".to_string())),
+        },
+        None => 0,
+    };
+    let limit = clamp_limit(params.limit);
+
+    let total_count = select.clone().count(db).await?;
+
+    let order = if sort.ascending { Order::Asc } else { Order::Desc };
+    let items = select
+        .order_by(column, order.clone())
+        .order_by(tie_breaker, order)
+        .offset(offset)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    let next_offset = offset + items.len() as u64;
+    let next_cursor = (next_offset < total_count).then(|| encode_offset_cursor(next_offset));
+
+    Ok(Ok(Page {
+        items,
+        total_count,
+        next_cursor,
+    }))
+}
+
+/// Keep only the requested keys on every object of a JSON array (or on a
+/// single object). Unknown field names are ignored.
+pub fn select_fields(value: &mut serde_json::Value, fields: &str) {
+    let fields: Vec<&str> = fields
+        .split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .collect();
+    if fields.is_empty() {
+        return;
+    }
+
+    let retain = |item: &mut serde_json::Value| {
+        if let Some(obj) = item.as_object_mut() {
+            obj.retain(|key, _| fields.contains(&key.as_str()));
+        }
+    };
+
+    match value {
+        serde_json::Value::Array(items) => items.iter_mut().for_each(retain),
+        item => retain(item),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sort() {
+        let allowed = ["created_at", "name"];
+        assert_eq!(
+            parse_sort(None, &allowed, "-created_at").unwrap(),
+            SortOrder { field: "created_at".to_string(), ascending: false }
+        );
+        assert_eq!(
+            parse_sort(Some("name"), &allowed, "-created_at").unwrap(),
+            SortOrder { field: "name".to_string(), ascending: true }
+        );
+        assert_eq!(
+            parse_sort(Some("asc"), &allowed, "-created_at").unwrap(),
+            SortOrder { field: "created_at".to_string(), ascending: true }
+        );
+        assert!(parse_sort(Some("-password"), &allowed, "-created_at").is_err());
+    }
+
+    #[test]
+    fn test_offset_cursor_roundtrip() {
+        assert_eq!(decode_offset_cursor(&encode_offset_cursor(150)), Some(150));
+        assert_eq!(decode_offset_cursor("not-a-cursor"), None);
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let mut value = serde_json::json!([{ "id": 1, "name": "a", "secret": "x" }]);
+        select_fields(&mut value, "id, name");
+        assert_eq!(value, serde_json::json!([{ "id": 1, "name": "a" }]));
+    }
+}
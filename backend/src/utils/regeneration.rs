@@ -0,0 +1,201 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::generated_heatmap;
+use crate::models::heatmap_theme::{self, HeatmapFormat};
+
+/// Days without a request after which a theme's regeneration skips a
+/// format; it is rendered on its next request instead
+pub const RARELY_USED_AFTER_DAYS: i64 = 30;
+
+/// Split a theme's formats into those its regeneration renders and those
+/// left to render on request. A format is left when it was generated before
+/// but not requested in the last `RARELY_USED_AFTER_DAYS` days; formats never
+/// generated are always rendered.
+pub fn split_rarely_used(
+    formats: &[HeatmapFormat],
+    generated: &[generated_heatmap::Model],
+    now: DateTime<Utc>,
+) -> (Vec<HeatmapFormat>, Vec<HeatmapFormat>) {
+    let cutoff = now - Duration::days(RARELY_USED_AFTER_DAYS);
+    formats.iter().cloned().partition(|format| {
+        generated
+            .iter()
+            .find(|file| file.format == *format)
+            .is_none_or(|file| file.last_accessed_at.is_some_and(|at| at >= cutoff))
+    })
+}
+
+/// When the file of `format` was last requested. Regenerating a format keeps
+/// it, since it decides whether the theme's next regeneration renders it.
+pub fn last_accessed_at(generated: &[generated_heatmap::Model], format: &HeatmapFormat) -> Option<DateTime<Utc>> {
+    generated
+        .iter()
+        .find(|file| file.format == *format)
+        .and_then(|file| file.last_accessed_at)
+}
+
+/// Whether `after` draws heatmaps `before` did not. The name, description
+/// and default flag are never drawn, and a change of formats leaves the
+/// files of the formats both have as they are.
+pub fn draws_differently(before: &heatmap_theme::Model, after: &heatmap_theme::Model) -> bool {
+    let drawn = heatmap_theme::Model {
+        name: before.name.clone(),
+        description: before.description.clone(),
+        is_default: before.is_default,
+        output_formats: before.output_formats.clone(),
+        updated_at: before.updated_at,
+        ..after.clone()
+    };
+    drawn != *before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn generated(format: HeatmapFormat, last_accessed_at: Option<&str>) -> generated_heatmap::Model {
+        generated_heatmap::Model {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            theme_id: Uuid::nil(),
+            format,
+            file_path: String::new(),
+            file_size_bytes: None,
+            file_hash: None,
+            generated_at: utc("2026-01-01T00:00:00Z"),
+            generation_duration_ms: None,
+            contribution_count: 0,
+            date_range_start: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            date_range_end: NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            access_count: 0,
+            last_accessed_at: last_accessed_at.map(utc),
+            is_valid: true,
+        }
+    }
+
+    fn theme() -> heatmap_theme::Model {
+        heatmap_theme::Model {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: "Default".to_string(),
+            slug: "default".to_string(),
+            description: None,
+            is_default: true,
+            theme_mode: heatmap_theme::ThemeMode::Light,
+            color_scheme: heatmap_theme::HeatmapColorScheme::GitHubGreen,
+            custom_colors: None,
+            background_color: "#ffffff".to_string(),
+            border_color: "#d1d5da".to_string(),
+            text_color: "#24292e".to_string(),
+            empty_cell_color: "#ebedf0".to_string(),
+            cell_size: 10,
+            cell_gap: 2,
+            cell_border_radius: 2,
+            cell_border_width: 0,
+            cell_border_color: "#d1d5da".to_string(),
+            heatmap_width: None,
+            heatmap_height: None,
+            padding_top: 20,
+            padding_right: 20,
+            padding_bottom: 17,
+            padding_left: 20,
+            day_label_width: 28,
+            month_label_height: 15,
+            title_height: 30,
+            legend_height: 8,
+            show_month_labels: true,
+            show_day_labels: true,
+            show_legend: true,
+            show_total_count: true,
+            show_username: true,
+            show_watermark: true,
+            font_family: "Nimbus Sans".to_string(),
+            font_size: 10,
+            legend_position: "bottom".to_string(),
+            layout: "grid".to_string(),
+            custom_template: None,
+            mini_days: None,
+            show_achievements: false,
+            show_code_frequency: false,
+            output_formats: vec![HeatmapFormat::Svg, HeatmapFormat::Png].into(),
+            created_at: utc("2026-01-01T00:00:00Z"),
+            updated_at: utc("2026-01-01T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn leaves_formats_not_requested_lately() {
+        let now = utc("2026-03-01T00:00:00Z");
+        let formats = [HeatmapFormat::Svg, HeatmapFormat::Png, HeatmapFormat::Jpeg, HeatmapFormat::WebP];
+        let files = [
+            generated(HeatmapFormat::Svg, Some("2026-02-28T12:00:00Z")),
+            generated(HeatmapFormat::Png, Some("2026-01-15T00:00:00Z")),
+            generated(HeatmapFormat::Jpeg, None),
+        ];
+
+        let (rendered, left) = split_rarely_used(&formats, &files, now);
+        // WebP was never generated, so it is rendered too
+        assert_eq!(rendered, vec![HeatmapFormat::Svg, HeatmapFormat::WebP]);
+        assert_eq!(left, vec![HeatmapFormat::Png, HeatmapFormat::Jpeg]);
+    }
+
+    #[test]
+    fn a_request_on_the_cutoff_counts_as_recent() {
+        let now = utc("2026-03-31T00:00:00Z");
+        let files = [generated(HeatmapFormat::Png, Some("2026-03-01T00:00:00Z"))];
+
+        let (rendered, left) = split_rarely_used(&[HeatmapFormat::Png], &files, now);
+        assert_eq!(rendered, vec![HeatmapFormat::Png]);
+        assert!(left.is_empty());
+    }
+
+    #[test]
+    fn keeps_the_last_request_of_each_format() {
+        let files = [
+            generated(HeatmapFormat::Svg, Some("2026-02-28T12:00:00Z")),
+            generated(HeatmapFormat::Png, None),
+        ];
+
+        assert_eq!(last_accessed_at(&files, &HeatmapFormat::Svg), Some(utc("2026-02-28T12:00:00Z")));
+        assert_eq!(last_accessed_at(&files, &HeatmapFormat::Png), None);
+        assert_eq!(last_accessed_at(&files, &HeatmapFormat::WebP), None);
+    }
+
+    #[test]
+    fn undrawn_edits_keep_the_files() {
+        let before = theme();
+        let after = heatmap_theme::Model {
+            name: "Renamed".to_string(),
+            description: Some("For the README".to_string()),
+            is_default: false,
+            output_formats: vec![HeatmapFormat::Svg, HeatmapFormat::WebP].into(),
+            updated_at: utc("2026-02-01T00:00:00Z"),
+            ..before.clone()
+        };
+
+        assert!(!draws_differently(&before, &after));
+    }
+
+    #[test]
+    fn drawn_edits_invalidate_the_files() {
+        let before = theme();
+        let edits = [
+            heatmap_theme::Model { text_color: "#112233".to_string(), ..before.clone() },
+            heatmap_theme::Model { color_scheme: heatmap_theme::HeatmapColorScheme::Ocean, ..before.clone() },
+            heatmap_theme::Model { layout: "skyline".to_string(), ..before.clone() },
+            heatmap_theme::Model { custom_template: Some("<svg></svg>".to_string()), ..before.clone() },
+            heatmap_theme::Model { mini_days: Some(30), ..before.clone() },
+            heatmap_theme::Model { show_legend: false, ..before.clone() },
+        ];
+
+        for after in &edits {
+            assert!(draws_differently(&before, after));
+        }
+    }
+}
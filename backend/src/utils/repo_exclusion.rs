@@ -0,0 +1,132 @@
+use sea_orm::sea_query::{Condition, Expr, Func, LikeExpr};
+use sea_orm::ColumnTrait;
+
+/// Most patterns a user can exclude
+pub const MAX_PATTERNS: usize = 100;
+const MAX_PATTERN_LENGTH: usize = 255;
+
+/// Validate exclusion patterns such as `acme/secret-project`, `acme/*` or
+/// `*/dotfiles`, returning them trimmed, lowercased and deduplicated.
+/// `*` matches any run of characters, including `/`.
+pub fn normalize(patterns: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for pattern in patterns {
+        let pattern = pattern.trim().to_lowercase();
+        if pattern.is_empty() {
+            continue;
+        }
+        if pattern.len() > MAX_PATTERN_LENGTH {
+            return Err(format!("Pattern '{}' is longer than {} characters", pattern, MAX_PATTERN_LENGTH));
+        }
+        if pattern.chars().all(|c| c == '*' || c == '/') {
+            return Err(format!("Pattern '{}' would hide every repository", pattern));
+        }
+        if !normalized.contains(&pattern) {
+            normalized.push(pattern);
+        }
+    }
+
+    if normalized.len() > MAX_PATTERNS {
+        return Err(format!("At most {} patterns can be excluded", MAX_PATTERNS));
+    }
+
+    Ok(normalized)
+}
+
+/// Whether a repository name matches a normalized pattern
+pub fn matches(pattern: &str, repository: &str) -> bool {
+    let repository = repository.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = repository.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty(); // No wildcard
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Whether a repository name matches any of the patterns
+pub fn is_excluded(patterns: &[String], repository: &str) -> bool {
+    patterns.iter().any(|p| matches(p, repository))
+}
+
+/// SQL LIKE equivalent of a normalized pattern
+fn like_pattern(pattern: &str) -> String {
+    pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+        .replace('*', "%")
+}
+
+/// Filter keeping rows whose repository `column` matches none of the
+/// patterns. Rows without a repository are always kept.
+pub fn not_excluded<C: ColumnTrait>(column: C, patterns: &[String]) -> Condition {
+    if patterns.is_empty() {
+        return Condition::all();
+    }
+
+    let kept = patterns.iter().fold(Condition::all(), |condition, pattern| {
+        condition.add(
+            Expr::expr(Func::lower(Expr::col(column)))
+                .not_like(LikeExpr::new(like_pattern(pattern)).escape('\\')),
+        )
+    });
+
+    Condition::any().add(column.is_null()).add(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_patterns() {
+        let patterns = vec![
+            " Acme/Secret ".to_string(),
+            "acme/secret".to_string(),
+            "".to_string(),
+            "*/dotfiles".to_string(),
+        ];
+        assert_eq!(normalize(&patterns).unwrap(), vec!["acme/secret", "*/dotfiles"]);
+    }
+
+    #[test]
+    fn rejects_catch_all_and_oversized_lists() {
+        assert!(normalize(&["*".to_string()]).is_err());
+        assert!(normalize(&["*/*".to_string()]).is_err());
+
+        let many: Vec<String> = (0..=MAX_PATTERNS).map(|i| format!("acme/repo-{}", i)).collect();
+        assert!(normalize(&many).is_err());
+    }
+
+    #[test]
+    fn matches_exact_and_wildcard_patterns() {
+        assert!(matches("acme/secret", "Acme/Secret"));
+        assert!(!matches("acme/secret", "acme/secret-2"));
+        assert!(matches("acme/*", "acme/anything"));
+        assert!(!matches("acme/*", "other/anything"));
+        assert!(matches("*/dotfiles", "jane/dotfiles"));
+        assert!(matches("acme/*-internal", "acme/billing-internal"));
+        assert!(!matches("acme/*-internal", "acme/internal-tools"));
+        assert!(matches("*client*", "acme/client-portal"));
+    }
+
+    #[test]
+    fn escapes_like_wildcards() {
+        assert_eq!(like_pattern("acme/my_repo*"), "acme/my\\_repo%");
+        assert_eq!(like_pattern("100%/x"), "100\\%/x");
+    }
+}
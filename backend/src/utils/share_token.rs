@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Audience of share link tokens. Like embed tokens, they are rejected by
+/// session validation.
+const AUDIENCE: &str = "share";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ShareClaims {
+    sub: String,
+    aud: String,
+    /// Left out for links that never expire
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<i64>,
+    iat: i64,
+}
+
+/// Sign the token for share link `link_id`, valid until `expires_at`
+pub fn create(link_id: Uuid, secret: &str, expires_at: Option<DateTime<Utc>>) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = ShareClaims {
+        sub: link_id.to_string(),
+        aud: AUDIENCE.to_string(),
+        exp: expires_at.map(|at| at.timestamp()),
+        iat: Utc::now().timestamp(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+}
+
+/// The share link a valid, unexpired token was signed for
+pub fn verify(token: &str, secret: &str) -> Option<Uuid> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[AUDIENCE]);
+    validation.required_spec_claims = HashSet::from(["aud".to_string()]);
+    decode::<ShareClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .ok()
+        .and_then(|data| Uuid::parse_str(&data.claims.sub).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::auth::decode_jwt;
+    use chrono::Duration;
+
+    const SECRET: &str = "test-secret";
+
+    #[test]
+    fn verifies_with_signing_secret_only() {
+        let link = Uuid::new_v4();
+        let token = create(link, SECRET, Some(Utc::now() + Duration::days(1))).unwrap();
+        assert_eq!(verify(&token, SECRET), Some(link));
+        assert_eq!(verify(&token, "other-secret"), None);
+    }
+
+    #[test]
+    fn expires_only_when_asked() {
+        let link = Uuid::new_v4();
+        let expired = create(link, SECRET, Some(Utc::now() - Duration::days(1))).unwrap();
+        assert_eq!(verify(&expired, SECRET), None);
+
+        let forever = create(link, SECRET, None).unwrap();
+        assert_eq!(verify(&forever, SECRET), Some(link));
+    }
+
+    #[test]
+    fn cannot_be_used_as_session() {
+        let token = create(Uuid::new_v4(), SECRET, None).unwrap();
+        assert!(decode_jwt(&token, SECRET).is_err());
+    }
+
+    #[test]
+    fn rejects_embed_tokens() {
+        let (token, _) = crate::utils::embed_token::create(Uuid::new_v4(), SECRET, Duration::days(1)).unwrap();
+        assert_eq!(verify(&token, SECRET), None);
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::NaiveDate;
+use std::ops::Range;
+
+/// A user's week, as the weekly summary post tells it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekSummary {
+    pub contributions: i32,
+    pub active_days: i32,
+    /// The day with the most contributions, and how many
+    pub busiest: Option<(NaiveDate, i32)>,
+    pub current_streak: i32,
+    /// Public profile link; None for unlisted and private profiles
+    pub profile_url: Option<String>,
+}
+
+fn plural(count: i32, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// The post text. Kept well under Bluesky's 300 characters, so it fits
+/// everywhere without cutting.
+pub fn summary_text(summary: &WeekSummary) -> String {
+    let mut text = if summary.contributions > 0 {
+        format!(
+            "📊 My coding week: {} on {}",
+            plural(summary.contributions, "contribution"),
+            plural(summary.active_days, "day")
+        )
+    } else {
+        "📊 A quiet coding week, no contributions".to_string()
+    };
+    if let Some((day, count)) = summary.busiest {
+        text.push_str(&format!(", busiest on {} with {}", day.format("%A"), count));
+    }
+    text.push('.');
+    if summary.current_streak > 0 {
+        text.push_str(&format!(" Current streak: {} 🔥", plural(summary.current_streak, "day")));
+    }
+    if let Some(url) = &summary.profile_url {
+        text.push_str("\n\n");
+        text.push_str(url);
+    }
+    text
+}
+
+/// Byte ranges of the http(s) links in `text`, which Bluesky only shows as
+/// links when told where they are
+pub fn link_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let trimmed = word.trim_end();
+        if trimmed.starts_with("https://") || trimmed.starts_with("http://") {
+            ranges.push(offset..offset + trimmed.len());
+        }
+        offset += word.len();
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_mentions_the_busiest_day_and_streak() {
+        let summary = WeekSummary {
+            contributions: 42,
+            active_days: 5,
+            busiest: Some(("2026-03-03".parse().unwrap(), 12)),
+            current_streak: 1,
+            profile_url: Some("https://hgitmap.example.com/alice".to_string()),
+        };
+        assert_eq!(
+            summary_text(&summary),
+            "📊 My coding week: 42 contributions on 5 days, busiest on Tuesday with 12. Current streak: 1 day 🔥\n\nhttps://hgitmap.example.com/alice"
+        );
+
+        let quiet = WeekSummary {
+            contributions: 0,
+            active_days: 0,
+            busiest: None,
+            current_streak: 0,
+            profile_url: None,
+        };
+        assert_eq!(summary_text(&quiet), "📊 A quiet coding week, no contributions.");
+    }
+
+    #[test]
+    fn link_ranges_are_byte_offsets() {
+        let text = "🔥 week\n\nhttps://hgitmap.example.com/alice and http://x.test";
+        let ranges = link_ranges(text);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(&text[ranges[0].clone()], "https://hgitmap.example.com/alice");
+        assert_eq!(&text[ranges[1].clone()], "http://x.test");
+    }
+}
@@ -0,0 +1,195 @@
+use minijinja::{AutoEscape, Environment, UndefinedBehavior};
+use serde::Serialize;
+
+/// Longest template a theme can store
+pub const MAX_TEMPLATE_BYTES: usize = 64 * 1024;
+
+/// Longest SVG a template may produce
+const MAX_OUTPUT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Instructions a render may run, several times what a full-year grid takes,
+/// so loops over `range()` cannot keep a worker busy
+const FUEL: u64 = 1_000_000;
+
+/// Deepest nesting of macro calls and blocks
+const RECURSION_LIMIT: usize = 32;
+
+/// Markup that could run code or pull in other documents when the SVG is
+/// opened on its own. Checked after entities are decoded, case and
+/// whitespace ignored.
+const FORBIDDEN: [&str; 10] = [
+    "<script",
+    "<foreignobject",
+    "<iframe",
+    "<embed",
+    "<object",
+    "<!entity",
+    "javascript:",
+    "vbscript:",
+    "data:text/html",
+    "@import",
+];
+
+/// An environment without a loader, so templates cannot include, import or
+/// extend others, and with every value HTML-escaped on output
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_fuel(Some(FUEL));
+    env.set_recursion_limit(RECURSION_LIMIT);
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_auto_escape_callback(|_| AutoEscape::Html);
+    env
+}
+
+/// Check that a template is short enough and parses
+pub fn compile(source: &str) -> Result<(), String> {
+    if source.len() > MAX_TEMPLATE_BYTES {
+        return Err(format!(
+            "Template must be at most {} KB",
+            MAX_TEMPLATE_BYTES / 1024
+        ));
+    }
+    environment()
+        .template_from_str(source)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid template: {}", e))
+}
+
+/// Render a template with `context` and check that the result is a safe SVG
+pub fn render<S: Serialize>(source: &str, context: S) -> Result<String, String> {
+    compile(source)?;
+    let output = environment()
+        .render_str(source, context)
+        .map_err(|e| format!("Template failed: {}", e))?;
+    check_svg(&output)?;
+    Ok(output)
+}
+
+/// An `<svg>` document without scripts, event handlers, embedded documents
+/// or script URLs, and not too large
+pub fn check_svg(svg: &str) -> Result<(), String> {
+    if svg.len() > MAX_OUTPUT_BYTES {
+        return Err(format!(
+            "Template output must be at most {} MB",
+            MAX_OUTPUT_BYTES / 1024 / 1024
+        ));
+    }
+    if !svg.trim_start().starts_with("<svg") {
+        return Err("Template output must start with <svg".to_string());
+    }
+
+    let decoded = decode_char_refs(svg).to_lowercase();
+    let compact: String = decoded
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    if let Some(found) = FORBIDDEN.iter().find(|markup| compact.contains(*markup)) {
+        return Err(format!("Template output must not contain {}", found));
+    }
+    if has_event_handler(&decoded) {
+        return Err("Template output must not contain event handler attributes".to_string());
+    }
+
+    Ok(())
+}
+
+/// Replace `&#NN;` and `&#xNN;` with the characters they stand for, so
+/// encoded markup is checked like plain markup
+fn decode_char_refs(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("&#") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find(';').filter(|end| *end <= 8);
+        let decoded = end.and_then(|end| {
+            let digits = &after[..end];
+            let code = match digits.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => digits.parse().ok(),
+            };
+            code.and_then(char::from_u32)
+        });
+        match (decoded, end) {
+            (Some(c), Some(end)) => {
+                out.push(c);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push_str("&#");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether a tag has an `on...=` attribute, such as onload or onclick
+fn has_event_handler(svg: &str) -> bool {
+    let bytes = svg.as_bytes();
+    let mut in_tag = false;
+    for i in 0..bytes.len() {
+        match bytes[i] {
+            b'<' => in_tag = true,
+            b'>' => in_tag = false,
+            b'o' if in_tag
+                && i > 0
+                && matches!(bytes[i - 1], b' ' | b'\t' | b'\n' | b'\r' | b'/' | b'"' | b'\'')
+                && bytes.get(i + 1) == Some(&b'n') =>
+            {
+                let name_end = bytes[i + 2..]
+                    .iter()
+                    .position(|b| !b.is_ascii_alphabetic())
+                    .map_or(bytes.len(), |offset| i + 2 + offset);
+                let value_start = bytes[name_end..]
+                    .iter()
+                    .position(|b| !b.is_ascii_whitespace())
+                    .map(|offset| name_end + offset);
+                if name_end > i + 2 && value_start.is_some_and(|at| bytes[at] == b'=') {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_with_escaped_values() {
+        let svg = render(
+            r#"<svg>{% for n in counts %}<rect height="{{ n }}"/>{% endfor %}<text>{{ name }}</text></svg>"#,
+            json!({ "counts": [1, 2], "name": "<b>" }),
+        )
+        .unwrap();
+        assert_eq!(svg, r#"<svg><rect height="1"/><rect height="2"/><text>&lt;b&gt;</text></svg>"#);
+    }
+
+    #[test]
+    fn rejects_unsafe_output() {
+        assert!(check_svg("<svg><script>alert(1)</script></svg>").is_err());
+        assert!(check_svg(r#"<svg><rect onload="alert(1)"/></svg>"#).is_err());
+        assert!(check_svg(r#"<svg><rect ONCLICK = "x"/></svg>"#).is_err());
+        assert!(check_svg(r#"<svg><a href="java&#x73;cript:alert(1)">x</a></svg>"#).is_err());
+        assert!(check_svg(r#"<svg><a href="java script:x">x</a></svg>"#).is_err());
+        assert!(check_svg("<html><body></body></html>").is_err());
+
+        // Words starting with "on" outside of attributes are fine
+        assert!(check_svg(r#"<svg><text font-family="Nimbus Sans">one = 1 online</text></svg>"#).is_ok());
+    }
+
+    #[test]
+    fn limits_templates() {
+        assert!(render("<svg>{% include 'other.svg' %}</svg>", json!({})).is_err());
+        assert!(render("<svg>{% for i in range(1000) %}{% for j in range(1000) %}.{% endfor %}{% endfor %}</svg>", json!({})).is_err());
+        assert!(render("<svg>{{ missing }}</svg>", json!({})).is_err());
+        assert!(compile("{% for %}").is_err());
+        assert!(compile(&"x".repeat(MAX_TEMPLATE_BYTES + 1)).is_err());
+    }
+}
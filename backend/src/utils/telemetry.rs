@@ -0,0 +1,123 @@
+use opentelemetry::trace::{Span as _, SpanKind, Status, Tracer, TracerProvider as _};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use std::time::SystemTime;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::EnvFilter;
+
+use crate::utils::config::Config;
+
+/// Name of the tracer used for spans created outside the `tracing` macros
+const TRACER_NAME: &str = "hgitmap";
+/// SQL longer than this is truncated in the `db.statement` attribute
+const MAX_STATEMENT_LEN: usize = 2048;
+
+/// Keeps the tracer provider alive; call `shutdown` before exiting to flush
+/// buffered spans
+pub struct Telemetry {
+    provider: Option<TracerProvider>,
+}
+
+impl Telemetry {
+    pub fn is_enabled(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                log::error!("Failed to flush traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Export `tracing` spans over OTLP when `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+/// Logging stays with env_logger either way.
+pub fn init(config: &Config) -> Telemetry {
+    let Some(endpoint) = config.otel_exporter_endpoint.as_deref() else {
+        return Telemetry { provider: None };
+    };
+
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+    {
+        Ok(e) => e,
+        Err(e) => {
+            log::error!("Failed to create OTLP exporter, tracing disabled: {}", e);
+            return Telemetry { provider: None };
+        }
+    };
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.otel_sample_ratio,
+        ))))
+        .with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            config.otel_service_name.clone(),
+        )]))
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+
+    let filter = EnvFilter::try_from_env("OTEL_TRACES_FILTER")
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME)));
+
+    if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+        log::error!("Failed to install tracing subscriber: {}", e);
+        return Telemetry { provider: None };
+    }
+
+    log::info!("📡 Exporting traces to {} as {}", endpoint, config.otel_service_name);
+    Telemetry {
+        provider: Some(provider),
+    }
+}
+
+/// SeaORM metric callback: record a finished query as a child span of the
+/// current `tracing` span
+pub fn record_db_query(info: &sea_orm::metric::Info<'_>) {
+    let end = SystemTime::now();
+    let start = end.checked_sub(info.elapsed).unwrap_or(end);
+
+    let sql = info.statement.sql.as_str();
+    let operation = sql
+        .split_whitespace()
+        .next()
+        .unwrap_or("QUERY")
+        .to_uppercase();
+    let statement = match sql.char_indices().nth(MAX_STATEMENT_LEN) {
+        Some((idx, _)) => &sql[..idx],
+        None => sql,
+    };
+
+    let parent = tracing::Span::current().context();
+    let tracer = global::tracer(TRACER_NAME);
+    let mut span = tracer
+        .span_builder(format!("db {}", operation))
+        .with_kind(SpanKind::Client)
+        .with_start_time(start)
+        .with_attributes(vec![
+            KeyValue::new("db.system", "postgresql"),
+            KeyValue::new("db.operation", operation),
+            KeyValue::new("db.statement", statement.to_string()),
+        ])
+        .start_with_context(&tracer, &parent);
+
+    if info.failed {
+        span.set_status(Status::error("query failed"));
+    }
+    span.end_with_timestamp(end);
+}
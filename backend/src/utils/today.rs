@@ -0,0 +1,107 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+use crate::models::user_setting::AwayPeriod;
+use crate::utils::away_periods;
+
+/// Where the current streak stands today
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StreakStatus {
+    /// Contributed today
+    Extended,
+    /// The streak reaches yesterday and ends tonight without a contribution
+    AtRisk,
+    /// Today is an away day, which holds the streak without a contribution
+    Away,
+    /// No current streak
+    None,
+}
+
+/// Today's contributions and the streak they belong to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Today {
+    pub contributions: i64,
+    pub current_streak: i64,
+    pub streak_status: StreakStatus,
+}
+
+/// Sum up `today` from daily counts. Days after `today` are ignored, and
+/// away days bridge a streak without adding to it, as in the stats.
+pub fn summarize(days: &BTreeMap<NaiveDate, i64>, away: &[AwayPeriod], today: NaiveDate) -> Today {
+    let mut previous: Option<NaiveDate> = None;
+    let mut length = 0;
+    for (&date, _) in days.range(..=today).filter(|(_, &count)| count > 0) {
+        length = match previous {
+            Some(prev) if away_periods::bridges(away, prev, date) => length + 1,
+            _ => 1,
+        };
+        previous = Some(date);
+    }
+
+    let contributions = days.get(&today).copied().unwrap_or(0).max(0);
+    let (current_streak, streak_status) = match previous {
+        Some(last) if last == today => (length, StreakStatus::Extended),
+        Some(last) if away_periods::bridges(away, last, today) => {
+            let status = if away_periods::contains(away, today) {
+                StreakStatus::Away
+            } else {
+                StreakStatus::AtRisk
+            };
+            (length, status)
+        }
+        _ => (0, StreakStatus::None),
+    };
+
+    Today {
+        contributions,
+        current_streak,
+        streak_status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+    }
+
+    #[test]
+    fn streak_is_extended_or_at_risk() {
+        let days = BTreeMap::from([(date(1), 2), (date(2), 1), (date(3), 4), (date(9), 5)]);
+
+        let extended = summarize(&days, &[], date(3));
+        assert_eq!(extended.contributions, 4);
+        assert_eq!(extended.current_streak, 3);
+        assert_eq!(extended.streak_status, StreakStatus::Extended);
+
+        let at_risk = summarize(&days, &[], date(4));
+        assert_eq!(at_risk.contributions, 0);
+        assert_eq!(at_risk.current_streak, 3);
+        assert_eq!(at_risk.streak_status, StreakStatus::AtRisk);
+
+        let broken = summarize(&days, &[], date(5));
+        assert_eq!(broken.current_streak, 0);
+        assert_eq!(broken.streak_status, StreakStatus::None);
+    }
+
+    #[test]
+    fn away_days_hold_the_streak() {
+        let days = BTreeMap::from([(date(1), 1), (date(2), 1)]);
+        let away = [AwayPeriod {
+            start: date(3),
+            end: date(6),
+        }];
+
+        let today = summarize(&days, &away, date(5));
+        assert_eq!(today.current_streak, 2);
+        assert_eq!(today.streak_status, StreakStatus::Away);
+
+        let back = summarize(&days, &away, date(7));
+        assert_eq!(back.streak_status, StreakStatus::AtRisk);
+    }
+}
@@ -0,0 +1,137 @@
+/// Size and refill rate of a token bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketLimit {
+    pub capacity: u32,
+    pub refill_per_sec: f64,
+}
+
+impl BucketLimit {
+    /// Allow `requests` per minute, with bursts up to the same amount
+    pub fn per_minute(requests: u32) -> Self {
+        let capacity = requests.max(1);
+        Self {
+            capacity,
+            refill_per_sec: capacity as f64 / 60.0,
+        }
+    }
+
+    /// Allow `requests` per hour, with bursts up to the same amount
+    pub fn per_hour(requests: u32) -> Self {
+        let capacity = requests.max(1);
+        Self {
+            capacity,
+            refill_per_sec: capacity as f64 / 3600.0,
+        }
+    }
+
+    /// Seconds until an empty bucket is full again
+    pub fn full_refill_secs(&self) -> f64 {
+        self.capacity as f64 / self.refill_per_sec
+    }
+}
+
+/// Outcome of taking a token, with the values for the X-RateLimit-* headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again
+    pub reset_after_secs: u64,
+    /// Seconds until the next token is available; 0 when allowed
+    pub retry_after_secs: u64,
+}
+
+impl Decision {
+    /// Build a decision from the tokens left after the attempt
+    pub fn new(allowed: bool, tokens: f64, limit: &BucketLimit) -> Self {
+        let missing = (limit.capacity as f64 - tokens).max(0.0);
+        let retry_after_secs = if allowed {
+            0
+        } else {
+            ((1.0 - tokens) / limit.refill_per_sec).ceil().max(1.0) as u64
+        };
+
+        Self {
+            allowed,
+            limit: limit.capacity,
+            remaining: tokens.floor().max(0.0) as u32,
+            reset_after_secs: (missing / limit.refill_per_sec).ceil() as u64,
+            retry_after_secs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBucket {
+    tokens: f64,
+    /// Time of the last update, in seconds since an arbitrary epoch
+    updated_at: f64,
+}
+
+impl TokenBucket {
+    pub fn full(limit: &BucketLimit, now: f64) -> Self {
+        Self {
+            tokens: limit.capacity as f64,
+            updated_at: now,
+        }
+    }
+
+    /// Refill for the time elapsed since the last call, then try to take one token
+    pub fn take(&mut self, limit: &BucketLimit, now: f64) -> Decision {
+        let elapsed = (now - self.updated_at).max(0.0);
+        self.tokens = (self.tokens + elapsed * limit.refill_per_sec).min(limit.capacity as f64);
+        self.updated_at = now;
+
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
+        }
+        Decision::new(allowed, self.tokens, limit)
+    }
+
+    /// Whether the bucket would be full at `now`, i.e. it carries no state worth keeping
+    pub fn is_idle(&self, limit: &BucketLimit, now: f64) -> bool {
+        now - self.updated_at >= limit.full_refill_secs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_exhausts_and_refills() {
+        let limit = BucketLimit::per_minute(2);
+        let mut bucket = TokenBucket::full(&limit, 0.0);
+
+        let first = bucket.take(&limit, 0.0);
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+        assert!(bucket.take(&limit, 0.0).allowed);
+
+        let denied = bucket.take(&limit, 0.0);
+        assert!(!denied.allowed);
+        assert_eq!(denied.remaining, 0);
+        assert_eq!(denied.retry_after_secs, 30);
+        assert_eq!(denied.reset_after_secs, 60);
+
+        // One token refills every 30 seconds
+        assert!(bucket.take(&limit, 30.0).allowed);
+        assert!(!bucket.take(&limit, 30.0).allowed);
+        assert!(bucket.is_idle(&limit, 90.0));
+    }
+
+    #[test]
+    fn test_hourly_limit_refills_slowly() {
+        let limit = BucketLimit::per_hour(4);
+        let mut bucket = TokenBucket::full(&limit, 0.0);
+
+        for _ in 0..4 {
+            assert!(bucket.take(&limit, 0.0).allowed);
+        }
+        let denied = bucket.take(&limit, 0.0);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after_secs, 900);
+    }
+}
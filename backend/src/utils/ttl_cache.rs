@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Small in-memory cache whose entries expire after a fixed time to live.
+/// When full, the least recently used entry makes room. A zero TTL disables
+/// it.
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    max_entries: usize,
+    /// Value with when it was inserted and last read
+    entries: Mutex<HashMap<K, (Instant, Instant, V)>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get_mut(key) {
+            Some((inserted_at, used_at, value)) if inserted_at.elapsed() < self.ttl => {
+                *used_at = Instant::now();
+                Some(value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.max_entries {
+            let ttl = self.ttl;
+            entries.retain(|_, (inserted_at, _, _)| inserted_at.elapsed() < ttl);
+        }
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            // Still full of live entries: evict the least recently used
+            let least_recent = entries
+                .iter()
+                .min_by_key(|(_, (_, used_at, _))| *used_at)
+                .map(|(k, _)| k.clone());
+            if let Some(least_recent) = least_recent {
+                entries.remove(&least_recent);
+            }
+        }
+        let now = Instant::now();
+        entries.insert(key, (now, now, value));
+    }
+
+    /// Drop the entries whose key matches
+    pub fn remove_where(&self, matches: impl Fn(&K) -> bool) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|key, _| !matches(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_values_until_they_expire() {
+        let cache = TtlCache::new(Duration::from_millis(50), 10);
+        cache.insert("a", 1);
+        assert_eq!(cache.get(&"a"), Some(1));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn evicts_oldest_entry_when_full() {
+        let cache = TtlCache::new(Duration::from_secs(60), 2);
+        cache.insert("a", 1);
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert("b", 2);
+        cache.insert("c", 3);
+
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.get(&"b"), Some(2));
+        assert_eq!(cache.get(&"c"), Some(3));
+    }
+
+    #[test]
+    fn reading_an_entry_keeps_it_over_older_reads() {
+        let cache = TtlCache::new(Duration::from_secs(60), 2);
+        cache.insert("a", 1);
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert("b", 2);
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(cache.get(&"a"), Some(1));
+        cache.insert("c", 3);
+
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(3));
+    }
+
+    #[test]
+    fn removes_matching_entries() {
+        let cache = TtlCache::new(Duration::from_secs(60), 10);
+        cache.insert("user-a|stats", 1);
+        cache.insert("user-a|contributions", 2);
+        cache.insert("user-b|stats", 3);
+
+        cache.remove_where(|key| key.starts_with("user-a|"));
+        assert_eq!(cache.get(&"user-a|stats"), None);
+        assert_eq!(cache.get(&"user-a|contributions"), None);
+        assert_eq!(cache.get(&"user-b|stats"), Some(3));
+    }
+
+    #[test]
+    fn zero_ttl_disables_cache() {
+        let cache = TtlCache::new(Duration::ZERO, 10);
+        cache.insert("a", 1);
+        assert!(!cache.is_enabled());
+        assert_eq!(cache.get(&"a"), None);
+    }
+}
@@ -2,7 +2,6 @@ use anyhow::{anyhow, Result};
 use url::Url;
 
 /// Validate that a string is a valid URL with http or https scheme
-#[allow(dead_code)]
 pub fn validate_url(url_str: &str) -> Result<Url> {
     let url = Url::parse(url_str)
         .map_err(|e| anyhow!("Invalid URL format: {}", e))?;
@@ -43,7 +42,6 @@ pub fn validate_instance_url(url_str: &str) -> Result<String> {
 }
 
 /// Validate username (alphanumeric, hyphens, underscores, 1-39 chars for GitHub compatibility)
-#[allow(dead_code)]
 pub fn validate_username(username: &str) -> Result<()> {
     if username.is_empty() || username.len() > 39 {
         return Err(anyhow!(
@@ -75,6 +73,59 @@ pub fn validate_platform(platform: &str) -> Result<()> {
     }
 }
 
+/// Validate a new password. bcrypt only uses the first 72 bytes, so longer
+/// passwords are rejected rather than silently truncated.
+pub fn validate_password(password: &str) -> Result<()> {
+    if password.chars().count() < 8 {
+        return Err(anyhow!("Password must be at least 8 characters"));
+    }
+    if password.len() > 72 {
+        return Err(anyhow!("Password must be at most 72 bytes"));
+    }
+    Ok(())
+}
+
+/// Basic shape check for an email address; delivery confirms the rest
+pub fn validate_email(email: &str) -> Result<()> {
+    let valid = email.len() <= 255
+        && !email.chars().any(char::is_whitespace)
+        && email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid email address"))
+    }
+}
+
+/// Validate a slug used in URLs (lowercase letters, digits and single
+/// hyphens, 1-100 chars)
+pub fn validate_slug(slug: &str) -> Result<()> {
+    if slug.is_empty() || slug.len() > 100 {
+        return Err(anyhow!("Slug must be between 1 and 100 characters"));
+    }
+
+    let valid = slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && !slug.contains("--");
+    if !valid {
+        return Err(anyhow!(
+            "Slug can only contain lowercase letters, digits and single hyphens between them"
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,6 +163,36 @@ mod tests {
         assert!(validate_username("user@example").is_err());
     }
 
+    #[test]
+    fn test_validate_password() {
+        assert!(validate_password("correct horse").is_ok());
+        assert!(validate_password("short").is_err());
+        assert!(validate_password(&"a".repeat(73)).is_err());
+    }
+
+    #[test]
+    fn test_validate_email() {
+        assert!(validate_email("jane@example.com").is_ok());
+        assert!(validate_email("jane.doe+hgitmap@mail.example.org").is_ok());
+        assert!(validate_email("jane").is_err());
+        assert!(validate_email("@example.com").is_err());
+        assert!(validate_email("jane@localhost").is_err());
+        assert!(validate_email("jane@@example.com").is_err());
+        assert!(validate_email("jane doe@example.com").is_err());
+    }
+
+    #[test]
+    fn test_validate_slug() {
+        assert!(validate_slug("platform-team").is_ok());
+        assert!(validate_slug("team2").is_ok());
+        assert!(validate_slug("").is_err());
+        assert!(validate_slug(&"a".repeat(101)).is_err());
+        assert!(validate_slug("Platform").is_err());
+        assert!(validate_slug("-team").is_err());
+        assert!(validate_slug("my--team").is_err());
+        assert!(validate_slug("team/1").is_err());
+    }
+
     #[test]
     fn test_validate_platform() {
         assert!(validate_platform("github").is_ok());
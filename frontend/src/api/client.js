@@ -123,6 +123,13 @@ class ApiClient {
     });
   }
 
+  async validateInstance(platform, instanceUrl, accessToken = null) {
+    return this.fetchWithAuth('/platforms/validate-instance', {
+      method: 'POST',
+      body: JSON.stringify({ platform, instance_url: instanceUrl, access_token: accessToken }),
+    });
+  }
+
   async listPlatforms() {
     return this.fetchWithAuth('/platforms');
   }
@@ -137,6 +144,16 @@ class ApiClient {
     });
   }
 
+  async listDisconnectedPlatforms() {
+    return this.fetchWithAuth('/platforms/disconnected');
+  }
+
+  async restorePlatform(platformId) {
+    return this.fetchWithAuth(`/platforms/${platformId}/restore`, {
+      method: 'POST',
+    });
+  }
+
   async syncPlatform(platformId, allYears = false, year = null, profileOnly = false) {
     let queryParams = [];
 
@@ -200,6 +217,43 @@ class ApiClient {
     });
   }
 
+  // file: a GH Archive slice or saved Events API response (File or Blob)
+  async importActivities(platformId, file) {
+    const response = await fetch(`${API_BASE_URL}/platforms/${platformId}/import`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/octet-stream',
+        'Authorization': `Bearer ${localStorage.getItem('token')}`,
+      },
+      body: file,
+    });
+
+    // Too large uploads are rejected before reaching the handler, without JSON
+    const data = await response.json().catch(() => ({}));
+
+    if (!response.ok) {
+      throw new Error(data.error || 'Import failed');
+    }
+
+    return data;
+  }
+
+  async getImportStatus(importId) {
+    return this.fetchWithAuth(`/platforms/imports/${importId}`);
+  }
+
+  async listImports() {
+    return this.fetchWithAuth('/platforms/imports');
+  }
+
+  async getPlatformCapabilities(platformId) {
+    return this.fetchWithAuth(`/platforms/${platformId}/capabilities`);
+  }
+
+  async getPlatformApiUsage(platformId, hours = 24) {
+    return this.fetchWithAuth(`/platforms/${platformId}/api-usage?hours=${hours}`);
+  }
+
   async updatePlatformSyncPreferences(platformId, preferences) {
     return this.fetchWithAuth(`/platforms/${platformId}/sync-preferences`, {
       method: 'PUT',
@@ -224,6 +278,12 @@ class ApiClient {
     return this.fetchWithAuth('/contributions/stats');
   }
 
+  // Organizations of the signed-in user's public accounts; sent with auth so
+  // it also works while the profile is private
+  async getOrganizations(username) {
+    return this.fetchWithAuth(`/users/${encodeURIComponent(username)}/organizations`);
+  }
+
   // Activities
   async getActivities(from = null, to = null, limit = 50, offset = 0, platform = null) {
     const params = new URLSearchParams();
@@ -251,6 +311,82 @@ class ApiClient {
     });
   }
 
+  async getCommitEmails() {
+    return this.fetchWithAuth('/settings/commit-emails');
+  }
+
+  async addCommitEmail(email) {
+    return this.fetchWithAuth('/settings/commit-emails', {
+      method: 'POST',
+      body: JSON.stringify({ email }),
+    });
+  }
+
+  async resendCommitEmailVerification(email) {
+    return this.fetchWithAuth(`/settings/commit-emails/${encodeURIComponent(email)}/verify`, {
+      method: 'POST',
+    });
+  }
+
+  async deleteCommitEmail(email) {
+    return this.fetchWithAuth(`/settings/commit-emails/${encodeURIComponent(email)}`, {
+      method: 'DELETE',
+    });
+  }
+
+  async getMqttSettings() {
+    return this.fetchWithAuth('/settings/mqtt');
+  }
+
+  async updateMqttSettings(mqtt) {
+    return this.fetchWithAuth('/settings/mqtt', {
+      method: 'PUT',
+      body: JSON.stringify(mqtt),
+    });
+  }
+
+  async deleteMqttSettings() {
+    return this.fetchWithAuth('/settings/mqtt', {
+      method: 'DELETE',
+    });
+  }
+
+  async testMqtt() {
+    return this.fetchWithAuth('/settings/mqtt/test', {
+      method: 'POST',
+    });
+  }
+
+  async getNotificationChannels() {
+    return this.fetchWithAuth('/settings/channels');
+  }
+
+  async createNotificationChannel(channel) {
+    return this.fetchWithAuth('/settings/channels', {
+      method: 'POST',
+      body: JSON.stringify(channel),
+    });
+  }
+
+  async updateNotificationChannel(id, events) {
+    return this.fetchWithAuth(`/settings/channels/${id}`, {
+      method: 'PUT',
+      body: JSON.stringify({ events }),
+    });
+  }
+
+  async testNotificationChannel(id) {
+    return this.fetchWithAuth(`/settings/channels/${id}/test`, {
+      method: 'POST',
+    });
+  }
+
+  async deleteNotificationChannel(id) {
+    return this.fetchWithAuth(`/settings/channels/${id}`, {
+      method: 'DELETE',
+    });
+  }
+
   // OAuth
   async startGithubOAuth() {
     const response = await this.fetchWithAuth('/oauth/github/authorize');
@@ -281,7 +417,7 @@ class ApiClient {
     return this.fetchWithAuth('/admin/oauth-apps');
   }
 
-  async createOAuthApp(platform, instanceName, instanceUrl, clientId, clientSecret, isDefault = true) {
+  async createOAuthApp(platform, instanceName, instanceUrl, clientId, clientSecret, isDefault = true, scopes = '', redirectUri = '', listing = {}) {
     return this.fetchWithAuth('/admin/oauth-apps', {
       method: 'POST',
       body: JSON.stringify({
@@ -292,10 +428,21 @@ class ApiClient {
         client_secret: clientSecret,
         is_default: isDefault,
         is_enabled: true,
+        scopes,
+        redirect_uri: redirectUri,
+        ...listing,
       }),
     });
   }
 
+  // Apps in connect screen order, each optionally with is_featured
+  async reorderOAuthApps(apps) {
+    return this.fetchWithAuth('/admin/oauth-apps/order', {
+      method: 'PUT',
+      body: JSON.stringify({ apps }),
+    });
+  }
+
   async updateOAuthApp(appId, updates) {
     return this.fetchWithAuth(`/admin/oauth-apps/${appId}`, {
       method: 'PUT',
@@ -309,6 +456,12 @@ class ApiClient {
     });
   }
 
+  async testOAuthApp(appId) {
+    return this.fetchWithAuth(`/admin/oauth-apps/${appId}/test`, {
+      method: 'POST',
+    });
+  }
+
   // Heatmap Themes
   async listThemes() {
     return this.fetchWithAuth('/heatmap/themes');
@@ -389,6 +542,115 @@ class ApiClient {
     return this.fetchWithAuth(`/heatmap/jobs?${queryString}`);
   }
 
+  async retryGenerationJob(jobId) {
+    return this.fetchWithAuth(`/heatmap/jobs/${jobId}/retry`, {
+      method: 'POST',
+    });
+  }
+
+  // Following
+  async listFollowing() {
+    return this.fetchWithAuth('/following');
+  }
+
+  async followUser(username) {
+    return this.fetchWithAuth(`/following/${encodeURIComponent(username)}`, {
+      method: 'PUT',
+    });
+  }
+
+  async unfollowUser(username) {
+    return this.fetchWithAuth(`/following/${encodeURIComponent(username)}`, {
+      method: 'DELETE',
+    });
+  }
+
+  async getFeed(cursor = null, limit = 50) {
+    const params = new URLSearchParams({ limit: limit.toString() });
+    if (cursor) params.append('cursor', cursor);
+    return this.fetchWithAuth(`/feed?${params.toString()}`);
+  }
+
+  // Milestones
+  async getMilestones() {
+    return this.fetchWithAuth('/milestones');
+  }
+
+  // Annotations
+  async listAnnotations(from = null, to = null) {
+    const params = new URLSearchParams();
+    if (from) params.append('from', from);
+    if (to) params.append('to', to);
+    const queryString = params.toString();
+    return this.fetchWithAuth(queryString ? `/annotations?${queryString}` : '/annotations');
+  }
+
+  async createAnnotation(annotation) {
+    return this.fetchWithAuth('/annotations', {
+      method: 'POST',
+      body: JSON.stringify(annotation),
+    });
+  }
+
+  async updateAnnotation(annotationId, annotation) {
+    return this.fetchWithAuth(`/annotations/${annotationId}`, {
+      method: 'PUT',
+      body: JSON.stringify(annotation),
+    });
+  }
+
+  async deleteAnnotation(annotationId) {
+    return this.fetchWithAuth(`/annotations/${annotationId}`, {
+      method: 'DELETE',
+    });
+  }
+
+  // Teams
+  async listTeams() {
+    return this.fetchWithAuth('/teams');
+  }
+
+  async createTeam(name, slug, description = null) {
+    return this.fetchWithAuth('/teams', {
+      method: 'POST',
+      body: JSON.stringify({ name, slug, description }),
+    });
+  }
+
+  async getTeam(slug) {
+    return this.fetchWithAuth(`/teams/${slug}`);
+  }
+
+  async updateTeam(slug, name, description = null) {
+    return this.fetchWithAuth(`/teams/${slug}`, {
+      method: 'PUT',
+      body: JSON.stringify({ name, description }),
+    });
+  }
+
+  async deleteTeam(slug) {
+    return this.fetchWithAuth(`/teams/${slug}`, {
+      method: 'DELETE',
+    });
+  }
+
+  async addTeamMember(slug, username) {
+    return this.fetchWithAuth(`/teams/${slug}/members`, {
+      method: 'POST',
+      body: JSON.stringify({ username }),
+    });
+  }
+
+  async removeTeamMember(slug, username) {
+    return this.fetchWithAuth(`/teams/${slug}/members/${encodeURIComponent(username)}`, {
+      method: 'DELETE',
+    });
+  }
+
+  async getTeamStats(slug) {
+    return this.fetchWithAuth(`/teams/${slug}/stats`);
+  }
+
   // Get heatmap embed URL
   getHeatmapEmbedUrl(username, themeSlug, format = 'png') {
     return `${API_BASE_URL}/embed/${username}/${themeSlug}.${format}`;
@@ -441,6 +703,13 @@ class ApiClient {
     return data;
   }
 
+  async listUsers(search = '', sort = 'recent', cursor = null) {
+    const params = new URLSearchParams({ sort });
+    if (search) params.append('search', search);
+    if (cursor) params.append('cursor', cursor);
+    return this.fetchWithAuth(`/users?${params.toString()}`);
+  }
+
   async getUserStats(username) {
     // Use regular fetch without auth
     const response = await fetch(`${API_BASE_URL}/users/${username}/contributions/stats`);
@@ -453,6 +722,18 @@ class ApiClient {
     return data;
   }
 
+  async getUserHeatmapSources(username) {
+    // Use regular fetch without auth
+    const response = await fetch(`${API_BASE_URL}/users/${username}/heatmap.json`);
+    const data = await response.json();
+
+    if (!response.ok) {
+      throw new Error(data.error || 'Failed to fetch user heatmap');
+    }
+
+    return data;
+  }
+
   async getUserPlatforms(username) {
     // Use regular fetch without auth
     const response = await fetch(`${API_BASE_URL}/users/${username}/platforms`);
@@ -465,6 +746,42 @@ class ApiClient {
     return data;
   }
 
+  async getUserOrganizations(username) {
+    // Use regular fetch without auth
+    const response = await fetch(`${API_BASE_URL}/users/${username}/organizations`);
+    const data = await response.json();
+
+    if (!response.ok) {
+      throw new Error(data.error || 'Failed to fetch user organizations');
+    }
+
+    return data;
+  }
+
+  async getUserLanguages(username) {
+    // Use regular fetch without auth
+    const response = await fetch(`${API_BASE_URL}/users/${username}/languages`);
+    const data = await response.json();
+
+    if (!response.ok) {
+      throw new Error(data.error || 'Failed to fetch user languages');
+    }
+
+    return data;
+  }
+
+  async getUserAchievements(username) {
+    // Use regular fetch without auth
+    const response = await fetch(`${API_BASE_URL}/users/${username}/achievements`);
+    const data = await response.json();
+
+    if (!response.ok) {
+      throw new Error(data.error || 'Failed to fetch user achievements');
+    }
+
+    return data;
+  }
+
   async getUserActivities(username, from = null, to = null, limit = 50, offset = 0, platform = null) {
     const params = new URLSearchParams();
     if (from) params.append('from', from);
@@ -101,6 +101,17 @@ const ActivityIcons = {
       <path d="M8 .25a.75.75 0 0 1 .673.418l1.882 3.815 4.21.612a.75.75 0 0 1 .416 1.279l-3.046 2.97.719 4.192a.751.751 0 0 1-1.088.791L8 12.347l-3.766 1.98a.75.75 0 0 1-1.088-.79l.72-4.194L.818 6.374a.75.75 0 0 1 .416-1.28l4.21-.611L7.327.668A.75.75 0 0 1 8 .25Zm0 2.445L6.615 5.5a.75.75 0 0 1-.564.41l-3.097.45 2.24 2.184a.75.75 0 0 1 .216.664l-.528 3.084 2.769-1.456a.75.75 0 0 1 .698 0l2.77 1.456-.53-3.084a.75.75 0 0 1 .216-.664l2.24-2.183-3.096-.45a.75.75 0 0 1-.564-.41L8 2.694Z"></path>
     </svg>
   ),
+  Milestone: () => (
+    <svg
+      aria-hidden="true"
+      height="16"
+      viewBox="0 0 16 16"
+      width="16"
+      fill="currentColor"
+    >
+      <path fillRule="evenodd" d="M4 1h8v1h2.25a.75.75 0 0 1 .75.75V4a3 3 0 0 1-3.1 3A4 4 0 0 1 8.75 9.93V12h2a1 1 0 0 1 1 1v2h-7.5v-2a1 1 0 0 1 1-1h2V9.93A4 4 0 0 1 4.1 7 3 3 0 0 1 1 4V2.75A.75.75 0 0 1 1.75 2H4Zm0 2.5H2.5V4A1.5 1.5 0 0 0 4 5.5Zm8 0v2A1.5 1.5 0 0 0 13.5 4v-.5Z"></path>
+    </svg>
+  ),
   Default: () => (
     <svg
       aria-hidden="true"
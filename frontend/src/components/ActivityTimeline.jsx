@@ -319,6 +319,17 @@ function ActivityTimeline({ platformFilter = 'all', username = null, isPublic =
           </div>
         );
 
+      case 'Milestone':
+        return (
+          <div className="activity-item" key={activity.id}>
+            <div className="activity-icon">{getActivityIcon(activity.activity_type)}</div>
+            <div className="activity-content">
+              <div className="activity-header">{metadata.title || 'Reached a milestone'}</div>
+              <div className="activity-date">on {formatDate(activity.date)}</div>
+            </div>
+          </div>
+        );
+
       case 'PullRequest':
       case 'Issue':
         return (
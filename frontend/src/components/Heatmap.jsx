@@ -4,6 +4,7 @@ import './Heatmap.css';
 
 function Heatmap({ platformFilter = 'all', setPlatformFilter, username = null, isPublic = false }) {
   const [contributions, setContributions] = useState([]);
+  const [annotations, setAnnotations] = useState([]); // Own heatmap only
   const [_stats, setStats] = useState(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState(null);
@@ -85,6 +86,7 @@ function Heatmap({ platformFilter = 'all', setPlatformFilter, username = null, i
       console.log(`📊 [loadData] Last 5 dates:`, contributionsData.contributions?.slice(-5).map(c => c.date));
 
       setContributions(contributionsData.contributions || []);
+      setAnnotations(contributionsData.annotations || []);
       setStats(statsData);
     } catch (err) {
       setError(err.message);
@@ -395,14 +397,18 @@ function Heatmap({ platformFilter = 'all', setPlatformFilter, username = null, i
                       const tooltipText = day.count === 0
                         ? `No contributions on ${tooltipDate}.`
                         : `${day.count} contribution${day.count === 1 ? '' : 's'} on ${tooltipDate}.`;
+                      const dayAnnotations = annotations.filter((a) => a.date === day.date);
+                      const annotationText = dayAnnotations
+                        .map((a) => (a.emoji ? `${a.emoji} ${a.text}` : a.text))
+                        .join('\n');
 
                       return (
                         <div
                           key={dayIndex}
-                          className={`heatmap-day level-${level}`}
+                          className={`heatmap-day level-${level}${dayAnnotations.length > 0 ? ' annotated' : ''}`}
                           data-count={day.count}
                           data-date={tooltipDate}
-                          title={tooltipText}
+                          title={annotationText ? `${tooltipText}\n${annotationText}` : tooltipText}
                         ></div>
                       );
                     })}
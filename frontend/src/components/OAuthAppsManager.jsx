@@ -2,15 +2,32 @@ import { useState, useEffect } from 'react';
 import { useToast } from '../context/ToastContext';
 import apiClient from '../api/client';
 import PlatformIcon from './PlatformIcon';
-import { FiEdit, FiTrash2, FiArrowLeft } from 'react-icons/fi';
+import { FiEdit, FiTrash2, FiArrowLeft, FiCheckCircle, FiArrowUp, FiArrowDown, FiStar } from 'react-icons/fi';
 import './OAuthAppsManager.css';
 
+// The connect screen's order: featured first, then the order admins set
+const byListingOrder = (a, b) =>
+  b.is_featured - a.is_featured ||
+  a.sort_order - b.sort_order ||
+  b.is_default - a.is_default ||
+  a.instance_name.localeCompare(b.instance_name);
+
+const EMPTY_LISTING = {
+  displayName: '',
+  iconUrl: '',
+  description: '',
+  loginHint: '',
+  isFeatured: false,
+};
+
 export default function OAuthAppsManager() {
   const { error: showError, success: showSuccess } = useToast();
   const [apps, setApps] = useState([]);
   const [loading, setLoading] = useState(true);
   const [showForm, setShowForm] = useState(false);
   const [editingApp, setEditingApp] = useState(null);
+  const [testReports, setTestReports] = useState({});
+  const [testingAppId, setTestingAppId] = useState(null);
 
   // Form state
   const [formData, setFormData] = useState({
@@ -20,6 +37,9 @@ export default function OAuthAppsManager() {
     clientId: '',
     clientSecret: '',
     isDefault: true,
+    scopes: '',
+    redirectUri: '',
+    ...EMPTY_LISTING,
   });
 
   useEffect(() => {
@@ -31,7 +51,7 @@ export default function OAuthAppsManager() {
     try {
       setLoading(true);
       const data = await apiClient.listOAuthApps();
-      setApps(data);
+      setApps([...data].sort(byListingOrder));
     } catch (err) {
       showError(err.message || 'Failed to load OAuth apps');
     } finally {
@@ -58,6 +78,14 @@ export default function OAuthAppsManager() {
         if (formData.clientId) updates.client_id = formData.clientId;
         if (formData.clientSecret) updates.client_secret = formData.clientSecret;
         if (formData.isDefault !== undefined) updates.is_default = formData.isDefault;
+        // Sent even when empty, which goes back to the defaults
+        updates.scopes = formData.scopes;
+        updates.redirect_uri = formData.redirectUri;
+        updates.display_name = formData.displayName;
+        updates.icon_url = formData.iconUrl;
+        updates.description = formData.description;
+        updates.login_hint = formData.loginHint;
+        updates.is_featured = formData.isFeatured;
 
         await apiClient.updateOAuthApp(editingApp.id, updates);
         showSuccess('OAuth app updated successfully!');
@@ -69,7 +97,17 @@ export default function OAuthAppsManager() {
           formData.instanceUrl,
           formData.clientId,
           formData.clientSecret,
-          formData.isDefault
+          formData.isDefault,
+          formData.scopes,
+          formData.redirectUri,
+          {
+            display_name: formData.displayName,
+            icon_url: formData.iconUrl,
+            description: formData.description,
+            login_hint: formData.loginHint,
+            is_featured: formData.isFeatured,
+            sort_order: apps.length,
+          }
         );
         showSuccess('OAuth app created successfully!');
       }
@@ -91,10 +129,41 @@ export default function OAuthAppsManager() {
       clientId: app.client_id,
       clientSecret: '', // Don't pre-fill secret
       isDefault: app.is_default,
+      scopes: app.scopes || '',
+      redirectUri: app.redirect_uri || '',
+      displayName: app.display_name || '',
+      iconUrl: app.icon_url || '',
+      description: app.description || '',
+      loginHint: app.login_hint || '',
+      isFeatured: app.is_featured,
     });
     setShowForm(true);
   };
 
+  const saveOrder = async (ordered) => {
+    try {
+      const data = await apiClient.reorderOAuthApps(
+        ordered.map((app) => ({ id: app.id, is_featured: app.is_featured }))
+      );
+      setApps(data);
+    } catch (err) {
+      showError(err.message || 'Failed to reorder OAuth apps');
+    }
+  };
+
+  const handleMove = (index, offset) => {
+    const ordered = [...apps];
+    const [app] = ordered.splice(index, 1);
+    ordered.splice(index + offset, 0, app);
+    saveOrder(ordered);
+  };
+
+  const handleToggleFeatured = (app) => {
+    saveOrder(
+      apps.map((a) => (a.id === app.id ? { ...a, is_featured: !a.is_featured } : a)).sort((a, b) => b.is_featured - a.is_featured)
+    );
+  };
+
   const handleDelete = async (appId) => {
     if (!confirm('Are you sure you want to delete this OAuth app? Users will no longer be able to use it.')) {
       return;
@@ -109,6 +178,27 @@ export default function OAuthAppsManager() {
     }
   };
 
+  const handleTest = async (app) => {
+    if (testingAppId) {
+      return;
+    }
+
+    setTestingAppId(app.id);
+    try {
+      const report = await apiClient.testOAuthApp(app.id);
+      setTestReports((reports) => ({ ...reports, [app.id]: report }));
+      if (report.ok) {
+        showSuccess(`${app.instance_name}: OAuth app works`);
+      } else {
+        showError(`${app.instance_name}: OAuth app has problems`);
+      }
+    } catch (err) {
+      showError(err.message || 'Failed to test OAuth app');
+    } finally {
+      setTestingAppId(null);
+    }
+  };
+
   const handleToggleEnabled = async (app) => {
     try {
       await apiClient.updateOAuthApp(app.id, {
@@ -131,6 +221,9 @@ export default function OAuthAppsManager() {
       clientId: '',
       clientSecret: '',
       isDefault: true,
+      scopes: '',
+      redirectUri: '',
+      ...EMPTY_LISTING,
     });
   };
 
@@ -166,16 +259,18 @@ export default function OAuthAppsManager() {
               </div>
             ) : (
               <div className="apps-list">
-                {apps.map((app) => (
+                {apps.map((app, index) => (
                   <div key={app.id} className={`app-card ${!app.is_enabled ? 'disabled' : ''}`}>
                     <div className="app-header">
                       <div className="app-title">
                         <div className="app-title-row">
                           <PlatformIcon platform={app.platform} size={24} />
-                          <h3>{app.instance_name}</h3>
+                          {app.icon_url && <img src={app.icon_url} alt="" className="app-icon" />}
+                          <h3>{app.display_name || app.instance_name}</h3>
                         </div>
                         <div className="app-badges">
                           {app.is_default && <span className="badge badge-default">Default</span>}
+                          {app.is_featured && <span className="badge badge-default">Featured</span>}
                           {!app.is_enabled && <span className="badge badge-disabled">Disabled</span>}
                           <label className="toggle-switch" title={app.is_enabled ? 'Enabled - Click to disable' : 'Disabled - Click to enable'}>
                             <input
@@ -195,6 +290,50 @@ export default function OAuthAppsManager() {
                           </span>
                         </div>
                         <div className="app-actions">
+                          <button
+                            type="button"
+                            onClick={() => handleMove(index, -1)}
+                            className="btn-icon btn-icon-secondary"
+                            title="Move up"
+                            disabled={index === 0}
+                            aria-label="Move OAuth app up"
+                          >
+                            <FiArrowUp size={18} />
+                          </button>
+
+                          <button
+                            type="button"
+                            onClick={() => handleMove(index, 1)}
+                            className="btn-icon btn-icon-secondary"
+                            title="Move down"
+                            disabled={index === apps.length - 1}
+                            aria-label="Move OAuth app down"
+                          >
+                            <FiArrowDown size={18} />
+                          </button>
+
+                          <button
+                            type="button"
+                            onClick={() => handleToggleFeatured(app)}
+                            className="btn-icon btn-icon-secondary"
+                            title={app.is_featured ? 'Unfeature' : 'Feature'}
+                            aria-label={app.is_featured ? 'Unfeature OAuth app' : 'Feature OAuth app'}
+                          >
+                            <FiStar size={18} fill={app.is_featured ? 'currentColor' : 'none'} />
+                          </button>
+
+                          <div
+                            onClick={() => handleTest(app)}
+                            className={`btn-icon btn-icon-secondary ${testingAppId === app.id ? 'testing' : ''}`}
+                            title={testingAppId === app.id ? 'Testing...' : 'Test'}
+                            role="button"
+                            tabIndex={0}
+                            onKeyDown={(e) => e.key === 'Enter' && handleTest(app)}
+                            aria-label="Test OAuth app"
+                          >
+                            <FiCheckCircle size={18} />
+                          </div>
+
                           <div
                             onClick={() => handleEdit(app)}
                             className="btn-icon btn-icon-secondary"
@@ -221,6 +360,17 @@ export default function OAuthAppsManager() {
                         </div>
                       </div>
                     </div>
+                    {testReports[app.id] && (
+                      <ul className="app-checks">
+                        {testReports[app.id].checks
+                          .filter((check) => check.status !== 'skipped')
+                          .map((check) => (
+                            <li key={check.name} className={`app-check ${check.status}`}>
+                              {check.status === 'passed' ? '✓' : check.status === 'warning' ? '!' : '✗'} {check.message}
+                            </li>
+                          ))}
+                      </ul>
+                    )}
                   </div>
                 ))}
               </div>
@@ -327,6 +477,102 @@ export default function OAuthAppsManager() {
                 </small>
               </div>
 
+              <div className="form-group">
+                <label htmlFor="scopes">Scopes</label>
+                <input
+                  type="text"
+                  id="scopes"
+                  name="scopes"
+                  value={formData.scopes}
+                  onChange={handleInputChange}
+                  placeholder={editingApp ? editingApp.default_scopes : 'Default scopes'}
+                />
+                <small className="form-hint">
+                  Leave empty to request the scopes hgitmap needs. Narrower scopes may leave out private contributions.
+                </small>
+              </div>
+
+              <div className="form-group">
+                <label htmlFor="redirectUri">Redirect URI</label>
+                <input
+                  type="url"
+                  id="redirectUri"
+                  name="redirectUri"
+                  value={formData.redirectUri}
+                  onChange={handleInputChange}
+                  placeholder={editingApp ? editingApp.default_redirect_uri : 'Default redirect URI'}
+                />
+                <small className="form-hint">
+                  Leave empty to use the callback under this server's URL. Set it when a proxy rewrites paths; it must match the app's callback URL on the platform.
+                </small>
+              </div>
+
+              <div className="form-group">
+                <label htmlFor="displayName">Display Name</label>
+                <input
+                  type="text"
+                  id="displayName"
+                  name="displayName"
+                  value={formData.displayName}
+                  onChange={handleInputChange}
+                  placeholder={formData.instanceName || 'Instance name'}
+                />
+                <small className="form-hint">
+                  Shown in the connect screen. Leave empty to show the instance name.
+                </small>
+              </div>
+
+              <div className="form-group">
+                <label htmlFor="iconUrl">Icon URL</label>
+                <input
+                  type="url"
+                  id="iconUrl"
+                  name="iconUrl"
+                  value={formData.iconUrl}
+                  onChange={handleInputChange}
+                  placeholder="https://git.example.com/favicon.png"
+                />
+              </div>
+
+              <div className="form-group">
+                <label htmlFor="description">Description</label>
+                <textarea
+                  id="description"
+                  name="description"
+                  value={formData.description}
+                  onChange={handleInputChange}
+                  rows={2}
+                  maxLength={1000}
+                />
+              </div>
+
+              <div className="form-group">
+                <label htmlFor="loginHint">Login Hint</label>
+                <input
+                  type="text"
+                  id="loginHint"
+                  name="loginHint"
+                  value={formData.loginHint}
+                  onChange={handleInputChange}
+                  placeholder="Sign in with your university account"
+                />
+              </div>
+
+              <div className="form-group checkbox-group">
+                <label>
+                  <input
+                    type="checkbox"
+                    name="isFeatured"
+                    checked={formData.isFeatured}
+                    onChange={handleInputChange}
+                  />
+                  Feature in the connect screen
+                </label>
+                <small className="form-hint">
+                  Featured apps are listed first
+                </small>
+              </div>
+
               <div className="form-group checkbox-group">
                 <label>
                   <input
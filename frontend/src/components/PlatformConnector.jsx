@@ -3,8 +3,26 @@ import apiClient from '../api/client';
 import PlatformIcon from './PlatformIcon';
 import './PlatformConnector.css';
 
+// What an admin wrote about the selected instance: its icon, description
+// and which account to sign in with
+function InstanceHint({ instance }) {
+  if (!instance || (!instance.icon_url && !instance.description && !instance.login_hint)) {
+    return null;
+  }
+  return (
+    <div className="instance-hint">
+      {instance.icon_url && <img src={instance.icon_url} alt="" className="instance-icon" />}
+      <div>
+        {instance.description && <p className="form-hint">{instance.description}</p>}
+        {instance.login_hint && <p className="form-hint">{instance.login_hint}</p>}
+      </div>
+    </div>
+  );
+}
+
 function PlatformConnector() {
   const [platforms, setPlatforms] = useState([]);
+  const [disconnectedPlatforms, setDisconnectedPlatforms] = useState([]);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState(null);
   const [showPATForm, setShowPATForm] = useState(false);
@@ -13,9 +31,12 @@ function PlatformConnector() {
   const [instanceUrl, setInstanceUrl] = useState(''); // For Gitea/GitLab custom instances
   const [patError, setPatError] = useState(null);
   const [patLoading, setPatLoading] = useState(false);
+  const [instanceReport, setInstanceReport] = useState(null);
+  const [checkingInstance, setCheckingInstance] = useState(false);
   const [syncingPlatformId, setSyncingPlatformId] = useState(null);
   const [selectedSyncYear, setSelectedSyncYear] = useState({});
   const [syncingProfileId, setSyncingProfileId] = useState(null);
+  const [capabilities, setCapabilities] = useState({});
   const [showGiteaOAuthForm, setShowGiteaOAuthForm] = useState(false);
   const [giteaOAuthInstanceUrl, setGiteaOAuthInstanceUrl] = useState('');
   const [giteaOAuthError, setGiteaOAuthError] = useState(null);
@@ -78,6 +99,8 @@ function PlatformConnector() {
       const data = await apiClient.listPlatforms();
       setPlatforms(data);
       setError(null);
+      loadCapabilities(data);
+      setDisconnectedPlatforms(await apiClient.listDisconnectedPlatforms());
     } catch (err) {
       setError(err.message);
     } finally {
@@ -85,6 +108,18 @@ function PlatformConnector() {
     }
   };
 
+  // Fetched per account so one unreachable instance does not hold up the others
+  const loadCapabilities = (accounts) => {
+    accounts.forEach(async (account) => {
+      try {
+        const caps = await apiClient.getPlatformCapabilities(account.id);
+        setCapabilities(prev => ({ ...prev, [account.id]: caps }));
+      } catch (err) {
+        console.log(`Capabilities unavailable for ${account.platform_username}`);
+      }
+    });
+  };
+
   const handleConnectOAuth = async () => {
     console.log('🔐 [OAuth] Starting OAuth flow');
 
@@ -204,6 +239,23 @@ function PlatformConnector() {
     }
   };
 
+  const handleCheckInstance = async () => {
+    if (!instanceUrl.trim()) {
+      setPatError(`Please enter your ${selectedPlatform === 'gitea' ? 'Gitea' : 'GitLab'} instance URL`);
+      return;
+    }
+
+    try {
+      setCheckingInstance(true);
+      setPatError(null);
+      setInstanceReport(await apiClient.validateInstance(selectedPlatform, instanceUrl, patToken || null));
+    } catch (err) {
+      setPatError(err.message);
+    } finally {
+      setCheckingInstance(false);
+    }
+  };
+
   const handleConnectPAT = async (e) => {
     e.preventDefault();
 
@@ -228,17 +280,49 @@ function PlatformConnector() {
       );
       setPatToken('');
       setInstanceUrl('');
+      setInstanceReport(null);
       setShowPATForm(false);
       await loadPlatforms();
     } catch (err) {
       setPatError(err.message);
+      // A rejected token is often a wrong URL or an unreachable instance
+      if (selectedPlatform === 'gitea' || selectedPlatform === 'gitlab') {
+        try {
+          const report = await apiClient.validateInstance(selectedPlatform, instanceUrl, patToken);
+          setInstanceReport(report.ok ? null : report);
+        } catch (checkErr) {
+          console.log('Instance check failed:', checkErr);
+        }
+      }
     } finally {
       setPatLoading(false);
     }
   };
 
   const handleDisconnect = async (platformId) => {
-    if (!confirm('Are you sure you want to disconnect this platform?')) {
+    if (!confirm('Are you sure you want to disconnect this platform? Its data is hidden and can be restored until it is deleted.')) {
+      return;
+    }
+
+    try {
+      await apiClient.disconnectPlatform(platformId);
+      await loadPlatforms();
+    } catch (err) {
+      setError(err.message);
+    }
+  };
+
+  const handleRestore = async (platformId) => {
+    try {
+      await apiClient.restorePlatform(platformId);
+      await loadPlatforms();
+    } catch (err) {
+      setError(err.message);
+    }
+  };
+
+  const handleDeleteNow = async (platformId) => {
+    if (!confirm('Delete this account and all its contributions and activities now? This cannot be undone.')) {
       return;
     }
 
@@ -375,11 +459,11 @@ function PlatformConnector() {
                       disabled={syncingPlatformId === platform.id}
                     >
                       <option value="current">Current year ({new Date().getFullYear()})</option>
-                      {Array.from({ length: new Date().getFullYear() - 2019 }, (_, i) => {
+                      {Array.from({ length: Math.max(new Date().getFullYear() - platform.earliest_sync_year, 0) }, (_, i) => {
                         const year = new Date().getFullYear() - 1 - i;
                         return <option key={year} value={year}>{year}</option>;
                       })}
-                      <option value="all">All years (2020-{new Date().getFullYear()})</option>
+                      <option value="all">All years ({platform.earliest_sync_year}-{new Date().getFullYear()})</option>
                     </select>
                     <button
                       className="btn btn-primary btn-sm"
@@ -390,6 +474,16 @@ function PlatformConnector() {
                       {syncingPlatformId === platform.id ? 'Syncing...' : 'Sync'}
                     </button>
                   </div>
+                  {capabilities[platform.id] && !capabilities[platform.id].contributions && (
+                    <span className="capability-note">
+                      This instance does not share contribution counts, so only activities are synced.
+                    </span>
+                  )}
+                  {capabilities[platform.id]?.contributions && !capabilities[platform.id].contribution_calendar && (
+                    <span className="capability-note">
+                      Counts come from recent events and may differ from the graph on the platform.
+                    </span>
+                  )}
                 </div>
               </div>
             </div>
@@ -397,6 +491,36 @@ function PlatformConnector() {
         </div>
       )}
 
+      {/* Recently disconnected accounts, restorable until purged */}
+      {disconnectedPlatforms.length > 0 && (
+        <div className="disconnected-platforms">
+          <h3>Recently Disconnected</h3>
+          {disconnectedPlatforms.map((platform) => (
+            <div key={platform.id} className="disconnected-platform">
+              <div className="platform-identity">
+                <div className="platform-icon-wrapper">
+                  <PlatformIcon platform={platform.platform} size={20} />
+                </div>
+                <div className="platform-info-text">
+                  <h3>{platform.platform_username}</h3>
+                  <span className="capability-note">
+                    Deleted after {new Date(platform.purge_after).toLocaleDateString()}. Reconnect after restoring to resume syncing.
+                  </span>
+                </div>
+              </div>
+              <div className="disconnected-actions">
+                <button className="btn btn-primary btn-sm" onClick={() => handleRestore(platform.id)}>
+                  Restore
+                </button>
+                <button className="btn btn-danger btn-sm" onClick={() => handleDeleteNow(platform.id)}>
+                  Delete now
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+
       {/* Connect New Platform */}
       <div className="connect-platform-section">
         <h3>Connect Platform</h3>
@@ -498,6 +622,10 @@ function PlatformConnector() {
               <p className="form-hint">Loading available GitLab instances...</p>
             ) : gitlabInstances.length === 0 ? (
               <>
+                <InstanceHint
+                  instance={gitlabInstances.find((i) => i.instance_url === gitlabOAuthInstanceUrl)}
+                />
+
                 {gitlabOAuthError && <div className="error-message">{gitlabOAuthError}</div>}
                 <p className="form-hint">
                   No GitLab OAuth applications are configured. Please ask your administrator to add a GitLab OAuth app in the admin panel at <code>/admin/oauth-apps</code>.
@@ -507,10 +635,14 @@ function PlatformConnector() {
               <>
                 <p className="form-hint">
                   {gitlabInstances.length === 1
-                    ? `Connecting to: ${gitlabInstances[0].instance_name}`
+                    ? `Connecting to: ${gitlabInstances[0].display_name}`
                     : 'Select which GitLab instance you want to connect to:'}
                 </p>
 
+                <InstanceHint
+                  instance={gitlabInstances.find((i) => i.instance_url === gitlabOAuthInstanceUrl)}
+                />
+
                 {gitlabOAuthError && <div className="error-message">{gitlabOAuthError}</div>}
 
                 {gitlabInstances.length > 1 && (
@@ -524,7 +656,7 @@ function PlatformConnector() {
                     >
                       {gitlabInstances.map((instance) => (
                         <option key={instance.instance_url} value={instance.instance_url}>
-                          {instance.instance_name} ({instance.instance_url || 'gitlab.com'})
+                          {instance.display_name} ({instance.instance_url || 'gitlab.com'})
                           {instance.is_default ? ' [Default]' : ''}
                         </option>
                       ))}
@@ -568,6 +700,10 @@ function PlatformConnector() {
               <p className="form-hint">Loading available Gitea instances...</p>
             ) : giteaInstances.length === 0 ? (
               <>
+                <InstanceHint
+                  instance={giteaInstances.find((i) => i.instance_url === giteaOAuthInstanceUrl)}
+                />
+
                 {giteaOAuthError && <div className="error-message">{giteaOAuthError}</div>}
                 <p className="form-hint">
                   No Gitea OAuth applications are configured. Please ask your administrator to add a Gitea OAuth app in the admin panel at <code>/admin/oauth-apps</code>.
@@ -577,10 +713,14 @@ function PlatformConnector() {
               <>
                 <p className="form-hint">
                   {giteaInstances.length === 1
-                    ? `Connecting to: ${giteaInstances[0].instance_name}`
+                    ? `Connecting to: ${giteaInstances[0].display_name}`
                     : 'Select which Gitea instance you want to connect to:'}
                 </p>
 
+                <InstanceHint
+                  instance={giteaInstances.find((i) => i.instance_url === giteaOAuthInstanceUrl)}
+                />
+
                 {giteaOAuthError && <div className="error-message">{giteaOAuthError}</div>}
 
                 {giteaInstances.length > 1 && (
@@ -594,7 +734,7 @@ function PlatformConnector() {
                     >
                       {giteaInstances.map((instance) => (
                         <option key={instance.instance_url} value={instance.instance_url}>
-                          {instance.instance_name} ({instance.instance_url})
+                          {instance.display_name} ({instance.instance_url})
                           {instance.is_default ? ' [Default]' : ''}
                         </option>
                       ))}
@@ -641,7 +781,10 @@ function PlatformConnector() {
                   id="instance-url"
                   type="url"
                   value={instanceUrl}
-                  onChange={(e) => setInstanceUrl(e.target.value)}
+                  onChange={(e) => {
+                    setInstanceUrl(e.target.value);
+                    setInstanceReport(null);
+                  }}
                   placeholder={selectedPlatform === 'gitea' ? "https://gitea.example.com" : "https://gitlab.com"}
                   className="instance-url"
                   disabled={patLoading}
@@ -652,6 +795,25 @@ function PlatformConnector() {
                     ? "Enter the full URL of your Gitea instance"
                     : "Enter the full URL of your GitLab instance (e.g., https://gitlab.com for GitLab.com)"}
                 </p>
+                <button
+                  type="button"
+                  className="btn btn-secondary btn-sm"
+                  onClick={handleCheckInstance}
+                  disabled={patLoading || checkingInstance}
+                >
+                  {checkingInstance ? 'Checking...' : 'Check instance'}
+                </button>
+                {instanceReport && (
+                  <ul className="instance-checks">
+                    {instanceReport.checks
+                      .filter((check) => check.status !== 'skipped')
+                      .map((check) => (
+                        <li key={check.name} className={`instance-check ${check.status}`}>
+                          {check.status === 'passed' ? '✓' : check.status === 'warning' ? '!' : '✗'} {check.message}
+                        </li>
+                      ))}
+                  </ul>
+                )}
               </div>
             )}
 
@@ -714,6 +876,7 @@ function PlatformConnector() {
                   setShowPATForm(false);
                   setPatToken('');
                   setInstanceUrl('');
+                  setInstanceReport(null);
                   setPatError(null);
                   setSelectedPlatform('github');
                 }}
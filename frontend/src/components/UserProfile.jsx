@@ -5,11 +5,18 @@ import apiClient from '../api/client';
 import PlatformIcon from './PlatformIcon';
 import './UserProfile.css';
 
+// Languages drawn in the chart before the rest are combined as "Other"
+const LANGUAGE_CHART_SIZE = 6;
+const LANGUAGE_COLORS = ['#0969da', '#1a7f37', '#bf8700', '#cf222e', '#8250df', '#1b7c83', '#6e7781'];
+
 const UserProfile = ({ username = null, isPublic = false }) => {
-  const { user: _user } = useAuth();
+  const { user } = useAuth();
   const { theme: _theme } = useTheme();
   const [stats, setStats] = useState(null);
   const [platforms, setPlatforms] = useState([]);
+  const [organizations, setOrganizations] = useState([]);
+  const [achievements, setAchievements] = useState([]);
+  const [languages, setLanguages] = useState([]);
   const [selectedPlatform, setSelectedPlatform] = useState(0);
 
   useEffect(() => {
@@ -29,6 +36,31 @@ const UserProfile = ({ username = null, isPublic = false }) => {
       } catch (error) {
         console.error('Failed to fetch user data:', error);
       }
+
+      // Organizations are optional, so a failure leaves the rest of the profile
+      try {
+        const organizationsData = isPublic
+          ? await apiClient.getUserOrganizations(username)
+          : user?.username
+            ? await apiClient.getOrganizations(user.username)
+            : [];
+        setOrganizations(organizationsData);
+      } catch (error) {
+        console.error('Failed to fetch organizations:', error);
+      }
+
+      const profileName = isPublic ? username : user?.username;
+      try {
+        setAchievements(profileName ? await apiClient.getUserAchievements(profileName) : []);
+      } catch (error) {
+        console.error('Failed to fetch achievements:', error);
+      }
+
+      try {
+        setLanguages(profileName ? await apiClient.getUserLanguages(profileName) : []);
+      } catch (error) {
+        console.error('Failed to fetch languages:', error);
+      }
     };
 
     fetchData();
@@ -46,13 +78,26 @@ const UserProfile = ({ username = null, isPublic = false }) => {
         window.removeEventListener('platformSynced', handlePlatformSynced);
       };
     }
-  }, [username, isPublic]);
+  }, [username, isPublic, user?.username]);
 
   // Generate fallback avatar URL (using UI Avatars service)
   const getFallbackAvatarUrl = (username) => {
     return `https://ui-avatars.com/api/?name=${encodeURIComponent(username)}&size=260&background=random&bold=true`;
   };
 
+  // The largest languages, with the rest combined
+  const languageChart = (() => {
+    const shown = languages.slice(0, LANGUAGE_CHART_SIZE);
+    const rest = languages.slice(LANGUAGE_CHART_SIZE);
+    if (rest.length > 0) {
+      shown.push({
+        language: 'Other',
+        percentage: Math.round(rest.reduce((sum, l) => sum + l.percentage, 0) * 10) / 10,
+      });
+    }
+    return shown.map((l, i) => ({ ...l, color: LANGUAGE_COLORS[i % LANGUAGE_COLORS.length] }));
+  })();
+
   // Get platform name
   const getPlatformName = (platformType) => {
     switch (platformType) {
@@ -91,6 +136,72 @@ const UserProfile = ({ username = null, isPublic = false }) => {
             <div className="stat-label">Platforms</div>
             <div className="stat-value">{stats.active_platforms || 0} connected</div>
           </div>
+
+          {organizations.length > 0 && (
+            <div className="stat-card stat-card-wide">
+              <div className="stat-label">Organizations</div>
+              <div className="org-logos">
+                {organizations.map((org) => (
+                  <a
+                    key={`${org.account_id}-${org.login}`}
+                    href={org.url}
+                    target="_blank"
+                    rel="noopener noreferrer"
+                    className="org-logo"
+                    title={org.joined_at ? `${org.login} (since ${org.joined_at})` : org.login}
+                  >
+                    <img
+                      src={org.avatar_url || getFallbackAvatarUrl(org.login)}
+                      alt={org.login}
+                      onError={(e) => {
+                        e.target.src = getFallbackAvatarUrl(org.login);
+                      }}
+                    />
+                  </a>
+                ))}
+              </div>
+            </div>
+          )}
+
+          {languageChart.length > 0 && (
+            <div className="stat-card stat-card-wide">
+              <div className="stat-label">Languages</div>
+              <div className="language-bar">
+                {languageChart.map((l) => (
+                  <span
+                    key={l.language}
+                    style={{ width: `${l.percentage}%`, background: l.color }}
+                    title={`${l.language} ${l.percentage}%`}
+                  />
+                ))}
+              </div>
+              <div className="language-legend">
+                {languageChart.map((l) => (
+                  <span key={l.language}>
+                    <span className="language-dot" style={{ background: l.color }} />
+                    {l.language} {l.percentage}%
+                  </span>
+                ))}
+              </div>
+            </div>
+          )}
+
+          {achievements.length > 0 && (
+            <div className="stat-card stat-card-wide">
+              <div className="stat-label">Achievements</div>
+              <div className="achievement-badges">
+                {achievements.map((achievement) => (
+                  <span
+                    key={achievement.key}
+                    className="achievement-badge"
+                    title={`${achievement.description} (since ${achievement.earned_on})`}
+                  >
+                    {achievement.emoji} {achievement.title}
+                  </span>
+                ))}
+              </div>
+            </div>
+          )}
         </div>
       )}
 
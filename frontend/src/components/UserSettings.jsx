@@ -4,6 +4,30 @@ import apiClient from '../api/client';
 import ThemeToggle from './ThemeToggle';
 import './UserSettings.css';
 
+const CHANNEL_KINDS = {
+  mastodon: 'Mastodon',
+  bluesky: 'Bluesky',
+  email: 'Email',
+  webhook: 'Webhook',
+  slack: 'Slack',
+  discord: 'Discord',
+  mqtt: 'MQTT',
+};
+
+const CHANNEL_EVENTS = {
+  weekly_summary: 'Weekly summary',
+  milestone: 'Milestones',
+  sync_failed: 'Failed syncs',
+};
+
+const CHANNEL_URL_PLACEHOLDERS = {
+  mastodon: 'https://mastodon.social',
+  bluesky: 'https://bsky.social (optional)',
+  webhook: 'https://example.com/hooks/hgitmap',
+  slack: 'https://hooks.slack.com/services/...',
+  discord: 'https://discord.com/api/webhooks/...',
+};
+
 export default function UserSettings() {
   const { error: showError, success: showSuccess } = useToast();
   const [settings, setSettings] = useState({
@@ -13,20 +37,60 @@ export default function UserSettings() {
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [showRequirementWarning, setShowRequirementWarning] = useState(false);
+  const [commitEmails, setCommitEmails] = useState([]);
+  const [newCommitEmail, setNewCommitEmail] = useState('');
+  const [mqtt, setMqtt] = useState(null);
+  const [channels, setChannels] = useState([]);
+  const [channelForm, setChannelForm] = useState({
+    kind: 'mastodon',
+    instance_url: '',
+    account: '',
+    token: '',
+  });
+  const [signingSecret, setSigningSecret] = useState(null);
+  const [mqttForm, setMqttForm] = useState({
+    broker_url: '',
+    topic: '',
+    username: '',
+    password: '',
+    home_assistant_discovery: false,
+  });
 
   useEffect(() => {
     loadSettings();
+
+    // Result of a commit email confirmation link
+    const params = new URLSearchParams(window.location.search);
+    const confirmation = params.get('commit_email');
+    if (confirmation === 'confirmed') {
+      showSuccess('Commit email verified');
+    } else if (confirmation === 'expired') {
+      showError('The confirmation link expired, send a new one');
+    } else if (confirmation === 'invalid') {
+      showError('The confirmation link is invalid');
+    }
+    if (confirmation) {
+      window.history.replaceState({}, '', window.location.pathname);
+    }
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, []);
 
   const loadSettings = async () => {
     try {
       setLoading(true);
-      const data = await apiClient.getSettings();
+      const [data, emails, mqttSettings, notificationChannels] = await Promise.all([
+        apiClient.getSettings(),
+        apiClient.getCommitEmails(),
+        apiClient.getMqttSettings(),
+        apiClient.getNotificationChannels(),
+      ]);
       setSettings({
         show_private_contributions: data.show_private_contributions,
         hide_private_repo_names: data.hide_private_repo_names,
       });
+      setCommitEmails(emails);
+      applyMqtt(mqttSettings);
+      setChannels(notificationChannels);
     } catch (err) {
       showError(err.message || 'Failed to load settings');
     } finally {
@@ -71,6 +135,144 @@ export default function UserSettings() {
     }));
   };
 
+  const handleAddCommitEmail = async (e) => {
+    e.preventDefault();
+
+    try {
+      const added = await apiClient.addCommitEmail(newCommitEmail);
+      setCommitEmails((prev) =>
+        [...prev, added].sort((a, b) => a.email.localeCompare(b.email))
+      );
+      setNewCommitEmail('');
+      showSuccess(`A confirmation link was sent to ${added.email}`);
+    } catch (err) {
+      showError(err.message || 'Failed to add email');
+      loadCommitEmails();
+    }
+  };
+
+  const loadCommitEmails = async () => {
+    try {
+      setCommitEmails(await apiClient.getCommitEmails());
+    } catch (err) {
+      showError(err.message || 'Failed to load commit emails');
+    }
+  };
+
+  const handleResendVerification = async (email) => {
+    try {
+      await apiClient.resendCommitEmailVerification(email);
+      showSuccess(`A confirmation link was sent to ${email}`);
+    } catch (err) {
+      showError(err.message || 'Failed to send confirmation link');
+    }
+  };
+
+  const handleDeleteCommitEmail = async (email) => {
+    try {
+      await apiClient.deleteCommitEmail(email);
+      setCommitEmails((prev) => prev.filter((e) => e.email !== email));
+    } catch (err) {
+      showError(err.message || 'Failed to remove email');
+    }
+  };
+
+  const applyMqtt = (mqttSettings) => {
+    setMqtt(mqttSettings);
+    setMqttForm({
+      broker_url: mqttSettings.broker_url || '',
+      topic: mqttSettings.topic || '',
+      username: mqttSettings.username || '',
+      password: '',
+      home_assistant_discovery: mqttSettings.home_assistant_discovery,
+    });
+  };
+
+  const handleSaveMqtt = async (e) => {
+    e.preventDefault();
+
+    // An empty password field keeps the stored one
+    const { password, ...rest } = mqttForm;
+    try {
+      applyMqtt(
+        await apiClient.updateMqttSettings(password ? { ...rest, password } : rest)
+      );
+      showSuccess('MQTT settings saved');
+    } catch (err) {
+      showError(err.message || 'Failed to save MQTT settings');
+    }
+  };
+
+  const handleTestMqtt = async () => {
+    try {
+      applyMqtt(await apiClient.testMqtt());
+      showSuccess('Published to your broker');
+    } catch (err) {
+      showError(err.message || 'Failed to publish');
+    }
+  };
+
+  const handleDeleteMqtt = async () => {
+    try {
+      await apiClient.deleteMqttSettings();
+      applyMqtt(await apiClient.getMqttSettings());
+    } catch (err) {
+      showError(err.message || 'Failed to turn off MQTT publishing');
+    }
+  };
+
+  const handleAddChannel = async (e) => {
+    e.preventDefault();
+
+    const { kind, instance_url, account, token } = channelForm;
+    const social = kind === 'mastodon' || kind === 'bluesky';
+    try {
+      const added = await apiClient.createNotificationChannel({
+        kind,
+        instance_url: instance_url || null,
+        account: kind === 'bluesky' ? account : null,
+        token: social ? token : null,
+      });
+      setChannels((prev) => [...prev, added]);
+      setChannelForm((prev) => ({ ...prev, instance_url: '', account: '', token: '' }));
+      setSigningSecret(added.signing_secret || null);
+      showSuccess(`Connected ${added.account || added.kind}`);
+    } catch (err) {
+      showError(err.message || 'Failed to connect channel');
+    }
+  };
+
+  const handleToggleChannelEvent = async (channel, event) => {
+    const events = channel.events.includes(event)
+      ? channel.events.filter((e) => e !== event)
+      : [...channel.events, event];
+    try {
+      const updated = await apiClient.updateNotificationChannel(channel.id, events);
+      setChannels((prev) => prev.map((c) => (c.id === updated.id ? updated : c)));
+    } catch (err) {
+      showError(err.message || 'Failed to update channel');
+    }
+  };
+
+  const handleTestChannel = async (id) => {
+    try {
+      const updated = await apiClient.testNotificationChannel(id);
+      setChannels((prev) => prev.map((c) => (c.id === updated.id ? updated : c)));
+      showSuccess('Test notification sent');
+    } catch (err) {
+      showError(err.message || 'Failed to send test notification');
+    }
+  };
+
+  const handleDeleteChannel = async (id) => {
+    try {
+      await apiClient.deleteNotificationChannel(id);
+      setChannels((prev) => prev.filter((c) => c.id !== id));
+    } catch (err) {
+      showError(err.message || 'Failed to disconnect channel');
+    }
+  };
+
   const handleDisabledToggleClick = () => {
     // Show warning when user tries to click the disabled toggle
     if (!settings.show_private_contributions) {
@@ -174,6 +376,226 @@ export default function UserSettings() {
           </button>
         </div>
       </form>
+
+      {/* Commit Emails */}
+      <div className="settings-section">
+        <h2>Commit Emails</h2>
+        <p className="setting-description">
+          Once verified, commits made with these emails, or with a Co-authored-by
+          trailer naming one of them, count as yours on accounts with detailed sync
+        </p>
+        <ul className="commit-email-list">
+          {commitEmails.map(({ email, verified }) => (
+            <li key={email}>
+              <span>
+                {email}
+                {!verified && <span className="commit-email-pending"> (unverified)</span>}
+              </span>
+              {!verified && (
+                <button
+                  type="button"
+                  className="btn btn-secondary"
+                  onClick={() => handleResendVerification(email)}
+                >
+                  Resend link
+                </button>
+              )}
+              <button
+                type="button"
+                className="btn btn-secondary"
+                onClick={() => handleDeleteCommitEmail(email)}
+              >
+                Remove
+              </button>
+            </li>
+          ))}
+        </ul>
+        <form className="commit-email-form" onSubmit={handleAddCommitEmail}>
+          <input
+            type="email"
+            placeholder="you@example.com"
+            value={newCommitEmail}
+            onChange={(e) => setNewCommitEmail(e.target.value)}
+            required
+          />
+          <button type="submit" className="btn btn-primary">
+            Add
+          </button>
+        </form>
+      </div>
+
+      {/* MQTT */}
+      {mqtt?.available && (
+        <div className="settings-section">
+          <h2>Home Display (MQTT)</h2>
+          <p className="setting-description">
+            After each sync, today's contributions, your streak and the last year of
+            daily counts are published to your MQTT broker, for LED matrices and
+            smart-home displays
+          </p>
+          <form className="mqtt-form" onSubmit={handleSaveMqtt}>
+            <input
+              type="text"
+              placeholder="mqtt://homeassistant.local:1883"
+              value={mqttForm.broker_url}
+              onChange={(e) => setMqttForm((prev) => ({ ...prev, broker_url: e.target.value }))}
+              required
+            />
+            <input
+              type="text"
+              placeholder="hgitmap/you"
+              value={mqttForm.topic}
+              onChange={(e) => setMqttForm((prev) => ({ ...prev, topic: e.target.value }))}
+              required
+            />
+            <input
+              type="text"
+              placeholder="Username (optional)"
+              value={mqttForm.username}
+              onChange={(e) => setMqttForm((prev) => ({ ...prev, username: e.target.value }))}
+            />
+            <input
+              type="password"
+              placeholder={mqtt.has_password ? 'Password (unchanged)' : 'Password (optional)'}
+              value={mqttForm.password}
+              onChange={(e) => setMqttForm((prev) => ({ ...prev, password: e.target.value }))}
+            />
+            <label className="mqtt-checkbox">
+              <input
+                type="checkbox"
+                checked={mqttForm.home_assistant_discovery}
+                onChange={(e) =>
+                  setMqttForm((prev) => ({ ...prev, home_assistant_discovery: e.target.checked }))
+                }
+              />
+              Publish Home Assistant discovery configs
+            </label>
+            {mqtt.configured && (
+              <p className="setting-description">
+                {mqtt.last_error
+                  ? `Last publish failed: ${mqtt.last_error}`
+                  : mqtt.last_published_at
+                    ? `Last published ${new Date(mqtt.last_published_at).toLocaleString()}`
+                    : 'Not published yet'}
+              </p>
+            )}
+            <div className="mqtt-actions">
+              <button type="submit" className="btn btn-primary">
+                Save
+              </button>
+              {mqtt.configured && (
+                <>
+                  <button type="button" className="btn btn-secondary" onClick={handleTestMqtt}>
+                    Publish now
+                  </button>
+                  <button type="button" className="btn btn-secondary" onClick={handleDeleteMqtt}>
+                    Turn off
+                  </button>
+                </>
+              )}
+            </div>
+          </form>
+        </div>
+      )}
+
+      {/* Notification channels */}
+      <div className="settings-section">
+        <h2>Notifications</h2>
+        <p className="setting-description">
+          Where you hear about your weekly summary (with your heatmap), milestones and failed syncs
+        </p>
+        <ul className="commit-email-list">
+          {channels.map((channel) => (
+            <li key={channel.id}>
+              <span>
+                {CHANNEL_KINDS[channel.kind]} {channel.account}
+                <span className="commit-email-pending">
+                  {channel.last_error
+                    ? ` (last send failed: ${channel.last_error})`
+                    : channel.next_post_at
+                      ? ` (next summary ${new Date(channel.next_post_at).toLocaleDateString()})`
+                      : ''}
+                </span>
+              </span>
+              <span className="mqtt-checkbox">
+                {Object.entries(CHANNEL_EVENTS).map(([event, label]) => (
+                  <label key={event}>
+                    <input
+                      type="checkbox"
+                      checked={channel.events.includes(event)}
+                      onChange={() => handleToggleChannelEvent(channel, event)}
+                    />
+                    {label}
+                  </label>
+                ))}
+              </span>
+              <button
+                type="button"
+                className="btn btn-secondary"
+                onClick={() => handleTestChannel(channel.id)}
+              >
+                Test
+              </button>
+              <button
+                type="button"
+                className="btn btn-secondary"
+                onClick={() => handleDeleteChannel(channel.id)}
+              >
+                Disconnect
+              </button>
+            </li>
+          ))}
+        </ul>
+        {signingSecret && (
+          <p className="setting-description">
+            Webhook signing secret, shown only once: <code>{signingSecret}</code>
+          </p>
+        )}
+        <form className="mqtt-form" onSubmit={handleAddChannel}>
+          <select
+            value={channelForm.kind}
+            onChange={(e) => setChannelForm((prev) => ({ ...prev, kind: e.target.value }))}
+          >
+            {Object.entries(CHANNEL_KINDS).map(([kind, label]) => (
+              <option key={kind} value={kind}>
+                {label}
+              </option>
+            ))}
+          </select>
+          {CHANNEL_URL_PLACEHOLDERS[channelForm.kind] && (
+            <input
+              type="text"
+              placeholder={CHANNEL_URL_PLACEHOLDERS[channelForm.kind]}
+              value={channelForm.instance_url}
+              onChange={(e) => setChannelForm((prev) => ({ ...prev, instance_url: e.target.value }))}
+              required={channelForm.kind !== 'bluesky'}
+            />
+          )}
+          {channelForm.kind === 'bluesky' && (
+            <input
+              type="text"
+              placeholder="you.bsky.social"
+              value={channelForm.account}
+              onChange={(e) => setChannelForm((prev) => ({ ...prev, account: e.target.value }))}
+              required
+            />
+          )}
+          {(channelForm.kind === 'mastodon' || channelForm.kind === 'bluesky') && (
+            <input
+              type="password"
+              placeholder={channelForm.kind === 'mastodon' ? 'Access token' : 'App password'}
+              value={channelForm.token}
+              onChange={(e) => setChannelForm((prev) => ({ ...prev, token: e.target.value }))}
+              required
+            />
+          )}
+          <div className="mqtt-actions">
+            <button type="submit" className="btn btn-primary">
+              Connect
+            </button>
+          </div>
+        </form>
+      </div>
     </div>
   );
 }
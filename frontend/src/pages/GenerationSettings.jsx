@@ -16,6 +16,11 @@ function GenerationSettings() {
     auto_generation_enabled: true,
     date_range_days: 365,
     include_private_contributions: true,
+    schedule_mode: 'interval',
+    timezone: Intl.DateTimeFormat().resolvedOptions().timeZone || 'UTC',
+    window_start: '02:00',
+    window_end: '05:00',
+    generation_cron: '',
   });
 
   useEffect(() => {
@@ -32,6 +37,11 @@ function GenerationSettings() {
         auto_generation_enabled: data.auto_generation_enabled,
         date_range_days: data.date_range_days,
         include_private_contributions: data.include_private_contributions,
+        schedule_mode: data.generation_cron ? 'cron' : data.generation_window ? 'window' : 'interval',
+        timezone: data.timezone,
+        window_start: data.generation_window ? data.generation_window.split('-')[0] : '02:00',
+        window_end: data.generation_window ? data.generation_window.split('-')[1] : '05:00',
+        generation_cron: data.generation_cron || '',
       });
       setError(null);
     } catch (err) {
@@ -61,7 +71,12 @@ function GenerationSettings() {
     try {
       setSaving(true);
       setError(null);
-      await apiClient.updateGenerationSettings(settings);
+      const { schedule_mode, window_start, window_end, ...rest } = settings;
+      await apiClient.updateGenerationSettings({
+        ...rest,
+        generation_window: schedule_mode === 'window' ? `${window_start}-${window_end}` : '',
+        generation_cron: schedule_mode === 'cron' ? settings.generation_cron : '',
+      });
       setSuccess(true);
       setTimeout(() => setSuccess(false), 3000);
     } catch (err) {
@@ -81,6 +96,15 @@ function GenerationSettings() {
     }
   };
 
+  const handleRetryJob = async (jobId) => {
+    try {
+      await apiClient.retryGenerationJob(jobId);
+      await loadJobs();
+    } catch (err) {
+      alert(`Failed to queue the job again: ${err.message}`);
+    }
+  };
+
   const getStatusBadgeClass = (status) => {
     switch (status) {
       case 'completed':
@@ -88,6 +112,7 @@ function GenerationSettings() {
       case 'processing':
         return 'status-processing';
       case 'failed':
+      case 'dead_letter':
         return 'status-failed';
       default:
         return 'status-pending';
@@ -158,6 +183,66 @@ function GenerationSettings() {
                 </p>
               </div>
             )}
+
+            {settings.auto_generation_enabled && (
+              <div className="form-group">
+                <label>Schedule</label>
+                <select
+                  value={settings.schedule_mode}
+                  onChange={(e) => handleChange('schedule_mode', e.target.value)}
+                >
+                  <option value="interval">Any time of day</option>
+                  <option value="window">Within a daily window</option>
+                  <option value="cron">Cron expression</option>
+                </select>
+
+                {settings.schedule_mode === 'window' && (
+                  <div className="schedule-window">
+                    <input
+                      type="time"
+                      value={settings.window_start}
+                      onChange={(e) => handleChange('window_start', e.target.value)}
+                    />
+                    <span>to</span>
+                    <input
+                      type="time"
+                      value={settings.window_end}
+                      onChange={(e) => handleChange('window_end', e.target.value)}
+                    />
+                  </div>
+                )}
+
+                {settings.schedule_mode === 'cron' && (
+                  <input
+                    type="text"
+                    className="schedule-cron"
+                    placeholder="5 0 * * *"
+                    value={settings.generation_cron}
+                    onChange={(e) => handleChange('generation_cron', e.target.value)}
+                  />
+                )}
+
+                {settings.schedule_mode !== 'interval' && (
+                  <div className="schedule-timezone">
+                    <label>Timezone</label>
+                    <input
+                      type="text"
+                      placeholder="Europe/Berlin"
+                      value={settings.timezone}
+                      onChange={(e) => handleChange('timezone', e.target.value)}
+                    />
+                  </div>
+                )}
+
+                <p className="hint-text">
+                  {settings.schedule_mode === 'window'
+                    ? 'Scheduled runs only start inside this window, e.g. at night so they do not compete with daytime traffic. The window may end after midnight.'
+                    : settings.schedule_mode === 'cron'
+                    ? 'Runs start at the times of this five-field cron expression instead of the interval, e.g. "5 0 * * *" for 00:05 every day.'
+                    : 'Scheduled runs start whenever the interval is up.'}
+                </p>
+              </div>
+            )}
           </section>
 
           <section className="form-section">
@@ -224,6 +309,7 @@ function GenerationSettings() {
                     <th>Started</th>
                     <th>Completed</th>
                     <th>Type</th>
+                    <th></th>
                   </tr>
                 </thead>
                 <tbody>
@@ -231,8 +317,13 @@ function GenerationSettings() {
                     <tr key={job.id}>
                       <td>
                         <span className={`status-badge ${getStatusBadgeClass(job.status)}`}>
-                          {job.status}
+                          {job.status.replace('_', ' ')}
                         </span>
+                        {job.status === 'pending' && job.retry_count > 0 && (
+                          <div className="job-retry" title={job.error_message || ''}>
+                            Retry {job.retry_count}/{job.max_retries}
+                          </div>
+                        )}
                       </td>
                       <td>{job.theme_id ? 'Single Theme' : 'All Themes'}</td>
                       <td>{formatDate(job.scheduled_at)}</td>
@@ -243,6 +334,17 @@ function GenerationSettings() {
                           {job.is_manual ? 'Manual' : 'Auto'}
                         </span>
                       </td>
+                      <td>
+                        {job.status === 'dead_letter' && (
+                          <button
+                            className="job-retry-btn"
+                            onClick={() => handleRetryJob(job.id)}
+                            title={job.error_message || ''}
+                          >
+                            Retry
+                          </button>
+                        )}
+                      </td>
                     </tr>
                   ))}
                 </tbody>
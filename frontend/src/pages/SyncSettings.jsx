@@ -92,12 +92,12 @@ function SyncSettings() {
     }
 
     try {
-      await apiClient.updatePlatformSyncPreferences(platformId, preferences);
+      const updated = await apiClient.updatePlatformSyncPreferences(platformId, preferences);
 
       // Update local state
       setPlatforms(platforms.map(p =>
         p.id === platformId
-          ? { ...p, ...preferences }
+          ? { ...p, ...updated }
           : p
       ));
 
@@ -110,6 +110,44 @@ function SyncSettings() {
     }
   };
 
+  const handleDetailedSyncChange = (platform, enabled) => {
+    const warning = enabled
+      ? 'Detailed sync stores every commit (about 250 bytes each) and makes extra API requests during syncs. Turn it on?'
+      : 'Turning detailed sync off deletes all commits stored for this account. Continue?';
+    if (!confirm(warning)) {
+      return;
+    }
+    handlePlatformSyncPreferenceChange(platform.id, {
+      sync_profile: platform.sync_profile,
+      sync_contributions: platform.sync_contributions,
+      detailed_sync: enabled,
+    });
+  };
+
+  const handleCommitRetentionChange = (platform, value) => {
+    const days = value === '' ? 0 : parseInt(value);
+    if ((platform.commit_retention_days || 0) === days) {
+      return;
+    }
+    handlePlatformSyncPreferenceChange(platform.id, {
+      sync_profile: platform.sync_profile,
+      sync_contributions: platform.sync_contributions,
+      commit_retention_days: days,
+    });
+  };
+
+  const handleEarliestSyncYearChange = (platform, value) => {
+    const year = parseInt(value);
+    if (!year || year === platform.earliest_sync_year) {
+      return;
+    }
+    handlePlatformSyncPreferenceChange(platform.id, {
+      sync_profile: platform.sync_profile,
+      sync_contributions: platform.sync_contributions,
+      earliest_sync_year: year,
+    });
+  };
+
   const formatDateTime = (dateStr) => {
     if (!dateStr) return 'Never';
     const date = new Date(dateStr);
@@ -227,6 +265,43 @@ function SyncSettings() {
                   <span>Heatmap + Activities</span>
                   <span className="hint-inline">(contributions and timeline events)</span>
                 </label>
+
+                <label className="checkbox-label">
+                  <input
+                    type="checkbox"
+                    checked={platform.detailed_sync}
+                    disabled={!platform.sync_contributions}
+                    onChange={(e) => handleDetailedSyncChange(platform, e.target.checked)}
+                  />
+                  <span>Detailed commits</span>
+                  <span className="hint-inline">(every commit, for hour-of-day and lines-changed stats; uses more storage)</span>
+                </label>
+
+                {platform.detailed_sync && (
+                  <label className="commit-retention">
+                    <span>Keep commits for</span>
+                    <input
+                      type="number"
+                      min="30"
+                      max="36500"
+                      placeholder="forever"
+                      defaultValue={platform.commit_retention_days || ''}
+                      onBlur={(e) => handleCommitRetentionChange(platform, e.target.value)}
+                    />
+                    <span>days</span>
+                  </label>
+                )}
+
+                <label className="commit-retention">
+                  <span>Sync all years from</span>
+                  <input
+                    type="number"
+                    min="2008"
+                    max={new Date().getFullYear()}
+                    defaultValue={platform.earliest_sync_year}
+                    onBlur={(e) => handleEarliestSyncYearChange(platform, e.target.value)}
+                  />
+                </label>
               </div>
             </div>
           ))}
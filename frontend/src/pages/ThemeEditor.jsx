@@ -6,6 +6,14 @@ import HeatmapPreview from '../components/HeatmapPreview';
 import './ThemeEditor.css';
 
 const FORMATS = ['svg', 'png', 'jpeg', 'webp'];
+const LAYOUTS = [
+  { value: 'grid', label: 'Grid (one cell per day)' },
+  { value: 'skyline', label: 'Skyline (one bar per week)' },
+  { value: 'month_grid', label: 'Month grid (a calendar per month)' },
+  { value: 'stats_card', label: 'Stats card' },
+  { value: 'badge', label: 'Badge' },
+  { value: 'mini', label: 'Mini (last 30 or 90 days)' },
+];
 
 function ThemeEditor() {
   const navigate = useNavigate();
@@ -64,6 +72,11 @@ function ThemeEditor() {
     font_family: 'Arial',
     font_size: 10,
     legend_position: 'bottom',
+    layout: 'grid',
+    custom_template: '',
+    mini_days: 0,
+    show_achievements: false,
+    show_code_frequency: false,
 
     // Formats (multiple selection)
     output_formats: ['png'],
@@ -137,6 +150,11 @@ function ThemeEditor() {
         font_family: theme.font_family,
         font_size: theme.font_size,
         legend_position: theme.legend_position,
+        layout: theme.layout || 'grid',
+        custom_template: theme.custom_template || '',
+        mini_days: theme.mini_days || 0,
+        show_achievements: theme.show_achievements || false,
+        show_code_frequency: theme.show_code_frequency || false,
         output_formats: theme.output_formats || ['png'],
       });
 
@@ -276,6 +294,50 @@ function ThemeEditor() {
               </label>
             </div>
           </div>
+
+          <div className="form-group">
+            <label>Layout</label>
+            <select
+              value={formData.layout}
+              onChange={(e) => handleChange('layout', e.target.value)}
+            >
+              {LAYOUTS.map(layout => (
+                <option key={layout.value} value={layout.value}>
+                  {layout.label}
+                </option>
+              ))}
+            </select>
+          </div>
+
+          <div className="form-group">
+            <label>Mini heatmap</label>
+            <select
+              value={formData.mini_days}
+              onChange={(e) => handleChange('mini_days', parseInt(e.target.value, 10))}
+            >
+              <option value={0}>None</option>
+              <option value={30}>Last 30 days</option>
+              <option value={90}>Last 90 days</option>
+            </select>
+            <p className="hint-text">
+              Also generates a tiny {'{slug}'}-mini file for signatures and email footers, and sets the days of the Mini layout
+            </p>
+          </div>
+
+          <div className="form-group">
+            <label>Advanced: custom SVG template</label>
+            <textarea
+              value={formData.custom_template}
+              onChange={(e) => handleChange('custom_template', e.target.value)}
+              placeholder={'<svg xmlns="http://www.w3.org/2000/svg" ...>{% for week in weeks %}...{% endfor %}</svg>'}
+              rows={8}
+              spellCheck={false}
+            />
+            <p className="hint-text">
+              Optional minijinja template drawn instead of the layout. It can use weeks, totals,
+              palette, username, months, theme, start_date and end_date. Leave empty to use the layout.
+            </p>
+          </div>
         </section>
 
         {/* Color Configuration */}
@@ -651,6 +713,28 @@ function ThemeEditor() {
               />
               <span>Show Watermark</span>
             </label>
+
+            {formData.layout === 'stats_card' && (
+              <label className="checkbox-option">
+                <input
+                  type="checkbox"
+                  checked={formData.show_achievements}
+                  onChange={(e) => handleChange('show_achievements', e.target.checked)}
+                />
+                <span>Show Achievements</span>
+              </label>
+            )}
+
+            {formData.layout === 'grid' && (
+              <label className="checkbox-option">
+                <input
+                  type="checkbox"
+                  checked={formData.show_code_frequency}
+                  onChange={(e) => handleChange('show_code_frequency', e.target.checked)}
+                />
+                <span>Show Lines Changed</span>
+              </label>
+            )}
           </div>
 
           <div className="form-row">